@@ -1,36 +1,366 @@
 use anyhow::{anyhow, Result};
-use opcua::client::{ClientBuilder, IdentityToken, Session, Password};
-use opcua::types::{EndpointDescription, MessageSecurityMode, UserTokenPolicy, StatusCode};
-use opcua::crypto::SecurityPolicy;
+use colored::*;
+use futures::TryStreamExt;
+use opcua::client::{Client, ClientBuilder, IdentityToken, Password, Session, SessionEventLoop, SessionPollResult, SubscriptionActivity};
+use opcua::types::{ApplicationDescription, ByteString, EndpointDescription, MessageSecurityMode, NodeId, ReadValueId, UserTokenPolicy, UserTokenType, StatusCode, TimestampsToReturn, VariableId};
+use opcua::core::comms::url::{hostname_from_url, url_with_replaced_hostname};
+use opcua::crypto::{SecurityPolicy, X509};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
 use std::path::Path;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OnceCell, OwnedSemaphorePermit, Semaphore};
 use tokio::task::JoinHandle;
-use tracing::{debug, info};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
 
+use crate::commands::discover::endpoint_matrix;
+use crate::session::{ServiceSession, ServiceStats};
 use crate::types::{AuthConfig, Cli};
+use crate::utils::client_cert::ClientKeypair;
+use crate::utils::namespace::{read_namespace_array, NamespaceMap, NodeSpec};
+use crate::utils::outcome::OperationResult;
+use crate::utils::time::format_duration;
+use crate::utils::endpoint::normalize_endpoint_url;
+use crate::utils::proxy::{split_endpoint_url, start_forwarder, ProxyUrl};
+use crate::utils::recording;
+use crate::utils::security::ConnectionInfo;
+use crate::utils::snapshot::Snapshot;
+
+/// Attempts made to reach the discovery endpoint before giving up
+const DISCOVERY_ATTEMPTS: u32 = 3;
+
+/// IssuedTokenType of user token policies accepting JSON Web Tokens
+const JWT_TOKEN_TYPE: &str = "http://opcfoundation.org/UA/UserToken#JWT";
+
+/// Retries of a session the server refused with BadTooManySessions
+const TOO_MANY_SESSIONS_RETRIES: u32 = 3;
+
+/// Open sessions per server address, each semaphore holding `--max-sessions` permits
+static SESSION_SLOTS: LazyLock<Mutex<HashMap<String, Arc<Semaphore>>>> = LazyLock::new(Mutex::default);
+
+fn session_slots(endpoint: &str, max_sessions: usize) -> Arc<Semaphore> {
+    SESSION_SLOTS.lock().unwrap()
+        .entry(server_address(endpoint))
+        .or_insert_with(|| Arc::new(Semaphore::new(max_sessions)))
+        .clone()
+}
+
+/// `host:port` of an endpoint URL, the sessions to one server count against the same limit
+fn server_address(endpoint: &str) -> String {
+    match split_endpoint_url(endpoint) {
+        Ok((host, port, _)) => format!("{}:{}", host.to_lowercase(), port),
+        Err(_) => endpoint.to_string(),
+    }
+}
+
+/// Fail early when an operation needs more sessions to one server at the same time than
+/// `--max-sessions` allows, waiting for one of them to close would never end
+pub fn check_session_budget<'a>(max_sessions: usize, endpoints: impl IntoIterator<Item = &'a str>) -> Result<()> {
+    let mut sessions: HashMap<String, usize> = HashMap::new();
+    for endpoint in endpoints {
+        *sessions.entry(server_address(endpoint)).or_default() += 1;
+    }
+    match sessions.into_iter().find(|(_, count)| *count > max_sessions) {
+        Some((address, count)) => Err(anyhow!(
+            "This needs {} sessions to {} at the same time, more than --max-sessions {} allows",
+            count, address, max_sessions
+        )),
+        None => Ok(()),
+    }
+}
+
+fn is_too_many_sessions(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<ConnectError>().is_some_and(|error| error.status == StatusCode::BadTooManySessions)
+}
+
+/// Outcome of the latest keep-alive read and when it finished
+#[derive(Clone, Debug)]
+pub struct KeepAliveStatus {
+    pub at: Instant,
+    pub result: OperationResult,
+}
+
+/// Latest keep-alive result of a client, shared with the task making the reads
+#[derive(Clone, Default)]
+pub struct KeepAlive(Arc<Mutex<Option<KeepAliveStatus>>>);
+
+impl KeepAlive {
+    /// `None` before the first read of a session and without `--keep-alive-read`
+    pub fn last(&self) -> Option<KeepAliveStatus> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn set(&self, status: Option<KeepAliveStatus>) {
+        *self.0.lock().unwrap() = status;
+    }
+}
+
+/// When the server last answered a publish request of the client's session, with notifications
+/// or a keep-alive, noted by the task running the session's event loop
+#[derive(Clone, Default)]
+pub struct PublishClock(Arc<Mutex<Option<Instant>>>);
+
+impl PublishClock {
+    /// `None` before the first publish response of a session
+    pub fn last(&self) -> Option<Instant> {
+        *self.0.lock().unwrap()
+    }
+
+    fn set(&self, at: Option<Instant>) {
+        *self.0.lock().unwrap() = at;
+    }
+}
+
+/// Run the event loop of a session like `SessionEventLoop::spawn` does, noting every publish
+/// response on `publishes`
+fn spawn_event_loop(event_loop: SessionEventLoop, publishes: PublishClock) -> JoinHandle<StatusCode> {
+    tokio::spawn(async move {
+        let activity = event_loop.enter();
+        tokio::pin!(activity);
+        loop {
+            match activity.try_next().await {
+                Ok(Some(SessionPollResult::Subscription(SubscriptionActivity::Publish))) => publishes.set(Some(Instant::now())),
+                Ok(Some(SessionPollResult::Subscription(SubscriptionActivity::PublishFailed(status)))) => {
+                    debug!("Publish request failed: {}", status);
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => break StatusCode::Good,
+                Err(status) => break status,
+            }
+        }
+    })
+}
+
+/// Read the server state every `interval` so servers that close idle sessions keep this one.
+/// Runs next to the commands, so it only logs at debug level.
+async fn keep_alive_reads(session: ServiceSession, interval: Duration, keep_alive: KeepAlive) {
+    let node_id: NodeId = VariableId::Server_ServerStatus_State.into();
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let result = match session.read(&[ReadValueId::from(&node_id)], TimestampsToReturn::Neither, 0.0).await {
+            Ok(values) => values.first().map_or(OperationResult::of_status(StatusCode::BadNoData), OperationResult::of_data_value),
+            Err(status) => OperationResult::of_service_error(status),
+        };
+        if result.is_good() {
+            debug!("Keep-alive read of the server state succeeded");
+        } else {
+            debug!("Keep-alive read of the server state failed: {}", result.describe());
+        }
+        keep_alive.set(Some(KeepAliveStatus { at: Instant::now(), result }));
+    }
+}
 
 pub struct OpcUaClient {
-    session: Option<Arc<Session>>,
+    session: Option<ServiceSession>,
     event_loop_handle: Option<JoinHandle<StatusCode>>,
     endpoint: String,
+    /// Server endpoint the session was opened on, as advertised by discovery
+    connected_endpoint: Option<EndpointDescription>,
+    /// Endpoints the server advertised when connecting, what the session's security is compared with
+    offered_endpoints: Vec<EndpointDescription>,
+    /// Security of the open session, unknown when replaying
+    connection_info: Option<ConnectionInfo>,
+    /// Print a warning header when the session is less secure than the server allows
+    warn_security: bool,
     auth_config: AuthConfig,
+    locales: Vec<String>,
+    auto_namespace: bool,
+    ns_map: NamespaceMap,
+    /// Namespace array of the connected server, read on first use
+    namespaces: OnceCell<Vec<String>>,
+    /// Service call counters, shared by clients created from this one
+    stats: Arc<ServiceStats>,
+    proxy: Option<ProxyUrl>,
+    /// Local URL forwarded through the proxy and the task serving it
+    tunnel: Option<(String, JoinHandle<()>)>,
     verbose: bool,
+    /// maxAge of value reads in milliseconds
+    max_age: f64,
+    max_sessions: usize,
+    /// Held while connected, counting the session against `--max-sessions` of its server
+    session_slot: Option<OwnedSemaphorePermit>,
+    /// Period of the keep-alive reads, `None` without `--keep-alive-read`
+    keep_alive_interval: Option<Duration>,
+    keep_alive_task: Option<JoinHandle<()>>,
+    keep_alive: KeepAlive,
+    publishes: PublishClock,
 }
 
 impl OpcUaClient {
     pub async fn new(cli: &Cli) -> Result<Self> {
+        Self::for_endpoint(cli, &cli.endpoint)
+    }
+
+    /// Create a client for another endpoint, sharing the CLI's authentication settings. The
+    /// endpoint URL is checked and normalized first, see [`normalize_endpoint_url`].
+    pub fn for_endpoint(cli: &Cli, endpoint: &str) -> Result<Self> {
+        let endpoint = normalize_endpoint_url(endpoint)?;
+        for note in &endpoint.notes {
+            warn!("{}", note);
+        }
         Ok(Self {
             session: None,
             event_loop_handle: None,
-            endpoint: cli.endpoint.clone(),
+            endpoint: endpoint.url,
+            connected_endpoint: None,
+            offered_endpoints: Vec::new(),
+            connection_info: None,
+            warn_security: !cli.no_warnings,
             auth_config: AuthConfig::from(cli),
-            verbose: cli.verbose,
+            locales: cli.locales.clone(),
+            auto_namespace: cli.auto_namespace,
+            ns_map: cli.ns_map.clone().unwrap_or_default(),
+            namespaces: OnceCell::new(),
+            stats: Arc::default(),
+            proxy: cli.proxy.clone(),
+            tunnel: None,
+            verbose: cli.verbose > 0,
+            max_age: cli.max_age,
+            max_sessions: cli.max_sessions as usize,
+            session_slot: None,
+            keep_alive_interval: cli.keep_alive_read,
+            keep_alive_task: None,
+            keep_alive: KeepAlive::default(),
+            publishes: PublishClock::default(),
         })
     }
 
+    /// Create an unconnected copy of this client that requests different session locales
+    pub fn with_locales(&self, locales: Vec<String>) -> Self {
+        Self {
+            session: None,
+            event_loop_handle: None,
+            endpoint: self.endpoint.clone(),
+            connected_endpoint: None,
+            offered_endpoints: Vec::new(),
+            connection_info: None,
+            warn_security: self.warn_security,
+            auth_config: self.auth_config.clone(),
+            locales,
+            auto_namespace: self.auto_namespace,
+            ns_map: self.ns_map.clone(),
+            namespaces: OnceCell::new(),
+            stats: self.stats.clone(),
+            proxy: self.proxy.clone(),
+            tunnel: None,
+            verbose: self.verbose,
+            max_age: self.max_age,
+            max_sessions: self.max_sessions,
+            session_slot: None,
+            keep_alive_interval: self.keep_alive_interval,
+            keep_alive_task: None,
+            keep_alive: KeepAlive::default(),
+            publishes: PublishClock::default(),
+        }
+    }
+
+    /// Count this client's service calls into an existing set of statistics
+    pub fn with_stats(mut self, stats: Arc<ServiceStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Answer reads and browses from a snapshot instead of connecting
+    pub fn open_snapshot(&mut self, snapshot: Arc<Snapshot>) {
+        info!("Reading snapshot {} instead of connecting to {}", snapshot.path, snapshot.endpoint);
+        self.endpoint = snapshot.endpoint.clone();
+        self.session = Some(ServiceSession::from_snapshot(snapshot, self.stats.clone()));
+    }
+
     pub async fn connect(&mut self) -> Result<()> {
+        if let Some(path) = recording::replay_path() {
+            self.endpoint = recording::replayed_endpoint(&self.endpoint)?;
+            info!("Replaying {} instead of connecting to {}", path, self.endpoint);
+            self.session = Some(ServiceSession::replayed(&self.endpoint, self.stats.clone(), self.max_age));
+            return Ok(());
+        }
+        
         info!("Connecting to OPC-UA server: {}", self.endpoint);
         
+        if let Some(proxy) = self.proxy.clone() {
+            self.open_proxy_tunnel(proxy).await?;
+        }
+        
+        self.session_slot = Some(self.acquire_session_slot().await?);
+        let mut retries = 0;
+        loop {
+            match self.open_session().await {
+                // Sessions of clients that just disconnected may take a moment to be closed on the server
+                Err(e) if retries < TOO_MANY_SESSIONS_RETRIES && is_too_many_sessions(&e) => {
+                    retries += 1;
+                    let delay = Duration::from_secs(1 << retries);
+                    warn!("{} has no session left, retrying in {} s ({}/{})",
+                          self.endpoint, delay.as_secs(), retries, TOO_MANY_SESSIONS_RETRIES);
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => {
+                    self.session_slot = None;
+                    return Err(e);
+                }
+                Ok(()) => {
+                    if self.connection_info.is_none()
+                        && let Some(endpoint) = &self.connected_endpoint
+                    {
+                        let policy = SecurityPolicy::from_uri(endpoint.security_policy_uri.as_ref());
+                        self.connection_info = Some(ConnectionInfo::new(policy, endpoint.security_mode, self.session_token_type(), &self.offered_endpoints));
+                    }
+                    self.start_keep_alive();
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn start_keep_alive(&mut self) {
+        if let (Some(interval), Some(session)) = (self.keep_alive_interval, &self.session) {
+            debug!("Reading the server state every {} to keep the session alive", format_duration(interval));
+            self.keep_alive_task = Some(tokio::spawn(keep_alive_reads(session.clone(), interval, self.keep_alive.clone())));
+        }
+    }
+
+    /// Result of the latest keep-alive read, e.g. to show the health of the connection
+    pub fn keep_alive(&self) -> &KeepAlive {
+        &self.keep_alive
+    }
+
+    /// When the server last answered a publish request, to notice subscriptions gone silent
+    pub fn publishes(&self) -> &PublishClock {
+        &self.publishes
+    }
+
+    /// Wait until fewer than `--max-sessions` sessions to the server are open
+    async fn acquire_session_slot(&self) -> Result<OwnedSemaphorePermit> {
+        let slots = session_slots(&self.endpoint, self.max_sessions);
+        if let Ok(permit) = slots.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+        warn!("{} session(s) to {} are open (--max-sessions), waiting for one to close",
+              self.max_sessions, server_address(&self.endpoint));
+        slots.acquire_owned().await.map_err(|e| anyhow!("Session limit closed: {}", e))
+    }
+
+    pub fn max_sessions(&self) -> usize {
+        self.max_sessions
+    }
+
+    async fn open_session(&mut self) -> Result<()> {
+        if self.auth_config.cert_path.is_none()
+            && let Some(pki_dir) = self.auth_config.auto_cert_dir.clone()
+        {
+            self.use_auto_certificate(&pki_dir).await?;
+        }
+
+        // Issued tokens need an endpoint that advertises a matching user token policy
+        if let Some(token) = self.issued_token()? {
+            return self.connect_with_issued_token(token).await;
+        }
+        
         // Check if certificate authentication is required
         if let (Some(cert_path), Some(key_path)) = (self.auth_config.cert_path.clone(), self.auth_config.key_path.clone()) {
             return self.connect_with_certificate(&cert_path, &key_path).await;
@@ -42,43 +372,71 @@ impl OpcUaClient {
             .application_uri("urn:opcua-walker")
             .create_sample_keypair(false)
             .trust_server_certs(true)
+            .preferred_locales(self.locales.clone())
+            .verify_server_certs(self.library_verifies_certs())
             .session_retry_limit(3)
             .client()
             .map_err(|e| anyhow!("Failed to create client: {:?}", e))?;
 
-        // Create endpoint description for anonymous/username auth
-        let endpoint: EndpointDescription = (
-            self.endpoint.as_str(),
-            "None",
-            MessageSecurityMode::None,
-            UserTokenPolicy::anonymous()
-        ).into();
-
         // Create identity token
         let identity_token = self.create_identity_token()?;
 
+        // Choose from what the server offers instead of assuming a None/Anonymous endpoint. The
+        // session client retries with back-off itself, discovery goes through one that does not.
+        let endpoints = self.discover_endpoints(&self.discovery_client()?).await
+            .map_err(|e| self.discovery_error(e))?;
+        self.offered_endpoints = endpoints.clone();
+        let token_type = identity_token_type(&identity_token);
+        let endpoint = if self.auth_config.pick_endpoint {
+            pick_endpoint(&endpoints, |ep| self.endpoint_problem(ep, token_type, false))?
+        } else {
+            select_session_endpoint(&self.matching_endpoints(&endpoints, token_type, false)?, token_type).ok_or_else(|| {
+                let login = if token_type == UserTokenType::UserName { "username" } else { "anonymous" };
+                self.no_endpoint_error(
+                    format!("No endpoint accepts {} logins without a client certificate", login),
+                    &endpoints,
+                    token_type,
+                    false,
+                )
+            })?
+        };
+        debug!("Selected endpoint {} / {} at {}",
+               SecurityPolicy::from_uri(endpoint.security_policy_uri.as_ref()),
+               endpoint.security_mode,
+               endpoint.endpoint_url);
+
         // Connect to server
         let (session, event_loop) = client
-            .connect_to_matching_endpoint(endpoint, identity_token)
-            .await
+            .connect_to_endpoint_directly(self.reachable_endpoint(&endpoint)?, identity_token)
             .map_err(|e| anyhow!("Failed to connect to OPC-UA server: {}", e))?;
 
         // Spawn the event loop
-        let handle = event_loop.spawn();
+        let mut handle = spawn_event_loop(event_loop, self.publishes.clone());
 
         // Wait for connection
-        session.wait_for_connection().await;
+        wait_for_session(&session, &mut handle).await
+            .map_err(|status| self.session_error("Failed to connect to OPC-UA server", status))?;
 
         info!("✅ Successfully connected to OPC-UA server");
         
-        self.session = Some(session);
+        self.session = Some(ServiceSession::new(session, &self.endpoint, self.stats.clone(), self.max_age).with_identity(self.user_identity()));
         self.event_loop_handle = Some(handle);
+        self.connected_endpoint = Some(endpoint);
         
         Ok(())
     }
 
     pub async fn disconnect(&mut self) -> Result<()> {
-        if let Some(session) = self.session.take() {
+        if let Some(task) = self.keep_alive_task.take() {
+            task.abort();
+        }
+        self.keep_alive.set(None);
+        self.publishes.set(None);
+        self.connection_info = None;
+
+        if let Some(session) = self.session.take()
+            && session.is_connected()
+        {
             debug!("Disconnecting from OPC-UA server");
             let _ = session.disconnect().await;
             info!("✅ Disconnected from OPC-UA server");
@@ -88,10 +446,15 @@ impl OpcUaClient {
             handle.abort();
         }
         
+        if let Some((_, forwarder)) = self.tunnel.take() {
+            forwarder.abort();
+        }
+        
+        self.session_slot = None;
         Ok(())
     }
 
-    pub fn session(&self) -> Result<&Arc<Session>> {
+    pub fn session(&self) -> Result<&ServiceSession> {
         self.session.as_ref()
             .ok_or_else(|| anyhow!("Not connected to OPC-UA server"))
     }
@@ -100,10 +463,128 @@ impl OpcUaClient {
         self.verbose
     }
 
+    pub fn auto_namespace(&self) -> bool {
+        self.auto_namespace
+    }
+
     pub fn endpoint(&self) -> &str {
         &self.endpoint
     }
 
+    /// The user identity sessions are opened with, following the order of `open_session`
+    pub fn user_identity(&self) -> String {
+        if self.auth_config.token.is_some() || self.auth_config.token_env.is_some() {
+            "issued token".to_string()
+        } else if self.auth_config.cert_path.is_some() && self.auth_config.key_path.is_some() {
+            // The certificate secures the channel, the session itself stays anonymous
+            "anonymous (certificate channel)".to_string()
+        } else if let Some(username) = &self.auth_config.username {
+            format!("user {}", username)
+        } else {
+            "anonymous".to_string()
+        }
+    }
+
+    /// The user token type sessions are opened with, following the order of `open_session`
+    fn session_token_type(&self) -> UserTokenType {
+        if self.auth_config.token.is_some() || self.auth_config.token_env.is_some() {
+            UserTokenType::IssuedToken
+        } else if self.auth_config.cert_path.is_some() && self.auth_config.key_path.is_some() {
+            UserTokenType::Anonymous
+        } else if self.auth_config.username.is_some() {
+            UserTokenType::UserName
+        } else {
+            UserTokenType::Anonymous
+        }
+    }
+
+    /// The server endpoint the session was opened on, unknown after the certificate fallback
+    pub fn connected_endpoint(&self) -> Option<&EndpointDescription> {
+        self.connected_endpoint.as_ref()
+    }
+
+    /// Security of the open session, for the `security` block of JSON output
+    pub fn connection_info(&self) -> Option<&ConnectionInfo> {
+        self.connection_info.as_ref()
+    }
+
+    /// Print the one-line warning when the session is less secure than the server allows, unless
+    /// `--no-warnings` is given. It goes to stderr, so JSON on stdout stays parseable.
+    pub fn print_security_warning(&self) {
+        if let Some(warning) = self.connection_info.as_ref().and_then(ConnectionInfo::warning)
+            && self.warn_security
+        {
+            eprintln!("{}", warning);
+        }
+    }
+
+    pub fn ns_map(&self) -> &NamespaceMap {
+        &self.ns_map
+    }
+
+    /// All endpoints the server advertises, queried with a separate discovery connection
+    pub async fn server_endpoints(&self) -> Result<Vec<EndpointDescription>> {
+        self.discovery_client()?
+            .get_server_endpoints_from_url(self.connection_url()).await
+            .map_err(|e| self.discovery_error(e))
+    }
+
+    /// The servers a discovery server knows, asked with FindServers on a separate connection
+    pub async fn find_servers(&self) -> Result<Vec<ApplicationDescription>> {
+        self.discovery_client()?
+            .find_servers(self.connection_url(), None, None).await
+            .map_err(|e| self.discovery_error(e))
+    }
+
+    fn discovery_client(&self) -> Result<Client> {
+        if let Some(path) = recording::replay_path() {
+            return Err(anyhow!("Discovery of {} cannot be replayed, recording {} only holds the service calls of sessions", self.endpoint, path));
+        }
+        ClientBuilder::new()
+            .application_name("OPC-UA Walker")
+            .application_uri("urn:opcua-walker")
+            .create_sample_keypair(false)
+            .trust_server_certs(true)
+            .verify_server_certs(self.library_verifies_certs())
+            // A server that rejects the request would otherwise be retried with growing back-off
+            .session_retry_limit(0)
+            .client()
+            .map_err(|e| anyhow!("Failed to create client: {:?}", e))
+    }
+
+    /// The server's namespace array, read on first use and cached for the lifetime of the session
+    pub async fn namespaces(&self) -> Result<&[String]> {
+        let session = self.session()?;
+        let namespaces = self.namespaces
+            .get_or_try_init(|| read_namespace_array(session))
+            .await?;
+        Ok(namespaces)
+    }
+
+    /// Index of a namespace URI on the server, `None` when it is not registered
+    pub async fn ns_index_for_uri(&self, uri: &str) -> Result<Option<u16>> {
+        Ok(self.namespaces().await?
+            .iter()
+            .position(|ns| ns == uri)
+            .and_then(|index| u16::try_from(index).ok()))
+    }
+
+    /// URI of a namespace index on the server, `None` beyond the end of the namespace array
+    pub async fn uri_for_index(&self, index: u16) -> Result<Option<&str>> {
+        Ok(self.namespaces().await?.get(index as usize).map(String::as_str))
+    }
+
+    /// Resolve a command line node ID against this server, applying the `--ns-map` translations
+    pub async fn resolve_node(&self, spec: &NodeSpec) -> Result<NodeId> {
+        let mut node_id = spec.node_id.clone();
+        node_id.namespace = match &spec.namespace_uri {
+            Some(uri) => self.ns_index_for_uri(self.ns_map.map_uri(uri)).await?
+                .ok_or_else(|| anyhow!("Namespace {} is not registered on the server", uri))?,
+            None => self.ns_map.map_index(node_id.namespace),
+        };
+        Ok(node_id)
+    }
+
     fn configure_certificate_auth(&self, cert_path: &str, key_path: &str) -> Result<()> {
         debug!("Validating certificate files");
         
@@ -119,6 +600,27 @@ impl OpcUaClient {
         Ok(())
     }
 
+    /// With `--auto-cert`, use the tool's own keypair when no endpoint can be used without a client
+    /// certificate: the server offers no None endpoint or `--security-*` rules those out. The
+    /// connection then goes the way of `--cert` and `--key`.
+    async fn use_auto_certificate(&mut self, pki_dir: &Path) -> Result<()> {
+        let endpoints = self.discover_endpoints(&self.discovery_client()?).await
+            .map_err(|e| self.discovery_error(e))?;
+        let certificate_free = endpoints.iter().any(|ep| {
+            ep.security_mode == MessageSecurityMode::None && self.security_mismatch(ep).is_none()
+        });
+        if certificate_free {
+            debug!("{} can be used without a client certificate, --auto-cert is not needed", self.endpoint);
+            return Ok(());
+        }
+
+        let keypair = ClientKeypair::load_or_create(pki_dir)?;
+        keypair.print_notice();
+        self.auth_config.cert_path = Some(keypair.cert_path.display().to_string());
+        self.auth_config.key_path = Some(keypair.key_path.display().to_string());
+        Ok(())
+    }
+
     async fn connect_with_certificate(&mut self, cert_path: &str, key_path: &str) -> Result<()> {
         self.configure_certificate_auth(cert_path, key_path)?;
         
@@ -137,22 +639,27 @@ impl OpcUaClient {
             .private_key_path(key_path)
             .create_sample_keypair(false)
             .trust_server_certs(true)
+            .preferred_locales(self.locales.clone())
+            .verify_server_certs(self.library_verifies_certs())
             .session_retry_limit(0) // Disable retries to prevent BadTooManyOperations
             .client()
             .map_err(|e| anyhow!("Failed to create certificate client: {:?}", e))?;
 
         // Try endpoint discovery first, but fallback if it fails
         debug!("Discovering server endpoints...");
-        let endpoints_result = client.get_server_endpoints().await;
+        let endpoints_result = self.discover_endpoints(&client).await;
         
         match endpoints_result {
             Ok(endpoints) => {
+                info!("🔍 Discovered {} endpoint(s) at {}", endpoints.len(), self.endpoint);
+                self.offered_endpoints = endpoints.clone();
+                
                 // Endpoint discovery succeeded, use the secure endpoint approach
                 self.connect_with_discovered_endpoints(client, endpoints).await
             }
             Err(e) => {
                 // Endpoint discovery failed, try fallback approach like the C implementation
-                debug!("Endpoint discovery failed: {:?}", e);
+                warn!("Endpoint discovery failed: {}", describe_discovery_error(e));
                 if self.verbose {
                     println!("⚠️  Endpoint discovery failed, trying fallback approach...");
                     println!("   Similar to C code: UA_ClientConfig_setDefaultEncryption + AcceptAll");
@@ -162,18 +669,39 @@ impl OpcUaClient {
         }
     }
 
-    async fn connect_with_discovered_endpoints(&mut self, mut client: opcua::client::Client, endpoints: Vec<EndpointDescription>) -> Result<()> {
-        if self.verbose {
-            println!("🔍 Discovered {} endpoint(s)", endpoints.len());
-            for (i, ep) in endpoints.iter().enumerate() {
-                println!("  {}. {} / {} / {}", 
-                    i + 1,
-                    SecurityPolicy::from_uri(ep.security_policy_uri.as_ref()),
-                    ep.security_mode,
-                    ep.endpoint_url
-                );
+    /// Query the server's endpoints, retrying briefly while the server refuses connections. This is
+    /// the only retry: `client` must be built with `session_retry_limit(0)`.
+    async fn discover_endpoints(&self, client: &opcua::client::Client) -> Result<Vec<EndpointDescription>, StatusCode> {
+        let mut attempt = 1;
+        loop {
+            match client.get_server_endpoints_from_url(self.connection_url()).await {
+                Ok(endpoints) => return Ok(endpoints),
+                Err(status) if attempt < DISCOVERY_ATTEMPTS && is_transient_discovery_error(status) => {
+                    let delay = tokio::time::Duration::from_secs(attempt as u64);
+                    debug!("Discovery attempt {} failed ({}), retrying in {:?}", attempt, status, delay);
+                    if self.verbose {
+                        println!("⚠️  {} (attempt {}/{}), retrying...",
+                                 describe_discovery_error(status), attempt, DISCOVERY_ATTEMPTS);
+                    }
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(status) => return Err(status),
             }
         }
+    }
+
+    async fn connect_with_discovered_endpoints(&mut self, client: opcua::client::Client, endpoints: Vec<EndpointDescription>) -> Result<()> {
+        // The certificate identifies the client, the session itself is anonymous
+        let problem = |ep: &EndpointDescription| self.endpoint_problem(ep, UserTokenType::Anonymous, true);
+        if self.auth_config.pick_endpoint {
+            let endpoint = pick_endpoint(&endpoints, problem)?;
+            return self.connect_with_certificate_endpoint(client, endpoint).await;
+        }
+        if self.verbose {
+            print_endpoints(&endpoints, problem);
+        }
+        let matching = self.matching_endpoints(&endpoints, UserTokenType::Anonymous, true)?;
 
         // Find a suitable endpoint for certificate authentication
         // Prefer stronger security policies first
@@ -194,7 +722,7 @@ impl OpcUaClient {
         
         for policy in &security_policies {
             for mode in &security_modes {
-                if let Some(endpoint) = endpoints.iter().find(|ep| {
+                if let Some(endpoint) = matching.iter().find(|ep| {
                     SecurityPolicy::from_uri(ep.security_policy_uri.as_ref()) == *policy
                         && ep.security_mode == *mode
                 }) {
@@ -210,27 +738,35 @@ impl OpcUaClient {
             }
         }
 
-        let endpoint = chosen_endpoint
-            .ok_or_else(|| anyhow!("No suitable secure endpoint found for certificate authentication"))?;
+        let endpoint = chosen_endpoint.ok_or_else(|| self.no_endpoint_error(
+            "No suitable secure endpoint found for certificate authentication".to_string(),
+            &endpoints,
+            UserTokenType::Anonymous,
+            true,
+        ))?;
+        self.connect_with_certificate_endpoint(client, endpoint).await
+    }
 
+    async fn connect_with_certificate_endpoint(&mut self, mut client: opcua::client::Client, endpoint: EndpointDescription) -> Result<()> {
         // Use anonymous identity token since the certificate is configured in the client
         let identity_token = IdentityToken::Anonymous;
 
         // Connect to the chosen endpoint
         let (session, event_loop) = client
-            .connect_to_matching_endpoint(endpoint, identity_token)
-            .await
-            .map_err(|e| anyhow!("Certificate authentication failed: {:?}", e))?;
+            .connect_to_endpoint_directly(self.reachable_endpoint(&endpoint)?, identity_token)
+            .map_err(|e| anyhow!("Certificate authentication failed: {}", e))?;
 
         // Spawn the event loop
-        let handle = event_loop.spawn();
+        let mut handle = spawn_event_loop(event_loop, self.publishes.clone());
 
         // Wait for connection
-        session.wait_for_connection().await;
+        wait_for_session(&session, &mut handle).await
+            .map_err(|status| self.session_error("Certificate authentication failed", status))?;
 
         info!("✅ Certificate authentication successful");
-        self.session = Some(session);
+        self.session = Some(ServiceSession::new(session, &self.endpoint, self.stats.clone(), self.max_age).with_identity(self.user_identity()));
         self.event_loop_handle = Some(handle);
+        self.connected_endpoint = Some(endpoint);
         
         Ok(())
     }
@@ -249,6 +785,7 @@ impl OpcUaClient {
             (SecurityPolicy::Aes256Sha256RsaPss, MessageSecurityMode::Sign),
         ];
 
+        let mut last_status = None;
         for (policy, mode) in &fallback_policies {
             if self.verbose {
                 println!("🔄 Trying fallback: {} / {}", policy, mode);
@@ -265,13 +802,15 @@ impl OpcUaClient {
                 .private_key_path(key_path)
                 .create_sample_keypair(false)
                 .trust_server_certs(true)
+                .preferred_locales(self.locales.clone())
+                .verify_server_certs(self.library_verifies_certs())
                 .session_retry_limit(0)
                 .client()
                 .map_err(|e| anyhow!("Failed to create fallback client: {:?}", e))?;
 
             // Create endpoint manually (no discovery)
             let endpoint: EndpointDescription = (
-                self.endpoint.as_str(),
+                self.connection_url(),
                 policy.to_uri(),
                 *mode,
                 UserTokenPolicy::anonymous()
@@ -280,28 +819,41 @@ impl OpcUaClient {
             // Use anonymous identity token for certificate auth
             let identity_token = IdentityToken::Anonymous;
 
-            match client.connect_to_matching_endpoint(endpoint, identity_token).await {
+            let connected = match self.connect_session(&mut client, endpoint, identity_token).await {
                 Ok((session, event_loop)) => {
                     // Spawn the event loop
-                    let handle = event_loop.spawn();
+                    let mut handle = spawn_event_loop(event_loop, self.publishes.clone());
 
                     // Wait for connection
-                    session.wait_for_connection().await;
+                    match wait_for_session(&session, &mut handle).await {
+                        Ok(()) => Ok((session, handle)),
+                        Err(status) => {
+                            last_status = Some(status);
+                            Err(anyhow!(status))
+                        }
+                    }
+                }
+                Err(e) => Err(e),
+            };
 
+            match connected {
+                Ok((session, handle)) => {
                     info!("✅ Certificate authentication successful (fallback: {} / {})", policy, mode);
                     if self.verbose && *policy == SecurityPolicy::None {
                         println!("   ✅ Connected with None security policy (skipped ApplicationURI verification)");
                     }
                     
-                    self.session = Some(session);
+                    self.session = Some(ServiceSession::new(session, &self.endpoint, self.stats.clone(), self.max_age).with_identity(self.user_identity()));
                     self.event_loop_handle = Some(handle);
+                    // Without discovery there is nothing to compare with
+                    self.connection_info = Some(ConnectionInfo::new(*policy, *mode, UserTokenType::Anonymous, &[]));
                     return Ok(());
                 }
                 Err(e) => {
                     if self.verbose {
-                        println!("   ❌ Failed: {:?}", e);
+                        println!("   ❌ Failed: {}", e);
                     }
-                    debug!("Fallback attempt failed for {} / {}: {:?}", policy, mode, e);
+                    debug!("Fallback attempt failed for {} / {}: {}", policy, mode, e);
                     
                     // Add small delay to avoid overwhelming server
                     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -310,7 +862,356 @@ impl OpcUaClient {
             }
         }
 
-        Err(anyhow!("All certificate authentication methods failed"))
+        match last_status {
+            Some(status) => Err(self.session_error("All certificate authentication methods failed, the last one with", status)),
+            None => Err(anyhow!("All certificate authentication methods failed")),
+        }
+    }
+
+    /// The endpoints allowed by `--security-policy` and `--security-mode`, the credentials are
+    /// only needed to suggest other flags when none is
+    fn matching_endpoints(
+        &self,
+        endpoints: &[EndpointDescription],
+        token_type: UserTokenType,
+        has_certificate: bool,
+    ) -> Result<Vec<EndpointDescription>> {
+        let matching: Vec<EndpointDescription> = endpoints
+            .iter()
+            .filter(|ep| self.security_mismatch(ep).is_none())
+            .cloned()
+            .collect();
+        if matching.is_empty() && !endpoints.is_empty() {
+            return Err(self.no_endpoint_error(
+                "No endpoint matches --security-policy/--security-mode".to_string(),
+                endpoints,
+                token_type,
+                has_certificate,
+            ));
+        }
+        Ok(matching)
+    }
+
+    /// No endpoint fits the requested security and identity: the error shows what the server
+    /// offers and the flags selecting the most secure endpoint that accepts the login
+    fn no_endpoint_error(
+        &self,
+        message: String,
+        endpoints: &[EndpointDescription],
+        token_type: UserTokenType,
+        has_certificate: bool,
+    ) -> anyhow::Error {
+        let mut hints = Vec::new();
+        let accepts = |endpoint: &EndpointDescription, token_type: UserTokenType| endpoint.user_identity_tokens
+            .iter()
+            .flatten()
+            .any(|policy| policy.token_type == token_type);
+        let best = endpoints.iter().max_by_key(|ep| (accepts(ep, token_type), ep.security_level));
+
+        if let Some(endpoint) = best {
+            let mut flags = Vec::new();
+            let policy = SecurityPolicy::from_uri(endpoint.security_policy_uri.as_ref());
+            if policy != SecurityPolicy::None || self.auth_config.security_policy.is_some() {
+                flags.push(format!("--security-policy {}", policy));
+            }
+            if endpoint.security_mode != MessageSecurityMode::None || self.auth_config.security_mode.is_some() {
+                flags.push(format!("--security-mode {}", security_mode_flag(endpoint.security_mode)));
+            }
+
+            let mut needs_certificate = endpoint.security_mode != MessageSecurityMode::None;
+            if !accepts(endpoint, token_type) {
+                let offered = [UserTokenType::Anonymous, UserTokenType::UserName, UserTokenType::IssuedToken, UserTokenType::Certificate]
+                    .into_iter()
+                    .find(|offered| accepts(endpoint, *offered));
+                match offered {
+                    Some(UserTokenType::Anonymous) => hints.push("Leave out --username and --token to log in anonymously".to_string()),
+                    Some(UserTokenType::UserName) => flags.push("--username <USER> --password <PASSWORD>".to_string()),
+                    Some(UserTokenType::IssuedToken) => flags.push("--token <JWT_OR_FILE>".to_string()),
+                    Some(UserTokenType::Certificate) => needs_certificate = true,
+                    None => {}
+                }
+            }
+            if needs_certificate && !has_certificate {
+                flags.push("--cert <FILE> --key <FILE>".to_string());
+            }
+            if !flags.is_empty() {
+                hints.insert(0, format!("Use {}", flags.join(" ").bright_white()));
+            }
+        }
+        if endpoints.is_empty() {
+            hints.push("The server did not return any endpoints, check the endpoint URL".to_string());
+        } else {
+            hints.push("Choose from the endpoints interactively with --pick-endpoint".to_string());
+        }
+
+        anyhow::Error::new(ConnectError {
+            message,
+            hints,
+            status: StatusCode::BadSecurityPolicyRejected,
+            endpoints: (!endpoints.is_empty()).then(|| endpoint_matrix(endpoints).to_string()),
+        })
+    }
+
+    fn security_mismatch(&self, endpoint: &EndpointDescription) -> Option<String> {
+        if let Some(policy) = self.auth_config.security_policy
+            && SecurityPolicy::from_uri(endpoint.security_policy_uri.as_ref()) != policy
+        {
+            return Some(format!("not {}", policy));
+        }
+        if let Some(mode) = self.auth_config.security_mode
+            && endpoint.security_mode != mode
+        {
+            return Some(format!("not {}", mode));
+        }
+        None
+    }
+
+    /// Why the session cannot be opened on an endpoint with the given credentials, if it can't
+    fn endpoint_problem(&self, endpoint: &EndpointDescription, token_type: UserTokenType, has_certificate: bool) -> Option<String> {
+        if let Some(mismatch) = self.security_mismatch(endpoint) {
+            return Some(mismatch);
+        }
+        if !has_certificate && endpoint.security_mode != MessageSecurityMode::None {
+            return Some("needs --cert and --key".to_string());
+        }
+        let accepts_token = endpoint.user_identity_tokens
+            .iter()
+            .flatten()
+            .any(|policy| policy.token_type == token_type);
+        if !accepts_token {
+            return Some(format!("no {:?} token policy", token_type));
+        }
+        None
+    }
+
+    /// The JWT from `--token` (inline or file) or the variable named by `--token-env`
+    fn issued_token(&self) -> Result<Option<String>> {
+        let token = match (&self.auth_config.token, &self.auth_config.token_env) {
+            (Some(token), _) if Path::new(token).is_file() => {
+                debug!("Reading issued token from {}", token);
+                fs::read_to_string(token)
+                    .map_err(|e| anyhow!("Failed to read token file {}: {}", token, e))?
+            }
+            (Some(token), _) => token.clone(),
+            (None, Some(var)) => std::env::var(var)
+                .map_err(|_| anyhow!("Environment variable {} with the issued token is not set", var))?,
+            (None, None) => return Ok(None),
+        };
+        
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(anyhow!("The issued token is empty"));
+        }
+        Ok(Some(token.to_string()))
+    }
+
+    async fn connect_with_issued_token(&mut self, token: String) -> Result<()> {
+        info!("🎫 Attempting issued token authentication");
+        
+        let mut builder = ClientBuilder::new()
+            .application_name("OPC-UA Walker")
+            .application_uri("urn:opcua-walker")
+            .create_sample_keypair(false)
+            .trust_server_certs(true)
+            .preferred_locales(self.locales.clone())
+            .verify_server_certs(self.library_verifies_certs())
+            .session_retry_limit(0);
+        // A client certificate allows signed and encrypted channels, otherwise only None endpoints can be used
+        let has_certificate = if let (Some(cert_path), Some(key_path)) = (&self.auth_config.cert_path, &self.auth_config.key_path) {
+            self.configure_certificate_auth(cert_path, key_path)?;
+            builder = builder.certificate_path(cert_path).private_key_path(key_path);
+            true
+        } else {
+            false
+        };
+        let mut client = builder
+            .client()
+            .map_err(|e| anyhow!("Failed to create client: {:?}", e))?;
+        
+        let endpoints = self.discover_endpoints(&client).await
+            .map_err(|e| self.discovery_error(e))?;
+        self.offered_endpoints = endpoints.clone();
+        let endpoint = if self.auth_config.pick_endpoint {
+            pick_endpoint(&endpoints, |ep| self.endpoint_problem(ep, UserTokenType::IssuedToken, has_certificate))?
+        } else {
+            select_issued_token_endpoint(&self.matching_endpoints(&endpoints, UserTokenType::IssuedToken, has_certificate)?, has_certificate)?
+        };
+        
+        if self.verbose {
+            println!("🎫 Selecting endpoint for issued token: {} / {}",
+                     SecurityPolicy::from_uri(endpoint.security_policy_uri.as_ref()),
+                     endpoint.security_mode);
+        }
+        
+        let identity_token = IdentityToken::new_issued_token(ByteString::from(token.into_bytes()));
+        let (session, event_loop) = client
+            .connect_to_endpoint_directly(self.reachable_endpoint(&endpoint)?, identity_token)
+            .map_err(|e| anyhow!("Failed to connect to OPC-UA server: {}", e))?;
+        
+        // Without retries the event loop ends as soon as activation fails
+        let mut handle = spawn_event_loop(event_loop, self.publishes.clone());
+        wait_for_session(&session, &mut handle).await
+            .map_err(|status| self.connect_error(status, describe_issued_token_error(status)))?;
+        
+        info!("✅ Issued token authentication successful");
+        self.session = Some(ServiceSession::new(session, &self.endpoint, self.stats.clone(), self.max_age).with_identity(self.user_identity()));
+        self.event_loop_handle = Some(handle);
+        self.connected_endpoint = Some(endpoint);
+        
+        Ok(())
+    }
+
+    fn discovery_error(&self, status: StatusCode) -> anyhow::Error {
+        self.connect_error(status, format!("Endpoint discovery failed: {}", describe_discovery_error(status)))
+    }
+
+    fn session_error(&self, context: &str, status: StatusCode) -> anyhow::Error {
+        self.connect_error(status, format!("{}: {}", context, describe_session_error(status)))
+    }
+
+    /// A failed connection attempt with troubleshooting hints matching its status
+    fn connect_error(&self, status: StatusCode, message: String) -> anyhow::Error {
+        let mut hints = Vec::new();
+        match ConnectFailure::classify(status) {
+            ConnectFailure::HostUnresolvable => {
+                hints.push(format!("Check the host name in the endpoint URL {}", self.endpoint));
+                hints.push("Use the IP address if the name only resolves on the server's network".to_string());
+            }
+            ConnectFailure::ConnectionRefused => {
+                let port = split_endpoint_url(&self.endpoint).map(|(_, port, _)| port.to_string()).unwrap_or_else(|_| "of the endpoint URL".to_string());
+                hints.push(format!("Check that the server is running and listening on port {}", port));
+                hints.push("Check firewalls between this machine and the server".to_string());
+            }
+            ConnectFailure::CertificateUntrusted => match &self.auth_config.cert_path {
+                Some(cert_path) => {
+                    hints.push("Trust the client certificate on the server, most servers keep it in their rejected certificates folder".to_string());
+                    match load_certificate(cert_path) {
+                        Ok(certificate) => hints.push(format!("Client certificate thumbprint: {}", certificate.thumbprint().as_hex_string())),
+                        Err(e) => hints.push(format!("Could not read the client certificate {}: {}", cert_path, e)),
+                    }
+                }
+                None => hints.push("The server may require a client certificate, pass one with --cert and --key".to_string()),
+            },
+            ConnectFailure::IdentityRejected => {
+                hints.push("Check the user name and password or the issued token".to_string());
+                hints.push("The endpoint may not accept this kind of login, list its token policies with --pick-endpoint".to_string());
+            }
+            ConnectFailure::CertificateTime => {
+                hints.push("Check that the clocks of this machine and of the server are correct".to_string());
+                if let Some(cert_path) = &self.auth_config.cert_path
+                    && let Ok(certificate) = load_certificate(cert_path)
+                    && let (Ok(not_before), Ok(not_after)) = (certificate.not_before(), certificate.not_after())
+                {
+                    hints.push(format!("The client certificate is valid from {} to {}", not_before.to_rfc3339(), not_after.to_rfc3339()));
+                }
+                hints.push("The server certificate may have expired as well".to_string());
+            }
+            ConnectFailure::Timeout => {
+                hints.push("The server may be overloaded, or a firewall drops the packets instead of refusing them".to_string());
+            }
+            ConnectFailure::TooManySessions => {
+                hints.push("Other clients use up the sessions of the server, close some of them or wait for abandoned sessions to time out".to_string());
+                hints.push("Lower --max-sessions or --concurrency when running several commands against the server".to_string());
+            }
+            ConnectFailure::Other => {}
+        }
+        anyhow::Error::new(ConnectError { message, hints, status, endpoints: None })
+    }
+
+    /// The URL sessions connect to, the local end of the proxy tunnel when there is one
+    fn connection_url(&self) -> &str {
+        match &self.tunnel {
+            Some((url, _)) => url,
+            None => &self.endpoint,
+        }
+    }
+
+    /// Forward a local port through the proxy to the server, checking the proxy first so
+    /// its failures are not mistaken for OPC-UA errors
+    async fn open_proxy_tunnel(&mut self, proxy: ProxyUrl) -> Result<()> {
+        if self.tunnel.is_some() {
+            return Ok(());
+        }
+
+        let (host, port, path) = split_endpoint_url(&self.endpoint)?;
+        info!("🧦 Connecting through proxy {}", proxy);
+        proxy.open_tunnel(&host, port).await
+            .map_err(|e| anyhow!("Proxy error: {}", e))?;
+
+        let (address, forwarder) = start_forwarder(proxy, host, port).await?;
+        self.tunnel = Some((format!("opc.tcp://{}{}", address, path), forwarder));
+        Ok(())
+    }
+
+    /// Whether the client library checks server certificates itself. Through the proxy tunnel it
+    /// would match them against the tunnel's local address, so [`Self::verify_tunneled_certificate`]
+    /// checks them against the endpoint's host instead.
+    fn library_verifies_certs(&self) -> bool {
+        self.proxy.is_none()
+    }
+
+    /// Check the certificate of an endpoint reached through the proxy tunnel as the client library
+    /// checks those of direct connections, with the host of the endpoint URL given by the user
+    fn verify_tunneled_certificate(&self, endpoint: &EndpointDescription) -> Result<()> {
+        if self.tunnel.is_none() || endpoint.security_mode == MessageSecurityMode::None {
+            return Ok(());
+        }
+        let hostname = hostname_from_url(&self.endpoint)
+            .map_err(|_| anyhow!("Invalid endpoint URL: {}", self.endpoint))?;
+        check_server_certificate(&endpoint.server_certificate, &hostname, endpoint.server.application_uri.as_ref())
+            .map_err(|status| self.connect_error(status, format!("The server certificate is not valid for {}: {}", hostname, status)))
+    }
+
+    /// A discovered endpoint with its URL pointed at the address this client reaches the server on.
+    /// Servers often advertise host names that only resolve on their own network.
+    fn reachable_endpoint(&self, endpoint: &EndpointDescription) -> Result<EndpointDescription> {
+        self.verify_tunneled_certificate(endpoint)?;
+        let mut endpoint = endpoint.clone();
+        endpoint.endpoint_url = match &self.tunnel {
+            Some((tunnel_url, _)) => tunnel_url.as_str().into(),
+            None => {
+                let hostname = hostname_from_url(&self.endpoint)
+                    .map_err(|_| anyhow!("Invalid endpoint URL: {}", self.endpoint))?;
+                url_with_replaced_hostname(endpoint.endpoint_url.as_ref(), &hostname)
+                    .map_err(|_| anyhow!("Server advertised an invalid endpoint URL: {}", endpoint.endpoint_url))?
+                    .into()
+            }
+        };
+        Ok(endpoint)
+    }
+
+    /// Open a session on the server endpoint matching `endpoint`'s security policy and mode
+    async fn connect_session(
+        &self,
+        client: &mut Client,
+        endpoint: EndpointDescription,
+        identity_token: IdentityToken,
+    ) -> Result<(Arc<Session>, SessionEventLoop)> {
+        let Some((tunnel_url, _)) = &self.tunnel else {
+            return client
+                .connect_to_matching_endpoint(endpoint, identity_token)
+                .await
+                .map_err(|e| anyhow!(e));
+        };
+
+        // The advertised endpoint URLs name the server's own address and port, which are only
+        // reachable through the tunnel
+        let policy = SecurityPolicy::from_str(endpoint.security_policy_uri.as_ref())
+            .map_err(|_| anyhow!("Unknown security policy {}", endpoint.security_policy_uri))?;
+        let mut endpoint = client
+            .get_server_endpoints_from_url(tunnel_url.as_str())
+            .await?
+            .into_iter()
+            .find(|ep| SecurityPolicy::from_uri(ep.security_policy_uri.as_ref()) == policy
+                && ep.security_mode == endpoint.security_mode)
+            .ok_or_else(|| anyhow!("Server has no {} / {} endpoint", endpoint.security_policy_uri, endpoint.security_mode))?;
+        debug!("Using endpoint {} through the proxy tunnel", endpoint.endpoint_url);
+        self.verify_tunneled_certificate(&endpoint)?;
+        endpoint.endpoint_url = tunnel_url.as_str().into();
+
+        client
+            .connect_to_endpoint_directly(endpoint, identity_token)
+            .map_err(|e| anyhow!(e))
     }
 
     fn create_identity_token(&self) -> Result<IdentityToken> {
@@ -333,4 +1234,504 @@ impl OpcUaClient {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// The user token type a policy must offer for the given identity token
+fn identity_token_type(identity_token: &IdentityToken) -> UserTokenType {
+    match identity_token {
+        IdentityToken::UserName(..) => UserTokenType::UserName,
+        _ => UserTokenType::Anonymous,
+    }
+}
+
+/// Number the endpoints, dimming the ones `problem` rules out together with the reason
+fn print_endpoints(endpoints: &[EndpointDescription], problem: impl Fn(&EndpointDescription) -> Option<String>) {
+    println!("🔍 Discovered {} endpoint(s)", endpoints.len());
+    for (i, ep) in endpoints.iter().enumerate() {
+        let tokens: Vec<String> = ep.user_identity_tokens
+            .iter()
+            .flatten()
+            .map(|policy| format!("{:?}", policy.token_type))
+            .collect();
+        let line = format!("  {}. {} / {} / {} [{}]",
+            i + 1,
+            SecurityPolicy::from_uri(ep.security_policy_uri.as_ref()),
+            ep.security_mode,
+            ep.endpoint_url,
+            tokens.join(", ")
+        );
+        match problem(ep) {
+            Some(reason) => println!("{} {}", line.dimmed(), format!("({})", reason).dimmed()),
+            None => println!("{}", line),
+        }
+    }
+}
+
+/// Ask on the terminal which endpoint to use, only accepting the ones without a problem
+fn pick_endpoint(endpoints: &[EndpointDescription], problem: impl Fn(&EndpointDescription) -> Option<String>) -> Result<EndpointDescription> {
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!("--pick-endpoint needs an interactive terminal, choose the endpoint with --security-policy and --security-mode instead"));
+    }
+    if endpoints.is_empty() {
+        return Err(anyhow!("The server did not return any endpoints"));
+    }
+    if endpoints.iter().all(|ep| problem(ep).is_some()) {
+        print_endpoints(endpoints, &problem);
+        return Err(anyhow!("None of the endpoints can be used with the given credentials"));
+    }
+
+    print_endpoints(endpoints, &problem);
+    let mut input = String::new();
+    loop {
+        print!("Select an endpoint [1-{}]: ", endpoints.len());
+        io::stdout().flush()?;
+        input.clear();
+        if io::stdin().lock().read_line(&mut input)? == 0 {
+            return Err(anyhow!("No endpoint selected"));
+        }
+
+        let Some(endpoint) = input.trim().parse::<usize>().ok().and_then(|n| endpoints.get(n.wrapping_sub(1))) else {
+            println!("⚠️  Enter a number between 1 and {}", endpoints.len());
+            continue;
+        };
+        match problem(endpoint) {
+            Some(reason) => println!("⚠️  That endpoint cannot be used: {}", reason),
+            None => return Ok(endpoint.clone()),
+        }
+    }
+}
+
+/// The most secure endpoint without message security accepting the login, if any
+fn select_session_endpoint(endpoints: &[EndpointDescription], token_type: UserTokenType) -> Option<EndpointDescription> {
+    let accepts_token = |endpoint: &EndpointDescription| endpoint.user_identity_tokens
+        .iter()
+        .flatten()
+        .any(|policy| policy.token_type == token_type);
+
+    endpoints
+        .iter()
+        .filter(|ep| ep.security_mode == MessageSecurityMode::None && accepts_token(ep))
+        .max_by_key(|ep| ep.security_level)
+        .cloned()
+}
+
+/// The `--security-mode` value selecting a mode
+fn security_mode_flag(mode: MessageSecurityMode) -> &'static str {
+    match mode {
+        MessageSecurityMode::Sign => "sign",
+        MessageSecurityMode::SignAndEncrypt => "sign-encrypt",
+        _ => "none",
+    }
+}
+
+/// Pick the most secure usable endpoint with a user token policy for JWTs
+fn select_issued_token_endpoint(endpoints: &[EndpointDescription], has_certificate: bool) -> Result<EndpointDescription> {
+    let issued_policies = |endpoint: &EndpointDescription| -> Vec<UserTokenPolicy> {
+        endpoint.user_identity_tokens
+            .iter()
+            .flatten()
+            .filter(|policy| policy.token_type == UserTokenType::IssuedToken)
+            .cloned()
+            .collect()
+    };
+    
+    let offered: Vec<&EndpointDescription> = endpoints
+        .iter()
+        .filter(|endpoint| !issued_policies(endpoint).is_empty())
+        .collect();
+    if offered.is_empty() {
+        return Err(anyhow!("Token policy mismatch: the server does not offer issued token authentication on any endpoint"));
+    }
+    
+    // Servers that leave IssuedTokenType empty are assumed to accept JWTs
+    let accepts_jwt = |endpoint: &EndpointDescription| {
+        issued_policies(endpoint)
+            .iter()
+            .any(|policy| policy.issued_token_type.is_empty() || policy.issued_token_type.as_ref() == JWT_TOKEN_TYPE)
+    };
+    let usable = |endpoint: &EndpointDescription| has_certificate || endpoint.security_mode == MessageSecurityMode::None;
+    
+    let Some(endpoint) = offered
+        .iter()
+        .copied()
+        .filter(|endpoint| accepts_jwt(endpoint) && usable(endpoint))
+        .max_by_key(|endpoint| (endpoint.security_mode as i32, endpoint.security_level))
+    else {
+        let token_types: Vec<String> = offered
+            .iter()
+            .flat_map(|endpoint| issued_policies(endpoint))
+            .map(|policy| policy.issued_token_type.to_string())
+            .collect();
+        if offered.iter().any(|endpoint| accepts_jwt(endpoint)) {
+            return Err(anyhow!("Token policy mismatch: issued tokens are only accepted on secure endpoints, pass --cert and --key to open one"));
+        }
+        return Err(anyhow!("Token policy mismatch: the server expects issued tokens of type {}, not JWT", token_types.join(", ")));
+    };
+    
+    Ok(endpoint.clone())
+}
+
+/// Wait until the session is activated, or until the event loop gives up and reports why
+async fn wait_for_session(session: &Session, handle: &mut JoinHandle<StatusCode>) -> Result<(), StatusCode> {
+    let ended = |result: Result<StatusCode, _>| match result {
+        Ok(status) if !status.is_good() => status,
+        _ => StatusCode::BadConnectionClosed,
+    };
+    tokio::select! {
+        connected = session.wait_for_connection() => {
+            if connected {
+                Ok(())
+            } else {
+                Err(ended(handle.await))
+            }
+        }
+        result = &mut *handle => Err(ended(result)),
+    }
+}
+
+fn load_certificate(path: &str) -> Result<X509> {
+    let data = fs::read(path)?;
+    X509::from_pem(&data)
+        .or_else(|_| X509::from_der(&data))
+        .map_err(|e| anyhow!("{}", e))
+}
+
+/// The checks the client library applies to a server certificate besides trust: its validity
+/// period, the host it was issued for and the server's application URI
+fn check_server_certificate(certificate: &ByteString, hostname: &str, application_uri: &str) -> Result<(), StatusCode> {
+    let certificate = X509::from_byte_string(certificate).map_err(|_| StatusCode::BadCertificateInvalid)?;
+    certificate.is_time_valid(&chrono::Utc::now())?;
+    certificate.is_hostname_valid(hostname)?;
+    certificate.is_application_uri_valid(application_uri)
+}
+
+/// Kinds of connection failures, each with its own troubleshooting hints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectFailure {
+    HostUnresolvable,
+    ConnectionRefused,
+    /// The server rejected the secure channel, usually because it does not trust the client certificate
+    CertificateUntrusted,
+    /// ActivateSession refused the user identity
+    IdentityRejected,
+    /// A certificate is outside its validity period, or a clock is wrong
+    CertificateTime,
+    Timeout,
+    /// The server has no session left for another client
+    TooManySessions,
+    Other,
+}
+
+impl ConnectFailure {
+    pub fn classify(status: StatusCode) -> Self {
+        match status {
+            StatusCode::BadTcpEndpointUrlInvalid => Self::HostUnresolvable,
+            StatusCode::BadCommunicationError
+            | StatusCode::BadConnectionClosed
+            | StatusCode::BadConnectionRejected
+            | StatusCode::BadNotConnected
+            | StatusCode::BadServerNotConnected => Self::ConnectionRefused,
+            StatusCode::BadSecurityChecksFailed
+            | StatusCode::BadCertificateUntrusted
+            | StatusCode::BadCertificateInvalid
+            | StatusCode::BadCertificateRevoked => Self::CertificateUntrusted,
+            StatusCode::BadIdentityTokenRejected
+            | StatusCode::BadIdentityTokenInvalid
+            | StatusCode::BadUserAccessDenied => Self::IdentityRejected,
+            StatusCode::BadCertificateTimeInvalid | StatusCode::BadCertificateIssuerTimeInvalid => Self::CertificateTime,
+            StatusCode::BadTimeout => Self::Timeout,
+            StatusCode::BadTooManySessions => Self::TooManySessions,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Connecting failed; the message is the error text, the hints are printed below it
+#[derive(Debug)]
+pub struct ConnectError {
+    message: String,
+    hints: Vec<String>,
+    status: StatusCode,
+    /// Matrix of the endpoints the server offers, when none of them could be used
+    endpoints: Option<String>,
+}
+
+impl ConnectError {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn print_hints(&self) {
+        if let Some(endpoints) = &self.endpoints {
+            eprintln!("\n📋 {}", "The server offers:".bright_white().bold());
+            eprintln!("{}", endpoints);
+        }
+        if self.hints.is_empty() {
+            return;
+        }
+        eprintln!("\n💡 {}", "Troubleshooting suggestions:".bright_yellow().bold());
+        for hint in &self.hints {
+            eprintln!("   • {}", hint);
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ConnectError {}
+
+/// Turn the status a session failed with into a readable sentence
+fn describe_session_error(status: StatusCode) -> String {
+    match ConnectFailure::classify(status) {
+        ConnectFailure::ConnectionRefused => format!("the connection was refused or lost ({})", status),
+        ConnectFailure::CertificateUntrusted => format!("the server rejected the secure channel, it does not trust the client certificate ({})", status),
+        ConnectFailure::IdentityRejected => format!("the server refused the user identity ({})", status),
+        ConnectFailure::CertificateTime => format!("a certificate is outside its validity period ({})", status),
+        ConnectFailure::Timeout => "the server did not respond in time".to_string(),
+        ConnectFailure::TooManySessions => format!("the server has no session left for another client ({})", status),
+        ConnectFailure::HostUnresolvable | ConnectFailure::Other => status.to_string(),
+    }
+}
+
+/// Separate a token the server refused from a token it could not be handed in the expected form
+fn describe_issued_token_error(status: StatusCode) -> String {
+    match status {
+        StatusCode::BadIdentityTokenRejected | StatusCode::BadUserAccessDenied => {
+            format!("Token rejected: the server refused the issued token, it may be expired, for another audience or from an untrusted issuer ({})", status)
+        }
+        StatusCode::BadIdentityTokenInvalid | StatusCode::BadSecurityPolicyRejected | StatusCode::BadSecurityModeRejected => {
+            format!("Token policy mismatch: the server could not accept the token in the form it was sent ({})", status)
+        }
+        status => format!("Issued token authentication failed: {}", status),
+    }
+}
+
+fn is_transient_discovery_error(status: StatusCode) -> bool {
+    matches!(status, StatusCode::BadCommunicationError | StatusCode::BadConnectionClosed)
+}
+
+/// Turn the status codes produced by the discovery round trip into a readable sentence
+fn describe_discovery_error(status: StatusCode) -> String {
+    match status {
+        StatusCode::BadTcpEndpointUrlInvalid => {
+            "the endpoint address could not be resolved".to_string()
+        }
+        StatusCode::BadCommunicationError => {
+            "the TCP connection was refused or could not be established".to_string()
+        }
+        StatusCode::BadConnectionClosed => {
+            "the server closed the connection during the hello/acknowledge handshake".to_string()
+        }
+        StatusCode::BadTimeout => "the discovery request timed out".to_string(),
+        status => format!("the discovery service returned an error ({})", status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use opcua::crypto::X509Data;
+
+    fn client(args: &[&str]) -> OpcUaClient {
+        let args = [&["opcua-walker", "-e", "opc.tcp://plc:4841"], args, &["info"]].concat();
+        let cli = Cli::try_parse_from(args).unwrap();
+        OpcUaClient::for_endpoint(&cli, &cli.endpoint).unwrap()
+    }
+
+    fn connect_error(client: &OpcUaClient, status: StatusCode) -> ConnectError {
+        client.session_error("Connecting failed", status).downcast::<ConnectError>().unwrap()
+    }
+
+    #[test]
+    fn classifies_connect_failures() {
+        let cases = [
+            (StatusCode::BadTcpEndpointUrlInvalid, ConnectFailure::HostUnresolvable),
+            (StatusCode::BadCommunicationError, ConnectFailure::ConnectionRefused),
+            (StatusCode::BadConnectionClosed, ConnectFailure::ConnectionRefused),
+            (StatusCode::BadConnectionRejected, ConnectFailure::ConnectionRefused),
+            (StatusCode::BadNotConnected, ConnectFailure::ConnectionRefused),
+            (StatusCode::BadServerNotConnected, ConnectFailure::ConnectionRefused),
+            (StatusCode::BadSecurityChecksFailed, ConnectFailure::CertificateUntrusted),
+            (StatusCode::BadCertificateUntrusted, ConnectFailure::CertificateUntrusted),
+            (StatusCode::BadCertificateInvalid, ConnectFailure::CertificateUntrusted),
+            (StatusCode::BadCertificateRevoked, ConnectFailure::CertificateUntrusted),
+            (StatusCode::BadIdentityTokenRejected, ConnectFailure::IdentityRejected),
+            (StatusCode::BadIdentityTokenInvalid, ConnectFailure::IdentityRejected),
+            (StatusCode::BadUserAccessDenied, ConnectFailure::IdentityRejected),
+            (StatusCode::BadCertificateTimeInvalid, ConnectFailure::CertificateTime),
+            (StatusCode::BadCertificateIssuerTimeInvalid, ConnectFailure::CertificateTime),
+            (StatusCode::BadTimeout, ConnectFailure::Timeout),
+            (StatusCode::BadTooManySessions, ConnectFailure::TooManySessions),
+            (StatusCode::BadNodeIdUnknown, ConnectFailure::Other),
+            (StatusCode::BadUnexpectedError, ConnectFailure::Other),
+        ];
+        for (status, failure) in cases {
+            assert_eq!(ConnectFailure::classify(status), failure, "{}", status);
+        }
+    }
+
+    #[test]
+    fn message_describes_the_status() {
+        let client = client(&[]);
+        let error = connect_error(&client, StatusCode::BadConnectionRejected);
+        assert_eq!(error.status(), StatusCode::BadConnectionRejected);
+        assert!(error.to_string().starts_with("Connecting failed: the connection was refused or lost"), "{}", error);
+
+        let error = connect_error(&client, StatusCode::BadTimeout);
+        assert_eq!(error.to_string(), "Connecting failed: the server did not respond in time");
+
+        let error = connect_error(&client, StatusCode::BadUnexpectedError);
+        assert_eq!(error.to_string(), format!("Connecting failed: {}", StatusCode::BadUnexpectedError));
+        assert!(error.hints.is_empty());
+    }
+
+    #[test]
+    fn hints_name_the_endpoint() {
+        let client = client(&[]);
+        let error = connect_error(&client, StatusCode::BadTcpEndpointUrlInvalid);
+        assert!(error.hints[0].contains("opc.tcp://plc:4841"), "{:?}", error.hints);
+
+        let error = connect_error(&client, StatusCode::BadCommunicationError);
+        assert!(error.hints[0].ends_with("listening on port 4841"), "{:?}", error.hints);
+    }
+
+    #[test]
+    fn untrusted_without_a_certificate_suggests_one() {
+        let error = connect_error(&client(&[]), StatusCode::BadSecurityChecksFailed);
+        assert_eq!(error.hints.len(), 1);
+        assert!(error.hints[0].contains("--cert and --key"), "{:?}", error.hints);
+    }
+
+    #[test]
+    fn untrusted_with_an_unreadable_certificate() {
+        let client = client(&["--cert", "/nonexistent/client.der", "--key", "/nonexistent/client.pem"]);
+        let error = connect_error(&client, StatusCode::BadCertificateUntrusted);
+        assert!(error.hints[0].starts_with("Trust the client certificate"), "{:?}", error.hints);
+        assert!(error.hints[1].starts_with("Could not read the client certificate /nonexistent/client.der"), "{:?}", error.hints);
+    }
+
+    #[test]
+    fn certificate_hints_describe_the_certificate() {
+        let pki_dir = std::env::temp_dir().join(format!("opcua-walker-client-test-{}", std::process::id()));
+        let keypair = ClientKeypair::load_or_create(&pki_dir).unwrap();
+        let cert = keypair.cert_path.display().to_string();
+        let key = keypair.key_path.display().to_string();
+        let client = client(&["--cert", &cert, "--key", &key]);
+
+        let error = connect_error(&client, StatusCode::BadCertificateUntrusted);
+        assert_eq!(error.hints[1], format!("Client certificate thumbprint: {}", keypair.thumbprint()));
+
+        let error = connect_error(&client, StatusCode::BadCertificateTimeInvalid);
+        assert!(error.hints[0].contains("clocks"), "{:?}", error.hints);
+        assert!(error.hints[1].starts_with("The client certificate is valid from "), "{:?}", error.hints);
+        assert!(error.hints[2].contains("server certificate"), "{:?}", error.hints);
+
+        fs::remove_dir_all(&pki_dir).unwrap();
+    }
+
+    #[test]
+    fn too_many_sessions_is_recognized() {
+        let client = client(&[]);
+        let error = client.session_error("Connecting failed", StatusCode::BadTooManySessions);
+        assert!(is_too_many_sessions(&error));
+        assert!(error.downcast_ref::<ConnectError>().unwrap().hints.iter().any(|hint| hint.contains("--max-sessions")));
+
+        assert!(!is_too_many_sessions(&client.session_error("Connecting failed", StatusCode::BadTimeout)));
+        assert!(!is_too_many_sessions(&anyhow!("{}", StatusCode::BadTooManySessions)));
+    }
+
+    #[test]
+    fn refusals_are_service_faults() {
+        let client = client(&[]);
+        for status in [StatusCode::BadCertificateUntrusted, StatusCode::BadIdentityTokenRejected, StatusCode::BadTooManySessions] {
+            let error = client.session_error("Connecting failed", status);
+            assert_eq!(OperationResult::of_error(&error), Some(OperationResult::ServiceFault(status)));
+        }
+        for status in [StatusCode::BadCommunicationError, StatusCode::BadTimeout, StatusCode::BadTcpEndpointUrlInvalid] {
+            let error = client.session_error("Connecting failed", status);
+            assert_eq!(OperationResult::of_error(&error), Some(OperationResult::ClientError(error.to_string())));
+        }
+    }
+
+    #[test]
+    fn issued_token_errors() {
+        assert!(describe_issued_token_error(StatusCode::BadIdentityTokenRejected).starts_with("Token rejected: "));
+        assert!(describe_issued_token_error(StatusCode::BadUserAccessDenied).starts_with("Token rejected: "));
+        assert!(describe_issued_token_error(StatusCode::BadIdentityTokenInvalid).starts_with("Token policy mismatch: "));
+        assert!(describe_issued_token_error(StatusCode::BadSecurityModeRejected).starts_with("Token policy mismatch: "));
+        assert!(describe_issued_token_error(StatusCode::BadTimeout).starts_with("Issued token authentication failed: "));
+    }
+
+    #[test]
+    fn discovery_errors() {
+        assert_eq!(describe_discovery_error(StatusCode::BadTcpEndpointUrlInvalid), "the endpoint address could not be resolved");
+        assert_eq!(describe_discovery_error(StatusCode::BadTimeout), "the discovery request timed out");
+        assert!(describe_discovery_error(StatusCode::BadServiceUnsupported).starts_with("the discovery service returned an error"));
+        assert!(is_transient_discovery_error(StatusCode::BadConnectionClosed));
+        assert!(!is_transient_discovery_error(StatusCode::BadTimeout));
+    }
+
+    fn server_certificate(host: &str) -> ByteString {
+        let application = ApplicationDescription { application_uri: "urn:plc".into(), ..Default::default() };
+        let mut x509_data = X509Data::from((application, Some(vec![host.to_string()])));
+        // Only the names matter, and shorter keys are generated much faster
+        x509_data.key_size = 1024;
+        let (certificate, _) = X509::cert_and_pkey(&x509_data).unwrap();
+        certificate.as_byte_string()
+    }
+
+    fn secure_endpoint(certificate: ByteString) -> EndpointDescription {
+        let mut endpoint: EndpointDescription = ("opc.tcp://plc:4841", SecurityPolicy::Basic256Sha256.to_uri(), MessageSecurityMode::SignAndEncrypt).into();
+        endpoint.server_certificate = certificate;
+        endpoint.server.application_uri = "urn:plc".into();
+        endpoint
+    }
+
+    #[test]
+    fn server_certificates_are_checked_against_host_and_uri() {
+        let certificate = server_certificate("plc");
+        assert_eq!(check_server_certificate(&certificate, "plc", "urn:plc"), Ok(()));
+        assert_eq!(check_server_certificate(&certificate, "impostor", "urn:plc"), Err(StatusCode::BadCertificateHostNameInvalid));
+        assert_eq!(check_server_certificate(&certificate, "plc", "urn:other"), Err(StatusCode::BadCertificateUriInvalid));
+        assert_eq!(check_server_certificate(&ByteString::from(vec![1, 2, 3]), "plc", "urn:plc"), Err(StatusCode::BadCertificateInvalid));
+    }
+
+    #[tokio::test]
+    async fn tunneled_certificates_are_checked_against_the_endpoint_host() {
+        let mut client = client(&["--proxy", "socks5://jumphost:1080"]);
+        assert!(!client.library_verifies_certs());
+        let impostor = secure_endpoint(server_certificate("impostor"));
+        // Without a tunnel the client library checks the certificate
+        assert!(client.verify_tunneled_certificate(&impostor).is_ok());
+
+        client.tunnel = Some(("opc.tcp://127.0.0.1:40000/".to_string(), tokio::spawn(async {})));
+        let error = client.reachable_endpoint(&impostor).unwrap_err().downcast::<ConnectError>().unwrap();
+        assert_eq!(error.status(), StatusCode::BadCertificateHostNameInvalid);
+        assert_eq!(error.message, format!("The server certificate is not valid for plc: {}", StatusCode::BadCertificateHostNameInvalid));
+
+        let endpoint = client.reachable_endpoint(&secure_endpoint(server_certificate("plc"))).unwrap();
+        assert_eq!(endpoint.endpoint_url.as_ref(), "opc.tcp://127.0.0.1:40000/");
+        let mut unsecured = impostor;
+        unsecured.security_mode = MessageSecurityMode::None;
+        assert!(client.reachable_endpoint(&unsecured).is_ok());
+    }
+
+    #[tokio::test]
+    async fn refused_connections_fail_fast() {
+        // A port that was just free refuses the connection
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let cli = Cli::try_parse_from(["opcua-walker", "-e", &format!("opc.tcp://127.0.0.1:{}", port), "info"]).unwrap();
+        let mut client = OpcUaClient::for_endpoint(&cli, &cli.endpoint).unwrap();
+
+        let started = Instant::now();
+        let error = client.connect().await.unwrap_err().downcast::<ConnectError>().unwrap();
+        // Only the discovery attempts wait, 1 s and 2 s, the client library must not retry below them
+        assert!(started.elapsed() < Duration::from_secs(6), "took {:?}", started.elapsed());
+        assert_eq!(ConnectFailure::classify(error.status()), ConnectFailure::ConnectionRefused);
+    }
+}
@@ -0,0 +1,347 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tabled::{Table, Tabled};
+
+use crate::client::OpcUaClient;
+use crate::commands::export::{attribute_request, browse_instances, BATCH_SIZE};
+use crate::commands::lint::browse_name_paths;
+use crate::commands::restore::{display_path, resolve_browse_paths, NamespaceTranslator};
+use crate::utils::formatter::{format_data_type, format_node_id, parse_node_id_text};
+use crate::utils::namespace::parse_node_id;
+
+/// Expected interface of a server, read by `assert` and written by `assert --generate`
+#[derive(Serialize, Deserialize)]
+pub struct AssertSpec {
+    /// Node the browse paths start at (default: Objects folder)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// Namespace array the indices in the spec refer to (default: the server's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespaces: Option<Vec<String>>,
+    pub nodes: Vec<AssertEntry>,
+}
+
+/// One expected node, found by node ID or by browse path, with optional expectations
+#[derive(Serialize, Deserialize)]
+pub struct AssertEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    /// BrowseNames below the root as `<namespace index>:<name>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browse_path: Option<Vec<String>>,
+    /// NodeClass by name, e.g. `Variable`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_class: Option<String>,
+    /// DataType by name for standard types, by node ID otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+    /// Whether the CurrentWrite bit of the AccessLevel is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub writable: Option<bool>,
+}
+
+impl AssertEntry {
+    fn describe(&self) -> String {
+        match (&self.node_id, &self.browse_path) {
+            (Some(node_id), _) => node_id.clone(),
+            (None, Some(path)) => display_path(path),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// The expectations of the entry, `exists` when it has none
+    fn checks(&self) -> String {
+        let mut checks = Vec::new();
+        if let Some(node_class) = &self.node_class {
+            checks.push(node_class.clone());
+        }
+        if let Some(data_type) = &self.data_type {
+            checks.push(data_type.clone());
+        }
+        match self.writable {
+            Some(true) => checks.push("writable".to_string()),
+            Some(false) => checks.push("read-only".to_string()),
+            None => {}
+        }
+        if checks.is_empty() {
+            "exists".to_string()
+        } else {
+            checks.join(", ")
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct AssertRow {
+    #[tabled(rename = "#")]
+    entry: usize,
+    #[tabled(rename = "Node")]
+    node: String,
+    #[tabled(rename = "Resolved To")]
+    node_id: String,
+    #[tabled(rename = "Checks")]
+    checks: String,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+pub struct AssertArgs {
+    pub spec: String,
+    /// Write the spec from the server below this node instead of checking it
+    pub generate: Option<NodeId>,
+    pub max_depth: u32,
+}
+
+pub fn validate(spec: &str, generate: bool, node: Option<&str>, max_depth: u32) -> Result<AssertArgs> {
+    let generate = match (generate, node) {
+        (true, Some(node)) => Some(parse_node_id(node)?),
+        (true, None) => Some(ObjectId::ObjectsFolder.into()),
+        (false, _) => None,
+    };
+    Ok(AssertArgs {
+        spec: spec.to_string(),
+        generate,
+        max_depth,
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &AssertArgs) -> Result<()> {
+    match &args.generate {
+        Some(root) => generate(client, args, root).await,
+        None => check(client, args).await,
+    }
+}
+
+/// Check every entry of the spec against the server, failing when any entry does not match
+async fn check(client: &mut OpcUaClient, args: &AssertArgs) -> Result<()> {
+    let content = fs::read_to_string(&args.spec)
+        .map_err(|e| anyhow!("Failed to read spec {}: {}", args.spec, e))?;
+    let spec: AssertSpec = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("{} is not a valid spec file: {}", args.spec, e))?;
+
+    // Catch broken entries before asking the server anything
+    let mut node_ids = Vec::with_capacity(spec.nodes.len());
+    for (idx, entry) in spec.nodes.iter().enumerate() {
+        node_ids.push(match (&entry.node_id, &entry.browse_path) {
+            (Some(_), Some(_)) => return Err(anyhow!("Entry {} of {} has both a node_id and a browse_path", idx + 1, args.spec)),
+            (None, None) => return Err(anyhow!("Entry {} of {} has neither a node_id nor a browse_path", idx + 1, args.spec)),
+            (Some(node_id), None) => Some(parse_node_id(node_id)?),
+            (None, Some(_)) => None,
+        });
+        if let Some(node_class) = &entry.node_class {
+            parse_node_class(node_class)
+                .ok_or_else(|| anyhow!("Entry {} of {} has an unknown node_class: {}", idx + 1, args.spec, node_class))?;
+        }
+    }
+
+    let session = client.session()?;
+    let server_namespaces = client.namespaces().await?;
+    let translator = NamespaceTranslator {
+        source: spec.namespaces.as_deref().unwrap_or(server_namespaces),
+        target: server_namespaces,
+        ns_map: client.ns_map(),
+    };
+
+    let root = match &spec.root {
+        Some(root) => translator.node_id(&parse_node_id(root)?),
+        None => ObjectId::ObjectsFolder.into(),
+    };
+
+    println!("\n{}", "🧪 Asserting Nodes".bright_cyan().bold());
+    println!("📄 Spec: {}", args.spec.bright_white());
+    println!("📍 Browse paths from: {}", format_node_id(&root).bright_white());
+    println!("📊 Entries: {}", spec.nodes.len().to_string().bright_white());
+    println!("{}", "─".repeat(60));
+
+    let paths: Vec<&[String]> = spec.nodes.iter().filter_map(|entry| entry.browse_path.as_deref()).collect();
+    let mut resolved_paths = resolve_browse_paths(session, &paths, &root, &translator).await?.into_iter();
+    let targets: Vec<Option<NodeId>> = node_ids
+        .into_iter()
+        .map(|node_id| match node_id {
+            Some(node_id) => Some(translator.node_id(&node_id)),
+            None => resolved_paths.next().flatten(),
+        })
+        .collect();
+
+    const ATTRIBUTES: [AttributeId; 3] = [AttributeId::NodeClass, AttributeId::DataType, AttributeId::AccessLevel];
+
+    let found: Vec<usize> = targets.iter().enumerate().filter(|(_, t)| t.is_some()).map(|(idx, _)| idx).collect();
+    let mut failures: Vec<Vec<String>> = targets
+        .iter()
+        .map(|target| match target {
+            Some(_) => Vec::new(),
+            None => vec!["not found".to_string()],
+        })
+        .collect();
+
+    for chunk in found.chunks(BATCH_SIZE / ATTRIBUTES.len()) {
+        let read_requests: Vec<ReadValueId> = chunk
+            .iter()
+            .flat_map(|idx| {
+                let node_id = targets[*idx].as_ref().expect("only found entries are read");
+                ATTRIBUTES.map(|attr| attribute_request(node_id, attr))
+            })
+            .collect();
+        let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+
+        for (idx, values) in chunk.iter().zip(results.chunks(ATTRIBUTES.len())) {
+            failures[*idx] = mismatches(&spec.nodes[*idx], values, &translator);
+        }
+    }
+
+    let rows: Vec<AssertRow> = spec.nodes
+        .iter()
+        .zip(&targets)
+        .zip(&failures)
+        .enumerate()
+        .map(|(idx, ((entry, target), failures))| AssertRow {
+            entry: idx + 1,
+            node: entry.describe(),
+            node_id: target.as_ref().map(format_node_id).unwrap_or_else(|| "—".dimmed().to_string()),
+            checks: entry.checks(),
+            result: if failures.is_empty() {
+                "✅ Pass".green().to_string()
+            } else {
+                format!("❌ {}", failures.join(", ")).red().to_string()
+            },
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+
+    let failed = failures.iter().filter(|f| !f.is_empty()).count();
+    if failed > 0 {
+        return Err(anyhow!("{} of {} assertion(s) failed", failed, spec.nodes.len()));
+    }
+
+    println!("\n✅ All {} assertion(s) passed", spec.nodes.len().to_string().bright_green());
+    Ok(())
+}
+
+/// What differs between an entry and the NodeClass, DataType and AccessLevel read for its node
+fn mismatches(entry: &AssertEntry, values: &[DataValue], translator: &NamespaceTranslator<'_>) -> Vec<String> {
+    let node_class = match &values[0].value {
+        Some(Variant::Int32(class)) => NodeClass::try_from(*class).ok(),
+        _ => None,
+    };
+    // A node without a NodeClass does not exist, whatever the status says
+    let Some(node_class) = node_class else {
+        return vec!["not found".to_string()];
+    };
+
+    let mut failures = Vec::new();
+    if entry.node_class.as_deref().and_then(parse_node_class).is_some_and(|expected| expected != node_class) {
+        failures.push(format!("is {:?}", node_class));
+    }
+
+    if let Some(expected) = &entry.data_type {
+        match &values[1].value {
+            Some(Variant::NodeId(data_type)) => {
+                // Types by node ID refer to the namespaces of the spec
+                let expected = match parse_node_id_text(expected) {
+                    Some(node_id) => format_data_type(&translator.node_id(&node_id)),
+                    None => expected.clone(),
+                };
+                let actual = format_data_type(data_type);
+                if actual != expected {
+                    failures.push(format!("DataType is {}", actual));
+                }
+            }
+            _ => failures.push("has no DataType".to_string()),
+        }
+    }
+
+    if let Some(expected) = entry.writable {
+        // Bit 1 of AccessLevel is CurrentWrite
+        match values[2].value {
+            Some(Variant::Byte(level)) => {
+                let writable = level & 0x02 != 0;
+                if writable != expected {
+                    failures.push(if writable { "writable" } else { "read-only" }.to_string());
+                }
+            }
+            _ => failures.push("has no AccessLevel".to_string()),
+        }
+    }
+    failures
+}
+
+fn parse_node_class(name: &str) -> Option<NodeClass> {
+    [
+        NodeClass::Object,
+        NodeClass::Variable,
+        NodeClass::Method,
+        NodeClass::ObjectType,
+        NodeClass::VariableType,
+        NodeClass::ReferenceType,
+        NodeClass::DataType,
+        NodeClass::View,
+    ]
+    .into_iter()
+    .find(|node_class| format!("{:?}", node_class).eq_ignore_ascii_case(name))
+}
+
+/// Write a spec with every instance node below `root`, by browse path so that it survives node
+/// IDs changing between builds of the PLC program
+async fn generate(client: &mut OpcUaClient, args: &AssertArgs, root: &NodeId) -> Result<()> {
+    let session = client.session()?;
+
+    println!("\n{}", "🧪 Generating Assertion Spec".bright_cyan().bold());
+    println!("📍 Subtree: {}", format_node_id(root).bright_white());
+    println!("📏 Max depth: {}", args.max_depth.to_string().bright_white());
+    println!("{}", "─".repeat(60));
+
+    let nodes = browse_instances(session, root, args.max_depth).await?;
+    let paths = browse_name_paths(&nodes, root);
+
+    let mut entries: Vec<AssertEntry> = nodes
+        .iter()
+        .zip(&paths)
+        .map(|(node, path)| AssertEntry {
+            node_id: None,
+            browse_path: Some(path.iter().map(|name| format!("{}:{}", name.namespace_index, name.name)).collect()),
+            node_class: Some(format!("{:?}", node.node_class)),
+            data_type: None,
+            writable: None,
+        })
+        .collect();
+
+    const ATTRIBUTES: [AttributeId; 2] = [AttributeId::DataType, AttributeId::AccessLevel];
+
+    let variables: Vec<usize> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.node_class == NodeClass::Variable)
+        .map(|(idx, _)| idx)
+        .collect();
+    for chunk in variables.chunks(BATCH_SIZE / ATTRIBUTES.len()) {
+        let read_requests: Vec<ReadValueId> = chunk
+            .iter()
+            .flat_map(|idx| ATTRIBUTES.map(|attr| attribute_request(&nodes[*idx].node_id, attr)))
+            .collect();
+        let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+
+        for (idx, values) in chunk.iter().zip(results.chunks(ATTRIBUTES.len())) {
+            if let Some(Variant::NodeId(data_type)) = &values[0].value {
+                entries[*idx].data_type = Some(format_data_type(data_type));
+            }
+            if let Some(Variant::Byte(level)) = values[1].value {
+                entries[*idx].writable = Some(level & 0x02 != 0);
+            }
+        }
+    }
+
+    let spec = AssertSpec {
+        root: Some(format_node_id(root)),
+        namespaces: Some(client.namespaces().await?.to_vec()),
+        nodes: entries,
+    };
+    fs::write(&args.spec, serde_json::to_string_pretty(&spec)?)
+        .map_err(|e| anyhow!("Failed to write {}: {}", args.spec, e))?;
+
+    println!("📊 Variables: {}", variables.len().to_string().bright_white());
+    println!("\n✅ {} {} node(s) to {}", "Saved".green(), spec.nodes.len().to_string().bright_white(), args.spec.bright_white());
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tracing::debug;
+
+use crate::client::OpcUaClient;
+use crate::commands::export::{attribute_request, browse_instances, BATCH_SIZE};
+use crate::commands::lint::browse_name_paths;
+use crate::commands::write::parse_value;
+use crate::session::ServiceSession;
+use crate::utils::formatter::{format_node_id, localized_text, text_locale};
+use crate::utils::namespace::parse_node_id;
+use crate::utils::security::ConnectionInfo;
+
+/// Value types a backup can hold, the ones `write` knows how to parse
+const SUPPORTED_TYPES: [VariantScalarTypeId; 16] = [
+    VariantScalarTypeId::Boolean,
+    VariantScalarTypeId::SByte,
+    VariantScalarTypeId::Byte,
+    VariantScalarTypeId::Int16,
+    VariantScalarTypeId::UInt16,
+    VariantScalarTypeId::Int32,
+    VariantScalarTypeId::UInt32,
+    VariantScalarTypeId::Int64,
+    VariantScalarTypeId::UInt64,
+    VariantScalarTypeId::Float,
+    VariantScalarTypeId::Double,
+    VariantScalarTypeId::String,
+    VariantScalarTypeId::DateTime,
+    VariantScalarTypeId::LocalizedText,
+    VariantScalarTypeId::QualifiedName,
+    VariantScalarTypeId::NodeId,
+];
+
+/// Parameter snapshot written by `backup` and read by `restore`
+#[derive(Serialize, Deserialize)]
+pub struct Backup {
+    pub endpoint: String,
+    pub created: String,
+    pub root: String,
+    /// Namespace array of the source server, used to translate namespace indices on restore
+    pub namespaces: Vec<String>,
+    pub nodes: Vec<BackupEntry>,
+    /// Security of the session the backup was taken over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<ConnectionInfo>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub node_id: String,
+    /// BrowseNames below the root as `<namespace index>:<name>`
+    pub browse_path: Vec<String>,
+    pub data_type: String,
+    pub value: BackupValue,
+}
+
+// Arrays come first: serde would also read a two-item array as one structured value
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BackupValue {
+    Array(Vec<BackupText>),
+    Scalar(BackupText),
+}
+
+/// One value in the text form `write` parses, or LocalizedText and QualifiedName values as
+/// `{"locale": "en", "text": "..."}` and `{"namespace_index": 2, "name": "..."}`
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BackupText {
+    Text(String),
+    LocalizedText { locale: Option<String>, text: String },
+    QualifiedName { namespace_index: u16, name: String },
+}
+
+impl BackupText {
+    /// The value as a variant of the recorded type
+    pub fn to_variant(&self, value_type: VariantScalarTypeId) -> Result<Variant> {
+        match self {
+            BackupText::Text(text) => parse_value(text, value_type),
+            BackupText::LocalizedText { locale, text } if value_type == VariantScalarTypeId::LocalizedText => {
+                Ok(Variant::LocalizedText(Box::new(LocalizedText::new(locale.as_deref().unwrap_or_default(), text))))
+            }
+            BackupText::QualifiedName { namespace_index, name } if value_type == VariantScalarTypeId::QualifiedName => {
+                Ok(Variant::QualifiedName(Box::new(QualifiedName::new(*namespace_index, name.as_str()))))
+            }
+            BackupText::LocalizedText { .. } => Err(anyhow!("a LocalizedText value does not fit a {} entry", value_type)),
+            BackupText::QualifiedName { .. } => Err(anyhow!("a QualifiedName value does not fit a {} entry", value_type)),
+        }
+    }
+}
+
+/// The writable variables of a subtree whose values a backup can hold
+pub struct Parameters {
+    pub entries: Vec<BackupEntry>,
+    /// Variables found below the root
+    pub variables: usize,
+    pub read_only: usize,
+    /// Writable variables whose values cannot be stored
+    pub unsupported: Vec<String>,
+}
+
+pub struct BackupArgs {
+    pub start_node_id: NodeId,
+    pub max_depth: u32,
+    pub output: String,
+}
+
+pub fn validate(node: &str, max_depth: u32, output: &str) -> Result<BackupArgs> {
+    Ok(BackupArgs {
+        start_node_id: parse_node_id(node)?,
+        max_depth,
+        output: output.to_string(),
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &BackupArgs) -> Result<()> {
+    let session = client.session()?;
+
+    println!("\n{}", "💾 Backing Up Writable Parameters".bright_cyan().bold());
+    println!("📍 Subtree: {}", format_node_id(&args.start_node_id).bright_white());
+    println!("📏 Max depth: {}", args.max_depth.to_string().bright_white());
+    println!("{}", "─".repeat(60));
+
+    let parameters = collect_parameters(session, &args.start_node_id, args.max_depth).await?;
+
+    let backup = Backup {
+        endpoint: client.endpoint().to_string(),
+        created: DateTime::now().to_string(),
+        root: format_node_id(&args.start_node_id),
+        namespaces: client.namespaces().await?.to_vec(),
+        nodes: parameters.entries,
+        security: client.connection_info().cloned(),
+    };
+
+    fs::write(&args.output, serde_json::to_string_pretty(&backup)?)
+        .map_err(|e| anyhow!("Failed to write {}: {}", args.output, e))?;
+
+    println!("📊 Variables: {}", parameters.variables.to_string().bright_white());
+    println!("🔒 Read-only: {}", parameters.read_only.to_string().bright_white());
+    if !parameters.unsupported.is_empty() {
+        println!("⚠️  Skipped {} writable variable(s) with values that cannot be stored:", parameters.unsupported.len().to_string().yellow());
+        for node_id in &parameters.unsupported {
+            println!("  • {}", node_id);
+        }
+    }
+    println!("\n✅ {} {} parameter(s) to {}", "Saved".green(), backup.nodes.len().to_string().bright_white(), args.output.bright_white());
+    Ok(())
+}
+
+/// Read the AccessLevel, DataType and Value of every variable below `start_node_id` and keep
+/// the writable ones
+pub async fn collect_parameters(session: &ServiceSession, start_node_id: &NodeId, max_depth: u32) -> Result<Parameters> {
+    let nodes = browse_instances(session, start_node_id, max_depth).await?;
+    let paths = browse_name_paths(&nodes, start_node_id);
+    let variables: Vec<usize> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.node_class == NodeClass::Variable)
+        .map(|(idx, _)| idx)
+        .collect();
+
+    const ATTRIBUTES: [AttributeId; 3] = [AttributeId::AccessLevel, AttributeId::DataType, AttributeId::Value];
+
+    let mut entries = Vec::new();
+    let mut read_only = 0;
+    let mut unsupported = Vec::new();
+
+    for chunk in variables.chunks(BATCH_SIZE / ATTRIBUTES.len()) {
+        let read_requests: Vec<ReadValueId> = chunk
+            .iter()
+            .flat_map(|idx| ATTRIBUTES.map(|attr| attribute_request(&nodes[*idx].node_id, attr)))
+            .collect();
+        let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+
+        for (idx, values) in chunk.iter().zip(results.chunks(ATTRIBUTES.len())) {
+            let node = &nodes[*idx];
+            // Bit 1 of AccessLevel is CurrentWrite
+            let writable = matches!(values[0].value, Some(Variant::Byte(level)) if level & 0x02 != 0);
+            if !writable {
+                read_only += 1;
+                continue;
+            }
+
+            let data_type = match &values[1].value {
+                Some(Variant::NodeId(data_type)) => VariantScalarTypeId::try_from(data_type.as_ref()).ok(),
+                _ => None,
+            };
+            let value = values[2].value.as_ref().filter(|_| values[2].status.is_none_or(|s| s.is_good()));
+
+            match value.and_then(|value| backup_value(value, data_type)) {
+                Some((value_type, value)) => entries.push(BackupEntry {
+                    node_id: format_node_id(&node.node_id),
+                    browse_path: paths[*idx].iter().map(|name| format!("{}:{}", name.namespace_index, name.name)).collect(),
+                    data_type: value_type.to_string(),
+                    value,
+                }),
+                None => unsupported.push(format_node_id(&node.node_id)),
+            }
+        }
+    }
+
+    Ok(Parameters {
+        entries,
+        variables: variables.len(),
+        read_only,
+        unsupported,
+    })
+}
+
+/// Text form of a scalar or one-dimensional array value of a supported type
+fn backup_value(value: &Variant, data_type: Option<VariantScalarTypeId>) -> Option<(VariantScalarTypeId, BackupValue)> {
+    match value {
+        Variant::Array(array) => {
+            if array.dimensions.as_ref().is_some_and(|d| d.len() > 1) {
+                debug!("Multi-dimensional arrays are not backed up");
+                return None;
+            }
+            let value_type = data_type.unwrap_or(array.value_type);
+            let values = array.values.iter().map(value_text).collect::<Option<Vec<_>>>()?;
+            is_supported(value_type).then_some((value_type, BackupValue::Array(values)))
+        }
+        value => {
+            let value_type = data_type.or_else(|| value.scalar_type_id())?;
+            is_supported(value_type).then_some((value_type, BackupValue::Scalar(value_text(value)?)))
+        }
+    }
+}
+
+/// The inverse of `write::parse_value`
+fn value_text(value: &Variant) -> Option<BackupText> {
+    Some(BackupText::Text(match value {
+        Variant::Boolean(v) => v.to_string(),
+        Variant::SByte(v) => v.to_string(),
+        Variant::Byte(v) => v.to_string(),
+        Variant::Int16(v) => v.to_string(),
+        Variant::UInt16(v) => v.to_string(),
+        Variant::Int32(v) => v.to_string(),
+        Variant::UInt32(v) => v.to_string(),
+        Variant::Int64(v) => v.to_string(),
+        Variant::UInt64(v) => v.to_string(),
+        Variant::Float(v) => v.to_string(),
+        Variant::Double(v) => v.to_string(),
+        Variant::String(v) => v.as_ref().to_string(),
+        Variant::DateTime(v) => v.to_string(),
+        Variant::NodeId(v) => v.to_string(),
+        Variant::LocalizedText(v) => {
+            return Some(BackupText::LocalizedText {
+                locale: text_locale(v).map(String::from),
+                text: localized_text(v).to_string(),
+            });
+        }
+        Variant::QualifiedName(v) => {
+            return Some(BackupText::QualifiedName { namespace_index: v.namespace_index, name: v.name.as_ref().to_string() });
+        }
+        _ => return None,
+    }))
+}
+
+fn is_supported(value_type: VariantScalarTypeId) -> bool {
+    SUPPORTED_TYPES.contains(&value_type)
+}
+
+/// Look up a value type by the name stored in a backup
+pub fn parse_value_type(name: &str) -> Option<VariantScalarTypeId> {
+    SUPPORTED_TYPES.into_iter().find(|value_type| value_type.to_string() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: Variant, value_type: VariantScalarTypeId) -> (String, Variant) {
+        let (_, backup) = backup_value(&value, Some(value_type)).unwrap();
+        let json = serde_json::to_string(&backup).unwrap();
+        let BackupValue::Scalar(text) = serde_json::from_str(&json).unwrap() else {
+            panic!("{} is not a scalar", json);
+        };
+        (json, text.to_variant(value_type).unwrap())
+    }
+
+    #[test]
+    fn structured_values_round_trip() {
+        let values = [
+            (Variant::from(LocalizedText::new("en", "Pump station 3")), VariantScalarTypeId::LocalizedText, r#"{"locale":"en","text":"Pump station 3"}"#),
+            (Variant::from(LocalizedText::new("", "Pump")), VariantScalarTypeId::LocalizedText, r#"{"locale":null,"text":"Pump"}"#),
+            (Variant::from(QualifiedName::new(2, "MotorSpeed")), VariantScalarTypeId::QualifiedName, r#"{"namespace_index":2,"name":"MotorSpeed"}"#),
+            (Variant::from(NodeId::new(2, "Target")), VariantScalarTypeId::NodeId, r#""ns=2;s=Target""#),
+        ];
+        for (value, value_type, expected) in values {
+            let (json, restored) = round_trip(value.clone(), value_type);
+            assert_eq!(json, expected);
+            assert_eq!(restored, value);
+        }
+    }
+
+    #[test]
+    fn text_forms_are_parsed_like_write() {
+        let text: BackupText = serde_json::from_str(r#""lt:de|Pumpe""#).unwrap();
+        assert_eq!(text.to_variant(VariantScalarTypeId::LocalizedText).unwrap(), Variant::from(LocalizedText::new("de", "Pumpe")));
+        let text: BackupText = serde_json::from_str(r#""2:MotorSpeed""#).unwrap();
+        assert_eq!(text.to_variant(VariantScalarTypeId::QualifiedName).unwrap(), Variant::from(QualifiedName::new(2, "MotorSpeed")));
+    }
+
+    #[test]
+    fn structured_values_must_fit_the_type() {
+        let text: BackupText = serde_json::from_str(r#"{"locale":"en","text":"Pump"}"#).unwrap();
+        assert!(text.to_variant(VariantScalarTypeId::QualifiedName).is_err());
+        let text: BackupText = serde_json::from_str(r#"{"namespace_index":2,"name":"Pump"}"#).unwrap();
+        assert!(text.to_variant(VariantScalarTypeId::String).is_err());
+    }
+
+    #[test]
+    fn arrays_are_not_read_as_structures() {
+        let value: BackupValue = serde_json::from_str(r#"["en", "Pump"]"#).unwrap();
+        assert!(matches!(value, BackupValue::Array(items) if items.len() == 2));
+    }
+}
@@ -0,0 +1,364 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tabled::{Table, Tabled};
+
+use crate::client::OpcUaClient;
+use crate::commands::export::browse_instances;
+use crate::commands::stale_scan::is_property;
+use crate::session::ServiceSession;
+use crate::types::BenchCommand;
+use crate::utils::expand::expand_all;
+use crate::utils::formatter::format_node_id;
+use crate::utils::namespace::{parse_node_id, parse_node_spec, NodeSpec};
+use crate::utils::security::ConnectionInfo;
+use crate::utils::time::format_duration;
+
+#[derive(Tabled)]
+struct MetricRow {
+    #[tabled(rename = "Metric")]
+    metric: &'static str,
+    #[tabled(rename = "Value")]
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Latency {
+    p50: f64,
+    p95: f64,
+    p99: f64,
+    max: f64,
+}
+
+#[derive(Serialize)]
+struct BenchReport {
+    mode: &'static str,
+    nodes: usize,
+    batch_size: usize,
+    concurrency: usize,
+    duration_seconds: f64,
+    requests: u64,
+    failed_requests: u64,
+    operations: u64,
+    failed_operations: u64,
+    requests_per_second: f64,
+    operations_per_second: f64,
+    latency_ms: Latency,
+    /// Occurrences of each bad status, from failed requests and failed operations alike
+    errors: BTreeMap<String, u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<ConnectionInfo>,
+}
+
+pub enum BenchTarget {
+    Nodes(Vec<NodeSpec>),
+    Subtree { start_node_id: NodeId, max_depth: u32, count: usize },
+    Write(NodeSpec),
+}
+
+pub struct BenchArgs {
+    pub target: BenchTarget,
+    pub batch_size: usize,
+    pub concurrency: usize,
+    pub duration: Duration,
+    pub json: bool,
+}
+
+/// The operations cycled through by the workers, each request takes the next `batch_size` of them
+enum Operations {
+    Read(Vec<ReadValueId>),
+    Write(Vec<WriteValue>),
+}
+
+/// Outcome of the requests sent by one worker
+#[derive(Default)]
+struct WorkerStats {
+    latencies: Vec<Duration>,
+    failed_requests: u64,
+    operations: u64,
+    failed_operations: u64,
+    errors: BTreeMap<String, u64>,
+}
+
+pub fn validate(args: &BenchCommand) -> Result<BenchArgs> {
+    if args.batch == 0 {
+        return Err(anyhow!("--batch must be at least 1"));
+    }
+    if args.concurrency == 0 {
+        return Err(anyhow!("--concurrency must be at least 1"));
+    }
+    if args.duration.is_zero() {
+        return Err(anyhow!("--duration must be greater than 0"));
+    }
+    if args.count == 0 {
+        return Err(anyhow!("--count must be at least 1"));
+    }
+
+    let target = if let Some(scratch) = &args.write {
+        BenchTarget::Write(parse_node_spec(scratch)?)
+    } else if !args.node_ids.is_empty() {
+        let node_ids = expand_all(&args.node_ids)?;
+        BenchTarget::Nodes(node_ids.iter().map(|node_id| parse_node_spec(node_id)).collect::<Result<_>>()?)
+    } else {
+        BenchTarget::Subtree {
+            start_node_id: args.node.as_deref().map(parse_node_id).transpose()?.unwrap_or_else(|| ObjectId::ObjectsFolder.into()),
+            max_depth: args.depth,
+            count: args.count,
+        }
+    };
+
+    Ok(BenchArgs {
+        target,
+        batch_size: args.batch,
+        concurrency: args.concurrency,
+        duration: args.duration,
+        json: args.json,
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &BenchArgs) -> Result<()> {
+    let (operations, nodes) = prepare_operations(client, args).await?;
+    let mode = match operations {
+        Operations::Read(_) => "read",
+        Operations::Write(_) => "write",
+    };
+
+    if !args.json {
+        println!("\n{}", "⏱️  OPC-UA Throughput Benchmark".bright_cyan().bold());
+        println!("{}", "─".repeat(60));
+        println!("📊 Mode: {} of {} node(s)", mode.bright_white(), nodes.to_string().bright_white());
+        println!("📦 Batch size: {}", args.batch_size.to_string().bright_white());
+        println!("🔀 Concurrency: {}", args.concurrency.to_string().bright_white());
+        println!("⏳ Duration: {} (Ctrl+C stops early)", format_duration(args.duration).bright_white());
+    }
+
+    let session = client.session()?.clone();
+    let operations = Arc::new(operations);
+    let stop = Arc::new(AtomicBool::new(false));
+    let started = Instant::now();
+    let deadline = started + args.duration;
+
+    let interrupt = {
+        let stop = stop.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                stop.store(true, Ordering::Relaxed);
+            }
+        })
+    };
+
+    // Workers start at different offsets so that they do not all read the same nodes at once
+    let handles: Vec<_> = (0..args.concurrency)
+        .map(|worker| {
+            let session = session.clone();
+            let operations = operations.clone();
+            let stop = stop.clone();
+            let batch_size = args.batch_size;
+            let offset = worker * nodes / args.concurrency;
+            tokio::spawn(async move { run_worker(&session, &operations, batch_size, offset, deadline, &stop).await })
+        })
+        .collect();
+
+    let mut total = WorkerStats::default();
+    for handle in handles {
+        let stats = handle.await.map_err(|e| anyhow!("Benchmark worker failed: {}", e))?;
+        total.latencies.extend(stats.latencies);
+        total.failed_requests += stats.failed_requests;
+        total.operations += stats.operations;
+        total.failed_operations += stats.failed_operations;
+        for (status, count) in stats.errors {
+            *total.errors.entry(status).or_default() += count;
+        }
+    }
+    interrupt.abort();
+
+    let elapsed = started.elapsed().as_secs_f64();
+    total.latencies.sort_unstable();
+    let requests = total.latencies.len() as u64;
+    let report = BenchReport {
+        mode,
+        nodes,
+        batch_size: args.batch_size,
+        concurrency: args.concurrency,
+        duration_seconds: elapsed,
+        requests,
+        failed_requests: total.failed_requests,
+        operations: total.operations,
+        failed_operations: total.failed_operations,
+        requests_per_second: requests as f64 / elapsed,
+        operations_per_second: total.operations as f64 / elapsed,
+        latency_ms: Latency {
+            p50: percentile(&total.latencies, 50.0),
+            p95: percentile(&total.latencies, 95.0),
+            p99: percentile(&total.latencies, 99.0),
+            max: total.latencies.last().map_or(0.0, |latency| latency.as_secs_f64() * 1000.0),
+        },
+        errors: total.errors,
+        security: client.connection_info().cloned(),
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        display_report(&report);
+    }
+
+    if report.requests > 0 && report.failed_requests == report.requests {
+        return Err(anyhow!("All {} request(s) failed", report.requests));
+    }
+    Ok(())
+}
+
+/// The read or write operations to benchmark and the number of distinct nodes they cover
+async fn prepare_operations(client: &mut OpcUaClient, args: &BenchArgs) -> Result<(Operations, usize)> {
+    let node_ids = match &args.target {
+        BenchTarget::Write(spec) => {
+            let node_id = client.resolve_node(spec).await?;
+            let value = client.session()?
+                .read(&[ReadValueId::from(&node_id)], TimestampsToReturn::Neither, 0.0)
+                .await?
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            let current = match (value.status, value.value) {
+                (Some(status), _) if !status.is_good() => {
+                    return Err(anyhow!("Cannot read scratch node {}: {}", format_node_id(&node_id), status));
+                }
+                (_, Some(current)) => current,
+                (_, None) => return Err(anyhow!("Scratch node {} has no value to write back", format_node_id(&node_id))),
+            };
+            let write = WriteValue {
+                node_id,
+                attribute_id: AttributeId::Value as u32,
+                index_range: NumericRange::None,
+                value: DataValue {
+                    value: Some(current),
+                    ..Default::default()
+                },
+            };
+            return Ok((Operations::Write(vec![write]), 1));
+        }
+        BenchTarget::Nodes(specs) => {
+            let mut node_ids = Vec::with_capacity(specs.len());
+            for spec in specs {
+                node_ids.push(client.resolve_node(spec).await?);
+            }
+            node_ids
+        }
+        BenchTarget::Subtree { start_node_id, max_depth, count } => {
+            if !args.json {
+                println!("🔎 Discovering variables below {}...", format_node_id(start_node_id).bright_white());
+            }
+            // Properties are mostly static configuration, data variables are what a gateway polls
+            let node_ids: Vec<NodeId> = browse_instances(client.session()?, start_node_id, *max_depth)
+                .await?
+                .into_iter()
+                .filter(|node| node.node_class == NodeClass::Variable && !is_property(node))
+                .map(|node| node.node_id)
+                .take(*count)
+                .collect();
+            if node_ids.is_empty() {
+                return Err(anyhow!("No variables found below {}", format_node_id(start_node_id)));
+            }
+            node_ids
+        }
+    };
+
+    let nodes = node_ids.len();
+    Ok((Operations::Read(node_ids.iter().map(ReadValueId::from).collect()), nodes))
+}
+
+/// Send batched requests back to back until the deadline, timing each service call
+async fn run_worker(
+    session: &ServiceSession,
+    operations: &Operations,
+    batch_size: usize,
+    mut offset: usize,
+    deadline: Instant,
+    stop: &AtomicBool,
+) -> WorkerStats {
+    let mut stats = WorkerStats::default();
+
+    while Instant::now() < deadline && !stop.load(Ordering::Relaxed) {
+        let (result, latency) = match operations {
+            Operations::Read(reads) => {
+                let batch = cycled_batch(reads, offset, batch_size);
+                let started = Instant::now();
+                let result = session.read(&batch, TimestampsToReturn::Neither, 0.0).await;
+                let latency = started.elapsed();
+                let statuses = result.map(|values| values.iter().map(|dv| dv.status.unwrap_or(StatusCode::Good)).collect::<Vec<_>>());
+                (statuses, latency)
+            }
+            Operations::Write(writes) => {
+                let batch = cycled_batch(writes, offset, batch_size);
+                let started = Instant::now();
+                let result = session.write(&batch).await;
+                (result, started.elapsed())
+            }
+        };
+        offset += batch_size;
+        stats.latencies.push(latency);
+
+        match result {
+            Ok(statuses) => {
+                stats.operations += statuses.len() as u64;
+                for status in statuses.iter().filter(|status| !status.is_good()) {
+                    stats.failed_operations += 1;
+                    *stats.errors.entry(status.to_string()).or_default() += 1;
+                }
+            }
+            Err(status) => {
+                stats.failed_requests += 1;
+                *stats.errors.entry(status.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    stats
+}
+
+/// `batch_size` operations starting at `offset`, wrapping around when the list is shorter
+fn cycled_batch<T: Clone>(items: &[T], offset: usize, batch_size: usize) -> Vec<T> {
+    (0..batch_size).map(|i| items[(offset + i) % items.len()].clone()).collect()
+}
+
+/// Nearest-rank percentile of sorted latencies in milliseconds
+fn percentile(sorted: &[Duration], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1].as_secs_f64() * 1000.0
+}
+
+fn display_report(report: &BenchReport) {
+    let ms = |value: f64| format!("{:.2} ms", value);
+    let rows = vec![
+        MetricRow { metric: "Duration", value: format!("{:.1} s", report.duration_seconds) },
+        MetricRow { metric: "Requests", value: report.requests.to_string() },
+        MetricRow { metric: "Failed requests", value: report.failed_requests.to_string() },
+        MetricRow { metric: "Operations", value: report.operations.to_string() },
+        MetricRow { metric: "Failed operations", value: report.failed_operations.to_string() },
+        MetricRow { metric: "Requests/s", value: format!("{:.1}", report.requests_per_second) },
+        MetricRow { metric: "Operations/s", value: format!("{:.1}", report.operations_per_second) },
+        MetricRow { metric: "Latency p50", value: ms(report.latency_ms.p50) },
+        MetricRow { metric: "Latency p95", value: ms(report.latency_ms.p95) },
+        MetricRow { metric: "Latency p99", value: ms(report.latency_ms.p99) },
+        MetricRow { metric: "Latency max", value: ms(report.latency_ms.max) },
+    ];
+    println!("\n{}", Table::new(rows));
+
+    if !report.errors.is_empty() {
+        println!("\n⚠️  Errors:");
+        for (status, count) in &report.errors {
+            println!("  • {} × {}", count.to_string().yellow(), status.red());
+        }
+    }
+
+    println!("\n✅ {}", "Benchmark completed".green());
+}
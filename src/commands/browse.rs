@@ -1,78 +1,476 @@
 use anyhow::{anyhow, Result};
 use colored::*;
-use opcua::client::Session;
 use opcua::types::*;
-use std::collections::HashSet;
-use std::str::FromStr;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use tracing::{debug, warn};
 
 use crate::client::OpcUaClient;
-use crate::utils::formatter::{format_node_id, format_node_class, format_variant, format_status_code, truncate_string};
+use crate::commands::export::attribute_request;
+use crate::session::ServiceSession;
+use crate::types::{BrowseCommand, BrowseSort, Radix};
+use crate::utils::browse_request::BrowseRequest;
+use crate::utils::formatter::{format_data_type, format_node_id, format_node_class, format_value_variant, format_variant_in, is_strict, radix, reference_name, sanitize_text, truncate_string, Severity};
+use crate::utils::enums::{enum_value, EnumValue};
+use crate::utils::guard::{self, Operations};
+use crate::utils::labels::label_for;
+use crate::utils::limits::descends;
+use crate::utils::namespace::parse_node_id;
+use crate::utils::outcome::OperationResult;
+use crate::utils::reference_types::reference_type_name;
+use crate::utils::snapshot::Snapshot;
+use crate::utils::sort::natural_cmp;
+use crate::utils::value_map::mapped_value;
 
+/// A browsed node with the children found below it
 #[derive(Clone)]
-struct TreeNode {
-    reference: ReferenceDescription,
-    children: Vec<TreeNode>,
+pub struct TreeNode {
+    pub reference: ReferenceDescription,
+    pub children: Vec<TreeNode>,
     value: Option<String>,
+    /// Expanded under an earlier parent already, where its children are shown
+    pub duplicate: bool,
 }
 
-pub async fn execute(
-    client: &mut OpcUaClient,
-    start_node: Option<&str>,
-    max_depth: u32,
-    compact: bool,
-    read_values: bool,
-) -> Result<()> {
-    let session = client.session()?;
-    
+/// One line of `browse --stream --json`
+#[derive(Serialize)]
+struct StreamedNode {
+    node_id: String,
+    /// Node whose children the node was browsed from, the starting node at depth 0
+    parent_id: String,
+    depth: u32,
+    browse_name: String,
+    display_name: String,
+    node_class: String,
+    /// The reference from the parent, by node ID and name
+    reference_type_id: String,
+    reference_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    type_definition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    /// Integer values in the radix of `--radix`, `value` stays decimal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value_formatted: Option<String>,
+    /// Display string of `--value-map`, `value` stays as read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value_mapped: Option<&'static str>,
+    /// Severity of the value's status, next to `value`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    severity: Option<Severity>,
+    /// Whether a failing value is the server's status for the node, a service fault or a client error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<&'static str>,
+    /// With `--show-duplicates`, the parent the node was expanded under when this line is a stub
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_of: Option<String>,
+}
+
+/// Results without a continuation point of at least this many references are browsed again in
+/// pages, since some servers cut long results instead of handing out a continuation point
+const TRUNCATION_CHECK_MIN: usize = 100;
+
+/// The requested page size and what the browse found out about the server's paging
+#[derive(Default)]
+struct Paging {
+    /// requestedMaxReferencesPerNode of every Browse, 0 leaves it to the server
+    max_refs_per_node: u32,
+    /// Continuation points followed with BrowseNext
+    continuations: AtomicUsize,
+    /// Pages the server returned shorter than requested
+    clamped_pages: AtomicUsize,
+    /// Nodes whose references the server cut without a continuation point
+    truncated: AtomicUsize,
+}
+
+/// The children of one node on the current path of a streaming browse that are still to print
+struct StreamLevel {
+    parent: NodeId,
+    pending: VecDeque<ReferenceDescription>,
+}
+
+pub struct BrowseArgs {
+    pub start_node_id: NodeId,
+    pub max_depth: u32,
+    pub compact: bool,
+    pub read_values: bool,
+    pub lines: bool,
+    pub sort: BrowseSort,
+    pub ids_only: bool,
+    pub classes: Vec<NodeClass>,
+    /// Lowercase text the display names must contain
+    pub filter: Option<String>,
+    pub stream: bool,
+    pub json: bool,
+    /// Mark children by the reference type linking them in the full tree format
+    pub ref_markers: bool,
+    /// Show nodes expanded under an earlier parent again as stubs
+    pub show_duplicates: bool,
+    /// Snapshot browsed instead of the server with `--from-snapshot`
+    pub snapshot: Option<Arc<Snapshot>>,
+    /// Values read with a failing status, failing the browse with `--strict`
+    failed_values: AtomicUsize,
+    paging: Paging,
+}
+
+impl BrowseArgs {
+    fn is_filtered(&self) -> bool {
+        !self.classes.is_empty() || self.filter.is_some()
+    }
+
+    /// Whether a node itself passes `--classes` and `--filter`
+    fn matches(&self, reference: &ReferenceDescription) -> bool {
+        (self.classes.is_empty() || self.classes.contains(&reference.node_class))
+            && self.filter.as_ref().is_none_or(|filter| reference_name(reference).0.to_lowercase().contains(filter))
+    }
+}
+
+pub fn validate(args: &BrowseCommand) -> Result<BrowseArgs> {
     // Determine starting node
-    let start_node_id = if let Some(node_str) = start_node {
+    let start_node_id = if let Some(node_str) = &args.node {
         parse_node_id(node_str)?
     } else {
         ObjectId::ObjectsFolder.into()
     };
     
+    Ok(BrowseArgs {
+        start_node_id,
+        max_depth: args.depth,
+        compact: args.compact,
+        read_values: args.values,
+        lines: args.lines,
+        sort: args.sort,
+        ids_only: args.ids_only,
+        classes: args.classes.iter().copied().map(NodeClass::from).collect(),
+        filter: args.filter.as_deref().map(str::to_lowercase),
+        stream: args.stream,
+        json: args.json,
+        ref_markers: !args.no_ref_markers,
+        show_duplicates: args.show_duplicates,
+        snapshot: args.from_snapshot.as_deref().map(Snapshot::load).transpose()?.map(Arc::new),
+        failed_values: AtomicUsize::new(0),
+        paging: Paging { max_refs_per_node: args.max_refs_per_node, ..Default::default() },
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &BrowseArgs) -> Result<()> {
+    browse(client, args).await?;
+    
+    let failed = args.failed_values.load(AtomicOrdering::Relaxed);
+    if is_strict() && failed > 0 {
+        return Err(anyhow!("{} value(s) are not Good (strict mode)", failed));
+    }
+    Ok(())
+}
+
+async fn browse(client: &mut OpcUaClient, args: &BrowseArgs) -> Result<()> {
+    let session = client.session()?;
+    
+    if args.stream && (args.lines || args.ids_only || args.json) {
+        // NDJSON starts with the security of the session, or the snapshot read offline, every
+        // later line is a node
+        if let Some(snapshot) = args.snapshot.as_ref().filter(|_| args.json) {
+            println!("{}", serde_json::json!({ "snapshot": snapshot.json() }));
+        } else if let Some(security) = client.connection_info().filter(|_| args.json) {
+            println!("{{\"security\":{}}}", serde_json::to_string(security)?);
+        } else if let Some(snapshot) = &args.snapshot {
+            eprintln!("{}", snapshot.banner());
+        }
+        stream_tree(session, args, client.is_verbose()).await?;
+        return Ok(());
+    }
+    
+    if args.lines || args.ids_only {
+        // Keeps stdout to the nodes only
+        if let Some(snapshot) = &args.snapshot {
+            eprintln!("{}", snapshot.banner());
+        }
+        let tree = browse_tree(session, args, client.is_verbose()).await?;
+        
+        if args.ids_only {
+            display_ids(&tree, args);
+            return Ok(());
+        }
+        confirm_value_reads(&tree, args)?;
+        return display_lines(session, &tree, args, 0).await;
+    }
+    
     println!("\n{}", "🌳 Browsing OPC-UA Address Space".bright_cyan().bold());
-    println!("📍 Starting node: {}", format_node_id(&start_node_id).bright_white());
-    println!("📏 Max depth: {}", max_depth.to_string().bright_white());
-    if read_values {
+    if let Some(snapshot) = &args.snapshot {
+        println!("{}", snapshot.banner());
+    }
+    println!("📍 Starting node: {}", format_node_id(&args.start_node_id).bright_white());
+    println!("📏 Max depth: {}", args.max_depth.to_string().bright_white());
+    if args.read_values {
         println!("📖 Reading values: {}", "Enabled".bright_green());
     }
-    if compact {
+    if args.compact {
         println!("📦 Compact view: {}", "Enabled".bright_green());
     }
+    if args.is_filtered() {
+        println!("🔎 Filter: {}", describe_filter(args).bright_white());
+    }
+    if args.stream {
+        println!("🌊 Streaming: {}", "Enabled".bright_green());
+    }
+    if args.paging.max_refs_per_node > 0 {
+        println!("📄 References per request: {}", args.paging.max_refs_per_node.to_string().bright_white());
+    }
+    if args.ref_markers && !args.compact {
+        let legend: Vec<String> = REFERENCE_MARKERS
+            .iter()
+            .map(|(reference_type, marker)| format!("{} {:?}", marker.dimmed(), reference_type))
+            .collect();
+        println!("🔖 References: {}", legend.join("  "));
+    }
     println!("{}", "─".repeat(60));
     
-    let mut visited = HashSet::new();
+    if args.stream {
+        if stream_tree(session, args, client.is_verbose()).await? == 0 {
+            println!("⚠️  No nodes found");
+        } else {
+            print_paging_summary(&args.paging);
+            println!("\n✅ {}", "Browse completed successfully".green());
+        }
+        return Ok(());
+    }
     
-    // Build tree structure starting from the root
-    let tree = build_tree_recursive(
-        session,
-        &start_node_id,
-        0,
-        max_depth,
-        &mut visited,
-        client.is_verbose(),
-    ).await?;
+    let tree = browse_tree(session, args, client.is_verbose()).await?;
     
     if tree.is_empty() {
         println!("⚠️  No nodes found");
         return Ok(());
     }
+    confirm_value_reads(&tree, args)?;
     
     // Display tree with values if requested
-    display_tree(session, &tree, compact, read_values, "").await?;
+    display_tree(session, &tree, args, client.is_verbose(), "").await?;
     
+    print_paging_summary(&args.paging);
     println!("\n✅ {}", "Browse completed successfully".green());
     Ok(())
 }
 
+/// The tree below the starting node down to `--depth`, pruned to `--classes` and `--filter`
+pub async fn browse_tree(session: &ServiceSession, args: &BrowseArgs, verbose: bool) -> Result<Vec<TreeNode>> {
+    let mut visited = HashSet::new();
+    let mut tree = build_tree_recursive(session, &args.start_node_id, 0, args, &mut visited, verbose).await?;
+    prune_tree(&mut tree, args);
+    Ok(tree)
+}
+
+/// With `--values`, check the reads of the variables in the tree with the guard before any is made
+fn confirm_value_reads(tree: &[TreeNode], args: &BrowseArgs) -> Result<()> {
+    fn variables(tree: &[TreeNode]) -> usize {
+        tree.iter()
+            .map(|node| (node.reference.node_class == NodeClass::Variable && !node.duplicate) as usize + variables(&node.children))
+            .sum()
+    }
+    if !args.read_values {
+        return Ok(());
+    }
+    guard::confirm(&[Operations::Reads(variables(tree))])
+}
+
+/// Tell how the server paged the references, and warn when it cut results
+fn print_paging_summary(paging: &Paging) {
+    let continuations = paging.continuations.load(AtomicOrdering::Relaxed);
+    let clamped = paging.clamped_pages.load(AtomicOrdering::Relaxed);
+    let truncated = paging.truncated.load(AtomicOrdering::Relaxed);
+    if continuations > 0 {
+        println!("\n📑 Continuation points followed: {}", continuations.to_string().bright_white());
+    }
+    if clamped > 0 {
+        println!("✂️  Pages the server made shorter than requested: {}", clamped.to_string().yellow());
+    }
+    if truncated > 0 {
+        println!("⚠️  {} node(s) returned cut results without a continuation point, they were browsed again in pages",
+                 truncated.to_string().yellow());
+        println!("   Exports and other browses of this server may miss references; set --max-refs-per-node for them");
+    }
+}
+
+/// Drop the nodes that neither match the filters nor have matching descendants
+fn prune_tree(tree: &mut Vec<TreeNode>, args: &BrowseArgs) {
+    if !args.is_filtered() {
+        return;
+    }
+    tree.retain_mut(|node| {
+        prune_tree(&mut node.children, args);
+        !node.children.is_empty() || args.matches(&node.reference)
+    });
+}
+
+fn describe_filter(args: &BrowseArgs) -> String {
+    let mut parts = Vec::new();
+    if !args.classes.is_empty() {
+        let classes: Vec<String> = args.classes.iter().map(|class| format!("{:?}", class)).collect();
+        parts.push(format!("classes {}", classes.join(", ")));
+    }
+    if let Some(filter) = &args.filter {
+        parts.push(format!("name contains '{}'", filter));
+    }
+    parts.join(", ")
+}
+
+/// Print the node IDs of the matching nodes in tree order, one per line
+fn display_ids(tree: &[TreeNode], args: &BrowseArgs) {
+    for node in tree {
+        if args.matches(&node.reference) {
+            println!("{}", format_node_id(&node.reference.node_id.node_id));
+        }
+        display_ids(&node.children, args);
+    }
+}
+
+fn compare_references(a: &ReferenceDescription, b: &ReferenceDescription, sort: BrowseSort) -> Ordering {
+    let by_name = || natural_cmp(&reference_name(a).0, &reference_name(b).0);
+    
+    match sort {
+        BrowseSort::Name => by_name(),
+        BrowseSort::Nodeid => natural_cmp(&format_node_id(&a.node_id.node_id), &format_node_id(&b.node_id.node_id)),
+        BrowseSort::Class => (a.node_class as u32).cmp(&(b.node_class as u32)).then_with(by_name),
+        BrowseSort::None => Ordering::Equal,
+    }
+}
+
+/// Browse depth-first and print each node right away in the format the arguments select.
+///
+/// Only the not yet printed siblings along the current path are held, the box-drawing prefixes
+/// follow from whether each ancestor level has siblings left. Returns the number of nodes printed.
+async fn stream_tree(session: &ServiceSession, args: &BrowseArgs, verbose: bool) -> Result<usize> {
+    let mut levels = vec![StreamLevel {
+        parent: args.start_node_id.clone(),
+        pending: browse_children(session, &args.start_node_id, args.sort, &args.paging, verbose).await,
+    }];
+    let mut printed = 0;
+    // With --show-duplicates, the nodes browsed so far with the parent they were expanded under
+    let mut expanded: HashMap<NodeId, NodeId> = HashMap::new();
+    
+    while let Some(level) = levels.last_mut() {
+        let Some(reference) = level.pending.pop_front() else {
+            levels.pop();
+            continue;
+        };
+        let is_last = level.pending.is_empty();
+        let parent = level.parent.clone();
+        let depth = levels.len() as u32 - 1;
+        let node_id = reference.node_id.node_id.clone();
+        
+        let duplicate_of = expanded.get(&node_id).cloned();
+        // A node already on the current path would repeat the path forever
+        let children = if duplicate_of.is_none() && descends(depth, args.max_depth) && !levels.iter().any(|level| level.parent == node_id) {
+            if args.show_duplicates {
+                expanded.insert(node_id.clone(), parent.clone());
+            }
+            browse_children(session, &node_id, args.sort, &args.paging, verbose).await
+        } else {
+            VecDeque::new()
+        };
+        
+        if args.matches(&reference) {
+            let mut prefix: String = levels
+                .iter()
+                .take(depth as usize)
+                .map(|level| if level.pending.is_empty() { "    " } else { "│   " })
+                .collect();
+            prefix.push_str(if is_last { "└── " } else { "├── " });
+            let entry = StreamedEntry { reference, parent: &parent, duplicate_of: duplicate_of.as_ref(), depth, prefix: &prefix };
+            print_streamed(session, entry, args, verbose).await?;
+            printed += 1;
+        }
+        
+        if !children.is_empty() {
+            levels.push(StreamLevel { parent: node_id, pending: children });
+        }
+    }
+    Ok(printed)
+}
+
+/// A node of a streamed browse with its place in the tree
+struct StreamedEntry<'a> {
+    reference: ReferenceDescription,
+    parent: &'a NodeId,
+    /// The parent the node was expanded under before, with `--show-duplicates`
+    duplicate_of: Option<&'a NodeId>,
+    depth: u32,
+    /// Box-drawing prefix of the tree format
+    prefix: &'a str,
+}
+
+async fn print_streamed(session: &ServiceSession, entry: StreamedEntry<'_>, args: &BrowseArgs, verbose: bool) -> Result<()> {
+    let StreamedEntry { reference, parent, duplicate_of, depth, prefix } = entry;
+    let node_id = &reference.node_id.node_id;
+    // Stubs of nodes shown before are not read again
+    let is_variable = args.read_values && reference.node_class == NodeClass::Variable && duplicate_of.is_none();
+    // Nothing tells up front how many values a stream reads, so they only count against the cap
+    if is_variable {
+        guard::spend(Operations::Reads(1))?;
+    }
+    
+    if args.ids_only {
+        println!("{}", format_node_id(node_id));
+    } else if args.json {
+        let node = StreamedNode {
+            node_id: format_node_id(node_id),
+            parent_id: format_node_id(parent),
+            depth,
+            browse_name: reference.browse_name.to_string(),
+            display_name: reference_name(&reference).0,
+            node_class: format!("{:?}", reference.node_class),
+            reference_type_id: format_node_id(&reference.reference_type_id),
+            reference_type: reference_type_name(session, &reference.reference_type_id, reference.is_forward).await,
+            type_definition: (!reference.type_definition.is_null()).then(|| format_node_id(&reference.type_definition.node_id)),
+            value: None,
+            value_formatted: None,
+            value_mapped: None,
+            severity: None,
+            kind: None,
+            duplicate_of: duplicate_of.map(format_node_id),
+        };
+        let node = if is_variable {
+            let (value, result) = read_plain_value(session, node_id, &args.failed_values).await;
+            let decimal = value.format_raw(Radix::Dec);
+            let formatted = Some(value.format_raw(radix())).filter(|formatted| *formatted != decimal);
+            StreamedNode {
+                value: Some(decimal),
+                value_formatted: formatted,
+                value_mapped: match value {
+                    PlainValue::Mapped(mapped, _) => Some(mapped),
+                    _ => None,
+                },
+                severity: Some(result.severity()),
+                kind: Some(result.kind()),
+                ..node
+            }
+        } else {
+            node
+        };
+        println!("{}", serde_json::to_string(&node)?);
+    } else if args.lines {
+        let value = if is_variable { read_plain_value(session, node_id, &args.failed_values).await.0.format(radix()) } else { String::new() };
+        println!("{}\t{}\t{}\t{}\t{}",
+            depth,
+            compact_node_class_code(reference.node_class),
+            sanitize_field(&reference_name(&reference).0),
+            sanitize_field(&format_node_id(node_id)),
+            sanitize_field(&value)
+        );
+    } else {
+        let node = TreeNode { reference, children: Vec::new(), value: None, duplicate: duplicate_of.is_some() };
+        display_node(session, &node, args, verbose, prefix).await?;
+    }
+    Ok(())
+}
+
 async fn display_tree(
-    session: &Arc<Session>,
+    session: &ServiceSession,
     tree: &[TreeNode],
-    compact: bool,
-    read_values: bool,
+    args: &BrowseArgs,
+    verbose: bool,
     prefix: &str,
 ) -> Result<()> {
     for (i, node) in tree.iter().enumerate() {
@@ -81,15 +479,15 @@ async fn display_tree(
         let child_prefix = if is_last { "    " } else { "│   " };
         
         // Display current node
-        display_node(session, node, compact, read_values, &format!("{}{}", prefix, current_prefix)).await?;
+        display_node(session, node, args, verbose, &format!("{}{}", prefix, current_prefix)).await?;
         
         // Display children recursively
         if !node.children.is_empty() {
             Box::pin(display_tree(
                 session,
                 &node.children,
-                compact,
-                read_values,
+                args,
+                verbose,
                 &format!("{}{}", prefix, child_prefix),
             )).await?;
         }
@@ -97,25 +495,177 @@ async fn display_tree(
     Ok(())
 }
 
+/// Print the tree as tab-separated `depth class name node_id value` lines for scripting
+async fn display_lines(
+    session: &ServiceSession,
+    tree: &[TreeNode],
+    args: &BrowseArgs,
+    depth: u32,
+) -> Result<()> {
+    for node in tree {
+        let ref_desc = &node.reference;
+        
+        let value = if args.read_values && ref_desc.node_class == NodeClass::Variable {
+            read_plain_value(session, &ref_desc.node_id.node_id, &args.failed_values).await.0.format(radix())
+        } else {
+            String::new()
+        };
+        
+        println!("{}\t{}\t{}\t{}\t{}",
+            depth,
+            compact_node_class_code(ref_desc.node_class),
+            sanitize_field(&reference_name(ref_desc).0),
+            sanitize_field(&format_node_id(&ref_desc.node_id.node_id)),
+            sanitize_field(&value)
+        );
+        
+        if !node.children.is_empty() {
+            Box::pin(display_lines(session, &node.children, args, depth + 1)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// A value read for plain output: text as it is printed, or a variant still to be formatted in
+/// the radix of the output
+enum PlainValue {
+    Text(String),
+    Variant(Variant),
+    /// The display string of `--value-map` for the variant
+    Mapped(&'static str, Variant),
+}
+
+impl PlainValue {
+    fn format(&self, radix: Radix) -> String {
+        match self {
+            PlainValue::Text(text) => text.clone(),
+            PlainValue::Variant(variant) => format_variant_in(variant, radix),
+            PlainValue::Mapped(mapped, _) => mapped.to_string(),
+        }
+    }
+
+    /// The value as read, also for mapped values
+    fn format_raw(&self, radix: Radix) -> String {
+        match self {
+            PlainValue::Mapped(_, variant) => format_variant_in(variant, radix),
+            _ => self.format(radix),
+        }
+    }
+}
+
+/// Read a value without truncation or decoration, reporting bad statuses by name, together with
+/// the result of the read. Service faults and client errors carry a prefix, bare status names
+/// are the node's own status.
+async fn read_plain_value(session: &ServiceSession, node_id: &NodeId, failed: &AtomicUsize) -> (PlainValue, OperationResult) {
+    match read_value(session, node_id, failed).await {
+        Ok((Some(data_value), enum_value, mapped)) => {
+            let result = OperationResult::of_data_value(&data_value);
+            let value = if let Some(status) = data_value.status.filter(|status| !status.is_good()) {
+                PlainValue::Text(format!("{}", status))
+            } else if let (Some(mapped), Some(variant)) = (mapped, &data_value.value) {
+                PlainValue::Mapped(mapped, variant.clone())
+            } else if let Some(enum_value) = enum_value {
+                PlainValue::Text(enum_value.format_plain())
+            } else {
+                match data_value.value {
+                    Some(variant) => PlainValue::Variant(variant),
+                    None => PlainValue::Text("null".to_string()),
+                }
+            };
+            (value, result)
+        }
+        Ok((None, _, _)) => (PlainValue::Text(String::new()), OperationResult::OperationBad(StatusCode::BadNoData)),
+        Err(status) => {
+            let result = OperationResult::of_service_error(status);
+            (PlainValue::Text(result.to_string()), result)
+        }
+    }
+}
+
+/// Read the Value of a variable together with its DataType, naming enumeration values and
+/// looking the value up in `--value-map`.
+///
+/// Values that cannot be read or have a failing status are counted in `failed`.
+async fn read_value(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    failed: &AtomicUsize,
+) -> Result<(Option<DataValue>, Option<EnumValue>, Option<&'static str>), StatusCode> {
+    let requests = [ReadValueId::from(node_id), attribute_request(node_id, AttributeId::DataType)];
+    let mut data_values = match session.read_values(&requests).await {
+        Ok(data_values) => data_values.into_iter(),
+        Err(status) => {
+            failed.fetch_add(1, AtomicOrdering::Relaxed);
+            return Err(status);
+        }
+    };
+    let value = data_values.next();
+    if value.as_ref().is_none_or(|dv| dv.status.is_some_and(|status| Severity::of(&status).is_failure())) {
+        failed.fetch_add(1, AtomicOrdering::Relaxed);
+    }
+    let data_type = match data_values.next().and_then(|dv| dv.value) {
+        Some(Variant::NodeId(data_type)) => Some(*data_type),
+        _ => None,
+    };
+    let variant = value.as_ref().and_then(|dv| dv.value.as_ref());
+    let enum_value = match (variant, &data_type) {
+        (Some(variant), Some(data_type)) => enum_value(session, data_type, variant).await,
+        _ => None,
+    };
+    let mapped = variant.and_then(|variant| {
+        let data_type = data_type.as_ref().map(format_data_type).unwrap_or_default();
+        mapped_value(node_id, &data_type, variant)
+    });
+    Ok((value, enum_value, mapped))
+}
+
+/// Keep a field on one line so the tab-separated columns stay intact
+fn sanitize_field(value: &str) -> String {
+    sanitize_text(value).replace(['\t', '\n', '\r'], " ")
+}
+
 async fn display_node(
-    session: &Arc<Session>,
+    session: &ServiceSession,
     node: &TreeNode,
-    compact: bool,
-    read_values: bool,
+    args: &BrowseArgs,
+    verbose: bool,
     prefix: &str,
 ) -> Result<()> {
+    let (compact, read_values) = (args.compact, args.read_values);
     let ref_desc = &node.reference;
     let node_id_str = format_node_id(&ref_desc.node_id.node_id);
-    let display_name = &ref_desc.display_name.to_string();
+    let (name, fallback) = reference_name(ref_desc);
+    let display_name = &label_for(&ref_desc.node_id.node_id).map(String::from).unwrap_or_else(|| sanitize_text(&name).into_owned());
+    let missing_marker = if fallback && verbose {
+        format!(" {}", "⟨no display name⟩".dimmed())
+    } else {
+        String::new()
+    };
+    let marker = if args.ref_markers && !compact {
+        format!("{} ", reference_marker(&ref_desc.reference_type_id).dimmed())
+    } else {
+        String::new()
+    };
+    
+    if node.duplicate && args.show_duplicates {
+        let class = if compact { format!("{}  ", format_compact_node_class(ref_desc.node_class)) } else { String::new() };
+        println!("{}{}{}{}{} [{}] {}",
+            prefix,
+            marker,
+            class,
+            display_name.bright_white(),
+            missing_marker,
+            node_id_str.dimmed(),
+            "(↺ shown above)".dimmed()
+        );
+        return Ok(());
+    }
     
     let value_str = if read_values && ref_desc.node_class == NodeClass::Variable {
         if let Some(cached_value) = &node.value {
             format!(" = {}", cached_value)
         } else {
-            match read_node_value(session, &ref_desc.node_id.node_id).await {
-                Ok(value) => format!(" = {}", value),
-                Err(e) => format!(" = {}", format!("Error: {}", e).red()),
-            }
+            format!(" = {}", read_node_value(session, &ref_desc.node_id.node_id, &args.failed_values).await)
         }
     } else {
         String::new()
@@ -123,10 +673,11 @@ async fn display_node(
     
     if compact {
         // Compact format: prefix + class + name [node_id] = value
-        println!("{}{}  {} [{}]{}",
+        println!("{}{}  {}{} [{}]{}",
             prefix,
             format_compact_node_class(ref_desc.node_class),
             display_name.bright_white(),
+            missing_marker,
             node_id_str.dimmed(),
             value_str
         );
@@ -138,9 +689,11 @@ async fn display_node(
             String::new()
         };
         
-        println!("{}{} ({}) [{}]{}{}",
+        println!("{}{}{}{} ({}) [{}]{}{}",
             prefix,
+            marker,
             display_name.bright_white(),
+            missing_marker,
             format_node_class(ref_desc.node_class),
             node_id_str.dimmed(),
             type_def.cyan(),
@@ -151,15 +704,30 @@ async fn display_node(
     Ok(())
 }
 
+/// Markers of the full tree format for the reference types linking a child to its parent
+const REFERENCE_MARKERS: [(ReferenceTypeId, &str); 3] = [
+    (ReferenceTypeId::Organizes, "·"),
+    (ReferenceTypeId::HasComponent, "◦"),
+    (ReferenceTypeId::HasProperty, "ᵖ"),
+];
+
+/// The marker of a reference type, a blank for the other hierarchical references
+fn reference_marker(reference_type: &NodeId) -> &'static str {
+    REFERENCE_MARKERS
+        .iter()
+        .find(|(known, _)| reference_type.namespace == 0 && reference_type.identifier == Identifier::Numeric(*known as u32))
+        .map_or(" ", |(_, marker)| marker)
+}
+
 async fn build_tree_recursive(
-    session: &Arc<Session>,
+    session: &ServiceSession,
     node_id: &NodeId,
     current_depth: u32,
-    max_depth: u32,
+    args: &BrowseArgs,
     visited: &mut HashSet<NodeId>,
     verbose: bool,
 ) -> Result<Vec<TreeNode>> {
-    if current_depth > max_depth || visited.contains(node_id) {
+    if visited.contains(node_id) {
         return Ok(Vec::new());
     }
     
@@ -169,120 +737,186 @@ async fn build_tree_recursive(
         debug!("Building tree for node: {} (depth: {})", format_node_id(node_id), current_depth);
     }
     
-    // Browse the current node
-    let browse_request = BrowseDescription {
-        node_id: node_id.clone(),
-        browse_direction: BrowseDirection::Forward,
-        reference_type_id: ReferenceTypeId::HierarchicalReferences.into(),
-        include_subtypes: true,
-        node_class_mask: 0u32, // All node classes
-        result_mask: BrowseResultMask::All as u32,
-    };
-    
     let mut tree_nodes = Vec::new();
     
-    match session.browse(&[browse_request], 0, None).await {
-        Ok(browse_results) => {
-            if let Some(browse_result) = browse_results.first() {
-                if browse_result.status_code.is_good() {
-                    if let Some(references) = &browse_result.references {
-                        for reference in references {
-                            let children = if current_depth < max_depth {
-                                Box::pin(build_tree_recursive(
-                                    session,
-                                    &reference.node_id.node_id,
-                                    current_depth + 1,
-                                    max_depth,
-                                    visited,
-                                    verbose,
-                                )).await.unwrap_or_else(|e| {
-                                    if verbose {
-                                        warn!("Failed to build tree for child {}: {}", 
-                                              format_node_id(&reference.node_id.node_id), e);
-                                    }
-                                    Vec::new()
-                                })
-                            } else {
-                                Vec::new()
-                            };
-                            
-                            tree_nodes.push(TreeNode {
-                                reference: reference.clone(),
-                                children,
-                                value: None, // Will be populated when needed
-                            });
-                        }
-                    }
-                } else if verbose {
-                    warn!("Browse failed for node {}: {}", 
-                          format_node_id(node_id), browse_result.status_code);
+    // Children are expanded in the order they are shown, so the first node a shared child shows
+    // under is the one holding its children
+    for reference in browse_children(session, node_id, args.sort, &args.paging, verbose).await {
+        let duplicate = visited.contains(&reference.node_id.node_id);
+        let children = if descends(current_depth, args.max_depth) {
+            Box::pin(build_tree_recursive(
+                session,
+                &reference.node_id.node_id,
+                current_depth + 1,
+                args,
+                visited,
+                verbose,
+            )).await.unwrap_or_else(|e| {
+                if verbose {
+                    warn!("Failed to build tree for child {}: {}", 
+                          format_node_id(&reference.node_id.node_id), e);
                 }
-            }
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        
+        tree_nodes.push(TreeNode {
+            reference,
+            children,
+            value: None, // Will be populated when needed
+            duplicate,
+        });
+    }
+    
+    Ok(tree_nodes)
+}
+
+/// The hierarchical children of a node in `sort` order, empty when the browse fails
+async fn browse_children(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    sort: BrowseSort,
+    paging: &Paging,
+    verbose: bool,
+) -> VecDeque<ReferenceDescription> {
+    let mut references = match browse_paged(session, node_id, paging.max_refs_per_node, paging).await {
+        Ok((references, false)) if references.len() >= TRUNCATION_CHECK_MIN => {
+            check_truncation(session, node_id, references, paging).await
         }
-        Err(e) if verbose => {
-            warn!("Browse error for node {}: {}", format_node_id(node_id), e);
+        Ok((references, _)) => references,
+        Err(e) => {
+            if verbose {
+                warn!("Browse failed for node {}: {}", format_node_id(node_id), e);
+            }
+            Vec::new()
         }
-        _ => {}
+    };
+    if sort != BrowseSort::None {
+        references.sort_by(|a, b| compare_references(a, b, sort));
     }
+    references.into()
+}
+
+/// Browse the hierarchical references of a node, following continuation points with BrowseNext.
+/// Also tells whether the server handed out a continuation point.
+async fn browse_paged(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    max_refs_per_node: u32,
+    paging: &Paging,
+) -> Result<(Vec<ReferenceDescription>, bool)> {
+    let browse_request = BrowseRequest::new(node_id, BrowseDirection::Forward, ReferenceTypeId::HierarchicalReferences).build();
     
-    Ok(tree_nodes)
+    let mut results = session.browse(&[browse_request], max_refs_per_node, None).await?;
+    let mut references = Vec::new();
+    let mut continued = false;
+    loop {
+        let Some(mut result) = results.into_iter().next() else {
+            return Err(anyhow!("The server returned no browse result"));
+        };
+        if !result.status_code.is_good() {
+            return Err(anyhow!("{}", result.status_code));
+        }
+        let page = result.references.take().unwrap_or_default();
+        if result.continuation_point.is_null_or_empty() {
+            references.extend(page);
+            return Ok((references, continued));
+        }
+        
+        // Without a requested size any continuation point means the server chose the page size
+        if max_refs_per_node == 0 || page.len() < max_refs_per_node as usize {
+            paging.clamped_pages.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+        references.extend(page);
+        continued = true;
+        paging.continuations.fetch_add(1, AtomicOrdering::Relaxed);
+        results = session.browse_next(false, &[result.continuation_point]).await?;
+    }
+}
+
+/// Browse a long result that came without a continuation point again in pages of half its
+/// size. More references that way mean the server cut the first result, the paged ones are used.
+async fn check_truncation(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    references: Vec<ReferenceDescription>,
+    paging: &Paging,
+) -> Vec<ReferenceDescription> {
+    let page_size = (references.len() / 2) as u32;
+    // The pages of the check are no paging of the server's own
+    match browse_paged(session, node_id, page_size, &Paging::default()).await {
+        Ok((paged, _)) if paged.len() > references.len() => {
+            paging.truncated.fetch_add(1, AtomicOrdering::Relaxed);
+            warn!("{} returned {} references without a continuation point but has {}; the server cuts browse results, the tree may be incomplete elsewhere",
+                  format_node_id(node_id), references.len(), paged.len());
+            paged
+        }
+        Ok(_) => references,
+        Err(e) => {
+            debug!("Browsing {} in pages of {} failed: {}", format_node_id(node_id), page_size, e);
+            references
+        }
+    }
 }
 
-async fn read_node_value(session: &Arc<Session>, node_id: &NodeId) -> Result<String> {
-    match session.read(&[ReadValueId::from(node_id)], TimestampsToReturn::Both, 0.0).await {
-        Ok(data_values) => {
-            if let Some(data_value) = data_values.first() {
+/// The value shown in the tree, or why there is none
+async fn read_node_value(session: &ServiceSession, node_id: &NodeId, failed: &AtomicUsize) -> String {
+    match read_value(session, node_id, failed).await {
+        Ok((data_value, enum_value, mapped)) => {
+            if let Some(data_value) = data_value {
                 // Handle status - None means Good status (OPC-UA uses encoding masks to omit default values)
-                let status = data_value.status.as_ref()
-                    .map(|s| s.clone())
-                    .unwrap_or_else(|| StatusCode::Good);
+                let result = OperationResult::of_data_value(&data_value);
                 
-                if status.is_good() {
+                if result.is_good() {
                     // Status is good, check for value
-                    if let Some(value) = &data_value.value {
-                        Ok(truncate_string(&format_variant(value), 20))
+                    if let Some(mapped) = mapped {
+                        truncate_string(mapped, 20)
+                    } else if let Some(enum_value) = enum_value {
+                        enum_value.format()
+                    } else if let Some(value) = &data_value.value {
+                        truncate_string(&format_value_variant(value), 20)
                     } else {
-                        Ok("null".dimmed().to_string())
+                        "null".dimmed().to_string()
                     }
                 } else {
                     // Status indicates an error or uncertain state
-                    Ok(format!("{}", format_status_code(&status)))
+                    result.describe()
                 }
             } else {
-                Ok("No data".dimmed().to_string())
+                "No data".dimmed().to_string()
             }
         }
-        Err(e) => Err(anyhow::anyhow!("Read error: {}", e)),
+        Err(status) => OperationResult::of_service_error(status).describe(),
     }
 }
 
-fn format_compact_node_class(node_class: NodeClass) -> String {
+pub fn format_compact_node_class(node_class: NodeClass) -> String {
+    let code = compact_node_class_code(node_class);
     match node_class {
-        NodeClass::Object => "Obj".blue().to_string(),
-        NodeClass::Variable => "Var".green().to_string(),
-        NodeClass::Method => "Met".yellow().to_string(),
-        NodeClass::ObjectType => "OTyp".cyan().to_string(),
-        NodeClass::VariableType => "VTyp".magenta().to_string(),
-        NodeClass::ReferenceType => "Ref".white().to_string(),
-        NodeClass::DataType => "Data".bright_white().to_string(),
-        NodeClass::View => "View".bright_blue().to_string(),
-        _ => "?".dimmed().to_string(),
+        NodeClass::Object => code.blue().to_string(),
+        NodeClass::Variable => code.green().to_string(),
+        NodeClass::Method => code.yellow().to_string(),
+        NodeClass::ObjectType => code.cyan().to_string(),
+        NodeClass::VariableType => code.magenta().to_string(),
+        NodeClass::ReferenceType => code.white().to_string(),
+        NodeClass::DataType => code.bright_white().to_string(),
+        NodeClass::View => code.bright_blue().to_string(),
+        _ => code.dimmed().to_string(),
     }
 }
 
-fn parse_node_id(node_str: &str) -> Result<NodeId> {
-    // Try to parse as standard node ID format (ns=X;i=Y, ns=X;s=Y, etc.)
-    if let Ok(node_id) = NodeId::from_str(node_str) {
-        return Ok(node_id);
+pub fn compact_node_class_code(node_class: NodeClass) -> &'static str {
+    match node_class {
+        NodeClass::Object => "Obj",
+        NodeClass::Variable => "Var",
+        NodeClass::Method => "Met",
+        NodeClass::ObjectType => "OTyp",
+        NodeClass::VariableType => "VTyp",
+        NodeClass::ReferenceType => "Ref",
+        NodeClass::DataType => "Data",
+        NodeClass::View => "View",
+        _ => "?",
     }
-    
-    // Try common object IDs
-    match node_str.to_lowercase().as_str() {
-        "objects" | "objectsfolder" => Ok(ObjectId::ObjectsFolder.into()),
-        "server" => Ok(ObjectId::Server.into()),
-        "types" | "typesfolder" => Ok(ObjectId::TypesFolder.into()),
-        "views" | "viewsfolder" => Ok(ObjectId::ViewsFolder.into()),
-        "root" => Ok(ObjectId::RootFolder.into()),
-        _ => Err(anyhow!("Invalid node ID format: {}", node_str)),
-    }
-}
\ No newline at end of file
+}
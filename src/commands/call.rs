@@ -1,61 +1,214 @@
 use anyhow::{anyhow, Result};
 use colored::*;
-use opcua::client::Session;
 use opcua::types::*;
+use serde::Serialize;
 use serde_json::Value as JsonValue;
-use std::str::FromStr;
-use std::sync::Arc;
-use tracing::info;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{Duration, Instant};
+use tabled::{Table, Tabled};
+use tracing::{debug, info};
 
 use crate::client::OpcUaClient;
-use crate::utils::formatter::{format_node_id, format_variant};
-use crate::utils::search::find_method_with_parent;
-
-pub async fn execute(
-    client: &mut OpcUaClient,
-    method_id: &str,
-    object_id: Option<&str>,
-    args: Option<&str>,
-    verbose: bool,
-) -> Result<()> {
-    let session = client.session()?;
-    
-    println!("\n{}", "⚙️ OPC-UA Method Call".bright_cyan().bold());
-    println!("{}", "─".repeat(40));
+use crate::commands::read::search_roots;
+use crate::commands::refs::browse_references;
+use crate::session::ServiceSession;
+use crate::types::CallCommand;
+use crate::utils::browse_request::BrowseRequest;
+use crate::utils::formatter::{format_data_type, format_diagnostic_info, format_node_id, format_variant, truncate_string, variant_to_json, variant_type_name, Severity};
+use crate::utils::guard::{self, Operations};
+use crate::utils::namespace::{parse_node_id, parse_node_spec, resolve_node_namespace, NodeSpec};
+use crate::utils::outcome::OperationResult;
+use crate::utils::search::{find_instances_of_type, find_method_with_parent, format_search_root, SearchConfig, SearchResult};
+use crate::utils::security::ConnectionInfo;
+use crate::utils::sort::natural_cmp;
+
+/// Methods sent in a single Call request when calling on all instances of a type
+const CALL_BATCH_SIZE: usize = 50;
+
+#[derive(Tabled)]
+struct PlannedCallRow {
+    #[tabled(rename = "Object")]
+    object: String,
+    #[tabled(rename = "Display Name")]
+    display_name: String,
+    #[tabled(rename = "Method")]
+    method: String,
+}
+
+/// Status the server gave one input argument of a failed call
+#[derive(Tabled)]
+struct InputResultRow {
+    #[tabled(rename = "#")]
+    index: String,
+    #[tabled(rename = "Argument")]
+    name: String,
+    #[tabled(rename = "Expected")]
+    expected: String,
+    #[tabled(rename = "Supplied")]
+    supplied: String,
+    #[tabled(rename = "Status")]
+    status: String,
+}
+
+#[derive(Tabled)]
+struct CallResultRow {
+    #[tabled(rename = "Object")]
+    object: String,
+    #[tabled(rename = "Display Name")]
+    display_name: String,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+/// A single method call as printed by `--json` and appended to `--output`
+#[derive(Serialize)]
+struct CallRecord {
+    timestamp: String,
+    method_id: String,
+    object_id: String,
+    input_arguments: Vec<ArgumentValue>,
+    status: StatusJson,
+    /// Empty unless the server reports per-argument results
+    input_argument_results: Vec<StatusJson>,
+    output_arguments: Vec<ArgumentValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<ConnectionInfo>,
+}
+
+#[derive(Serialize)]
+struct ArgumentValue {
+    /// Name from the method's InputArguments or OutputArguments property when readable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    data_type: String,
+    value: JsonValue,
+}
+
+#[derive(Serialize)]
+struct StatusJson {
+    name: String,
+    code: u32,
+    severity: Severity,
+    /// `operation_bad` for the method's own status, `service_fault` or `client_error` when the call failed as a whole
+    kind: &'static str,
+}
+
+impl StatusJson {
+    fn new(status: StatusCode, result: &OperationResult) -> Self {
+        StatusJson { name: status.to_string(), code: status.bits(), severity: result.severity(), kind: result.kind() }
+    }
+}
+
+impl From<StatusCode> for StatusJson {
+    fn from(status: StatusCode) -> Self {
+        StatusJson::new(status, &OperationResult::of_status(status))
+    }
+}
+
+pub enum CallTarget {
+    /// Both method and object node IDs were given
+    Exact { method_id: NodeId, object_id: NodeId },
+    /// Method node ID given, parent object is looked up on the server
+    Method(NodeId),
+    /// Method name given, method and object are searched on the server
+    Name(String),
+    /// Method BrowseName called on every instance of an ObjectType
+    OnType {
+        type_id: NodeId,
+        method_name: String,
+        filter: Option<String>,
+    },
+}
+
+pub struct CallArgs {
+    pub target: CallTarget,
+    pub input_arguments: Vec<Variant>,
+    pub verbose: bool,
+    pub timeout: Option<Duration>,
+    pub dry_run: bool,
+    /// Print the call as JSON instead of the human-readable report
+    pub json: bool,
+    /// File each call is appended to as one JSON line
+    pub output: Option<String>,
+    /// Where a method given by name is searched, the default roots when empty
+    pub search_roots: Vec<NodeSpec>,
+}
+
+impl CallArgs {
+    fn records(&self) -> bool {
+        self.json || self.output.is_some()
+    }
+}
+
+pub fn validate(args: &CallCommand) -> Result<CallArgs> {
+    if args.call_timeout.is_some_and(|timeout| timeout.is_zero()) {
+        return Err(anyhow!("--call-timeout must be greater than 0"));
+    }
     
+
     // Parse method and object node IDs
-    let (method_node_id, object_node_id) = if let Some(obj_id) = object_id {
-        // Both method and object IDs provided
-        let method_id = parse_node_id(method_id)?;
-        let object_id = parse_node_id(obj_id)?;
-        (method_id, object_id)
+    let method_id = &args.method_id;
+    let target = if let Some(type_id) = &args.on_type {
+        CallTarget::OnType {
+            type_id: parse_node_id(type_id)?,
+            method_name: method_id.to_string(),
+            filter: args.filter.as_deref().map(str::to_lowercase),
+        }
+    } else if let Some(obj_id) = &args.object_id {
+        CallTarget::Exact {
+            method_id: parse_node_id(method_id)?,
+            object_id: parse_node_id(obj_id)?,
+        }
     } else if let Ok(method_node_id) = parse_node_id(method_id) {
-        // Method ID provided as node ID format, need to find parent object
-        info!("🔍 Finding parent object for method: {}", format_node_id(&method_node_id));
-        let parent_object_id = find_parent_object(session, &method_node_id).await?;
-        (method_node_id, parent_object_id)
+        CallTarget::Method(method_node_id)
     } else {
-        // Method name provided, need to search for both method and object
-        info!("🔍 Searching for method: '{}'", method_id);
-        
-        if let Some((method_node_id, object_node_id)) = 
-            find_method_with_parent(session, method_id, verbose).await? {
-            info!("✅ Found method: {} on object: {}", 
-                 format_node_id(&method_node_id).bright_green(),
-                 format_node_id(&object_node_id).bright_cyan());
-            (method_node_id, object_node_id)
-        } else {
-            return Err(anyhow!("Method '{}' not found", method_id));
-        }
+        CallTarget::Name(method_id.to_string())
     };
     
     // Parse input arguments
-    let input_arguments = if let Some(args_str) = args {
+    let input_arguments = if let Some(args_str) = &args.args {
         parse_arguments(args_str)?
     } else {
         Vec::new()
     };
     
+    Ok(CallArgs {
+        target,
+        input_arguments,
+        verbose: args.verbose,
+        timeout: args.call_timeout,
+        dry_run: args.dry_run,
+        json: args.json,
+        output: args.output.clone(),
+        search_roots: args.search_roots.iter().map(|root| parse_node_spec(root)).collect::<Result<_>>()?,
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &CallArgs) -> Result<()> {
+    let session = client.session()?;
+    let verbose = args.verbose;
+    
+    if !args.json {
+        println!("\n{}", "⚙️ OPC-UA Method Call".bright_cyan().bold());
+        println!("{}", "─".repeat(40));
+    }
+    
+    if let CallTarget::OnType { type_id, method_name, filter } = &args.target {
+        // Without the namespace array there are just no other namespaces to suggest
+        let namespaces = client.namespaces().await.unwrap_or_default();
+        let type_id = resolve_node_namespace(session, type_id, namespaces, client.auto_namespace()).await?;
+        return execute_on_type(session, args, &type_id, method_name, filter.as_deref()).await;
+    }
+    
+    let (method_node_id, object_node_id) = resolve_target(client, args).await?;
+    
+    let input_arguments = &args.input_arguments;
+    
+    if args.json {
+        return execute_recorded(session, client.connection_info(), args, &method_node_id, &object_node_id).await;
+    }
+    
     // Display call information
     println!("📋 {}", "Method Call Details".bright_white().bold());
     println!("   🎯 Method: {}", format_node_id(&method_node_id).bright_cyan());
@@ -91,22 +244,52 @@ pub async fn execute(
     // Execute the method call
     println!("\n⚡ Executing method call...");
     
-    let call_request = CallMethodRequest {
-        object_id: object_node_id.clone(),
-        method_id: method_node_id.clone(),
-        input_arguments: Some(input_arguments),
+    let started = Instant::now();
+    let response = tokio::select! {
+        response = call_method(session, &method_node_id, &object_node_id, args) => response,
+        _ = tokio::signal::ctrl_c() => {
+            println!("\n🛑 {} after {:.1}s", "Method call cancelled".bright_yellow().bold(),
+                     started.elapsed().as_secs_f64());
+            println!("   ⚠️  The request was already sent and may still be executing on the server");
+            return Err(anyhow!("Method call cancelled"));
+        }
     };
     
-    match session.call(vec![call_request]).await {
-        Ok(call_results) => {
-            if let Some(result) = call_results.first() {
-                display_call_result(result, verbose);
+    if args.output.is_some() {
+        let result = response.as_ref().ok().and_then(|response| response.results.as_ref()).and_then(|results| results.first());
+        record_call(session, client.connection_info(), args, &method_node_id, &object_node_id, &response, result).await?;
+    }
+    
+    match response {
+        Ok(response) => {
+            let string_table = response.response_header.string_table.as_deref().unwrap_or_default();
+            let diagnostics = response.diagnostic_infos.as_ref().and_then(|infos| infos.first());
+            
+            if let Some(result) = response.results.as_ref().and_then(|results| results.first()) {
+                let input_definitions = if shows_input_results(result) {
+                    method_arguments(session, &method_node_id, "InputArguments").await
+                } else {
+                    Vec::new()
+                };
+                let inputs = CallInputs { values: input_arguments, definitions: &input_definitions };
+                display_call_result(result, &inputs, diagnostics, string_table, verbose);
+                if let Some(output) = &args.output {
+                    println!("\n📝 Call recorded in {}", output.bright_white());
+                }
             } else {
                 println!("❌ No result returned from method call");
             }
         }
+        Err(StatusCode::BadTimeout) if args.timeout.is_some() => {
+            println!("⏱️  {} after {:.1}s", "Method call timed out".red().bold(),
+                     started.elapsed().as_secs_f64());
+            println!("   ⚠️  The request may still be executing on the server");
+            println!("   • Check the server state before calling the method again");
+            
+            return Err(anyhow::Error::new(StatusCode::BadTimeout).context("Method call timed out"));
+        }
         Err(e) => {
-            println!("❌ {}: {}", "Method call failed".red().bold(), e);
+            println!("❌ {}: {}", "Method call failed".red().bold(), OperationResult::of_service_error(e).describe());
             
             // Provide troubleshooting suggestions
             println!("\n💡 {}", "Troubleshooting suggestions:".bright_yellow().bold());
@@ -117,14 +300,409 @@ pub async fn execute(
             println!("     {}", format!("opcua-walker browse --node {}", 
                                       format_node_id(&object_node_id)).dimmed());
             
-            return Err(anyhow!("Method call failed: {}", e));
+            return Err(anyhow::Error::new(e).context("Method call failed"));
+        }
+    }
+    
+    Ok(())
+}
+
+/// The method and the object it is called on, looked up on the server unless both IDs were given
+pub async fn resolve_target(client: &OpcUaClient, args: &CallArgs) -> Result<(NodeId, NodeId)> {
+    let session = client.session()?;
+    let verbose = args.verbose;
+    // Without the namespace array there are just no other namespaces to suggest
+    let namespaces = client.namespaces().await.unwrap_or_default();
+    
+    Ok(match &args.target {
+        CallTarget::Exact { method_id, object_id } => (
+            resolve_node_namespace(session, method_id, namespaces, client.auto_namespace()).await?,
+            resolve_node_namespace(session, object_id, namespaces, client.auto_namespace()).await?,
+        ),
+        CallTarget::Method(method_node_id) => {
+            let method_node_id = resolve_node_namespace(session, method_node_id, namespaces, client.auto_namespace()).await?;
+            
+            // Method ID provided as node ID format, need to find parent object
+            info!("🔍 Finding parent object for method: {}", format_node_id(&method_node_id));
+            let parent_object_id = find_parent_object(session, &method_node_id).await?;
+            (method_node_id, parent_object_id)
+        }
+        CallTarget::OnType { .. } => return Err(anyhow!("A call on every instance of a type has no single method and object")),
+        CallTarget::Name(method_name) => {
+            // Method name provided, need to search for both method and object
+            info!("🔍 Searching for method: '{}'", method_name);
+            
+            let search_roots = search_roots(client, &args.search_roots).await?;
+            let Some(found) = find_method_with_parent(session, method_name, search_roots, verbose).await? else {
+                return Err(anyhow!("Method '{}' not found", method_name));
+            };
+            let object_node_id = found.parent_node_id.expect("methods are only found with their parent");
+            info!("✅ Found method: {} on object: {}", 
+                 format_node_id(&found.node_id).bright_green(),
+                 format_node_id(&object_node_id).bright_cyan());
+            if verbose && let Some(root) = &found.root {
+                info!("   Found under {}", format_search_root(root));
+            }
+            (found.node_id, object_node_id)
+        }
+    })
+}
+
+/// Send the Call request for the method with operation-level diagnostics
+pub async fn call_method(session: &ServiceSession, method_id: &NodeId, object_id: &NodeId, args: &CallArgs) -> Result<CallResponse, StatusCode> {
+    let call_request = CallMethodRequest {
+        object_id: object_id.clone(),
+        method_id: method_id.clone(),
+        input_arguments: Some(args.input_arguments.clone()),
+    };
+    session.call(vec![call_request], call_diagnostics(), args.timeout).await
+}
+
+/// Call the method without the human-readable report and print the call as JSON
+async fn execute_recorded(session: &ServiceSession, security: Option<&ConnectionInfo>, args: &CallArgs, method_id: &NodeId, object_id: &NodeId) -> Result<()> {
+    let response = tokio::select! {
+        response = call_method(session, method_id, object_id, args) => response,
+        _ = tokio::signal::ctrl_c() => return Err(anyhow!("Method call cancelled")),
+    };
+    let result = response.as_ref().ok().and_then(|response| response.results.as_ref()).and_then(|results| results.first());
+    record_call(session, security, args, method_id, object_id, &response, result).await?;
+    
+    match (&response, result) {
+        (Err(e), _) => Err(anyhow::Error::new(*e).context("Method call failed")),
+        (Ok(_), None) => Err(anyhow!("No result returned from method call")),
+        (Ok(_), Some(_)) => Ok(()),
+    }
+}
+
+/// Print the call as JSON with `--json` and append it to the `--output` file
+async fn record_call(
+    session: &ServiceSession,
+    security: Option<&ConnectionInfo>,
+    args: &CallArgs,
+    method_id: &NodeId,
+    object_id: &NodeId,
+    response: &Result<CallResponse, StatusCode>,
+    result: Option<&CallMethodResult>,
+) -> Result<()> {
+    if !args.records() {
+        return Ok(());
+    }
+    
+    let (status, call_result) = match (response, result) {
+        (Err(e), _) => (*e, OperationResult::of_service_error(*e)),
+        (Ok(_), Some(result)) => (result.status_code, OperationResult::of_status(result.status_code)),
+        (Ok(_), None) => (StatusCode::BadNoData, OperationResult::OperationBad(StatusCode::BadNoData)),
+    };
+    let outputs = result.and_then(|result| result.output_arguments.as_deref()).unwrap_or_default();
+    let input_names = method_arguments(session, method_id, "InputArguments").await;
+    let output_names = if outputs.is_empty() {
+        Vec::new()
+    } else {
+        method_arguments(session, method_id, "OutputArguments").await
+    };
+    
+    let record = CallRecord {
+        timestamp: DateTime::now().as_chrono().to_rfc3339(),
+        method_id: format_node_id(method_id),
+        object_id: format_node_id(object_id),
+        input_arguments: argument_values(&args.input_arguments, &input_names),
+        status: StatusJson::new(status, &call_result),
+        input_argument_results: result
+            .and_then(|result| result.input_argument_results.as_deref())
+            .unwrap_or_default()
+            .iter()
+            .map(|status| StatusJson::from(*status))
+            .collect(),
+        output_arguments: argument_values(outputs, &output_names),
+        security: security.cloned(),
+    };
+    
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&record)?);
+    }
+    if let Some(output) = &args.output {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(output)
+            .map_err(|e| anyhow!("Failed to open {}: {}", output, e))?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)
+            .map_err(|e| anyhow!("Failed to write {}: {}", output, e))?;
+    }
+    Ok(())
+}
+
+fn argument_values(values: &[Variant], arguments: &[Argument]) -> Vec<ArgumentValue> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| ArgumentValue {
+            name: arguments.get(idx).map(|argument| argument.name.to_string()),
+            data_type: variant_type_name(value),
+            value: variant_to_json(value),
+        })
+        .collect()
+}
+
+/// The Arguments of the method's InputArguments or OutputArguments property, empty when the
+/// property is missing or unreadable
+async fn method_arguments(session: &ServiceSession, method_id: &NodeId, property: &str) -> Vec<Argument> {
+    let properties = match browse_references(session, method_id, BrowseDirection::Forward, ReferenceTypeId::HasProperty).await {
+        Ok(properties) => properties,
+        Err(e) => {
+            debug!("Browsing the properties of {} failed: {}", format_node_id(method_id), e);
+            return Vec::new();
+        }
+    };
+    let Some(property) = properties.iter().find(|r| r.browse_name.name.as_ref() == property) else {
+        return Vec::new();
+    };
+    
+    match session.read(&[ReadValueId::from(&property.node_id.node_id)], TimestampsToReturn::Neither, 0.0).await {
+        Ok(values) => match values.into_iter().next().and_then(|dv| dv.value) {
+            Some(Variant::Array(array)) => array.values
+                .iter()
+                .filter_map(|value| match value {
+                    Variant::ExtensionObject(object) => object.inner_as::<Argument>().cloned(),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        },
+        Err(e) => {
+            debug!("Reading {} of {} failed: {}", property.browse_name.name, format_node_id(method_id), e);
+            Vec::new()
+        }
+    }
+}
+
+fn has_bad_input(result: &CallMethodResult) -> bool {
+    result.input_argument_results.as_deref().unwrap_or_default().iter().any(|status| !status.is_good())
+}
+
+/// Whether a failed call gets the per-argument table: the server rejected an argument, or it
+/// blamed the arguments without saying which one
+fn shows_input_results(result: &CallMethodResult) -> bool {
+    has_bad_input(result)
+        || matches!(
+            result.status_code.sub_code(),
+            SubStatusCode::BadInvalidArgument
+                | SubStatusCode::BadTypeMismatch
+                | SubStatusCode::BadArgumentsMissing
+                | SubStatusCode::BadTooManyArguments
+        )
+}
+
+/// Whether a value has another built-in type than the argument is declared with; abstract and
+/// structured DataTypes are not compared
+fn type_differs(argument: &Argument, value: &Variant) -> bool {
+    let supplied = match value {
+        Variant::Array(array) => Some(array.value_type),
+        other => other.scalar_type_id(),
+    };
+    match (&argument.data_type.identifier, supplied) {
+        (Identifier::Numeric(expected), Some(supplied)) if argument.data_type.namespace == 0 && (1..=25).contains(expected) => {
+            supplied as u32 != *expected
         }
+        _ => false,
+    }
+}
+
+/// Ask for operation-level diagnostics so vendor error texts are not dropped
+fn call_diagnostics() -> DiagnosticBits {
+    DiagnosticBits::OPERATIONAL_LEVEL_SYMBOLIC_ID
+        | DiagnosticBits::OPERATIONAL_LEVEL_LOCALIZED_TEXT
+        | DiagnosticBits::OPERATIONAL_LEVEL_ADDITIONAL_INFO
+        | DiagnosticBits::OPERATIONAL_LEVEL_INNER_STATUS_CODE
+        | DiagnosticBits::OPERATIONAL_LEVEL_INNER_DIAGNOSTICS
+}
+
+/// Call `method_name` on every (filtered) instance of `type_id` in batched Call requests
+async fn execute_on_type(
+    session: &ServiceSession,
+    args: &CallArgs,
+    type_id: &NodeId,
+    method_name: &str,
+    filter: Option<&str>,
+) -> Result<()> {
+    info!("🔍 Looking up instances of {}", format_node_id(type_id));
+    
+    let config = SearchConfig {
+        max_nodes: 5000,
+        max_depth: 10,
+        ..Default::default()
+    };
+    let mut instances = find_instances_of_type(session, type_id, &config, args.verbose).await?;
+    if let Some(filter) = filter {
+        instances.retain(|instance| instance.display_name.to_lowercase().contains(filter));
+    }
+    
+    if instances.is_empty() {
+        return Err(anyhow!("No instances of {} found", format_node_id(type_id)));
     }
+    instances.sort_by(|a, b| natural_cmp(&a.display_name, &b.display_name));
     
+    let methods = resolve_instance_methods(session, type_id, &instances, method_name).await?;
+    
+    let planned: Vec<PlannedCallRow> = instances
+        .iter()
+        .zip(&methods)
+        .map(|(instance, method)| PlannedCallRow {
+            object: format_node_id(&instance.node_id),
+            display_name: instance.display_name.clone(),
+            method: method.as_ref()
+                .map(format_node_id)
+                .unwrap_or_else(|| "❌ not found".red().to_string()),
+        })
+        .collect();
+    
+    println!("📋 {} {} on {} instance(s)", "Calling".bright_white().bold(), method_name.bright_cyan(), instances.len());
+    if !args.input_arguments.is_empty() {
+        println!("   📥 Arguments: {} values", args.input_arguments.len().to_string().bright_white());
+    }
+    println!("{}", Table::new(planned));
+    
+    if args.dry_run {
+        println!("\n🧪 {}", "Dry run: no methods were called".bright_yellow());
+        return Ok(());
+    }
+    
+    let calls: Vec<(usize, CallMethodRequest)> = methods
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, method)| method.as_ref().map(|method_id| (idx, CallMethodRequest {
+            object_id: instances[idx].node_id.clone(),
+            method_id: method_id.clone(),
+            input_arguments: Some(args.input_arguments.clone()),
+        })))
+        .collect();
+    guard::confirm(&[Operations::Calls(calls.len())])?;
+    
+    println!("\n⚡ Executing {} method call(s)...", calls.len());
+    
+    let mut outcomes: Vec<String> = methods
+        .iter()
+        .map(|method| match method {
+            Some(_) => String::new(),
+            None => format!("❌ Method '{}' not found", method_name).red().to_string(),
+        })
+        .collect();
+    let mut failures = methods.iter().filter(|m| m.is_none()).count();
+    
+    for chunk in calls.chunks(CALL_BATCH_SIZE) {
+        let methods_to_call = chunk.iter().map(|(_, call)| call.clone()).collect();
+        let response = tokio::select! {
+            response = session.call(methods_to_call, call_diagnostics(), args.timeout) => response,
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n🛑 {}", "Method calls cancelled".bright_yellow().bold());
+                println!("   ⚠️  Calls already sent may still be executing on the server");
+                return Err(anyhow!("Method calls cancelled"));
+            }
+        };
+        
+        match response {
+            Ok(response) => {
+                let string_table = response.response_header.string_table.as_deref().unwrap_or_default();
+                let results = response.results.unwrap_or_default();
+                let diagnostics = response.diagnostic_infos.unwrap_or_default();
+                
+                for (position, (idx, _)) in chunk.iter().enumerate() {
+                    outcomes[*idx] = match results.get(position) {
+                        Some(result) if result.status_code.is_good() => {
+                            let outputs: Vec<String> = result.output_arguments.as_deref()
+                                .unwrap_or_default()
+                                .iter()
+                                .map(format_variant)
+                                .collect();
+                            if outputs.is_empty() {
+                                "✅ Success".green().to_string()
+                            } else {
+                                format!("{} → {}", "✅ Success".green(), outputs.join(", "))
+                            }
+                        }
+                        Some(result) => {
+                            failures += 1;
+                            let mut outcome = OperationResult::of_status(result.status_code).describe();
+                            if let Some(info) = diagnostics.get(position) {
+                                for line in format_diagnostic_info(info, string_table) {
+                                    outcome.push_str(&format!("\n{}", line));
+                                }
+                            }
+                            outcome
+                        }
+                        None => {
+                            failures += 1;
+                            "❌ No result returned".red().to_string()
+                        }
+                    };
+                }
+            }
+            Err(e) => {
+                for (idx, _) in chunk {
+                    failures += 1;
+                    outcomes[*idx] = OperationResult::of_service_error(e).describe();
+                }
+            }
+        }
+    }
+    
+    let rows: Vec<CallResultRow> = instances
+        .iter()
+        .zip(outcomes)
+        .map(|(instance, result)| CallResultRow {
+            object: format_node_id(&instance.node_id),
+            display_name: instance.display_name.clone(),
+            result,
+        })
+        .collect();
+    
+    println!("\n{}", "📤 Method Call Results".bright_cyan().bold());
+    println!("{}", Table::new(rows));
+    
+    let total = instances.len();
+    if failures > 0 {
+        return Err(anyhow!("{} of {} method call(s) failed", failures, total));
+    }
+    
+    println!("\n✅ {}", format!("All {} method call(s) succeeded", total).green());
     Ok(())
 }
 
-async fn check_method_executable(session: &Arc<Session>, method_node_id: &NodeId) -> Result<bool> {
+/// Find the method with the given BrowseName on each instance, falling back to the method
+/// declared on the type itself
+async fn resolve_instance_methods(
+    session: &ServiceSession,
+    type_id: &NodeId,
+    instances: &[SearchResult],
+    method_name: &str,
+) -> Result<Vec<Option<NodeId>>> {
+    let nodes: Vec<&NodeId> = instances.iter().map(|i| &i.node_id).chain(std::iter::once(type_id)).collect();
+    let mut methods = Vec::with_capacity(nodes.len());
+    
+    for chunk in nodes.chunks(CALL_BATCH_SIZE) {
+        let browse_requests: Vec<BrowseDescription> = chunk
+            .iter()
+            .map(|node_id| BrowseRequest::new(node_id, BrowseDirection::Forward, ReferenceTypeId::HasComponent)
+                .classes(NodeClassMask::METHOD)
+                .results(&[BrowseResultMask::BrowseName])
+                .build())
+            .collect();
+        
+        let results = session.browse(&browse_requests, 0, None).await?;
+        methods.extend(results.into_iter().map(|result| {
+            result.references
+                .unwrap_or_default()
+                .into_iter()
+                .find(|r| r.browse_name.name.as_ref() == method_name)
+                .map(|r| r.node_id.node_id)
+        }));
+    }
+    
+    let type_method = methods.pop().flatten();
+    Ok(methods.into_iter().map(|method| method.or_else(|| type_method.clone())).collect())
+}
+
+async fn check_method_executable(session: &ServiceSession, method_node_id: &NodeId) -> Result<bool> {
     // Read the Executable attribute (AttributeId = 23)
     let read_request = ReadValueId {
         node_id: method_node_id.clone(),
@@ -138,14 +716,12 @@ async fn check_method_executable(session: &Arc<Session>, method_node_id: &NodeId
                 // Check if status is good (if present) or assume good if no status
                 let status_good = read_result.status
                     .as_ref()
-                    .map_or(true, |status| status.is_good());
+                    .is_none_or(|status| status.is_good());
                 
-                if status_good {
-                    if let Some(ref value) = read_result.value {
-                        if let Variant::Boolean(executable) = value {
-                            return Ok(*executable);
-                        }
-                    }
+                if status_good
+                    && let Some(Variant::Boolean(executable)) = read_result.value
+                {
+                    return Ok(executable);
                 }
             }
             // Default to true if we can't read the attribute
@@ -158,27 +734,20 @@ async fn check_method_executable(session: &Arc<Session>, method_node_id: &NodeId
     }
 }
 
-async fn find_parent_object(session: &Arc<Session>, method_node_id: &NodeId) -> Result<NodeId> {
+async fn find_parent_object(session: &ServiceSession, method_node_id: &NodeId) -> Result<NodeId> {
     // Browse inverse references to find the parent object
-    let browse_request = BrowseDescription {
-        node_id: method_node_id.clone(),
-        browse_direction: BrowseDirection::Inverse,
-        reference_type_id: ReferenceTypeId::HasComponent.into(),
-        include_subtypes: true,
-        node_class_mask: NodeClassMask::OBJECT.bits(),
-        result_mask: BrowseResultMask::All as u32,
-    };
+    let browse_request = BrowseRequest::new(method_node_id, BrowseDirection::Inverse, ReferenceTypeId::HasComponent)
+        .classes(NodeClassMask::OBJECT)
+        .build();
     
     let browse_results = session.browse(&[browse_request], 0, None).await?;
     
-    if let Some(browse_result) = browse_results.first() {
-        if browse_result.status_code.is_good() {
-            if let Some(references) = &browse_result.references {
-                if !references.is_empty() {
-                    return Ok(references[0].node_id.node_id.clone());
-                }
-            }
-        }
+    if let Some(browse_result) = browse_results.first()
+        && browse_result.status_code.is_good()
+        && let Some(references) = &browse_result.references
+        && !references.is_empty()
+    {
+        return Ok(references[0].node_id.node_id.clone());
     }
     
     Err(anyhow!("Could not find parent object for method: {}", 
@@ -261,9 +830,26 @@ fn parse_simple_value(value_str: &str) -> Result<Variant> {
     Ok(Variant::String(UAString::from(value_str)))
 }
 
-fn display_call_result(result: &CallMethodResult, verbose: bool) {
+/// The input arguments of a call as sent, next to their definitions on the method
+struct CallInputs<'a> {
+    values: &'a [Variant],
+    /// The method's InputArguments property, empty when it could not be read
+    definitions: &'a [Argument],
+}
+
+fn display_call_result(
+    result: &CallMethodResult,
+    inputs: &CallInputs,
+    diagnostics: Option<&DiagnosticInfo>,
+    string_table: &[UAString],
+    verbose: bool,
+) {
     println!("\n{}", "📤 Method Call Result".bright_cyan().bold());
     
+    let diagnostic_lines = diagnostics
+        .map(|info| format_diagnostic_info(info, string_table))
+        .unwrap_or_default();
+    
     if result.status_code.is_good() {
         println!("  {}: {}", "Status".bright_white(), "✅ Success".green().bold());
         
@@ -287,8 +873,10 @@ fn display_call_result(result: &CallMethodResult, verbose: bool) {
             println!("  {}: No return values", "Output".bright_white());
         }
     } else {
-        println!("  {}: {}", "Status".bright_white(), 
-                format!("❌ Failed ({})", result.status_code).red().bold());
+        println!("  {}: {}", "Status".bright_white(), OperationResult::of_status(result.status_code).describe());
+        
+        print_input_results(result, inputs, string_table, verbose);
+        print_diagnostics(&diagnostic_lines);
         
         // Provide specific error guidance
         let status_code_value = result.status_code.bits();
@@ -314,10 +902,210 @@ fn display_call_result(result: &CallMethodResult, verbose: bool) {
         } else {
             println!("\n💡 Check server logs and method requirements for more details");
         }
+        return;
+    }
+    
+    print_input_results(result, inputs, string_table, verbose);
+    print_diagnostics(&diagnostic_lines);
+}
+
+/// The per-argument results of a call that failed over its arguments. Results, values and
+/// definitions share the argument index; servers may return fewer results than arguments were
+/// sent, or none at all.
+fn print_input_results(result: &CallMethodResult, inputs: &CallInputs, string_table: &[UAString], verbose: bool) {
+    if !shows_input_results(result) {
+        return;
+    }
+    
+    let statuses = result.input_argument_results.as_deref().unwrap_or_default();
+    let rows = input_result_rows(statuses, inputs);
+    if rows.is_empty() {
+        return;
+    }
+    
+    println!("  {}:", "Input Arguments".bright_white());
+    println!("{}", Table::new(rows));
+    if !statuses.is_empty() && statuses.len() < inputs.values.len() {
+        println!("  ⚠️  The server returned {} argument result(s) for {} argument(s) sent", statuses.len(), inputs.values.len());
+    }
+    
+    if verbose {
+        let infos = result.input_argument_diagnostic_infos.as_deref().unwrap_or_default();
+        for (i, info) in infos.iter().enumerate() {
+            let lines = format_diagnostic_info(info, string_table);
+            if lines.is_empty() {
+                continue;
+            }
+            let name = inputs.definitions.get(i).map(|argument| format!(" {}", argument.name)).unwrap_or_default();
+            println!("  {} [{}]{}:", "Diagnostics".bright_white(), i, name);
+            for line in lines {
+                println!("    {}", line);
+            }
+        }
+    }
+}
+
+/// One row per argument position, lining up the server's results, the values sent and the
+/// definitions by index; any of them may be shorter than the others
+fn input_result_rows(statuses: &[StatusCode], inputs: &CallInputs) -> Vec<InputResultRow> {
+    let count = statuses.len().max(inputs.values.len()).max(inputs.definitions.len());
+    (0..count)
+        .map(|i| {
+            let definition = inputs.definitions.get(i);
+            let value = inputs.values.get(i);
+            let status = statuses.get(i);
+            let differs = definition.zip(value).is_some_and(|(argument, value)| type_differs(argument, value));
+            InputResultRow {
+                index: match status {
+                    Some(status) if !status.is_good() => format!("❌ {}", i).red().to_string(),
+                    _ if differs => format!("⚠️  {}", i).yellow().to_string(),
+                    _ => i.to_string(),
+                },
+                name: definition.map(|argument| argument.name.to_string()).unwrap_or_else(|| "—".dimmed().to_string()),
+                expected: definition.map(format_argument_type).unwrap_or_default(),
+                supplied: value.map_or_else(
+                    || "not sent".dimmed().to_string(),
+                    |value| format!("{} ({})", truncate_string(&format_variant(value), 40), variant_type_name(value)),
+                ),
+                status: match status {
+                    Some(status) => OperationResult::of_status(*status).describe(),
+                    None if differs => "type differs".yellow().to_string(),
+                    None => "not reported".dimmed().to_string(),
+                },
+            }
+        })
+        .collect()
+}
+
+/// DataType of an argument definition with its array dimensions, e.g. `Double[]`
+fn format_argument_type(argument: &Argument) -> String {
+    let data_type = format_data_type(&argument.data_type);
+    match argument.value_rank {
+        rank if rank > 0 => format!("{}{}", data_type, "[]".repeat(rank as usize)),
+        0 => format!("{}[…]", data_type),
+        -2 => format!("{} (any rank)", data_type),
+        -3 => format!("{} or {}[]", data_type, data_type),
+        _ => data_type,
+    }
+}
+
+fn print_diagnostics(lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    
+    println!("  {}:", "Diagnostics".bright_white());
+    for line in lines {
+        println!("    {}", line);
     }
 }
 
-fn parse_node_id(node_str: &str) -> Result<NodeId> {
-    NodeId::from_str(node_str)
-        .map_err(|_| anyhow!("Invalid node ID format: {}", node_str))
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argument(name: &str, data_type: DataTypeId, value_rank: i32) -> Argument {
+        Argument {
+            name: name.into(),
+            data_type: data_type.into(),
+            value_rank,
+            array_dimensions: None,
+            description: LocalizedText::null(),
+        }
+    }
+
+    /// Scale(Value: Double, Factor: Int32)
+    fn definitions() -> Vec<Argument> {
+        vec![argument("Value", DataTypeId::Double, -1), argument("Factor", DataTypeId::Int32, -1)]
+    }
+
+    fn failed(status: StatusCode, input_results: Option<Vec<StatusCode>>) -> CallMethodResult {
+        CallMethodResult { status_code: status, input_argument_results: input_results, ..Default::default() }
+    }
+
+    #[test]
+    fn fewer_results_than_arguments() {
+        let definitions = definitions();
+        let values = [Variant::Double(2.5), Variant::String("x".into())];
+        let inputs = CallInputs { values: &values, definitions: &definitions };
+        let rows = input_result_rows(&[StatusCode::Good], &inputs);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "Value");
+        assert_eq!(rows[0].status, OperationResult::of_status(StatusCode::Good).describe());
+        // The missing result is not taken from the first argument
+        assert_eq!(rows[1].name, "Factor");
+        assert!(rows[1].expected.contains("Int32"));
+        assert!(rows[1].supplied.contains("String"));
+        assert!(rows[1].status.contains("type differs"));
+    }
+
+    #[test]
+    fn empty_results_array() {
+        let definitions = definitions();
+        let values = [Variant::Double(2.5), Variant::Int32(4)];
+        let inputs = CallInputs { values: &values, definitions: &definitions };
+        let rows = input_result_rows(&[], &inputs);
+
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.status.contains("not reported")));
+        assert_eq!((rows[0].name.as_str(), rows[1].name.as_str()), ("Value", "Factor"));
+        // The whole call blamed the arguments, so the table is shown without per-argument results
+        assert!(shows_input_results(&failed(StatusCode::BadInvalidArgument, Some(Vec::new()))));
+    }
+
+    #[test]
+    fn more_definitions_than_values() {
+        let definitions = definitions();
+        let values = [Variant::Double(2.5)];
+        let inputs = CallInputs { values: &values, definitions: &definitions };
+        let rows = input_result_rows(&[StatusCode::Good, StatusCode::BadArgumentsMissing], &inputs);
+
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].supplied.contains("2.5"));
+        assert_eq!(rows[1].name, "Factor");
+        assert!(rows[1].supplied.contains("not sent"));
+        assert_eq!(rows[1].status, OperationResult::of_status(StatusCode::BadArgumentsMissing).describe());
+    }
+
+    #[test]
+    fn more_values_than_definitions() {
+        let definitions = definitions();
+        let values = [Variant::Double(2.5), Variant::Int32(4), Variant::Int32(5)];
+        let inputs = CallInputs { values: &values, definitions: &definitions };
+        let rows = input_result_rows(&[], &inputs);
+
+        assert_eq!(rows.len(), 3);
+        assert!(rows[2].name.contains('—'));
+        assert!(rows[2].expected.is_empty());
+    }
+
+    #[test]
+    fn input_results_only_for_argument_failures() {
+        assert!(!shows_input_results(&failed(StatusCode::Good, None)));
+        assert!(!shows_input_results(&failed(StatusCode::BadUserAccessDenied, None)));
+        assert!(shows_input_results(&failed(StatusCode::BadTypeMismatch, None)));
+        assert!(shows_input_results(&failed(StatusCode::BadInvalidArgument, Some(vec![StatusCode::Good, StatusCode::BadOutOfRange]))));
+    }
+
+    #[test]
+    fn types_differ_for_built_in_types_only() {
+        let double = argument("Value", DataTypeId::Double, -1);
+        assert!(!type_differs(&double, &Variant::Double(1.0)));
+        assert!(type_differs(&double, &Variant::Int32(1)));
+
+        let array = Variant::from((VariantScalarTypeId::Double, vec![Variant::Double(1.0)]));
+        assert!(!type_differs(&argument("Values", DataTypeId::Double, 1), &array));
+
+        // Abstract and structured DataTypes are not compared
+        assert!(!type_differs(&argument("Number", DataTypeId::Number, -1), &Variant::Int32(1)));
+        assert!(!type_differs(&argument("Range", DataTypeId::Range, -1), &Variant::Int32(1)));
+    }
+
+    #[test]
+    fn argument_types_show_their_rank() {
+        assert_eq!(format_argument_type(&argument("a", DataTypeId::Double, -1)), format_data_type(&DataTypeId::Double.into()));
+        assert!(format_argument_type(&argument("a", DataTypeId::Double, 2)).ends_with("[][]"));
+        assert!(format_argument_type(&argument("a", DataTypeId::Double, -2)).ends_with("(any rank)"));
+    }
+}
@@ -0,0 +1,294 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use std::sync::Arc;
+use tabled::{Table, Tabled};
+use tracing::debug;
+
+use crate::client::{check_session_budget, OpcUaClient};
+use crate::commands::backup::{collect_parameters, parse_value_type};
+use crate::commands::export::{attribute_request, BATCH_SIZE};
+use crate::commands::restore::{display_path, entry_variant, resolve_browse_paths, NamespaceTranslator};
+use crate::session::{ServiceSession, ServiceStats};
+use crate::types::Cli;
+use crate::utils::formatter::{format_node_id, format_variant, truncate_string};
+use crate::utils::guard::{self, Operations};
+use crate::utils::namespace::parse_node_id;
+
+#[derive(Tabled)]
+struct CopyRow {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Source")]
+    source: String,
+    #[tabled(rename = "Destination")]
+    destination: String,
+    #[tabled(rename = "Value")]
+    value: String,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+pub struct CopyArgs {
+    pub start_node_id: NodeId,
+    pub destination: String,
+    /// Subtree root on the destination (default: the source root translated by namespace URI)
+    pub destination_node_id: Option<NodeId>,
+    pub max_depth: u32,
+    pub dry_run: bool,
+}
+
+/// What happened to one parameter
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Pending,
+    WouldCopy,
+    Copied,
+    Skipped(String),
+    Failed(String),
+}
+
+impl Outcome {
+    fn describe(&self) -> String {
+        match self {
+            Outcome::Pending => String::new(),
+            Outcome::WouldCopy => "would copy".dimmed().to_string(),
+            Outcome::Copied => "✅ Copied".green().to_string(),
+            Outcome::Skipped(reason) => format!("⏭️  {}", reason).yellow().to_string(),
+            Outcome::Failed(reason) => format!("❌ {}", reason).red().to_string(),
+        }
+    }
+}
+
+pub fn validate(node: &str, destination: &str, destination_node: Option<&str>, max_depth: u32, dry_run: bool) -> Result<CopyArgs> {
+    Ok(CopyArgs {
+        start_node_id: parse_node_id(node)?,
+        destination: destination.to_string(),
+        destination_node_id: destination_node.map(parse_node_id).transpose()?,
+        max_depth,
+        dry_run,
+    })
+}
+
+/// Copy the writable values below a subtree of the `--endpoint` server onto the same browse
+/// paths of the destination server. Both servers are connected here since the command needs two
+/// sessions.
+pub async fn execute(cli: &Cli, args: &CopyArgs, stats: &Arc<ServiceStats>) -> Result<()> {
+    check_session_budget(cli.max_sessions as usize, [cli.endpoint.as_str(), args.destination.as_str()])?;
+    let mut source = OpcUaClient::for_endpoint(cli, &cli.endpoint)?.with_stats(stats.clone());
+    source.connect().await?;
+    let mut destination = OpcUaClient::for_endpoint(cli, &args.destination)?.with_stats(stats.clone());
+    if let Err(e) = destination.connect().await {
+        disconnect(&mut source).await;
+        return Err(e);
+    }
+    source.print_security_warning();
+    destination.print_security_warning();
+
+    let result = copy(&source, &destination, args).await;
+
+    disconnect(&mut source).await;
+    disconnect(&mut destination).await;
+    result
+}
+
+async fn disconnect(client: &mut OpcUaClient) {
+    if let Err(e) = client.disconnect().await {
+        debug!("Disconnect from {} failed: {}", client.endpoint(), e);
+    }
+}
+
+/// A source parameter with the destination node it maps to and what happened to it
+pub struct CopiedParameter {
+    pub path: Vec<String>,
+    pub source: String,
+    pub destination: Option<NodeId>,
+    pub value: Variant,
+    pub outcome: Outcome,
+}
+
+async fn copy(source: &OpcUaClient, destination: &OpcUaClient, args: &CopyArgs) -> Result<()> {
+    let parameters = copy_parameters(source, destination, args).await?;
+
+    let rows: Vec<CopyRow> = parameters
+        .iter()
+        .map(|parameter| CopyRow {
+            path: display_path(&parameter.path),
+            source: parameter.source.clone(),
+            destination: parameter.destination.as_ref().map(format_node_id).unwrap_or_else(|| "—".dimmed().to_string()),
+            value: truncate_string(&format_variant(&parameter.value), 30),
+            result: parameter.outcome.describe(),
+        })
+        .collect();
+    if !rows.is_empty() {
+        println!("{}", Table::new(rows));
+    }
+
+    let count = |matches: fn(&Outcome) -> bool| parameters.iter().filter(|parameter| matches(&parameter.outcome)).count();
+    let skipped = count(|outcome| matches!(outcome, Outcome::Skipped(_)));
+    let failed = count(|outcome| matches!(outcome, Outcome::Failed(_)));
+    if args.dry_run {
+        println!("\n🔍 {} {} of {} parameter(s) would be copied, {} skipped",
+                 "Dry run:".bright_white().bold(), count(|outcome| *outcome == Outcome::WouldCopy), parameters.len(), skipped);
+    } else {
+        println!("\n📊 {} copied, {} skipped, {} failed",
+                 count(|outcome| *outcome == Outcome::Copied).to_string().bright_green(), skipped, failed);
+    }
+
+    if skipped + failed > 0 {
+        return Err(anyhow!("{} of {} parameter(s) {} copied", skipped + failed, parameters.len(),
+                           if args.dry_run { "would not be" } else { "were not" }));
+    }
+    if !args.dry_run {
+        println!("\n✅ {} {} parameter(s)", "Copied".green(), parameters.len());
+    }
+    Ok(())
+}
+
+/// Write the parameters of the source subtree to the destination, or with `--dry-run` only check
+/// which could be written. A failed write request fails the parameters it held, not the copy.
+pub async fn copy_parameters(source: &OpcUaClient, destination: &OpcUaClient, args: &CopyArgs) -> Result<Vec<CopiedParameter>> {
+    let translator = NamespaceTranslator {
+        source: source.namespaces().await?,
+        target: destination.namespaces().await?,
+        ns_map: destination.ns_map(),
+    };
+    let root = args.destination_node_id.clone().unwrap_or_else(|| translator.node_id(&args.start_node_id));
+
+    println!("\n{}", "📋 Copying Parameters Between Servers".bright_cyan().bold());
+    println!("📤 From: {} {}", source.endpoint().bright_white(), format_node_id(&args.start_node_id).dimmed());
+    println!("📥 To: {} {}", destination.endpoint().bright_white(), format_node_id(&root).dimmed());
+    println!("📏 Max depth: {}", args.max_depth.to_string().bright_white());
+    if args.dry_run {
+        println!("🧪 Dry run: {}", "Enabled".bright_green());
+    }
+    println!("{}", "─".repeat(60));
+
+    let parameters = collect_parameters(source.session()?, &args.start_node_id, args.max_depth).await?;
+    println!("📊 {} writable of {} variable(s) on the source",
+             parameters.entries.len().to_string().bright_white(), parameters.variables);
+    for node_id in &parameters.unsupported {
+        println!("⚠️  Skipping {}: value type cannot be copied", node_id);
+    }
+
+    let entries = &parameters.entries;
+    let values: Vec<Variant> = entries
+        .iter()
+        .map(|entry| entry_variant(entry).map(|value| translator.value(value)))
+        .collect::<Result<_>>()?;
+
+    let session = destination.session()?;
+    let paths: Vec<&[String]> = entries.iter().map(|entry| entry.browse_path.as_slice()).collect();
+    let targets = resolve_browse_paths(session, &paths, &root, &translator).await?;
+
+    let mut outcomes: Vec<Outcome> = targets
+        .iter()
+        .map(|target| match target {
+            Some(_) => Outcome::Pending,
+            None => Outcome::Skipped("Missing on destination".to_string()),
+        })
+        .collect();
+    check_targets(session, entries.iter().map(|entry| entry.data_type.as_str()), &targets, &mut outcomes).await?;
+
+    let writes: Vec<(usize, WriteValue)> = targets
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| matches!(outcomes[*idx], Outcome::Pending))
+        .filter_map(|(idx, target)| target.as_ref().map(|node_id| (idx, WriteValue {
+            node_id: node_id.clone(),
+            attribute_id: AttributeId::Value as u32,
+            index_range: NumericRange::None,
+            value: DataValue {
+                value: Some(values[idx].clone()),
+                ..Default::default()
+            },
+        })))
+        .collect();
+
+    if args.dry_run {
+        for (idx, _) in &writes {
+            outcomes[*idx] = Outcome::WouldCopy;
+        }
+    } else {
+        guard::confirm(&[Operations::Writes(writes.len())])?;
+        for chunk in writes.chunks(BATCH_SIZE) {
+            let write_requests: Vec<WriteValue> = chunk.iter().map(|(_, write)| write.clone()).collect();
+            match session.write(&write_requests).await {
+                Ok(statuses) => {
+                    for ((idx, _), status) in chunk.iter().zip(statuses) {
+                        outcomes[*idx] = if status.is_good() {
+                            Outcome::Copied
+                        } else {
+                            Outcome::Failed(status.to_string())
+                        };
+                    }
+                }
+                Err(e) => {
+                    for (idx, _) in chunk {
+                        outcomes[*idx] = Outcome::Failed(e.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(parameters.entries
+        .into_iter()
+        .zip(targets)
+        .zip(values.into_iter().zip(outcomes))
+        .map(|((entry, destination), (value, outcome))| CopiedParameter {
+            path: entry.browse_path,
+            source: entry.node_id,
+            destination,
+            value,
+            outcome,
+        })
+        .collect())
+}
+
+/// Skip destination nodes the session's user may not write or that hold a different value type
+/// than the source
+async fn check_targets<'a>(
+    session: &ServiceSession,
+    data_types: impl Iterator<Item = &'a str>,
+    targets: &[Option<NodeId>],
+    outcomes: &mut [Outcome],
+) -> Result<()> {
+    const ATTRIBUTES: [AttributeId; 2] = [AttributeId::UserAccessLevel, AttributeId::DataType];
+
+    let source_types: Vec<&str> = data_types.collect();
+    let found: Vec<(usize, &NodeId)> = targets
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, target)| target.as_ref().map(|node_id| (idx, node_id)))
+        .collect();
+
+    for chunk in found.chunks(BATCH_SIZE / ATTRIBUTES.len()) {
+        let read_requests: Vec<ReadValueId> = chunk
+            .iter()
+            .flat_map(|(_, node_id)| ATTRIBUTES.map(|attr| attribute_request(node_id, attr)))
+            .collect();
+        let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+
+        for ((idx, _), values) in chunk.iter().zip(results.chunks(ATTRIBUTES.len())) {
+            // Bit 1 of UserAccessLevel is CurrentWrite, AccessLevel would not tell whether this user may write
+            if !matches!(values[0].value, Some(Variant::Byte(level)) if level & 0x02 != 0) {
+                outcomes[*idx] = Outcome::Skipped("Not writable".to_string());
+                continue;
+            }
+
+            let source_type = parse_value_type(source_types[*idx]);
+            let destination_type = match &values[1].value {
+                Some(Variant::NodeId(data_type)) => VariantScalarTypeId::try_from(data_type.as_ref()).ok(),
+                _ => None,
+            };
+            // Non built-in DataTypes (e.g. enumerations) are left for the server to check
+            if let (Some(source_type), Some(destination_type)) = (source_type, destination_type)
+                && source_type != destination_type
+            {
+                outcomes[*idx] = Outcome::Skipped(format!("Type mismatch ({} on destination)", destination_type));
+            }
+        }
+    }
+    Ok(())
+}
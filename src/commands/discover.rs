@@ -1,14 +1,17 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
 use colored::*;
-use opcua::types::{MessageSecurityMode, UserTokenType, ApplicationType, UserTokenPolicy};
-use tabled::Tabled;
+use opcua::types::{EndpointDescription, MessageSecurityMode, UserTokenType, ApplicationType, UserTokenPolicy};
+use std::path::Path;
+use tabled::{Table, Tabled};
 
 use crate::client::OpcUaClient;
+use crate::utils::cert::{check_server_certificate, CertificateDetails, CheckState};
+use crate::utils::endpoint::DEFAULT_PORT;
+use crate::utils::proxy::split_host_port;
 
 #[derive(Tabled)]
 struct EndpointInfo {
-    #[tabled(rename = "URL")]
-    url: String,
     #[tabled(rename = "Security Policy")]
     security_policy: String,
     #[tabled(rename = "Security Mode")]
@@ -17,22 +20,150 @@ struct EndpointInfo {
     auth_tokens: String,
 }
 
-pub async fn execute(client: &mut OpcUaClient) -> Result<()> {
+pub struct DiscoverArgs {
+    pub server_cert: bool,
+    pub save_cert: Option<String>,
+    /// The CA file given and its certificates
+    pub ca_file: Option<(String, Vec<CertificateDetails>)>,
+}
+
+pub fn validate(server_cert: bool, save_cert: Option<&str>, ca_file: Option<&str>) -> Result<DiscoverArgs> {
+    let ca_file = ca_file.map(|path| CertificateDetails::load_file(path).map(|cas| (path.to_string(), cas))).transpose()?;
+    Ok(DiscoverArgs { server_cert, save_cert: save_cert.map(str::to_string), ca_file })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &DiscoverArgs) -> Result<()> {
     println!("\n{}", "🔍 OPC-UA Server Discovery".bright_cyan().bold());
     println!("{}", "─".repeat(50));
     
     println!("📡 {}: {}", "Connected Endpoint".bright_white(), 
              client.endpoint().bright_cyan());
     
-    println!("✅ {}", "Discovery shows active session connection".green());
+    let endpoints = client.server_endpoints().await?;
+    println!("🔐 {}: {}", "Endpoints".bright_white(), endpoints.len());
+    if !endpoints.is_empty() {
+        println!("{}", endpoint_matrix(&endpoints));
+    }
     
-    // Note: Full endpoint and server discovery requires access to the Client object
-    // which is not currently available after session creation.
-    // This would require architectural changes to expose the Client.
+    if args.server_cert {
+        print_server_certificates(client.endpoint(), &endpoints, args)?;
+    }
     
     Ok(())
 }
 
+/// Print every distinct certificate the endpoints carry with its checks, failing when one fails any
+fn print_server_certificates(url: &str, endpoints: &[EndpointDescription], args: &DiscoverArgs) -> Result<()> {
+    let mut certificates: Vec<(CertificateDetails, &str, Vec<String>)> = Vec::new();
+    for endpoint in endpoints {
+        let der = endpoint.server_certificate.as_ref();
+        if der.is_empty() {
+            continue;
+        }
+        let label = format!("{} {}", format_security_policy(endpoint.security_policy_uri.as_ref()), format_security_mode(endpoint.security_mode));
+        match certificates.iter_mut().find(|(certificate, _, _)| certificate.der == der) {
+            Some((_, _, labels)) => labels.push(label),
+            None => {
+                let certificate = CertificateDetails::from_der(der).map_err(|e| anyhow!("The server certificate cannot be read: {}", e))?;
+                certificates.push((certificate, endpoint.server.application_uri.as_ref(), vec![label]));
+            }
+        }
+    }
+    if certificates.is_empty() {
+        return Err(anyhow!("The server sends no certificate with its endpoints"));
+    }
+    
+    let host = url
+        .split_once("://")
+        .map(|(_, rest)| rest.split('/').next().unwrap_or(rest))
+        .and_then(|authority| split_host_port(authority, DEFAULT_PORT).ok())
+        .map(|(host, _)| host);
+    let cas = args.ca_file.as_ref().map(|(_, cas)| cas.as_slice());
+    let mut failed = 0;
+    for (index, (certificate, application_uri, labels)) in certificates.iter().enumerate() {
+        println!("\n{}", "🔏 Server Certificate".bright_cyan().bold());
+        println!("{}", "─".repeat(50));
+        println!("🔐 {}: {}", "Endpoints".bright_white(), labels.join(", "));
+        println!("👤 {}: {}", "Subject".bright_white(), certificate.subject);
+        println!("🏛️  {}: {}", "Issuer".bright_white(), certificate.issuer);
+        if !certificate.alt_names.is_empty() {
+            println!("🏷️  {}: {}", "Alternative Names".bright_white(), certificate.alt_names.join(", "));
+        }
+        println!(
+            "📅 {}: {} until {}",
+            "Valid".bright_white(),
+            certificate.not_before.format("%Y-%m-%d %H:%M:%S UTC"),
+            certificate.not_after.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        let key = certificate.key_bits.map(|bits| format!("RSA {} bits", bits)).unwrap_or_else(|| "not RSA".to_string());
+        println!("🔑 {}: {}", "Key".bright_white(), key);
+        println!("✍️  {}: {}", "Signature".bright_white(), certificate.signature_algorithm);
+        println!("#️⃣  {}: {}", "SHA-1".bright_white(), certificate.sha1.bright_cyan());
+        println!("#️⃣  {}: {}", "SHA-256".bright_white(), certificate.sha256.bright_cyan());
+        
+        if let Some((path, _)) = &args.ca_file {
+            println!("📜 {}: {}", "CA File".bright_white(), path);
+        }
+        for check in check_server_certificate(certificate, application_uri, host.as_deref(), cas, Utc::now()) {
+            let line = format!("{}: {}", check.name, check.detail);
+            match check.state {
+                CheckState::Pass => println!("   ✅ {}", line),
+                CheckState::Warn => println!("   ⚠️  {}", line.yellow()),
+                CheckState::Fail => {
+                    failed += 1;
+                    println!("   ❌ {}", line.red());
+                }
+            }
+        }
+        
+        if let Some(path) = &args.save_cert {
+            let path = numbered_path(path, index, certificates.len());
+            std::fs::write(&path, &certificate.der).map_err(|e| anyhow!("Failed to write {}: {}", path, e))?;
+            println!("💾 Saved as {}", path.bright_white());
+        }
+    }
+    
+    if failed > 0 {
+        return Err(anyhow!("The server certificate failed {} check(s)", failed));
+    }
+    Ok(())
+}
+
+/// `path` for a single certificate, `name-2.der` and so on for the further ones of several
+fn numbered_path(path: &str, index: usize, count: usize) -> String {
+    if count == 1 || index == 0 {
+        return path.to_string();
+    }
+    let file = Path::new(path);
+    let stem = file.file_stem().and_then(|stem| stem.to_str()).unwrap_or(path);
+    let name = match file.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => format!("{}-{}.{}", stem, index + 1, extension),
+        None => format!("{}-{}", stem, index + 1),
+    };
+    file.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// One row per security policy and mode the server offers, with the token types accepted on
+/// any endpoint of that combination
+pub fn endpoint_matrix(endpoints: &[EndpointDescription]) -> Table {
+    let mut combinations: Vec<(&str, MessageSecurityMode, Vec<UserTokenPolicy>)> = Vec::new();
+    for endpoint in endpoints {
+        let policy = endpoint.security_policy_uri.as_ref();
+        let tokens = endpoint.user_identity_tokens.iter().flatten().cloned();
+        match combinations.iter_mut().find(|(p, mode, _)| *p == policy && *mode == endpoint.security_mode) {
+            Some((_, _, policies)) => policies.extend(tokens),
+            None => combinations.push((policy, endpoint.security_mode, tokens.collect())),
+        }
+    }
+    
+    let rows = combinations.iter().map(|(policy, mode, tokens)| EndpointInfo {
+        security_policy: format_security_policy(policy),
+        security_mode: format_security_mode(*mode),
+        auth_tokens: format_user_tokens(tokens),
+    });
+    Table::new(rows)
+}
+
 fn format_security_policy(policy_uri: &str) -> String {
     match policy_uri {
         "http://opcfoundation.org/UA/SecurityPolicy#None" => "None".dimmed().to_string(),
@@ -41,7 +172,7 @@ fn format_security_policy(policy_uri: &str) -> String {
         "http://opcfoundation.org/UA/SecurityPolicy#Basic256Sha256" => "Basic256Sha256".bright_green().to_string(),
         "http://opcfoundation.org/UA/SecurityPolicy#Aes128_Sha256_RsaOaep" => "Aes128Sha256RsaOaep".cyan().to_string(),
         "http://opcfoundation.org/UA/SecurityPolicy#Aes256_Sha256_RsaPss" => "Aes256Sha256RsaPss".bright_cyan().to_string(),
-        _ => policy_uri.split('#').last().unwrap_or(policy_uri).to_string(),
+        _ => policy_uri.split('#').next_back().unwrap_or(policy_uri).to_string(),
     }
 }
 
@@ -59,20 +190,24 @@ fn format_user_tokens(tokens: &[UserTokenPolicy]) -> String {
         return "None".dimmed().to_string();
     }
     
-    let token_types: Vec<String> = tokens
-        .iter()
-        .map(|token| match token.token_type {
-            UserTokenType::Anonymous => "Anonymous".to_string(),
-            UserTokenType::UserName => "Username".to_string(),
-            UserTokenType::Certificate => "Certificate".to_string(),
-            UserTokenType::IssuedToken => "IssuedToken".to_string(),
-        })
-        .collect();
+    let mut token_types: Vec<String> = Vec::new();
+    for token in tokens {
+        let name = match token.token_type {
+            UserTokenType::Anonymous => "Anonymous",
+            UserTokenType::UserName => "Username",
+            UserTokenType::Certificate => "Certificate",
+            UserTokenType::IssuedToken => "IssuedToken",
+        };
+        // Servers list one policy per password encryption or token type
+        if !token_types.iter().any(|known| known == name) {
+            token_types.push(name.to_string());
+        }
+    }
     
     token_types.join(", ")
 }
 
-fn format_application_type(app_type: ApplicationType) -> String {
+pub fn format_application_type(app_type: ApplicationType) -> String {
     match app_type {
         ApplicationType::Server => "Server",
         ApplicationType::Client => "Client", 
@@ -0,0 +1,258 @@
+use anyhow::{anyhow, Result};
+use clap::{Command, CommandFactory, Parser};
+use colored::*;
+
+use crate::types::Cli;
+
+/// Examples of each command shown below its `--help`, the rest only with `examples <command>`
+const EXAMPLES_IN_HELP: usize = 2;
+
+/// A runnable invocation of a command, kept as arguments so that the tests and `examples --check`
+/// can parse it with the command line definition
+pub struct Example {
+    /// Subcommand the example runs
+    pub command: &'static str,
+    /// Arguments after the subcommand, as the shell passes them
+    pub args: &'static [&'static str],
+    /// What the example does, in one line
+    pub description: &'static str,
+}
+
+const fn example(command: &'static str, args: &'static [&'static str], description: &'static str) -> Example {
+    Example { command, args, description }
+}
+
+/// The examples of all commands, grouped by command in the order of `--help`; the first ones of each
+/// command appear below its `--help`
+pub const EXAMPLES: &[Example] = &[
+    example("discover", &[], "List the security policies, modes and login types of the endpoints"),
+    example("discover", &["--server-cert", "--ca-file", "plant-ca.pem", "--save-cert", "server.der"], "Check the server certificate against a CA and save it"),
+    example("find-servers", &[], "List the servers registered at the discovery server at --endpoint"),
+    example("find-servers", &["--mdns", "--get-endpoints"], "Find servers on the local network and ask each of them for its endpoints"),
+    example("browse", &["--node", "ns=2;s=Plant", "--depth", "2"], "Browse two levels below a folder"),
+    example("browse", &["--classes", "variable", "--filter", "temperature", "--values"], "Show only Variables whose name contains \"temperature\", with their values"),
+    example("browse", &["--ids-only", "--classes", "variable", "--filter", "temperature"], "Print the matching node IDs only, one per line, for read --from-file"),
+    example("browse", &["--depth", "8", "--stream", "--json"], "Stream a deep browse as NDJSON with parent_id while it runs"),
+    example("browse", &["--sort", "name", "--compact"], "Compact tree in a stable order, for diffing servers"),
+    example("browse", &["--from-snapshot", "plant.snapshot.json", "--values"], "Browse a snapshot taken with export --snapshot, without a server"),
+    example("read", &["ns=2;s=Temperature", "-V", "--show-types"], "Read a node with its value and the type the value arrived as"),
+    example("read", &["--search", "Temperature", "--top", "5"], "Search nodes by display name and read the five best matches"),
+    example("read", &["--search", "--first", "BoilerTemperature"], "Stop searching at the first match"),
+    example("read", &["ns=3;s=Axis{1..4}.Position", "-V"], "Read the positions of four axes, braces expand to one node each"),
+    example("read", &["ns=3;s=Prg.Cfg;Rev=2", "ns=3;s=\" Padded \"", "-V"], "Read string identifiers with semicolons and, quoted, with surrounding spaces"),
+    example("read", &["ns=2;s=Temperature", "-V", "--convert", "°F"], "Show a value converted by its EngineeringUnits"),
+    example("read", &["ns=2;s=Temperature", "--all-attributes=full", "--from-snapshot", "plant.snapshot.json"], "Read every attribute of a node from a snapshot, offline"),
+    example("call", &["AddNumbers", "--args", "5,10"], "Call a method by name with simple arguments, converted to the declared types"),
+    example("call", &["ns=2;s=ProcessData", "ns=2;s=DataObject", "--args", "[42, \"test\"]", "--json"], "Call with typed JSON arguments and print the result as JSON"),
+    example("call", &["Open", "--on-type", "ns=2;s=ValveType", "--dry-run"], "List the calls of a method on every instance of an ObjectType"),
+    example("write", &["ns=2;s=Setpoint", "42.5"], "Write a value, converted to the node's DataType"),
+    example("write", &["ns=2;s=Watchdog", "--heartbeat", "1s"], "Keep a watchdog alive until Ctrl+C"),
+    example("write", &["ns=2;s=Recipe", "7,8", "--index-range", "2:3"], "Write two elements of an array"),
+    example("monitor", &["ns=2;s=Temperature", "--interval", "200", "--duration", "1m"], "Print the changes of a node for one minute, sampled every 200 ms"),
+    example("monitor", &["ns=2;s=Temperature", "--endpoint", "a=opc.tcp://plc-a:4840", "--endpoint", "b=opc.tcp://plc-b:4840", "--compare", "--tolerance", "0.5"], "Compare a node on two servers, ignoring differences up to 0.5"),
+    example("monitor", &["ns=2;s=Line1.{Speed,Torque}", "--archive", "runs/soak", "--archive-compress"], "Archive every change as gzipped NDJSON files, a new one every hour"),
+    example("monitor", &["ns=2;s=Temperature", "--influx-lp", "-"], "Write the changes as InfluxDB line protocol to stdout"),
+    example("refs", &["ns=2;s=Temperature"], "List the references of a node in both directions"),
+    example("refs", &["i=14533", "--encodings"], "Show the encodings of a DataType"),
+    example("info", &[], "Show the server status, the endpoint and security in use and the namespaces"),
+    example("info", &["--subscriptions", "--profile-detect"], "Also list the server's subscriptions and the profile facets it appears to support"),
+    example("fleet", &["opc.tcp://plc-a:4840", "opc.tcp://plc-b:4840", "--node", "ns=2;s=FirmwareVersion"], "Compare two servers and a node of each"),
+    example("fleet", &["--file", "plant.txt", "--concurrency", "8", "--json"], "Probe the endpoints listed in a file, eight at a time, as JSON"),
+    example("export", &["--nodeset2", "model.xml", "--node", "ns=2;s=Plant"], "Export the instance nodes below a folder as NodeSet2 XML"),
+    example("export", &["--nodeset2", "model.xml", "--emit-schema", "values.schema.json"], "Also write a JSON Schema of the exported values"),
+    example("export", &["--snapshot", "plant.snapshot.json", "--node", "ns=2;s=Plant", "--depth", "5"], "Take a snapshot of a folder to browse and read offline"),
+    example("lint", &[], "Check the instance nodes below Objects for modelling problems"),
+    example("lint", &["--rules", "abstract-data-type,duplicate-browse-name", "--json"], "Run two of the rules and print the findings as JSON"),
+    example("assert", &["interface.json", "--generate", "--node", "ns=2;s=Plant"], "Write a spec of the nodes below a folder"),
+    example("assert", &["interface.json"], "Check a server against the spec, failing when nodes are missing or differ"),
+    example("permissions", &["ns=2;s=Setpoint"], "Show what the session's user may do with a node"),
+    example("permissions", &["--node", "ns=2;s=Plant", "--json"], "Check the nodes below a folder, as JSON"),
+    example("stale-scan", &["--window", "1m", "--samples", "6"], "Report the variables that did not change within a minute"),
+    example("stale-scan", &["--node", "ns=2;s=Plant", "--exclude", "*Config*", "--min-change", "0.01"], "Skip configuration nodes and ignore numeric noise"),
+    example("backup", &["ns=2;s=Drive1", "--output", "drive1.json"], "Save the writable values below a node"),
+    example("restore", &["drive1.json", "--dry-run"], "Show what a restore would write"),
+    example("restore", &["drive1.json", "--node", "ns=2;s=Drive2"], "Restore the values below another node"),
+    example("copy", &["ns=2;s=Recipes", "--to", "opc.tcp://line2:4840", "--dry-run"], "Show which values a copy to another server would write"),
+    example("report", &["--output", "support.json"], "Write a JSON health report for a support case"),
+    example("bench", &["--duration", "30s", "--batch", "100", "--concurrency", "4"], "Measure reads per second with four batches of 100 in flight"),
+    example("bench", &["--write", "ns=2;s=Scratch", "--duration", "10s"], "Measure writes per second on a scratch variable"),
+    example("replay", &["runs/soak/monitor-20250301-140000-0001.ndjson.gz"], "Print a monitor archive without connecting"),
+    example("tui", &["--node", "ns=2;s=Plant"], "Browse interactively, starting at a folder"),
+    example("tui", &["--interval", "500ms", "--highlight", "5s"], "Refresh the values on screen twice a second and highlight changes for five seconds"),
+    example("examples", &["monitor"], "Show all examples of a command"),
+    example("examples", &["--check"], "Parse every example, failing when one no longer does"),
+];
+
+pub struct ExamplesArgs {
+    pub command: Option<String>,
+    pub check: bool,
+}
+
+pub fn validate(command: Option<&str>, check: bool) -> Result<ExamplesArgs> {
+    if let Some(command) = command
+        && !EXAMPLES.iter().any(|example| example.command == command)
+    {
+        let mut commands: Vec<&str> = EXAMPLES.iter().map(|example| example.command).collect();
+        commands.dedup();
+        return Err(anyhow!("There are no examples of '{}', commands with examples: {}", command, commands.join(", ")));
+    }
+    Ok(ExamplesArgs { command: command.map(str::to_string), check })
+}
+
+/// Print the examples, or with `--check` parse them instead
+pub fn execute(args: &ExamplesArgs) -> Result<()> {
+    let selected: Vec<&Example> = EXAMPLES
+        .iter()
+        .filter(|example| args.command.as_deref().is_none_or(|command| example.command == command))
+        .collect();
+
+    if args.check {
+        return check(&selected, args.command.is_none());
+    }
+
+    println!("\n{}", "📚 Examples".bright_cyan().bold());
+    let mut previous = None;
+    for example in selected {
+        if previous != Some(example.command) {
+            println!("\n{}", example.command.bright_white().bold());
+            previous = Some(example.command);
+        }
+        println!("  {}", format!("# {}", example.description).dimmed());
+        println!("  {}", example.command_line());
+    }
+    Ok(())
+}
+
+/// Parse the examples with the command line definition; with `all`, also fail on commands without
+/// examples
+fn check(examples: &[&Example], all: bool) -> Result<()> {
+    let mut failures = 0;
+    for example in examples {
+        let argv = std::iter::once("opcua-walker").chain(std::iter::once(example.command)).chain(example.args.iter().copied());
+        if let Err(e) = Cli::try_parse_from(argv) {
+            failures += 1;
+            let error = e.render().to_string();
+            let reason = error.lines().next().unwrap_or_default().trim_start_matches("error: ");
+            println!("❌ {}: {}", example.command_line(), reason.red());
+        }
+    }
+
+    let mut missing = Vec::new();
+    if all {
+        for subcommand in Cli::command().get_subcommands() {
+            let name = subcommand.get_name();
+            if name != "help" && !EXAMPLES.iter().any(|example| example.command == name) {
+                missing.push(name.to_string());
+            }
+        }
+    }
+    for name in &missing {
+        println!("❌ {}: no examples", name.bright_white());
+    }
+
+    if failures > 0 || !missing.is_empty() {
+        return Err(anyhow!(
+            "{} of {} examples no longer parse, {} commands have no examples",
+            failures, examples.len(), missing.len()
+        ));
+    }
+    println!("✅ All {} examples parse", examples.len());
+    Ok(())
+}
+
+impl Example {
+    /// The example as typed into a shell, arguments quoted where the shell would change them
+    pub fn command_line(&self) -> String {
+        let mut line = format!("opcua-walker {}", self.command);
+        for arg in self.args {
+            line.push(' ');
+            line.push_str(&shell_quote(arg));
+        }
+        line
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_alphanumeric() || "-_./:=,@%+°".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else if !arg.contains(['"', '$', '`', '\\', '!']) {
+        format!("\"{}\"", arg)
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Add the first examples of every subcommand to its `--help`
+pub fn with_examples(mut command: Command) -> Command {
+    let names: Vec<String> = command.get_subcommands().map(|subcommand| subcommand.get_name().to_string()).collect();
+    for name in names {
+        let examples: Vec<&Example> = EXAMPLES.iter().filter(|example| example.command == name).collect();
+        if examples.is_empty() {
+            continue;
+        }
+        let mut help = String::from("Examples:");
+        for example in examples.iter().take(EXAMPLES_IN_HELP) {
+            help.push_str(&format!("\n  # {}\n  {}", example.description, example.command_line()));
+        }
+        if examples.len() > EXAMPLES_IN_HELP {
+            help.push_str(&format!("\n\nMore examples: opcua-walker examples {}", name));
+        }
+        command = command.mut_subcommand(name, |subcommand| subcommand.after_help(help));
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_example_parses() {
+        for example in EXAMPLES {
+            let argv = std::iter::once("opcua-walker").chain(std::iter::once(example.command)).chain(example.args.iter().copied());
+            if let Err(e) = Cli::try_parse_from(argv) {
+                panic!("{} does not parse: {}", example.command_line(), e.render());
+            }
+        }
+    }
+
+    #[test]
+    fn every_command_has_examples() {
+        for subcommand in Cli::command().get_subcommands() {
+            let name = subcommand.get_name();
+            assert!(
+                EXAMPLES.iter().any(|example| example.command == name),
+                "{} has no examples", name
+            );
+        }
+    }
+
+    #[test]
+    fn examples_of_a_command_are_together() {
+        let mut seen: Vec<&str> = Vec::new();
+        for example in EXAMPLES {
+            if seen.last() != Some(&example.command) {
+                assert!(!seen.contains(&example.command), "examples of {} are not together", example.command);
+                seen.push(example.command);
+            }
+        }
+    }
+
+    #[test]
+    fn help_shows_examples() {
+        let mut command = with_examples(Cli::command());
+        command.build();
+        let help = command.find_subcommand_mut("browse").unwrap().render_long_help().to_string();
+        assert!(help.contains("Examples:"));
+        assert!(help.contains(&EXAMPLES.iter().find(|example| example.command == "browse").unwrap().command_line()));
+    }
+
+    #[test]
+    fn arguments_are_quoted_for_the_shell() {
+        assert_eq!(shell_quote("ns=2;s=Temperature"), "\"ns=2;s=Temperature\"");
+        assert_eq!(shell_quote("--depth"), "--depth");
+        assert_eq!(shell_quote("[42, \"test\"]"), "'[42, \"test\"]'");
+        assert_eq!(shell_quote(""), "\"\"");
+    }
+}
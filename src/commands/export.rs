@@ -0,0 +1,425 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use serde_json::Map;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use tracing::{debug, warn};
+
+use crate::client::OpcUaClient;
+use crate::session::ServiceSession;
+use crate::utils::browse_request::BrowseRequest;
+use crate::utils::formatter::format_node_id;
+use crate::utils::guard::{self, Operations};
+use crate::utils::limits::descends;
+use crate::utils::namespace::parse_node_id;
+use crate::utils::nodeset::{write_nodeset, NodeSetNode, NodeSetReference, NodeSetVariable};
+use crate::utils::schema::{value_schema, values_schema, write_schema, ValueType};
+use crate::utils::snapshot::{self, SnapshotEntry, MAX_ATTRIBUTE_ID};
+
+/// Number of nodes browsed or read in a single service call
+pub const BATCH_SIZE: usize = 100;
+
+/// Nodes whose attributes a snapshot reads per request, all 27 of them each
+const SNAPSHOT_READ_NODES: usize = BATCH_SIZE / 4;
+
+pub struct ExportArgs {
+    pub start_node_id: NodeId,
+    pub max_depth: u32,
+    pub nodeset2: Option<String>,
+    /// Where to write the JSON Schema of the variables' values
+    pub emit_schema: Option<String>,
+    /// Where to write a snapshot for offline browsing and reading
+    pub snapshot: Option<String>,
+}
+
+pub fn validate(
+    node: Option<&str>,
+    max_depth: u32,
+    nodeset2: Option<&str>,
+    emit_schema: Option<&str>,
+    snapshot: Option<&str>,
+) -> Result<ExportArgs> {
+    let start_node_id = if let Some(node_str) = node {
+        parse_node_id(node_str)?
+    } else {
+        ObjectId::ObjectsFolder.into()
+    };
+
+    if let Some(path) = nodeset2.into_iter().chain(snapshot).find(|path| Path::new(path).is_dir()) {
+        return Err(anyhow!("Output path is a directory: {}", path));
+    }
+    if let Some(path) = emit_schema.filter(|path| Path::new(path).is_dir()) {
+        return Err(anyhow!("Schema path is a directory: {}", path));
+    }
+
+    Ok(ExportArgs {
+        start_node_id,
+        max_depth,
+        nodeset2: nodeset2.map(String::from),
+        emit_schema: emit_schema.map(String::from),
+        snapshot: snapshot.map(String::from),
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &ExportArgs) -> Result<()> {
+    let session = client.session()?;
+
+    println!("\n{}", "📦 Exporting OPC-UA Address Space".bright_cyan().bold());
+    println!("📍 Starting node: {}", format_node_id(&args.start_node_id).bright_white());
+    println!("📏 Max depth: {}", args.max_depth.to_string().bright_white());
+    println!("{}", "─".repeat(60));
+
+    if let Some(path) = &args.snapshot {
+        export_snapshot(session, client.endpoint(), args, path).await?;
+    }
+    match &args.nodeset2 {
+        Some(path) => export_nodeset(client, args, path).await,
+        None => Ok(()),
+    }
+}
+
+async fn export_nodeset(client: &OpcUaClient, args: &ExportArgs, path: &str) -> Result<()> {
+    let session = client.session()?;
+
+    // Translated through --ns-map so exports of differently registered servers line up
+    let namespaces: Vec<String> = client.namespaces().await?
+        .iter()
+        .map(|uri| client.ns_map().map_uri(uri).to_string())
+        .collect();
+    let mut nodes = browse_instances(session, &args.start_node_id, args.max_depth).await?;
+
+    if nodes.is_empty() {
+        println!("⚠️  No nodes outside the OPC UA namespace found");
+        return Ok(());
+    }
+
+    let variables = nodes.iter().filter(|node| node.node_class == NodeClass::Variable).count();
+    guard::confirm(&[Operations::Reads(nodes.len() + variables * 5)])?;
+    read_node_attributes(session, &mut nodes).await?;
+
+    let xml = write_nodeset(&namespaces, &nodes);
+    fs::write(path, xml)
+        .map_err(|e| anyhow!("Failed to write {}: {}", path, e))?;
+
+    let count = |class: NodeClass| nodes.iter().filter(|n| n.node_class == class).count();
+    println!("📁 Objects: {}", count(NodeClass::Object).to_string().bright_white());
+    println!("📊 Variables: {}", count(NodeClass::Variable).to_string().bright_white());
+    println!("⚙️  Methods: {}", count(NodeClass::Method).to_string().bright_white());
+    println!("\n✅ {} {}", "NodeSet2 written to".green(), path.bright_white());
+
+    if let Some(path) = &args.emit_schema {
+        let mut values = Map::new();
+        for node in &nodes {
+            if let Some(variable) = &node.variable {
+                let value_type = ValueType { data_type: Some(variable.data_type.clone()), value_rank: variable.value_rank };
+                values.insert(format_node_id(&node.node_id), value_schema(session, &value_type).await);
+            }
+        }
+        let count = values.len();
+        write_schema(path, &values_schema(values))?;
+        println!("✅ {} {} ({} variable(s))", "Schema written to".green(), path.bright_white(), count);
+    }
+    Ok(())
+}
+
+/// Write a snapshot of the nodes below the start node, of the reference types, which browsing
+/// offline needs to follow subtypes, and of the DataTypes of the variables
+async fn export_snapshot(session: &ServiceSession, endpoint: &str, args: &ExportArgs, path: &str) -> Result<()> {
+    let mut crawl = SnapshotCrawl::default();
+    crawl.run(session, std::slice::from_ref(&args.start_node_id), args.max_depth, ReferenceTypeId::HierarchicalReferences).await?;
+    crawl.run(session, &[ReferenceTypeId::References.into()], u32::MAX, ReferenceTypeId::HasSubtype).await?;
+    crawl.reach(&VariableId::Server_NamespaceArray.into());
+    guard::confirm(&[Operations::Reads(crawl.entries.len() * MAX_ATTRIBUTE_ID as usize)])?;
+    crawl.read_attributes(session).await?;
+
+    // The DataTypes with their properties, e.g. EnumStrings, so that values show like online
+    let data_types: Vec<NodeId> = crawl
+        .entries
+        .iter()
+        .flat_map(|entry| &entry.attributes)
+        .filter_map(|(id, dv)| match (*id == AttributeId::DataType as u32, &dv.value) {
+            (true, Some(Variant::NodeId(data_type))) => Some((**data_type).clone()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    crawl.run(session, &data_types, 0, ReferenceTypeId::HasProperty).await?;
+    crawl.read_attributes(session).await?;
+
+    snapshot::write(path, endpoint, &args.start_node_id, args.max_depth, &crawl.entries)?;
+    let browsed = crawl.entries.iter().filter(|entry| entry.references.is_some()).count();
+    println!("🗂️  Snapshot nodes: {} ({} browsed)", crawl.entries.len().to_string().bright_white(), browsed);
+    println!("✅ {} {}", "Snapshot written to".green(), path.bright_white());
+    Ok(())
+}
+
+/// Nodes collected for a snapshot: every node reached gets its attributes, the browsed ones
+/// also their references in both directions
+#[derive(Default)]
+struct SnapshotCrawl {
+    entries: Vec<SnapshotEntry>,
+    index: HashMap<NodeId, usize>,
+    /// Entries up to here have their attributes read
+    read: usize,
+}
+
+impl SnapshotCrawl {
+    fn reach(&mut self, node_id: &NodeId) -> usize {
+        if let Some(&index) = self.index.get(node_id) {
+            return index;
+        }
+        self.index.insert(node_id.clone(), self.entries.len());
+        self.entries.push(SnapshotEntry { node_id: node_id.clone(), attributes: Vec::new(), references: None });
+        self.entries.len() - 1
+    }
+
+    /// Breadth-first from `roots` along `follow` and its subtypes, browsing nodes up to `max_depth`
+    /// levels below them
+    async fn run(&mut self, session: &ServiceSession, roots: &[NodeId], max_depth: u32, follow: ReferenceTypeId) -> Result<()> {
+        let mut queued: HashSet<NodeId> = roots.iter().cloned().collect();
+        let mut queue: VecDeque<(NodeId, u32)> = roots.iter().map(|root| (root.clone(), 0)).collect();
+
+        while !queue.is_empty() {
+            let batch: Vec<(NodeId, u32)> = queue.drain(..queue.len().min(BATCH_SIZE)).collect();
+            let descriptions: Vec<BrowseDescription> = batch
+                .iter()
+                .flat_map(|(node_id, _)| {
+                    [
+                        BrowseRequest::new(node_id, BrowseDirection::Both, ReferenceTypeId::References).build(),
+                        BrowseRequest::new(node_id, BrowseDirection::Forward, follow).build(),
+                    ]
+                })
+                .collect();
+            let mut results = browse_complete(session, &descriptions).await?.into_iter();
+
+            for (node_id, depth) in batch {
+                let (Some(references), Some(children)) = (results.next(), results.next()) else {
+                    return Err(anyhow!("The server returned fewer browse results than requested"));
+                };
+                let index = self.reach(&node_id);
+                match references {
+                    Ok(references) => self.entries[index].references = Some(references),
+                    Err(status) => warn!("Browse failed for node {}: {}", format_node_id(&node_id), status),
+                }
+                for child in children.unwrap_or_default() {
+                    let child = child.node_id.node_id;
+                    let child_index = self.reach(&child);
+                    if descends(depth, max_depth) && self.entries[child_index].references.is_none() && queued.insert(child.clone()) {
+                        queue.push_back((child, depth + 1));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read every attribute of the entries reached since the last call
+    async fn read_attributes(&mut self, session: &ServiceSession) -> Result<()> {
+        for chunk in self.entries[self.read..].chunks_mut(SNAPSHOT_READ_NODES) {
+            let requests: Vec<ReadValueId> = chunk
+                .iter()
+                .flat_map(|entry| (1..=MAX_ATTRIBUTE_ID).map(|id| ReadValueId { attribute_id: id, ..ReadValueId::from(&entry.node_id) }))
+                .collect();
+            let mut values = session.read(&requests, TimestampsToReturn::Both, 0.0).await?.into_iter();
+            for entry in chunk {
+                for id in 1..=MAX_ATTRIBUTE_ID {
+                    let Some(value) = values.next() else {
+                        return Err(anyhow!("The server returned fewer values than requested"));
+                    };
+                    // Attributes of other node classes are left out, so offline they are invalid as well
+                    if value.status != Some(StatusCode::BadAttributeIdInvalid) {
+                        entry.attributes.push((id, value));
+                    }
+                }
+            }
+        }
+        self.read = self.entries.len();
+        Ok(())
+    }
+}
+
+/// Browse `descriptions`, following continuation points until every result is complete
+async fn browse_complete(
+    session: &ServiceSession,
+    descriptions: &[BrowseDescription],
+) -> Result<Vec<Result<Vec<ReferenceDescription>, StatusCode>>> {
+    let results = session.browse(descriptions, 0, None).await?;
+    let mut complete = Vec::with_capacity(results.len());
+    for mut result in results {
+        let mut references = Vec::new();
+        loop {
+            if !result.status_code.is_good() {
+                complete.push(Err(result.status_code));
+                break;
+            }
+            references.extend(result.references.take().unwrap_or_default());
+            if result.continuation_point.is_null_or_empty() {
+                complete.push(Ok(references));
+                break;
+            }
+            let next = session.browse_next(false, &[result.continuation_point]).await?;
+            result = next.into_iter().next().ok_or_else(|| anyhow!("The server returned no browse result"))?;
+        }
+    }
+    Ok(complete)
+}
+
+/// Breadth-first browse of hierarchical references, keeping every parent of shared
+/// nodes and skipping nodes of the OPC UA namespace itself
+pub async fn browse_instances(session: &ServiceSession, start: &NodeId, max_depth: u32) -> Result<Vec<NodeSetNode>> {
+    let mut nodes: Vec<NodeSetNode> = Vec::new();
+    let mut index: HashMap<NodeId, usize> = HashMap::new();
+    let mut visited = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([(start.clone(), 0u32)]);
+
+    while !queue.is_empty() {
+        let batch: Vec<(NodeId, u32)> = queue.drain(..queue.len().min(BATCH_SIZE)).collect();
+        let browse_requests: Vec<BrowseDescription> = batch
+            .iter()
+            .map(|(node_id, _)| BrowseRequest::new(node_id, BrowseDirection::Forward, ReferenceTypeId::HierarchicalReferences).build())
+            .collect();
+
+        let results = session.browse(&browse_requests, 0, None).await?;
+
+        for ((parent, depth), result) in batch.iter().zip(results) {
+            if !result.status_code.is_good() {
+                warn!("Browse failed for node {}: {}", format_node_id(parent), result.status_code);
+                continue;
+            }
+
+            for reference in result.references.unwrap_or_default() {
+                let child = reference.node_id.node_id.clone();
+                if child.namespace == 0 {
+                    continue;
+                }
+
+                let parent_reference = NodeSetReference {
+                    reference_type: reference.reference_type_id.clone(),
+                    target: parent.clone(),
+                    is_forward: false,
+                };
+
+                if let Some(&existing) = index.get(&child) {
+                    nodes[existing].references.push(parent_reference);
+                    continue;
+                }
+
+                let mut references = vec![parent_reference];
+                if !reference.type_definition.is_null() {
+                    references.push(NodeSetReference {
+                        reference_type: ReferenceTypeId::HasTypeDefinition.into(),
+                        target: reference.type_definition.node_id.clone(),
+                        is_forward: true,
+                    });
+                }
+
+                let is_child_of = reference.reference_type_id == ReferenceTypeId::HasComponent
+                    || reference.reference_type_id == ReferenceTypeId::HasProperty;
+
+                index.insert(child.clone(), nodes.len());
+                nodes.push(NodeSetNode {
+                    node_id: child.clone(),
+                    node_class: reference.node_class,
+                    browse_name: reference.browse_name.clone(),
+                    display_name: reference.display_name.clone(),
+                    description: None,
+                    parent_node_id: is_child_of.then(|| parent.clone()),
+                    references,
+                    variable: None,
+                });
+
+                if descends(*depth, max_depth) && visited.insert(child.clone()) {
+                    queue.push_back((child, depth + 1));
+                }
+            }
+        }
+    }
+
+    let skipped = nodes.iter()
+        .filter(|n| !matches!(n.node_class, NodeClass::Object | NodeClass::Variable | NodeClass::Method))
+        .count();
+    if skipped > 0 {
+        debug!("Skipping {} node(s) that are not objects, variables or methods", skipped);
+    }
+    nodes.retain(|n| matches!(n.node_class, NodeClass::Object | NodeClass::Variable | NodeClass::Method));
+
+    Ok(nodes)
+}
+
+/// Fill in Description for all nodes and the variable attributes for variables
+pub async fn read_node_attributes(session: &ServiceSession, nodes: &mut [NodeSetNode]) -> Result<()> {
+    const VARIABLE_ATTRIBUTES: [AttributeId; 5] = [
+        AttributeId::DataType,
+        AttributeId::ValueRank,
+        AttributeId::ArrayDimensions,
+        AttributeId::AccessLevel,
+        AttributeId::Value,
+    ];
+
+    for chunk in nodes.chunks_mut(BATCH_SIZE) {
+        let mut read_requests = Vec::new();
+        for node in chunk.iter() {
+            read_requests.push(attribute_request(&node.node_id, AttributeId::Description));
+            if node.node_class == NodeClass::Variable {
+                read_requests.extend(VARIABLE_ATTRIBUTES.iter().map(|attr| attribute_request(&node.node_id, *attr)));
+            }
+        }
+
+        let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+        let mut results = results.into_iter();
+
+        for node in chunk.iter_mut() {
+            if let Some(DataValue { value: Some(Variant::LocalizedText(text)), .. }) = results.next() {
+                node.description = Some(*text);
+            }
+
+            if node.node_class != NodeClass::Variable {
+                continue;
+            }
+
+            let values: Vec<Option<Variant>> = results.by_ref()
+                .take(VARIABLE_ATTRIBUTES.len())
+                .map(|dv| dv.value)
+                .collect();
+
+            node.variable = Some(NodeSetVariable {
+                data_type: match values.first() {
+                    Some(Some(Variant::NodeId(data_type))) => (**data_type).clone(),
+                    _ => DataTypeId::BaseDataType.into(),
+                },
+                value_rank: match values.get(1) {
+                    Some(Some(Variant::Int32(rank))) => *rank,
+                    _ => -1,
+                },
+                array_dimensions: match values.get(2) {
+                    Some(Some(Variant::Array(array))) => Some(array.values.iter()
+                        .filter_map(|v| match v {
+                            Variant::UInt32(d) => Some(*d),
+                            _ => None,
+                        })
+                        .collect()),
+                    _ => None,
+                },
+                access_level: match values.get(3) {
+                    Some(Some(Variant::Byte(level))) => *level,
+                    _ => 1,
+                },
+                value: values.get(4).cloned().flatten(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub fn attribute_request(node_id: &NodeId, attribute: AttributeId) -> ReadValueId {
+    ReadValueId {
+        node_id: node_id.clone(),
+        attribute_id: attribute as u32,
+        index_range: NumericRange::None,
+        data_encoding: QualifiedName::null(),
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::ApplicationDescription;
+use std::time::Duration;
+use tabled::{Table, Tabled};
+use tracing::debug;
+
+use crate::client::OpcUaClient;
+use crate::commands::discover::{endpoint_matrix, format_application_type};
+use crate::types::Cli;
+use crate::utils::formatter::{localized_text, sanitize_text};
+use crate::utils::mdns::{self, MdnsServer, OPCUA_SERVICE};
+use crate::utils::time::format_duration;
+
+#[derive(Tabled)]
+struct AnnouncedServer {
+    #[tabled(rename = "Instance")]
+    instance: String,
+    #[tabled(rename = "Host")]
+    hostname: String,
+    #[tabled(rename = "Address")]
+    addresses: String,
+    #[tabled(rename = "Port")]
+    port: u16,
+    #[tabled(rename = "Capabilities")]
+    capabilities: String,
+}
+
+#[derive(Tabled)]
+struct KnownServer {
+    #[tabled(rename = "Application")]
+    name: String,
+    #[tabled(rename = "Type")]
+    application_type: String,
+    #[tabled(rename = "Application URI")]
+    uri: String,
+    #[tabled(rename = "Discovery URLs")]
+    discovery_urls: String,
+}
+
+pub struct FindServersArgs {
+    /// Listen for mDNS announcements this long instead of asking the discovery server
+    pub mdns: Option<Duration>,
+    pub get_endpoints: bool,
+}
+
+pub fn validate(mdns: bool, duration: Duration, get_endpoints: bool) -> Result<FindServersArgs> {
+    if duration.is_zero() {
+        return Err(anyhow!("--duration must be greater than 0"));
+    }
+    Ok(FindServersArgs {
+        mdns: mdns.then_some(duration),
+        get_endpoints,
+    })
+}
+
+/// List the servers a discovery server knows, or the ones announcing themselves on the local
+/// network. No session is opened, so this runs before connecting.
+pub async fn execute(cli: &Cli, args: &FindServersArgs) -> Result<()> {
+    let urls = match args.mdns {
+        Some(duration) => find_on_network(duration).await?,
+        None => find_via_discovery_server(cli).await?,
+    };
+
+    if args.get_endpoints && !urls.is_empty() {
+        get_endpoints(cli, &urls).await;
+    }
+    Ok(())
+}
+
+async fn find_on_network(duration: Duration) -> Result<Vec<String>> {
+    println!("\n{}", "📡 Finding Servers via mDNS".bright_cyan().bold());
+    println!("🔎 Service: {}", OPCUA_SERVICE.bright_white());
+    println!("⏱️  Listening: {}", format_duration(duration));
+    println!("{}", "─".repeat(50));
+
+    let servers = mdns::browse(duration).await?;
+    if servers.is_empty() {
+        println!("⚠️  No server announced itself");
+        println!("   • Servers only announce via mDNS when configured to, many rely on an LDS instead");
+        println!("   • Routers and VPNs usually do not forward multicast, the server must be on this network");
+        return Ok(Vec::new());
+    }
+
+    let rows: Vec<AnnouncedServer> = servers
+        .iter()
+        .map(|server| AnnouncedServer {
+            instance: sanitize_text(&server.instance).into_owned(),
+            hostname: sanitize_text(&server.hostname).into_owned(),
+            addresses: server.addresses.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+            port: server.port,
+            capabilities: if server.capabilities.is_empty() {
+                "—".dimmed().to_string()
+            } else {
+                server.capabilities.join(", ")
+            },
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+    println!("\n✅ {} server(s) announced", servers.len().to_string().bright_green());
+
+    Ok(servers.iter().map(MdnsServer::url).collect())
+}
+
+async fn find_via_discovery_server(cli: &Cli) -> Result<Vec<String>> {
+    println!("\n{}", "📡 Finding Servers via FindServers".bright_cyan().bold());
+    println!("🖥️  Discovery server: {}", cli.endpoint.bright_white());
+    println!("{}", "─".repeat(50));
+
+    let servers = OpcUaClient::for_endpoint(cli, &cli.endpoint)?.find_servers().await?;
+    if servers.is_empty() {
+        println!("⚠️  The discovery server knows no servers");
+        return Ok(Vec::new());
+    }
+
+    let rows: Vec<KnownServer> = servers.iter().map(known_server).collect();
+    println!("{}", Table::new(rows));
+    println!("\n✅ {} server(s) registered", servers.len().to_string().bright_green());
+
+    let mut urls = Vec::new();
+    for server in &servers {
+        match server.discovery_urls.iter().flatten().find(|url| url.as_ref().starts_with("opc.tcp://")) {
+            Some(url) => urls.push(url.to_string()),
+            None => debug!("{} has no opc.tcp discovery URL", server.application_uri),
+        }
+    }
+    Ok(urls)
+}
+
+fn known_server(server: &ApplicationDescription) -> KnownServer {
+    KnownServer {
+        name: sanitize_text(localized_text(&server.application_name)).into_owned(),
+        application_type: format_application_type(server.application_type),
+        uri: server.application_uri.to_string(),
+        discovery_urls: server.discovery_urls
+            .iter()
+            .flatten()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Ask each server for its endpoints. Servers that refuse stay in the list above, only with
+/// a warning here.
+async fn get_endpoints(cli: &Cli, urls: &[String]) {
+    for url in urls {
+        println!("\n🔐 {}", url.bright_white());
+        let endpoints = match OpcUaClient::for_endpoint(cli, url) {
+            Ok(client) => client.server_endpoints().await,
+            Err(e) => Err(e),
+        };
+        match endpoints {
+            Ok(endpoints) if endpoints.is_empty() => println!("⚠️  The server offers no endpoints"),
+            Ok(endpoints) => println!("{}", endpoint_matrix(&endpoints)),
+            Err(e) => println!("⚠️  {}: {}", "GetEndpoints failed".yellow(), e),
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Arc;
+use tabled::{Table, Tabled};
+use tokio::sync::Semaphore;
+use tracing::debug;
+
+use crate::client::OpcUaClient;
+use crate::session::ServiceStats;
+use crate::types::Cli;
+use crate::utils::formatter::{format_variant, localized_text};
+use crate::utils::namespace::{parse_namespace_array, parse_node_spec, NamespaceMap, NodeSpec};
+use crate::utils::security::ConnectionInfo;
+
+#[derive(Tabled)]
+struct FleetRow {
+    #[tabled(rename = "Endpoint")]
+    endpoint: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "State")]
+    state: String,
+    #[tabled(rename = "Product")]
+    product: String,
+    #[tabled(rename = "Version")]
+    version: String,
+    #[tabled(rename = "Namespaces")]
+    namespaces: String,
+    #[tabled(rename = "Value")]
+    value: String,
+}
+
+#[derive(Serialize)]
+struct ProbeResult {
+    endpoint: String,
+    ok: bool,
+    state: Option<String>,
+    product: Option<String>,
+    version: Option<String>,
+    namespace_count: Option<usize>,
+    namespace_hash: Option<String>,
+    value: Option<String>,
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<ConnectionInfo>,
+}
+
+impl ProbeResult {
+    fn failed(endpoint: &str, error: String) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            ok: false,
+            state: None,
+            product: None,
+            version: None,
+            namespace_count: None,
+            namespace_hash: None,
+            value: None,
+            error: Some(error),
+            security: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Mismatch {
+    field: &'static str,
+    values: BTreeMap<String, Vec<String>>,
+}
+
+type FieldExtractor = fn(&ProbeResult) -> Option<String>;
+
+#[derive(Serialize)]
+struct FleetReport {
+    total: usize,
+    reachable: usize,
+    endpoints: Vec<ProbeResult>,
+    mismatches: Vec<Mismatch>,
+}
+
+pub struct FleetArgs {
+    pub endpoints: Vec<String>,
+    pub concurrency: usize,
+    pub node: Option<NodeSpec>,
+    pub json: bool,
+}
+
+pub fn validate(
+    endpoints: &[String],
+    file: Option<&str>,
+    concurrency: usize,
+    node: Option<&str>,
+    json: bool,
+) -> Result<FleetArgs> {
+    let endpoints = collect_endpoints(endpoints, file)?;
+    if endpoints.is_empty() {
+        return Err(anyhow!("No endpoints provided (pass them as arguments or via --file)"));
+    }
+    if concurrency == 0 {
+        return Err(anyhow!("--concurrency must be at least 1"));
+    }
+
+    let node = node.map(parse_node_spec).transpose()?;
+
+    Ok(FleetArgs {
+        endpoints,
+        concurrency: concurrency.max(1),
+        node,
+        json,
+    })
+}
+
+pub async fn execute(cli: &Cli, args: &FleetArgs, stats: &Arc<ServiceStats>) -> Result<()> {
+    if !args.json {
+        println!("\n{}", "🛰️  OPC-UA Fleet Check".bright_cyan().bold());
+        println!("{}", "─".repeat(60));
+        println!("📡 Endpoints: {}", args.endpoints.len().to_string().bright_white());
+        println!("🔀 Concurrency: {}", args.concurrency.to_string().bright_white());
+        if let Some(node) = &args.node {
+            println!("📍 Probe node: {}", node.to_string().bright_white());
+        }
+        println!();
+    }
+
+    // Probe all endpoints, never letting one failure abort the others
+    let semaphore = Arc::new(Semaphore::new(args.concurrency));
+    let mut handles = Vec::new();
+
+    for endpoint in &args.endpoints {
+        let client = match OpcUaClient::for_endpoint(cli, endpoint) {
+            Ok(client) => client.with_stats(stats.clone()),
+            Err(e) => {
+                let failed = ProbeResult::failed(endpoint, e.to_string());
+                handles.push(tokio::spawn(async move { failed }));
+                continue;
+            }
+        };
+        let node = args.node.clone();
+        let semaphore = semaphore.clone();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            probe_endpoint(client, node.as_ref()).await
+        }));
+    }
+
+    let mut results = Vec::new();
+    for (handle, endpoint) in handles.into_iter().zip(&args.endpoints) {
+        let result = handle
+            .await
+            .unwrap_or_else(|e| ProbeResult::failed(endpoint, format!("Probe task failed: {}", e)));
+        results.push(result);
+    }
+
+    let mismatches = find_mismatches(&results, args.node.is_some());
+    let mismatch_count = mismatches.len();
+    let reachable = results.iter().filter(|r| r.ok).count();
+    let total = results.len();
+
+    if args.json {
+        let report = FleetReport {
+            total,
+            reachable,
+            endpoints: results,
+            mismatches,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        display_results(&results, &mismatches, args.node.is_some(), !cli.no_warnings);
+    }
+
+    if reachable < total {
+        return Err(anyhow!("{} of {} endpoints failed the probe", total - reachable, total));
+    }
+    if mismatch_count > 0 {
+        return Err(anyhow!("Fleet is inconsistent: {} field(s) differ", mismatch_count));
+    }
+
+    Ok(())
+}
+
+fn collect_endpoints(endpoints: &[String], file: Option<&str>) -> Result<Vec<String>> {
+    let mut list: Vec<String> = endpoints.to_vec();
+
+    if let Some(path) = file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read endpoint file {}: {}", path, e))?;
+
+        list.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from),
+        );
+    }
+
+    Ok(list)
+}
+
+async fn probe_endpoint(mut client: OpcUaClient, node: Option<&NodeSpec>) -> ProbeResult {
+    let endpoint = client.endpoint().to_string();
+
+    if let Err(e) = client.connect().await {
+        return ProbeResult::failed(&endpoint, e.to_string());
+    }
+
+    let result = match read_probe_values(&client, node).await {
+        Ok(result) => result,
+        Err(e) => ProbeResult::failed(&endpoint, e.to_string()),
+    };
+
+    if let Err(e) = client.disconnect().await {
+        debug!("Disconnect from {} failed: {}", endpoint, e);
+    }
+
+    result
+}
+
+async fn read_probe_values(client: &OpcUaClient, node: Option<&NodeSpec>) -> Result<ProbeResult> {
+    let session = client.session()?;
+
+    let mut read_requests: Vec<ReadValueId> = vec![
+        ReadValueId::from(&NodeId::from(VariableId::Server_ServerStatus_State)),
+        ReadValueId::from(&NodeId::from(VariableId::Server_ServerStatus_BuildInfo_ProductName)),
+        ReadValueId::from(&NodeId::from(VariableId::Server_ServerStatus_BuildInfo_SoftwareVersion)),
+        ReadValueId::from(&NodeId::from(VariableId::Server_NamespaceArray)),
+    ];
+    if let Some(node) = node {
+        read_requests.push(ReadValueId::from(&client.resolve_node(node).await?));
+    }
+
+    let results = session
+        .read(&read_requests, TimestampsToReturn::Neither, 0.0)
+        .await
+        .map_err(|e| anyhow!("Probe read failed: {}", e))?;
+
+    let state = results.first()
+        .and_then(|dv| dv.value.as_ref())
+        .and_then(|v| match v {
+            Variant::Int32(state) => Some(server_state_name(*state)),
+            Variant::UInt32(state) => Some(server_state_name(*state as i32)),
+            _ => None,
+        });
+
+    let namespaces: Option<Vec<String>> = results.get(3)
+        .and_then(|dv| dv.value.as_ref())
+        .and_then(parse_namespace_array);
+
+    let value = results.get(4).map(|dv| {
+        let status = dv.status.unwrap_or(StatusCode::Good);
+        if !status.is_good() {
+            format!("{}", status)
+        } else {
+            dv.value.as_ref()
+                .map(format_variant)
+                .unwrap_or_else(|| "null".to_string())
+        }
+    });
+
+    Ok(ProbeResult {
+        endpoint: client.endpoint().to_string(),
+        ok: true,
+        state,
+        product: string_value(results.get(1)),
+        version: string_value(results.get(2)),
+        namespace_count: namespaces.as_ref().map(Vec::len),
+        namespace_hash: namespaces.as_deref().map(|uris| namespace_fingerprint(uris, client.ns_map())),
+        value,
+        error: None,
+        security: client.connection_info().cloned(),
+    })
+}
+
+fn string_value(data_value: Option<&DataValue>) -> Option<String> {
+    data_value
+        .and_then(|dv| dv.value.as_ref())
+        .map(|v| match v {
+            Variant::String(s) => s.as_ref().to_string(),
+            Variant::LocalizedText(lt) => localized_text(lt).to_string(),
+            _ => format_variant(v),
+        })
+}
+
+fn server_state_name(state: i32) -> String {
+    match state {
+        0 => "Running",
+        1 => "Failed",
+        2 => "NoConfiguration",
+        3 => "Suspended",
+        4 => "Shutdown",
+        5 => "Test",
+        6 => "CommunicationFault",
+        _ => "Unknown",
+    }.to_string()
+}
+
+/// Stable FNV-1a fingerprint of the namespace table, so identical servers hash identically.
+///
+/// With a `--ns-map` the URIs are translated and compared regardless of their index.
+fn namespace_fingerprint(uris: &[String], ns_map: &NamespaceMap) -> String {
+    let mut uris: Vec<&str> = uris.iter().map(|uri| ns_map.map_uri(uri)).collect();
+    if !ns_map.is_empty() {
+        uris.sort_unstable();
+    }
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for uri in uris {
+        for byte in uri.bytes().chain(std::iter::once(b'\n')) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+fn find_mismatches(results: &[ProbeResult], compare_value: bool) -> Vec<Mismatch> {
+    let reachable: Vec<&ProbeResult> = results.iter().filter(|r| r.ok).collect();
+
+    let mut fields: Vec<(&'static str, FieldExtractor)> = vec![
+        ("state", |r| r.state.clone()),
+        ("product", |r| r.product.clone()),
+        ("version", |r| r.version.clone()),
+        ("namespace_hash", |r| r.namespace_hash.clone()),
+    ];
+    if compare_value {
+        fields.push(("value", |r| r.value.clone()));
+    }
+
+    fields
+        .into_iter()
+        .filter_map(|(field, extract)| {
+            let mut values: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for result in &reachable {
+                let value = extract(result).unwrap_or_else(|| "unavailable".to_string());
+                values.entry(value).or_default().push(result.endpoint.clone());
+            }
+
+            if values.len() > 1 {
+                Some(Mismatch { field, values })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn display_results(results: &[ProbeResult], mismatches: &[Mismatch], show_value: bool, warn_security: bool) {
+    let dash = || "—".dimmed().to_string();
+
+    let rows: Vec<FleetRow> = results
+        .iter()
+        .map(|r| FleetRow {
+            endpoint: r.endpoint.clone(),
+            status: if r.ok { "✅ OK".green().to_string() } else { "❌ Failed".red().to_string() },
+            state: r.state.clone().unwrap_or_else(dash),
+            product: r.product.clone().unwrap_or_else(dash),
+            version: r.version.clone().unwrap_or_else(dash),
+            namespaces: match (r.namespace_count, &r.namespace_hash) {
+                (Some(count), Some(hash)) => format!("{} ({})", count, hash),
+                _ => dash(),
+            },
+            value: if show_value { r.value.clone().unwrap_or_else(dash) } else { dash() },
+        })
+        .collect();
+
+    println!("{}", Table::new(rows));
+
+    let failures: Vec<&ProbeResult> = results.iter().filter(|r| !r.ok).collect();
+    if !failures.is_empty() {
+        println!("\n❌ {}", "Unreachable endpoints".red().bold());
+        for failure in failures {
+            println!("   • {}: {}", failure.endpoint.bright_white(),
+                     failure.error.as_deref().unwrap_or("unknown error"));
+        }
+    }
+
+    let weaker: Vec<(&str, String)> = results
+        .iter()
+        .filter_map(|r| Some((r.endpoint.as_str(), r.security.as_ref()?.warning()?)))
+        .collect();
+    if warn_security && !weaker.is_empty() {
+        println!();
+        for (endpoint, warning) in weaker {
+            println!("{} {}", format!("{}:", endpoint).bright_white(), warning);
+        }
+    }
+
+    if mismatches.is_empty() {
+        println!("\n✅ {}", "All reachable endpoints report identical values".green());
+    } else {
+        println!("\n⚠️  {}", "Mismatches across the fleet".bright_yellow().bold());
+        for mismatch in mismatches {
+            println!("   {}:", mismatch.field.bright_white());
+            for (value, endpoints) in &mismatch.values {
+                println!("      {} → {}", value.bright_cyan(), endpoints.join(", "));
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,98 +1,351 @@
 use anyhow::Result;
 use colored::*;
+use opcua::crypto::SecurityPolicy;
 use opcua::types::*;
+use std::collections::HashMap;
+use tabled::{Table, Tabled};
 use tracing::debug;
 
 use crate::client::OpcUaClient;
+use crate::session::ServiceSession;
+use crate::utils::formatter::{format_node_id, format_status_code, format_variant};
+use crate::utils::outcome::OperationResult;
+use crate::utils::shutdown::{read_shutdown, ShutdownNotice};
 
-pub async fn execute(client: &mut OpcUaClient) -> Result<()> {
+pub struct InfoArgs {
+    pub subscriptions: bool,
+    pub profile_detect: bool,
+}
+
+#[derive(Tabled)]
+struct FacetRow {
+    #[tabled(rename = "Facet")]
+    facet: &'static str,
+    #[tabled(rename = "Evidence")]
+    evidence: &'static str,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+/// Reads of standard nodes of the Server object. Nano profile servers may omit some of them;
+/// those are collected instead of failing the command.
+struct StandardNodes<'a> {
+    session: &'a ServiceSession,
+    /// Browse paths of the nodes the server does not know
+    missing: Vec<&'static str>,
+}
+
+impl StandardNodes<'_> {
+    /// The value of a standard node, or the status it could not be read with
+    async fn read(&mut self, variable: VariableId, path: &'static str) -> Result<std::result::Result<Variant, StatusCode>> {
+        let data_value = self.session
+            .read_values(&[ReadValueId::from(&NodeId::from(variable))])
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        Ok(self.value(data_value, path))
+    }
+
+    fn value(&mut self, data_value: DataValue, path: &'static str) -> std::result::Result<Variant, StatusCode> {
+        match (data_value.status, data_value.value) {
+            (Some(status), _) if !status.is_good() => {
+                if is_missing(status) {
+                    self.missing.push(path);
+                }
+                Err(status)
+            }
+            (_, Some(value)) => Ok(value),
+            (_, None) => Err(StatusCode::BadNoData),
+        }
+    }
+
+    /// Note the node as missing when `error` says the server does not know it
+    fn check_error(&mut self, error: &anyhow::Error, path: &'static str) -> bool {
+        let missing = matches!(OperationResult::of_error(error), Some(OperationResult::OperationBad(status)) if is_missing(status));
+        if missing {
+            self.missing.push(path);
+        }
+        missing
+    }
+}
+
+fn is_missing(status: StatusCode) -> bool {
+    matches!(status.sub_code(), SubStatusCode::BadNodeIdUnknown | SubStatusCode::BadAttributeIdInvalid)
+}
+
+/// Why a section has no value
+fn unavailable(status: StatusCode) -> String {
+    if is_missing(status) {
+        "not available on this server".dimmed().to_string()
+    } else {
+        format_status_code(&status)
+    }
+}
+
+#[derive(Tabled)]
+struct SubscriptionRow {
+    #[tabled(rename = "Subscription")]
+    subscription_id: u32,
+    #[tabled(rename = "Session")]
+    session: String,
+    #[tabled(rename = "Interval (ms)")]
+    publishing_interval: String,
+    #[tabled(rename = "Items")]
+    monitored_items: u32,
+    #[tabled(rename = "Publish Requests")]
+    publish_requests: u32,
+    #[tabled(rename = "Late")]
+    late_publish_requests: u32,
+    #[tabled(rename = "Notifications")]
+    notifications: u32,
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &InfoArgs) -> Result<()> {
     let session = client.session()?;
     
     println!("\n{}", "🔍 OPC-UA Server Information".bright_cyan().bold());
     println!("{}", "─".repeat(40));
     
-    // Get server status
-    let server_status_request = ReadValueId {
-        node_id: VariableId::Server_ServerStatus.into(),
-        attribute_id: AttributeId::Value as u32,
-        index_range: NumericRange::None,
-        data_encoding: QualifiedName::null(),
-    };
-    let server_status_results = session
-        .read(&[server_status_request], TimestampsToReturn::Neither, 0.0)
-        .await?;
-    let server_status = server_status_results.first();
-        
-    if let Some(status_data) = server_status {
-        println!("📊 {}: {}", "Server Status".bright_white(), 
-                 format_server_status(status_data));
-    }
-    
-    // Get server timestamp
-    let current_time_request = ReadValueId {
-        node_id: VariableId::Server_ServerStatus_CurrentTime.into(),
-        attribute_id: AttributeId::Value as u32,
-        index_range: NumericRange::None,
-        data_encoding: QualifiedName::null(),
-    };
-    let current_time_results = session
-        .read(&[current_time_request], TimestampsToReturn::Neither, 0.0)
-        .await?;
-        
-    if let Some(current_time) = current_time_results.first() {
-        if let Some(timestamp) = &current_time.value {
-            println!("🕐 {}: {}", "Server Time".bright_white(), 
-                     format_timestamp(timestamp));
+    if let Some(endpoint) = client.connected_endpoint() {
+        println!("🔌 {}: {}", "Endpoint".bright_white(), endpoint.endpoint_url);
+        println!("🔐 {}: {} / {} (security level {})", "Security".bright_white(),
+                 SecurityPolicy::from_uri(endpoint.security_policy_uri.as_ref()),
+                 endpoint.security_mode,
+                 endpoint.security_level);
+    }
+    
+    let mut standard = StandardNodes { session, missing: Vec::new() };
+    
+    let state = standard.read(VariableId::Server_ServerStatus_State, "ServerStatus.State").await?;
+    println!("📊 {}: {}", "Server Status".bright_white(),
+             state.map_or_else(unavailable, |state| format_server_status(&state)));
+    
+    let current_time = standard.read(VariableId::Server_ServerStatus_CurrentTime, "ServerStatus.CurrentTime").await?;
+    println!("🕐 {}: {}", "Server Time".bright_white(),
+             current_time.map_or_else(unavailable, |timestamp| format_timestamp(&timestamp)));
+    
+    match read_shutdown(session).await {
+        Ok(Some(notice)) => println!("🛑 {}: {}", "Shutdown".bright_white(), format_shutdown(&notice)),
+        Ok(None) => println!("🛑 {}: {}", "Shutdown".bright_white(), "none announced".dimmed()),
+        Err(e) if standard.check_error(&e, "ServerStatus.SecondsTillShutdown") => {
+            println!("🛑 {}: {}", "Shutdown".bright_white(), "not available on this server".dimmed());
         }
+        Err(e) => debug!("Shutdown status not available: {}", e),
     }
     
-    // Get build info
-    let build_info_request = ReadValueId {
-        node_id: VariableId::Server_ServerStatus_BuildInfo.into(),
-        attribute_id: AttributeId::Value as u32,
-        index_range: NumericRange::None,
-        data_encoding: QualifiedName::null(),
-    };
-    let build_info_results = session
-        .read(&[build_info_request], TimestampsToReturn::Neither, 0.0)
-        .await?;
-        
-    if let Some(build_info) = build_info_results.first() {
-        if let Some(build_info_value) = &build_info.value {
-            println!("🏗️  {}: {}", "Build Info".bright_white(), 
-                     format_build_info(build_info_value));
+    let build_info = standard.read(VariableId::Server_ServerStatus_BuildInfo, "ServerStatus.BuildInfo").await?;
+    println!("🏗️  {}: {}", "Build Info".bright_white(),
+             build_info.map_or_else(unavailable, |build_info| format_build_info(&build_info)));
+    
+    println!("\n📁 {}", "Available Namespaces".bright_cyan());
+    match client.namespaces().await {
+        Ok(namespaces) => {
+            for (i, ns) in namespaces.iter().enumerate() {
+                println!("   ns={}: {}", i, ns);
+            }
         }
+        Err(e) if standard.check_error(&e, "NamespaceArray") => {
+            println!("   {}", "not available on this server".dimmed());
+        }
+        Err(e) => println!("   {} {}", "Namespace array not available:".yellow(), e),
+    }
+    
+    if args.subscriptions {
+        display_subscriptions(session).await?;
+    }
+    
+    if args.profile_detect {
+        display_profile(&mut standard).await?;
     }
     
-    // Get namespace array
-    debug!("Reading namespace array");
-    let namespaces_request = ReadValueId {
-        node_id: VariableId::Server_NamespaceArray.into(),
-        attribute_id: AttributeId::Value as u32,
-        index_range: NumericRange::None,
-        data_encoding: QualifiedName::null(),
+    if !standard.missing.is_empty() {
+        println!("\nℹ️  {} {}", "Standard nodes missing on this server:".yellow(), standard.missing.join(", "));
+        println!("   Servers of the nano and micro profiles may omit them; commands relying on them fall back or report it");
+    }
+    
+    println!("\n✅ {}", "Server information retrieved successfully".green());
+    Ok(())
+}
+
+/// List the subscriptions of all sessions from the SubscriptionDiagnosticsArray, naming their
+/// sessions from the SessionDiagnosticsArray
+async fn display_subscriptions(session: &ServiceSession) -> Result<()> {
+    println!("\n📡 {}", "Subscriptions".bright_cyan());
+    
+    let read_requests: Vec<ReadValueId> = [
+        VariableId::Server_ServerDiagnostics_EnabledFlag,
+        VariableId::Server_ServerDiagnostics_SubscriptionDiagnosticsArray,
+        VariableId::Server_ServerDiagnostics_SessionsDiagnosticsSummary_SessionDiagnosticsArray,
+    ]
+    .into_iter()
+    .map(|variable| ReadValueId::from(&NodeId::from(variable)))
+    .collect();
+    let results = session.read_values(&read_requests).await?;
+    
+    if matches!(results.first().and_then(|dv| dv.value.as_ref()), Some(Variant::Boolean(false))) {
+        println!("   {}", "Server diagnostics are disabled (ServerDiagnostics.EnabledFlag is false)".yellow());
+        return Ok(());
+    }
+    
+    let Some(subscriptions) = results.get(1) else {
+        return Ok(());
     };
-    let namespaces_results = session
-        .read(&[namespaces_request], TimestampsToReturn::Neither, 0.0)
-        .await?;
-        
-    if let Some(namespaces) = namespaces_results.first() {
-        if let Some(Variant::Array(ns_array)) = &namespaces.value {
-            println!("\n📁 {}", "Available Namespaces".bright_cyan());
-            for (i, ns) in ns_array.values.iter().enumerate() {
-                if let Variant::String(ns_string) = ns {
-                    println!("   ns={}: {}", i, ns_string.as_ref());
-                }
+    if let Some(status) = subscriptions.status.filter(|status| !status.is_good()) {
+        println!("   {} {}", "Subscription diagnostics are not available:".yellow(), format_status_code(&status));
+        return Ok(());
+    }
+    if !matches!(subscriptions.value, Some(Variant::Array(_))) {
+        println!("   {}", "The server does not publish subscription diagnostics".yellow());
+        return Ok(());
+    }
+    
+    let session_names: HashMap<NodeId, String> = diagnostics::<SessionDiagnosticsDataType>(results.get(2))
+        .into_iter()
+        .map(|session| (session.session_id.clone(), session.session_name.to_string()))
+        .collect();
+    
+    let mut rows: Vec<SubscriptionRow> = diagnostics::<SubscriptionDiagnosticsDataType>(Some(subscriptions))
+        .into_iter()
+        .map(|subscription| SubscriptionRow {
+            subscription_id: subscription.subscription_id,
+            session: session_names
+                .get(&subscription.session_id)
+                .filter(|name| !name.is_empty())
+                .cloned()
+                .unwrap_or_else(|| format_node_id(&subscription.session_id)),
+            publishing_interval: subscription.publishing_interval.to_string(),
+            monitored_items: subscription.monitored_item_count,
+            publish_requests: subscription.publish_request_count,
+            late_publish_requests: subscription.late_publish_request_count,
+            notifications: subscription.notifications_count,
+        })
+        .collect();
+    
+    if rows.is_empty() {
+        println!("   {}", "No subscriptions".dimmed());
+        return Ok(());
+    }
+    
+    rows.sort_by_key(|row| row.subscription_id);
+    let total_items: u32 = rows.iter().map(|row| row.monitored_items).sum();
+    println!("{}", Table::new(&rows));
+    println!("   {} subscription(s) with {} monitored item(s)", rows.len().to_string().bright_white(), total_items.to_string().bright_white());
+    Ok(())
+}
+
+/// The decodable diagnostic structures of an array of ExtensionObjects
+fn diagnostics<T: Send + Sync + 'static>(data_value: Option<&DataValue>) -> Vec<&T> {
+    match data_value.and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::Array(array)) => array.values
+            .iter()
+            .filter_map(|value| match value {
+                Variant::ExtensionObject(obj) => obj.inner_as::<T>(),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Facets whose standard nodes the server exposes. Evidence only: the nodes show the server models
+/// a facet, not that every service of it works.
+async fn display_profile(standard: &mut StandardNodes<'_>) -> Result<()> {
+    println!("\n🧩 {}", "Profile Facets".bright_cyan());
+    
+    let probes = [
+        (VariableId::Server_ServerCapabilities_ServerProfileArray, "ServerCapabilities.ServerProfileArray"),
+        (VariableId::Server_NamespaceArray, "NamespaceArray"),
+        (VariableId::Server_ServerStatus_State, "ServerStatus.State"),
+        (VariableId::Server_ServerCapabilities_LocaleIdArray, "ServerCapabilities.LocaleIdArray"),
+        (VariableId::Server_ServerCapabilities_MaxMonitoredItemsPerSubscription, "ServerCapabilities.MaxMonitoredItemsPerSubscription"),
+        (VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerMethodCall, "OperationLimits.MaxNodesPerMethodCall"),
+        (VariableId::Server_ServerDiagnostics_EnabledFlag, "ServerDiagnostics.EnabledFlag"),
+        (VariableId::Server_Auditing, "Auditing"),
+        (VariableId::Server_ServerRedundancy_RedundancySupport, "ServerRedundancy.RedundancySupport"),
+    ];
+    let read_requests: Vec<ReadValueId> = probes.iter().map(|(variable, _)| ReadValueId::from(&NodeId::from(*variable))).collect();
+    let results = standard.session.read_values(&read_requests).await?;
+    let values: Vec<std::result::Result<Variant, StatusCode>> = results
+        .into_iter()
+        .zip(&probes)
+        .map(|(data_value, (_, path))| {
+            // Nodes noted missing by the sections above are not noted twice
+            if standard.missing.contains(path) {
+                Err(StatusCode::BadNodeIdUnknown)
+            } else {
+                standard.value(data_value, path)
+            }
+        })
+        .collect();
+    
+    match &values[0] {
+        Ok(Variant::Array(array)) if !array.values.is_empty() => {
+            println!("   Declared profiles:");
+            for profile in &array.values {
+                println!("   • {}", format_variant(profile));
             }
         }
+        _ => println!("   {}", "The server declares no profiles in ServerProfileArray".dimmed()),
     }
     
-    println!("\n✅ {}", "Server information retrieved successfully".green());
+    let present = |idx: usize| values[idx].is_ok();
+    let flag = |idx: usize| match &values[idx] {
+        Ok(Variant::Boolean(true)) => "✅ enabled".green().to_string(),
+        Ok(Variant::Boolean(false)) => "⚪ disabled".dimmed().to_string(),
+        Ok(_) => "❓ unexpected value".yellow().to_string(),
+        Err(status) => absent(*status),
+    };
+    let stated = |idx: usize| match &values[idx] {
+        Ok(value) => format!("✅ {}", format_variant(value)).green().to_string(),
+        Err(_) => "❓ not stated".dimmed().to_string(),
+    };
+    let rows = vec![
+        FacetRow {
+            facet: "Core Server",
+            evidence: "NamespaceArray, ServerStatus",
+            result: if present(1) && present(2) { "✅ present".green().to_string() } else { "❌ incomplete".red().to_string() },
+        },
+        FacetRow {
+            facet: "Locales",
+            evidence: "LocaleIdArray",
+            result: match &values[3] {
+                Ok(Variant::Array(array)) if !array.values.is_empty() => format!("✅ {} locale(s)", array.values.len()).green().to_string(),
+                _ => "❓ not stated".dimmed().to_string(),
+            },
+        },
+        FacetRow { facet: "Data Change Subscriptions", evidence: "MaxMonitoredItemsPerSubscription", result: stated(4) },
+        FacetRow { facet: "Method Calls", evidence: "MaxNodesPerMethodCall", result: stated(5) },
+        FacetRow { facet: "Diagnostics", evidence: "ServerDiagnostics.EnabledFlag", result: flag(6) },
+        FacetRow { facet: "Auditing", evidence: "Server.Auditing", result: flag(7) },
+        FacetRow {
+            facet: "Redundancy",
+            evidence: "RedundancySupport",
+            result: match &values[8] {
+                Ok(Variant::Int32(0)) => "⚪ none".dimmed().to_string(),
+                Ok(value) => format!("✅ {}", format_variant(value)).green().to_string(),
+                Err(status) => absent(*status),
+            },
+        },
+    ];
+    println!("{}", Table::new(rows));
+    println!("   {}", "Limits a server does not state are no proof it lacks the facet".dimmed());
     Ok(())
 }
 
-fn format_server_status(status: &DataValue) -> String {
-    if let Some(Variant::UInt32(status_code)) = &status.value {
+/// Result of a probe whose node has no usable value
+fn absent(status: StatusCode) -> String {
+    if is_missing(status) {
+        "❌ missing".red().to_string()
+    } else if status == StatusCode::BadNoData {
+        "❓ no value".dimmed().to_string()
+    } else {
+        format!("❓ {}", status).yellow().to_string()
+    }
+}
+
+fn format_server_status(state: &Variant) -> String {
+    if let Variant::Int32(status_code) = state {
         match *status_code {
             0 => "Running".green().to_string(),
             1 => "Failed".red().to_string(),
@@ -109,6 +362,15 @@ fn format_server_status(status: &DataValue) -> String {
     }
 }
 
+fn format_shutdown(notice: &ShutdownNotice) -> String {
+    let countdown = format!("{} {}", "announced,".bold(), notice.countdown());
+    if notice.reason.is_empty() {
+        countdown.red().to_string()
+    } else {
+        format!("{} ({})", countdown.red(), notice.reason.yellow())
+    }
+}
+
 fn format_timestamp(timestamp: &Variant) -> String {
     if let Variant::DateTime(dt) = timestamp {
         dt.as_chrono().format("%Y-%m-%d %H:%M:%S UTC").to_string()
@@ -118,10 +380,11 @@ fn format_timestamp(timestamp: &Variant) -> String {
 }
 
 fn format_build_info(build_info: &Variant) -> String {
-    if let Variant::ExtensionObject(_ext_obj) = build_info {
-        // Try to extract build info fields
-        format!("Build information available")
-    } else {
-        "Not available".dimmed().to_string()
+    match build_info {
+        Variant::ExtensionObject(obj) => match obj.inner_as::<BuildInfo>() {
+            Some(info) => format!("{} {} (build {})", info.product_name, info.software_version, info.build_number),
+            None => "Build information available".to_string(),
+        },
+        _ => "Not available".dimmed().to_string(),
     }
 }
\ No newline at end of file
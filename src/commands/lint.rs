@@ -0,0 +1,367 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use colored::*;
+use opcua::types::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tabled::{Table, Tabled};
+use tracing::warn;
+
+use crate::client::OpcUaClient;
+use crate::commands::export::{attribute_request, browse_instances, read_node_attributes, BATCH_SIZE};
+use crate::session::ServiceSession;
+use crate::types::LintRule;
+use crate::utils::browse_request::BrowseRequest;
+use crate::utils::formatter::{format_node_id, localized_text};
+use crate::utils::namespace::parse_node_id;
+use crate::utils::nodeset::NodeSetNode;
+use crate::utils::security::ConnectionInfo;
+
+#[derive(Tabled)]
+struct FindingRow {
+    #[tabled(rename = "Node ID")]
+    node_id: String,
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Rule")]
+    rule: String,
+    #[tabled(rename = "Finding")]
+    message: String,
+}
+
+#[derive(Serialize)]
+struct Finding {
+    node_id: String,
+    path: String,
+    rule: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct LintReport {
+    checked: usize,
+    rules: Vec<&'static str>,
+    findings: Vec<Finding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<ConnectionInfo>,
+}
+
+pub struct LintArgs {
+    pub start_node_id: NodeId,
+    pub max_depth: u32,
+    pub rules: Vec<LintRule>,
+    pub json: bool,
+}
+
+pub fn validate(node: Option<&str>, max_depth: u32, rules: &[LintRule], json: bool) -> Result<LintArgs> {
+    let start_node_id = if let Some(node_str) = node {
+        parse_node_id(node_str)?
+    } else {
+        ObjectId::ObjectsFolder.into()
+    };
+
+    let mut rules = if rules.is_empty() {
+        LintRule::value_variants().to_vec()
+    } else {
+        rules.to_vec()
+    };
+    rules.sort();
+    rules.dedup();
+
+    Ok(LintArgs {
+        start_node_id,
+        max_depth,
+        rules,
+        json,
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &LintArgs) -> Result<()> {
+    let session = client.session()?;
+
+    if !args.json {
+        println!("\n{}", "🧹 Linting OPC-UA Address Space".bright_cyan().bold());
+        println!("📍 Starting node: {}", format_node_id(&args.start_node_id).bright_white());
+        println!("📏 Max depth: {}", args.max_depth.to_string().bright_white());
+        println!("📋 Rules: {}", args.rules.iter().map(|r| rule_name(*r)).collect::<Vec<_>>().join(", ").bright_white());
+        println!("{}", "─".repeat(60));
+    }
+
+    let mut nodes = browse_instances(session, &args.start_node_id, args.max_depth).await?;
+    read_node_attributes(session, &mut nodes).await?;
+
+    let paths = node_paths(&nodes, &args.start_node_id);
+    let mut findings = Vec::new();
+
+    for rule in &args.rules {
+        let violations = match rule {
+            LintRule::AbstractDataType => check_data_types(session, &nodes).await?,
+            LintRule::ValueRank => check_value_ranks(&nodes),
+            LintRule::EmptyBrowseName => check_empty_browse_names(&nodes),
+            LintRule::EmptyDescription => check_empty_descriptions(&nodes),
+            LintRule::MissingInputArguments => check_input_arguments(session, &nodes).await?,
+            LintRule::DuplicateBrowseName => check_duplicate_browse_names(&nodes),
+        };
+
+        findings.extend(violations.into_iter().map(|(idx, message)| Finding {
+            node_id: format_node_id(&nodes[idx].node_id),
+            path: paths[idx].clone(),
+            rule: rule_name(*rule),
+            message,
+        }));
+    }
+
+    let finding_count = findings.len();
+
+    if args.json {
+        let report = LintReport {
+            checked: nodes.len(),
+            rules: args.rules.iter().map(|r| rule_name(*r)).collect(),
+            findings,
+            security: client.connection_info().cloned(),
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        display_findings(&findings, nodes.len());
+    }
+
+    if finding_count > 0 {
+        return Err(anyhow!("Lint found {} problem(s)", finding_count));
+    }
+
+    Ok(())
+}
+
+fn rule_name(rule: LintRule) -> &'static str {
+    match rule {
+        LintRule::AbstractDataType => "abstract-data-type",
+        LintRule::ValueRank => "value-rank",
+        LintRule::EmptyBrowseName => "empty-browse-name",
+        LintRule::EmptyDescription => "empty-description",
+        LintRule::MissingInputArguments => "missing-input-arguments",
+        LintRule::DuplicateBrowseName => "duplicate-browse-name",
+    }
+}
+
+/// Browse path of every node relative to the starting node, following the first parent found
+pub fn node_paths(nodes: &[NodeSetNode], start: &NodeId) -> Vec<String> {
+    browse_name_paths(nodes, start)
+        .into_iter()
+        .map(|path| path.iter().map(|name| name.name.as_ref()).collect::<Vec<_>>().join("/"))
+        .collect()
+}
+
+/// BrowseNames from below the starting node down to every node, following the first parent found
+pub fn browse_name_paths(nodes: &[NodeSetNode], start: &NodeId) -> Vec<Vec<QualifiedName>> {
+    let index: HashMap<&NodeId, usize> = nodes.iter().enumerate().map(|(i, n)| (&n.node_id, i)).collect();
+
+    nodes
+        .iter()
+        .map(|node| {
+            let mut segments = vec![node.browse_name.clone()];
+            let mut seen = HashSet::from([&node.node_id]);
+            let mut current = node;
+
+            while let Some(parent) = current.references.iter().find(|r| !r.is_forward).map(|r| &r.target) {
+                if parent == start || !seen.insert(parent) {
+                    break;
+                }
+                match index.get(parent) {
+                    Some(&idx) => {
+                        current = &nodes[idx];
+                        segments.push(current.browse_name.clone());
+                    }
+                    None => break,
+                }
+            }
+
+            segments.reverse();
+            segments
+        })
+        .collect()
+}
+
+/// Variables whose DataType is abstract or does not resolve to a DataType node
+async fn check_data_types(session: &ServiceSession, nodes: &[NodeSetNode]) -> Result<Vec<(usize, String)>> {
+    let data_types: Vec<NodeId> = nodes
+        .iter()
+        .filter_map(|n| n.variable.as_ref().map(|v| v.data_type.clone()))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut is_abstract: HashMap<NodeId, Option<bool>> = HashMap::new();
+    for chunk in data_types.chunks(BATCH_SIZE) {
+        let read_requests: Vec<ReadValueId> = chunk
+            .iter()
+            .map(|data_type| attribute_request(data_type, AttributeId::IsAbstract))
+            .collect();
+        let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+
+        for (data_type, result) in chunk.iter().zip(results) {
+            let value = match result.value {
+                Some(Variant::Boolean(value)) => Some(value),
+                _ => None,
+            };
+            is_abstract.insert(data_type.clone(), value);
+        }
+    }
+
+    Ok(nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, node)| {
+            let data_type = &node.variable.as_ref()?.data_type;
+            match is_abstract.get(data_type).copied().flatten() {
+                Some(true) => Some(format!("DataType {} is abstract", format_node_id(data_type))),
+                Some(false) => None,
+                None => Some(format!("DataType {} is unknown to the server", format_node_id(data_type))),
+            }
+            .map(|message| (idx, message))
+        })
+        .collect())
+}
+
+/// ArrayDimensions and the current value must agree with ValueRank
+fn check_value_ranks(nodes: &[NodeSetNode]) -> Vec<(usize, String)> {
+    let mut findings = Vec::new();
+
+    for (idx, node) in nodes.iter().enumerate() {
+        let Some(variable) = &node.variable else {
+            continue;
+        };
+        let rank = variable.value_rank;
+        let dimensions = variable.array_dimensions.as_deref().unwrap_or_default();
+
+        // ArrayDimensions must be empty unless ValueRank fixes the number of dimensions
+        if !dimensions.is_empty() && (rank <= 0 || dimensions.len() != rank as usize) {
+            findings.push((idx, format!("ValueRank {} but ArrayDimensions has {} entries", rank, dimensions.len())));
+        }
+
+        match &variable.value {
+            Some(Variant::Array(_)) if rank == -1 => {
+                findings.push((idx, "ValueRank -1 (scalar) but the value is an array".to_string()));
+            }
+            Some(value) if rank >= 0 && !matches!(value, Variant::Array(_) | Variant::Empty) => {
+                findings.push((idx, format!("ValueRank {} but the value is a scalar", rank)));
+            }
+            _ => {}
+        }
+    }
+
+    findings
+}
+
+fn check_empty_browse_names(nodes: &[NodeSetNode]) -> Vec<(usize, String)> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.browse_name.name.is_empty())
+        .map(|(idx, _)| (idx, "BrowseName is empty".to_string()))
+        .collect()
+}
+
+fn check_empty_descriptions(nodes: &[NodeSetNode]) -> Vec<(usize, String)> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.description.as_ref().is_none_or(|d| localized_text(d).is_empty()))
+        .map(|(idx, _)| (idx, "Description is missing or empty".to_string()))
+        .collect()
+}
+
+/// Methods without an InputArguments property, looked up regardless of the property's namespace
+async fn check_input_arguments(session: &ServiceSession, nodes: &[NodeSetNode]) -> Result<Vec<(usize, String)>> {
+    let methods: Vec<usize> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.node_class == NodeClass::Method)
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let mut findings = Vec::new();
+    for chunk in methods.chunks(BATCH_SIZE) {
+        let browse_requests: Vec<BrowseDescription> = chunk
+            .iter()
+            .map(|idx| BrowseRequest::new(&nodes[*idx].node_id, BrowseDirection::Forward, ReferenceTypeId::HasProperty)
+                .classes(NodeClassMask::VARIABLE)
+                .results(&[BrowseResultMask::BrowseName])
+                .build())
+            .collect();
+
+        let results = session.browse(&browse_requests, 0, None).await?;
+
+        for (idx, result) in chunk.iter().zip(results) {
+            if !result.status_code.is_good() {
+                warn!("Browse failed for method {}: {}", format_node_id(&nodes[*idx].node_id), result.status_code);
+                continue;
+            }
+
+            let has_input_arguments = result
+                .references
+                .unwrap_or_default()
+                .iter()
+                .any(|r| r.browse_name.name.as_ref() == "InputArguments");
+            if !has_input_arguments {
+                findings.push((*idx, "Method has no InputArguments property".to_string()));
+            }
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Children of the same parent must be distinguishable by BrowseName
+fn check_duplicate_browse_names(nodes: &[NodeSetNode]) -> Vec<(usize, String)> {
+    let mut children: BTreeMap<(String, String), Vec<usize>> = BTreeMap::new();
+
+    for (idx, node) in nodes.iter().enumerate() {
+        for parent in node.references.iter().filter(|r| !r.is_forward) {
+            let key = (format_node_id(&parent.target), node.browse_name.to_string());
+            let siblings = children.entry(key).or_default();
+            if !siblings.contains(&idx) {
+                siblings.push(idx);
+            }
+        }
+    }
+
+    children
+        .into_iter()
+        .filter(|(_, siblings)| siblings.len() > 1)
+        .flat_map(|((parent, browse_name), siblings)| {
+            let count = siblings.len();
+            siblings.into_iter().map(move |idx| {
+                (idx, format!("BrowseName {} is used by {} children of {}", browse_name, count, parent))
+            })
+        })
+        .collect()
+}
+
+fn display_findings(findings: &[Finding], checked: usize) {
+    println!("🔎 Checked {} node(s)", checked.to_string().bright_white());
+
+    if findings.is_empty() {
+        println!("\n✅ {}", "No modelling problems found".green());
+        return;
+    }
+
+    let rows: Vec<FindingRow> = findings
+        .iter()
+        .map(|finding| FindingRow {
+            node_id: finding.node_id.clone(),
+            path: finding.path.clone(),
+            rule: finding.rule.yellow().to_string(),
+            message: finding.message.clone(),
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+
+    let mut per_rule: BTreeMap<&str, usize> = BTreeMap::new();
+    for finding in findings {
+        *per_rule.entry(finding.rule).or_default() += 1;
+    }
+    println!("\n{}", "📊 Findings per rule:".bright_white().bold());
+    for (rule, count) in per_rule {
+        println!("  • {}: {}", rule, count.to_string().yellow());
+    }
+}
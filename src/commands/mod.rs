@@ -1,7 +1,136 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::utils::snapshot::Snapshot;
+
+pub mod assert;
+pub mod backup;
+pub mod bench;
 pub mod browse;
 pub mod call;
+pub mod copy;
 pub mod discover;
+pub mod examples;
+pub mod export;
+pub mod find_servers;
+pub mod fleet;
 pub mod info;
+pub mod lint;
+pub mod monitor;
+pub mod permissions;
 pub mod read;
+pub mod refs;
+pub mod replay;
+pub mod report;
+pub mod restore;
+pub mod stale_scan;
+pub mod tui;
+pub mod write;
+
+pub use crate::types::Commands;
+
+/// A command whose arguments passed the offline validation step
+pub enum ParsedCommand {
+    Discover(discover::DiscoverArgs),
+    Browse(browse::BrowseArgs),
+    Read(read::ReadArgs),
+    Call(call::CallArgs),
+    Write(write::WriteArgs),
+    Monitor(monitor::MonitorArgs),
+    Refs(refs::RefsArgs),
+    Info(info::InfoArgs),
+    Fleet(fleet::FleetArgs),
+    Export(export::ExportArgs),
+    Lint(lint::LintArgs),
+    Assert(assert::AssertArgs),
+    Permissions(permissions::PermissionsArgs),
+    StaleScan(stale_scan::StaleScanArgs),
+    Backup(backup::BackupArgs),
+    Restore(restore::RestoreArgs),
+    Copy(copy::CopyArgs),
+    Report(report::ReportArgs),
+    Bench(bench::BenchArgs),
+    Replay(replay::ReplayArgs),
+    FindServers(find_servers::FindServersArgs),
+    Tui(tui::TuiArgs),
+    Examples(examples::ExamplesArgs),
+}
+
+impl ParsedCommand {
+    /// Whether the command prints or writes JSON, whose strings the serializer escapes itself
+    pub fn prints_json(&self) -> bool {
+        match self {
+            ParsedCommand::Browse(args) => args.json,
+            ParsedCommand::Call(args) => args.json,
+            ParsedCommand::Fleet(args) => args.json,
+            ParsedCommand::Lint(args) => args.json,
+            ParsedCommand::Permissions(args) => args.json,
+            ParsedCommand::StaleScan(args) => args.json,
+            ParsedCommand::Report(_) => true,
+            ParsedCommand::Bench(args) => args.json,
+            _ => false,
+        }
+    }
+
+    /// Snapshot the command reads offline with `--from-snapshot`
+    pub fn snapshot(&self) -> Option<&Arc<Snapshot>> {
+        match self {
+            ParsedCommand::Browse(args) => args.snapshot.as_ref(),
+            ParsedCommand::Read(args) => args.snapshot.as_ref(),
+            _ => None,
+        }
+    }
+}
 
-pub use crate::types::Commands;
\ No newline at end of file
+/// Validate command arguments without touching the server
+pub fn validate(command: &Commands) -> Result<ParsedCommand> {
+    Ok(match command {
+        Commands::Discover { server_cert, save_cert, ca_file } => {
+            ParsedCommand::Discover(discover::validate(*server_cert, save_cert.as_deref(), ca_file.as_deref())?)
+        }
+        Commands::Browse(args) => ParsedCommand::Browse(browse::validate(args)?),
+        Commands::Read(args) => ParsedCommand::Read(read::validate(args)?),
+        Commands::Call(args) => ParsedCommand::Call(call::validate(args)?),
+        Commands::Write(args) => ParsedCommand::Write(write::validate(args)?),
+        Commands::Monitor(args) => ParsedCommand::Monitor(monitor::validate(args)?),
+        Commands::Refs { node, encodings } => ParsedCommand::Refs(refs::validate(node, *encodings)?),
+        Commands::Info { subscriptions, profile_detect } => {
+            ParsedCommand::Info(info::InfoArgs { subscriptions: *subscriptions, profile_detect: *profile_detect })
+        }
+        Commands::Fleet { endpoints, file, concurrency, node, json } => {
+            ParsedCommand::Fleet(fleet::validate(endpoints, file.as_deref(), *concurrency, node.as_deref(), *json)?)
+        }
+        Commands::Export { nodeset2, node, depth, emit_schema, snapshot } => {
+            ParsedCommand::Export(export::validate(node.as_deref(), *depth, nodeset2.as_deref(), emit_schema.as_deref(), snapshot.as_deref())?)
+        }
+        Commands::Lint { node, depth, rules, json } => {
+            ParsedCommand::Lint(lint::validate(node.as_deref(), *depth, rules, *json)?)
+        }
+        Commands::Assert { spec, generate, node, depth } => {
+            ParsedCommand::Assert(assert::validate(spec, *generate, node.as_deref(), *depth)?)
+        }
+        Commands::Permissions { node_ids, node, depth, json } => {
+            ParsedCommand::Permissions(permissions::validate(node_ids, node.as_deref(), *depth, *json)?)
+        }
+        Commands::StaleScan { node, depth, window, samples, exclude, min_change, json } => {
+            ParsedCommand::StaleScan(stale_scan::validate(node.as_deref(), *depth, *window, *samples, exclude, *min_change, *json)?)
+        }
+        Commands::Backup { node, output, depth } => {
+            ParsedCommand::Backup(backup::validate(node, *depth, output)?)
+        }
+        Commands::Restore { file, node, dry_run } => {
+            ParsedCommand::Restore(restore::validate(file, node.as_deref(), *dry_run)?)
+        }
+        Commands::Copy { node, to, to_node, depth, dry_run } => {
+            ParsedCommand::Copy(copy::validate(node, to, to_node.as_deref(), *depth, *dry_run)?)
+        }
+        Commands::Report { output, timeout } => ParsedCommand::Report(report::validate(output, *timeout)?),
+        Commands::Bench(args) => ParsedCommand::Bench(bench::validate(args)?),
+        Commands::Replay { file } => ParsedCommand::Replay(replay::ReplayArgs { file: file.clone() }),
+        Commands::Tui { node, interval, highlight } => ParsedCommand::Tui(tui::validate(node.as_deref(), *interval, *highlight)?),
+        Commands::FindServers { mdns, duration, get_endpoints } => {
+            ParsedCommand::FindServers(find_servers::validate(*mdns, *duration, *get_endpoints)?)
+        }
+        Commands::Examples { command, check } => ParsedCommand::Examples(examples::validate(command.as_deref(), *check)?),
+    })
+}
\ No newline at end of file
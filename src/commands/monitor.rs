@@ -0,0 +1,1218 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use serde_json::Map;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::future::poll_fn;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::{Duration, SystemTime};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use crate::client::{check_session_budget, KeepAlive, KeepAliveStatus, OpcUaClient, PublishClock};
+use crate::commands::export::attribute_request;
+use crate::session::{ServiceSession, ServiceStats};
+use crate::types::{Cli, MonitorCommand};
+use crate::utils::archive::{ArchiveEndpoint, ArchiveNode, ArchiveSummary, ArchiveWriter, PublishingState, PublishingStatus, Rotation};
+use crate::utils::change_stats::{print_summary, ChangeStats, NodeSummary, SummaryRow};
+use crate::utils::enums::{enum_names, read_data_types, EnumNames, EnumValue};
+use crate::utils::expand::expand_all;
+use crate::utils::formatter::{format_data_type, format_node_id, format_status_code, format_value_variant, is_strict, sanitize_text, Severity};
+use crate::utils::influx::line_protocol;
+use crate::utils::labels::label_for;
+use crate::utils::namespace::{find_in_other_namespaces, parse_node_spec, read_namespace_array, NamespaceMap, NodeSpec};
+use crate::utils::schema::{archive_schema, read_value_types, value_schema, write_schema};
+use crate::utils::shutdown::{read_shutdown, ShutdownNotice};
+use crate::utils::subscription::ValueSubscription;
+use crate::utils::time::format_duration;
+use crate::utils::units::numeric_value;
+use crate::utils::value_map::mapped_value;
+
+/// How often diverging values are checked against the grace period
+const COMPARE_TICK: Duration = Duration::from_millis(250);
+
+/// How often nodes waiting for re-resolution are checked
+const REBIND_TICK: Duration = Duration::from_secs(1);
+
+/// How often the `--nodes-file` is checked for changes
+const NODES_FILE_TICK: Duration = Duration::from_secs(2);
+
+/// How often the time since the last publish response is checked
+const PUBLISH_TICK: Duration = Duration::from_secs(1);
+
+/// How often the servers are asked whether they announce a shutdown
+const SHUTDOWN_TICK: Duration = Duration::from_secs(5);
+
+/// Seconds till shutdown at which an announced shutdown is reported again
+const SHUTDOWN_REMINDERS: [u32; 3] = [300, 60, 10];
+
+pub struct MonitorArgs {
+    pub nodes: Vec<NodeSpec>,
+    pub interval: Duration,
+    pub duration: Option<Duration>,
+    /// Line protocol output, `-` for stdout
+    pub influx_lp: Option<String>,
+    pub measurement: String,
+    /// Servers to monitor side by side instead of the global endpoint
+    pub endpoints: Vec<MonitorEndpoint>,
+    pub compare: Option<CompareSettings>,
+    /// Publishing intervals without a publish response after which the subscriptions count as silent
+    pub gap_intervals: u32,
+    /// Minimum time between re-resolutions of a node the server no longer knows, `None` to
+    /// only report the failure
+    pub rebind_interval: Option<Duration>,
+    pub archive: Option<ArchiveSettings>,
+    /// Where `nodes` were read from, watched for changes while monitoring
+    pub nodes_file: Option<NodesFile>,
+    /// Where to write the JSON Schema of the archive records
+    pub emit_schema: Option<String>,
+}
+
+/// The node list of a `--nodes-file` as it was when monitoring started
+pub struct NodesFile {
+    pub path: String,
+    /// The line each node of `MonitorArgs::nodes` came from, braces expanded
+    pub lines: Vec<String>,
+    pub stamp: Option<FileStamp>,
+}
+
+/// Modification time and size of a file, to notice it was written
+type FileStamp = (SystemTime, u64);
+
+/// Where and how `--archive` files are written
+pub struct ArchiveSettings {
+    pub dir: String,
+    pub rotation: Rotation,
+    pub compress: bool,
+}
+
+pub struct MonitorEndpoint {
+    pub alias: String,
+    pub url: String,
+}
+
+/// When the values of a node on different endpoints count as diverging
+pub struct CompareSettings {
+    /// Numeric values may differ by this much
+    pub tolerance: f64,
+    /// Values must differ for this long before it is reported
+    pub grace: Duration,
+}
+
+pub fn validate(args: &MonitorCommand) -> Result<MonitorArgs> {
+    let (nodes, nodes_file) = match &args.nodes_file {
+        Some(path) => {
+            // Stamped before reading, so a write while reading shows as a change later
+            let stamp = file_stamp(path);
+            let (lines, nodes): (Vec<String>, Vec<NodeSpec>) = read_nodes_file(path)?.into_iter().unzip();
+            if nodes.is_empty() {
+                return Err(anyhow!("The nodes file {} lists no nodes", path));
+            }
+            (nodes, Some(NodesFile { path: path.to_string(), lines, stamp }))
+        }
+        None => {
+            let nodes = expand_all(&args.node_ids)?
+                .iter()
+                .map(|node_str| parse_node_spec(node_str))
+                .collect::<Result<Vec<_>>>()?;
+            (nodes, None)
+        }
+    };
+    if nodes.is_empty() {
+        return Err(anyhow!("No node IDs provided"));
+    }
+
+    if args.interval == 0 {
+        return Err(anyhow!("--interval must be greater than 0"));
+    }
+    if args.duration.is_some_and(|duration| duration.is_zero()) {
+        return Err(anyhow!("--duration must be greater than 0"));
+    }
+    if args.measurement.is_empty() {
+        return Err(anyhow!("--measurement must not be empty"));
+    }
+
+    let endpoints: Vec<MonitorEndpoint> = args.endpoints.iter().map(|value| parse_endpoint(value)).collect();
+    let mut aliases = HashSet::new();
+    for endpoint in &endpoints {
+        if !aliases.insert(endpoint.alias.as_str()) {
+            return Err(anyhow!("Endpoint alias '{}' is used twice, name them with ALIAS=URL", endpoint.alias));
+        }
+    }
+
+    if args.compare && endpoints.len() < 2 {
+        return Err(anyhow!("--compare needs at least two --endpoint values"));
+    }
+    if !args.tolerance.is_finite() || args.tolerance < 0.0 {
+        return Err(anyhow!("--tolerance must be a non-negative number"));
+    }
+    if let Some(path) = args.emit_schema.as_ref().filter(|path| Path::new(path).is_dir()) {
+        return Err(anyhow!("Schema path is a directory: {}", path));
+    }
+
+    Ok(MonitorArgs {
+        nodes,
+        interval: Duration::from_millis(args.interval),
+        duration: args.duration,
+        influx_lp: args.influx_lp.clone(),
+        measurement: args.measurement.clone(),
+        endpoints,
+        compare: args.compare.then_some(CompareSettings {
+            tolerance: args.tolerance,
+            grace: Duration::from_millis(args.grace),
+        }),
+        gap_intervals: args.gap_intervals,
+        rebind_interval: (!args.rebind_interval.is_zero()).then_some(args.rebind_interval),
+        archive: args.archive.as_ref().map(|dir| ArchiveSettings {
+            dir: dir.clone(),
+            rotation: args.rotate_size.map_or(Rotation::Hourly, |mb| Rotation::Size(mb * 1024 * 1024)),
+            compress: args.archive_compress,
+        }),
+        nodes_file,
+        emit_schema: args.emit_schema.clone(),
+    })
+}
+
+/// The nodes of a nodes file with the line each came from, braces expanded and duplicates
+/// dropped. Lines starting with # are comments.
+fn read_nodes_file(path: &str) -> Result<Vec<(String, NodeSpec)>> {
+    let content = fs::read_to_string(path).map_err(|e| anyhow!("Failed to read nodes file {}: {}", path, e))?;
+
+    let mut seen = HashSet::new();
+    let mut nodes = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let expanded = expand_all(&[line.to_string()]).map_err(|e| anyhow!("{} line {}: {}", path, number + 1, e))?;
+        for node_str in expanded {
+            let spec = parse_node_spec(&node_str).map_err(|e| anyhow!("{} line {}: {}", path, number + 1, e))?;
+            if seen.insert(node_str.clone()) {
+                nodes.push((node_str, spec));
+            }
+        }
+    }
+    Ok(nodes)
+}
+
+fn file_stamp(path: &str) -> Option<FileStamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// `ALIAS=URL`, or a bare URL aliased by its host and port
+fn parse_endpoint(value: &str) -> MonitorEndpoint {
+    if let Some((alias, url)) = value.split_once('=')
+        && !alias.is_empty()
+        && !alias.contains("://")
+    {
+        return MonitorEndpoint { alias: alias.to_string(), url: url.to_string() };
+    }
+
+    let address = value.split_once("://").map_or(value, |(_, rest)| rest);
+    let alias = address.split('/').next().unwrap_or(address);
+    MonitorEndpoint { alias: alias.to_string(), url: value.to_string() }
+}
+
+/// Writes data changes as InfluxDB line protocol
+struct InfluxSink {
+    writer: Box<dyn Write>,
+    measurement: String,
+    written: u64,
+    skipped: u64,
+}
+
+impl InfluxSink {
+    fn open(path: &str, measurement: &str) -> Result<Self> {
+        let writer: Box<dyn Write> = if path == "-" {
+            Box::new(io::stdout())
+        } else {
+            let file = File::create(path).map_err(|e| anyhow!("Failed to create {}: {}", path, e))?;
+            Box::new(BufWriter::new(file))
+        };
+
+        Ok(InfluxSink {
+            writer,
+            measurement: measurement.to_string(),
+            written: 0,
+            skipped: 0,
+        })
+    }
+
+    fn write(&mut self, tags: &[(&str, &str)], data_value: &DataValue) -> Result<()> {
+        let timestamp = data_value.source_timestamp
+            .or(data_value.server_timestamp)
+            .unwrap_or_else(DateTime::now);
+        let timestamp_ns = timestamp.as_chrono().timestamp_nanos_opt().unwrap_or_default();
+
+        let line = data_value.value.as_ref().and_then(|value| line_protocol(
+            &self.measurement,
+            tags,
+            value,
+            timestamp_ns,
+        ));
+
+        match line {
+            Some(line) => {
+                writeln!(self.writer, "{}", line)?;
+                self.writer.flush()?;
+                self.written += 1;
+            }
+            None => self.skipped += 1,
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// The monitored nodes on one server
+struct Source {
+    /// Prefix of output lines and `endpoint` tag, only set when monitoring several endpoints
+    alias: Option<String>,
+    endpoint: String,
+    browse_names: Vec<String>,
+    display_names: Vec<String>,
+    formatted_ids: Vec<String>,
+    node_ids: Vec<NodeId>,
+    /// DataType names for the archive header
+    data_types: Vec<String>,
+    /// Value names of nodes whose DataType is an enumeration
+    enums: Vec<Option<Arc<EnumNames>>>,
+    /// When the next re-resolution of a node the server stopped knowing may run, `None`
+    /// while the node is fine
+    rebind_after: Vec<Option<Instant>>,
+    session: ServiceSession,
+    ns_map: NamespaceMap,
+    /// Namespace array of the server, read again only when a node is re-resolved
+    namespaces: Vec<String>,
+    keep_alive: KeepAlive,
+    publishes: PublishClock,
+    /// When the subscriptions were created, the start of the silence before the first publish response
+    opened: Instant,
+    /// Since when the server has answered no publish request, while that is reported as a gap
+    silent_since: Option<Instant>,
+    /// Gaps in the publish responses so far
+    gaps: u32,
+    subscription: ValueSubscription,
+    /// Nodes whose monitored item was created
+    monitored: usize,
+    /// The shutdown the server announced at the last check
+    shutdown: Option<ShutdownNotice>,
+    /// What each node sent so far, for the summary at the end
+    stats: Vec<ChangeStats>,
+}
+
+impl Source {
+    async fn open(client: &OpcUaClient, alias: Option<&str>, args: &MonitorArgs) -> Result<Self> {
+        let session = client.session()?;
+
+        let mut node_ids = Vec::with_capacity(args.nodes.len());
+        for spec in &args.nodes {
+            node_ids.push(client.resolve_node(spec).await?);
+        }
+
+        let (browse_names, display_names) = node_names(session, &node_ids).await?;
+        let formatted_ids: Vec<String> = node_ids.iter().map(format_node_id).collect();
+        let data_types = read_data_types(session, &node_ids).await?;
+        let enums = data_type_enums(session, &data_types).await;
+
+        let (subscription, statuses) = ValueSubscription::create(session, &node_ids, args.interval).await?;
+
+        let prefix = alias.map(|alias| format!("{}: ", alias)).unwrap_or_default();
+        let failed: Vec<usize> = statuses.iter().enumerate().filter(|(_, s)| !s.is_good()).map(|(idx, _)| idx).collect();
+        for idx in &failed {
+            warn!("{}Cannot monitor {}: {}", prefix, formatted_ids[*idx], statuses[*idx]);
+        }
+        if failed.len() == node_ids.len() {
+            subscription.delete().await;
+            return Err(anyhow!("{}None of the nodes could be monitored", prefix));
+        }
+
+        Ok(Source {
+            alias: alias.map(String::from),
+            endpoint: client.endpoint().to_string(),
+            browse_names,
+            display_names,
+            formatted_ids,
+            rebind_after: vec![None; node_ids.len()],
+            stats: (0..node_ids.len()).map(|_| ChangeStats::default()).collect(),
+            monitored: node_ids.len() - failed.len(),
+            node_ids,
+            data_types: data_types.iter().map(|data_type| data_type.as_ref().map(format_data_type).unwrap_or_default()).collect(),
+            enums,
+            session: session.clone(),
+            ns_map: client.ns_map().clone(),
+            namespaces: client.namespaces().await.map(<[String]>::to_vec).unwrap_or_else(|e| {
+                debug!("{}Reading the namespace array failed: {}", prefix, e);
+                Vec::new()
+            }),
+            keep_alive: client.keep_alive().clone(),
+            publishes: client.publishes().clone(),
+            opened: Instant::now(),
+            silent_since: None,
+            gaps: 0,
+            subscription,
+            shutdown: None,
+        })
+    }
+
+    /// Monitor more nodes after the current ones. Nodes that cannot be resolved or monitored
+    /// still take their position, so positions stay the same on all sources.
+    async fn add(&mut self, specs: &[NodeSpec]) {
+        let prefix = self.alias.as_deref().map(|alias| format!("{}: ", alias)).unwrap_or_default();
+        let resolved: Vec<Option<NodeId>> = specs
+            .iter()
+            .map(|spec| match self.ns_map.resolve(spec, &self.namespaces) {
+                Ok(node_id) => Some(node_id),
+                Err(e) => {
+                    warn!("{}Cannot monitor {}: {}", prefix, spec, e);
+                    None
+                }
+            })
+            .collect();
+        let node_ids: Vec<NodeId> = resolved.iter().map(|node_id| node_id.clone().unwrap_or_else(NodeId::null)).collect();
+
+        let (browse_names, display_names) = match node_names(&self.session, &node_ids).await {
+            Ok(names) => names,
+            Err(e) => {
+                debug!("{}Reading the names of {} node(s) failed: {}", prefix, node_ids.len(), e);
+                let names: Vec<String> = specs.iter().map(ToString::to_string).collect();
+                (names.clone(), names)
+            }
+        };
+        let data_types = read_data_types(&self.session, &node_ids).await.unwrap_or_else(|_| vec![None; node_ids.len()]);
+        let enums = data_type_enums(&self.session, &data_types).await;
+        let statuses = self.subscription.add(&node_ids).await.unwrap_or_else(|e| {
+            warn!("{}Cannot monitor {} more node(s): {}", prefix, node_ids.len(), e);
+            vec![StatusCode::BadSubscriptionIdInvalid; node_ids.len()]
+        });
+
+        for (idx, spec) in specs.iter().enumerate() {
+            let formatted = resolved[idx].as_ref().map_or_else(|| spec.to_string(), format_node_id);
+            if resolved[idx].is_some() && !statuses[idx].is_good() {
+                warn!("{}Cannot monitor {}: {}", prefix, formatted, statuses[idx]);
+            }
+            self.monitored += usize::from(statuses[idx].is_good());
+            self.formatted_ids.push(formatted);
+            self.data_types.push(data_types[idx].as_ref().map(format_data_type).unwrap_or_default());
+            self.rebind_after.push(None);
+            self.stats.push(ChangeStats::default());
+        }
+        self.node_ids.extend(node_ids);
+        self.browse_names.extend(browse_names);
+        self.display_names.extend(display_names);
+        self.enums.extend(enums);
+    }
+
+    /// A value of the node at `idx` as the change lines show it: by `--value-map`, enumeration
+    /// values by name, others as they are
+    fn display_value(&self, idx: usize, variant: &Variant) -> String {
+        if let Some(mapped) = mapped_value(&self.node_ids[idx], &self.data_types[idx], variant) {
+            return mapped.to_string();
+        }
+        self.enums[idx]
+            .as_deref()
+            .and_then(|enums| EnumValue::new(enums, variant))
+            .map(|enum_value| enum_value.format())
+            .unwrap_or_else(|| format_value_variant(variant))
+    }
+
+    /// Stop monitoring the node at `idx`
+    async fn remove(&mut self, idx: usize) {
+        if self.subscription.remove(idx).await {
+            self.monitored = self.monitored.saturating_sub(1);
+        }
+        self.rebind_after[idx] = None;
+    }
+
+    /// Resolve a node given by namespace URI or label again, returning its new node ID
+    async fn re_resolve(&mut self, idx: usize, spec: &NodeSpec) -> Result<Option<NodeId>> {
+        // The namespace array can change when the server is re-deployed, unlike everywhere
+        // else the cached one will not do here
+        self.namespaces = read_namespace_array(&self.session).await?;
+        let current = &self.node_ids[idx];
+        if spec.namespace_uri.is_some() {
+            let node_id = self.ns_map.resolve(spec, &self.namespaces)?;
+            return Ok((node_id != *current).then_some(node_id));
+        }
+
+        // A re-deployment may register the namespace of a labelled string node ID elsewhere
+        match find_in_other_namespaces(&self.session, current, &self.namespaces).await?.as_slice() {
+            [(candidate, _)] => Ok(Some(candidate.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Re-create the monitored item of a node on its re-resolved node ID, or schedule the next attempt
+    async fn rebind(&mut self, idx: usize, spec: &NodeSpec, retry: Duration, quiet: bool) {
+        let prefix = self.alias.as_deref().map(|alias| format!("{}: ", alias)).unwrap_or_default();
+        let outcome = match self.re_resolve(idx, spec).await {
+            Ok(Some(node_id)) => self.subscription.replace(idx, &node_id).await.map(|status| (node_id, status)),
+            Ok(None) => Err(anyhow!("no new node ID found")),
+            Err(e) => Err(e),
+        };
+
+        match outcome {
+            Ok((node_id, status)) if status.is_good() => {
+                let formatted = format_node_id(&node_id);
+                report(quiet, format!("🔁 {}{} re-bound from {} to {}",
+                    prefix, self.display_names[idx].bright_white(), self.formatted_ids[idx].dimmed(), formatted.bright_white()));
+                self.enums[idx] = node_enums(&self.session, std::slice::from_ref(&node_id)).await
+                    .ok()
+                    .and_then(|mut enums| enums.pop())
+                    .flatten();
+                self.node_ids[idx] = node_id;
+                self.formatted_ids[idx] = formatted;
+                self.rebind_after[idx] = None;
+            }
+            Ok((node_id, status)) => {
+                debug!("{}Cannot monitor {} in place of {}: {}", prefix, format_node_id(&node_id), self.formatted_ids[idx], status);
+                self.rebind_after[idx] = Some(Instant::now() + retry);
+            }
+            Err(e) => {
+                debug!("{}Re-resolving {} failed: {}", prefix, spec, e);
+                self.rebind_after[idx] = Some(Instant::now() + retry);
+            }
+        }
+    }
+}
+
+/// Latest values of every node on every endpoint and since when they disagree
+struct Divergence {
+    /// `[node][endpoint]`, `None` until a good value arrived
+    latest: Vec<Vec<Option<Variant>>>,
+    since: Vec<Option<Instant>>,
+    reported: Vec<bool>,
+    count: u64,
+}
+
+impl Divergence {
+    fn new(nodes: usize, endpoints: usize) -> Self {
+        Divergence {
+            latest: vec![vec![None; endpoints]; nodes],
+            since: vec![None; nodes],
+            reported: vec![false; nodes],
+            count: 0,
+        }
+    }
+
+    /// Make room for nodes added to the node list
+    fn grow(&mut self, nodes: usize) {
+        let endpoints = self.latest.first().map_or(0, Vec::len);
+        self.latest.resize(nodes, vec![None; endpoints]);
+        self.since.resize(nodes, None);
+        self.reported.resize(nodes, false);
+    }
+
+    /// Forget a node removed from the node list
+    fn forget(&mut self, node: usize) {
+        self.latest[node].fill(None);
+        self.since[node] = None;
+        self.reported[node] = false;
+    }
+
+    /// Record a value, returning for how long the node had diverged if it now agrees again
+    fn update(&mut self, node: usize, endpoint: usize, data_value: &DataValue, tolerance: f64) -> Option<Duration> {
+        let good = data_value.status.is_none_or(|status| status.is_good());
+        self.latest[node][endpoint] = data_value.value.clone().filter(|_| good);
+
+        if self.differs(node, tolerance) {
+            self.since[node].get_or_insert_with(Instant::now);
+            return None;
+        }
+
+        let since = self.since[node].take()?;
+        std::mem::take(&mut self.reported[node]).then(|| since.elapsed())
+    }
+
+    fn differs(&self, node: usize, tolerance: f64) -> bool {
+        let known: Vec<&Variant> = self.latest[node].iter().flatten().collect();
+        if known.len() < 2 {
+            return false;
+        }
+
+        let numbers: Option<Vec<f64>> = known.iter().map(|value| numeric_value(value)).collect();
+        match numbers {
+            Some(numbers) => {
+                let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                max - min > tolerance
+            }
+            None => known.iter().any(|value| *value != known[0]),
+        }
+    }
+
+    /// Nodes that have now diverged for longer than the grace period, each reported once
+    fn overdue(&mut self, grace: Duration) -> Vec<usize> {
+        let overdue: Vec<usize> = (0..self.since.len())
+            .filter(|node| !self.reported[*node] && self.since[*node].is_some_and(|since| since.elapsed() >= grace))
+            .collect();
+        for node in &overdue {
+            self.reported[*node] = true;
+        }
+        self.count += overdue.len() as u64;
+        overdue
+    }
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &MonitorArgs) -> Result<()> {
+    let source = Source::open(client, None, args).await?;
+    run(vec![source], args).await
+}
+
+/// Monitor the nodes on every `--endpoint`, with one connection per server
+pub async fn execute_endpoints(cli: &Cli, args: &MonitorArgs, stats: &Arc<ServiceStats>) -> Result<()> {
+    check_session_budget(cli.max_sessions as usize, args.endpoints.iter().map(|endpoint| endpoint.url.as_str()))?;
+    let mut clients = Vec::with_capacity(args.endpoints.len());
+    let mut sources = Vec::with_capacity(args.endpoints.len());
+    let mut result = Ok(());
+
+    for endpoint in &args.endpoints {
+        let mut client = match OpcUaClient::for_endpoint(cli, &endpoint.url) {
+            Ok(client) => client.with_stats(stats.clone()),
+            Err(e) => {
+                result = Err(anyhow!("{}: {}", endpoint.alias, e));
+                break;
+            }
+        };
+        let opened = match client.connect().await {
+            Ok(()) => {
+                client.print_security_warning();
+                Source::open(&client, Some(&endpoint.alias), args).await
+            }
+            Err(e) => Err(anyhow!("{}: {}", endpoint.alias, e)),
+        };
+        clients.push(client);
+        match opened {
+            Ok(source) => sources.push(source),
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+
+    if result.is_ok() {
+        result = run(sources, args).await;
+    } else {
+        delete_subscriptions(sources).await;
+    }
+
+    for client in &mut clients {
+        if let Err(e) = client.disconnect().await {
+            debug!("Disconnect from {} failed: {}", client.endpoint(), e);
+        }
+    }
+    result
+}
+
+async fn run(mut sources: Vec<Source>, args: &MonitorArgs) -> Result<()> {
+    // The subscriptions exist already, outputs that cannot be opened must not leave them behind
+    let (mut sink, mut archive, schema_nodes) = match open_outputs(&sources, args).await {
+        Ok(outputs) => outputs,
+        Err(e) => {
+            delete_subscriptions(sources).await;
+            return Err(e);
+        }
+    };
+    // Line protocol on stdout must not be interleaved with the human readable output
+    let quiet = args.influx_lp.as_deref() == Some("-");
+
+    if !quiet {
+        println!("\n{}", "📡 Monitoring OPC-UA Nodes".bright_cyan().bold());
+        println!("📊 Nodes: {}", args.nodes.len().to_string().bright_white());
+        if sources.len() > 1 {
+            let aliases: Vec<&str> = sources.iter().filter_map(|source| source.alias.as_deref()).collect();
+            println!("🖥️  Endpoints: {}", aliases.join(", ").bright_white());
+        }
+        if let Some(file) = &args.nodes_file {
+            println!("📄 Nodes file: {} (checked every {})", file.path.bright_white(), format_duration(NODES_FILE_TICK));
+        }
+        println!("⏱️  Interval: {} ms", args.interval.as_millis().to_string().bright_white());
+        for source in &sources {
+            println!("📬 {}Subscriptions: {} with {} of {} item(s)",
+                source.alias.as_deref().map(|alias| format!("{}: ", alias)).unwrap_or_default(),
+                source.subscription.subscription_count().to_string().bright_white(),
+                source.monitored.to_string().bright_white(),
+                source.node_ids.len());
+        }
+        if let Some(compare) = &args.compare {
+            println!("⚖️  Compare: tolerance {}, grace {} ms",
+                compare.tolerance.to_string().bright_white(),
+                compare.grace.as_millis().to_string().bright_white());
+        }
+        if let Some(path) = &args.influx_lp {
+            println!("💾 Line protocol: {} (measurement {})", path.bright_white(), args.measurement.bright_white());
+        }
+        if let Some(settings) = &args.archive {
+            let rotation = match settings.rotation {
+                Rotation::Hourly => "hourly".to_string(),
+                Rotation::Size(bytes) => format!("every {} MB", bytes / (1024 * 1024)),
+            };
+            println!("🗄️  Archive: {} (rotated {}{})", settings.dir.bright_white(), rotation,
+                if settings.compress { ", gzip" } else { "" });
+        }
+        if let (Some(path), Some(count)) = (&args.emit_schema, schema_nodes) {
+            println!("📐 Schema: {} ({} node(s))", path.bright_white(), count);
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    // Positions of nodes removed from the nodes file stay taken, they are `None` here
+    let mut nodes: Vec<Option<NodeSpec>> = args.nodes.iter().cloned().map(Some).collect();
+    let mut watched = args.nodes_file.as_ref().map(|file| WatchedNodes {
+        path: file.path.clone(),
+        lines: file.lines.iter().cloned().map(Some).collect(),
+        stamp: file.stamp,
+    });
+    let mut divergence = Divergence::new(args.nodes.len(), sources.len());
+    let mut ticker = tokio::time::interval(COMPARE_TICK);
+    let mut rebind_ticker = tokio::time::interval(REBIND_TICK);
+    let mut nodes_file_ticker = tokio::time::interval(NODES_FILE_TICK);
+    let mut shutdown_ticker = tokio::time::interval(SHUTDOWN_TICK);
+    let mut publish_ticker = tokio::time::interval(PUBLISH_TICK);
+    let started = Instant::now();
+    let deadline = args.duration.map(|duration| started + duration);
+    let mut changes: u64 = 0;
+    // Changes whose status is Bad, or Uncertain with --strict
+    let mut failures: u64 = 0;
+
+    let result = loop {
+        let notification = tokio::select! {
+            notification = next_change(&mut sources) => notification,
+            _ = ticker.tick(), if args.compare.is_some() => {
+                let grace = args.compare.as_ref().map(|compare| compare.grace).unwrap_or_default();
+                for node in divergence.overdue(grace) {
+                    print_divergence(&sources, &divergence, node, quiet);
+                }
+                continue;
+            }
+            _ = rebind_ticker.tick(), if args.rebind_interval.is_some() => {
+                rebind_due(&mut sources, &nodes, args, quiet).await;
+                continue;
+            }
+            _ = nodes_file_ticker.tick(), if watched.is_some() => {
+                if let Some(watched) = &mut watched {
+                    reload_nodes(watched, &mut nodes, &mut sources, &mut divergence, quiet).await;
+                }
+                continue;
+            }
+            _ = shutdown_ticker.tick() => {
+                check_shutdown(&mut sources, &mut sink, &mut archive, quiet).await;
+                continue;
+            }
+            _ = publish_ticker.tick() => {
+                check_publishing(&mut sources, args.gap_intervals, &mut archive, quiet);
+                continue;
+            }
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+            _ = async { tokio::time::sleep_until(deadline.unwrap()).await }, if deadline.is_some() => break Ok(()),
+        };
+        let (source_idx, idx, data_value) = match notification {
+            Ok(notification) => notification,
+            Err(source_idx) => {
+                let source = &sources[source_idx];
+                let announced = if source.shutdown.is_some() { " after announcing its shutdown" } else { "" };
+                break Err(match &source.alias {
+                    Some(alias) => anyhow!("Subscription closed by the server {}{}", alias, announced),
+                    None => anyhow!("Subscription closed by the server{}", announced),
+                });
+            }
+        };
+        // Notifications still queued when the node was removed
+        let Some(spec) = &nodes[idx] else {
+            continue;
+        };
+        changes += 1;
+        if data_value.status.is_some_and(|status| Severity::of(&status).is_failure()) {
+            failures += 1;
+        }
+        sources[source_idx].stats[idx].add(&data_value);
+        let source = &sources[source_idx];
+
+        if !quiet {
+            print_change(source, idx, &data_value);
+        }
+        if let Some(compare) = &args.compare
+            && let Some(diverged_for) = divergence.update(idx, source_idx, &data_value, compare.tolerance)
+        {
+            report(quiet, format!("✅ {} agrees again after {:.1}s", source.display_names[idx].bright_white(), diverged_for.as_secs_f64()));
+        }
+        if let Some(sink) = &mut sink {
+            let mut tags = vec![("node", source.browse_names[idx].as_str()), ("node_id", source.formatted_ids[idx].as_str())];
+            if let Some(alias) = &source.alias {
+                tags.push(("endpoint", alias));
+            }
+            if let Err(e) = sink.write(&tags, &data_value) {
+                break Err(anyhow!("Failed to write line protocol: {}", e));
+            }
+        }
+        if let Some(archive) = &mut archive
+            && let Err(e) = archive.write(source_idx * args.nodes.len() + idx, &data_value)
+        {
+            break Err(anyhow!("Failed to write the archive: {}", e));
+        }
+
+        // Nodes given by raw node ID only report the failure
+        if args.rebind_interval.is_some()
+            && (spec.namespace_uri.is_some() || spec.by_label)
+            && is_unknown_node(&data_value)
+            && source.rebind_after[idx].is_none()
+        {
+            report(quiet, format!("🔍 {}{} ({}) is unknown to the server, resolving it again",
+                source.alias.as_deref().map(|alias| format!("{}: ", alias)).unwrap_or_default(),
+                source.display_names[idx].bright_white(), source.formatted_ids[idx].dimmed()));
+            sources[source_idx].rebind_after[idx] = Some(Instant::now());
+            rebind_due(&mut sources, &nodes, args, quiet).await;
+        }
+    };
+
+    let elapsed = started.elapsed();
+    let (summaries, rows, names) = summarize(&sources, &nodes, args.nodes.len(), elapsed);
+    let gaps: Vec<(Option<String>, u32)> = sources
+        .iter()
+        .filter(|source| source.gaps > 0)
+        .map(|source| (source.alias.clone(), source.gaps))
+        .collect();
+    let keep_alives: Vec<(Option<String>, KeepAliveStatus)> = sources
+        .iter()
+        .filter_map(|source| Some((source.alias.clone(), source.keep_alive.last()?)))
+        .collect();
+    delete_subscriptions(sources).await;
+
+    if let Some(sink) = &sink {
+        if sink.skipped > 0 {
+            eprintln!("⚠️  Skipped {} value(s) without a line protocol field type", sink.skipped.to_string().yellow());
+        }
+        if !quiet {
+            println!("💾 Wrote {} point(s)", sink.written.to_string().bright_white());
+        }
+    }
+    if let Some(archive) = &mut archive {
+        let summary = ArchiveSummary {
+            end_time: DateTime::now().as_chrono().to_rfc3339(),
+            duration_secs: elapsed.as_secs_f64(),
+            nodes: summaries.clone(),
+        };
+        if let Err(e) = archive.write_summary(summary) {
+            warn!("Failed to write the archive summary: {}", e);
+        }
+        if let Err(e) = archive.finish() {
+            warn!("Failed to close the archive: {}", e);
+        }
+        report(quiet, format!("🗄️  Archived {} change(s) in {} file(s)", archive.changes.to_string().bright_white(), archive.files));
+    }
+    if !quiet {
+        if !rows.is_empty() {
+            print_summary(rows, &summaries, &names);
+        }
+        if args.compare.is_some() {
+            println!("⚖️  Divergences: {}", divergence.count.to_string().bright_white());
+        }
+        if failures > 0 {
+            println!("⚠️  Failing statuses: {}", failures.to_string().yellow());
+        }
+        for (source, count) in gaps {
+            let prefix = source.map(|alias| format!("{}: ", alias)).unwrap_or_default();
+            println!("⏸️  {}Publish gaps: {}", prefix, count.to_string().yellow());
+        }
+        for (source, status) in keep_alives {
+            let prefix = source.map(|alias| format!("{}: ", alias)).unwrap_or_default();
+            let result = if status.result.is_good() { status.result.describe().green() } else { status.result.describe().red() };
+            println!("💓 {}Last keep-alive read: {} ({:.0}s ago)", prefix, result, status.at.elapsed().as_secs_f64());
+        }
+        println!("\n✅ {} {} change(s)", "Monitoring stopped after".green(), changes);
+    }
+
+    if result.is_ok() && is_strict() && failures > 0 {
+        return Err(anyhow!("{} of {} change(s) are not Good (strict mode)", failures, changes));
+    }
+    result
+}
+
+/// The statistics of every node still monitored, numbered the way the archive numbers them,
+/// with the table rows and node names of the summary
+fn summarize(
+    sources: &[Source],
+    nodes: &[Option<NodeSpec>],
+    per_source: usize,
+    elapsed: Duration,
+) -> (Vec<NodeSummary>, Vec<SummaryRow>, Vec<String>) {
+    let mut summaries = Vec::new();
+    let mut rows = Vec::new();
+    let mut names = Vec::new();
+    for (source_idx, source) in sources.iter().enumerate() {
+        let prefix = source.alias.as_deref().map(|alias| format!("[{}] ", alias)).unwrap_or_default();
+        for (idx, stats) in source.stats.iter().enumerate() {
+            if nodes.get(idx).is_none_or(Option::is_none) {
+                continue;
+            }
+            let summary = stats.summary(source_idx * per_source + idx, elapsed);
+            let name = format!("{}{}", prefix, sanitize_text(&source.display_names[idx]));
+            rows.push(SummaryRow::new(
+                name.clone(),
+                &summary,
+                stats.first().map(|value| source.display_value(idx, value)),
+                stats.last().map(|value| source.display_value(idx, value)),
+            ));
+            names.push(name);
+            summaries.push(summary);
+        }
+    }
+    (summaries, rows, names)
+}
+
+/// Archive the nodes of all sources, numbered source by source
+/// The line protocol sink, the archive and the number of nodes in the archive schema, each when requested
+async fn open_outputs(sources: &[Source], args: &MonitorArgs) -> Result<(Option<InfluxSink>, Option<ArchiveWriter>, Option<usize>)> {
+    let sink = args.influx_lp
+        .as_deref()
+        .map(|path| InfluxSink::open(path, &args.measurement))
+        .transpose()?;
+    let archive = args.archive.as_ref().map(|settings| open_archive(sources, settings)).transpose()?;
+    let schema_nodes = match &args.emit_schema {
+        Some(path) => Some(emit_archive_schema(sources, path).await?),
+        None => None,
+    };
+    Ok((sink, archive, schema_nodes))
+}
+
+async fn delete_subscriptions(sources: Vec<Source>) {
+    for source in sources {
+        source.subscription.delete().await;
+    }
+}
+
+fn open_archive(sources: &[Source], settings: &ArchiveSettings) -> Result<ArchiveWriter> {
+    let endpoints = sources
+        .iter()
+        .map(|source| ArchiveEndpoint { url: source.endpoint.clone(), alias: source.alias.clone() })
+        .collect();
+    let nodes = sources
+        .iter()
+        .enumerate()
+        .flat_map(|(endpoint, source)| (0..source.node_ids.len()).map(move |idx| ArchiveNode {
+            node_id: source.formatted_ids[idx].clone(),
+            display_name: source.display_names[idx].clone(),
+            data_type: source.data_types[idx].clone(),
+            endpoint,
+        }))
+        .collect();
+    ArchiveWriter::create(&settings.dir, endpoints, nodes, settings.rotation, settings.compress)
+}
+
+/// Write the `--emit-schema` file for the archive records of all sources. Returns the number
+/// of node IDs it describes, which the same node on several servers counts once for.
+async fn emit_archive_schema(sources: &[Source], path: &str) -> Result<usize> {
+    let mut nodes = Vec::new();
+    let mut values = Map::new();
+    for source in sources {
+        let value_types = read_value_types(&source.session, &source.node_ids).await?;
+        for (node_id, value_type) in source.formatted_ids.iter().zip(&value_types) {
+            nodes.push(node_id.clone());
+            if !values.contains_key(node_id) {
+                values.insert(node_id.clone(), value_schema(&source.session, value_type).await);
+            }
+        }
+    }
+    let count = values.len();
+    write_schema(path, &archive_schema(&nodes, values))?;
+    Ok(count)
+}
+
+/// The next notification of any source as `(source, node, value)`, or the source whose
+/// subscription was closed
+async fn next_change(sources: &mut [Source]) -> std::result::Result<(usize, usize, DataValue), usize> {
+    poll_fn(|cx| {
+        for (source_idx, source) in sources.iter_mut().enumerate() {
+            match source.subscription.poll_next(cx) {
+                Poll::Ready(Some((idx, data_value))) => return Poll::Ready(Ok((source_idx, idx, data_value))),
+                Poll::Ready(None) => return Poll::Ready(Err(source_idx)),
+                Poll::Pending => {}
+            }
+        }
+        Poll::Pending
+    }).await
+}
+
+/// Re-resolve the nodes whose next attempt is due
+async fn rebind_due(sources: &mut [Source], nodes: &[Option<NodeSpec>], args: &MonitorArgs, quiet: bool) {
+    let Some(retry) = args.rebind_interval else {
+        return;
+    };
+    let now = Instant::now();
+    for source in sources.iter_mut() {
+        for (idx, spec) in nodes.iter().enumerate() {
+            let Some(spec) = spec else {
+                continue;
+            };
+            if source.rebind_after[idx].is_some_and(|after| after <= now) {
+                source.rebind(idx, spec, retry, quiet).await;
+            }
+        }
+    }
+}
+
+/// The nodes file while monitoring, with the line of every position in the node list
+struct WatchedNodes {
+    path: String,
+    /// `None` where the node was removed
+    lines: Vec<Option<String>>,
+    stamp: Option<FileStamp>,
+}
+
+/// Apply edits of the nodes file: lines no longer listed are removed from every source, new
+/// lines are added at the end of the node list. A file that cannot be read or parsed, or that
+/// lists no nodes, leaves the monitored nodes as they are.
+async fn reload_nodes(
+    watched: &mut WatchedNodes,
+    nodes: &mut Vec<Option<NodeSpec>>,
+    sources: &mut [Source],
+    divergence: &mut Divergence,
+    quiet: bool,
+) {
+    let stamp = file_stamp(&watched.path);
+    if stamp == watched.stamp {
+        return;
+    }
+    watched.stamp = stamp;
+
+    let listed = match read_nodes_file(&watched.path) {
+        Ok(listed) if listed.is_empty() => {
+            warn!("The nodes file {} lists no nodes, keeping the {} monitored node(s)", watched.path, nodes.iter().flatten().count());
+            return;
+        }
+        Ok(listed) => listed,
+        Err(e) => {
+            warn!("{}, keeping the {} monitored node(s)", e, nodes.iter().flatten().count());
+            return;
+        }
+    };
+
+    let removed: Vec<usize> = {
+        let wanted: HashSet<&str> = listed.iter().map(|(line, _)| line.as_str()).collect();
+        (0..watched.lines.len())
+            .filter(|idx| watched.lines[*idx].as_deref().is_some_and(|line| !wanted.contains(line)))
+            .collect()
+    };
+    let added: Vec<(String, NodeSpec)> = {
+        let current: HashSet<&str> = watched.lines.iter().flatten().map(String::as_str).collect();
+        listed.into_iter().filter(|(line, _)| !current.contains(line.as_str())).collect()
+    };
+    if removed.is_empty() && added.is_empty() {
+        return;
+    }
+
+    for idx in &removed {
+        for source in sources.iter_mut() {
+            source.remove(*idx).await;
+        }
+        divergence.forget(*idx);
+        nodes[*idx] = None;
+        watched.lines[*idx] = None;
+        report(quiet, format!("➖ Stopped monitoring {} ({})", sources[0].display_names[*idx].bright_white(), sources[0].formatted_ids[*idx].dimmed()));
+    }
+
+    if !added.is_empty() {
+        let specs: Vec<NodeSpec> = added.iter().map(|(_, spec)| spec.clone()).collect();
+        for source in sources.iter_mut() {
+            source.add(&specs).await;
+        }
+        let first = nodes.len();
+        for (line, spec) in added {
+            nodes.push(Some(spec));
+            watched.lines.push(Some(line));
+        }
+        divergence.grow(nodes.len());
+        for idx in first..nodes.len() {
+            report(quiet, format!("➕ Monitoring {} ({})", sources[0].display_names[idx].bright_white(), sources[0].formatted_ids[idx].dimmed()));
+        }
+    }
+
+    let items: Vec<String> = sources
+        .iter()
+        .map(|source| format!("{}{} item(s)", source.alias.as_deref().map(|alias| format!("{}: ", alias)).unwrap_or_default(), source.monitored))
+        .collect();
+    report(quiet, format!("📄 {} changed: {} node(s) now, {}", watched.path.bright_white(), nodes.iter().flatten().count(), items.join(", ")));
+}
+
+/// Ask every server whether it announces a shutdown. The first announcement prints a banner and
+/// gets the file output onto disk; after that the countdown is reported again at the reminder
+/// marks or when the reason changes.
+async fn check_shutdown(sources: &mut [Source], sink: &mut Option<InfluxSink>, archive: &mut Option<ArchiveWriter>, quiet: bool) {
+    let reminder = |seconds: u32| SHUTDOWN_REMINDERS.iter().filter(|mark| seconds <= **mark).count();
+    for source in sources.iter_mut() {
+        let notice = match read_shutdown(&source.session).await {
+            Ok(notice) => notice,
+            Err(e) => {
+                debug!("Checking {} for a shutdown failed: {}", source.endpoint, e);
+                continue;
+            }
+        };
+        let prefix = source.alias.as_deref().map(|alias| format!("{}: ", alias)).unwrap_or_default();
+
+        match (&source.shutdown, &notice) {
+            (None, Some(notice)) => {
+                report(quiet, "━".repeat(60).red().to_string());
+                report(quiet, format!("🛑 {}{} {}", prefix, "SERVER SHUTDOWN ANNOUNCED".red().bold(), notice.countdown().bold()));
+                if !notice.reason.is_empty() {
+                    report(quiet, format!("   Reason: {}", notice.reason.yellow()));
+                }
+                report(quiet, "━".repeat(60).red().to_string());
+
+                if let Some(sink) = sink
+                    && let Err(e) = sink.flush()
+                {
+                    warn!("Failed to flush the line protocol output: {}", e);
+                }
+                if let Some(archive) = archive
+                    && let Err(e) = archive.sync()
+                {
+                    warn!("Failed to sync the archive: {}", e);
+                }
+            }
+            (Some(previous), Some(notice)) if reminder(previous.seconds) != reminder(notice.seconds) || previous.reason != notice.reason => {
+                report(quiet, format!("⏳ {}{} {}", prefix, "Server shuts down".red(), notice.countdown()));
+            }
+            (Some(_), None) => report(quiet, format!("✅ {}The server no longer announces a shutdown", prefix)),
+            _ => {}
+        }
+        source.shutdown = notice;
+    }
+}
+
+/// Report servers that answered no publish request for longer than `gap_intervals` publishing
+/// intervals, a keep-alive included, and those whose publish responses arrive again
+fn check_publishing(sources: &mut [Source], gap_intervals: u32, archive: &mut Option<ArchiveWriter>, quiet: bool) {
+    let now = Instant::now();
+    for (endpoint, source) in sources.iter_mut().enumerate() {
+        let last = source.publishes.last().map(Instant::from_std).filter(|last| *last > source.opened).unwrap_or(source.opened);
+        let silence = now - last;
+        let limit = source.subscription.silence_limit(gap_intervals);
+        let prefix = source.alias.as_deref().map(|alias| format!("{}: ", alias)).unwrap_or_default();
+
+        let (state, silent_for) = match source.silent_since {
+            None if silence > limit => {
+                report(quiet, format!("⏸️  {}{} for {:.1}s, longer than the {:.1}s allowed: the connection may be lost",
+                    prefix, "No publish response".yellow().bold(), silence.as_secs_f64(), limit.as_secs_f64()));
+                source.silent_since = Some(last);
+                source.gaps += 1;
+                (PublishingState::Silent, silence)
+            }
+            Some(since) if last > since => {
+                report(quiet, format!("▶️  {}Publish responses resumed after {:.1}s", prefix, (last - since).as_secs_f64()));
+                source.silent_since = None;
+                (PublishingState::Recovered, last - since)
+            }
+            _ => continue,
+        };
+        if let Some(archive) = archive
+            && let Err(e) = archive.write_publishing(PublishingStatus {
+                endpoint,
+                state,
+                time: DateTime::now().as_chrono().to_rfc3339(),
+                silent_secs: silent_for.as_secs_f64(),
+            })
+        {
+            warn!("Failed to archive the publishing state: {}", e);
+        }
+    }
+}
+
+fn is_unknown_node(data_value: &DataValue) -> bool {
+    data_value.status.is_some_and(|status| {
+        matches!(status.sub_code(), SubStatusCode::BadNodeIdUnknown | SubStatusCode::BadNodeIdInvalid)
+    })
+}
+
+/// Status lines go to stderr while stdout carries line protocol
+fn report(quiet: bool, message: String) {
+    if quiet {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+fn print_divergence(sources: &[Source], divergence: &Divergence, node: usize, quiet: bool) {
+    let values: Vec<String> = sources
+        .iter()
+        .zip(&divergence.latest[node])
+        .map(|(source, value)| format!(
+            "{}={}",
+            source.alias.as_deref().unwrap_or_default(),
+            value.as_ref().map(format_value_variant).unwrap_or_else(|| "—".to_string())
+        ))
+        .collect();
+    let diverged_for = divergence.since[node].map(|since| since.elapsed()).unwrap_or_default();
+
+    report(quiet, format!("{} {} ({}) for {:.1}s: {}",
+        "⚠️  Divergence".yellow().bold(),
+        sources[0].display_names[node].bright_white(),
+        sources[0].formatted_ids[node].dimmed(),
+        diverged_for.as_secs_f64(),
+        values.join("  ").yellow()));
+}
+
+/// BrowseNames of the nodes and the names they are shown with, their labels where they have one
+async fn node_names(session: &ServiceSession, node_ids: &[NodeId]) -> Result<(Vec<String>, Vec<String>)> {
+    let read_requests: Vec<ReadValueId> = node_ids
+        .iter()
+        .map(|node_id| attribute_request(node_id, AttributeId::BrowseName))
+        .collect();
+    let browse_names: Vec<String> = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?
+        .into_iter()
+        .zip(node_ids)
+        .map(|(dv, node_id)| match dv.value {
+            Some(Variant::QualifiedName(name)) => name.name.to_string(),
+            _ => format_node_id(node_id),
+        })
+        .collect();
+    let display_names: Vec<String> = node_ids
+        .iter()
+        .zip(&browse_names)
+        .map(|(node_id, browse_name)| label_for(node_id).map(String::from).unwrap_or_else(|| sanitize_text(browse_name).into_owned()))
+        .collect();
+    Ok((browse_names, display_names))
+}
+
+/// Value names of the nodes whose DataType is an enumeration
+async fn node_enums(session: &ServiceSession, node_ids: &[NodeId]) -> Result<Vec<Option<Arc<EnumNames>>>> {
+    Ok(data_type_enums(session, &read_data_types(session, node_ids).await?).await)
+}
+
+async fn data_type_enums(session: &ServiceSession, data_types: &[Option<NodeId>]) -> Vec<Option<Arc<EnumNames>>> {
+    let mut enums = Vec::with_capacity(data_types.len());
+    for data_type in data_types {
+        enums.push(match data_type {
+            Some(data_type) => enum_names(session, data_type).await,
+            None => None,
+        });
+    }
+    enums
+}
+
+fn print_change(source: &Source, idx: usize, data_value: &DataValue) {
+    let timestamp = data_value.source_timestamp
+        .or(data_value.server_timestamp)
+        .map(|ts| ts.as_chrono().format("%H:%M:%S%.3f").to_string())
+        .unwrap_or_else(|| "--:--:--.---".to_string());
+    let value = data_value.value.as_ref().map_or_else(|| "—".to_string(), |variant| source.display_value(idx, variant));
+    let status = data_value.status.unwrap_or(StatusCode::Good);
+    let prefix = source.alias.as_deref().map(|alias| format!("{} ", format!("[{}]", alias).bright_magenta())).unwrap_or_default();
+    let (name, node_id) = (&source.display_names[idx], &source.formatted_ids[idx]);
+
+    if status.is_good() {
+        println!("{} {}{} ({}) = {}", timestamp.dimmed(), prefix, name.bright_white(), node_id.dimmed(), value.bright_green());
+    } else {
+        println!("{} {}{} ({}) = {} {}", timestamp.dimmed(), prefix, name.bright_white(), node_id.dimmed(), value, format_status_code(&status));
+    }
+}
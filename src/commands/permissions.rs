@@ -0,0 +1,284 @@
+use anyhow::Result;
+use colored::*;
+use opcua::types::*;
+use serde::Serialize;
+use tabled::{Table, Tabled};
+
+use crate::client::OpcUaClient;
+use crate::commands::export::{attribute_request, browse_instances, BATCH_SIZE};
+use crate::commands::lint::node_paths;
+use crate::utils::expand::expand_all;
+use crate::utils::formatter::{format_node_class, format_node_id, format_user_access_level, format_user_write_mask, write_mask_names};
+use crate::utils::namespace::{parse_node_id, parse_node_spec, NodeSpec};
+use crate::utils::security::ConnectionInfo;
+
+/// Node attributes next to the User attributes that restrict them for the session's user
+const ATTRIBUTES: [AttributeId; 7] = [
+    AttributeId::NodeClass,
+    AttributeId::AccessLevel,
+    AttributeId::UserAccessLevel,
+    AttributeId::Executable,
+    AttributeId::UserExecutable,
+    AttributeId::WriteMask,
+    AttributeId::UserWriteMask,
+];
+
+#[derive(Tabled)]
+struct PermissionRow {
+    #[tabled(rename = "Node")]
+    node: String,
+    #[tabled(rename = "Class")]
+    node_class: String,
+    #[tabled(rename = "Operations")]
+    operations: String,
+    #[tabled(rename = "Writable Attributes")]
+    attributes: String,
+}
+
+/// What a node supports and what of it the session's user may do
+#[derive(Serialize, Clone, Copy)]
+struct Permission {
+    supported: bool,
+    user: bool,
+}
+
+/// The AccessLevel bits of a variable
+#[derive(Serialize)]
+struct AccessPermissions {
+    read: Permission,
+    write: Permission,
+    history_read: Permission,
+    history_write: Permission,
+}
+
+#[derive(Serialize)]
+struct AttributePermissions {
+    supported: Vec<&'static str>,
+    user: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct NodePermissions {
+    node_id: String,
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node_class: Option<String>,
+    /// Why the node could not be checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Variables only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    access: Option<AccessPermissions>,
+    /// Methods only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    execute: Option<Permission>,
+    writable_attributes: AttributePermissions,
+    #[serde(skip)]
+    class: Option<NodeClass>,
+    /// AccessLevel and UserAccessLevel
+    #[serde(skip)]
+    levels: (u8, u8),
+    /// WriteMask and UserWriteMask
+    #[serde(skip)]
+    masks: (u32, u32),
+}
+
+impl NodePermissions {
+    /// Whether the user may use an AccessLevel bit of a variable
+    fn user_may(&self, bit: u8) -> bool {
+        self.class == Some(NodeClass::Variable) && self.levels.0 & self.levels.1 & bit != 0
+    }
+}
+
+#[derive(Serialize)]
+struct PermissionsReport {
+    user: String,
+    checked: usize,
+    readable: usize,
+    writable: usize,
+    executable: usize,
+    nodes: Vec<NodePermissions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<ConnectionInfo>,
+}
+
+pub enum PermissionTarget {
+    Nodes(Vec<NodeSpec>),
+    Subtree { start_node_id: NodeId, max_depth: u32 },
+}
+
+pub struct PermissionsArgs {
+    pub target: PermissionTarget,
+    pub json: bool,
+}
+
+pub fn validate(node_ids: &[String], node: Option<&str>, max_depth: u32, json: bool) -> Result<PermissionsArgs> {
+    let target = if !node_ids.is_empty() {
+        let node_ids = expand_all(node_ids)?;
+        PermissionTarget::Nodes(node_ids.iter().map(|node_id| parse_node_spec(node_id)).collect::<Result<_>>()?)
+    } else {
+        PermissionTarget::Subtree {
+            start_node_id: node.map(parse_node_id).transpose()?.unwrap_or_else(|| ObjectId::ObjectsFolder.into()),
+            max_depth,
+        }
+    };
+    Ok(PermissionsArgs { target, json })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &PermissionsArgs) -> Result<()> {
+    let user = client.user_identity();
+
+    if !args.json {
+        println!("\n{}", "🔑 Checking Permissions".bright_cyan().bold());
+        println!("👤 User: {}", user.bright_white());
+        if let PermissionTarget::Subtree { start_node_id, max_depth } = &args.target {
+            println!("📍 Subtree: {}", format_node_id(start_node_id).bright_white());
+            println!("📏 Max depth: {}", max_depth.to_string().bright_white());
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    let nodes: Vec<(NodeId, String)> = match &args.target {
+        PermissionTarget::Nodes(specs) => {
+            let mut nodes = Vec::with_capacity(specs.len());
+            for spec in specs {
+                nodes.push((client.resolve_node(spec).await?, spec.to_string()));
+            }
+            nodes
+        }
+        PermissionTarget::Subtree { start_node_id, max_depth } => {
+            let instances = browse_instances(client.session()?, start_node_id, *max_depth).await?;
+            let paths = node_paths(&instances, start_node_id);
+            instances.into_iter().map(|node| node.node_id).zip(paths).collect()
+        }
+    };
+
+    let session = client.session()?;
+    let mut permissions = Vec::with_capacity(nodes.len());
+    for chunk in nodes.chunks(BATCH_SIZE / ATTRIBUTES.len()) {
+        let read_requests: Vec<ReadValueId> = chunk
+            .iter()
+            .flat_map(|(node_id, _)| ATTRIBUTES.map(|attr| attribute_request(node_id, attr)))
+            .collect();
+        let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+
+        for ((node_id, path), values) in chunk.iter().zip(results.chunks(ATTRIBUTES.len())) {
+            permissions.push(node_permissions(node_id, path, values));
+        }
+    }
+
+    let count = |bit: u8| permissions.iter().filter(|p| p.user_may(bit)).count();
+    let report = PermissionsReport {
+        user,
+        checked: permissions.len(),
+        readable: count(0x01),
+        writable: count(0x02),
+        executable: permissions.iter().filter(|p| p.execute.is_some_and(|e| e.user)).count(),
+        nodes: permissions,
+        security: client.connection_info().cloned(),
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        display_permissions(&report);
+    }
+    Ok(())
+}
+
+fn node_permissions(node_id: &NodeId, path: &str, values: &[DataValue]) -> NodePermissions {
+    let node_class = match &values[0].value {
+        Some(Variant::Int32(class)) => NodeClass::try_from(*class).ok(),
+        _ => None,
+    };
+    let byte = |value: &DataValue| match value.value {
+        Some(Variant::Byte(v)) => v,
+        _ => 0,
+    };
+    let flag = |value: &DataValue| matches!(value.value, Some(Variant::Boolean(true)));
+    let mask = |value: &DataValue| match value.value {
+        Some(Variant::UInt32(v)) => v,
+        _ => 0,
+    };
+
+    // The User attributes can only narrow what the node supports
+    let (access_level, user_access_level) = (byte(&values[1]), byte(&values[2]));
+    let (write_mask, user_write_mask) = (mask(&values[5]), mask(&values[6]));
+
+    NodePermissions {
+        node_id: format_node_id(node_id),
+        path: path.to_string(),
+        node_class: node_class.map(|class| format!("{:?}", class)),
+        error: match node_class {
+            Some(_) => None,
+            None => Some(values[0].status.map_or("no NodeClass".to_string(), |status| status.to_string())),
+        },
+        access: (node_class == Some(NodeClass::Variable)).then(|| {
+            let permission = |bit: u8| Permission {
+                supported: access_level & bit != 0,
+                user: access_level & user_access_level & bit != 0,
+            };
+            AccessPermissions {
+                read: permission(0x01),
+                write: permission(0x02),
+                history_read: permission(0x04),
+                history_write: permission(0x08),
+            }
+        }),
+        execute: (node_class == Some(NodeClass::Method)).then(|| {
+            let executable = flag(&values[3]);
+            Permission { supported: executable, user: executable && flag(&values[4]) }
+        }),
+        writable_attributes: AttributePermissions {
+            supported: write_mask_names(write_mask),
+            user: write_mask_names(write_mask & user_write_mask),
+        },
+        class: node_class,
+        levels: (access_level, user_access_level),
+        masks: (write_mask, user_write_mask),
+    }
+}
+
+fn display_permissions(report: &PermissionsReport) {
+    if report.nodes.is_empty() {
+        println!("⚠️  No nodes found");
+        return;
+    }
+
+    let rows: Vec<PermissionRow> = report.nodes
+        .iter()
+        .map(|node| PermissionRow {
+            node: node.path.clone(),
+            node_class: match &node.error {
+                Some(error) => format!("❌ {}", error).red().to_string(),
+                None => node.class.map(format_node_class).unwrap_or_default(),
+            },
+            operations: match (&node.execute, node.access.is_some()) {
+                (Some(Permission { supported: true, user: true }), _) => "Execute".yellow().to_string(),
+                (Some(Permission { supported: true, user: false }), _) => "🔒 Execute".dimmed().to_string(),
+                (Some(_), _) => "None".dimmed().to_string(),
+                (None, true) => format_user_access_level(node.levels.0, node.levels.1),
+                (None, false) => "—".dimmed().to_string(),
+            },
+            attributes: format_user_write_mask(node.masks.0, node.masks.1),
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+
+    let variables = report.nodes.iter().filter(|node| node.access.is_some()).count();
+    let methods = report.nodes.iter().filter(|node| node.execute.is_some()).count();
+    let withheld = report.nodes
+        .iter()
+        .filter(|node| node.levels.0 & !node.levels.1 != 0
+            || node.masks.0 & !node.masks.1 != 0
+            || node.execute.is_some_and(|e| e.supported && !e.user))
+        .count();
+
+    println!("\n📊 Checked {} node(s) as {}", report.checked.to_string().bright_white(), report.user.bright_white());
+    println!("📖 Readable: {} of {} variable(s)", report.readable.to_string().green(), variables);
+    println!("✏️  Writable: {} of {} variable(s)", report.writable.to_string().blue(), variables);
+    println!("⚙️  Executable: {} of {} method(s)", report.executable.to_string().yellow(), methods);
+    if withheld > 0 {
+        println!("🔒 {} node(s) support operations this user may not perform", withheld.to_string().yellow());
+    }
+}
@@ -1,14 +1,36 @@
 use anyhow::{anyhow, Result};
 use colored::*;
-use opcua::client::Session;
 use opcua::types::*;
-use std::str::FromStr;
-use tabled::{Table, Tabled};
-use tracing::{debug, info};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tabled::Tabled;
+use tracing::{debug, info, warn};
 
-use crate::client::OpcUaClient;
-use crate::utils::formatter::{format_node_id, format_variant, format_status_code, format_node_class, format_access_level};
-use crate::utils::search::{search_nodes_by_name, SearchConfig};
+use crate::client::{check_session_budget, OpcUaClient};
+use crate::commands::export::{attribute_request, BATCH_SIZE};
+use crate::session::ServiceSession;
+use crate::types::{AttributeSet, ReadCommand};
+use crate::utils::browse_request::BrowseRequest;
+use crate::utils::bytestring::{max_byte_string_length, read_byte_string, read_chunk_size, sha256_hex};
+use crate::utils::enums::{enum_value, integer_value, read_data_types, EnumValue};
+use crate::utils::expand::expand_all;
+use crate::utils::index_range::{parse_index_range, range_lengths, range_suffix};
+use crate::utils::formatter::{format_array_full, format_array_grid, format_data_type, format_node_id, format_value_variant, format_variant, format_variant_typed, format_node_class, format_access_level, format_access_level_ex, format_data_type_definition, format_event_notifier, format_write_mask, is_strict, localized_text, sanitize_text, text_locale, variant_type_name, Severity};
+use crate::utils::labels::format_labelled_node_id;
+use crate::utils::table_layout::{print_keyed_table, print_table};
+use crate::utils::namespace::{parse_node_spec, suggest_namespace, NodeSpec};
+use crate::utils::outcome::OperationResult;
+use crate::utils::search::{default_search_roots, format_search_root, search_nodes_by_name, SearchConfig};
+use crate::utils::snapshot::Snapshot;
+use crate::utils::subscription::ValueSubscription;
+use crate::utils::units::{convert, find_unit, find_unit_by_id, numeric_value, supported_symbols, Unit};
+use crate::utils::value_map::{mapped_value, maps_types};
+
+/// Publishing and sampling interval of the temporary subscription used by `--via-subscription`
+const SUBSCRIPTION_INTERVAL: Duration = Duration::from_millis(100);
 
 #[derive(Tabled)]
 struct NodeReadInfo {
@@ -25,102 +47,412 @@ struct NodeReadInfo {
 }
 
 #[derive(Tabled)]
-struct DetailedNodeInfo {
+pub struct DetailedNodeInfo {
     #[tabled(rename = "Attribute")]
-    attribute: String,
+    pub attribute: String,
     #[tabled(rename = "Value")]
-    value: String,
+    pub value: String,
     #[tabled(rename = "Status")]
-    status: String,
+    pub status: String,
 }
 
-pub async fn execute(
-    client: &mut OpcUaClient,
-    node_ids: &[String],
-    all_attributes: bool,
-    include_value: bool,
-    search: bool,
-) -> Result<()> {
-    let session = client.session()?;
+/// Where `--save-bytes` and `--save` put ByteString values
+pub enum SaveBytes {
+    /// One file per node, named after its BrowseName
+    Directory(String),
+    File(String),
+}
+
+pub enum ReadTarget {
+    Node(NodeSpec),
+    Search(String),
+}
+
+pub struct ReadArgs {
+    pub targets: Vec<ReadTarget>,
+    pub all_attributes: Option<AttributeSet>,
+    pub include_value: bool,
+    pub first_match: bool,
+    /// Best ranked search matches shown per term
+    pub top: Option<usize>,
+    /// Where searches start, the default roots when empty
+    pub search_roots: Vec<NodeSpec>,
+    pub all_locales: bool,
+    pub show_types: bool,
+    /// Print arrays completely, matrices as a full grid
+    pub full_values: bool,
+    /// Read values through a temporary subscription, waiting at most this long per node
+    pub subscription_wait: Option<Duration>,
+    /// Unit numeric values are additionally shown in
+    pub convert_to: Option<&'static Unit>,
+    pub with_properties: bool,
+    /// Download ByteString values to files instead of showing them
+    pub save: Option<SaveBytes>,
+    /// Print rows as `key: value` blocks instead of tables
+    pub no_table: bool,
+    /// Elements of array values to read, `NumericRange::None` for the whole value
+    pub index_range: NumericRange,
+    /// Snapshot read instead of the server with `--from-snapshot`
+    pub snapshot: Option<Arc<Snapshot>>,
+}
+
+pub fn validate(args: &ReadCommand) -> Result<ReadArgs> {
+    let mut node_ids = args.node_ids.clone();
+    if let Some(path) = &args.from_file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read node ID file {}: {}", path, e))?;
+        node_ids.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from),
+        );
+    }
     
     if node_ids.is_empty() {
         return Err(anyhow!("No node IDs provided"));
     }
+    let node_ids = expand_all(&node_ids)?;
+    
+    let subscription_wait_ms = args.via_subscription.then_some(args.wait);
+    if subscription_wait_ms == Some(0) {
+        return Err(anyhow!("--wait must be greater than 0"));
+    }
+    
+    let convert_to = args.convert
+        .as_deref()
+        .map(|unit| find_unit(unit).ok_or_else(|| anyhow!(
+            "Unknown unit: {} (supported: {})",
+            unit,
+            supported_symbols().join(", ")
+        )))
+        .transpose()?;
+    
+    let save = match (&args.save_bytes, &args.save) {
+        (Some(dir), _) => Some(SaveBytes::Directory(dir.to_string())),
+        (None, Some(_)) if node_ids.len() != 1 => {
+            return Err(anyhow!("--save writes a single node, use --save-bytes <DIR> for {} nodes", node_ids.len()));
+        }
+        (None, Some(file)) => Some(SaveBytes::File(file.to_string())),
+        (None, None) => None,
+    };
+    
+    let index_range = args.index_range.as_deref().map(parse_index_range).transpose()?.unwrap_or(NumericRange::None);
+    
+    let targets = node_ids
+        .iter()
+        .map(|node_str| {
+            if args.search {
+                Ok(ReadTarget::Search(node_str.clone()))
+            } else {
+                parse_node_spec(node_str).map(ReadTarget::Node)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    
+    Ok(ReadArgs {
+        targets,
+        all_attributes: args.all_attributes,
+        include_value: args.include_value,
+        first_match: args.first,
+        top: args.top.map(|top| top as usize),
+        search_roots: args.search_roots.iter().map(|root| parse_node_spec(root)).collect::<Result<_>>()?,
+        all_locales: args.all_locales,
+        show_types: args.show_types,
+        full_values: args.full_values,
+        subscription_wait: subscription_wait_ms.map(Duration::from_millis),
+        convert_to,
+        with_properties: args.with_properties,
+        save,
+        no_table: args.no_table,
+        index_range,
+        snapshot: args.from_snapshot.as_deref().map(Snapshot::load).transpose()?.map(Arc::new),
+    })
+}
+
+/// The `--search-root` nodes resolved on the server, the default roots when none were given
+pub async fn search_roots(client: &OpcUaClient, specs: &[NodeSpec]) -> Result<Vec<NodeId>> {
+    if specs.is_empty() {
+        return Ok(default_search_roots());
+    }
+    let mut roots = Vec::with_capacity(specs.len());
+    for spec in specs {
+        roots.push(client.resolve_node(spec).await?);
+    }
+    Ok(roots)
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &ReadArgs) -> Result<()> {
+    if let Some(save) = &args.save {
+        return save_byte_strings(client, args, save).await;
+    }
     
     println!("\n{}", "📖 Reading OPC-UA Nodes".bright_cyan().bold());
+    if let Some(snapshot) = &args.snapshot {
+        println!("{}", snapshot.banner());
+    }
     println!("{}", "─".repeat(40));
     
+    let all_results = read_nodes(client, args).await?;
+    if all_results.is_empty() {
+        println!("⚠️  No data retrieved");
+        return Ok(());
+    }
+    
+    // Nodes without any read result are listed below the others instead of as empty rows
+    let total = all_results.len();
+    let (failed_nodes, all_results): (Vec<NodeData>, Vec<NodeData>) =
+        all_results.into_iter().partition(|data| data.fault.is_some());
+    
+    // Display results
+    if !all_results.is_empty() {
+        if args.all_attributes.is_some() {
+            display_detailed_results(&all_results, args.show_types, args.full_values, args.no_table);
+        } else {
+            display_summary_results(&all_results, args.show_types, args.full_values, args.include_value, args.no_table);
+        }
+    }
+    
+    // Nodes that could not be read fail the command whether strict or not
+    if let Some(fault) = failed_nodes.first().and_then(|data| data.fault.clone()) {
+        display_failed_nodes(&failed_nodes);
+        return Err(anyhow::Error::new(fault)
+            .context(format!("{} of {} node(s) could not be read", failed_nodes.len(), total)));
+    }
+    
+    // Attributes other than the Value are often Bad for nodes of other classes and do not count
+    let failed = all_results
+        .iter()
+        .filter(|data| data.value_result().is_some_and(|result| result.is_failure()))
+        .count();
+    if is_strict() && failed > 0 {
+        return Err(anyhow!("{} of {} value(s) are not Good (strict mode)", failed, all_results.len()));
+    }
+    
+    println!("\n✅ {}", "Read operation completed successfully".green());
+    Ok(())
+}
+
+/// Read the targets with everything the options add to them, one entry per node read. Searches
+/// without a match add nothing, nodes that could not be read at all carry their fault.
+pub async fn read_nodes(client: &OpcUaClient, args: &ReadArgs) -> Result<Vec<NodeData>> {
+    let session = client.session()?;
     let mut all_results = Vec::new();
+    // Values come from the subscription instead when reading via subscription
+    let read_value = |include_value: bool| include_value && args.subscription_wait.is_none();
     
-    for node_str in node_ids {
-        if search {
-            // Search for nodes by name
-            info!("🔍 Searching for nodes matching: '{}'", node_str);
-            
-            let config = SearchConfig {
-                max_nodes: 1000,
-                max_depth: 10,
-                ..Default::default()
-            };
-            
-            let search_results = search_nodes_by_name(session, node_str, config, client.is_verbose()).await?;
-            
-            if search_results.is_empty() {
-                println!("⚠️  No nodes found matching: '{}'", node_str.yellow());
-                continue;
+    for target in &args.targets {
+        match target {
+            ReadTarget::Search(search_term) => {
+                // Search for nodes by name
+                info!("🔍 Searching for nodes matching: '{}'", search_term);
+                
+                let config = SearchConfig {
+                    max_nodes: 1000,
+                    max_depth: 10,
+                    stop_after_matches: args.first_match.then_some(1),
+                    prioritize_prefix: args.first_match,
+                    search_roots: search_roots(client, &args.search_roots).await?,
+                    ..Default::default()
+                };
+                
+                let mut search_results = search_nodes_by_name(session, search_term, config, client.is_verbose()).await?;
+                
+                if search_results.is_empty() {
+                    println!("⚠️  No nodes found matching: '{}'", search_term.yellow());
+                    continue;
+                }
+                
+                println!("✅ Found {} matching nodes for '{}'", 
+                        search_results.len().to_string().bright_green(), 
+                        search_term.bright_white());
+                if let Some(top) = args.top.filter(|top| *top < search_results.len()) {
+                    println!("🏆 Showing the best {} of them", top.to_string().bright_white());
+                    search_results.truncate(top);
+                }
+                if client.is_verbose() {
+                    for (rank, search_result) in search_results.iter().enumerate() {
+                        let score = search_result.score.map(|score| score.to_string()).unwrap_or_default();
+                        let root = search_result.root.as_ref().map(|root| format!("under {}", format_search_root(root))).unwrap_or_default();
+                        println!("   {}. {} ({}) {} {}", rank + 1, search_result.display_name.bright_white(),
+                                 format_node_id(&search_result.node_id), root, score.dimmed());
+                    }
+                }
+                
+                for search_result in search_results {
+                    let result = read_node_info(
+                        session, 
+                        &search_result.node_id, 
+                        args.all_attributes, 
+                        read_value(args.include_value || search_result.node_class == NodeClass::Variable),
+                        &args.index_range,
+                        Some(search_result.node_class),
+                        client.is_verbose()
+                    ).await;
+                    all_results.push(NodeData::from_search_read(&search_result.node_id, result));
+                }
             }
-            
-            println!("✅ Found {} matching nodes for '{}'", 
-                    search_results.len().to_string().bright_green(), 
-                    node_str.bright_white());
-            
-            for search_result in search_results {
+            ReadTarget::Node(spec) => {
+                // Read specific node ID
+                let node_id = &client.resolve_node(spec).await?;
+                debug!("Reading node: {}", format_node_id(node_id));
+                
                 let result = read_node_info(
                     session, 
-                    &search_result.node_id, 
-                    all_attributes, 
-                    include_value || search_result.node_class == NodeClass::Variable,
+                    node_id, 
+                    args.all_attributes, 
+                    read_value(args.include_value),
+                    &args.index_range,
+                    None,
                     client.is_verbose()
-                ).await?;
+                ).await;
+                let mut result = NodeData::from_read(node_id, result)?;
+                
+                if result.is_unknown()
+                    && let Ok(namespaces) = client.namespaces().await
+                    && let Some(alternative) = suggest_namespace(session, node_id, namespaces, client.auto_namespace()).await?
+                {
+                    let alternative_result = read_node_info(
+                        session,
+                        &alternative,
+                        args.all_attributes, 
+                        read_value(args.include_value),
+                        &args.index_range,
+                        None,
+                        client.is_verbose()
+                    ).await;
+                    result = NodeData::from_read(&alternative, alternative_result)?;
+                } else if result.is_unknown() && matches!(client.uri_for_index(node_id.namespace).await, Ok(None)) {
+                    println!("💡 ns={} is not registered on the server, `opcua-walker info` lists its namespaces", node_id.namespace);
+                }
                 all_results.push(result);
             }
-        } else {
-            // Read specific node ID
-            let node_id = parse_node_id(node_str)?;
-            debug!("Reading node: {}", format_node_id(&node_id));
-            
-            let result = read_node_info(
-                session, 
-                &node_id, 
-                all_attributes, 
-                include_value,
-                client.is_verbose()
-            ).await?;
-            all_results.push(result);
         }
     }
     
     if all_results.is_empty() {
-        println!("⚠️  No data retrieved");
-        return Ok(());
+        return Ok(all_results);
     }
     
-    // Display results
-    if all_attributes {
-        display_detailed_results(&all_results);
-    } else {
-        display_summary_results(&all_results);
+    if let Some(wait) = args.subscription_wait {
+        read_values_via_subscription(session, &mut all_results, wait).await?;
     }
     
-    println!("\n✅ {}", "Read operation completed successfully".green());
+    if let Some(target) = args.convert_to {
+        convert_values(session, &mut all_results, target).await?;
+    }
+    
+    if args.all_locales {
+        read_all_locales(client, &mut all_results).await?;
+    }
+    
+    read_array_dimensions(session, &mut all_results).await?;
+    read_enum_values(session, &mut all_results).await?;
+    map_values(session, &mut all_results).await?;
+    
+    if args.with_properties {
+        read_properties(session, &mut all_results).await?;
+    }
+    
+    Ok(all_results)
+}
+
+/// Download the ByteString values of the nodes to files, in slices when a value is larger than
+/// one read can carry
+async fn save_byte_strings(client: &OpcUaClient, args: &ReadArgs, save: &SaveBytes) -> Result<()> {
+    let session = client.session()?;
+    let chunk = read_chunk_size(max_byte_string_length(session).await);
+    
+    println!("\n{}", "💾 Saving ByteString Values".bright_cyan().bold());
+    if let Some(snapshot) = &args.snapshot {
+        println!("{}", snapshot.banner());
+    }
+    println!("📦 Slice size: {} bytes", chunk.to_string().bright_white());
+    println!("{}", "─".repeat(40));
+    
+    if let SaveBytes::Directory(dir) = save {
+        fs::create_dir_all(dir).map_err(|e| anyhow!("Failed to create directory {}: {}", dir, e))?;
+    }
+    
+    let mut used_names = HashSet::new();
+    let mut failures = Vec::new();
+    for target in &args.targets {
+        let ReadTarget::Node(spec) = target else {
+            unreachable!("--save-bytes and --save conflict with --search");
+        };
+        let node_id = client.resolve_node(spec).await?;
+        if let Err(e) = save_byte_string(session, &node_id, save, chunk, &mut used_names).await {
+            println!("❌ {}: {:#}", format_node_id(&node_id).bright_white(), e);
+            failures.push(e);
+        }
+    }
+    
+    let failed = failures.len();
+    if let Some(error) = failures.into_iter().next() {
+        return Err(error.context(format!("{} of {} value(s) could not be saved", failed, args.targets.len())));
+    }
+    println!("\n✅ {}", "ByteString values saved".green());
     Ok(())
 }
 
+async fn save_byte_string(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    save: &SaveBytes,
+    chunk: usize,
+    used_names: &mut HashSet<String>,
+) -> Result<()> {
+    let (data, reads) = read_byte_string(session, node_id, chunk).await?;
+    
+    let path = match save {
+        SaveBytes::File(file) => PathBuf::from(file),
+        SaveBytes::Directory(dir) => {
+            let browse_name = session.read(&[attribute_request(node_id, AttributeId::BrowseName)], TimestampsToReturn::Neither, 0.0).await?
+                .into_iter()
+                .next()
+                .and_then(|dv| match dv.value {
+                    Some(Variant::QualifiedName(name)) => Some(name.name.to_string()),
+                    _ => None,
+                });
+            Path::new(dir).join(unique_file_name(browse_name.as_deref().unwrap_or(&format_node_id(node_id)), used_names))
+        }
+    };
+    fs::write(&path, &data).map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+    
+    println!("💾 {} → {}", format_node_id(node_id).bright_white(), path.display().to_string().bright_white());
+    println!("   Size: {} bytes in {} read(s)", data.len().to_string().bright_green(), reads);
+    println!("   SHA-256: {}", sha256_hex(&data));
+    Ok(())
+}
+
+/// A file name for a BrowseName, with characters unsafe in paths replaced and a counter appended
+/// when several nodes share the name
+fn unique_file_name(name: &str, used_names: &mut HashSet<String>) -> String {
+    let base: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    let base = match base.trim_start_matches('.') {
+        "" => "value".to_string(),
+        base => base.to_string(),
+    };
+    let mut candidate = base.clone();
+    let mut counter = 1;
+    while !used_names.insert(candidate.clone()) {
+        counter += 1;
+        candidate = format!("{}-{}", base, counter);
+    }
+    candidate
+}
+
 async fn read_node_info(
-    session: &Session,
+    session: &ServiceSession,
     node_id: &NodeId,
-    all_attributes: bool,
+    attribute_set: Option<AttributeSet>,
     include_value: bool,
+    index_range: &NumericRange,
+    known_class: Option<NodeClass>,
     verbose: bool,
 ) -> Result<NodeData> {
     if verbose {
@@ -133,54 +465,466 @@ async fn read_node_info(
         AttributeId::BrowseName,
     ];
     
-    if include_value {
-        attributes.push(AttributeId::Value);
-    }
-    
-    if all_attributes {
+    if attribute_set == Some(AttributeSet::Full) {
+        attributes = (1..=27).filter_map(|id| AttributeId::from_u32(id).ok()).collect();
+    } else if attribute_set.is_some() {
         attributes.extend_from_slice(&[
             AttributeId::Description,
             AttributeId::DataType,
             AttributeId::ValueRank,
+            AttributeId::ArrayDimensions,
             AttributeId::AccessLevel,
             AttributeId::UserAccessLevel,
             AttributeId::MinimumSamplingInterval,
             AttributeId::Historizing,
+            AttributeId::WriteMask,
+            AttributeId::UserWriteMask,
         ]);
     }
     
     let read_requests: Vec<ReadValueId> = attributes
-        .into_iter()
+        .iter()
         .map(|attr| ReadValueId {
             node_id: node_id.clone(),
-            attribute_id: attr as u32,
-            index_range: NumericRange::None,
+            attribute_id: *attr as u32,
+            index_range: if *attr == AttributeId::Value { index_range.clone() } else { NumericRange::None },
             data_encoding: QualifiedName::null(),
         })
         .collect();
     
-    let read_results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+    let read_results = session.read_values(&read_requests).await?;
     
-    Ok(NodeData {
+    let mut data = NodeData {
         node_id: node_id.clone(),
+        attributes,
         read_results,
-        all_attributes,
-    })
+        index_range: index_range.clone(),
+        locales: None,
+        conversion: None,
+        array_dimensions: None,
+        enum_value: None,
+        mapped_value: None,
+        properties: Vec::new(),
+        fault: None,
+        value_fault: None,
+    };
+    
+    // Attributes the node class does not define are answered with BadAttributeIdInvalid
+    if attribute_set == Some(AttributeSet::Full) {
+        let (attributes, read_results) = data.attributes
+            .into_iter()
+            .zip(data.read_results)
+            .filter(|(_, dv)| dv.status != Some(StatusCode::BadAttributeIdInvalid))
+            .unzip();
+        data.attributes = attributes;
+        data.read_results = read_results;
+    }
+    
+    // Only Variables and VariableTypes have a Value, reading it from other nodes is bound to fail.
+    // The class a search found stands in when the NodeClass itself could not be read.
+    let has_value = match data.node_class() {
+        Some(_) => data.has_value_attribute(),
+        None => known_class.is_some_and(|class| matches!(class, NodeClass::Variable | NodeClass::VariableType)),
+    };
+    if include_value && has_value && data.attribute(AttributeId::Value).is_none() {
+        let value = session
+            .read_values(&[ReadValueId { index_range: index_range.clone(), ..ReadValueId::from(node_id) }])
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        data.attributes.insert(3, AttributeId::Value);
+        data.read_results.insert(3, value);
+    }
+    
+    Ok(data)
 }
 
-struct NodeData {
+/// What was read of one node
+pub struct NodeData {
     node_id: NodeId,
+    /// Attributes in the order they were requested, parallel to `read_results`
+    attributes: Vec<AttributeId>,
     read_results: Vec<DataValue>,
-    all_attributes: bool,
+    /// Elements of the Value that were read, shown next to the node ID
+    index_range: NumericRange,
+    locales: Option<LocalizedVariants>,
+    conversion: Option<Conversion>,
+    /// ArrayDimensions attribute of array values whose Variant carries no usable dimensions
+    array_dimensions: Option<Vec<u32>>,
+    /// Symbolic name of values whose DataType is an enumeration
+    enum_value: Option<EnumValue>,
+    /// Display string of the value from `--value-map`, shown instead of the value
+    mapped_value: Option<&'static str>,
+    /// HasProperty targets with their values, read with `--with-properties`
+    properties: Vec<PropertyValue>,
+    /// Why the node has no read results at all
+    fault: Option<OperationResult>,
+    /// Set when a Value read via subscription never arrived, its BadTimeout is the client's
+    value_fault: Option<OperationResult>,
+}
+
+struct PropertyValue {
+    name: String,
+    node_id: NodeId,
+    value: DataValue,
+}
+
+/// Outcome of `--convert` for a single value
+enum Conversion {
+    /// Original and converted value with their units
+    Converted(String),
+    /// Why the value is shown unconverted
+    Unconverted(String),
+}
+
+impl NodeData {
+    /// Keep service faults as the node's result, other errors end the read
+    fn from_read(node_id: &NodeId, result: Result<NodeData>) -> Result<NodeData> {
+        let error = match result {
+            Ok(data) => return Ok(data),
+            Err(error) => error,
+        };
+        match OperationResult::of_error(&error) {
+            Some(fault @ OperationResult::ServiceFault(_)) => Ok(NodeData::faulted(node_id, fault)),
+            _ => Err(error),
+        }
+    }
+    
+    /// Keep every error as the node's result: a found node may vanish before it is read, and one
+    /// such node must not discard the results of all the others
+    fn from_search_read(node_id: &NodeId, result: Result<NodeData>) -> NodeData {
+        result.unwrap_or_else(|error| {
+            debug!("Reading found node {} failed: {:#}", format_node_id(node_id), error);
+            let fault = OperationResult::of_error(&error)
+                .unwrap_or_else(|| OperationResult::ClientError(format!("{:#}", error)));
+            NodeData::faulted(node_id, fault)
+        })
+    }
+    
+    fn faulted(node_id: &NodeId, fault: OperationResult) -> NodeData {
+        NodeData {
+            node_id: node_id.clone(),
+            attributes: Vec::new(),
+            read_results: Vec::new(),
+            index_range: NumericRange::None,
+            locales: None,
+            conversion: None,
+            array_dimensions: None,
+            enum_value: None,
+            mapped_value: None,
+            properties: Vec::new(),
+            fault: Some(fault),
+            value_fault: None,
+        }
+    }
+    
+    /// Result of the attribute at `position`
+    fn result(&self, position: usize) -> OperationResult {
+        match (&self.value_fault, self.attributes.get(position)) {
+            (Some(fault), Some(AttributeId::Value)) => fault.clone(),
+            _ => self.read_results.get(position).map_or(OperationResult::Good, OperationResult::of_data_value),
+        }
+    }
+    
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+    
+    /// Why the node has no read results at all
+    pub fn fault(&self) -> Option<&OperationResult> {
+        self.fault.as_ref()
+    }
+    
+    pub fn value_result(&self) -> Option<OperationResult> {
+        self.attributes
+            .iter()
+            .position(|a| *a == AttributeId::Value)
+            .map(|position| self.result(position))
+    }
+    
+    fn is_unknown(&self) -> bool {
+        self.read_results
+            .first()
+            .and_then(|dv| dv.status)
+            .is_some_and(|status| status == StatusCode::BadNodeIdUnknown)
+    }
+    
+    pub fn node_class(&self) -> Option<u32> {
+        match self.attribute(AttributeId::NodeClass).and_then(|dv| dv.value.as_ref()) {
+            Some(Variant::Int32(class)) => Some(*class as u32),
+            Some(Variant::UInt32(class)) => Some(*class),
+            _ => None,
+        }
+    }
+    
+    /// Variables and VariableTypes carry a Value attribute
+    fn has_value_attribute(&self) -> bool {
+        matches!(self.node_class(), Some(2 | 16))
+    }
+    
+    pub fn attribute(&self, attribute: AttributeId) -> Option<&DataValue> {
+        self.attributes
+            .iter()
+            .position(|a| *a == attribute)
+            .and_then(|idx| self.read_results.get(idx))
+    }
 }
 
-fn display_summary_results(results: &[NodeData]) {
+/// Fill in the Value attribute from the first notification of a temporary subscription.
+///
+/// Only nodes with a Value attribute are monitored. Nodes that do not report within `wait`
+/// get a BadTimeout status.
+async fn read_values_via_subscription(
+    session: &ServiceSession,
+    results: &mut [NodeData],
+    wait: Duration,
+) -> Result<()> {
+    let monitored: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, data)| !data.is_unknown() && data.has_value_attribute())
+        .map(|(idx, _)| idx)
+        .collect();
+    
+    if monitored.is_empty() {
+        return Ok(());
+    }
+    
+    let node_ids: Vec<NodeId> = monitored.iter().map(|idx| results[*idx].node_id.clone()).collect();
+    info!("📡 Waiting up to {} ms for {} node(s) via subscription", wait.as_millis(), node_ids.len());
+    
+    let (mut subscription, statuses) = ValueSubscription::create(session, &node_ids, SUBSCRIPTION_INTERVAL).await?;
+    
+    let mut values: Vec<Option<DataValue>> = statuses
+        .iter()
+        .map(|status| (!status.is_good()).then(|| DataValue {
+            status: Some(*status),
+            ..DataValue::null()
+        }))
+        .collect();
+    let mut pending: HashSet<usize> = (0..values.len()).filter(|idx| values[*idx].is_none()).collect();
+    
+    let deadline = tokio::time::Instant::now() + wait;
+    while !pending.is_empty() {
+        match tokio::time::timeout_at(deadline, subscription.next()).await {
+            Ok(Some((idx, data_value))) => {
+                if pending.remove(&idx) {
+                    values[idx] = Some(data_value);
+                }
+            }
+            Ok(None) | Err(_) => break,
+        }
+    }
+    
+    subscription.delete().await;
+    
+    if !pending.is_empty() {
+        warn!("{} node(s) did not report within {} ms", pending.len(), wait.as_millis());
+    }
+    
+    for (idx, value) in monitored.into_iter().zip(values) {
+        let data = &mut results[idx];
+        if value.is_none() {
+            data.value_fault = Some(OperationResult::ClientError(format!("no notification within {} ms", wait.as_millis())));
+        }
+        let value = value.unwrap_or_else(|| DataValue {
+            status: Some(StatusCode::BadTimeout),
+            ..DataValue::null()
+        });
+        // Every attribute includes a plain read of the Value, otherwise it goes to the same
+        // position a plain read puts it, right after the basic attributes
+        if let Some(position) = data.attributes.iter().position(|a| *a == AttributeId::Value) {
+            data.read_results[position] = value;
+        } else {
+            let position = data.attributes.len().min(3);
+            data.attributes.insert(position, AttributeId::Value);
+            data.read_results.insert(position, value);
+        }
+    }
+    
+    Ok(())
+}
+
+/// Convert numeric values into `target` using the EngineeringUnits property of each node.
+///
+/// Values stay untouched when the node has no EngineeringUnits, the unit is not in the
+/// conversion table or belongs to another family; the reason is noted next to the value.
+async fn convert_values(session: &ServiceSession, results: &mut [NodeData], target: &Unit) -> Result<()> {
+    let numeric: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, data)| data.attribute(AttributeId::Value)
+            .and_then(|dv| dv.value.as_ref())
+            .and_then(numeric_value)
+            .is_some())
+        .map(|(idx, _)| idx)
+        .collect();
+    
+    if numeric.is_empty() {
+        return Ok(());
+    }
+    
+    let browse_paths: Vec<BrowsePath> = numeric
+        .iter()
+        .map(|idx| BrowsePath {
+            starting_node: results[*idx].node_id.clone(),
+            relative_path: RelativePath {
+                elements: Some(vec![RelativePathElement {
+                    reference_type_id: ReferenceTypeId::HasProperty.into(),
+                    is_inverse: false,
+                    include_subtypes: true,
+                    target_name: QualifiedName::new(0, "EngineeringUnits"),
+                }]),
+            },
+        })
+        .collect();
+    
+    let path_results = session.translate_browse_paths_to_node_ids(&browse_paths).await?;
+    let unit_nodes: Vec<Option<NodeId>> = path_results
+        .iter()
+        .map(|result| result.targets.as_ref()
+            .and_then(|targets| targets.first())
+            .map(|target| target.target_id.node_id.clone()))
+        .collect();
+    
+    let read_requests: Vec<ReadValueId> = unit_nodes.iter().flatten().map(ReadValueId::from).collect();
+    let mut unit_values = if read_requests.is_empty() {
+        Vec::new()
+    } else {
+        session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?
+    }
+    .into_iter();
+    
+    for (idx, unit_node) in numeric.into_iter().zip(unit_nodes) {
+        let data = &mut results[idx];
+        let Some(value) = data.attribute(AttributeId::Value).and_then(|dv| dv.value.as_ref()).and_then(numeric_value) else {
+            continue;
+        };
+        
+        let units = unit_node
+            .and(unit_values.next())
+            .and_then(|dv| match dv.value {
+                Some(Variant::ExtensionObject(obj)) => obj.into_inner_as::<EUInformation>(),
+                _ => None,
+            });
+        
+        data.conversion = Some(match units {
+            None => Conversion::Unconverted("no EngineeringUnits".to_string()),
+            Some(units) => match find_unit_by_id(units.unit_id) {
+                None => Conversion::Unconverted(format!("unit {} not convertible", localized_text(&units.display_name))),
+                Some(source) => match convert(value, source, target) {
+                    Some(converted) => Conversion::Converted(format!("{} {} → {} {}", value, source.symbol, converted, target.symbol)),
+                    None => Conversion::Unconverted(format!("{} cannot be converted to {}", source.symbol, target.symbol)),
+                },
+            },
+        });
+    }
+    
+    Ok(())
+}
+
+/// Distinct locale/text pairs collected for the localized attributes of a node
+#[derive(Default)]
+struct LocalizedVariants {
+    display_names: Vec<LocalizedText>,
+    descriptions: Vec<LocalizedText>,
+}
+
+impl LocalizedVariants {
+    fn add(texts: &mut Vec<LocalizedText>, data_value: Option<&DataValue>) {
+        if let Some(DataValue { value: Some(Variant::LocalizedText(text)), .. }) = data_value
+            && !texts.iter().any(|t| t.locale == text.locale && t.text == text.text)
+        {
+            texts.push((**text).clone());
+        }
+    }
+    
+    fn for_attribute(&self, attribute: AttributeId) -> Option<&[LocalizedText]> {
+        match attribute {
+            AttributeId::DisplayName => Some(&self.display_names),
+            AttributeId::Description => Some(&self.descriptions),
+            _ => None,
+        }
+    }
+}
+
+/// Re-read DisplayName and Description once per server locale using a dedicated session each
+async fn read_all_locales(client: &OpcUaClient, results: &mut [NodeData]) -> Result<()> {
+    let session = client.session()?;
+    
+    let locale_request = ReadValueId::from(&NodeId::from(VariableId::Server_ServerCapabilities_LocaleIdArray));
+    let locale_results = session.read(&[locale_request], TimestampsToReturn::Neither, 0.0).await?;
+    let locales: Vec<String> = match locale_results.first().and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::Array(array)) => array.values.iter()
+            .filter_map(|v| match v {
+                Variant::String(s) if !s.is_empty() => Some(s.as_ref().to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    
+    // Seed with the texts returned for the current session locale
+    for data in results.iter_mut() {
+        let mut variants = LocalizedVariants::default();
+        LocalizedVariants::add(&mut variants.display_names, data.attribute(AttributeId::DisplayName));
+        LocalizedVariants::add(&mut variants.descriptions, data.attribute(AttributeId::Description));
+        data.locales = Some(variants);
+    }
+    
+    if locales.is_empty() {
+        println!("ℹ️  Server does not advertise any locales, showing default texts only");
+        return Ok(());
+    }
+    
+    info!("🌐 Reading localized texts for {} locale(s): {}", locales.len(), locales.join(", "));
+    // One session per locale is opened next to the current one
+    check_session_budget(client.max_sessions(), [client.endpoint(), client.endpoint()])?;
+    
+    let read_requests: Vec<ReadValueId> = results
+        .iter()
+        .flat_map(|data| {
+            [AttributeId::DisplayName, AttributeId::Description].map(|attr| ReadValueId {
+                node_id: data.node_id.clone(),
+                attribute_id: attr as u32,
+                index_range: NumericRange::None,
+                data_encoding: QualifiedName::null(),
+            })
+        })
+        .collect();
+    
+    for locale in locales {
+        let mut locale_client = client.with_locales(vec![locale.clone()]);
+        if let Err(e) = locale_client.connect().await {
+            warn!("Could not open session for locale {}: {}", locale, e);
+            continue;
+        }
+        
+        match locale_client.session()?.read(&read_requests, TimestampsToReturn::Neither, 0.0).await {
+            Ok(values) => {
+                for (data, pair) in results.iter_mut().zip(values.chunks(2)) {
+                    if let Some(variants) = data.locales.as_mut() {
+                        LocalizedVariants::add(&mut variants.display_names, pair.first());
+                        LocalizedVariants::add(&mut variants.descriptions, pair.get(1));
+                    }
+                }
+            }
+            Err(e) => warn!("Reading localized texts for locale {} failed: {}", locale, e),
+        }
+        
+        locale_client.disconnect().await?;
+    }
+    
+    Ok(())
+}
+
+fn display_summary_results(results: &[NodeData], show_types: bool, full_values: bool, include_value: bool, no_table: bool) {
     let table_data: Vec<NodeReadInfo> = results
         .iter()
-        .map(|data| {
-            let display_name = get_attribute_value(&data.read_results, 0)
-                .unwrap_or_else(|| "Unknown".to_string());
-            let node_class_str = get_attribute_value(&data.read_results, 1)
+        .flat_map(|data| {
+            let display_name = node_name(data);
+            let node_class_str = get_attribute_value(data, AttributeId::NodeClass)
                 .and_then(|s| s.parse::<u32>().ok())
                 .and_then(|val| match val {
                     1 => Some(NodeClass::Object),
@@ -195,120 +939,504 @@ fn display_summary_results(results: &[NodeData]) {
                 })
                 .map(format_node_class)
                 .unwrap_or_else(|| "Unknown".to_string());
-            let value = get_value_string(&data.read_results, data.all_attributes);
-            let status = get_status_string(&data.read_results);
+            let value = if include_value && data.node_class().is_some() && !data.has_value_attribute() {
+                "n/a (not a Variable)".dimmed().to_string()
+            } else {
+                get_value_string(data, show_types, full_values)
+            };
+            let status = get_status_string(data);
             
-            NodeReadInfo {
-                node_id: format_node_id(&data.node_id),
+            let row = NodeReadInfo {
+                node_id: format!("{}{}", format_labelled_node_id(&data.node_id), range_suffix(&data.index_range)),
                 display_name,
                 node_class: node_class_str,
                 value,
                 status,
-            }
+            };
+            std::iter::once(row).chain(data.properties.iter().map(|property| NodeReadInfo {
+                node_id: format!("  ↳ {}", format_node_id(&property.node_id)).dimmed().to_string(),
+                display_name: format!("  {}", sanitize_text(&property.name)),
+                node_class: "ᵖ Property".dimmed().to_string(),
+                value: format_property_value(&property.value),
+                status: property_status(&property.value),
+            }))
         })
         .collect();
     
-    let table = Table::new(table_data);
-    println!("{}", table);
+    // The value column gives way, node IDs stay readable
+    print_table(&table_data, 3, no_table);
 }
 
-fn display_detailed_results(results: &[NodeData]) {
+/// The nodes that have no read result at all, with the reason
+fn display_failed_nodes(failed_nodes: &[NodeData]) {
+    println!("\n❌ {} node(s) could not be read:", failed_nodes.len().to_string().red());
+    for data in failed_nodes {
+        if let Some(fault) = &data.fault {
+            println!("  • {}: {}", format_labelled_node_id(&data.node_id).bright_white(), fault.describe());
+        }
+    }
+}
+
+fn display_detailed_results(results: &[NodeData], show_types: bool, full_values: bool, no_table: bool) {
     for (i, data) in results.iter().enumerate() {
         if i > 0 {
             println!();
         }
         
-        println!("📋 {}: {}", "Node".bright_white(), format_node_id(&data.node_id).bright_cyan());
-        
-        let attributes = [
-            "DisplayName", "NodeClass", "BrowseName", "Value", 
-            "Description", "DataType", "ValueRank", "AccessLevel",
-            "UserAccessLevel", "MinimumSamplingInterval", "Historizing"
-        ];
-        
-        let mut table_data = Vec::new();
+        let node = format!("{}{}", format_labelled_node_id(&data.node_id), range_suffix(&data.index_range));
+        println!("📋 {}: {}", "Node".bright_white(), node.bright_cyan());
+        print_keyed_table(&detailed_rows(data, show_types, full_values), 1, no_table);
+    }
+}
+
+/// The attributes and properties of a node as `read --all-attributes` lists them
+fn detailed_rows(data: &NodeData, show_types: bool, full_values: bool) -> Vec<DetailedNodeInfo> {
+    let mut table_data = Vec::new();
+    
+    for (position, (attribute, data_value)) in data.attributes.iter().zip(&data.read_results).enumerate() {
+        let localized = data.locales.as_ref()
+            .and_then(|variants| variants.for_attribute(*attribute))
+            .filter(|texts| texts.len() > 1);
         
-        for (idx, attr_name) in attributes.iter().enumerate() {
-            if idx < data.read_results.len() {
-                let data_value = &data.read_results[idx];
-                let value_str = if let Some(variant) = &data_value.value {
-                    match attr_name {
-                        &"NodeClass" => {
-                            if let Variant::UInt32(val) = variant {
-                                match val {
-                                    1 => Some(NodeClass::Object),
-                                    2 => Some(NodeClass::Variable), 
-                                    4 => Some(NodeClass::Method),
-                                    8 => Some(NodeClass::ObjectType),
-                                    16 => Some(NodeClass::VariableType),
-                                    32 => Some(NodeClass::ReferenceType),
-                                    64 => Some(NodeClass::DataType),
-                                    128 => Some(NodeClass::View),
-                                    _ => None,
-                                }
-                                .map(format_node_class)
-                                .unwrap_or_else(|| format!("Unknown ({})", val))
-                            } else {
-                                format_variant(variant)
-                            }
-                        }
-                        &"AccessLevel" | &"UserAccessLevel" => {
-                            if let Variant::Byte(val) = variant {
-                                format_access_level(*val)
-                            } else {
-                                format_variant(variant)
-                            }
+        let value_str = if let Some(texts) = localized {
+            format_locale_list(texts)
+        } else if let Some(variant) = &data_value.value {
+            match attribute {
+                AttributeId::NodeClass => {
+                    if let Some(val) = data.node_class() {
+                        match val {
+                            1 => Some(NodeClass::Object),
+                            2 => Some(NodeClass::Variable), 
+                            4 => Some(NodeClass::Method),
+                            8 => Some(NodeClass::ObjectType),
+                            16 => Some(NodeClass::VariableType),
+                            32 => Some(NodeClass::ReferenceType),
+                            64 => Some(NodeClass::DataType),
+                            128 => Some(NodeClass::View),
+                            _ => None,
                         }
-                        _ => format_variant(variant)
+                        .map(format_node_class)
+                        .unwrap_or_else(|| format!("Unknown ({})", val))
+                    } else {
+                        format_variant(variant)
                     }
-                } else {
-                    "—".dimmed().to_string()
-                };
-                
-                table_data.push(DetailedNodeInfo {
-                    attribute: attr_name.to_string(),
-                    value: value_str,
-                    status: data_value.status.as_ref()
-                        .map(|s| format_status_code(s))
-                        .unwrap_or_else(|| "✅ Good".green().to_string()),
-                });
+                }
+                AttributeId::AccessLevel | AttributeId::UserAccessLevel => {
+                    if let Variant::Byte(val) = variant {
+                        format_access_level(*val)
+                    } else {
+                        format_variant(variant)
+                    }
+                }
+                AttributeId::AccessLevelEx => {
+                    if let Variant::UInt32(val) = variant {
+                        format_access_level_ex(*val)
+                    } else {
+                        format_variant(variant)
+                    }
+                }
+                AttributeId::EventNotifier => {
+                    if let Variant::Byte(val) = variant {
+                        format_event_notifier(*val)
+                    } else {
+                        format_variant(variant)
+                    }
+                }
+                AttributeId::WriteMask | AttributeId::UserWriteMask => {
+                    if let Variant::UInt32(val) = variant {
+                        format_write_mask(*val)
+                    } else {
+                        format_variant(variant)
+                    }
+                }
+                AttributeId::InverseName => {
+                    if let Variant::LocalizedText(text) = variant {
+                        let locale = text_locale(text).unwrap_or("(default)");
+                        format!("\"{}\" {}", sanitize_text(localized_text(text)), locale.dimmed())
+                    } else {
+                        format_variant(variant)
+                    }
+                }
+                AttributeId::DataTypeDefinition => {
+                    if let Variant::ExtensionObject(definition) = variant {
+                        format_data_type_definition(definition)
+                    } else {
+                        format_variant(variant)
+                    }
+                }
+                AttributeId::Value => with_conversion(data, format_value(data, variant, show_types, full_values)),
+                _ => format_variant(variant)
             }
-        }
+        } else {
+            "—".dimmed().to_string()
+        };
         
-        let table = Table::new(table_data);
-        println!("{}", table);
+        table_data.push(DetailedNodeInfo {
+            attribute: format!("{:?}", attribute),
+            value: value_str,
+            status: data.result(position).describe(),
+        });
+    }
+    for property in &data.properties {
+        table_data.push(DetailedNodeInfo {
+            attribute: format!("ᵖ {}", sanitize_text(&property.name)),
+            value: format_property_value(&property.value),
+            status: property_status(&property.value),
+        });
     }
+    
+    table_data
+}
+
+/// The Common attributes of a node, and its value when asked for, as `read --all-attributes`
+/// lists them
+pub async fn read_attribute_rows(session: &ServiceSession, node_id: &NodeId, include_value: bool) -> Result<Vec<DetailedNodeInfo>> {
+    let mut data = read_node_info(session, node_id, Some(AttributeSet::Common), include_value, &NumericRange::None, None, false).await?;
+    // The Common set asks Objects for Variable attributes, which they do not have
+    let (attributes, read_results) = data.attributes
+        .into_iter()
+        .zip(data.read_results)
+        .filter(|(_, dv)| dv.status != Some(StatusCode::BadAttributeIdInvalid))
+        .unzip();
+    data.attributes = attributes;
+    data.read_results = read_results;
+    let mut results = [data];
+    read_enum_values(session, &mut results).await?;
+    map_values(session, &mut results).await?;
+    Ok(detailed_rows(&results[0], false, false))
+}
+
+fn format_locale_list(texts: &[LocalizedText]) -> String {
+    texts
+        .iter()
+        .map(|text| {
+            let locale = text_locale(text).unwrap_or("(default)");
+            format!("{}: {}", locale.bright_white(), sanitize_text(localized_text(text)))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
-fn get_attribute_value(results: &[DataValue], index: usize) -> Option<String> {
-    results.get(index)
+fn get_attribute_value(data: &NodeData, attribute: AttributeId) -> Option<String> {
+    data.attribute(attribute)
         .and_then(|dv| dv.value.as_ref())
         .map(|v| match v {
-            Variant::String(s) => s.to_string(),
-            Variant::LocalizedText(lt) => lt.text.to_string(),
+            Variant::String(s) => sanitize_text(s.as_ref()).into_owned(),
+            Variant::LocalizedText(lt) => sanitize_text(localized_text(lt)).into_owned(),
             _ => format_variant(v),
         })
 }
 
-fn get_value_string(results: &[DataValue], has_value_attr: bool) -> String {
-    let value_index = if has_value_attr { 3 } else { return "—".dimmed().to_string() };
-    
-    results.get(value_index)
+/// The DisplayName, or the BrowseName for servers that leave the DisplayName empty
+fn node_name(data: &NodeData) -> String {
+    let value = |attribute| data.attribute(attribute).and_then(|dv| dv.value.as_ref());
+    let name = match (value(AttributeId::DisplayName), value(AttributeId::BrowseName)) {
+        (Some(Variant::LocalizedText(lt)), _) if !localized_text(lt).is_empty() => localized_text(lt),
+        (_, Some(Variant::QualifiedName(qn))) if !qn.name.is_empty() => qn.name.as_ref(),
+        _ => "Unknown",
+    };
+    sanitize_text(name).into_owned()
+}
+
+fn get_value_string(data: &NodeData, show_types: bool, full_values: bool) -> String {
+    data.attribute(AttributeId::Value)
         .and_then(|dv| dv.value.as_ref())
-        .map(format_variant)
+        .map(|variant| format_value(data, variant, show_types, full_values))
+        .map(|value| with_conversion(data, value))
         .unwrap_or_else(|| "—".dimmed().to_string())
 }
 
-fn get_status_string(results: &[DataValue]) -> String {
-    if results.iter().all(|dv| dv.status.as_ref().map_or(true, |s| s.is_good())) {
-        "✅ All Good".green().to_string()
+/// Format a value, laying out multi-dimensional arrays as rows
+fn format_value(data: &NodeData, variant: &Variant, show_types: bool, full_values: bool) -> String {
+    if let Some(mapped) = data.mapped_value {
+        return if show_types {
+            format!("{} {}", mapped, format!("({})", variant_type_name(variant)).dimmed())
+        } else {
+            mapped.to_string()
+        };
+    }
+    if let Some(enum_value) = &data.enum_value {
+        return if show_types {
+            format!("{} {}", enum_value.format(), format!("({})", variant_type_name(variant)).dimmed())
+        } else {
+            enum_value.format()
+        };
+    }
+    let Variant::Array(array) = variant else {
+        return if show_types { format_variant_typed(variant) } else { format_value_variant(variant) };
+    };
+    
+    let dimensions = array.dimensions.as_deref()
+        .filter(|dims| dims.len() > 1 && dimensions_match(dims, array.values.len()))
+        .or(data.array_dimensions.as_deref())
+        .or(array.dimensions.as_deref().filter(|dims| dims.len() > 1));
+    let formatted = match dimensions {
+        Some(dims) => format_array_grid(&array.values, dims, full_values),
+        None if full_values => format_array_full(&array.values),
+        None => format_value_variant(variant),
+    };
+    
+    if show_types {
+        format!("{} {}", formatted, format!("({})", variant_type_name(variant)).dimmed())
+    } else {
+        formatted
+    }
+}
+
+fn dimensions_match(dimensions: &[u32], len: usize) -> bool {
+    dimensions.iter().try_fold(1usize, |acc, dim| acc.checked_mul(*dim as usize)) == Some(len)
+}
+
+/// Fetch the ArrayDimensions of array values that arrive flat or with a bad shape, so matrices
+/// can still be shown as rows. Those of the node do not fit a part read with an index range, a
+/// part of several dimensions is shaped like the range instead.
+async fn read_array_dimensions(session: &ServiceSession, results: &mut [NodeData]) -> Result<()> {
+    for data in results.iter_mut().filter(|data| data.index_range.has_range()) {
+        let lengths = range_lengths(&data.index_range);
+        let len = match data.attribute(AttributeId::Value).and_then(|dv| dv.value.as_ref()) {
+            Some(Variant::Array(array)) => array.values.len(),
+            _ => continue,
+        };
+        if lengths.len() > 1 && dimensions_match(&lengths, len) {
+            data.array_dimensions = Some(lengths);
+        }
+    }
+    let flat: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, data)| data.index_range.is_none())
+        .filter(|(_, data)| matches!(
+            data.attribute(AttributeId::Value).and_then(|dv| dv.value.as_ref()),
+            Some(Variant::Array(array)) if !array.dimensions.as_deref()
+                .is_some_and(|dims| dims.len() > 1 && dimensions_match(dims, array.values.len()))
+        ))
+        .map(|(idx, _)| idx)
+        .collect();
+    if flat.is_empty() {
+        return Ok(());
+    }
+    
+    let read_requests: Vec<ReadValueId> = flat
+        .iter()
+        .flat_map(|idx| [AttributeId::ValueRank, AttributeId::ArrayDimensions].map(|attribute| ReadValueId {
+            node_id: results[*idx].node_id.clone(),
+            attribute_id: attribute as u32,
+            index_range: NumericRange::None,
+            data_encoding: QualifiedName::null(),
+        }))
+        .collect();
+    let values = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+    
+    for (idx, pair) in flat.iter().zip(values.chunks(2)) {
+        let value_rank = match pair.first().and_then(|dv| dv.value.as_ref()) {
+            Some(Variant::Int32(rank)) => *rank,
+            _ => continue,
+        };
+        let dimensions: Option<Vec<u32>> = match pair.get(1).and_then(|dv| dv.value.as_ref()) {
+            Some(Variant::Array(array)) => array.values
+                .iter()
+                .map(|dim| match dim {
+                    Variant::UInt32(dim) => Some(*dim),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        };
+        if let Some(dimensions) = dimensions.filter(|dims| dims.len() > 1 && value_rank != 1) {
+            debug!("Using ArrayDimensions {:?} of {}", dimensions, format_node_id(&results[*idx].node_id));
+            results[*idx].array_dimensions = Some(dimensions);
+        }
+    }
+    Ok(())
+}
+
+/// Look up the names of integer values whose DataType is an enumeration
+async fn read_enum_values(session: &ServiceSession, results: &mut [NodeData]) -> Result<()> {
+    let integers: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, data)| data.attribute(AttributeId::Value)
+            .and_then(|dv| dv.value.as_ref())
+            .is_some_and(|variant| integer_value(variant).is_some()))
+        .map(|(idx, _)| idx)
+        .collect();
+    if integers.is_empty() {
+        return Ok(());
+    }
+    
+    let unread: Vec<NodeId> = integers
+        .iter()
+        .filter(|idx| results[**idx].attribute(AttributeId::DataType).is_none())
+        .map(|idx| results[*idx].node_id.clone())
+        .collect();
+    let mut read_types = read_data_types(session, &unread).await?.into_iter();
+    
+    for idx in integers {
+        let data = &mut results[idx];
+        let data_type = match data.attribute(AttributeId::DataType) {
+            Some(dv) => match &dv.value {
+                Some(Variant::NodeId(data_type)) => Some((**data_type).clone()),
+                _ => None,
+            },
+            None => read_types.next().flatten(),
+        };
+        let (Some(data_type), Some(variant)) = (data_type, data.attribute(AttributeId::Value).and_then(|dv| dv.value.as_ref())) else {
+            continue;
+        };
+        data.enum_value = enum_value(session, &data_type, variant).await;
+    }
+    Ok(())
+}
+
+/// Look the values up in `--value-map`, reading the DataType of nodes that were read without it
+/// when rules apply by DataType
+async fn map_values(session: &ServiceSession, results: &mut [NodeData]) -> Result<()> {
+    let with_value: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, data)| data.attribute(AttributeId::Value).is_some_and(|dv| dv.value.is_some()))
+        .map(|(idx, _)| idx)
+        .collect();
+    let unread: Vec<NodeId> = if maps_types() {
+        with_value
+            .iter()
+            .filter(|idx| results[**idx].attribute(AttributeId::DataType).is_none())
+            .map(|idx| results[*idx].node_id.clone())
+            .collect()
     } else {
-        let bad_count = results.iter().filter(|dv| !dv.status.as_ref().map_or(true, |s| s.is_good())).count();
-        format!("⚠️  {} errors", bad_count).yellow().to_string()
+        Vec::new()
+    };
+    let mut read_types = read_data_types(session, &unread).await?.into_iter();
+
+    for idx in with_value {
+        let data = &mut results[idx];
+        let data_type = match data.attribute(AttributeId::DataType) {
+            Some(dv) => match &dv.value {
+                Some(Variant::NodeId(data_type)) => Some(format_data_type(data_type)),
+                _ => None,
+            },
+            None if maps_types() => read_types.next().flatten().map(|data_type| format_data_type(&data_type)),
+            None => None,
+        };
+        if let Some(variant) = data.attribute(AttributeId::Value).and_then(|dv| dv.value.as_ref()) {
+            data.mapped_value = mapped_value(&data.node_id, data_type.as_deref().unwrap_or_default(), variant);
+        }
+    }
+    Ok(())
+}
+
+/// Browse the HasProperty children of each node and read their values, one batched Browse and
+/// Read per `BATCH_SIZE` nodes
+async fn read_properties(session: &ServiceSession, results: &mut [NodeData]) -> Result<()> {
+    // Nodes the server does not know have no properties to browse
+    let known: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, data)| data.read_results.iter().any(|dv| dv.status.as_ref().is_none_or(|s| s.is_good())))
+        .map(|(idx, _)| idx)
+        .collect();
+    
+    let mut found: Vec<(usize, String, NodeId)> = Vec::new();
+    for chunk in known.chunks(BATCH_SIZE) {
+        let browse_requests: Vec<BrowseDescription> = chunk
+            .iter()
+            .map(|idx| BrowseRequest::new(&results[*idx].node_id, BrowseDirection::Forward, ReferenceTypeId::HasProperty)
+                .classes(NodeClassMask::VARIABLE)
+                .build())
+            .collect();
+        let browse_results = session.browse(&browse_requests, 0, None).await?;
+        
+        for (idx, result) in chunk.iter().zip(browse_results) {
+            if !result.status_code.is_good() {
+                debug!("Browsing properties of {} failed: {}", format_node_id(&results[*idx].node_id), result.status_code);
+                continue;
+            }
+            for reference in result.references.unwrap_or_default() {
+                let name = if localized_text(&reference.display_name).is_empty() {
+                    reference.browse_name.name.to_string()
+                } else {
+                    localized_text(&reference.display_name).to_string()
+                };
+                found.push((*idx, name, reference.node_id.node_id));
+            }
+        }
+    }
+    
+    for chunk in found.chunks(BATCH_SIZE) {
+        let read_requests: Vec<ReadValueId> = chunk.iter().map(|(_, _, node_id)| ReadValueId::from(node_id)).collect();
+        let values = session.read_values(&read_requests).await?;
+        for ((idx, name, node_id), value) in chunk.iter().zip(values) {
+            results[*idx].properties.push(PropertyValue {
+                name: name.clone(),
+                node_id: node_id.clone(),
+                value,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// A property value, with the structures properties commonly hold decoded
+fn format_property_value(value: &DataValue) -> String {
+    match &value.value {
+        Some(Variant::ExtensionObject(object)) => {
+            if let Some(range) = object.inner_as::<Range>() {
+                format!("{} … {}", range.low, range.high)
+            } else if let Some(unit) = object.inner_as::<EUInformation>() {
+                let symbol = sanitize_text(localized_text(&unit.display_name)).to_string();
+                match localized_text(&unit.description) {
+                    "" => symbol,
+                    description => format!("{} {}", symbol, format!("({})", sanitize_text(description)).dimmed()),
+                }
+            } else if let Some(definition) = object.inner_as::<EnumValueType>() {
+                format!("{} = {}", definition.value, sanitize_text(localized_text(&definition.display_name)))
+            } else if let Some(zone) = object.inner_as::<TimeZoneDataType>() {
+                let dst = if zone.daylight_saving_in_offset { " (DST)" } else { "" };
+                format!("UTC{:+} min{}", zone.offset, dst)
+            } else {
+                // Structures without a dedicated rendering show their type, the body is rarely readable
+                match object.type_name() {
+                    Some(name) => format!("{} {{…}}", name.rsplit("::").next().unwrap_or(name)),
+                    None => format_variant(&Variant::ExtensionObject(object.clone())),
+                }
+            }
+        }
+        Some(variant) => format_variant(variant),
+        None => "—".dimmed().to_string(),
+    }
+}
+
+fn property_status(value: &DataValue) -> String {
+    OperationResult::of_data_value(value).describe()
+}
+
+/// Replace a converted value, or note why the value is shown unconverted
+fn with_conversion(data: &NodeData, value: String) -> String {
+    if data.mapped_value.is_some() {
+        return value;
+    }
+    match &data.conversion {
+        Some(Conversion::Converted(conversion)) => conversion.bright_green().to_string(),
+        Some(Conversion::Unconverted(note)) => format!("{} {}", value, format!("({})", note).dimmed()),
+        None => value,
+    }
+}
+
+fn get_status_string(data: &NodeData) -> String {
+    let results: Vec<OperationResult> = (0..data.read_results.len()).map(|position| data.result(position)).collect();
+    let client_errors = results.iter().filter(|result| matches!(result, OperationResult::ClientError(_))).count();
+    if client_errors > 0 {
+        return format!("🔌 {} client error(s)", client_errors).magenta().to_string();
+    }
+    let failures = results.iter().filter(|result| result.is_failure()).count();
+    let uncertain = results.iter().filter(|result| result.severity() == Severity::Uncertain && !result.is_failure()).count();
+    
+    match (failures, uncertain) {
+        (0, 0) => "✅ All Good".green().to_string(),
+        (0, uncertain) => format!("⚠️  {} uncertain", uncertain).yellow().to_string(),
+        (failures, _) if is_strict() => format!("❌ {} errors", failures).red().to_string(),
+        (failures, 0) => format!("⚠️  {} errors", failures).yellow().to_string(),
+        (failures, uncertain) => format!("⚠️  {} errors, {} uncertain", failures, uncertain).yellow().to_string(),
     }
 }
 
-fn parse_node_id(node_str: &str) -> Result<NodeId> {
-    NodeId::from_str(node_str)
-        .map_err(|_| anyhow!("Invalid node ID format: {}", node_str))
-}
\ No newline at end of file
@@ -0,0 +1,246 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use tabled::{Table, Tabled};
+use tracing::debug;
+
+use crate::client::OpcUaClient;
+use crate::commands::export::attribute_request;
+use crate::session::ServiceSession;
+use crate::utils::browse_request::BrowseRequest;
+use crate::utils::formatter::{format_node_class, format_node_id, format_status_code, reference_name, sanitize_text};
+use crate::utils::labels::format_labelled_node_id;
+use crate::utils::namespace::{parse_node_spec, NodeSpec};
+use crate::utils::reference_types::reference_type_name;
+
+/// Bytes of a type dictionary requested per read
+const DICTIONARY_CHUNK: u32 = 16 * 1024;
+
+#[derive(Tabled)]
+struct ReferenceRow {
+    #[tabled(rename = "Dir")]
+    direction: &'static str,
+    #[tabled(rename = "Reference Type")]
+    reference_type: String,
+    #[tabled(rename = "Target")]
+    target: String,
+    #[tabled(rename = "Node ID")]
+    node_id: String,
+    #[tabled(rename = "Class")]
+    node_class: String,
+}
+
+pub struct RefsArgs {
+    pub node: NodeSpec,
+    pub encodings: bool,
+}
+
+pub fn validate(node: &str, encodings: bool) -> Result<RefsArgs> {
+    Ok(RefsArgs {
+        node: parse_node_spec(node)?,
+        encodings,
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &RefsArgs) -> Result<()> {
+    let session = client.session()?;
+    let node_id = client.resolve_node(&args.node).await?;
+
+    if args.encodings {
+        return display_encodings(session, &node_id).await;
+    }
+
+    println!("\n{}", "🔗 OPC-UA References".bright_cyan().bold());
+    println!("📍 Node: {}", format_labelled_node_id(&node_id).bright_white());
+    println!("{}", "─".repeat(60));
+
+    let references = browse_references(session, &node_id, BrowseDirection::Both, ReferenceTypeId::References).await?;
+    if references.is_empty() {
+        println!("⚠️  No references found");
+        return Ok(());
+    }
+
+    let mut rows = Vec::with_capacity(references.len());
+    for reference in &references {
+        rows.push(ReferenceRow {
+            direction: if reference.is_forward { "→" } else { "←" },
+            reference_type: reference_type_name(session, &reference.reference_type_id, reference.is_forward).await,
+            target: sanitize_text(&reference_name(reference).0).into_owned(),
+            node_id: format_node_id(&reference.node_id.node_id),
+            node_class: format_node_class(reference.node_class),
+        });
+    }
+
+    println!("{}", Table::new(&rows));
+    println!("\n✅ {} reference(s)", rows.len().to_string().bright_green());
+    Ok(())
+}
+
+/// List the encodings of a DataType and, for servers publishing type dictionaries (before
+/// OPC UA 1.04), the DataTypeDescription of each encoding with its dictionary fragment
+async fn display_encodings(session: &ServiceSession, data_type: &NodeId) -> Result<()> {
+    let read = session.read(&[attribute_request(data_type, AttributeId::NodeClass)], TimestampsToReturn::Neither, 0.0).await?;
+    match read.first().and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::Int32(class)) if *class == NodeClass::DataType as i32 => {}
+        Some(_) => return Err(anyhow!("{} is not a DataType node", format_node_id(data_type))),
+        None => return Err(anyhow!("Cannot read {}: {}", format_node_id(data_type),
+            read.first().and_then(|dv| dv.status).map(|s| s.to_string()).unwrap_or_default())),
+    }
+
+    println!("\n{}", "🧬 DataType Encodings".bright_cyan().bold());
+    println!("📍 DataType: {}", format_labelled_node_id(data_type).bright_white());
+    println!("{}", "─".repeat(60));
+
+    let encodings = browse_references(session, data_type, BrowseDirection::Forward, ReferenceTypeId::HasEncoding).await?;
+    if encodings.is_empty() {
+        println!("⚠️  The DataType has no encodings (abstract types and built-in types have none)");
+        return Ok(());
+    }
+
+    for encoding in &encodings {
+        let encoding_id = &encoding.node_id.node_id;
+        println!("\n📦 {} [{}]", reference_name(encoding).0.bright_white(), format_node_id(encoding_id).dimmed());
+
+        let descriptions = browse_references(session, encoding_id, BrowseDirection::Forward, ReferenceTypeId::HasDescription).await?;
+        if descriptions.is_empty() {
+            println!("   {}", "No DataTypeDescription (servers since OPC UA 1.04 describe types with DataTypeDefinition)".dimmed());
+            continue;
+        }
+
+        for description in &descriptions {
+            display_description(session, &description.node_id.node_id).await?;
+        }
+    }
+
+    println!("\n✅ {} encoding(s)", encodings.len().to_string().bright_green());
+    Ok(())
+}
+
+/// Show a DataTypeDescription, the dictionary containing it and the dictionary's entry for the type
+async fn display_description(session: &ServiceSession, description_id: &NodeId) -> Result<()> {
+    let values = session.read(&[ReadValueId::from(description_id)], TimestampsToReturn::Neither, 0.0).await?;
+    let type_name = match values.first().and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::String(name)) => name.to_string(),
+        _ => String::new(),
+    };
+    println!("   📝 Description {} [{}]", type_name.bright_white(), format_node_id(description_id).dimmed());
+
+    let dictionaries = browse_references(session, description_id, BrowseDirection::Inverse, ReferenceTypeId::HasComponent).await?;
+    let Some(dictionary) = dictionaries.first() else {
+        println!("      {}", "Dictionary not found".yellow());
+        return Ok(());
+    };
+    let dictionary_id = &dictionary.node_id.node_id;
+    println!("   📚 Dictionary {} [{}]", reference_name(dictionary).0.bright_white(), format_node_id(dictionary_id).dimmed());
+
+    let schema = match read_dictionary(session, dictionary_id).await? {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(status) => {
+            println!("      {} {}", "Dictionary value not readable:".yellow(), format_status_code(&status));
+            return Ok(());
+        }
+    };
+    debug!("Dictionary {} has {} bytes", format_node_id(dictionary_id), schema.len());
+
+    // XML dictionaries describe the entry as an XPath such as //xs:element[@name='BuildInfo']
+    let type_name = type_name
+        .split_once("@name='")
+        .and_then(|(_, rest)| rest.split_once('\''))
+        .map_or(type_name.as_str(), |(name, _)| name);
+
+    match dictionary_fragment(&schema, type_name) {
+        Some(fragment) => {
+            for line in fragment.lines() {
+                println!("      {}", sanitize_text(line).cyan());
+            }
+        }
+        None => println!("      {}", format!("No entry named '{}' in the dictionary", type_name).yellow()),
+    }
+    Ok(())
+}
+
+/// Read a dictionary in pieces, whole dictionaries easily exceed the message size limit
+async fn read_dictionary(session: &ServiceSession, dictionary_id: &NodeId) -> Result<std::result::Result<Vec<u8>, StatusCode>> {
+    let mut bytes = Vec::new();
+    loop {
+        let start = bytes.len() as u32;
+        let request = ReadValueId {
+            index_range: NumericRange::Range(start, start + DICTIONARY_CHUNK - 1),
+            ..ReadValueId::from(dictionary_id)
+        };
+        let data_value = session.read(&[request], TimestampsToReturn::Neither, 0.0).await?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+
+        match (data_value.status, data_value.value) {
+            // Reading past the end
+            (Some(status), _) if status == StatusCode::BadIndexRangeNoData && start > 0 => break,
+            (Some(status), _) if !status.is_good() => return Ok(Err(status)),
+            (_, Some(Variant::ByteString(chunk))) => {
+                let chunk = chunk.as_ref();
+                bytes.extend_from_slice(chunk);
+                if chunk.len() < DICTIONARY_CHUNK as usize {
+                    break;
+                }
+            }
+            _ => return Ok(Err(StatusCode::BadTypeMismatch)),
+        }
+    }
+    Ok(Ok(bytes))
+}
+
+/// The type declaration of `name` in a binary (`<opc:StructuredType Name="...">`) or XML
+/// (`<xs:complexType name="...">`) type dictionary, skipping fields that happen to share the name
+fn dictionary_fragment<'a>(schema: &'a str, name: &str) -> Option<&'a str> {
+    if name.is_empty() {
+        return None;
+    }
+
+    let attributes = [format!(" Name=\"{}\"", name), format!(" name=\"{}\"", name)];
+    let mut positions: Vec<usize> = attributes
+        .iter()
+        .flat_map(|attribute| schema.match_indices(attribute.as_str()).map(|(position, _)| position))
+        .collect();
+    positions.sort_unstable();
+
+    for position in positions {
+        let Some(start) = schema[..position].rfind('<') else {
+            continue;
+        };
+        let Some(tag_len) = schema[start + 1..].find(|c: char| c.is_whitespace() || c == '>' || c == '/') else {
+            continue;
+        };
+        let tag = &schema[start + 1..start + 1 + tag_len];
+        if !tag.ends_with("Type") {
+            continue;
+        }
+
+        let open_end = schema[start..].find('>')? + start;
+        if schema[..open_end].ends_with('/') {
+            return Some(&schema[start..=open_end]);
+        }
+        let close = format!("</{}>", tag);
+        let end = schema[open_end..].find(&close)? + open_end + close.len();
+        return Some(&schema[start..end]);
+    }
+    None
+}
+
+pub async fn browse_references(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    direction: BrowseDirection,
+    reference_type: ReferenceTypeId,
+) -> Result<Vec<ReferenceDescription>> {
+    let browse_request = BrowseRequest::new(node_id, direction, reference_type).build();
+
+    let result = session.browse(&[browse_request], 0, None).await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Empty browse response"))?;
+    if !result.status_code.is_good() {
+        return Err(anyhow!("Browsing {} failed: {}", format_node_id(node_id), result.status_code));
+    }
+    Ok(result.references.unwrap_or_default())
+}
@@ -0,0 +1,138 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::DateTime;
+use std::io::BufRead;
+use tabled::{Table, Tabled};
+
+use crate::utils::archive::{open_archive, ArchiveChange, ArchiveHeader, ArchivePublishing, ArchiveSummary, ArchiveTrailer, PublishingState};
+use crate::utils::change_stats::{print_summary, SummaryRow};
+use crate::utils::formatter::sanitize_text;
+
+#[derive(Tabled)]
+struct NodeRow {
+    #[tabled(rename = "#")]
+    index: usize,
+    #[tabled(rename = "Endpoint")]
+    endpoint: String,
+    #[tabled(rename = "Node ID")]
+    node_id: String,
+    #[tabled(rename = "Display Name")]
+    display_name: String,
+    #[tabled(rename = "Data Type")]
+    data_type: String,
+}
+
+pub struct ReplayArgs {
+    pub file: String,
+}
+
+/// Print an archive written by `monitor --archive` the way monitor printed the changes
+pub fn execute(args: &ReplayArgs) -> Result<()> {
+    let (header, reader) = open_archive(&args.file)?;
+
+    println!("\n{}", "⏪ Replaying Monitor Archive".bright_cyan().bold());
+    println!("📄 File: {} (part {})", args.file.bright_white(), header.sequence);
+    println!("🏷️  Written by: opcua-walker {}", header.tool_version);
+    println!("🕐 Started: {}", header.start_time.bright_white());
+    let endpoints: Vec<&str> = header.endpoints.iter().map(|endpoint| endpoint.url.as_str()).collect();
+    println!("🖥️  Endpoints: {}", endpoints.join(", ").bright_white());
+    let rows: Vec<NodeRow> = header.nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| NodeRow {
+            index,
+            endpoint: header.endpoints.get(node.endpoint)
+                .map(|endpoint| endpoint.alias.clone().unwrap_or_else(|| endpoint.url.clone()))
+                .unwrap_or_default(),
+            node_id: node.node_id.clone(),
+            display_name: sanitize_text(&node.display_name).into_owned(),
+            data_type: node.data_type.clone(),
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+    println!("{}", "─".repeat(60));
+
+    let mut changes: u64 = 0;
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // The header is line 1
+        let record: serde_json::Value = serde_json::from_str(&line)
+            .map_err(|e| anyhow!("{} line {}: {}", args.file, number + 2, e))?;
+        if record.get("summary").is_some() {
+            let trailer: ArchiveTrailer = serde_json::from_value(record)
+                .map_err(|e| anyhow!("{} line {}: {}", args.file, number + 2, e))?;
+            replay_summary(&header, &trailer.summary);
+            continue;
+        }
+        if record.get("publishing").is_some() {
+            let ArchivePublishing { publishing } = serde_json::from_value(record)
+                .map_err(|e| anyhow!("{} line {}: {}", args.file, number + 2, e))?;
+            let prefix = header.endpoints.get(publishing.endpoint)
+                .and_then(|endpoint| endpoint.alias.as_deref())
+                .map(|alias| format!("{} ", format!("[{}]", alias).bright_magenta()))
+                .unwrap_or_default();
+            let timestamp = clock_time(Some(&publishing.time));
+            match publishing.state {
+                PublishingState::Silent => println!("{} {}⏸️  {} for {:.1}s", timestamp.dimmed(), prefix,
+                    "No publish response".yellow(), publishing.silent_secs),
+                PublishingState::Recovered => println!("{} {}▶️  Publish responses resumed after {:.1}s", timestamp.dimmed(), prefix,
+                    publishing.silent_secs),
+            }
+            continue;
+        }
+        let change: ArchiveChange = serde_json::from_value(record)
+            .map_err(|e| anyhow!("{} line {}: {}", args.file, number + 2, e))?;
+        let node = header.nodes.get(change.node)
+            .ok_or_else(|| anyhow!("{} line {}: unknown node {}", args.file, number + 2, change.node))?;
+
+        let timestamp = clock_time(change.time.as_deref());
+        let prefix = header.endpoints.get(node.endpoint)
+            .and_then(|endpoint| endpoint.alias.as_deref())
+            .map(|alias| format!("{} ", format!("[{}]", alias).bright_magenta()))
+            .unwrap_or_default();
+        let value = sanitize_text(&change.value.to_string()).into_owned();
+        match &change.status {
+            None => println!("{} {}{} ({}) = {}", timestamp.dimmed(), prefix, node.display_name.bright_white(), node.node_id.dimmed(), value.bright_green()),
+            Some(status) => println!("{} {}{} ({}) = {} {}", timestamp.dimmed(), prefix, node.display_name.bright_white(), node.node_id.dimmed(), value, status.yellow()),
+        }
+        changes += 1;
+    }
+
+    println!("\n✅ {} {} change(s)", "Replayed".green(), changes);
+    Ok(())
+}
+
+/// The time of day of an RFC 3339 timestamp as the change lines show it
+fn clock_time(time: Option<&str>) -> String {
+    time.and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .map(|ts| ts.as_chrono().format("%H:%M:%S%.3f").to_string())
+        .unwrap_or_else(|| "--:--:--.---".to_string())
+}
+
+/// Print the summary the run ended with, only found in its last file
+fn replay_summary(header: &ArchiveHeader, summary: &ArchiveSummary) {
+    let names: Vec<String> = summary.nodes
+        .iter()
+        .map(|node| match header.nodes.get(node.node) {
+            Some(archived) => {
+                let prefix = header.endpoints.get(archived.endpoint)
+                    .and_then(|endpoint| endpoint.alias.as_deref())
+                    .map(|alias| format!("[{}] ", alias))
+                    .unwrap_or_default();
+                format!("{}{}", prefix, sanitize_text(&archived.display_name))
+            }
+            None => format!("#{}", node.node),
+        })
+        .collect();
+    let text = |value: &Option<serde_json::Value>| value.as_ref().map(|value| sanitize_text(&value.to_string()).into_owned());
+    let rows: Vec<SummaryRow> = summary.nodes
+        .iter()
+        .zip(&names)
+        .map(|(node, name)| SummaryRow::new(name.clone(), node, text(&node.first), text(&node.last)))
+        .collect();
+    print_summary(rows, &summary.nodes, &names);
+    println!("🕐 Stopped: {} after {:.0}s", summary.end_time.bright_white(), summary.duration_secs);
+}
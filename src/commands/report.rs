@@ -0,0 +1,364 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::crypto::SecurityPolicy;
+use opcua::types::*;
+use serde::Serialize;
+use std::fs;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::client::OpcUaClient;
+use crate::commands::refs::browse_references;
+use crate::session::{ServiceSession, ServiceStats};
+use crate::types::Cli;
+use crate::utils::formatter::{format_node_id, format_variant, reference_name, Severity};
+use crate::utils::limits::descends;
+use crate::utils::security::ConnectionInfo;
+use crate::utils::time::format_duration;
+
+/// Levels below the children of the Objects folder included in the report
+const BROWSE_DEPTH: u32 = 1;
+
+#[derive(Serialize)]
+struct Report {
+    tool: &'static str,
+    version: &'static str,
+    generated: String,
+    endpoint: String,
+    duration_ms: u64,
+    connection: Section<Connection>,
+    server: Section<ServerSummary>,
+    endpoints: Section<Vec<Endpoint>>,
+    namespaces: Section<Vec<String>>,
+    capabilities: Section<Vec<Capability>>,
+    objects: Section<Vec<BrowsedNode>>,
+    clock: Section<ClockCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<ConnectionInfo>,
+}
+
+/// Outcome of one part of the report, failed parts keep their error instead of data
+#[derive(Serialize)]
+struct Section<T> {
+    ok: bool,
+    duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl<T> Section<T> {
+    fn skipped(reason: &str) -> Self {
+        Self { ok: false, duration_ms: 0, data: None, error: Some(reason.to_string()) }
+    }
+}
+
+#[derive(Serialize)]
+struct Connection {
+    endpoint_url: Option<String>,
+    security_policy: Option<String>,
+    security_mode: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ServerSummary {
+    state: String,
+    start_time: String,
+    current_time: String,
+    product_name: String,
+    product_uri: String,
+    manufacturer: String,
+    software_version: String,
+    build_number: String,
+    build_date: String,
+    service_level: Option<u8>,
+}
+
+#[derive(Serialize)]
+struct Endpoint {
+    url: String,
+    security_policy: String,
+    security_mode: String,
+    security_level: u8,
+    user_tokens: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Capability {
+    name: String,
+    node_id: String,
+    value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    severity: Severity,
+}
+
+#[derive(Serialize)]
+struct BrowsedNode {
+    name: String,
+    node_id: String,
+    node_class: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<BrowsedNode>,
+}
+
+#[derive(Serialize)]
+struct ClockCheck {
+    server_time: String,
+    local_time: String,
+    /// Server clock minus local clock, measured at the middle of the round trip
+    skew_ms: i64,
+    round_trip_ms: i64,
+}
+
+pub struct ReportArgs {
+    pub output: String,
+    pub timeout: Duration,
+}
+
+pub fn validate(output: &str, timeout: Duration) -> Result<ReportArgs> {
+    if timeout.is_zero() {
+        return Err(anyhow!("--timeout must be greater than 0"));
+    }
+    Ok(ReportArgs {
+        output: output.to_string(),
+        timeout,
+    })
+}
+
+/// Collect every section into one JSON file. The report connects by itself so that a failed
+/// connection still ends up in the file.
+pub async fn execute(cli: &Cli, args: &ReportArgs, stats: &Arc<ServiceStats>) -> Result<()> {
+    println!("\n{}", "🩺 OPC-UA Health Report".bright_cyan().bold());
+    println!("{}", "─".repeat(60));
+    println!("🔌 Endpoint: {}", cli.endpoint.bright_white());
+    println!("⏱️  Timeout per section: {}", format_duration(args.timeout).bright_white());
+    println!();
+
+    let started = Instant::now();
+    let generated = DateTime::now().as_chrono().to_rfc3339();
+    let mut client = OpcUaClient::new(cli).await?.with_stats(stats.clone());
+
+    let connection = run_section("Connection", args.timeout, async {
+        client.connect().await?;
+        client.print_security_warning();
+        let endpoint = client.connected_endpoint();
+        Ok(Connection {
+            endpoint_url: endpoint.map(|e| e.endpoint_url.to_string()),
+            security_policy: endpoint.map(|e| SecurityPolicy::from_uri(e.security_policy_uri.as_ref()).to_string()),
+            security_mode: endpoint.map(|e| e.security_mode.to_string()),
+        })
+    }).await;
+
+    let report = match client.session() {
+        Ok(session) if connection.ok => Report {
+            server: run_section("Server status", args.timeout, server_summary(session)).await,
+            endpoints: run_section("Endpoints", args.timeout, endpoints(&client)).await,
+            namespaces: run_section("Namespaces", args.timeout, async { Ok(client.namespaces().await?.to_vec()) }).await,
+            capabilities: run_section("Capabilities", args.timeout, capabilities(session)).await,
+            objects: run_section("Objects", args.timeout, browse_tree(session, &ObjectId::ObjectsFolder.into(), 0)).await,
+            clock: run_section("Clock skew", args.timeout, clock_check(session)).await,
+            tool: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            generated,
+            endpoint: cli.endpoint.clone(),
+            duration_ms: 0,
+            connection,
+            security: client.connection_info().cloned(),
+        },
+        _ => Report {
+            server: Section::skipped("Not connected"),
+            endpoints: Section::skipped("Not connected"),
+            namespaces: Section::skipped("Not connected"),
+            capabilities: Section::skipped("Not connected"),
+            objects: Section::skipped("Not connected"),
+            clock: Section::skipped("Not connected"),
+            tool: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            generated,
+            endpoint: cli.endpoint.clone(),
+            duration_ms: 0,
+            connection,
+            security: None,
+        },
+    };
+    let report = Report { duration_ms: started.elapsed().as_millis() as u64, ..report };
+
+    let _ = client.disconnect().await;
+
+    fs::write(&args.output, serde_json::to_string_pretty(&report)?)
+        .map_err(|e| anyhow!("Failed to write {}: {}", args.output, e))?;
+
+    if !report.connection.ok {
+        return Err(anyhow!("Could not connect to {}, the failure was recorded in {}", cli.endpoint, args.output));
+    }
+
+    let failed = [
+        report.server.ok,
+        report.endpoints.ok,
+        report.namespaces.ok,
+        report.capabilities.ok,
+        report.objects.ok,
+        report.clock.ok,
+    ].iter().filter(|ok| !**ok).count();
+    if failed > 0 {
+        println!("\n⚠️  {} section(s) failed, see the errors in the report", failed.to_string().yellow());
+    }
+    println!("\n✅ {} report to {} in {:.1}s", "Wrote".green(), args.output.bright_white(),
+             report.duration_ms as f64 / 1000.0);
+    Ok(())
+}
+
+/// Run one section with its own timeout, printing a line with the outcome
+async fn run_section<T>(name: &str, timeout: Duration, section: impl Future<Output = Result<T>>) -> Section<T> {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(timeout, section).await {
+        Ok(result) => result,
+        Err(_) => Err(anyhow!("Timed out after {}", format_duration(timeout))),
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(data) => {
+            println!("  ✅ {} {}", name.bright_white(), format!("({} ms)", duration_ms).dimmed());
+            Section { ok: true, duration_ms, data: Some(data), error: None }
+        }
+        Err(e) => {
+            println!("  ❌ {}: {}", name.bright_white(), e.to_string().red());
+            Section { ok: false, duration_ms, data: None, error: Some(e.to_string()) }
+        }
+    }
+}
+
+async fn server_summary(session: &ServiceSession) -> Result<ServerSummary> {
+    let requests = [
+        ReadValueId::from(&NodeId::from(VariableId::Server_ServerStatus)),
+        ReadValueId::from(&NodeId::from(VariableId::Server_ServiceLevel)),
+    ];
+    let results = session.read(&requests, TimestampsToReturn::Neither, 0.0).await?;
+
+    let status = match results.first().and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::ExtensionObject(obj)) => obj.inner_as::<ServerStatusDataType>()
+            .ok_or_else(|| anyhow!("ServerStatus has an unexpected structure"))?,
+        _ => return Err(anyhow!("ServerStatus is not readable: {}",
+            results.first().and_then(|dv| dv.status).unwrap_or(StatusCode::BadNoData))),
+    };
+    let service_level = match results.get(1).and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::Byte(level)) => Some(*level),
+        _ => None,
+    };
+
+    let build_info = &status.build_info;
+    Ok(ServerSummary {
+        state: format!("{:?}", status.state),
+        start_time: status.start_time.as_chrono().to_rfc3339(),
+        current_time: status.current_time.as_chrono().to_rfc3339(),
+        product_name: build_info.product_name.to_string(),
+        product_uri: build_info.product_uri.to_string(),
+        manufacturer: build_info.manufacturer_name.to_string(),
+        software_version: build_info.software_version.to_string(),
+        build_number: build_info.build_number.to_string(),
+        build_date: build_info.build_date.as_chrono().to_rfc3339(),
+        service_level,
+    })
+}
+
+async fn endpoints(client: &OpcUaClient) -> Result<Vec<Endpoint>> {
+    Ok(client.server_endpoints().await?
+        .into_iter()
+        .map(|endpoint| Endpoint {
+            url: endpoint.endpoint_url.to_string(),
+            security_policy: SecurityPolicy::from_uri(endpoint.security_policy_uri.as_ref()).to_string(),
+            security_mode: endpoint.security_mode.to_string(),
+            security_level: endpoint.security_level,
+            user_tokens: endpoint.user_identity_tokens
+                .unwrap_or_default()
+                .iter()
+                .map(|token| format!("{:?}", token.token_type))
+                .collect(),
+        })
+        .collect())
+}
+
+/// The variables of ServerCapabilities and its OperationLimits object
+async fn capabilities(session: &ServiceSession) -> Result<Vec<Capability>> {
+    let mut variables = Vec::new();
+    for reference in browse_references(session, &ObjectId::Server_ServerCapabilities.into(),
+                                       BrowseDirection::Forward, ReferenceTypeId::HierarchicalReferences).await? {
+        let name = reference_name(&reference).0;
+        match reference.node_class {
+            NodeClass::Variable => variables.push((name, reference.node_id.node_id)),
+            NodeClass::Object if reference.browse_name.name.as_ref() == "OperationLimits" => {
+                let limits = browse_references(session, &reference.node_id.node_id,
+                                               BrowseDirection::Forward, ReferenceTypeId::HierarchicalReferences).await?;
+                variables.extend(limits
+                    .into_iter()
+                    .filter(|limit| limit.node_class == NodeClass::Variable)
+                    .map(|limit| (format!("{}/{}", name, reference_name(&limit).0), limit.node_id.node_id)));
+            }
+            _ => {}
+        }
+    }
+    debug!("Reading {} capability variable(s)", variables.len());
+    if variables.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let requests: Vec<ReadValueId> = variables.iter().map(|(_, node_id)| ReadValueId::from(node_id)).collect();
+    let results = session.read(&requests, TimestampsToReturn::Neither, 0.0).await?;
+
+    Ok(variables
+        .into_iter()
+        .zip(results)
+        .map(|((name, node_id), data_value)| Capability {
+            name,
+            node_id: format_node_id(&node_id),
+            value: data_value.value.as_ref().map(format_variant),
+            status: data_value.status.filter(|status| !status.is_good()).map(|status| status.to_string()),
+            severity: data_value.status.as_ref().map_or(Severity::Good, Severity::of),
+        })
+        .collect())
+}
+
+async fn browse_tree(session: &ServiceSession, node_id: &NodeId, depth: u32) -> Result<Vec<BrowsedNode>> {
+    let mut nodes = Vec::new();
+    for reference in browse_references(session, node_id, BrowseDirection::Forward, ReferenceTypeId::HierarchicalReferences).await? {
+        let child_id = reference.node_id.node_id.clone();
+        let children = if descends(depth, BROWSE_DEPTH) {
+            Box::pin(browse_tree(session, &child_id, depth + 1)).await?
+        } else {
+            Vec::new()
+        };
+        nodes.push(BrowsedNode {
+            name: reference_name(&reference).0,
+            node_id: format_node_id(&child_id),
+            node_class: format!("{:?}", reference.node_class),
+            children,
+        });
+    }
+    Ok(nodes)
+}
+
+async fn clock_check(session: &ServiceSession) -> Result<ClockCheck> {
+    let request = ReadValueId::from(&NodeId::from(VariableId::Server_ServerStatus_CurrentTime));
+    let before = DateTime::now().as_chrono();
+    let results = session.read(&[request], TimestampsToReturn::Neither, 0.0).await?;
+    let after = DateTime::now().as_chrono();
+
+    let server_time = match results.first().and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::DateTime(time)) => time.as_chrono(),
+        _ => return Err(anyhow!("CurrentTime is not readable")),
+    };
+    let round_trip = after - before;
+    let local_time = before + round_trip / 2;
+
+    Ok(ClockCheck {
+        server_time: server_time.to_rfc3339(),
+        local_time: local_time.to_rfc3339(),
+        skew_ms: (server_time - local_time).num_milliseconds(),
+        round_trip_ms: round_trip.num_milliseconds(),
+    })
+}
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use std::fs;
+use tabled::{Table, Tabled};
+
+use crate::client::OpcUaClient;
+use crate::commands::backup::{parse_value_type, Backup, BackupEntry, BackupText, BackupValue};
+use crate::commands::export::{attribute_request, BATCH_SIZE};
+use crate::session::ServiceSession;
+use crate::utils::formatter::{format_node_id, format_status_code, format_variant, parse_node_id_text, truncate_string};
+use crate::utils::namespace::{parse_node_id, NamespaceMap};
+
+#[derive(Tabled)]
+struct RestoreRow {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Node ID")]
+    node_id: String,
+    #[tabled(rename = "Value")]
+    value: String,
+    #[tabled(rename = "Resolved By")]
+    resolved_by: String,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+pub struct RestoreArgs {
+    pub file: String,
+    pub root: Option<NodeId>,
+    pub dry_run: bool,
+}
+
+/// Where the target node of a backup entry was found
+enum Resolution {
+    NodeId(NodeId),
+    BrowsePath(NodeId),
+    NotFound,
+}
+
+impl Resolution {
+    fn node_id(&self) -> Option<&NodeId> {
+        match self {
+            Resolution::NodeId(node_id) | Resolution::BrowsePath(node_id) => Some(node_id),
+            Resolution::NotFound => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Resolution::NodeId(_) => "node id".to_string(),
+            Resolution::BrowsePath(_) => "browse path".yellow().to_string(),
+            Resolution::NotFound => "—".dimmed().to_string(),
+        }
+    }
+}
+
+pub fn validate(file: &str, root: Option<&str>, dry_run: bool) -> Result<RestoreArgs> {
+    Ok(RestoreArgs {
+        file: file.to_string(),
+        root: root.map(parse_node_id).transpose()?,
+        dry_run,
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &RestoreArgs) -> Result<()> {
+    let content = fs::read_to_string(&args.file)
+        .map_err(|e| anyhow!("Failed to read backup {}: {}", args.file, e))?;
+    let backup: Backup = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("{} is not a valid backup file: {}", args.file, e))?;
+
+    let session = client.session()?;
+    let translator = NamespaceTranslator {
+        source: &backup.namespaces,
+        target: client.namespaces().await?,
+        ns_map: client.ns_map(),
+    };
+
+    let root = match &args.root {
+        Some(root) => root.clone(),
+        None => translator.node_id(&parse_node_id(&backup.root)?),
+    };
+
+    println!("\n{}", "♻️  Restoring Parameters".bright_cyan().bold());
+    println!("📄 Backup: {} ({} from {})", args.file.bright_white(), backup.created, backup.endpoint);
+    println!("📍 Subtree: {}", format_node_id(&root).bright_white());
+    println!("📊 Parameters: {}", backup.nodes.len().to_string().bright_white());
+    println!("{}", "─".repeat(60));
+
+    // Parse every value up front, a broken entry should not leave the device half restored
+    let values: Vec<Variant> = backup.nodes
+        .iter()
+        .map(|entry| entry_variant(entry).map(|value| translator.value(value)))
+        .collect::<Result<_>>()?;
+
+    let resolutions = resolve_entries(session, &backup.nodes, &root, &translator).await?;
+    let mut results: Vec<String> = resolutions
+        .iter()
+        .map(|resolution| match resolution {
+            Resolution::NotFound => "❌ Not found".red().to_string(),
+            _ if args.dry_run => "would write".dimmed().to_string(),
+            _ => String::new(),
+        })
+        .collect();
+
+    let writes: Vec<(usize, WriteValue)> = resolutions
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, resolution)| resolution.node_id().map(|node_id| (idx, node_id)))
+        .map(|(idx, node_id)| (idx, WriteValue {
+            node_id: node_id.clone(),
+            attribute_id: AttributeId::Value as u32,
+            index_range: NumericRange::None,
+            value: DataValue {
+                value: Some(values[idx].clone()),
+                ..Default::default()
+            },
+        }))
+        .collect();
+
+    if !args.dry_run {
+        for chunk in writes.chunks(BATCH_SIZE) {
+            let write_requests: Vec<WriteValue> = chunk.iter().map(|(_, write)| write.clone()).collect();
+            let statuses = session.write(&write_requests).await?;
+            for ((idx, _), status) in chunk.iter().zip(statuses) {
+                results[*idx] = format_status_code(&status);
+                if !status.is_good() {
+                    results[*idx] = format!("❌ {}", results[*idx]);
+                }
+            }
+        }
+    }
+
+    let rows: Vec<RestoreRow> = backup.nodes
+        .iter()
+        .zip(&resolutions)
+        .zip(values.iter().zip(results.iter()))
+        .map(|((entry, resolution), (value, result))| RestoreRow {
+            path: display_path(&entry.browse_path),
+            node_id: resolution.node_id().map(format_node_id).unwrap_or_else(|| entry.node_id.clone()),
+            value: truncate_string(&format_variant(value), 30),
+            resolved_by: resolution.describe(),
+            result: result.clone(),
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+
+    let missing = resolutions.iter().filter(|r| r.node_id().is_none()).count();
+    if args.dry_run {
+        println!("\n🔍 {} {} of {} parameter(s) would be written",
+                 "Dry run:".bright_white().bold(), writes.len(), backup.nodes.len());
+        if missing > 0 {
+            return Err(anyhow!("{} parameter(s) have no matching node on the target", missing));
+        }
+        return Ok(());
+    }
+
+    let failed = results.iter().filter(|r| r.starts_with('❌')).count();
+    if failed > 0 {
+        return Err(anyhow!("{} of {} parameter(s) could not be restored", failed, backup.nodes.len()));
+    }
+
+    println!("\n✅ {} {} parameter(s)", "Restored".green(), backup.nodes.len());
+    Ok(())
+}
+
+/// The value of a backup entry as a variant of its recorded type
+pub fn entry_variant(entry: &BackupEntry) -> Result<Variant> {
+    let value_type = parse_value_type(&entry.data_type)
+        .ok_or_else(|| anyhow!("{}: unsupported data type {}", entry.node_id, entry.data_type))?;
+    let parse = |text: &BackupText| text.to_variant(value_type).map_err(|e| anyhow!("{}: {}", entry.node_id, e));
+
+    Ok(match &entry.value {
+        BackupValue::Scalar(text) => parse(text)?,
+        BackupValue::Array(texts) => {
+            let values = texts.iter().map(parse).collect::<Result<Vec<_>>>()?;
+            let array = Array::new(value_type, values)
+                .map_err(|e| anyhow!("{}: invalid array value: {:?}", entry.node_id, e))?;
+            Variant::Array(Box::new(array))
+        }
+    })
+}
+
+/// Use the recorded node IDs where they exist on the target, otherwise follow the browse paths
+async fn resolve_entries(
+    session: &ServiceSession,
+    entries: &[BackupEntry],
+    root: &NodeId,
+    translator: &NamespaceTranslator<'_>,
+) -> Result<Vec<Resolution>> {
+    let candidates: Vec<Option<NodeId>> = entries
+        .iter()
+        .map(|entry| parse_node_id_text(&entry.node_id).map(|node_id| translator.node_id(&node_id)))
+        .collect();
+
+    let mut resolutions: Vec<Resolution> = candidates.iter().map(|_| Resolution::NotFound).collect();
+
+    let known: Vec<(usize, &NodeId)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, candidate)| candidate.as_ref().map(|node_id| (idx, node_id)))
+        .collect();
+    for chunk in known.chunks(BATCH_SIZE) {
+        let read_requests: Vec<ReadValueId> = chunk
+            .iter()
+            .map(|(_, node_id)| attribute_request(node_id, AttributeId::NodeClass))
+            .collect();
+        let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+
+        for ((idx, node_id), result) in chunk.iter().zip(results) {
+            if result.status.is_none_or(|s| s.is_good()) {
+                resolutions[*idx] = Resolution::NodeId((*node_id).clone());
+            }
+        }
+    }
+
+    let drifted: Vec<usize> = (0..entries.len())
+        .filter(|idx| matches!(resolutions[*idx], Resolution::NotFound) && !entries[*idx].browse_path.is_empty())
+        .collect();
+    let paths: Vec<&[String]> = drifted.iter().map(|idx| entries[*idx].browse_path.as_slice()).collect();
+    let targets = resolve_browse_paths(session, &paths, root, translator).await?;
+    for (idx, target) in drifted.into_iter().zip(targets) {
+        if let Some(target) = target {
+            resolutions[idx] = Resolution::BrowsePath(target);
+        }
+    }
+
+    Ok(resolutions)
+}
+
+/// Follow recorded browse paths from `root` with TranslateBrowsePathsToNodeIds, `None` where
+/// no node matches
+pub async fn resolve_browse_paths(
+    session: &ServiceSession,
+    paths: &[&[String]],
+    root: &NodeId,
+    translator: &NamespaceTranslator<'_>,
+) -> Result<Vec<Option<NodeId>>> {
+    let mut targets = Vec::with_capacity(paths.len());
+    for chunk in paths.chunks(BATCH_SIZE) {
+        let browse_paths: Vec<BrowsePath> = chunk
+            .iter()
+            .map(|path| BrowsePath {
+                starting_node: root.clone(),
+                relative_path: RelativePath {
+                    elements: Some(path.iter().map(|name| RelativePathElement {
+                        reference_type_id: ReferenceTypeId::HierarchicalReferences.into(),
+                        is_inverse: false,
+                        include_subtypes: true,
+                        target_name: translator.browse_name(name),
+                    }).collect()),
+                },
+            })
+            .collect();
+        let results = session.translate_browse_paths_to_node_ids(&browse_paths).await?;
+
+        targets.extend(results.into_iter().map(|result| {
+            let status = result.status_code;
+            result.targets
+                .unwrap_or_default()
+                .into_iter()
+                .next()
+                .filter(|_| status.is_good())
+                .map(|target| target.target_id.node_id)
+        }));
+    }
+    Ok(targets)
+}
+
+pub fn display_path(browse_path: &[String]) -> String {
+    browse_path
+        .iter()
+        .map(|name| name.split_once(':').map_or(name.as_str(), |(_, name)| name))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Moves namespace indices of the source server onto the target through their URIs
+pub struct NamespaceTranslator<'a> {
+    pub source: &'a [String],
+    pub target: &'a [String],
+    pub ns_map: &'a NamespaceMap,
+}
+
+impl NamespaceTranslator<'_> {
+    fn index(&self, index: u16) -> u16 {
+        self.source
+            .get(index as usize)
+            .map(|uri| self.ns_map.map_uri(uri))
+            .and_then(|uri| self.target.iter().position(|ns| ns == uri))
+            .map(|position| position as u16)
+            .unwrap_or_else(|| self.ns_map.map_index(index))
+    }
+
+    pub fn node_id(&self, node_id: &NodeId) -> NodeId {
+        let mut node_id = node_id.clone();
+        node_id.namespace = self.index(node_id.namespace);
+        node_id
+    }
+
+    /// NodeId and QualifiedName values refer to namespaces of the source server just like node IDs
+    pub fn value(&self, value: Variant) -> Variant {
+        match value {
+            Variant::NodeId(node_id) => Variant::NodeId(Box::new(self.node_id(&node_id))),
+            Variant::QualifiedName(name) => Variant::QualifiedName(Box::new(QualifiedName::new(self.index(name.namespace_index), name.name))),
+            Variant::Array(mut array) => {
+                array.values = array.values.into_iter().map(|value| self.value(value)).collect();
+                Variant::Array(array)
+            }
+            value => value,
+        }
+    }
+
+    fn browse_name(&self, name: &str) -> QualifiedName {
+        match name.split_once(':').and_then(|(index, name)| Some((index.parse::<u16>().ok()?, name))) {
+            Some((index, name)) => QualifiedName::new(self.index(index), name),
+            None => QualifiedName::from(name),
+        }
+    }
+}
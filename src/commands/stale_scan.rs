@@ -0,0 +1,327 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use serde::Serialize;
+use std::time::Duration;
+use tabled::{Table, Tabled};
+
+use crate::client::OpcUaClient;
+use crate::commands::export::{browse_instances, BATCH_SIZE};
+use crate::commands::lint::node_paths;
+use crate::session::ServiceSession;
+use crate::utils::enums::{enum_value, read_data_types};
+use crate::utils::formatter::{format_node_id, format_variant, truncate_string, Severity};
+use crate::utils::guard::{self, Operations};
+use crate::utils::namespace::parse_node_id;
+use crate::utils::nodeset::NodeSetNode;
+use crate::utils::security::ConnectionInfo;
+use crate::utils::sort::natural_cmp;
+use crate::utils::time::format_duration;
+use crate::utils::units::numeric_value;
+
+#[derive(Tabled)]
+struct StaleRow {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Node ID")]
+    node_id: String,
+    #[tabled(rename = "Value")]
+    value: String,
+    #[tabled(rename = "Source Timestamp")]
+    source_timestamp: String,
+}
+
+#[derive(Serialize)]
+struct StaleSignal {
+    node_id: String,
+    path: String,
+    value: String,
+    /// Name of the value when the DataType is an enumeration, `value` keeps the number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enum_name: Option<String>,
+    source_timestamp: Option<String>,
+}
+
+#[derive(Serialize)]
+struct UnreadableSignal {
+    node_id: String,
+    path: String,
+    status: String,
+    severity: Severity,
+}
+
+#[derive(Serialize)]
+struct StaleReport {
+    sampled: usize,
+    samples: u32,
+    window_seconds: u64,
+    stale: Vec<StaleSignal>,
+    unreadable: Vec<UnreadableSignal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<ConnectionInfo>,
+}
+
+pub struct StaleScanArgs {
+    pub start_node_id: NodeId,
+    pub max_depth: u32,
+    pub window: Duration,
+    pub samples: u32,
+    pub exclude: Vec<String>,
+    pub min_change: f64,
+    pub json: bool,
+}
+
+/// What the samples of one variable have shown so far
+enum Observation {
+    Unchanged(DataValue),
+    Changed,
+    Unreadable(StatusCode),
+}
+
+pub fn validate(
+    node: Option<&str>,
+    max_depth: u32,
+    window: Duration,
+    samples: u32,
+    exclude: &[String],
+    min_change: f64,
+    json: bool,
+) -> Result<StaleScanArgs> {
+    let start_node_id = if let Some(node_str) = node {
+        parse_node_id(node_str)?
+    } else {
+        ObjectId::ObjectsFolder.into()
+    };
+
+    if window.is_zero() {
+        return Err(anyhow!("--window must be greater than 0"));
+    }
+    if samples < 2 {
+        return Err(anyhow!("--samples must be at least 2 to detect a change"));
+    }
+    if !min_change.is_finite() || min_change < 0.0 {
+        return Err(anyhow!("--min-change must be a non-negative number"));
+    }
+
+    Ok(StaleScanArgs {
+        start_node_id,
+        max_depth,
+        window,
+        samples,
+        exclude: exclude.iter().map(|pattern| pattern.to_lowercase()).collect(),
+        min_change,
+        json,
+    })
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &StaleScanArgs) -> Result<()> {
+    let session = client.session()?;
+
+    if !args.json {
+        println!("\n{}", "🧊 Scanning for Stale Signals".bright_cyan().bold());
+        println!("📍 Starting node: {}", format_node_id(&args.start_node_id).bright_white());
+        println!("⏱️  Window: {}, {} samples", format_duration(args.window).bright_white(), args.samples.to_string().bright_white());
+        if !args.exclude.is_empty() {
+            println!("🚫 Excluding: {}", args.exclude.join(", ").bright_white());
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    let nodes = browse_instances(session, &args.start_node_id, args.max_depth).await?;
+    let paths = node_paths(&nodes, &args.start_node_id);
+
+    // Properties describe their parent and are static by design, only data variables are sampled
+    let mut variables: Vec<(NodeId, String)> = nodes
+        .iter()
+        .zip(paths)
+        .filter(|(node, _)| node.node_class == NodeClass::Variable && !is_property(node))
+        .map(|(node, path)| (node.node_id.clone(), path))
+        .filter(|(node_id, path)| !is_excluded(&args.exclude, node_id, path))
+        .collect();
+    variables.sort_by(|a, b| natural_cmp(&a.1, &b.1));
+
+    if !args.json {
+        println!("🔎 Sampling {} variable(s)...", variables.len().to_string().bright_white());
+    }
+
+    let node_ids: Vec<NodeId> = variables.iter().map(|(node_id, _)| node_id.clone()).collect();
+    guard::confirm(&[Operations::Reads(node_ids.len() * args.samples as usize)])?;
+    let observations = sample_values(session, &node_ids, args).await?;
+
+    let stale_ids: Vec<NodeId> = node_ids
+        .iter()
+        .zip(&observations)
+        .filter(|(_, observation)| matches!(observation, Observation::Unchanged(_)))
+        .map(|(node_id, _)| node_id.clone())
+        .collect();
+    let mut data_types = read_data_types(session, &stale_ids).await?.into_iter();
+
+    let mut stale = Vec::new();
+    let mut unreadable = Vec::new();
+    for ((node_id, path), observation) in variables.iter().zip(observations) {
+        match observation {
+            Observation::Unchanged(value) => {
+                let enum_name = match (data_types.next().flatten(), &value.value) {
+                    (Some(data_type), Some(variant)) => enum_value(session, &data_type, variant).await
+                        .and_then(|enum_value| enum_value.name),
+                    _ => None,
+                };
+                stale.push(StaleSignal {
+                    node_id: format_node_id(node_id),
+                    path: path.clone(),
+                    value: value.value.as_ref().map(format_variant).unwrap_or_default(),
+                    enum_name,
+                    source_timestamp: value.source_timestamp.map(|ts| ts.as_chrono().to_rfc3339()),
+                })
+            }
+            Observation::Unreadable(status) => unreadable.push(UnreadableSignal {
+                node_id: format_node_id(node_id),
+                path: path.clone(),
+                status: status.to_string(),
+                severity: Severity::of(&status),
+            }),
+            Observation::Changed => {}
+        }
+    }
+
+    let stale_count = stale.len();
+
+    if args.json {
+        let report = StaleReport {
+            sampled: variables.len(),
+            samples: args.samples,
+            window_seconds: args.window.as_secs(),
+            stale,
+            unreadable,
+            security: client.connection_info().cloned(),
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        display_report(&stale, &unreadable, variables.len());
+    }
+
+    if stale_count > 0 {
+        return Err(anyhow!("Found {} stale signal(s)", stale_count));
+    }
+
+    Ok(())
+}
+
+/// Read all values `samples` times, evenly spread over the window
+async fn sample_values(session: &ServiceSession, node_ids: &[NodeId], args: &StaleScanArgs) -> Result<Vec<Observation>> {
+    let interval = args.window / (args.samples - 1);
+    let mut observations: Vec<Option<Observation>> = node_ids.iter().map(|_| None).collect();
+
+    for sample in 0..args.samples {
+        if sample > 0 {
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = tokio::signal::ctrl_c() => return Err(anyhow!("Stale scan cancelled after {} sample(s)", sample)),
+            }
+        }
+
+        for (chunk_index, chunk) in node_ids.chunks(BATCH_SIZE).enumerate() {
+            let read_requests: Vec<ReadValueId> = chunk.iter().map(ReadValueId::from).collect();
+            let results = session.read(&read_requests, TimestampsToReturn::Source, 0.0).await?;
+
+            for (offset, result) in results.into_iter().enumerate() {
+                let observation = &mut observations[chunk_index * BATCH_SIZE + offset];
+                *observation = Some(match observation.take() {
+                    _ if result.status.is_some_and(|s| !s.is_good()) => {
+                        Observation::Unreadable(result.status.unwrap_or_default())
+                    }
+                    None => Observation::Unchanged(result),
+                    Some(Observation::Unchanged(first)) if has_changed(&first, &result, args.min_change) => {
+                        Observation::Changed
+                    }
+                    Some(observation) => observation,
+                });
+            }
+        }
+    }
+
+    Ok(observations.into_iter().map(|o| o.unwrap_or(Observation::Changed)).collect())
+}
+
+/// With a minimum change, numeric values are judged by how far they moved alone, since
+/// simulated or noisy sources refresh their timestamp on every update
+fn has_changed(first: &DataValue, sample: &DataValue, min_change: f64) -> bool {
+    let numeric = (
+        first.value.as_ref().and_then(numeric_value),
+        sample.value.as_ref().and_then(numeric_value),
+    );
+
+    match numeric {
+        (Some(a), Some(b)) if min_change > 0.0 => (a - b).abs() > min_change,
+        _ => first.value != sample.value || first.source_timestamp != sample.source_timestamp,
+    }
+}
+
+pub fn is_property(node: &NodeSetNode) -> bool {
+    node.references
+        .iter()
+        .filter(|r| !r.is_forward)
+        .all(|r| r.reference_type == ReferenceTypeId::HasProperty)
+}
+
+/// Case-insensitive match of the path or node ID against `*` wildcard patterns
+fn is_excluded(patterns: &[String], node_id: &NodeId, path: &str) -> bool {
+    let node_id = format_node_id(node_id).to_lowercase();
+    let path = path.to_lowercase();
+    patterns
+        .iter()
+        .any(|pattern| wildcard_match(pattern, &path) || wildcard_match(pattern, &node_id))
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all, the whole text must match
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn display_report(stale: &[StaleSignal], unreadable: &[UnreadableSignal], sampled: usize) {
+    println!("📊 Sampled {} variable(s)", sampled.to_string().bright_white());
+
+    if !unreadable.is_empty() {
+        println!("\n⚠️  {} variable(s) could not be read:", unreadable.len().to_string().yellow());
+        for signal in unreadable {
+            println!("  • {} ({}): {}", signal.path, signal.node_id.dimmed(), signal.status.red());
+        }
+    }
+
+    if stale.is_empty() {
+        println!("\n✅ {}", "Every sampled variable changed during the window".green());
+        return;
+    }
+
+    let rows: Vec<StaleRow> = stale
+        .iter()
+        .map(|signal| StaleRow {
+            path: signal.path.clone(),
+            node_id: signal.node_id.clone(),
+            value: match &signal.enum_name {
+                Some(name) => truncate_string(&format!("{} ({})", name, signal.value), 40),
+                None => truncate_string(&signal.value, 40),
+            },
+            source_timestamp: signal.source_timestamp.clone().unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+    println!("\n{} {}", "🧊 Stale signals:".bright_white().bold(), stale.len().to_string().yellow());
+    println!("{}", Table::new(rows));
+}
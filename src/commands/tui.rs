@@ -0,0 +1,386 @@
+use anyhow::Result;
+use base64::prelude::*;
+use chrono::{DateTime, Local};
+use colored::*;
+use opcua::types::*;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::client::OpcUaClient;
+use crate::commands::browse::{compact_node_class_code, format_compact_node_class};
+use crate::commands::read::{read_attribute_rows, DetailedNodeInfo};
+use crate::commands::refs::browse_references;
+use crate::session::ServiceSession;
+use crate::utils::formatter::{format_node_id, localized_text, reference_name, sanitize_text};
+use crate::utils::live_values::{LiveFeed, LiveValues};
+use crate::utils::namespace::parse_node_id;
+use crate::utils::terminal::{check_interactive, fit_width, spawn_key_reader, Key, RawTerminal};
+
+/// How often the screen is checked for a new size while no key arrives
+const RESIZE_POLL: Duration = Duration::from_millis(250);
+
+const HELP: &str = "↑↓ move  → expand  ← collapse  r read value  c copy ID  q quit";
+
+pub struct TuiArgs {
+    pub start_node_id: NodeId,
+    /// Refresh interval of the values on screen
+    pub interval: Duration,
+    /// How long changed values stay highlighted
+    pub highlight: Duration,
+}
+
+pub fn validate(node: Option<&str>, interval: Duration, highlight: Duration) -> Result<TuiArgs> {
+    // Fail before connecting when the view could not be shown anyway
+    check_interactive()?;
+    Ok(TuiArgs {
+        start_node_id: match node {
+            Some(node) => parse_node_id(node)?,
+            None => ObjectId::ObjectsFolder.into(),
+        },
+        interval,
+        highlight,
+    })
+}
+
+/// One visible line of the tree
+struct Row {
+    node_id: NodeId,
+    name: String,
+    node_class: NodeClass,
+    depth: usize,
+    expanded: bool,
+}
+
+/// Attributes shown for a node, with the Value once it was read
+struct Details {
+    rows: std::result::Result<Vec<DetailedNodeInfo>, String>,
+    with_value: bool,
+}
+
+struct Browser<'a> {
+    session: &'a ServiceSession,
+    rows: Vec<Row>,
+    selected: usize,
+    /// First tree row on screen
+    offset: usize,
+    /// Children browsed so far; nodes are only browsed once they are expanded
+    children: HashMap<NodeId, std::result::Result<Vec<ReferenceDescription>, String>>,
+    details: HashMap<NodeId, Details>,
+    /// Values of the Variables on screen, kept current by the live feed
+    values: LiveValues,
+    /// Whether the last frame showed highlighted values, which is redrawn once they expire
+    highlighted: bool,
+    message: String,
+    copied: Option<NodeId>,
+}
+
+/// Browse the address space interactively: children are browsed when a node is expanded and
+/// attributes read when it is selected, so no part of the server is crawled up front. Only the
+/// values of the Variables on screen are kept current.
+pub async fn execute(client: &mut OpcUaClient, args: &TuiArgs) -> Result<()> {
+    let session = client.session()?;
+    let (name, node_class) = read_name_and_class(session, &args.start_node_id).await;
+    let mut browser = Browser {
+        session,
+        rows: vec![Row { node_id: args.start_node_id.clone(), name, node_class, depth: 0, expanded: false }],
+        selected: 0,
+        offset: 0,
+        children: HashMap::new(),
+        details: HashMap::new(),
+        values: LiveValues::new(args.highlight),
+        highlighted: false,
+        message: format!("Connected to {}", client.endpoint()),
+        copied: None,
+    };
+
+    let terminal = RawTerminal::enter()?;
+    let mut keys = spawn_key_reader();
+    browser.expand(0).await;
+    browser.load_details(false).await;
+
+    let mut feed = LiveFeed::new(session, args.interval);
+    let mut size = terminal.size();
+    browser.draw(size)?;
+    feed.watch(&browser.visible_variables(size)).await;
+    loop {
+        tokio::select! {
+            key = keys.recv() => match key {
+                Some(key) if browser.handle(key).await => {}
+                _ => break,
+            },
+            values = feed.next() => {
+                let now = Local::now();
+                for (node_id, value) in values {
+                    browser.values.update(node_id, value, now);
+                }
+            }
+            _ = tokio::time::sleep(RESIZE_POLL) => {
+                if terminal.size() == size && !browser.highlighted {
+                    continue;
+                }
+            }
+        }
+        if let Some(reason) = feed.fallback.take() {
+            browser.message = format!("⚠️  Reading the values every {:?}, no subscription: {}", args.interval, reason);
+        }
+        size = terminal.size();
+        browser.draw(size)?;
+        let visible = browser.visible_variables(size);
+        browser.values.retain(&visible);
+        feed.watch(&visible).await;
+    }
+    drop(terminal);
+
+    if let Some(node_id) = &browser.copied {
+        println!("📋 {}", format_node_id(node_id));
+    }
+    Ok(())
+}
+
+async fn read_name_and_class(session: &ServiceSession, node_id: &NodeId) -> (String, NodeClass) {
+    let requests = [AttributeId::DisplayName, AttributeId::NodeClass].map(|attribute| ReadValueId {
+        node_id: node_id.clone(),
+        attribute_id: attribute as u32,
+        index_range: NumericRange::None,
+        data_encoding: QualifiedName::null(),
+    });
+    let results = session.read(&requests, TimestampsToReturn::Neither, 0.0).await.unwrap_or_default();
+    let name = match results.first().and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::LocalizedText(text)) if !localized_text(text).is_empty() => sanitize_text(localized_text(text)).into_owned(),
+        _ => format_node_id(node_id),
+    };
+    let node_class = match results.get(1).and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::Int32(class)) => NodeClass::try_from(*class).unwrap_or(NodeClass::Unspecified),
+        _ => NodeClass::Unspecified,
+    };
+    (name, node_class)
+}
+
+impl Browser<'_> {
+    /// Act on a key, false once the view should close
+    async fn handle(&mut self, key: Key) -> bool {
+        self.message.clear();
+        match key {
+            Key::Char('q') | Key::Escape | Key::Interrupt => return false,
+            Key::Up | Key::Char('k') => self.select(self.selected.saturating_sub(1)),
+            Key::Down | Key::Char('j') => self.select(self.selected + 1),
+            Key::PageUp => self.select(self.selected.saturating_sub(10)),
+            Key::PageDown => self.select(self.selected + 10),
+            Key::Home | Key::Char('g') => self.select(0),
+            Key::End | Key::Char('G') => self.select(usize::MAX),
+            Key::Right | Key::Enter | Key::Char('l') | Key::Char(' ') => {
+                if self.rows[self.selected].expanded {
+                    self.select(self.selected + 1);
+                } else {
+                    self.expand(self.selected).await;
+                }
+            }
+            Key::Left | Key::Char('h') => {
+                if self.rows[self.selected].expanded {
+                    self.collapse(self.selected);
+                } else if let Some(parent) = self.parent(self.selected) {
+                    self.select(parent);
+                }
+            }
+            Key::Char('r') => {
+                self.load_details(true).await;
+                return true;
+            }
+            Key::Char('c') | Key::Char('y') => self.copy(),
+            Key::Char(_) => {}
+        }
+        self.load_details(false).await;
+        true
+    }
+
+    fn select(&mut self, row: usize) {
+        self.selected = row.min(self.rows.len() - 1);
+    }
+
+    fn parent(&self, row: usize) -> Option<usize> {
+        let depth = self.rows[row].depth;
+        (0..row).rev().find(|idx| self.rows[*idx].depth < depth)
+    }
+
+    async fn expand(&mut self, row: usize) {
+        let node_id = self.rows[row].node_id.clone();
+        if !self.children.contains_key(&node_id) {
+            let children = browse_references(self.session, &node_id, BrowseDirection::Forward, ReferenceTypeId::HierarchicalReferences)
+                .await
+                .map_err(|e| format!("{:#}", e));
+            self.children.insert(node_id.clone(), children);
+        }
+        let children = match &self.children[&node_id] {
+            Ok(children) => children,
+            Err(e) => {
+                self.message = format!("⚠️  {}", e);
+                return;
+            }
+        };
+        if children.is_empty() {
+            self.message = "No children".to_string();
+            return;
+        }
+
+        let depth = self.rows[row].depth + 1;
+        let rows: Vec<Row> = children
+            .iter()
+            .map(|reference| Row {
+                node_id: reference.node_id.node_id.clone(),
+                name: reference_name(reference).0,
+                node_class: reference.node_class,
+                depth,
+                expanded: false,
+            })
+            .collect();
+        self.rows[row].expanded = true;
+        self.rows.splice(row + 1..row + 1, rows);
+    }
+
+    fn collapse(&mut self, row: usize) {
+        let depth = self.rows[row].depth;
+        let end = (row + 1..self.rows.len()).find(|idx| self.rows[*idx].depth <= depth).unwrap_or(self.rows.len());
+        self.rows.drain(row + 1..end);
+        self.rows[row].expanded = false;
+    }
+
+    /// Read the attributes of the selected node unless they are known, with `with_value` (again)
+    /// including its Value
+    async fn load_details(&mut self, with_value: bool) {
+        let node_id = self.rows[self.selected].node_id.clone();
+        if !with_value && self.details.contains_key(&node_id) {
+            return;
+        }
+        let rows = read_attribute_rows(self.session, &node_id, with_value).await.map_err(|e| format!("{:#}", e));
+        if with_value && rows.is_ok() {
+            self.message = format!("Read {}", format_node_id(&node_id));
+        }
+        self.details.insert(node_id, Details { rows, with_value });
+    }
+
+    /// Put the node ID on the clipboard of terminals that support OSC 52 and print it on exit
+    fn copy(&mut self) {
+        let node_id = self.rows[self.selected].node_id.clone();
+        let text = format_node_id(&node_id);
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "\x1b]52;c;{}\x07", BASE64_STANDARD.encode(&text));
+        self.message = format!("Copied {}, printed on exit", text);
+        self.copied = Some(node_id);
+    }
+
+    /// The Variables among the tree rows on screen
+    fn visible_variables(&self, (_, lines): (usize, usize)) -> Vec<NodeId> {
+        self.rows
+            .iter()
+            .skip(self.offset)
+            .take(body_lines(lines))
+            .filter(|row| row.node_class == NodeClass::Variable)
+            .map(|row| row.node_id.clone())
+            .collect()
+    }
+
+    fn draw(&mut self, (columns, lines): (usize, usize)) -> Result<()> {
+        let body = body_lines(lines);
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + body {
+            self.offset = self.selected + 1 - body;
+        }
+        let tree_width = (columns * 2 / 5).max(20).min(columns.saturating_sub(1));
+        let detail_width = columns.saturating_sub(tree_width + 1);
+        let now = Local::now();
+        let details = self.detail_lines(now);
+        self.highlighted = false;
+
+        let mut frame = String::new();
+        let selected = &self.rows[self.selected];
+        let title = format!(" OPC-UA Walker · {}", format_node_id(&selected.node_id));
+        let _ = write!(frame, "\x1b[1;1H{}", fit_width(&title, columns).bold().reversed());
+        for line in 0..body {
+            let tree = match self.rows.get(self.offset + line) {
+                Some(row) => {
+                    self.highlighted |= self.values.is_changed(&row.node_id, now);
+                    self.tree_line(row, self.offset + line == self.selected, tree_width, now)
+                }
+                None => fit_width("", tree_width),
+            };
+            let detail = details.get(line).map_or("", String::as_str);
+            let _ = write!(frame, "\x1b[{};1H{}{}{}", line + 2, tree, "│".dimmed(), fit_width(detail, detail_width));
+        }
+        let status = if self.message.is_empty() { HELP.dimmed().to_string() } else { self.message.clone() };
+        let _ = write!(frame, "\x1b[{};1H{}", lines, fit_width(&status, columns));
+
+        let mut stdout = io::stdout();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    fn tree_line(&self, row: &Row, selected: bool, width: usize, now: DateTime<Local>) -> String {
+        let changed = self.values.is_changed(&row.node_id, now);
+        let marker = match self.children.get(&row.node_id) {
+            _ if row.expanded => "▾",
+            _ if changed => "●",
+            Some(Ok(children)) if children.is_empty() => " ",
+            _ if matches!(row.node_class, NodeClass::Variable | NodeClass::Method) => " ",
+            _ => "▸",
+        };
+        let indent = "  ".repeat(row.depth);
+        if selected {
+            // Without colours the pointer alone marks the selection
+            let class = fit_width(compact_node_class_code(row.node_class), 4);
+            let value = self.values.get(&row.node_id).map(|live| format!(" = {}", live.text())).unwrap_or_default();
+            fit_width(&format!("›{}{} {} {}{}", indent, marker, class, row.name, value), width).reversed().to_string()
+        } else {
+            let class = fit_width(&format_compact_node_class(row.node_class), 4);
+            let value = match self.values.get(&row.node_id) {
+                Some(live) if changed => format!(" = {}", live.text()).yellow().bold().to_string(),
+                Some(live) => format!(" = {}", live.text()).dimmed().to_string(),
+                None => String::new(),
+            };
+            fit_width(&format!(" {}{} {} {}{}", indent, marker, class, row.name, value), width)
+        }
+    }
+
+    fn detail_lines(&self, now: DateTime<Local>) -> Vec<String> {
+        let row = &self.rows[self.selected];
+        let mut lines = vec![
+            format!(" {}", row.name.bold()),
+            format!(" {} {}", "NodeId".bright_white(), format_node_id(&row.node_id)),
+        ];
+        if let Some(live) = self.values.get(&row.node_id) {
+            let value = if self.values.is_changed(&row.node_id, now) { live.text().yellow().bold().to_string() } else { live.text() };
+            let changed = match live.last_change {
+                Some(time) => format!("changed {}", time.format("%H:%M:%S")),
+                None => "unchanged".to_string(),
+            };
+            lines.push(format!(" {} {}  {}", "Live".bright_white(), value, changed.dimmed()));
+        }
+        lines.push(String::new());
+        match self.details.get(&row.node_id) {
+            None => lines.push(" Reading…".dimmed().to_string()),
+            Some(Details { rows: Err(e), .. }) => lines.push(format!(" ⚠️  {}", e)),
+            Some(Details { rows: Ok(rows), with_value }) => {
+                let label_width = rows.iter().map(|row| row.attribute.chars().count()).max().unwrap_or(0);
+                for attribute in rows {
+                    let mut values = attribute.value.lines();
+                    let first = values.next().unwrap_or_default();
+                    lines.push(format!(" {:<width$}  {}  {}", attribute.attribute.bright_white(), first, attribute.status, width = label_width));
+                    lines.extend(values.map(|value| format!(" {:<width$}  {}", "", value, width = label_width)));
+                }
+                if !with_value && row.node_class == NodeClass::VariableType {
+                    lines.push(String::new());
+                    lines.push(" Press r to read the value".dimmed().to_string());
+                }
+            }
+        }
+        lines
+    }
+}
+
+/// Tree rows that fit between the title and the status line
+fn body_lines(lines: usize) -> usize {
+    lines.saturating_sub(2).max(1)
+}
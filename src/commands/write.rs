@@ -0,0 +1,583 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use std::fs;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::client::OpcUaClient;
+use crate::session::ServiceSession;
+use crate::types::WriteCommand;
+use crate::utils::bytestring::{max_byte_string_length, sha256_hex, write_byte_string};
+use crate::utils::formatter::{format_node_id, format_status_code, format_variant, parse_node_id_text, sanitize_text};
+use crate::utils::index_range::{parse_index_range, range_lengths, range_suffix};
+use crate::utils::outcome::OperationResult;
+use crate::utils::labels::resolve_label;
+use crate::utils::namespace::resolve_node_namespace;
+use crate::utils::time::{format_duration, parse_time};
+
+pub struct WriteArgs {
+    pub node_id: NodeId,
+    pub value: Option<String>,
+    pub heartbeat: Option<Duration>,
+    pub modulo: Option<u64>,
+    pub max_failures: u32,
+    pub status_every: u64,
+    /// SourceTimestamp sent along with the value, e.g. to back-fill a historian
+    pub source_timestamp: Option<DateTime>,
+    /// StatusCode sent along with the value
+    pub status: Option<StatusCode>,
+    /// File whose bytes are written as a ByteString
+    pub from_bytes: Option<String>,
+    /// Elements of the array to replace, `NumericRange::None` for the whole value
+    pub index_range: NumericRange,
+}
+
+pub fn validate(args: &WriteCommand) -> Result<WriteArgs> {
+    let node_id = parse_node_id_text(&args.node_id)
+        .or_else(|| resolve_label(&args.node_id))
+        .ok_or_else(|| anyhow!("Invalid node ID format: {}", args.node_id))?;
+
+    if args.heartbeat.is_some_and(|interval| interval.is_zero()) {
+        return Err(anyhow!("--heartbeat interval must be greater than 0"));
+    }
+
+    match (&args.value, args.heartbeat) {
+        (None, None) if args.from_bytes.is_none() => return Err(anyhow!("Provide a value to write, --from-bytes or --heartbeat")),
+        (Some(_), Some(_)) => return Err(anyhow!("--heartbeat computes the written values itself; omit the value")),
+        _ => {}
+    }
+
+    if args.modulo == Some(0) {
+        return Err(anyhow!("--modulo must be greater than zero"));
+    }
+
+    let source_timestamp = args.source_timestamp
+        .as_deref()
+        .map(|timestamp| parse_time(timestamp).map(DateTime::from).map_err(|e| anyhow!("--source-timestamp: {}", e)))
+        .transpose()?;
+    let status = args.status.as_deref().map(parse_status_code).transpose()?;
+    let index_range = args.index_range.as_deref().map(parse_index_range).transpose()?.unwrap_or(NumericRange::None);
+
+    Ok(WriteArgs {
+        node_id,
+        value: args.value.clone(),
+        heartbeat: args.heartbeat,
+        modulo: args.modulo,
+        max_failures: args.max_failures,
+        status_every: args.status_every.max(1),
+        source_timestamp,
+        status,
+        from_bytes: args.from_bytes.clone(),
+        index_range,
+    })
+}
+
+/// Accept a status code by name, e.g. `UncertainLastUsableValue`, or as a number like `0x408F0000`
+fn parse_status_code(input: &str) -> Result<StatusCode> {
+    let input = input.trim();
+    let number = match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => input.parse::<u32>().ok(),
+    };
+    number
+        .map(StatusCode::from)
+        .or_else(|| SubStatusCode::from_str(input).ok().map(|code| StatusCode::from(code as u32)))
+        .ok_or_else(|| anyhow!("Unknown status code '{}', use a name like Good or UncertainLastUsableValue or a number", input))
+}
+
+pub async fn execute(client: &mut OpcUaClient, args: &WriteArgs) -> Result<()> {
+    let session = client.session()?;
+
+    println!("\n{}", "✏️  OPC-UA Write".bright_cyan().bold());
+    println!("{}", "─".repeat(40));
+
+    // Without the namespace array there are just no other namespaces to suggest
+    let namespaces = client.namespaces().await.unwrap_or_default();
+    let node_id = resolve_node_namespace(session, &args.node_id, namespaces, client.auto_namespace()).await?;
+    // The current value of a file-sized ByteString may be too large to read in one piece
+    if let Some(path) = &args.from_bytes {
+        return upload_bytes(session, &node_id, path).await;
+    }
+    let (value_type, current) = read_write_target(session, &node_id, &args.index_range).await?;
+
+    println!("📍 Node: {}", format!("{}{}", format_node_id(&node_id), range_suffix(&args.index_range)).bright_white());
+    println!("🏷️  Type: {}", value_type.to_string().bright_white());
+    if let Some(current) = &current {
+        println!("📖 Current value: {}", describe_value(current));
+    }
+
+    match (&args.value, args.heartbeat) {
+        (Some(input), _) => write_once(session, &node_id, value_type, input, args).await,
+        (None, Some(interval)) => run_heartbeat(session, &node_id, value_type, current, interval, args).await,
+        (None, None) => unreachable!("validate requires a value or --heartbeat"),
+    }
+}
+
+/// Write the bytes of a file into a ByteString variable, in slices when they exceed what one
+/// write can carry
+async fn upload_bytes(session: &ServiceSession, node_id: &NodeId, path: &str) -> Result<()> {
+    let data = fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+    let max_length = max_byte_string_length(session).await;
+
+    println!("📍 Node: {}", format_node_id(node_id).bright_white());
+    println!("📄 File: {}", path.bright_white());
+    println!("   Size: {} bytes", data.len().to_string().bright_white());
+    println!("   SHA-256: {}", sha256_hex(&data));
+    if let Some(max) = max_length {
+        println!("📦 Server MaxByteStringLength: {} bytes", max.to_string().bright_white());
+    }
+
+    let writes = write_byte_string(session, node_id, &data, max_length).await?;
+    println!("   Status: {} in {} write(s)", OperationResult::Good.describe(), writes);
+
+    println!("\n✅ {}", "Upload completed successfully".green());
+    Ok(())
+}
+
+/// Determine the scalar type to write from the DataType attribute, falling back to
+/// the type of the current value for non built-in (e.g. enumeration) data types. The current
+/// value is read with the index range that is going to be written.
+async fn read_write_target(session: &ServiceSession, node_id: &NodeId, index_range: &NumericRange) -> Result<(VariantScalarTypeId, Option<Variant>)> {
+    let read_requests = [AttributeId::DataType, AttributeId::Value].map(|attr| ReadValueId {
+        node_id: node_id.clone(),
+        attribute_id: attr as u32,
+        index_range: if attr == AttributeId::Value { index_range.clone() } else { NumericRange::None },
+        data_encoding: QualifiedName::null(),
+    });
+
+    let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+
+    let data_type = results.first().ok_or_else(|| anyhow!("No DataType returned for node"))?;
+    if let Some(status) = data_type.status.filter(|s| !s.is_good()) {
+        return Err(anyhow!("Cannot read node {}: {}", format_node_id(node_id), status));
+    }
+
+    let current = results.get(1).and_then(|dv| dv.value.clone());
+
+    let value_type = match &data_type.value {
+        Some(Variant::NodeId(data_type_id)) => VariantScalarTypeId::try_from(data_type_id.as_ref()).ok(),
+        _ => None,
+    }
+    .or_else(|| current.as_ref().and_then(|v| v.scalar_type_id()))
+    .ok_or_else(|| anyhow!("Cannot determine the value type of {}", format_node_id(node_id)))?;
+
+    Ok((value_type, current))
+}
+
+async fn write_once(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    value_type: VariantScalarTypeId,
+    input: &str,
+    args: &WriteArgs,
+) -> Result<()> {
+    let value = if args.index_range.has_range() {
+        parse_range_value(input, value_type, &args.index_range)?
+    } else {
+        parse_value(input, value_type)?
+    };
+    println!("✏️  Writing: {}", describe_value(&value).bright_white());
+    if let Some(status) = &args.status {
+        println!("   With status: {}", format_status_code(status));
+    }
+    if let Some(timestamp) = &args.source_timestamp {
+        println!("   With source timestamp: {}", timestamp.as_chrono().to_rfc3339().bright_white());
+    }
+
+    let data_value = DataValue {
+        value: Some(value),
+        status: args.status,
+        source_timestamp: args.source_timestamp,
+        ..Default::default()
+    };
+    let result = write_data_value(session, node_id, data_value, &args.index_range).await;
+    println!("   Status: {}", result.describe());
+
+    if !result.is_good() {
+        if let OperationResult::OperationBad(status) = result
+            && let Some(refused) = refused_part(status, args)
+        {
+            println!("   💡 The server refused {}", refused);
+        }
+        return Err(anyhow::Error::new(result).context("Write failed"));
+    }
+
+    println!("\n✅ {}", "Write completed successfully".green());
+    Ok(())
+}
+
+/// A value as `format_variant` shows it, LocalizedText with its locale as that is written too
+fn describe_value(value: &Variant) -> String {
+    match value {
+        Variant::LocalizedText(text) if !text.locale.is_empty() => {
+            format!("{} ({})", format_variant(value), sanitize_text(text.locale.as_ref()))
+        }
+        value => format_variant(value),
+    }
+}
+
+/// Which part of the written DataValue a failed write status points at, when it names one
+fn refused_part(status: StatusCode, args: &WriteArgs) -> Option<&'static str> {
+    match status.sub_code() {
+        SubStatusCode::BadWriteNotSupported => match (args.status.is_some(), args.source_timestamp.is_some()) {
+            (true, true) => Some("the status code or the source timestamp, it does not accept them with the value"),
+            (true, false) => Some("the status code, it only accepts writing the value"),
+            (false, true) => Some("the source timestamp, it only accepts writing the value"),
+            (false, false) => None,
+        },
+        SubStatusCode::BadTypeMismatch | SubStatusCode::BadOutOfRange => Some("the value"),
+        _ => None,
+    }
+}
+
+async fn write_value(session: &ServiceSession, node_id: &NodeId, value: Variant) -> OperationResult {
+    let data_value = DataValue {
+        value: Some(value),
+        ..Default::default()
+    };
+    write_data_value(session, node_id, data_value, &NumericRange::None).await
+}
+
+/// Write the Value attribute, or the elements of it `index_range` selects; a server that
+/// answers without a result counts as BadNoData
+async fn write_data_value(session: &ServiceSession, node_id: &NodeId, value: DataValue, index_range: &NumericRange) -> OperationResult {
+    let write_request = WriteValue {
+        node_id: node_id.clone(),
+        attribute_id: AttributeId::Value as u32,
+        index_range: index_range.clone(),
+        value,
+    };
+
+    match session.write(&[write_request]).await {
+        Ok(results) => OperationResult::of_status(results.first().copied().unwrap_or(StatusCode::BadNoData)),
+        Err(status) => OperationResult::of_service_error(status),
+    }
+}
+
+/// How the heartbeat derives the next value from the previous one
+#[derive(Clone, Copy)]
+enum HeartbeatMode {
+    Toggle,
+    Increment,
+    Timestamp,
+}
+
+impl HeartbeatMode {
+    fn for_type(value_type: VariantScalarTypeId) -> Result<Self> {
+        match value_type {
+            VariantScalarTypeId::Boolean => Ok(Self::Toggle),
+            VariantScalarTypeId::SByte
+            | VariantScalarTypeId::Byte
+            | VariantScalarTypeId::Int16
+            | VariantScalarTypeId::UInt16
+            | VariantScalarTypeId::Int32
+            | VariantScalarTypeId::UInt32
+            | VariantScalarTypeId::Int64
+            | VariantScalarTypeId::UInt64 => Ok(Self::Increment),
+            VariantScalarTypeId::DateTime => Ok(Self::Timestamp),
+            other => Err(anyhow!("--heartbeat supports Boolean, integer and DateTime nodes, not {}", other)),
+        }
+    }
+
+    fn describe(&self, modulo: Option<u64>) -> String {
+        match (self, modulo) {
+            (Self::Toggle, _) => "toggle".to_string(),
+            (Self::Increment, Some(modulo)) => format!("increment modulo {}", modulo),
+            (Self::Increment, None) => "increment".to_string(),
+            (Self::Timestamp, _) => "current time".to_string(),
+        }
+    }
+
+    fn next_value(&self, previous: Option<&Variant>, value_type: VariantScalarTypeId, modulo: Option<u64>) -> Variant {
+        match self {
+            Self::Toggle => Variant::Boolean(!matches!(previous, Some(Variant::Boolean(true)))),
+            Self::Increment => increment(previous, value_type, modulo),
+            Self::Timestamp => Variant::from(DateTime::now()),
+        }
+    }
+}
+
+async fn run_heartbeat(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    value_type: VariantScalarTypeId,
+    current: Option<Variant>,
+    interval: Duration,
+    args: &WriteArgs,
+) -> Result<()> {
+    let mode = HeartbeatMode::for_type(value_type)?;
+
+    println!("💓 Heartbeat: {} every {} (Ctrl+C to stop)",
+             mode.describe(args.modulo).bright_white(), format_duration(interval));
+    println!();
+
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let mut last = current;
+    let mut writes: u64 = 0;
+    let mut failures: u64 = 0;
+    let mut consecutive_failures: u32 = 0;
+
+    // Listen once, a Ctrl+C pressed while a write is in flight still stops the next tick
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = &mut ctrl_c => break,
+        }
+
+        let value = mode.next_value(last.as_ref(), value_type, args.modulo);
+
+        let result = write_value(session, node_id, value.clone()).await;
+        let error = (!result.is_good()).then_some(result);
+
+        match error {
+            None => {
+                writes += 1;
+                consecutive_failures = 0;
+                last = Some(value);
+
+                if writes.is_multiple_of(args.status_every) {
+                    println!("💓 {} writes, last value {}, {} failure(s)",
+                             writes.to_string().bright_green(),
+                             last.as_ref().map(format_variant).unwrap_or_default(),
+                             failures);
+                }
+            }
+            Some(error) => {
+                failures += 1;
+                consecutive_failures += 1;
+                println!("❌ Heartbeat write failed ({} in a row): {}", consecutive_failures, error);
+
+                if consecutive_failures > args.max_failures {
+                    return Err(anyhow!("Heartbeat stopped after {} consecutive failed writes", consecutive_failures));
+                }
+            }
+        }
+    }
+
+    println!("\n🛑 Heartbeat stopped after {} writes ({} failure(s))", writes, failures);
+    Ok(())
+}
+
+/// Add one to an integer value, wrapping at `modulo` or at the type's range
+fn increment(previous: Option<&Variant>, value_type: VariantScalarTypeId, modulo: Option<u64>) -> Variant {
+    let current: i128 = match previous {
+        Some(Variant::SByte(v)) => *v as i128,
+        Some(Variant::Byte(v)) => *v as i128,
+        Some(Variant::Int16(v)) => *v as i128,
+        Some(Variant::UInt16(v)) => *v as i128,
+        Some(Variant::Int32(v)) => *v as i128,
+        Some(Variant::UInt32(v)) => *v as i128,
+        Some(Variant::Int64(v)) => *v as i128,
+        Some(Variant::UInt64(v)) => *v as i128,
+        _ => -1,
+    };
+
+    let mut next = current + 1;
+    if let Some(modulo) = modulo {
+        next = next.rem_euclid(modulo as i128);
+    }
+
+    integer_variant(next, value_type)
+        .or_else(|| integer_variant(0, value_type))
+        .unwrap_or(Variant::Empty)
+}
+
+fn integer_variant(value: i128, value_type: VariantScalarTypeId) -> Option<Variant> {
+    match value_type {
+        VariantScalarTypeId::SByte => i8::try_from(value).ok().map(Variant::SByte),
+        VariantScalarTypeId::Byte => u8::try_from(value).ok().map(Variant::Byte),
+        VariantScalarTypeId::Int16 => i16::try_from(value).ok().map(Variant::Int16),
+        VariantScalarTypeId::UInt16 => u16::try_from(value).ok().map(Variant::UInt16),
+        VariantScalarTypeId::Int32 => i32::try_from(value).ok().map(Variant::Int32),
+        VariantScalarTypeId::UInt32 => u32::try_from(value).ok().map(Variant::UInt32),
+        VariantScalarTypeId::Int64 => i64::try_from(value).ok().map(Variant::Int64),
+        VariantScalarTypeId::UInt64 => u64::try_from(value).ok().map(Variant::UInt64),
+        _ => None,
+    }
+}
+
+/// Prefixes naming the type of a written value, for types whose text form is ambiguous
+const TYPE_PREFIXES: [(&str, VariantScalarTypeId); 3] = [
+    ("lt:", VariantScalarTypeId::LocalizedText),
+    ("qn:", VariantScalarTypeId::QualifiedName),
+    ("nodeid:", VariantScalarTypeId::NodeId),
+];
+
+/// Convert the comma separated elements written to an index range into an array shaped like the
+/// range, which must select exactly as many elements
+fn parse_range_value(input: &str, value_type: VariantScalarTypeId, index_range: &NumericRange) -> Result<Variant> {
+    let lengths = range_lengths(index_range);
+    let expected: usize = lengths.iter().map(|len| *len as usize).product();
+    let elements = input
+        .split(',')
+        .map(|element| parse_value(element, value_type))
+        .collect::<Result<Vec<_>>>()?;
+    if elements.len() != expected {
+        return Err(anyhow!(
+            "Index range {} selects {} element(s), but {} value(s) were given; separate them with commas",
+            index_range, expected, elements.len()
+        ));
+    }
+    let array = if lengths.len() > 1 {
+        Array::new_multi(value_type, elements, lengths)
+    } else {
+        Array::new(value_type, elements)
+    };
+    Ok(Variant::from(array.map_err(|e| anyhow!("Cannot build the {} array to write: {}", value_type, e))?))
+}
+
+/// Convert a command line value into a variant of the node's type.
+///
+/// LocalizedText, QualifiedName and NodeId values may carry a prefix: `lt:en|Pump station 3`,
+/// `qn:2:MotorSpeed`, `nodeid:ns=2;s=Target`. Without one the node's type decides, a plain
+/// text for a LocalizedText node has no locale and one for a QualifiedName node namespace 0
+/// unless it starts with `<index>:`. Strings are always taken literally.
+pub fn parse_value(input: &str, value_type: VariantScalarTypeId) -> Result<Variant> {
+    let invalid = || anyhow!("'{}' is not a valid {} value", input, value_type);
+    let mut trimmed = input.trim();
+
+    if value_type != VariantScalarTypeId::String
+        && let Some((prefix, prefixed_type)) = TYPE_PREFIXES.iter().find(|(prefix, _)| trimmed.starts_with(prefix))
+    {
+        if *prefixed_type != value_type {
+            return Err(anyhow!("'{}' is a {} value, but the node holds {} values", input, prefixed_type, value_type));
+        }
+        trimmed = &trimmed[prefix.len()..];
+    }
+
+    Ok(match value_type {
+        VariantScalarTypeId::Boolean => match trimmed.to_lowercase().as_str() {
+            "true" | "1" | "on" => Variant::Boolean(true),
+            "false" | "0" | "off" => Variant::Boolean(false),
+            _ => return Err(invalid()),
+        },
+        VariantScalarTypeId::SByte => Variant::SByte(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::Byte => Variant::Byte(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::Int16 => Variant::Int16(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::UInt16 => Variant::UInt16(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::Int32 => Variant::Int32(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::UInt32 => Variant::UInt32(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::Int64 => Variant::Int64(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::UInt64 => Variant::UInt64(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::Float => Variant::Float(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::Double => Variant::Double(trimmed.parse().map_err(|_| invalid())?),
+        VariantScalarTypeId::String => Variant::String(UAString::from(input)),
+        VariantScalarTypeId::DateTime => Variant::from(DateTime::from(parse_time(trimmed).map_err(|e| anyhow!("{}", e))?)),
+        VariantScalarTypeId::LocalizedText => {
+            // Only the prefixed form has a locale, a plain text may contain `|` itself
+            let (locale, text) = match input.trim_start().strip_prefix("lt:") {
+                Some(rest) => rest.split_once('|').ok_or_else(|| {
+                    anyhow!("'{}' has no '|' between locale and text, expected e.g. lt:en|Pump station 3", input)
+                })?,
+                None => ("", input),
+            };
+            Variant::LocalizedText(Box::new(LocalizedText::new(locale.trim(), text)))
+        }
+        VariantScalarTypeId::QualifiedName => {
+            let name = match trimmed.split_once(':').and_then(|(index, name)| Some((index.parse::<u16>().ok()?, name))) {
+                Some((index, name)) => QualifiedName::new(index, name),
+                None => QualifiedName::new(0, trimmed),
+            };
+            if name.name.is_empty() {
+                return Err(anyhow!("'{}' has no name, expected e.g. qn:2:MotorSpeed", input));
+            }
+            Variant::QualifiedName(Box::new(name))
+        }
+        VariantScalarTypeId::NodeId => {
+            let node_id = parse_node_id_text(trimmed)
+                .or_else(|| resolve_label(trimmed))
+                .ok_or_else(|| anyhow!("'{}' is not a valid NodeId value, expected e.g. nodeid:ns=2;s=Target", input))?;
+            Variant::NodeId(Box::new(node_id))
+        }
+        other => return Err(anyhow!("Writing {} values is not supported yet", other)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str, value_type: VariantScalarTypeId) -> Variant {
+        parse_value(input, value_type).unwrap()
+    }
+
+    #[test]
+    fn prefixed_values() {
+        assert_eq!(parse("lt:en|Pump station 3", VariantScalarTypeId::LocalizedText), Variant::from(LocalizedText::new("en", "Pump station 3")));
+        assert_eq!(parse("qn:2:MotorSpeed", VariantScalarTypeId::QualifiedName), Variant::from(QualifiedName::new(2, "MotorSpeed")));
+        assert_eq!(parse("nodeid:ns=2;s=Target", VariantScalarTypeId::NodeId), Variant::from(NodeId::new(2, "Target")));
+        assert_eq!(parse("nodeid:i=85", VariantScalarTypeId::NodeId), Variant::from(NodeId::new(0, 85u32)));
+    }
+
+    #[test]
+    fn plain_values_take_the_node_type() {
+        assert_eq!(parse("Pump station 3", VariantScalarTypeId::LocalizedText), Variant::from(LocalizedText::new("", "Pump station 3")));
+        // Only the prefixed form splits off a locale
+        assert_eq!(parse("In | Out", VariantScalarTypeId::LocalizedText), Variant::from(LocalizedText::new("", "In | Out")));
+        assert_eq!(parse("MotorSpeed", VariantScalarTypeId::QualifiedName), Variant::from(QualifiedName::new(0, "MotorSpeed")));
+        assert_eq!(parse("Motor:Speed", VariantScalarTypeId::QualifiedName), Variant::from(QualifiedName::new(0, "Motor:Speed")));
+        assert_eq!(parse("ns=2;s=Target", VariantScalarTypeId::NodeId), Variant::from(NodeId::new(2, "Target")));
+        // Strings are literal, prefixes included
+        assert_eq!(parse("lt:en|Pump", VariantScalarTypeId::String), Variant::from("lt:en|Pump"));
+    }
+
+    #[test]
+    fn prefixed_text_keeps_separators() {
+        assert_eq!(parse("lt: de-DE |Ein|Aus", VariantScalarTypeId::LocalizedText), Variant::from(LocalizedText::new("de-DE", "Ein|Aus")));
+        assert_eq!(parse("lt:|No locale", VariantScalarTypeId::LocalizedText), Variant::from(LocalizedText::new("", "No locale")));
+        assert_eq!(parse("qn:3:Line:1", VariantScalarTypeId::QualifiedName), Variant::from(QualifiedName::new(3, "Line:1")));
+    }
+
+    #[test]
+    fn invalid_values() {
+        let error = |input: &str, value_type| parse_value(input, value_type).unwrap_err().to_string();
+        assert!(error("lt:Pump station 3", VariantScalarTypeId::LocalizedText).contains("has no '|'"));
+        assert!(error("qn:2:", VariantScalarTypeId::QualifiedName).contains("has no name"));
+        assert!(error("nodeid:not a node", VariantScalarTypeId::NodeId).contains("is not a valid NodeId value"));
+        assert_eq!(
+            error("qn:2:MotorSpeed", VariantScalarTypeId::LocalizedText),
+            "'qn:2:MotorSpeed' is a QualifiedName value, but the node holds LocalizedText values"
+        );
+        assert!(error("lt:en|42", VariantScalarTypeId::Int32).contains("is a LocalizedText value"));
+    }
+
+    /// The display form of QualifiedName and NodeId values parses back to the same value, with
+    /// and without the prefix
+    #[test]
+    fn display_form_round_trips() {
+        let values = [
+            (VariantScalarTypeId::QualifiedName, "qn:", Variant::from(QualifiedName::new(2, "MotorSpeed"))),
+            (VariantScalarTypeId::QualifiedName, "qn:", Variant::from(QualifiedName::new(0, "Name"))),
+            (VariantScalarTypeId::NodeId, "nodeid:", Variant::from(NodeId::new(2, "Target"))),
+            (VariantScalarTypeId::NodeId, "nodeid:", Variant::from(NodeId::new(3, 1001u32))),
+            (VariantScalarTypeId::NodeId, "nodeid:", Variant::from(NodeId::new(2, "Prg.Cfg;Rev=2"))),
+        ];
+        for (value_type, prefix, value) in values {
+            let shown = format_variant(&value);
+            assert_eq!(parse(&shown, value_type), value, "{}", shown);
+            assert_eq!(parse(&format!("{}{}", prefix, shown), value_type), value, "{}", shown);
+        }
+    }
+
+    /// LocalizedText shows its text quoted and without the locale, the write output adds the
+    /// locale so that both parts of the written value are visible
+    #[test]
+    fn localized_text_display() {
+        let value = parse("lt:en|Pump station 3", VariantScalarTypeId::LocalizedText);
+        assert_eq!(format_variant(&value), "\"Pump station 3\"");
+        assert_eq!(describe_value(&value), "\"Pump station 3\" (en)");
+
+        let value = parse("Pump station 3", VariantScalarTypeId::LocalizedText);
+        assert_eq!(describe_value(&value), "\"Pump station 3\"");
+
+        let shown = format_variant(&value);
+        let text = shown.strip_prefix('"').and_then(|shown| shown.strip_suffix('"')).unwrap();
+        assert_eq!(parse(&format!("lt:en|{}", text), VariantScalarTypeId::LocalizedText), Variant::from(LocalizedText::new("en", "Pump station 3")));
+    }
+}
@@ -0,0 +1,8 @@
+//! The commands of opcua-walker and the client they share, used by the binary and by the
+//! integration tests in `tests/`
+
+pub mod client;
+pub mod commands;
+pub mod session;
+pub mod types;
+pub mod utils;
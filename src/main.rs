@@ -1,74 +1,395 @@
-use anyhow::Result;
-use clap::Parser;
-use tracing::debug;
+use anyhow::{anyhow, Result};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use std::sync::Arc;
+use tracing::{debug, warn};
 
-mod client;
-mod commands;
-mod types;
-mod utils;
-
-use crate::client::OpcUaClient;
-use crate::commands::Commands;
-use crate::types::Cli;
+use opcua_walker::client::{ConnectError, OpcUaClient};
+use opcua_walker::commands::{self, ParsedCommand};
+use opcua_walker::session::ServiceStats;
+use opcua_walker::types::Cli;
+use opcua_walker::utils;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() {
+    if let Err(error) = run().await.and_then(|()| utils::audit::check()) {
+        eprintln!("Error: {:?}", error);
+        std::process::exit(utils::outcome::exit_code(&error));
+    }
+}
+
+async fn run() -> Result<()> {
+    let matches = commands::examples::with_examples(Cli::command()).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
     
     // Initialize tracing
-    init_tracing(cli.verbose);
+    init_tracing(cli.verbose, cli.trace_opcua, cli.no_color);
     
     debug!("Starting OPC-UA Walker v{}", env!("CARGO_PKG_VERSION"));
+    log_connection_sources(&matches);
     
-    // Create and configure the OPC-UA client
-    let mut client = OpcUaClient::new(&cli).await?;
+    if let Some(labels) = cli.labels.clone() {
+        utils::labels::install(labels);
+    }
+    if let Some(value_map) = cli.value_map.clone() {
+        utils::value_map::install(value_map);
+    }
     
-    // Connect to the server
-    client.connect().await?;
+    if cli.max_age > 0.0 {
+        warn!("Reading with a max age of {} ms, some servers ignore it and always read from the device", cli.max_age);
+    }
     
-    // Execute the requested command
-    let result = match &cli.command {
-        Commands::Discover => commands::discover::execute(&mut client).await,
-        Commands::Browse { node, depth, compact, values } => {
-            commands::browse::execute(&mut client, node.as_deref(), *depth, *compact, *values).await
+    check_read_only(&cli, &matches)?;
+    
+    if let Some(path) = &cli.audit_log
+        && let Some(modification) = cli.command.modification()
+        && let Err(e) = utils::audit::open(path, matches.subcommand_name().unwrap_or_default())
+    {
+        return Err(anyhow!(
+            "Refusing to run {}: it {}, and --audit-log is set: {}",
+            matches.subcommand_name().unwrap_or("the command"), modification, e
+        ));
+    }
+    
+    // Validate command arguments before spending a connect/disconnect cycle on them
+    if let Err(e) = utils::limits::check(&cli.command, cli.i_know_what_im_doing) {
+        exit_with_usage_error(matches.subcommand_name(), &e.to_string());
+    }
+    let command = commands::validate(&cli.command).unwrap_or_else(|e| {
+        exit_with_usage_error(matches.subcommand_name(), &e.to_string())
+    });
+    
+    utils::formatter::set_raw_strings(cli.raw_strings || command.prints_json());
+    utils::formatter::set_strict(cli.strict);
+    utils::formatter::set_radix(cli.radix);
+    utils::guard::install(cli.yes, cli.max_operations);
+    
+    // Only a command that is going to run replaces an older recording
+    if let Some(path) = &cli.record {
+        utils::recording::start_recording(path)?;
+    }
+    if let Some(recording) = cli.replay.clone() {
+        utils::recording::install_replay(recording);
+    }
+    
+    let stats = Arc::new(ServiceStats::default());
+    
+    // Fleet checks manage their own connections to every listed endpoint
+    if let ParsedCommand::Fleet(args) = &command {
+        let result = commands::fleet::execute(&cli, args, &stats).await;
+        if cli.stats {
+            stats.print();
+        }
+        return result;
+    }
+    
+    // So does monitoring several servers side by side
+    if let ParsedCommand::Monitor(args) = &command
+        && !args.endpoints.is_empty()
+    {
+        let result = commands::monitor::execute_endpoints(&cli, args, &stats).await;
+        if cli.stats {
+            stats.print();
+        }
+        return result;
+    }
+    
+    // Copies need a session to the source and one to the destination
+    if let ParsedCommand::Copy(args) = &command {
+        let result = commands::copy::execute(&cli, args, &stats).await;
+        if cli.stats {
+            stats.print();
         }
-        Commands::Read { node_ids, all_attributes, include_value, search } => {
-            commands::read::execute(
-                &mut client, 
-                node_ids, 
-                *all_attributes, 
-                *include_value, 
-                *search
-            ).await
+        return result;
+    }
+    
+    // Replaying an archive needs no server
+    if let ParsedCommand::Replay(args) = &command {
+        return commands::replay::execute(args);
+    }
+    
+    // Examples are printed from the tool's own data
+    if let ParsedCommand::Examples(args) = &command {
+        return commands::examples::execute(args);
+    }
+    
+    // Finding servers uses discovery connections only
+    if let ParsedCommand::FindServers(args) = &command {
+        return commands::find_servers::execute(&cli, args).await;
+    }
+    
+    // Reports connect by themselves so that connection failures end up in the report
+    if let ParsedCommand::Report(args) = &command {
+        let result = commands::report::execute(&cli, args, &stats).await;
+        if cli.stats {
+            stats.print();
         }
-        Commands::Call { method_id, object_id, args, verbose } => {
-            commands::call::execute(
-                &mut client, 
-                method_id, 
-                object_id.as_deref(), 
-                args.as_deref(),
-                *verbose
-            ).await
+        return result;
+    }
+    
+    // Create and configure the OPC-UA client
+    let mut client = OpcUaClient::new(&cli).await?.with_stats(stats.clone());
+    
+    // Connect to the server, or answer from a snapshot offline
+    if let Some(snapshot) = command.snapshot() {
+        client.open_snapshot(snapshot.clone());
+    } else if let Err(e) = client.connect().await {
+        // Hints go below the error, where returning it would print it last
+        if let Some(error) = e.downcast_ref::<ConnectError>() {
+            eprintln!("Error: {}", error);
+            error.print_hints();
+            std::process::exit(utils::outcome::exit_code(&e));
         }
-        Commands::Info => commands::info::execute(&mut client).await,
+        return Err(e);
+    }
+    client.print_security_warning();
+    
+    // Execute the requested command
+    let result = match &command {
+        ParsedCommand::Discover(args) => commands::discover::execute(&mut client, args).await,
+        ParsedCommand::Browse(args) => commands::browse::execute(&mut client, args).await,
+        ParsedCommand::Read(args) => commands::read::execute(&mut client, args).await,
+        ParsedCommand::Call(args) => commands::call::execute(&mut client, args).await,
+        ParsedCommand::Write(args) => commands::write::execute(&mut client, args).await,
+        ParsedCommand::Monitor(args) => commands::monitor::execute(&mut client, args).await,
+        ParsedCommand::Refs(args) => commands::refs::execute(&mut client, args).await,
+        ParsedCommand::Info(args) => commands::info::execute(&mut client, args).await,
+        ParsedCommand::Export(args) => commands::export::execute(&mut client, args).await,
+        ParsedCommand::Lint(args) => commands::lint::execute(&mut client, args).await,
+        ParsedCommand::Assert(args) => commands::assert::execute(&mut client, args).await,
+        ParsedCommand::Permissions(args) => commands::permissions::execute(&mut client, args).await,
+        ParsedCommand::StaleScan(args) => commands::stale_scan::execute(&mut client, args).await,
+        ParsedCommand::Backup(args) => commands::backup::execute(&mut client, args).await,
+        ParsedCommand::Restore(args) => commands::restore::execute(&mut client, args).await,
+        ParsedCommand::Bench(args) => commands::bench::execute(&mut client, args).await,
+        ParsedCommand::Tui(args) => commands::tui::execute(&mut client, args).await,
+        ParsedCommand::Fleet(_) => unreachable!("fleet is dispatched before connecting"),
+        ParsedCommand::Report(_) => unreachable!("reports are dispatched before connecting"),
+        ParsedCommand::Copy(_) => unreachable!("copies are dispatched before connecting"),
+        ParsedCommand::Replay(_) => unreachable!("replays are dispatched before connecting"),
+        ParsedCommand::FindServers(_) => unreachable!("server searches are dispatched before connecting"),
+        ParsedCommand::Examples(_) => unreachable!("examples are printed before connecting"),
     };
     
     // Disconnect gracefully
     client.disconnect().await?;
     
+    if cli.stats {
+        stats.print();
+    }
+    
     result
 }
 
-fn init_tracing(verbose: bool) {
-    let filter = if verbose {
-        "opcua_walker=debug,opcua_async=info"
-    } else {
-        "opcua_walker=info,opcua_async=warn"
-    };
+/// Print a clap-style error with the usage of the offending subcommand and exit
+fn exit_with_usage_error(subcommand: Option<&str>, message: &str) -> ! {
+    let mut command = Cli::command();
+    command.build();
+    
+    match subcommand.and_then(|name| command.find_subcommand_mut(name)) {
+        Some(subcommand) => subcommand.error(ErrorKind::ValueValidation, message).exit(),
+        None => command.error(ErrorKind::ValueValidation, message).exit(),
+    }
+}
+
+/// Refuse a command that changes the server under `--read-only`, before any connection is made
+fn check_read_only(cli: &Cli, matches: &ArgMatches) -> Result<()> {
+    if cli.read_only
+        && let Some(modification) = cli.command.modification()
+    {
+        let source = match matches.value_source("read_only") {
+            Some(ValueSource::EnvVariable) => "OPCUA_WALKER_READ_ONLY",
+            _ => "--read-only",
+        };
+        return Err(anyhow!(
+            "Refusing to run {}: it {}, and {} is set",
+            matches.subcommand_name().unwrap_or("the command"), modification, source
+        ));
+    }
+    Ok(())
+}
+
+/// Say where each connection parameter came from, flags take precedence over environment variables
+fn log_connection_sources(matches: &ArgMatches) {
+    for (id, env_var) in [
+        ("endpoint", "OPCUA_WALKER_ENDPOINT"),
+        ("username", "OPCUA_WALKER_USERNAME"),
+        ("password", "OPCUA_WALKER_PASSWORD"),
+        ("cert", "OPCUA_WALKER_CERT"),
+        ("key", "OPCUA_WALKER_KEY"),
+        ("proxy", "OPCUA_WALKER_PROXY"),
+    ] {
+        match matches.value_source(id) {
+            Some(ValueSource::CommandLine) => debug!("{} taken from --{}", id, id),
+            Some(ValueSource::EnvVariable) => debug!("{} taken from {}", id, env_var),
+            Some(ValueSource::DefaultValue) => debug!("{} using built-in default", id),
+            _ => {}
+        }
+    }
+}
+
+/// Log targets of the async-opcua crates
+const OPCUA_TARGETS: [&str; 5] = ["opcua_client", "opcua_core", "opcua_crypto", "opcua_nodes", "opcua_types"];
+
+fn init_tracing(verbosity: u8, trace_opcua: bool, no_color: bool) {
+    let rust_log = std::env::var("RUST_LOG").ok();
+    let filter = tracing_filter(verbosity, trace_opcua, rust_log.as_deref());
     
     tracing_subscriber::fmt()
         .with_env_filter(filter)
-        .with_target(false)
-        .with_level(verbose)
+        .with_target(verbosity > 1 || trace_opcua)
+        .with_level(verbosity > 0 || trace_opcua)
+        .with_ansi(!no_color)
+        .with_writer(std::io::stderr)
         .init();
-}
\ No newline at end of file
+}
+
+/// Filter directives for the verbosity, later directives overriding earlier ones for the same target:
+/// `-v` adds debug output of the tool, `-vv` debug output of everything and hex dumps of the
+/// exchanged messages, `--trace-opcua` traces the OPC-UA crates and `RUST_LOG` comes last
+fn tracing_filter(verbosity: u8, trace_opcua: bool, rust_log: Option<&str>) -> String {
+    let (own, opcua) = match verbosity {
+        // The stack's warnings about missing client certificates are noise for most commands
+        0 => ("info", "off"),
+        1 => ("debug", "info"),
+        _ => ("debug", "debug"),
+    };
+    
+    let mut directives = Vec::new();
+    if verbosity > 1 {
+        directives.push("debug".to_string());
+        directives.push("hex=trace".to_string());
+    }
+    directives.push(format!("opcua_walker={}", own));
+    let opcua = if trace_opcua { "trace" } else { opcua };
+    directives.extend(OPCUA_TARGETS.iter().map(|target| format!("{}={}", target, opcua)));
+    if trace_opcua && verbosity <= 1 {
+        directives.push("hex=trace".to_string());
+    }
+    if let Some(rust_log) = rust_log.map(str::trim).filter(|rust_log| !rust_log.is_empty()) {
+        directives.push(rust_log.to_string());
+    }
+    
+    directives.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::EnvFilter;
+
+    fn opcua_directives(level: &str) -> String {
+        OPCUA_TARGETS.map(|target| format!("{}={}", target, level)).join(",")
+    }
+
+    #[test]
+    fn default_filter_silences_the_stack() {
+        assert_eq!(tracing_filter(0, false, None), format!("opcua_walker=info,{}", opcua_directives("off")));
+    }
+
+    #[test]
+    fn verbose_filters() {
+        assert_eq!(tracing_filter(1, false, None), format!("opcua_walker=debug,{}", opcua_directives("info")));
+        assert_eq!(tracing_filter(2, false, None), format!("debug,hex=trace,opcua_walker=debug,{}", opcua_directives("debug")));
+        assert_eq!(tracing_filter(3, false, None), tracing_filter(2, false, None));
+    }
+
+    #[test]
+    fn trace_opcua_traces_the_stack_at_any_verbosity() {
+        assert_eq!(tracing_filter(0, true, None), format!("opcua_walker=info,{},hex=trace", opcua_directives("trace")));
+        assert_eq!(tracing_filter(1, true, None), format!("opcua_walker=debug,{},hex=trace", opcua_directives("trace")));
+        assert_eq!(tracing_filter(2, true, None), format!("debug,hex=trace,opcua_walker=debug,{}", opcua_directives("trace")));
+    }
+
+    #[test]
+    fn rust_log_comes_last() {
+        assert_eq!(
+            tracing_filter(0, false, Some(" opcua_client=warn ")),
+            format!("opcua_walker=info,{},opcua_client=warn", opcua_directives("off"))
+        );
+        assert_eq!(tracing_filter(1, true, Some("")), tracing_filter(1, true, None));
+        assert_eq!(tracing_filter(1, false, Some("  ")), tracing_filter(1, false, None));
+    }
+
+    #[test]
+    fn filters_parse() {
+        for verbosity in 0..=2 {
+            for trace_opcua in [false, true] {
+                for rust_log in [None, Some("warn"), Some("opcua_core=trace,tokio=off")] {
+                    let filter = tracing_filter(verbosity, trace_opcua, rust_log);
+                    assert!(EnvFilter::try_new(&filter).is_ok(), "{}", filter);
+                }
+            }
+        }
+    }
+
+    fn check(args: &[&str]) -> Result<()> {
+        let matches = Cli::command().try_get_matches_from([&["opcua-walker"], args].concat()).unwrap();
+        let cli = Cli::from_arg_matches(&matches).unwrap();
+        check_read_only(&cli, &matches)
+    }
+
+    const MUTATING: [&[&str]; 8] = [
+        &["write", "ns=2;s=Setpoint", "42.5"],
+        &["write", "ns=2;s=Watchdog", "--heartbeat", "1s"],
+        &["call", "AddNumbers", "--args", "5,10"],
+        &["call", "Open", "--on-type", "ns=2;s=ValveType"],
+        &["restore", "drive1.json"],
+        &["restore", "drive1.json", "--node", "ns=2;s=Drive2"],
+        &["copy", "ns=2;s=Recipes", "--to", "opc.tcp://line2:4840"],
+        &["bench", "--write", "ns=2;s=Scratch"],
+    ];
+
+    const READ_ONLY: [&[&str]; 22] = [
+        &["discover"],
+        &["find-servers"],
+        &["browse", "--node", "ns=2;s=Plant"],
+        &["read", "ns=2;s=Temperature"],
+        &["monitor", "ns=2;s=Temperature"],
+        &["refs", "ns=2;s=Temperature"],
+        &["info"],
+        &["fleet", "opc.tcp://plc-a:4840"],
+        &["export", "--nodeset2", "model.xml"],
+        &["lint"],
+        &["assert", "interface.json"],
+        &["permissions", "ns=2;s=Setpoint"],
+        &["stale-scan"],
+        &["backup", "ns=2;s=Drive1", "--output", "drive1.json"],
+        &["report", "--output", "support.json"],
+        &["replay", "soak.ndjson"],
+        &["tui"],
+        &["examples"],
+        &["bench"],
+        &["call", "Open", "--on-type", "ns=2;s=ValveType", "--dry-run"],
+        &["restore", "drive1.json", "--dry-run"],
+        &["copy", "ns=2;s=Recipes", "--to", "opc.tcp://line2:4840", "--dry-run"],
+    ];
+
+    #[test]
+    fn read_only_blocks_mutating_commands() {
+        for args in MUTATING {
+            let error = check(&[&["--read-only"], args].concat()).unwrap_err().to_string();
+            assert!(error.starts_with(&format!("Refusing to run {}: it ", args[0])), "{}", error);
+            assert!(error.ends_with("and --read-only is set"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn read_only_passes_read_only_commands() {
+        for args in READ_ONLY {
+            assert!(check(&[&["--read-only"], args].concat()).is_ok(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn mutating_commands_run_without_read_only() {
+        for args in MUTATING {
+            assert!(check(args).is_ok(), "{:?}", args);
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use colored::*;
+use opcua::client::services::Call;
+use opcua::client::{OnSubscriptionNotificationCore, Session, UARequest};
+use opcua::types::*;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tabled::{Table, Tabled};
+
+use tracing::{debug, warn};
+
+use crate::utils::audit;
+use crate::utils::enums::EnumCache;
+use crate::utils::recording::{self, Recorded, RequestKey};
+use crate::utils::reference_types::ReferenceTypeCache;
+use crate::utils::schema::DataTypeCache;
+use crate::utils::snapshot::Snapshot;
+
+/// Counters of one service type
+#[derive(Default, Clone, Copy)]
+struct ServiceCounter {
+    requests: u64,
+    operations: u64,
+    failures: u64,
+    elapsed: Duration,
+}
+
+/// Service calls made through one or more sessions, printed with `--stats`
+#[derive(Default)]
+pub struct ServiceStats {
+    counters: Mutex<BTreeMap<&'static str, ServiceCounter>>,
+}
+
+#[derive(Tabled)]
+struct StatsRow {
+    #[tabled(rename = "Service")]
+    service: String,
+    #[tabled(rename = "Requests")]
+    requests: u64,
+    #[tabled(rename = "Operations")]
+    operations: u64,
+    #[tabled(rename = "Failed")]
+    failures: u64,
+    #[tabled(rename = "Total Time")]
+    total: String,
+    #[tabled(rename = "Avg Time")]
+    average: String,
+}
+
+impl ServiceStats {
+    fn record(&self, service: &'static str, operations: usize, elapsed: Duration, failed: bool) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let counter = counters.entry(service).or_default();
+        counter.requests += 1;
+        counter.operations += operations as u64;
+        counter.failures += failed as u64;
+        counter.elapsed += elapsed;
+    }
+
+    /// Print the summary table to stderr so machine-readable output on stdout stays intact
+    pub fn print(&self) {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner()).clone();
+
+        eprintln!("\n{}", "📈 Service Statistics".bright_cyan().bold());
+        if counters.is_empty() {
+            eprintln!("   No service calls were made");
+            return;
+        }
+
+        let total = counters.values().fold(ServiceCounter::default(), |sum, c| ServiceCounter {
+            requests: sum.requests + c.requests,
+            operations: sum.operations + c.operations,
+            failures: sum.failures + c.failures,
+            elapsed: sum.elapsed + c.elapsed,
+        });
+
+        let rows: Vec<StatsRow> = counters
+            .iter()
+            .map(|(service, counter)| stats_row(service, counter))
+            .chain(std::iter::once(stats_row("Total", &total)))
+            .collect();
+        eprintln!("{}", Table::new(rows));
+    }
+}
+
+fn stats_row(service: &str, counter: &ServiceCounter) -> StatsRow {
+    StatsRow {
+        service: service.to_string(),
+        requests: counter.requests,
+        operations: counter.operations,
+        failures: counter.failures,
+        total: format!("{:.1} ms", counter.elapsed.as_secs_f64() * 1000.0),
+        average: format!("{:.1} ms", counter.elapsed.as_secs_f64() * 1000.0 / counter.requests.max(1) as f64),
+    }
+}
+
+/// A connected session whose service calls are counted and timed, and recorded with `--record`.
+/// While replaying a recording there is no session and the recording answers the calls; offline,
+/// a snapshot answers reads and browses.
+#[derive(Clone)]
+pub struct ServiceSession {
+    session: Option<Arc<Session>>,
+    /// Snapshot opened with `--from-snapshot`, answering in place of a server
+    snapshot: Option<Arc<Snapshot>>,
+    /// Endpoint URL the calls are recorded under
+    endpoint: String,
+    /// Who the session is logged in as, for the `--audit-log`
+    identity: String,
+    stats: Arc<ServiceStats>,
+    /// Enumeration names of DataTypes, looked up once per session
+    enums: Arc<EnumCache>,
+    /// Names of non-standard reference types, looked up once per session
+    reference_types: Arc<ReferenceTypeCache>,
+    /// Base types and structure fields of DataTypes, looked up once per session
+    data_types: Arc<DataTypeCache>,
+    /// maxAge in milliseconds of the reads made through [`ServiceSession::read_values`]
+    max_age: f64,
+}
+
+impl ServiceSession {
+    pub fn new(session: Arc<Session>, endpoint: &str, stats: Arc<ServiceStats>, max_age: f64) -> Self {
+        Self::with_session(Some(session), endpoint, stats, max_age)
+    }
+
+    /// A session answered from the `--replay` recording of `endpoint`
+    pub fn replayed(endpoint: &str, stats: Arc<ServiceStats>, max_age: f64) -> Self {
+        Self::with_session(None, endpoint, stats, max_age)
+    }
+
+    /// A session answered from a snapshot taken with `export --snapshot`
+    pub fn from_snapshot(snapshot: Arc<Snapshot>, stats: Arc<ServiceStats>) -> Self {
+        let mut session = Self::with_session(None, &snapshot.endpoint, stats, 0.0);
+        session.snapshot = Some(snapshot);
+        session
+    }
+
+    fn with_session(session: Option<Arc<Session>>, endpoint: &str, stats: Arc<ServiceStats>, max_age: f64) -> Self {
+        Self {
+            session,
+            snapshot: None,
+            endpoint: endpoint.to_string(),
+            identity: "anonymous".to_string(),
+            stats,
+            enums: Arc::default(),
+            reference_types: Arc::default(),
+            data_types: Arc::default(),
+            max_age,
+        }
+    }
+
+    /// Name the user the session is logged in as in the `--audit-log`
+    pub fn with_identity(mut self, identity: String) -> Self {
+        self.identity = identity;
+        self
+    }
+
+    pub fn enums(&self) -> &EnumCache {
+        &self.enums
+    }
+
+    pub fn reference_types(&self) -> &ReferenceTypeCache {
+        &self.reference_types
+    }
+
+    pub fn data_types(&self) -> &DataTypeCache {
+        &self.data_types
+    }
+
+    /// The session calls are made on; subscriptions cannot be replayed as their notifications
+    /// are not part of recordings
+    fn live(&self, service: &str) -> Result<&Arc<Session>, StatusCode> {
+        self.session.as_ref().ok_or_else(|| {
+            if self.snapshot.is_some() {
+                warn!("{} cannot be made offline, snapshots only hold attributes and references", service);
+                return StatusCode::BadNotSupported;
+            }
+            warn!("{} cannot be replayed, recordings only hold the results of reads, browses and calls", service);
+            StatusCode::BadNotSupported
+        })
+    }
+
+    async fn measure<T>(
+        &self,
+        service: &'static str,
+        operations: usize,
+        call: impl Future<Output = Result<T, StatusCode>>,
+    ) -> Result<T, StatusCode> {
+        let started = Instant::now();
+        let result = call.await;
+        self.stats.record(service, operations, started.elapsed(), result.is_err());
+        result
+    }
+
+    /// [`Self::measure`] for calls that are recorded: while replaying `request` is answered
+    /// from the recording and `call` is never made
+    async fn measure_recorded<T: Recorded>(
+        &self,
+        service: &'static str,
+        operations: usize,
+        request: RequestKey,
+        call: impl Future<Output = Result<T, StatusCode>>,
+    ) -> Result<T, StatusCode> {
+        if let Some(answer) = recording::answer(&self.endpoint, service, &request) {
+            self.stats.record(service, operations, Duration::ZERO, answer.is_err());
+            return answer;
+        }
+        let result = self.measure(service, operations, call).await;
+        recording::record(&self.endpoint, service, &request, &result);
+        result
+    }
+
+    /// Answer a call from the snapshot when there is one, counted as taking no time
+    fn offline<T>(&self, service: &'static str, operations: usize, answer: impl FnOnce(&Snapshot) -> Vec<T>) -> Option<Vec<T>> {
+        let snapshot = self.snapshot.as_ref()?;
+        self.stats.record(service, operations, Duration::ZERO, false);
+        Some(answer(snapshot))
+    }
+
+    pub async fn read(
+        &self,
+        nodes_to_read: &[ReadValueId],
+        timestamps_to_return: TimestampsToReturn,
+        max_age: f64,
+    ) -> Result<Vec<DataValue>, StatusCode> {
+        if let Some(values) = self.offline("Read", nodes_to_read.len(), |snapshot| snapshot.read(nodes_to_read)) {
+            return Ok(values);
+        }
+        let request = RequestKey::default().array(nodes_to_read).value(&timestamps_to_return).value(&max_age);
+        self.measure_recorded("Read", nodes_to_read.len(), request, async {
+            self.live("Read")?.read(nodes_to_read, timestamps_to_return, max_age).await
+        })
+        .await
+    }
+
+    /// Read with the `--max-age` of the session. With a maxAge the server timestamps are requested
+    /// as well, and values stamped before the request are logged as served from the server's cache.
+    pub async fn read_values(&self, nodes_to_read: &[ReadValueId]) -> Result<Vec<DataValue>, StatusCode> {
+        if self.max_age == 0.0 {
+            return self.read(nodes_to_read, TimestampsToReturn::Neither, 0.0).await;
+        }
+
+        let requested = DateTime::now();
+        let values = self.read(nodes_to_read, TimestampsToReturn::Server, self.max_age).await?;
+        for (request, data_value) in nodes_to_read.iter().zip(&values) {
+            let Some(timestamp) = data_value.server_timestamp else {
+                continue;
+            };
+            // Clock differences between client and server blur this by a few milliseconds
+            let age = (requested.as_chrono() - timestamp.as_chrono()).num_milliseconds();
+            if age <= 0 {
+                continue;
+            }
+            if age as f64 > self.max_age {
+                debug!("{} is {} ms old, older than the max age of {} ms", request.node_id, age, self.max_age);
+            } else {
+                debug!("{} served from cache, {} ms old", request.node_id, age);
+            }
+        }
+        Ok(values)
+    }
+
+    pub async fn write(&self, nodes_to_write: &[WriteValue]) -> Result<Vec<StatusCode>, StatusCode> {
+        // Replayed writes change no server and are not audited
+        let audit = match self.session {
+            Some(_) => audit::write(&self.endpoint, &self.identity, nodes_to_write)?,
+            None => None,
+        };
+        let request = RequestKey::default().array(nodes_to_write);
+        let result = self
+            .measure_recorded("Write", nodes_to_write.len(), request, async { self.live("Write")?.write(nodes_to_write).await })
+            .await;
+        if let Some(audit) = audit {
+            audit.written(&result);
+        }
+        result
+    }
+
+    pub async fn browse(
+        &self,
+        nodes_to_browse: &[BrowseDescription],
+        max_references_per_node: u32,
+        view: Option<ViewDescription>,
+    ) -> Result<Vec<BrowseResult>, StatusCode> {
+        if let Some(results) = self.offline("Browse", nodes_to_browse.len(), |snapshot| snapshot.browse(nodes_to_browse)) {
+            return Ok(results);
+        }
+        let request = RequestKey::default()
+            .array(nodes_to_browse)
+            .value(&max_references_per_node)
+            .value(&view.clone().unwrap_or_default());
+        self.measure_recorded("Browse", nodes_to_browse.len(), request, async {
+            self.live("Browse")?.browse(nodes_to_browse, max_references_per_node, view).await
+        })
+        .await
+    }
+
+    pub async fn browse_next(
+        &self,
+        release_continuation_points: bool,
+        continuation_points: &[ByteString],
+    ) -> Result<Vec<BrowseResult>, StatusCode> {
+        // Offline browses return everything at once, so no continuation point is valid
+        if self.snapshot.is_some() {
+            return Err(StatusCode::BadContinuationPointInvalid);
+        }
+        let request = RequestKey::default().value(&release_continuation_points).array(continuation_points);
+        self.measure_recorded("BrowseNext", continuation_points.len(), request, async {
+            self.live("BrowseNext")?.browse_next(release_continuation_points, continuation_points).await
+        })
+        .await
+    }
+
+    pub async fn translate_browse_paths_to_node_ids(
+        &self,
+        browse_paths: &[BrowsePath],
+    ) -> Result<Vec<BrowsePathResult>, StatusCode> {
+        if let Some(results) =
+            self.offline("TranslateBrowsePaths", browse_paths.len(), |snapshot| snapshot.translate_browse_paths(browse_paths))
+        {
+            return Ok(results);
+        }
+        let request = RequestKey::default().array(browse_paths);
+        self.measure_recorded("TranslateBrowsePaths", browse_paths.len(), request, async {
+            self.live("TranslateBrowsePaths")?.translate_browse_paths_to_node_ids(browse_paths).await
+        })
+        .await
+    }
+
+    /// Call methods, returning the full response so diagnostics and the string table are kept
+    pub async fn call(
+        &self,
+        methods_to_call: Vec<CallMethodRequest>,
+        diagnostics: DiagnosticBits,
+        timeout: Option<Duration>,
+    ) -> Result<CallResponse, StatusCode> {
+        let audit = match self.session {
+            Some(_) => audit::call(&self.endpoint, &self.identity, &methods_to_call)?,
+            None => None,
+        };
+        let operations = methods_to_call.len();
+        let key = RequestKey::default().array(&methods_to_call).value(&diagnostics.bits());
+        let result = self.measure_recorded("Call", operations, key, async {
+            let session = self.live("Call")?;
+            let mut request = Call::new(session)
+                .methods_to_call(methods_to_call)
+                .diagnostics(diagnostics);
+            if let Some(timeout) = timeout {
+                request = request.timeout(timeout);
+            }
+            request.send(session.channel()).await
+        })
+        .await;
+        if let Some(audit) = audit {
+            audit.called(&result);
+        }
+        result
+    }
+
+    /// Publishing interval and keep-alive count of a subscription as the server revised them
+    pub fn subscription_timing(&self, subscription_id: u32) -> Option<(Duration, u32)> {
+        let state = self.session.as_ref()?.subscription_state().lock();
+        let subscription = state.get(subscription_id)?;
+        Some((subscription.publishing_interval(), subscription.max_keep_alive_count()))
+    }
+
+    pub async fn create_subscription(
+        &self,
+        publishing_interval: Duration,
+        lifetime_count: u32,
+        max_keep_alive_count: u32,
+        callback: impl OnSubscriptionNotificationCore + 'static,
+    ) -> Result<u32, StatusCode> {
+        self.measure("CreateSubscription", 1, async {
+            self.live("CreateSubscription")?
+                .create_subscription(publishing_interval, lifetime_count, max_keep_alive_count, 0, 0, true, callback)
+                .await
+        })
+        .await
+    }
+
+    /// Create monitored items, returning the result of each item in request order
+    pub async fn create_monitored_items(
+        &self,
+        subscription_id: u32,
+        timestamps_to_return: TimestampsToReturn,
+        items_to_create: Vec<MonitoredItemCreateRequest>,
+    ) -> Result<Vec<MonitoredItemCreateResult>, StatusCode> {
+        let operations = items_to_create.len();
+        let created = self
+            .measure("CreateMonitoredItems", operations, async {
+                self.live("CreateMonitoredItems")?
+                    .create_monitored_items(subscription_id, timestamps_to_return, items_to_create)
+                    .await
+            })
+            .await?;
+        Ok(created.into_iter().map(|item| item.result).collect())
+    }
+
+    pub async fn delete_monitored_items(&self, subscription_id: u32, items_to_delete: &[u32]) -> Result<Vec<StatusCode>, StatusCode> {
+        self.measure("DeleteMonitoredItems", items_to_delete.len(), async {
+            self.live("DeleteMonitoredItems")?.delete_monitored_items(subscription_id, items_to_delete).await
+        })
+        .await
+    }
+
+    pub async fn delete_subscription(&self, subscription_id: u32) -> Result<StatusCode, StatusCode> {
+        self.measure("DeleteSubscription", 1, async {
+            self.live("DeleteSubscription")?.delete_subscription(subscription_id).await
+        })
+        .await
+    }
+
+    /// Whether calls go to a server rather than a recording or snapshot
+    pub fn is_connected(&self) -> bool {
+        self.session.is_some()
+    }
+
+    pub async fn disconnect(&self) -> Result<(), StatusCode> {
+        match &self.session {
+            Some(session) => session.disconnect().await,
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use opcua::types::{DataValue, DateTime};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::utils::change_stats::NodeSummary;
+use crate::utils::formatter::variant_to_json;
+
+/// Version of the archive records, raised when they change incompatibly. Format 2 added the
+/// summary trailer, format 3 the publishing records.
+pub const ARCHIVE_FORMAT: u32 = 3;
+
+/// First line of every archive file, describing the nodes the change records refer to
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveHeader {
+    pub format: u32,
+    pub tool_version: String,
+    pub endpoints: Vec<ArchiveEndpoint>,
+    pub nodes: Vec<ArchiveNode>,
+    /// When this file was started, RFC 3339
+    pub start_time: String,
+    /// Position of the file among the files of one monitor run, from 1
+    pub sequence: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ArchiveEndpoint {
+    pub url: String,
+    /// Set when several endpoints were monitored side by side
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ArchiveNode {
+    pub node_id: String,
+    pub display_name: String,
+    /// DataType by name for standard types, by node ID otherwise, empty when unreadable
+    pub data_type: String,
+    /// Position of the node's server in `endpoints`
+    pub endpoint: usize,
+}
+
+/// One data change, referring to its node by the position in the header's node list
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveChange {
+    pub node: usize,
+    /// Source timestamp, or the server timestamp when the source has none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    pub value: serde_json::Value,
+    /// Omitted for Good values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// The subscriptions of an endpoint went silent or recovered
+#[derive(Serialize, Deserialize)]
+pub struct ArchivePublishing {
+    pub publishing: PublishingStatus,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PublishingStatus {
+    /// Position of the server in the header's `endpoints`
+    pub endpoint: usize,
+    pub state: PublishingState,
+    /// When the state changed, RFC 3339
+    pub time: String,
+    /// Seconds since the last publish response, or that the gap lasted once recovered
+    pub silent_secs: f64,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishingState {
+    /// No publish response, not even a keep-alive, for longer than the limit
+    Silent,
+    /// Publish responses arrive again
+    Recovered,
+}
+
+/// Last record of the last file of a run: the statistics of every node over the whole run
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveTrailer {
+    pub summary: ArchiveSummary,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    /// When monitoring stopped, RFC 3339
+    pub end_time: String,
+    pub duration_secs: f64,
+    pub nodes: Vec<NodeSummary>,
+}
+
+/// When a new archive file is started
+#[derive(Clone, Copy)]
+pub enum Rotation {
+    /// With every new hour of the wall clock
+    Hourly,
+    /// Once the file holds this many bytes
+    Size(u64),
+}
+
+/// Writes data changes as NDJSON files in a directory, each starting with an [`ArchiveHeader`]
+pub struct ArchiveWriter {
+    dir: PathBuf,
+    endpoints: Vec<ArchiveEndpoint>,
+    nodes: Vec<ArchiveNode>,
+    rotation: Rotation,
+    compress: bool,
+    current: Option<ArchiveFile>,
+    pub files: u32,
+    pub changes: u64,
+}
+
+struct ArchiveFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    bytes: u64,
+    /// `YYYYMMDDHH` the file was started in, for hourly rotation
+    hour: String,
+}
+
+impl ArchiveWriter {
+    pub fn create(dir: &str, endpoints: Vec<ArchiveEndpoint>, nodes: Vec<ArchiveNode>, rotation: Rotation, compress: bool) -> Result<Self> {
+        fs::create_dir_all(dir).map_err(|e| anyhow!("Failed to create archive directory {}: {}", dir, e))?;
+        Ok(ArchiveWriter {
+            dir: PathBuf::from(dir),
+            endpoints,
+            nodes,
+            rotation,
+            compress,
+            current: None,
+            files: 0,
+            changes: 0,
+        })
+    }
+
+    /// Append a change of the node at `node` in the header's node list
+    pub fn write(&mut self, node: usize, data_value: &DataValue) -> Result<()> {
+        self.rotate_if_due()?;
+
+        let change = ArchiveChange {
+            node,
+            time: data_value.source_timestamp
+                .or(data_value.server_timestamp)
+                .map(|ts| ts.as_chrono().to_rfc3339()),
+            value: data_value.value.as_ref().map(variant_to_json).unwrap_or_default(),
+            status: data_value.status.filter(|status| !status.is_good()).map(|status| status.to_string()),
+        };
+        self.append(&serde_json::to_string(&change)?)?;
+        self.changes += 1;
+        Ok(())
+    }
+
+    /// Append a change of the publishing state of an endpoint
+    pub fn write_publishing(&mut self, publishing: PublishingStatus) -> Result<()> {
+        self.rotate_if_due()?;
+        self.append(&serde_json::to_string(&ArchivePublishing { publishing })?)
+    }
+
+    /// Append the trailer of the run, in a file of its own when no change was archived
+    pub fn write_summary(&mut self, summary: ArchiveSummary) -> Result<()> {
+        if self.current.is_none() {
+            self.start()?;
+        }
+        self.append(&serde_json::to_string(&ArchiveTrailer { summary })?)
+    }
+
+    /// Start a new file when there is none yet or the current one is due for rotation
+    fn rotate_if_due(&mut self) -> Result<()> {
+        let now = DateTime::now().as_chrono();
+        let rotate = match (&self.current, self.rotation) {
+            (None, _) => true,
+            (Some(file), Rotation::Hourly) => file.hour != now.format("%Y%m%d%H").to_string(),
+            (Some(file), Rotation::Size(limit)) => file.bytes >= limit,
+        };
+        if rotate {
+            self.finish()?;
+            self.start()?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, line: &str) -> Result<()> {
+        let file = self.current.as_mut().expect("archive file was just started");
+        writeln!(file.writer, "{}", line)?;
+        // Flushed per record so an interrupted run loses nothing
+        file.writer.flush()?;
+        file.bytes += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// Get the current file onto disk, e.g. before the server goes away
+    pub fn sync(&mut self) -> Result<()> {
+        if let Some(file) = &mut self.current {
+            file.writer.flush()?;
+            file.writer.get_ref().sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Close the current file, compressing it with `--archive-compress`
+    pub fn finish(&mut self) -> Result<()> {
+        let Some(file) = self.current.take() else {
+            return Ok(());
+        };
+        let ArchiveFile { path, mut writer, .. } = file;
+        writer.flush()?;
+        drop(writer);
+        if self.compress {
+            compress(&path)?;
+        }
+        Ok(())
+    }
+
+    fn start(&mut self) -> Result<()> {
+        let now = DateTime::now().as_chrono();
+        self.files += 1;
+        let path = self.dir.join(format!("monitor-{}-{:04}.ndjson", now.format("%Y%m%d-%H%M%S"), self.files));
+        let file = File::create(&path).map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))?;
+
+        let header = ArchiveHeader {
+            format: ARCHIVE_FORMAT,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            endpoints: self.endpoints.clone(),
+            nodes: self.nodes.clone(),
+            start_time: now.to_rfc3339(),
+            sequence: self.files,
+        };
+        let line = serde_json::to_string(&header)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", line)?;
+        writer.flush()?;
+
+        self.current = Some(ArchiveFile {
+            path,
+            writer,
+            bytes: line.len() as u64 + 1,
+            hour: now.format("%Y%m%d%H").to_string(),
+        });
+        Ok(())
+    }
+}
+
+/// Replace a file by its gzip compressed `.gz` copy
+fn compress(path: &Path) -> Result<()> {
+    let mut gz_path = path.as_os_str().to_owned();
+    gz_path.push(".gz");
+    let mut input = File::open(path)?;
+    let output = File::create(&gz_path).map_err(|e| anyhow!("Failed to create {}: {}", Path::new(&gz_path).display(), e))?;
+    let mut encoder = GzEncoder::new(BufWriter::new(output), Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.flush()?;
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Open an archive file, gzip compressed ones by their `.gz` extension, returning its header and
+/// a reader positioned at the first change record
+pub fn open_archive(path: &str) -> Result<(ArchiveHeader, Box<dyn BufRead>)> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path, e))?;
+    let mut reader: Box<dyn BufRead> = if path.ends_with(".gz") {
+        Box::new(BufReader::new(GzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(anyhow!("{} is empty", path));
+    }
+    let header: ArchiveHeader = serde_json::from_str(&line)
+        .map_err(|e| anyhow!("{} does not start with an archive header: {}", path, e))?;
+    if header.format > ARCHIVE_FORMAT {
+        return Err(anyhow!("{} has archive format {}, this version reads up to {}", path, header.format, ARCHIVE_FORMAT));
+    }
+    Ok((header, reader))
+}
@@ -0,0 +1,229 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use opcua::types::{CallMethodRequest, CallResponse, StatusCode, WriteValue};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+
+use crate::utils::formatter::{format_node_id, variant_to_json};
+
+/// Audit log of the current run, opened once from `--audit-log` for commands that change servers
+static AUDIT: OnceLock<AuditLog> = OnceLock::new();
+
+struct AuditLog {
+    path: PathBuf,
+    /// Subcommand the run executes, e.g. `restore`
+    command: String,
+    file: Mutex<File>,
+    /// Set once writing failed, after that writes and calls are refused
+    failed: AtomicBool,
+}
+
+/// Open `path` for appending the writes and calls of `command`, failing when it cannot be written
+pub fn open(path: &Path, command: &str) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow!("Cannot open the audit log {}: {}", path.display(), e))?;
+    let _ = AUDIT.set(AuditLog {
+        path: path.to_path_buf(),
+        command: command.to_string(),
+        file: Mutex::new(file),
+        failed: AtomicBool::new(false),
+    });
+    Ok(())
+}
+
+/// Fail when records of the run could not be written, so the changes made do not go unnoticed
+pub fn check() -> Result<()> {
+    match AUDIT.get() {
+        Some(log) if log.failed.load(Ordering::Relaxed) => {
+            Err(anyhow!("The audit log {} is missing records of this run, writing it failed", log.path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// One write or method call as a line of the audit log
+#[derive(Serialize)]
+struct AuditRecord {
+    time: String,
+    command: String,
+    endpoint: String,
+    identity: String,
+    service: &'static str,
+    operations: Vec<Operation>,
+    /// Status of a service call that failed as a whole, or why it got no answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Operation {
+    Write(WriteOperation),
+    Call(CallOperation),
+}
+
+#[derive(Serialize)]
+struct WriteOperation {
+    node_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_range: Option<String>,
+    value: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_timestamp: Option<String>,
+    /// StatusCode written along with the value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value_status: Option<String>,
+    /// Result of the operation, absent when the service failed as a whole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CallOperation {
+    object_id: String,
+    method_id: String,
+    input_arguments: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+}
+
+/// An audit record of a write or call that is being made. It is written once the result is
+/// known, or when the request is dropped without one, e.g. on Ctrl+C.
+pub struct Pending {
+    record: Option<AuditRecord>,
+}
+
+/// Start the audit record of a Write request. `None` without `--audit-log`; once writing the log
+/// failed the request is refused.
+pub fn write(endpoint: &str, identity: &str, nodes_to_write: &[WriteValue]) -> Result<Option<Pending>, StatusCode> {
+    let operations = nodes_to_write
+        .iter()
+        .map(|write_value| {
+            let value = &write_value.value;
+            Operation::Write(WriteOperation {
+                node_id: format_node_id(&write_value.node_id),
+                index_range: (!write_value.index_range.is_none()).then(|| write_value.index_range.to_string()),
+                value: value.value.as_ref().map(variant_to_json).unwrap_or_default(),
+                source_timestamp: value.source_timestamp.map(|time| time.as_chrono().to_rfc3339()),
+                value_status: value.status.map(|status| status.to_string()),
+                status: None,
+            })
+        })
+        .collect();
+    begin(endpoint, identity, "Write", operations)
+}
+
+/// Start the audit record of a Call request, like [`write`]
+pub fn call(endpoint: &str, identity: &str, methods_to_call: &[CallMethodRequest]) -> Result<Option<Pending>, StatusCode> {
+    let operations = methods_to_call
+        .iter()
+        .map(|method| {
+            Operation::Call(CallOperation {
+                object_id: format_node_id(&method.object_id),
+                method_id: format_node_id(&method.method_id),
+                input_arguments: method.input_arguments.iter().flatten().map(variant_to_json).collect(),
+                status: None,
+            })
+        })
+        .collect();
+    begin(endpoint, identity, "Call", operations)
+}
+
+fn begin(endpoint: &str, identity: &str, service: &'static str, operations: Vec<Operation>) -> Result<Option<Pending>, StatusCode> {
+    let Some(log) = AUDIT.get() else {
+        return Ok(None);
+    };
+    if log.failed.load(Ordering::Relaxed) {
+        warn!("Refusing the {} request: the audit log {} cannot be written", service, log.path.display());
+        return Err(StatusCode::BadRequestNotAllowed);
+    }
+    Ok(Some(Pending {
+        record: Some(AuditRecord {
+            time: Utc::now().to_rfc3339(),
+            command: log.command.clone(),
+            endpoint: endpoint.to_string(),
+            identity: identity.to_string(),
+            service,
+            operations,
+            error: None,
+        }),
+    }))
+}
+
+impl Pending {
+    /// Write the record with the results of a Write request
+    pub fn written(mut self, result: &Result<Vec<StatusCode>, StatusCode>) {
+        let Some(mut record) = self.record.take() else {
+            return;
+        };
+        match result {
+            Ok(statuses) => {
+                for (operation, status) in record.operations.iter_mut().zip(statuses) {
+                    if let Operation::Write(write) = operation {
+                        write.status = Some(status.to_string());
+                    }
+                }
+            }
+            Err(status) => record.error = Some(status.to_string()),
+        }
+        append(&record);
+    }
+
+    /// Write the record with the results of a Call request
+    pub fn called(mut self, result: &Result<CallResponse, StatusCode>) {
+        let Some(mut record) = self.record.take() else {
+            return;
+        };
+        match result {
+            Ok(response) => {
+                let results = response.results.iter().flatten();
+                for (operation, call_result) in record.operations.iter_mut().zip(results) {
+                    if let Operation::Call(call) = operation {
+                        call.status = Some(call_result.status_code.to_string());
+                    }
+                }
+            }
+            Err(status) => record.error = Some(status.to_string()),
+        }
+        append(&record);
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        // The request may still have reached the server, so it is logged without a result
+        if let Some(mut record) = self.record.take() {
+            record.error = Some("cancelled before the server answered".to_string());
+            append(&record);
+        }
+    }
+}
+
+fn append(record: &AuditRecord) {
+    let Some(log) = AUDIT.get() else {
+        return;
+    };
+    let Ok(mut line) = serde_json::to_string(record) else {
+        return;
+    };
+    line.push('\n');
+    let mut file = log.file.lock().unwrap_or_else(|e| e.into_inner());
+    // One write per line; syncing is best effort, a file system without it still gets the line
+    let written = file.write_all(line.as_bytes());
+    if written.is_ok() {
+        let _ = file.sync_data();
+    }
+    if let Err(e) = written
+        && !log.failed.swap(true, Ordering::Relaxed)
+    {
+        warn!("Writing the audit log {} failed, further writes and calls are refused: {}", log.path.display(), e);
+    }
+}
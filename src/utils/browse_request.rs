@@ -0,0 +1,92 @@
+use opcua::types::{BrowseDescription, BrowseDirection, BrowseResultMask, NodeClassMask, NodeId};
+
+// The result masks are an enum of bit values in the client stack; combined they must still
+// cover every field, which breaks at compile time should a version bump renumber them
+const _: () = assert!(BrowseResultMask::ReferenceTypeInfo as u32 | BrowseResultMask::TargetInfo as u32 == BrowseResultMask::All as u32);
+
+// The masks are plain u32 fields of BrowseDescription; should the client stack change them to its
+// enum or bitflags types, this stops compiling here rather than in every command that browses
+const _: fn(&BrowseDescription) -> (u32, u32) = |description| (description.node_class_mask, description.result_mask);
+
+/// A Browse of one node, the only place BrowseDescriptions are put together. Without further
+/// settings subtypes of the reference type are followed and every node class and result field
+/// is returned.
+pub struct BrowseRequest {
+    description: BrowseDescription,
+}
+
+impl BrowseRequest {
+    pub fn new(node_id: &NodeId, direction: BrowseDirection, reference_type: impl Into<NodeId>) -> Self {
+        BrowseRequest {
+            description: BrowseDescription {
+                node_id: node_id.clone(),
+                browse_direction: direction,
+                reference_type_id: reference_type.into(),
+                include_subtypes: true,
+                node_class_mask: 0,
+                result_mask: BrowseResultMask::All as u32,
+            },
+        }
+    }
+
+    /// Follow only the reference type itself, not its subtypes
+    pub fn exact_type(mut self) -> Self {
+        self.description.include_subtypes = false;
+        self
+    }
+
+    /// Return only targets of these node classes, an empty mask returns all
+    pub fn classes(mut self, mask: NodeClassMask) -> Self {
+        self.description.node_class_mask = mask.bits();
+        self
+    }
+
+    /// Return only these fields of each reference; the others are left at their defaults by the
+    /// server, so callers must not read them. The node ID of the target is always returned.
+    pub fn results(mut self, fields: &[BrowseResultMask]) -> Self {
+        self.description.result_mask = fields.iter().fold(0, |mask, field| mask | *field as u32);
+        self
+    }
+
+    pub fn build(self) -> BrowseDescription {
+        self.description
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opcua::types::ReferenceTypeId;
+
+    #[test]
+    fn defaults_return_everything() {
+        let node_id = NodeId::new(2, "Plant");
+        let description = BrowseRequest::new(&node_id, BrowseDirection::Forward, ReferenceTypeId::HierarchicalReferences).build();
+        assert_eq!(description.node_id, node_id);
+        assert_eq!(description.browse_direction, BrowseDirection::Forward);
+        assert_eq!(description.reference_type_id, NodeId::from(ReferenceTypeId::HierarchicalReferences));
+        assert!(description.include_subtypes);
+        assert_eq!(description.node_class_mask, 0);
+        assert_eq!(description.result_mask, 0x3f);
+    }
+
+    #[test]
+    fn knobs_set_their_fields() {
+        let description = BrowseRequest::new(&NodeId::new(2, "Plant"), BrowseDirection::Inverse, ReferenceTypeId::HasComponent)
+            .exact_type()
+            .classes(NodeClassMask::OBJECT | NodeClassMask::VARIABLE)
+            .results(&[BrowseResultMask::BrowseName, BrowseResultMask::NodeClass])
+            .build();
+        assert_eq!(description.browse_direction, BrowseDirection::Inverse);
+        assert!(!description.include_subtypes);
+        assert_eq!(description.node_class_mask, 0x03);
+        assert_eq!(description.result_mask, 0x0c);
+    }
+
+    #[test]
+    fn no_result_fields_is_node_ids_only() {
+        let description = BrowseRequest::new(&NodeId::new(2, "Plant"), BrowseDirection::Both, ReferenceTypeId::References).results(&[]).build();
+        assert_eq!(description.result_mask, 0);
+    }
+}
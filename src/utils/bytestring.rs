@@ -0,0 +1,180 @@
+use anyhow::{anyhow, Result};
+use opcua::types::*;
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::session::ServiceSession;
+use crate::utils::formatter::{format_node_id, variant_type_name};
+use crate::utils::outcome::OperationResult;
+
+/// Longest ByteString the client decodes, so larger values have to be moved in slices
+const CLIENT_MAX_BYTE_STRING_LENGTH: usize = constants::MAX_BYTE_STRING_LENGTH;
+
+/// The MaxByteStringLength capability, `None` when the server states no limit
+pub async fn max_byte_string_length(session: &ServiceSession) -> Option<usize> {
+    let node_id: NodeId = VariableId::Server_ServerCapabilities_MaxByteStringLength.into();
+    match session.read(&[ReadValueId::from(&node_id)], TimestampsToReturn::Neither, 0.0).await {
+        Ok(values) => match values.into_iter().next().and_then(|dv| dv.value) {
+            Some(Variant::UInt32(max)) if max > 0 => Some(max as usize),
+            _ => None,
+        },
+        Err(e) => {
+            debug!("Reading MaxByteStringLength failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Bytes read per request: the client's limit, lowered to the server's when that is smaller
+pub fn read_chunk_size(max_byte_string_length: Option<usize>) -> usize {
+    max_byte_string_length.map_or(CLIENT_MAX_BYTE_STRING_LENGTH, |max| max.min(CLIENT_MAX_BYTE_STRING_LENGTH))
+}
+
+/// Status codes of a write that was too large for the server or the channel
+fn is_too_large(status: StatusCode) -> bool {
+    matches!(
+        status.sub_code(),
+        SubStatusCode::BadEncodingLimitsExceeded
+            | SubStatusCode::BadRequestTooLarge
+            | SubStatusCode::BadDecodingError
+            | SubStatusCode::BadOutOfRange
+    )
+}
+
+/// Inclusive IndexRange of the slice starting at `start`
+fn slice_range(start: usize, len: usize) -> NumericRange {
+    if len == 1 {
+        NumericRange::Index(start as u32)
+    } else {
+        NumericRange::Range(start as u32, (start + len - 1) as u32)
+    }
+}
+
+/// Read a ByteString value in slices of `chunk` bytes with IndexRange reads until a slice comes
+/// back short. Returns the bytes and the number of reads it took.
+pub async fn read_byte_string(session: &ServiceSession, node_id: &NodeId, chunk: usize) -> Result<(Vec<u8>, usize)> {
+    let mut data = Vec::new();
+    let mut reads = 0;
+    loop {
+        let request = ReadValueId {
+            node_id: node_id.clone(),
+            attribute_id: AttributeId::Value as u32,
+            index_range: slice_range(data.len(), chunk),
+            data_encoding: QualifiedName::null(),
+        };
+        let data_value = session.read(&[request], TimestampsToReturn::Neither, 0.0).await
+            .map_err(|status| anyhow::Error::new(OperationResult::of_service_error(status)))?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        reads += 1;
+
+        match data_value.status.map(|status| status.sub_code()) {
+            // The previous slice ended exactly at the end of the value
+            Some(SubStatusCode::BadIndexRangeNoData) if !data.is_empty() => break,
+            // Empty values have no range to read, scalars of other types none at all, and some
+            // servers take no ranges. The whole value is small then, or its read fails by itself.
+            Some(SubStatusCode::BadIndexRangeNoData | SubStatusCode::BadIndexRangeInvalid | SubStatusCode::BadIndexRangeDataMismatch) => {
+                debug!("Reading {} without an IndexRange", format_node_id(node_id));
+                let data_value = session.read(&[ReadValueId::from(node_id)], TimestampsToReturn::Neither, 0.0).await
+                    .map_err(|status| anyhow::Error::new(OperationResult::of_service_error(status)))?
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+                return Ok((byte_string_bytes(node_id, &data_value)?, reads + 1));
+            }
+            _ => {}
+        }
+
+        let bytes = byte_string_bytes(node_id, &data_value)?;
+        let done = bytes.len() < chunk;
+        data.extend_from_slice(&bytes);
+        if done {
+            break;
+        }
+    }
+    Ok((data, reads))
+}
+
+fn byte_string_bytes(node_id: &NodeId, data_value: &DataValue) -> Result<Vec<u8>> {
+    let result = OperationResult::of_data_value(data_value);
+    if !result.is_good() {
+        return Err(anyhow::Error::new(result).context(format!("Reading {} failed", format_node_id(node_id))));
+    }
+    match &data_value.value {
+        Some(Variant::ByteString(bytes)) => Ok(bytes.value.clone().unwrap_or_default()),
+        Some(other) => Err(anyhow!("{} holds a {}, not a ByteString", format_node_id(node_id), variant_type_name(other))),
+        None => Err(anyhow!("{} has no value", format_node_id(node_id))),
+    }
+}
+
+/// Write bytes into a ByteString value. The whole value goes in one write unless it exceeds the
+/// server's MaxByteStringLength or the server refuses it as too large; then it is written slice
+/// by slice with IndexRange writes. Returns the number of writes it took.
+///
+/// Slices can only replace bytes of the current value, so servers refuse the ranged writes unless
+/// the variable already holds a value at least as long as the data.
+pub async fn write_byte_string(session: &ServiceSession, node_id: &NodeId, data: &[u8], max_byte_string_length: Option<usize>) -> Result<usize> {
+    if max_byte_string_length.is_none_or(|max| data.len() <= max) {
+        match write_slice(session, node_id, NumericRange::None, data).await {
+            Ok(()) => return Ok(1),
+            Err(e) => match OperationResult::of_error(&e) {
+                Some(OperationResult::OperationBad(status) | OperationResult::ServiceFault(status))
+                    if is_too_large(status) && data.len() > CLIENT_MAX_BYTE_STRING_LENGTH =>
+                {
+                    debug!("{} refused {} bytes in one write ({}), writing slices", format_node_id(node_id), data.len(), status);
+                }
+                _ => return Err(e),
+            },
+        }
+    }
+
+    let chunk = read_chunk_size(max_byte_string_length);
+    let mut writes = 0;
+    for (idx, slice) in data.chunks(chunk).enumerate() {
+        let start = idx * chunk;
+        if let Err(e) = write_slice(session, node_id, slice_range(start, slice.len()), slice).await {
+            let refused = matches!(
+                OperationResult::of_error(&e),
+                Some(OperationResult::OperationBad(status))
+                    if matches!(status.sub_code(), SubStatusCode::BadIndexRangeNoData | SubStatusCode::BadIndexRangeInvalid | SubStatusCode::BadWriteNotSupported)
+            );
+            let e = e.context(format!("Writing bytes {}..{} failed", start, start + slice.len()));
+            if refused && idx == 0 {
+                return Err(e.context(format!(
+                    "The server does not accept IndexRange writes here; the data is larger than the {} bytes one write can carry",
+                    chunk
+                )));
+            }
+            return Err(e);
+        }
+        writes += 1;
+    }
+    Ok(writes)
+}
+
+async fn write_slice(session: &ServiceSession, node_id: &NodeId, index_range: NumericRange, bytes: &[u8]) -> Result<()> {
+    let write_request = WriteValue {
+        node_id: node_id.clone(),
+        attribute_id: AttributeId::Value as u32,
+        index_range,
+        value: DataValue {
+            value: Some(Variant::ByteString(ByteString::from(bytes.to_vec()))),
+            ..Default::default()
+        },
+    };
+    let result = match session.write(&[write_request]).await {
+        Ok(results) => OperationResult::of_status(results.first().copied().unwrap_or(StatusCode::BadNoData)),
+        Err(status) => OperationResult::of_service_error(status),
+    };
+    if result.is_good() {
+        Ok(())
+    } else {
+        Err(anyhow::Error::new(result))
+    }
+}
+
+/// Lowercase hex SHA-256 of the data, to compare transferred files with their source
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
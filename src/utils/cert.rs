@@ -0,0 +1,265 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use opcua::crypto::X509;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use x509_cert::der::{Decode, Encode};
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::{BasicConstraints, SubjectAltName};
+use x509_cert::Certificate;
+
+/// Certificates expiring within this many days get a warning
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// RSA keys below this many bits are too weak for the current security policies
+const MIN_KEY_BITS: usize = 2048;
+
+/// Issuers followed at most from a certificate to its root
+const MAX_CHAIN_LENGTH: usize = 8;
+
+/// An X.509 certificate with the fields a security review looks at
+pub struct CertificateDetails {
+    pub der: Vec<u8>,
+    pub subject: String,
+    pub issuer: String,
+    /// Subject alternative names as `URI:…`, `DNS:…` and `IP:…`
+    pub alt_names: Vec<String>,
+    /// The first URI alternative name, which OPC UA requires to be the application URI
+    pub application_uri: Option<String>,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    /// Bits of the RSA key, `None` for other key types
+    pub key_bits: Option<usize>,
+    pub signature_algorithm: String,
+    pub is_ca: bool,
+    pub sha1: String,
+    pub sha256: String,
+    certificate: Certificate,
+}
+
+impl CertificateDetails {
+    pub fn from_der(der: &[u8]) -> Result<Self> {
+        let certificate = Certificate::from_der(der).map_err(|e| anyhow!("Not a DER encoded X.509 certificate: {}", e))?;
+        let opcua_certificate = X509::from_der(der).map_err(|e| anyhow!("Not a DER encoded X.509 certificate: {}", e))?;
+        let tbs = &certificate.tbs_certificate;
+
+        let mut alt_names = Vec::new();
+        let mut application_uri = None;
+        if let Ok(Some((_, SubjectAltName(names)))) = tbs.get::<SubjectAltName>() {
+            for name in names {
+                match name {
+                    GeneralName::UniformResourceIdentifier(uri) => {
+                        application_uri.get_or_insert_with(|| uri.to_string());
+                        alt_names.push(format!("URI:{}", uri));
+                    }
+                    GeneralName::DnsName(dns) => alt_names.push(format!("DNS:{}", dns)),
+                    GeneralName::IpAddress(address) => alt_names.push(format!("IP:{}", format_ip(address.as_bytes()))),
+                    _ => {}
+                }
+            }
+        }
+        let is_ca = matches!(tbs.get::<BasicConstraints>(), Ok(Some((_, BasicConstraints { ca: true, .. }))));
+
+        Ok(CertificateDetails {
+            der: der.to_vec(),
+            subject: tbs.subject.to_string(),
+            issuer: tbs.issuer.to_string(),
+            alt_names,
+            application_uri,
+            not_before: opcua_certificate.not_before().map_err(|_| anyhow!("The certificate has no valid start date"))?,
+            not_after: opcua_certificate.not_after().map_err(|_| anyhow!("The certificate has no valid end date"))?,
+            key_bits: opcua_certificate.key_length().ok(),
+            signature_algorithm: signature_algorithm_name(&certificate.signature_algorithm.oid.to_string()),
+            is_ca,
+            sha1: opcua_certificate.thumbprint().as_hex_string(),
+            sha256: format!("{:x}", Sha256::digest(der)),
+            certificate,
+        })
+    }
+
+    /// Load the certificates of a CA file, PEM with one or more certificates or a single DER one
+    pub fn load_file(path: &str) -> Result<Vec<Self>> {
+        let bytes = fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+        if !bytes.starts_with(b"-----BEGIN") {
+            return Ok(vec![Self::from_der(&bytes).map_err(|e| anyhow!("{}: {}", path, e))?]);
+        }
+        let certificates = Certificate::load_pem_chain(&bytes).map_err(|e| anyhow!("{} holds no readable PEM certificates: {}", path, e))?;
+        if certificates.is_empty() {
+            return Err(anyhow!("{} holds no certificates", path));
+        }
+        certificates
+            .iter()
+            .map(|certificate| {
+                let der = certificate.to_der().map_err(|e| anyhow!("{}: {}", path, e))?;
+                Self::from_der(&der).map_err(|e| anyhow!("{}: {}", path, e))
+            })
+            .collect()
+    }
+
+    /// Whether the signature of this certificate verifies with the key of `issuer`
+    pub fn signed_by(&self, issuer: &CertificateDetails) -> Result<bool> {
+        let key = X509::from_der(&issuer.der)
+            .map_err(|e| anyhow!("{}", e))?
+            .public_key()
+            .map_err(|e| anyhow!("The key of {} cannot be used: {}", issuer.subject, e))?;
+        let signed = self.certificate.tbs_certificate.to_der().map_err(|e| anyhow!("{}", e))?;
+        let signature = self.certificate.signature.raw_bytes();
+        let verified = match self.certificate.signature_algorithm.oid.to_string().as_str() {
+            "1.2.840.113549.1.1.5" => key.verify_sha1(&signed, signature),
+            "1.2.840.113549.1.1.11" => key.verify_sha256(&signed, signature),
+            "1.2.840.113549.1.1.10" => key.verify_sha256_pss(&signed, signature),
+            _ => return Err(anyhow!("{} signatures cannot be checked", self.signature_algorithm)),
+        };
+        verified.map_err(|e| anyhow!("{}", e))
+    }
+
+    /// Issued by itself and signed with its own key
+    pub fn is_self_signed(&self) -> bool {
+        self.subject == self.issuer && self.signed_by(self).unwrap_or(false)
+    }
+}
+
+/// Outcome of one check of a server certificate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Pass,
+    Warn,
+    Fail,
+}
+
+pub struct CertificateCheck {
+    pub name: &'static str,
+    pub state: CheckState,
+    pub detail: String,
+}
+
+fn check(name: &'static str, state: CheckState, detail: String) -> CertificateCheck {
+    CertificateCheck { name, state, detail }
+}
+
+/// Check a server certificate against the application URI the server reports, the host it was
+/// reached at and, when given, the certificates of a CA file
+pub fn check_server_certificate(
+    certificate: &CertificateDetails,
+    application_uri: &str,
+    host: Option<&str>,
+    cas: Option<&[CertificateDetails]>,
+    now: DateTime<Utc>,
+) -> Vec<CertificateCheck> {
+    let mut checks = vec![validity(certificate, now)];
+
+    checks.push(match &certificate.application_uri {
+        Some(uri) if uri == application_uri => check("Application URI", CheckState::Pass, format!("matches {}", uri)),
+        Some(uri) => check(
+            "Application URI",
+            CheckState::Fail,
+            format!("the certificate names {}, the server reports {}", uri, application_uri),
+        ),
+        None => check("Application URI", CheckState::Fail, "the certificate has no URI alternative name".to_string()),
+    });
+
+    if let Some(host) = host {
+        let listed = certificate.alt_names.iter().any(|name| {
+            name.strip_prefix("DNS:").or_else(|| name.strip_prefix("IP:")).is_some_and(|name| name.eq_ignore_ascii_case(host))
+        });
+        checks.push(if listed {
+            check("Host name", CheckState::Pass, format!("{} is listed", host))
+        } else {
+            check("Host name", CheckState::Warn, format!("{} is not among the DNS names and addresses of the certificate", host))
+        });
+    }
+
+    checks.push(match certificate.key_bits {
+        Some(bits) if bits < MIN_KEY_BITS => check("Key size", CheckState::Warn, format!("RSA {} bits, below {}", bits, MIN_KEY_BITS)),
+        Some(bits) => check("Key size", CheckState::Pass, format!("RSA {} bits", bits)),
+        None => check("Key size", CheckState::Warn, "not an RSA key, which the OPC UA security policies need".to_string()),
+    });
+
+    if certificate.signature_algorithm.starts_with("sha1") {
+        checks.push(check("Signature", CheckState::Warn, format!("{} is deprecated", certificate.signature_algorithm)));
+    }
+
+    let self_signed = certificate.is_self_signed();
+    let trusted_directly = cas.is_some_and(|cas| cas.iter().any(|ca| ca.der == certificate.der));
+    checks.push(match (self_signed, cas) {
+        (true, Some(_)) if trusted_directly => check("Issuer", CheckState::Pass, "self-signed and listed in the CA file".to_string()),
+        (true, Some(_)) => check("Issuer", CheckState::Fail, "self-signed and not in the CA file".to_string()),
+        (true, None) => check("Issuer", CheckState::Warn, "self-signed, trust it by its thumbprint".to_string()),
+        (false, _) => check("Issuer", CheckState::Pass, format!("issued by {}", certificate.issuer)),
+    });
+
+    if let Some(cas) = cas
+        && !self_signed
+    {
+        checks.push(chain(certificate, cas, now));
+    }
+    checks
+}
+
+fn validity(certificate: &CertificateDetails, now: DateTime<Utc>) -> CertificateCheck {
+    let day = |time: DateTime<Utc>| time.format("%Y-%m-%d").to_string();
+    if now < certificate.not_before {
+        check("Validity", CheckState::Fail, format!("not valid before {}", day(certificate.not_before)))
+    } else if now > certificate.not_after {
+        check("Validity", CheckState::Fail, format!("expired on {}", day(certificate.not_after)))
+    } else if certificate.not_after - now < Duration::days(EXPIRY_WARNING_DAYS) {
+        check("Validity", CheckState::Warn, format!("expires on {}", day(certificate.not_after)))
+    } else {
+        check("Validity", CheckState::Pass, format!("valid until {}", day(certificate.not_after)))
+    }
+}
+
+/// Follow the issuers of `certificate` through `cas` up to a self-signed root
+fn chain(certificate: &CertificateDetails, cas: &[CertificateDetails], now: DateTime<Utc>) -> CertificateCheck {
+    let mut current = certificate;
+    let mut path = Vec::new();
+    for _ in 0..MAX_CHAIN_LENGTH {
+        let Some(issuer) = cas.iter().find(|ca| ca.subject == current.issuer) else {
+            return check("Chain", CheckState::Fail, format!("the issuer {} is not in the CA file", current.issuer));
+        };
+        match current.signed_by(issuer) {
+            Ok(true) => {}
+            Ok(false) => {
+                return check("Chain", CheckState::Fail, format!("the signature of {} does not verify with the key of {}", current.subject, issuer.subject));
+            }
+            Err(e) => return check("Chain", CheckState::Fail, e.to_string()),
+        }
+        if !issuer.is_ca {
+            return check("Chain", CheckState::Fail, format!("{} is no CA certificate", issuer.subject));
+        }
+        if let CertificateCheck { state: CheckState::Fail, detail, .. } = validity(issuer, now) {
+            return check("Chain", CheckState::Fail, format!("{}: {}", issuer.subject, detail));
+        }
+        path.push(issuer.subject.as_str());
+        if issuer.is_self_signed() {
+            return check("Chain", CheckState::Pass, format!("signed by {}", path.join(" ← ")));
+        }
+        current = issuer;
+    }
+    check("Chain", CheckState::Fail, format!("no self-signed root within {} issuers", MAX_CHAIN_LENGTH))
+}
+
+fn format_ip(bytes: &[u8]) -> String {
+    if let Ok(octets) = <[u8; 4]>::try_from(bytes) {
+        Ipv4Addr::from(octets).to_string()
+    } else if let Ok(octets) = <[u8; 16]>::try_from(bytes) {
+        Ipv6Addr::from(octets).to_string()
+    } else {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+fn signature_algorithm_name(oid: &str) -> String {
+    match oid {
+        "1.2.840.113549.1.1.5" => "sha1WithRSAEncryption",
+        "1.2.840.113549.1.1.11" => "sha256WithRSAEncryption",
+        "1.2.840.113549.1.1.12" => "sha384WithRSAEncryption",
+        "1.2.840.113549.1.1.13" => "sha512WithRSAEncryption",
+        "1.2.840.113549.1.1.10" => "RSASSA-PSS",
+        "1.2.840.10045.4.3.2" => "ecdsa-with-SHA256",
+        "1.2.840.10045.4.3.3" => "ecdsa-with-SHA384",
+        _ => return oid.to_string(),
+    }
+    .to_string()
+}
@@ -0,0 +1,173 @@
+use colored::*;
+use opcua::types::{DataValue, Variant};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tabled::{Table, Tabled};
+
+use crate::utils::formatter::{truncate_string, variant_to_json, Severity};
+use crate::utils::units::numeric_value;
+
+/// Width values are cut to in the summary table
+const VALUE_WIDTH: usize = 20;
+
+/// What one node sent during a monitor run, accumulated as the notifications arrive without
+/// keeping them
+#[derive(Default)]
+pub struct ChangeStats {
+    notifications: u64,
+    /// Notifications whose value differs from the one before
+    changes: u64,
+    bad: u64,
+    first: Option<Variant>,
+    last: Option<Variant>,
+    min: Option<f64>,
+    max: Option<f64>,
+    sum: f64,
+    numeric: u64,
+}
+
+impl ChangeStats {
+    pub fn add(&mut self, data_value: &DataValue) {
+        self.notifications += 1;
+        if data_value.status.is_some_and(|status| Severity::of(&status).is_failure()) {
+            self.bad += 1;
+        }
+        let Some(value) = &data_value.value else {
+            return;
+        };
+        if let Some(number) = numeric_value(value) {
+            self.min = Some(self.min.map_or(number, |min| min.min(number)));
+            self.max = Some(self.max.map_or(number, |max| max.max(number)));
+            self.sum += number;
+            self.numeric += 1;
+        }
+        match &self.last {
+            None => self.first = Some(value.clone()),
+            Some(last) if last != value => self.changes += 1,
+            Some(_) => {}
+        }
+        self.last = Some(value.clone());
+    }
+
+    pub fn last(&self) -> Option<&Variant> {
+        self.last.as_ref()
+    }
+
+    pub fn first(&self) -> Option<&Variant> {
+        self.first.as_ref()
+    }
+
+    /// The statistics of the node at `node` over a run of `elapsed`, in the form of archive trailers
+    pub fn summary(&self, node: usize, elapsed: Duration) -> NodeSummary {
+        NodeSummary {
+            node,
+            notifications: self.notifications,
+            changes: self.changes,
+            bad: self.bad,
+            first: self.first.as_ref().map(variant_to_json),
+            last: self.last.as_ref().map(variant_to_json),
+            min: self.min,
+            max: self.max,
+            avg: (self.numeric > 0).then(|| self.sum / self.numeric as f64),
+            per_minute: per_minute(self.notifications, elapsed),
+        }
+    }
+}
+
+/// Statistics of one node over a monitor run
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NodeSummary {
+    /// Position of the node in the archive header's node list
+    pub node: usize,
+    pub notifications: u64,
+    /// Notifications whose value differs from the one before, 0 for signals that never changed
+    pub changes: u64,
+    /// Notifications with a Bad status, or Uncertain with `--strict`
+    pub bad: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last: Option<serde_json::Value>,
+    /// Set for numeric values only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg: Option<f64>,
+    /// Notifications per minute over the whole run
+    pub per_minute: f64,
+}
+
+fn per_minute(count: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        0.0
+    } else {
+        count as f64 * 60.0 / elapsed.as_secs_f64()
+    }
+}
+
+#[derive(Tabled)]
+pub struct SummaryRow {
+    #[tabled(rename = "Node")]
+    pub name: String,
+    #[tabled(rename = "Updates")]
+    pub notifications: String,
+    #[tabled(rename = "Changes")]
+    pub changes: String,
+    #[tabled(rename = "First")]
+    pub first: String,
+    #[tabled(rename = "Last")]
+    pub last: String,
+    #[tabled(rename = "Min")]
+    pub min: String,
+    #[tabled(rename = "Max")]
+    pub max: String,
+    #[tabled(rename = "Avg")]
+    pub avg: String,
+    #[tabled(rename = "Bad")]
+    pub bad: String,
+    #[tabled(rename = "Rate")]
+    pub rate: String,
+}
+
+impl SummaryRow {
+    /// A row of `summary`, with `first` and `last` as the caller shows values
+    pub fn new(name: String, summary: &NodeSummary, first: Option<String>, last: Option<String>) -> Self {
+        let none = || "—".dimmed().to_string();
+        let number = |value: Option<f64>| value.map_or_else(none, |value| format!("{:.6}", value).trim_end_matches('0').trim_end_matches('.').to_string());
+        let value = |value: Option<String>| value.map_or_else(none, |value| truncate_string(&value, VALUE_WIDTH));
+        SummaryRow {
+            name,
+            notifications: summary.notifications.to_string(),
+            changes: if summary.changes == 0 { "0".dimmed().to_string() } else { summary.changes.to_string() },
+            first: value(first),
+            last: value(last),
+            min: number(summary.min),
+            max: number(summary.max),
+            avg: number(summary.avg),
+            bad: if summary.bad == 0 { "0".to_string() } else { summary.bad.to_string().yellow().to_string() },
+            rate: format!("{:.1}/min", summary.per_minute),
+        }
+    }
+}
+
+/// Print the summary table followed by the nodes that never changed, `names` holding the name
+/// of every row
+pub fn print_summary(rows: Vec<SummaryRow>, summaries: &[NodeSummary], names: &[String]) {
+    println!("\n{}", "📈 Summary".bright_cyan().bold());
+    println!("{}", Table::new(rows));
+    let silent: Vec<&str> = summaries
+        .iter()
+        .zip(names)
+        .filter(|(summary, _)| summary.changes == 0)
+        .map(|(_, name)| name.as_str())
+        .collect();
+    let never_sent = summaries.iter().filter(|summary| summary.notifications == 0).count();
+    if !silent.is_empty() {
+        println!("⏸️  {} node(s) never changed: {}", silent.len().to_string().bright_white(), silent.join(", ").dimmed());
+    }
+    if never_sent > 0 {
+        println!("⚠️  {} node(s) sent no value at all", never_sent.to_string().yellow());
+    }
+}
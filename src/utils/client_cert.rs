@@ -0,0 +1,111 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use colored::*;
+use opcua::crypto::{CertificateStore, X509Data, X509};
+use opcua::types::{ApplicationDescription, ApplicationType, LocalizedText};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name and URI the certificate is issued to, the ones the client sessions announce
+const APPLICATION_NAME: &str = "OPC-UA Walker";
+const APPLICATION_URI: &str = "urn:opcua-walker";
+
+/// Days the certificate is valid, long enough that a server trusting it keeps doing so
+const VALIDITY_DAYS: u32 = 5 * 365;
+
+/// The PKI directory of `--auto-cert` without `--pki-dir`: below the user's data directory, so
+/// every run uses the same keypair whatever the working directory
+pub fn default_pki_dir() -> PathBuf {
+    let data_dir = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")));
+    match data_dir {
+        Some(dir) => dir.join("opcua-walker").join("pki"),
+        None => PathBuf::from("pki"),
+    }
+}
+
+/// The tool's own keypair for secure channels, laid out like the PKI directory of the client
+/// stack: `own/cert.der` and `private/private.pem`
+pub struct ClientKeypair {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    certificate: X509,
+    /// Created by this run rather than found from an earlier one
+    created: bool,
+}
+
+impl ClientKeypair {
+    /// Reuse the keypair in `pki_dir`, creating it on first use. Servers trust a certificate once,
+    /// so an existing one is never replaced.
+    pub fn load_or_create(pki_dir: &Path) -> Result<Self> {
+        let cert_path = pki_dir.join("own").join("cert.der");
+        let key_path = pki_dir.join("private").join("private.pem");
+
+        if cert_path.exists() || key_path.exists() {
+            if !key_path.exists() {
+                return Err(anyhow!("{} has no private key {}, remove the certificate to create a new keypair", pki_dir.display(), key_path.display()));
+            }
+            let certificate = CertificateStore::read_cert(&cert_path).map_err(|e| anyhow!("{}", e))?;
+            if let Err(status) = certificate.is_time_valid(&Utc::now()) {
+                return Err(anyhow!(
+                    "The client certificate {} is no longer valid ({}), remove {} to create a new one and trust that on the server",
+                    cert_path.display(), status, pki_dir.display()
+                ));
+            }
+            return Ok(ClientKeypair { cert_path, key_path, certificate, created: false });
+        }
+
+        // The private key must not be readable by others, not even while it is written
+        let private_dir = pki_dir.join("private");
+        fs::create_dir_all(&private_dir).map_err(|e| anyhow!("Cannot create {}: {}", private_dir.display(), e))?;
+        restrict_to_owner(&private_dir, 0o700)?;
+
+        let application = ApplicationDescription {
+            application_uri: APPLICATION_URI.into(),
+            application_name: LocalizedText::from(APPLICATION_NAME),
+            application_type: ApplicationType::Client,
+            ..Default::default()
+        };
+        let mut x509_data = X509Data::from(application);
+        x509_data.certificate_duration_days = VALIDITY_DAYS;
+        let (certificate, _) = CertificateStore::create_certificate_and_key(&x509_data, false, &cert_path, &key_path)
+            .map_err(|e| anyhow!("Failed to create a client certificate in {}: {}", pki_dir.display(), e))?;
+        restrict_to_owner(&key_path, 0o600)?;
+
+        Ok(ClientKeypair { cert_path, key_path, certificate, created: true })
+    }
+
+    pub fn thumbprint(&self) -> String {
+        self.certificate.thumbprint().as_hex_string()
+    }
+
+    /// Tell which certificate the channel uses; a new one comes with the steps to trust it
+    pub fn print_notice(&self) {
+        if !self.created {
+            eprintln!("🔑 Using the client certificate {} (thumbprint {})", self.cert_path.display(), self.thumbprint().dimmed());
+            return;
+        }
+        eprintln!("🔑 {}", "Created a client certificate for the secure channel".bright_white().bold());
+        eprintln!("   Certificate: {}", self.cert_path.display());
+        eprintln!("   Thumbprint:  {}", self.thumbprint().bright_cyan());
+        eprintln!("   Trust it on the server once: most servers put an unknown certificate in their rejected");
+        eprintln!("   certificates folder on the first attempt, move it from there to the trusted ones or import");
+        eprintln!("   the file above. Later runs reuse this certificate.");
+    }
+}
+
+/// Take away the access of group and others. Elsewhere than on Unix the private key is left to
+/// the permissions of the directory it is in.
+#[cfg(unix)]
+fn restrict_to_owner(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn restrict_to_owner(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use anyhow::Result;
+use colored::*;
+use opcua::types::*;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+use crate::commands::export::attribute_request;
+use crate::commands::refs::browse_references;
+use crate::session::ServiceSession;
+use crate::utils::formatter::{localized_text, sanitize_text};
+
+/// Supertypes followed before a DataType is taken for no enumeration
+const MAX_SUPERTYPE_DEPTH: usize = 16;
+
+/// Symbolic names of an enumeration DataType by value
+pub type EnumNames = BTreeMap<i64, String>;
+
+/// Enumeration names of the DataTypes looked up so far, `None` for DataTypes that are no
+/// enumeration or define no names
+#[derive(Default)]
+pub struct EnumCache {
+    types: Mutex<HashMap<NodeId, Option<Arc<EnumNames>>>>,
+}
+
+/// An integer value of an enumeration DataType
+pub struct EnumValue {
+    pub value: i64,
+    /// `None` when the enumeration defines no name for the value
+    pub name: Option<String>,
+}
+
+impl EnumValue {
+    pub fn new(names: &EnumNames, variant: &Variant) -> Option<Self> {
+        let value = integer_value(variant)?;
+        Some(EnumValue {
+            value,
+            name: names.get(&value).cloned(),
+        })
+    }
+
+    /// `Starting (3)`, or the number with a warning marker when it is out of range
+    pub fn format(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} ({})", sanitize_text(name), self.value),
+            None => format!("{} {}", self.value, "⚠️".yellow()),
+        }
+    }
+
+    /// The same without colors, for line-oriented output
+    pub fn format_plain(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} ({})", sanitize_text(name), self.value),
+            None => self.value.to_string(),
+        }
+    }
+}
+
+/// Enumeration values travel as Int32, some servers send other integer types
+pub fn integer_value(variant: &Variant) -> Option<i64> {
+    match variant {
+        Variant::SByte(v) => Some(*v as i64),
+        Variant::Byte(v) => Some(*v as i64),
+        Variant::Int16(v) => Some(*v as i64),
+        Variant::UInt16(v) => Some(*v as i64),
+        Variant::Int32(v) => Some(*v as i64),
+        Variant::UInt32(v) => Some(*v as i64),
+        Variant::Int64(v) => Some(*v),
+        Variant::UInt64(v) => i64::try_from(*v).ok(),
+        _ => None,
+    }
+}
+
+/// The value names of `data_type` when it is an enumeration, looked up once per session.
+///
+/// Lookup failures are logged and treated like DataTypes without names, values then show as numbers.
+pub async fn enum_names(session: &ServiceSession, data_type: &NodeId) -> Option<Arc<EnumNames>> {
+    // The built-in types of namespace 0 (Boolean to DiagnosticInfo and the abstract number types) are
+    // never enumerations
+    if data_type.namespace == 0 && matches!(data_type.identifier, Identifier::Numeric(1..=28)) {
+        return None;
+    }
+
+    if let Some(cached) = session.enums().types.lock().unwrap().get(data_type) {
+        return cached.clone();
+    }
+
+    let names = match lookup(session, data_type).await {
+        Ok(names) => names.map(Arc::new),
+        Err(e) => {
+            debug!("Enumeration lookup of {} failed: {}", data_type, e);
+            None
+        }
+    };
+    session.enums().types.lock().unwrap().insert(data_type.clone(), names.clone());
+    names
+}
+
+/// The enumeration value of `variant` for a node of `data_type`
+pub async fn enum_value(session: &ServiceSession, data_type: &NodeId, variant: &Variant) -> Option<EnumValue> {
+    integer_value(variant)?;
+    let names = enum_names(session, data_type).await?;
+    EnumValue::new(&names, variant)
+}
+
+/// The DataType attribute of each node, `None` where it cannot be read
+pub async fn read_data_types(session: &ServiceSession, node_ids: &[NodeId]) -> Result<Vec<Option<NodeId>>> {
+    if node_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let requests: Vec<ReadValueId> = node_ids
+        .iter()
+        .map(|node_id| attribute_request(node_id, AttributeId::DataType))
+        .collect();
+    Ok(session.read(&requests, TimestampsToReturn::Neither, 0.0).await?
+        .into_iter()
+        .map(|dv| match dv.value {
+            Some(Variant::NodeId(data_type)) => Some(*data_type),
+            _ => None,
+        })
+        .collect())
+}
+
+async fn lookup(session: &ServiceSession, data_type: &NodeId) -> Result<Option<EnumNames>> {
+    if !is_enumeration(session, data_type).await? {
+        return Ok(None);
+    }
+
+    let properties = browse_references(session, data_type, BrowseDirection::Forward, ReferenceTypeId::HasProperty).await?;
+    for property in &properties {
+        let name = property.browse_name.name.as_ref();
+        if name != "EnumStrings" && name != "EnumValues" {
+            continue;
+        }
+        let value = session.read(&[ReadValueId::from(&property.node_id.node_id)], TimestampsToReturn::Neither, 0.0).await?
+            .into_iter()
+            .next()
+            .and_then(|dv| dv.value);
+        if let Some(Variant::Array(array)) = value {
+            return Ok(Some(names_from_property(&array.values)));
+        }
+    }
+
+    // Servers since OPC UA 1.04 may describe the values only in the DataTypeDefinition
+    let definition = session.read(&[attribute_request(data_type, AttributeId::DataTypeDefinition)], TimestampsToReturn::Neither, 0.0).await?
+        .into_iter()
+        .next()
+        .and_then(|dv| dv.value);
+    if let Some(Variant::ExtensionObject(definition)) = definition
+        && let Some(definition) = definition.inner_as::<EnumDefinition>()
+    {
+        let names = definition.fields
+            .iter()
+            .flatten()
+            .map(|field| (field.value, field.name.to_string()))
+            .collect();
+        return Ok(Some(names));
+    }
+    Ok(None)
+}
+
+/// EnumStrings hold the names of the values 0, 1, 2, ..., EnumValues pairs of value and name
+fn names_from_property(values: &[Variant]) -> EnumNames {
+    values
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, value)| match value {
+            Variant::LocalizedText(text) => Some((idx as i64, localized_text(text).to_string())),
+            Variant::ExtensionObject(object) => object
+                .inner_as::<EnumValueType>()
+                .map(|value| (value.value, localized_text(&value.display_name).to_string())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the supertype chain of `data_type` reaches Enumeration
+async fn is_enumeration(session: &ServiceSession, data_type: &NodeId) -> Result<bool> {
+    let enumeration: NodeId = DataTypeId::Enumeration.into();
+    let mut current = data_type.clone();
+    for _ in 0..MAX_SUPERTYPE_DEPTH {
+        if current == enumeration {
+            return Ok(true);
+        }
+        let supertypes = browse_references(session, &current, BrowseDirection::Inverse, ReferenceTypeId::HasSubtype).await?;
+        match supertypes.into_iter().next() {
+            Some(supertype) => current = supertype.node_id.node_id,
+            None => return Ok(false),
+        }
+    }
+    Ok(false)
+}
@@ -0,0 +1,275 @@
+use anyhow::{anyhow, Result};
+
+/// Upper bound for the node IDs the arguments of one command may expand to
+pub const MAX_EXPANSIONS: usize = 10_000;
+
+/// A piece of a node ID pattern
+enum Part {
+    Literal(String),
+    /// `{1..12}`, zero padded to `width` digits when a bound is written with leading zeros
+    Range { start: i64, end: i64, width: usize },
+    /// `{a,b,c}`, each alternative again a pattern
+    Alternatives(Vec<Vec<Part>>),
+}
+
+/// Expand every argument, keeping the order of arguments and expansions
+pub fn expand_all(patterns: &[String]) -> Result<Vec<String>> {
+    let mut expanded = Vec::new();
+    for pattern in patterns {
+        expanded.extend(expand_pattern(pattern)?);
+        if expanded.len() > MAX_EXPANSIONS {
+            return Err(anyhow!("Node ID patterns expand to more than {} node IDs", MAX_EXPANSIONS));
+        }
+    }
+    Ok(expanded)
+}
+
+/// Expand shell-style braces in a node ID, e.g. `ns=3;s=Axis{1..12}.Position` or
+/// `ns=2;s={Left,Right}Motor`. Groups may be nested, `\{`, `\}`, `\,` and `\\` are literal.
+/// Braces without a range or comma inside are kept as they are, and so is a quoted string
+/// identifier, `s="…"`.
+pub fn expand_pattern(pattern: &str) -> Result<Vec<String>> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut pos = 0;
+    let mut alternatives = parse_sequence(&chars, &mut pos, None)
+        .map_err(|e| anyhow!("Invalid node ID pattern '{}': {}", pattern, e))?;
+    let parts = alternatives.pop().unwrap_or_default();
+    expand_parts(&parts).map_err(|e| anyhow!("Invalid node ID pattern '{}': {}", pattern, e))
+}
+
+/// Parse up to the end of the input or, inside the group opened at `open`, up to its
+/// closing brace, splitting into alternatives at commas of this level
+fn parse_sequence(chars: &[char], pos: &mut usize, open: Option<usize>) -> Result<Vec<Vec<Part>>> {
+    let in_group = open.is_some();
+    let mut alternatives = vec![Vec::new()];
+    let mut literal = String::new();
+
+    fn flush(literal: &mut String, parts: &mut Vec<Part>) {
+        if !literal.is_empty() {
+            parts.push(Part::Literal(std::mem::take(literal)));
+        }
+    }
+
+    while *pos < chars.len() {
+        let current = alternatives.last_mut().expect("at least one alternative");
+        match chars[*pos] {
+            '\\' if *pos + 1 < chars.len() => {
+                literal.push(chars[*pos + 1]);
+                *pos += 2;
+                continue;
+            }
+            '"' if chars[..*pos].ends_with(&['s', '=']) => {
+                let end = quote_end(chars, *pos);
+                literal.extend(&chars[*pos..end]);
+                *pos = end;
+                continue;
+            }
+            '{' => {
+                flush(&mut literal, current);
+                let open = *pos;
+                *pos += 1;
+                current.extend(parse_group(chars, pos, open)?);
+                continue;
+            }
+            '}' if in_group => {
+                flush(&mut literal, current);
+                return Ok(alternatives);
+            }
+            '}' => return Err(anyhow!("unmatched '}}' at position {} (escape literal braces as \\}})", *pos + 1)),
+            ',' if in_group => {
+                flush(&mut literal, current);
+                alternatives.push(Vec::new());
+            }
+            c => literal.push(c),
+        }
+        *pos += 1;
+    }
+
+    if let Some(open) = open {
+        return Err(anyhow!("unmatched '{{' at position {} (escape literal braces as \\{{)", open + 1));
+    }
+    flush(&mut literal, alternatives.last_mut().expect("at least one alternative"));
+    Ok(alternatives)
+}
+
+/// Position after the quote closing the one at `open`, escaped quotes skipped, or the end
+fn quote_end(chars: &[char], open: usize) -> usize {
+    let mut pos = open + 1;
+    while pos < chars.len() {
+        match chars[pos] {
+            '\\' => pos += 2,
+            '"' => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+    chars.len()
+}
+
+/// Parse a group after its opening brace at `open`, consuming the closing brace
+fn parse_group(chars: &[char], pos: &mut usize, open: usize) -> Result<Vec<Part>> {
+    let close = chars[*pos..].iter().position(|c| matches!(c, '{' | '}' | ',' | '\\')).map(|offset| *pos + offset);
+    if let Some(close) = close.filter(|close| chars[*close] == '}') {
+        let content: String = chars[*pos..close].iter().collect();
+        if let Some(range) = parse_range(&content)? {
+            *pos = close + 1;
+            return Ok(vec![range]);
+        }
+    }
+
+    let mut alternatives = parse_sequence(chars, pos, Some(open))?;
+    *pos += 1;
+
+    if alternatives.len() > 1 {
+        return Ok(vec![Part::Alternatives(alternatives)]);
+    }
+
+    // Neither a range nor a list, the braces belong to the identifier
+    let mut parts = vec![Part::Literal("{".to_string())];
+    parts.append(&mut alternatives[0]);
+    parts.push(Part::Literal("}".to_string()));
+    Ok(parts)
+}
+
+fn parse_range(content: &str) -> Result<Option<Part>> {
+    let Some((start_text, end_text)) = content.split_once("..") else {
+        return Ok(None);
+    };
+    let is_number = |text: &str| {
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    };
+    if !is_number(start_text) || !is_number(end_text) {
+        return Err(anyhow!("range {{{}}} needs two integers", content));
+    }
+
+    let start: i64 = start_text.parse().map_err(|_| anyhow!("range bound {} is too large", start_text))?;
+    let end: i64 = end_text.parse().map_err(|_| anyhow!("range bound {} is too large", end_text))?;
+    if start.abs_diff(end) >= MAX_EXPANSIONS as u64 {
+        return Err(anyhow!("range {{{}}} has more than {} values", content, MAX_EXPANSIONS));
+    }
+
+    let padded = |text: &str| text.trim_start_matches('-').len() > 1 && text.trim_start_matches('-').starts_with('0');
+    let width = if padded(start_text) || padded(end_text) {
+        start_text.len().max(end_text.len())
+    } else {
+        0
+    };
+
+    Ok(Some(Part::Range { start, end, width }))
+}
+
+fn expand_parts(parts: &[Part]) -> Result<Vec<String>> {
+    let mut results = vec![String::new()];
+
+    for part in parts {
+        let options = match part {
+            Part::Literal(text) => vec![text.clone()],
+            Part::Range { start, end, width } => {
+                let values: Vec<i64> = if start <= end {
+                    (*start..=*end).collect()
+                } else {
+                    (*end..=*start).rev().collect()
+                };
+                values.into_iter().map(|value| format!("{:0width$}", value, width = *width)).collect()
+            }
+            Part::Alternatives(alternatives) => {
+                let mut options = Vec::new();
+                for alternative in alternatives {
+                    options.extend(expand_parts(alternative)?);
+                }
+                options
+            }
+        };
+
+        if results.len().saturating_mul(options.len()) > MAX_EXPANSIONS {
+            return Err(anyhow!("expands to more than {} node IDs", MAX_EXPANSIONS));
+        }
+        results = results
+            .iter()
+            .flat_map(|prefix| options.iter().map(move |option| format!("{}{}", prefix, option)))
+            .collect();
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(pattern: &str) -> Vec<String> {
+        expand_pattern(pattern).unwrap()
+    }
+
+    fn error(pattern: &str) -> String {
+        match expand_pattern(pattern) {
+            Ok(expanded) => panic!("'{}' expanded to {} node IDs", pattern, expanded.len()),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn ranges_and_lists() {
+        assert_eq!(expanded("ns=3;s=Axis{1..3}.Position"), ["ns=3;s=Axis1.Position", "ns=3;s=Axis2.Position", "ns=3;s=Axis3.Position"]);
+        assert_eq!(expanded("ns=2;i={1000..1002}"), ["ns=2;i=1000", "ns=2;i=1001", "ns=2;i=1002"]);
+        assert_eq!(expanded("ns=2;s={Left,Right}Motor"), ["ns=2;s=LeftMotor", "ns=2;s=RightMotor"]);
+        assert_eq!(expanded("ns=2;s=Axis{08..10}"), ["ns=2;s=Axis08", "ns=2;s=Axis09", "ns=2;s=Axis10"]);
+        assert_eq!(expanded("ns=2;s=T{-1..1}"), ["ns=2;s=T-1", "ns=2;s=T0", "ns=2;s=T1"]);
+        assert_eq!(expanded("ns=2;s=Plain"), ["ns=2;s=Plain"]);
+    }
+
+    #[test]
+    fn nested_braces() {
+        assert_eq!(
+            expanded("ns=2;s={Line{1..2},Spare}.Speed"),
+            ["ns=2;s=Line1.Speed", "ns=2;s=Line2.Speed", "ns=2;s=Spare.Speed"]
+        );
+        assert_eq!(expanded("ns=2;s={a,{b,c}d}"), ["ns=2;s=a", "ns=2;s=bd", "ns=2;s=cd"]);
+        assert_eq!(
+            expanded("ns=2;s=L{1..2}.{Speed,Torque}"),
+            ["ns=2;s=L1.Speed", "ns=2;s=L1.Torque", "ns=2;s=L2.Speed", "ns=2;s=L2.Torque"]
+        );
+        assert_eq!(expanded("ns=2;s={a,{x}}"), ["ns=2;s=a", "ns=2;s={x}"]);
+    }
+
+    #[test]
+    fn literal_braces() {
+        assert_eq!(expanded("ns=2;s=Cfg{x}"), ["ns=2;s=Cfg{x}"]);
+        assert_eq!(expanded("ns=2;s=Cfg{}"), ["ns=2;s=Cfg{}"]);
+        assert_eq!(expanded("ns=2;s=\\{1..3\\}"), ["ns=2;s={1..3}"]);
+        assert_eq!(expanded("ns=2;s=a\\,b{1..2}"), ["ns=2;s=a,b1", "ns=2;s=a,b2"]);
+        assert_eq!(expanded("ns=2;s=\"{a,b}\""), ["ns=2;s=\"{a,b}\""]);
+    }
+
+    #[test]
+    fn unbalanced_braces() {
+        assert!(error("ns=3;s=Axis{1..3").contains("unmatched '{' at position 12"));
+        assert!(error("ns=3;s=Axis1..3}").contains("unmatched '}' at position 16"));
+        assert!(error("ns=2;s={a,{b,c}").contains("unmatched '{' at position 8"));
+        assert!(error("ns=2;s={a,b}}").contains("unmatched '}'"));
+    }
+
+    #[test]
+    fn reversed_and_empty_ranges() {
+        // A reversed range counts down, a range of one bound is that value
+        assert_eq!(expanded("ns=2;i={3..1}"), ["ns=2;i=3", "ns=2;i=2", "ns=2;i=1"]);
+        assert_eq!(expanded("ns=2;i={5..5}"), ["ns=2;i=5"]);
+        assert!(error("ns=2;i={..}").contains("range {..} needs two integers"));
+        assert!(error("ns=2;i={1..}").contains("needs two integers"));
+        assert!(error("ns=2;i={..3}").contains("needs two integers"));
+        assert!(error("ns=2;i={a..c}").contains("needs two integers"));
+        assert!(error("ns=2;i={1..99999999999999999999}").contains("is too large"));
+    }
+
+    #[test]
+    fn expansion_count_cap() {
+        assert_eq!(expanded("ns=2;i={1..10000}").len(), MAX_EXPANSIONS);
+        assert!(error("ns=2;i={0..10000}").contains("has more than 10000 values"));
+        assert!(error("ns=2;s=A{1..100}B{1..101}").contains("expands to more than 10000 node IDs"));
+
+        let patterns = vec!["ns=2;i={1..6000}".to_string(), "ns=3;i={1..6000}".to_string()];
+        assert!(expand_all(&patterns).unwrap_err().to_string().contains("more than 10000 node IDs"));
+        let patterns = vec!["ns=2;i={1..5000}".to_string(), "ns=3;i={1..5000}".to_string()];
+        assert_eq!(expand_all(&patterns).unwrap().len(), MAX_EXPANSIONS);
+    }
+}
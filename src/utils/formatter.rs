@@ -1,45 +1,271 @@
 use colored::*;
 use opcua::types::*;
 use base64::prelude::*;
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::types::Radix;
+
+/// Strings of the server are printed unchanged, set by `--raw-strings` and for JSON output
+static RAW_STRINGS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_raw_strings(raw: bool) {
+    RAW_STRINGS.store(raw, Ordering::Relaxed);
+}
+
+/// Uncertain statuses count as failures, set by `--strict`
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_strict(strict: bool) {
+    STRICT.store(strict, Ordering::Relaxed);
+}
+
+pub fn is_strict() -> bool {
+    STRICT.load(Ordering::Relaxed)
+}
+
+/// Radix of integer values, set by `--radix`
+static RADIX: AtomicU8 = AtomicU8::new(Radix::Dec as u8);
+
+pub fn set_radix(radix: Radix) {
+    RADIX.store(radix as u8, Ordering::Relaxed);
+}
+
+pub fn radix() -> Radix {
+    match RADIX.load(Ordering::Relaxed) {
+        value if value == Radix::Hex as u8 => Radix::Hex,
+        value if value == Radix::Bin as u8 => Radix::Bin,
+        _ => Radix::Dec,
+    }
+}
+
+/// An integer in `radix`. `bits` is its two's complement pattern in the `width` bits of its type,
+/// which hex and bin show in full so that the position of each bit stays put: an Int16 of -2 is
+/// `0xFFFE`, a Byte of 5 `0b0000_0101`.
+fn format_integer(value: impl Display, bits: u64, width: usize, radix: Radix) -> String {
+    match radix {
+        Radix::Dec => value.to_string(),
+        Radix::Hex => format!("0x{:0digits$X}", bits, digits = width / 4),
+        Radix::Bin => {
+            let digits = format!("{:0width$b}", bits, width = width);
+            let nibbles: Vec<&str> = (0..width).step_by(4).map(|start| &digits[start..start + 4]).collect();
+            format!("0b{}", nibbles.join("_"))
+        }
+    }
+}
+
+/// Severity bits of a status code, as given in JSON output for tooling with its own policy
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Good,
+    Uncertain,
+    Bad,
+}
+
+impl Severity {
+    pub fn of(status: &StatusCode) -> Self {
+        if status.is_good() {
+            Severity::Good
+        } else if status.is_uncertain() {
+            Severity::Uncertain
+        } else {
+            Severity::Bad
+        }
+    }
+
+    /// Bad statuses always fail, Uncertain ones only with `--strict`
+    pub fn is_failure(self) -> bool {
+        self.is_failure_in(is_strict())
+    }
+
+    fn is_failure_in(self, strict: bool) -> bool {
+        match self {
+            Severity::Good => false,
+            Severity::Uncertain => strict,
+            Severity::Bad => true,
+        }
+    }
+}
+
+/// Escape control characters as `\n`, `\t`, `\x1b` and the like, so text from the server cannot
+/// recolour the terminal, move the cursor or fake table rows. Bidirectional overrides are escaped too.
+pub fn sanitize_text(text: &str) -> Cow<'_, str> {
+    escape_text(text, RAW_STRINGS.load(Ordering::Relaxed))
+}
+
+/// [`sanitize_text`], leaving the text unchanged when `raw`
+fn escape_text(text: &str, raw: bool) -> Cow<'_, str> {
+    let unsafe_char = |c: char| c.is_control() || matches!(c, '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}');
+    if raw || !text.chars().any(unsafe_char) {
+        return Cow::Borrowed(text);
+    }
+    
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if unsafe_char(c) && (c as u32) < 0x100 => {
+                let _ = write!(escaped, "\\x{:02x}", c as u32);
+            }
+            c if unsafe_char(c) => {
+                let _ = write!(escaped, "\\u{{{:x}}}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
 
 pub fn format_node_id(node_id: &NodeId) -> String {
-    match &node_id.identifier {
-        Identifier::Numeric(id) => format!("ns={};i={}", node_id.namespace, id),
-        Identifier::String(id) => format!("ns={};s={}", node_id.namespace, id),
-        Identifier::Guid(id) => format!("ns={};g={}", node_id.namespace, id),
-        Identifier::ByteString(id) => format!("ns={};b={}", node_id.namespace, 
-            base64::prelude::BASE64_STANDARD.encode(id)),
+    format!("ns={};{}", node_id.namespace, format_identifier(&node_id.identifier))
+}
+
+/// The identifier part of a node ID, `i=…`, `s=…`, `g=…` or `b=…`. String identifiers are printed
+/// as they are, `;` and `=` included, and only quoted when [`parse_node_id_text`] or the brace
+/// expansion of node arguments would not read them back otherwise.
+pub fn format_identifier(identifier: &Identifier) -> String {
+    match identifier {
+        Identifier::Numeric(id) => format!("i={}", id),
+        Identifier::String(id) => {
+            let id = id.as_ref();
+            if id.is_empty() || id.trim() != id || id.starts_with('"') || id.contains(['{', '}', '\\']) {
+                format!("s=\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+            } else {
+                format!("s={}", id)
+            }
+        }
+        Identifier::Guid(id) => format!("g={}", id),
+        Identifier::ByteString(id) => format!("b={}", BASE64_STANDARD.encode(id)),
+    }
+}
+
+/// Parse a node ID as [`format_node_id`] prints it, `ns=<index>;<identifier>` or just the
+/// identifier for namespace 0. Everything after `s=` is the string identifier verbatim, `;`, `=`
+/// and spaces included. Quoted as `s="…"` it may also be empty or start and end with spaces, with
+/// `\"` and `\\` for quotes and backslashes; after `s="` only one whole quoted string is accepted.
+pub fn parse_node_id_text(text: &str) -> Option<NodeId> {
+    let (namespace, identifier) = match text.strip_prefix("ns=") {
+        Some(rest) => {
+            let (namespace, identifier) = rest.split_once(';')?;
+            if namespace.is_empty() || !namespace.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            (namespace.parse::<u16>().ok()?, identifier)
+        }
+        None => (0, text),
+    };
+    let identifier = match identifier.strip_prefix("s=") {
+        Some(value) if value.starts_with('"') => Identifier::from(unquote(value)?),
+        Some("") => return None,
+        Some(value) => Identifier::from(value),
+        None => identifier.parse::<Identifier>().ok()?,
+    };
+    Some(NodeId { namespace, identifier })
+}
+
+/// The text inside `"…"` with its escapes resolved, `None` unless `text` is exactly one quoted
+/// string
+fn unquote(text: &str) -> Option<String> {
+    let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            '"' => return None,
+            c => value.push(c),
+        }
     }
+    Some(value)
+}
+
+/// The text of a LocalizedText without its locale, empty when the server sent none. Matching and
+/// display go through this so that a locale never ends up in a name.
+pub fn localized_text(text: &LocalizedText) -> &str {
+    text.text.as_ref()
+}
+
+/// The locale of a LocalizedText, `None` when the server sent none
+pub fn text_locale(text: &LocalizedText) -> Option<&str> {
+    Some(text.locale.as_ref()).filter(|locale| !locale.is_empty())
+}
+
+/// JSON form of a LocalizedText, `{"locale": null, "text": ""}` when both are empty
+pub fn localized_text_json(text: &LocalizedText) -> serde_json::Value {
+    serde_json::json!({
+        "locale": text_locale(text),
+        "text": localized_text(text),
+    })
+}
+
+/// The name of a referenced node. Some servers send references without a DisplayName, those fall
+/// back to the BrowseName and then to the node ID identifier; the flag tells whether they did.
+pub fn reference_name(reference: &ReferenceDescription) -> (String, bool) {
+    if !localized_text(&reference.display_name).is_empty() {
+        return (localized_text(&reference.display_name).to_string(), false);
+    }
+    if !reference.browse_name.name.is_empty() {
+        return (reference.browse_name.name.to_string(), true);
+    }
+    let node_id = &reference.node_id.node_id;
+    let identifier = match &node_id.identifier {
+        Identifier::Numeric(id) => id.to_string(),
+        Identifier::String(id) => id.to_string(),
+        Identifier::Guid(id) => id.to_string(),
+        Identifier::ByteString(_) => format_node_id(node_id),
+    };
+    (identifier, true)
 }
 
 pub fn format_variant(variant: &Variant) -> String {
+    format_variant_in(variant, Radix::Dec)
+}
+
+/// A Value attribute as read, monitor and browse show it, integers in the radix of `--radix`.
+/// Other integers such as ValueRank stay decimal.
+pub fn format_value_variant(variant: &Variant) -> String {
+    format_variant_in(variant, radix())
+}
+
+/// [`format_variant`] with integers in `radix`
+pub fn format_variant_in(variant: &Variant, radix: Radix) -> String {
     match variant {
         Variant::Empty => "Empty".dimmed().to_string(),
         Variant::Boolean(val) => format!("{}", val),
-        Variant::SByte(val) => format!("{}", val),
-        Variant::Byte(val) => format!("{}", val),
-        Variant::Int16(val) => format!("{}", val),
-        Variant::UInt16(val) => format!("{}", val),
-        Variant::Int32(val) => format!("{}", val),
-        Variant::UInt32(val) => format!("{}", val),
-        Variant::Int64(val) => format!("{}", val),
-        Variant::UInt64(val) => format!("{}", val),
+        Variant::SByte(val) => format_integer(val, *val as u8 as u64, 8, radix),
+        Variant::Byte(val) => format_integer(val, *val as u64, 8, radix),
+        Variant::Int16(val) => format_integer(val, *val as u16 as u64, 16, radix),
+        Variant::UInt16(val) => format_integer(val, *val as u64, 16, radix),
+        Variant::Int32(val) => format_integer(val, *val as u32 as u64, 32, radix),
+        Variant::UInt32(val) => format_integer(val, *val as u64, 32, radix),
+        Variant::Int64(val) => format_integer(val, *val as u64, 64, radix),
+        Variant::UInt64(val) => format_integer(val, *val, 64, radix),
         Variant::Float(val) => format!("{}", val),
         Variant::Double(val) => format!("{}", val),
-        Variant::String(val) => format!("\"{}\"", val.as_ref()),
+        Variant::String(val) => format!("\"{}\"", sanitize_text(val.as_ref())),
         Variant::DateTime(val) => format!("{}", val.as_chrono().format("%Y-%m-%d %H:%M:%S")),
         Variant::Guid(val) => format!("{}", val),
         Variant::ByteString(val) => format!("ByteString({} bytes)", val.as_ref().len()),
-        Variant::XmlElement(val) => format!("XmlElement({})", val),
+        Variant::XmlElement(val) => format!("XmlElement({})", sanitize_text(&val.to_string())),
         Variant::NodeId(val) => format_node_id(val),
         Variant::ExpandedNodeId(val) => format!("{}", val),
-        Variant::StatusCode(val) => format!("StatusCode({})", val),
-        Variant::QualifiedName(val) => format!("{}:{}", val.namespace_index, val.name.as_ref()),
-        Variant::LocalizedText(val) => format!("\"{}\"", val.text.as_ref()),
+        Variant::StatusCode(val) => format_status_code(val),
+        Variant::DiagnosticInfo(val) => format_diagnostic_value(val),
+        Variant::QualifiedName(val) => format!("{}:{}", val.namespace_index, sanitize_text(val.name.as_ref())),
+        Variant::LocalizedText(val) => format!("\"{}\"", sanitize_text(localized_text(val))),
+        Variant::Array(array) if array.dimensions.as_ref().is_some_and(|dims| dims.len() > 1) => {
+            format!("[{} items]", format_dimensions(array.dimensions.as_deref().unwrap_or_default()))
+        }
+        Variant::Array(array) if let Some(statuses) = format_status_list(&array.values) => statuses,
         Variant::Array(array) => {
             if array.values.len() <= 3 {
                 let items: Vec<String> = array.values.iter()
-                    .map(|v| format_variant(v))
+                    .map(|value| format_variant_in(value, radix))
                     .collect();
                 format!("[{}]", items.join(", "))
             } else {
@@ -50,9 +276,273 @@ pub fn format_variant(variant: &Variant) -> String {
     }
 }
 
+/// A status name coloured by its severity, without the icon of [`format_status_code`]
+fn format_status_name(status: &StatusCode) -> String {
+    let name = status.to_string();
+    let severity = Severity::of(status);
+    match severity {
+        Severity::Good => name.green().to_string(),
+        _ if severity.is_failure() => name.red().to_string(),
+        _ => name.yellow().to_string(),
+    }
+}
+
+/// Arrays of StatusCodes, such as the health words of a module's channels, as a list of names
+/// with runs of the same status counted: `[Good ×6, BadTimeout, Good]`. `None` for other arrays.
+fn format_status_list(values: &[Variant]) -> Option<String> {
+    let statuses: Vec<&StatusCode> = values
+        .iter()
+        .map(|value| match value {
+            Variant::StatusCode(status) => Some(status),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    if statuses.is_empty() {
+        return None;
+    }
+    let items: Vec<String> = statuses
+        .chunk_by(|a, b| a == b)
+        .map(|run| match run.len() {
+            1 => format_status_name(run[0]),
+            count => format!("{} ×{}", format_status_name(run[0]), count),
+        })
+        .collect();
+    Some(format!("[{}]", items.join(", ")))
+}
+
+/// A DiagnosticInfo value on one line. Values carry no string table, so the symbolic ID,
+/// namespace and text show as their indices into the table of the response they came from.
+fn format_diagnostic_value(info: &DiagnosticInfo) -> String {
+    let indices = [
+        ("symbolic ID", info.symbolic_id),
+        ("namespace", info.namespace_uri),
+        ("text", info.localized_text),
+        ("locale", info.locale),
+    ];
+    let mut parts: Vec<String> = indices
+        .iter()
+        .filter_map(|(name, index)| index.map(|index| format!("{} #{}", name, index)))
+        .collect();
+    if let Some(additional) = info.additional_info.as_ref().filter(|s| !s.is_empty()) {
+        parts.push(format!("\"{}\"", sanitize_text(additional.as_ref())));
+    }
+    if let Some(inner_status) = &info.inner_status_code {
+        parts.push(format!("inner {}", format_status_name(inner_status)));
+    }
+    if let Some(inner) = &info.inner_diagnostic_info {
+        parts.push(format!("inner {}", format_diagnostic_value(inner)));
+    }
+    if parts.is_empty() {
+        "DiagnosticInfo(empty)".dimmed().to_string()
+    } else {
+        format!("DiagnosticInfo({})", parts.join(", "))
+    }
+}
+
+/// Rows and columns of a matrix shown when values are not printed in full
+const GRID_PREVIEW_ROWS: usize = 8;
+const GRID_PREVIEW_COLUMNS: usize = 8;
+
+/// Array dimensions as `4x16`
+pub fn format_dimensions(dimensions: &[u32]) -> String {
+    dimensions.iter().map(u32::to_string).collect::<Vec<_>>().join("x")
+}
+
+/// Every element of a one-dimensional array value
+pub fn format_array_full(values: &[Variant]) -> String {
+    if let Some(statuses) = format_status_list(values) {
+        return statuses;
+    }
+    let items: Vec<String> = values.iter().map(format_value_variant).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Render a multi-dimensional array as a shape line followed by one line per row of the last
+/// dimension; rows of arrays with more than two dimensions are prefixed with their outer indices.
+/// Without `full` only the first rows and columns are shown. Dimensions that do not multiply up
+/// to the number of values are reported instead of reshaping.
+pub fn format_array_grid(values: &[Variant], dimensions: &[u32], full: bool) -> String {
+    let shape = format_dimensions(dimensions);
+    let expected = dimensions.iter().try_fold(1usize, |acc, dim| acc.checked_mul(*dim as usize));
+    if expected != Some(values.len()) {
+        return format!("[{} items] {}", values.len(),
+            format!("⚠️  dimensions {} do not match {} values", shape, values.len()).yellow());
+    }
+    let columns = dimensions.last().copied().unwrap_or_default() as usize;
+    if columns == 0 {
+        return format!("[{} items]", shape);
+    }
+    
+    let (max_rows, max_columns) = if full { (usize::MAX, usize::MAX) } else { (GRID_PREVIEW_ROWS, GRID_PREVIEW_COLUMNS) };
+    let cells: Vec<String> = values.iter().map(format_value_variant).collect();
+    let rows: Vec<&[String]> = cells.chunks(columns).collect();
+    let shown_rows = &rows[..rows.len().min(max_rows)];
+    let shown_columns = columns.min(max_columns);
+    let widths: Vec<usize> = (0..shown_columns)
+        .map(|column| shown_rows.iter().map(|row| row[column].chars().count()).max().unwrap_or_default())
+        .collect();
+    
+    let outer = &dimensions[..dimensions.len() - 1];
+    let mut lines = vec![format!("[{} items]", shape).dimmed().to_string()];
+    for (row_idx, row) in shown_rows.iter().enumerate() {
+        let mut line = String::new();
+        if outer.len() > 1 {
+            line.push_str(&format!("{} ", format!("[{}]", outer_indices(row_idx, outer)).dimmed()));
+        }
+        let row_cells: Vec<String> = row[..shown_columns]
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+            .collect();
+        line.push_str(&row_cells.join("  "));
+        if shown_columns < columns {
+            line.push_str(&format!("  … {} more", columns - shown_columns).dimmed().to_string());
+        }
+        lines.push(line);
+    }
+    if shown_rows.len() < rows.len() {
+        lines.push(format!("… {} more rows", rows.len() - shown_rows.len()).dimmed().to_string());
+    }
+    lines.join("\n")
+}
+
+/// Indices of the outer dimensions for a row, e.g. `1,2` for row 6 of a 2x4xN array
+fn outer_indices(row: usize, outer: &[u32]) -> String {
+    let mut remainder = row;
+    let mut indices = vec![0; outer.len()];
+    for (idx, dim) in outer.iter().enumerate().rev() {
+        indices[idx] = remainder % *dim as usize;
+        remainder /= *dim as usize;
+    }
+    indices.iter().map(usize::to_string).collect::<Vec<_>>().join(",")
+}
+
+/// Name of the concrete Variant type as it came over the wire, e.g. `UInt16` or `Float[4]`
+pub fn variant_type_name(variant: &Variant) -> String {
+    match variant {
+        Variant::Empty => "Empty".to_string(),
+        Variant::Array(array) => {
+            let dimensions = match &array.dimensions {
+                Some(dims) if dims.len() > 1 => dims.iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join("x"),
+                _ => array.values.len().to_string(),
+            };
+            format!("{:?}[{}]", array.value_type, dimensions)
+        }
+        _ => variant.scalar_type_id()
+            .map(|type_id| format!("{:?}", type_id))
+            .unwrap_or_else(|| "Unknown".to_string()),
+    }
+}
+
+/// Format a value followed by its concrete type in parentheses
+pub fn format_variant_typed(variant: &Variant) -> String {
+    format!("{} {}", format_value_variant(variant), format!("({})", variant_type_name(variant)).dimmed())
+}
+
+/// A variant as the closest JSON value: numbers, booleans and strings stay typed, arrays
+/// become JSON arrays (flattened for matrices), ByteStrings base64 and other types their text form
+pub fn variant_to_json(variant: &Variant) -> serde_json::Value {
+    use serde_json::Value as JsonValue;
+    match variant {
+        Variant::Empty => JsonValue::Null,
+        Variant::Boolean(val) => JsonValue::from(*val),
+        Variant::SByte(val) => JsonValue::from(*val),
+        Variant::Byte(val) => JsonValue::from(*val),
+        Variant::Int16(val) => JsonValue::from(*val),
+        Variant::UInt16(val) => JsonValue::from(*val),
+        Variant::Int32(val) => JsonValue::from(*val),
+        Variant::UInt32(val) => JsonValue::from(*val),
+        Variant::Int64(val) => JsonValue::from(*val),
+        Variant::UInt64(val) => JsonValue::from(*val),
+        // NaN and infinity have no JSON number and become null
+        Variant::Float(val) => JsonValue::from(*val),
+        Variant::Double(val) => JsonValue::from(*val),
+        Variant::String(val) => JsonValue::from(val.as_ref()),
+        Variant::LocalizedText(val) => localized_text_json(val),
+        Variant::DateTime(val) => JsonValue::from(val.as_chrono().to_rfc3339()),
+        Variant::ByteString(val) => JsonValue::from(BASE64_STANDARD.encode(val.as_ref())),
+        Variant::NodeId(val) => JsonValue::from(format_node_id(val)),
+        Variant::StatusCode(val) => JsonValue::from(val.to_string()),
+        Variant::DiagnosticInfo(val) => diagnostic_info_json(val),
+        Variant::Array(array) => JsonValue::Array(array.values.iter().map(variant_to_json).collect()),
+        _ => JsonValue::from(format_variant(variant)),
+    }
+}
+
+/// A DiagnosticInfo value as an object of the fields it carries, indices into the string table
+/// of the response as numbers
+fn diagnostic_info_json(info: &DiagnosticInfo) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    let indices = [
+        ("symbolic_id", info.symbolic_id),
+        ("namespace_uri", info.namespace_uri),
+        ("localized_text", info.localized_text),
+        ("locale", info.locale),
+    ];
+    for (name, index) in indices {
+        if let Some(index) = index {
+            object.insert(name.to_string(), index.into());
+        }
+    }
+    if let Some(additional) = info.additional_info.as_ref().filter(|s| !s.is_empty()) {
+        object.insert("additional_info".to_string(), additional.as_ref().into());
+    }
+    if let Some(inner_status) = &info.inner_status_code {
+        object.insert("inner_status_code".to_string(), inner_status.to_string().into());
+    }
+    if let Some(inner) = &info.inner_diagnostic_info {
+        object.insert("inner_diagnostic_info".to_string(), diagnostic_info_json(inner));
+    }
+    serde_json::Value::Object(object)
+}
+
+/// Render a DiagnosticInfo as readable lines, resolving indices into the response string table
+pub fn format_diagnostic_info(info: &DiagnosticInfo, string_table: &[UAString]) -> Vec<String> {
+    let lookup = |index: Option<i32>| {
+        index
+            .and_then(|i| usize::try_from(i).ok())
+            .and_then(|i| string_table.get(i))
+            .filter(|s| !s.is_empty())
+            .map(|s| s.as_ref().to_string())
+    };
+    
+    let mut lines = Vec::new();
+    if let Some(symbolic_id) = lookup(info.symbolic_id) {
+        lines.push(format!("Symbolic ID: {}", symbolic_id));
+    }
+    if let Some(namespace) = lookup(info.namespace_uri) {
+        lines.push(format!("Namespace: {}", namespace));
+    }
+    if let Some(text) = lookup(info.localized_text) {
+        match lookup(info.locale) {
+            Some(locale) => lines.push(format!("Text: {} ({})", text, locale)),
+            None => lines.push(format!("Text: {}", text)),
+        }
+    }
+    if let Some(additional) = info.additional_info.as_ref().filter(|s| !s.is_empty()) {
+        lines.push(format!("Additional info: {}", additional.as_ref()));
+    }
+    if let Some(inner_status) = info.inner_status_code {
+        lines.push(format!("Inner status: {}", inner_status));
+    }
+    if let Some(inner) = &info.inner_diagnostic_info {
+        let inner_lines = format_diagnostic_info(inner, string_table);
+        if !inner_lines.is_empty() {
+            lines.push("Inner diagnostics:".to_string());
+            lines.extend(inner_lines.into_iter().map(|line| format!("  {}", line)));
+        }
+    }
+    lines
+}
+
 pub fn format_status_code(status: &StatusCode) -> String {
     if status.is_good() {
         "✅ Good".green().to_string()
+    } else if status.is_uncertain() && is_strict() {
+        format!("❌ Uncertain ({})", status).red().to_string()
     } else if status.is_uncertain() {
         format!("⚠️  Uncertain ({})", status).yellow().to_string()
     } else {
@@ -109,10 +599,533 @@ pub fn format_access_level(access_level: u8) -> String {
     }
 }
 
+const ACCESS_LEVEL_BITS: [(u8, &str); 4] = [(0x01, "Read"), (0x02, "Write"), (0x04, "HistoryRead"), (0x08, "HistoryWrite")];
+
+/// The AccessLevel of a node with the bits its UserAccessLevel withholds from the session's user
+/// marked as locked
+pub fn format_user_access_level(access_level: u8, user_access_level: u8) -> String {
+    let granted = access_level & user_access_level;
+    let mut parts = Vec::new();
+    if granted != 0 {
+        parts.push(format_access_level(granted));
+    }
+    parts.extend(ACCESS_LEVEL_BITS
+        .iter()
+        .filter(|(bit, _)| access_level & bit != 0 && granted & bit == 0)
+        .map(|(_, name)| format!("🔒 {}", name).dimmed().to_string()));
+
+    if parts.is_empty() {
+        "None".dimmed().to_string()
+    } else {
+        parts.join(" | ")
+    }
+}
+
+/// AccessLevelEx: the AccessLevel bits in the low byte plus the extended flags above them
+pub fn format_access_level_ex(access_level: u32) -> String {
+    let mut parts = vec![format_access_level(access_level as u8)];
+    for (bit, name) in [
+        (0x100, "NonatomicRead"),
+        (0x200, "NonatomicWrite"),
+        (0x400, "WriteFullArrayOnly"),
+        (0x800, "NoSubDataTypes"),
+    ] {
+        if access_level & bit != 0 {
+            parts.push(name.magenta().to_string());
+        }
+    }
+    parts.join(" | ")
+}
+
+pub fn format_event_notifier(event_notifier: u8) -> String {
+    let mut parts = Vec::new();
+    
+    if event_notifier & 0x01 != 0 {
+        parts.push("SubscribeToEvents".green().to_string());
+    }
+    if event_notifier & 0x04 != 0 {
+        parts.push("HistoryRead".yellow().to_string());
+    }
+    if event_notifier & 0x08 != 0 {
+        parts.push("HistoryWrite".cyan().to_string());
+    }
+    
+    if parts.is_empty() {
+        "None".dimmed().to_string()
+    } else {
+        parts.join(" | ")
+    }
+}
+
+/// The attributes a WriteMask or UserWriteMask marks as writable
+pub fn format_write_mask(write_mask: u32) -> String {
+    let parts = write_mask_names(write_mask);
+    
+    if parts.is_empty() {
+        "None".dimmed().to_string()
+    } else {
+        parts.join(" | ")
+    }
+}
+
+/// The WriteMask of a node with the attributes its UserWriteMask withholds from the session's
+/// user marked as locked
+pub fn format_user_write_mask(write_mask: u32, user_write_mask: u32) -> String {
+    let granted = write_mask_names(write_mask & user_write_mask);
+    let withheld = write_mask_names(write_mask & !user_write_mask);
+    let parts: Vec<String> = granted
+        .iter()
+        .map(|name| name.to_string())
+        .chain(withheld.iter().map(|name| format!("🔒 {}", name).dimmed().to_string()))
+        .collect();
+
+    if parts.is_empty() {
+        "None".dimmed().to_string()
+    } else {
+        parts.join(" | ")
+    }
+}
+
+/// Names of the attributes whose bits are set in a WriteMask, in bit order
+pub fn write_mask_names(write_mask: u32) -> Vec<&'static str> {
+    const BITS: [&str; 26] = [
+        "AccessLevel", "ArrayDimensions", "BrowseName", "ContainsNoLoops", "DataType",
+        "Description", "DisplayName", "EventNotifier", "Executable", "Historizing",
+        "InverseName", "IsAbstract", "MinimumSamplingInterval", "NodeClass", "NodeId",
+        "Symmetric", "UserAccessLevel", "UserExecutable", "UserWriteMask", "ValueRank",
+        "WriteMask", "ValueForVariableType", "DataTypeDefinition", "RolePermissions",
+        "AccessRestrictions", "AccessLevelEx",
+    ];
+    BITS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| write_mask & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Standard DataTypes by name, other types by node ID
+pub fn format_data_type(data_type: &NodeId) -> String {
+    match &data_type.identifier {
+        Identifier::Numeric(id) if data_type.namespace == 0 => DataTypeId::try_from(*id)
+            .map(|id| format!("{:?}", id))
+            .unwrap_or_else(|_| format_node_id(data_type)),
+        _ => format_node_id(data_type),
+    }
+}
+
+/// Standard reference types by name as read in the direction of the reference, `HasComponent`
+/// forward and `ComponentOf` inverse. `None` for other types and for standard types whose inverse
+/// name is not listed here.
+pub fn standard_reference_type_name(reference_type: &NodeId, is_forward: bool) -> Option<String> {
+    let Identifier::Numeric(id) = reference_type.identifier else {
+        return None;
+    };
+    if reference_type.namespace != 0 {
+        return None;
+    }
+    let id = ReferenceTypeId::try_from(id).ok()?;
+    if is_forward {
+        return Some(format!("{:?}", id));
+    }
+
+    let inverse = match id {
+        // Symmetric types
+        ReferenceTypeId::References | ReferenceTypeId::NonHierarchicalReferences => return Some(format!("{:?}", id)),
+        ReferenceTypeId::HierarchicalReferences => "InverseHierarchicalReferences",
+        ReferenceTypeId::HasChild => "ChildOf",
+        ReferenceTypeId::Organizes => "OrganizedBy",
+        ReferenceTypeId::HasEventSource => "EventSourceOf",
+        ReferenceTypeId::HasModellingRule => "ModellingRuleOf",
+        ReferenceTypeId::HasEncoding => "EncodingOf",
+        ReferenceTypeId::HasDescription => "DescriptionOf",
+        ReferenceTypeId::HasTypeDefinition => "TypeDefinitionOf",
+        ReferenceTypeId::GeneratesEvent => "GeneratedBy",
+        ReferenceTypeId::AlwaysGeneratesEvent => "AlwaysGeneratedBy",
+        ReferenceTypeId::Aggregates => "AggregatedBy",
+        ReferenceTypeId::HasSubtype => "SubtypeOf",
+        ReferenceTypeId::HasProperty => "PropertyOf",
+        ReferenceTypeId::HasComponent => "ComponentOf",
+        ReferenceTypeId::HasNotifier => "NotifierOf",
+        ReferenceTypeId::HasOrderedComponent => "OrderedComponentOf",
+        ReferenceTypeId::FromState => "ToTransition",
+        ReferenceTypeId::ToState => "FromTransition",
+        ReferenceTypeId::HasCause => "MayBeCausedBy",
+        ReferenceTypeId::HasEffect => "MayBeEffectedBy",
+        ReferenceTypeId::HasHistoricalConfiguration => "HistoricalConfigurationOf",
+        ReferenceTypeId::HasSubStateMachine => "SubStateMachineOf",
+        ReferenceTypeId::HasTrueSubState => "IsTrueSubStateOf",
+        ReferenceTypeId::HasFalseSubState => "IsFalseSubStateOf",
+        ReferenceTypeId::HasCondition => "IsConditionOf",
+        ReferenceTypeId::HasInterface => "InterfaceOf",
+        ReferenceTypeId::HasAddIn => "AddInOf",
+        _ => return None,
+    };
+    Some(inverse.to_string())
+}
+
+/// The fields of a StructureDefinition or EnumDefinition, one per line
+pub fn format_data_type_definition(definition: &ExtensionObject) -> String {
+    if let Some(structure) = definition.inner_as::<StructureDefinition>() {
+        let mut text = format!("{:?}", structure.structure_type);
+        if !structure.base_data_type.is_null() {
+            let _ = write!(text, " based on {}", format_data_type(&structure.base_data_type));
+        }
+        for field in structure.fields.as_deref().unwrap_or_default() {
+            let array = if field.value_rank >= 0 { "[]" } else { "" };
+            let optional = if field.is_optional { " (optional)" } else { "" };
+            let _ = write!(text, "\n  {}: {}{}{}", sanitize_text(field.name.as_ref()), format_data_type(&field.data_type), array, optional);
+        }
+        text
+    } else if let Some(enumeration) = definition.inner_as::<EnumDefinition>() {
+        let mut text = "Enumeration".to_string();
+        for field in enumeration.fields.as_deref().unwrap_or_default() {
+            let _ = write!(text, "\n  {} = {}", field.value, sanitize_text(field.name.as_ref()));
+        }
+        text
+    } else {
+        format!("{:?}", definition)
+    }
+}
+
 pub fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
     } else {
         format!("{}...", &s[..max_len.saturating_sub(3)])
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localized_text_with_empty_locale() {
+        for text in [LocalizedText::new("", "Temperatur"), LocalizedText { locale: UAString::null(), text: "Temperatur".into() }] {
+            assert_eq!(localized_text(&text), "Temperatur");
+            assert_eq!(text_locale(&text), None);
+            assert_eq!(localized_text_json(&text), serde_json::json!({"locale": null, "text": "Temperatur"}));
+            assert_eq!(format_variant(&Variant::from(text.clone())), "\"Temperatur\"");
+        }
+    }
+
+    #[test]
+    fn localized_text_with_empty_text() {
+        for text in [LocalizedText::new("de-DE", ""), LocalizedText { locale: "de-DE".into(), text: UAString::null() }] {
+            assert_eq!(localized_text(&text), "");
+            assert_eq!(text_locale(&text), Some("de-DE"));
+            assert_eq!(localized_text_json(&text), serde_json::json!({"locale": "de-DE", "text": ""}));
+            assert_eq!(format_variant(&Variant::from(text.clone())), "\"\"");
+        }
+        let empty = LocalizedText { locale: UAString::null(), text: UAString::null() };
+        assert_eq!(localized_text_json(&empty), serde_json::json!({"locale": null, "text": ""}));
+    }
+
+    #[test]
+    fn localized_text_with_locale_and_text() {
+        let text = LocalizedText::new("de-DE", "Temperatur");
+        assert_eq!(localized_text(&text), "Temperatur");
+        assert_eq!(text_locale(&text), Some("de-DE"));
+        assert_eq!(localized_text_json(&text), serde_json::json!({"locale": "de-DE", "text": "Temperatur"}));
+        assert_eq!(variant_to_json(&Variant::from(text.clone())), localized_text_json(&text));
+        // The locale never leaks into the text shown or matched
+        assert_eq!(format_variant(&Variant::from(text)), "\"Temperatur\"");
+    }
+
+    #[test]
+    fn decimal_integers() {
+        assert_eq!(format_variant(&Variant::Byte(65)), "65");
+        assert_eq!(format_variant(&Variant::Int16(-2)), "-2");
+        assert_eq!(format_variant_in(&Variant::Int64(i64::MIN), Radix::Dec), i64::MIN.to_string());
+    }
+
+    #[test]
+    fn hex_keeps_the_width_of_the_type() {
+        let hex = |variant: Variant| format_variant_in(&variant, Radix::Hex);
+        assert_eq!(hex(Variant::Byte(0x41)), "0x41");
+        assert_eq!(hex(Variant::Byte(5)), "0x05");
+        assert_eq!(hex(Variant::UInt16(0x41)), "0x0041");
+        assert_eq!(hex(Variant::UInt32(0x41)), "0x00000041");
+        assert_eq!(hex(Variant::UInt64(0x41)), "0x0000000000000041");
+        assert_eq!(hex(Variant::UInt64(u64::MAX)), "0xFFFFFFFFFFFFFFFF");
+    }
+
+    /// Negative values are shown as their two's complement in the width of the type, not with a
+    /// minus sign, so that the bits read the same as in the device
+    #[test]
+    fn negative_hex_is_twos_complement() {
+        let hex = |variant: Variant| format_variant_in(&variant, Radix::Hex);
+        assert_eq!(hex(Variant::SByte(-1)), "0xFF");
+        assert_eq!(hex(Variant::SByte(i8::MIN)), "0x80");
+        assert_eq!(hex(Variant::Int16(-2)), "0xFFFE");
+        assert_eq!(hex(Variant::Int32(-1)), "0xFFFFFFFF");
+        assert_eq!(hex(Variant::Int32(i32::MIN)), "0x80000000");
+        assert_eq!(hex(Variant::Int64(-1)), "0xFFFFFFFFFFFFFFFF");
+        assert_eq!(hex(Variant::Int16(0x41)), "0x0041");
+    }
+
+    #[test]
+    fn binary_is_grouped_in_nibbles() {
+        let bin = |variant: Variant| format_variant_in(&variant, Radix::Bin);
+        assert_eq!(bin(Variant::Byte(5)), "0b0000_0101");
+        assert_eq!(bin(Variant::UInt16(0x41)), "0b0000_0000_0100_0001");
+        assert_eq!(bin(Variant::SByte(-2)), "0b1111_1110");
+        assert_eq!(bin(Variant::Int32(-1)), format!("0b{}", ["1111"; 8].join("_")));
+        assert_eq!(bin(Variant::UInt64(1)), format!("0b{}_0001", ["0000"; 15].join("_")));
+    }
+
+    #[test]
+    fn radix_applies_to_integers_only() {
+        assert_eq!(format_variant_in(&Variant::Double(65.0), Radix::Hex), "65");
+        assert_eq!(format_variant_in(&Variant::Boolean(true), Radix::Bin), "true");
+        let array = Variant::from(vec![1u16, 0x41, 0xFFFF]);
+        assert_eq!(format_variant_in(&array, Radix::Hex), "[0x0001, 0x0041, 0xFFFF]");
+        assert_eq!(format_variant(&array), "[1, 65, 65535]");
+    }
+
+    /// Text without the colour escapes, which depend on the environment the tests run in
+    fn plain(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn status_code_values() {
+        assert_eq!(plain(&format_variant(&Variant::StatusCode(StatusCode::Good))), "✅ Good");
+        assert_eq!(plain(&format_variant(&Variant::StatusCode(StatusCode::BadSensorFailure))), "❌ Bad (BadSensorFailure)");
+        assert_eq!(plain(&format_variant(&Variant::StatusCode(StatusCode::UncertainLastUsableValue))), "⚠️  Uncertain (UncertainLastUsableValue)");
+        assert_eq!(variant_to_json(&Variant::StatusCode(StatusCode::BadSensorFailure)), serde_json::json!("BadSensorFailure"));
+    }
+
+    #[test]
+    fn status_code_arrays_count_runs() {
+        let statuses = |codes: &[StatusCode]| {
+            Variant::from(Array::new(VariantScalarTypeId::StatusCode, codes.iter().copied().map(Variant::StatusCode).collect::<Vec<_>>()).unwrap())
+        };
+        let health = statuses(&[StatusCode::Good; 6].iter().copied().chain([StatusCode::BadTimeout, StatusCode::Good]).collect::<Vec<_>>());
+        assert_eq!(plain(&format_variant(&health)), "[Good ×6, BadTimeout, Good]");
+        let mixed = statuses(&[StatusCode::UncertainSensorNotAccurate, StatusCode::BadSensorFailure, StatusCode::BadSensorFailure]);
+        assert_eq!(plain(&format_variant(&mixed)), "[UncertainSensorNotAccurate, BadSensorFailure ×2]");
+        let Variant::Array(array) = &health else { unreachable!() };
+        assert_eq!(plain(&format_array_full(&array.values)), "[Good ×6, BadTimeout, Good]");
+        assert_eq!(variant_to_json(&mixed), serde_json::json!(["UncertainSensorNotAccurate", "BadSensorFailure", "BadSensorFailure"]));
+    }
+
+    #[test]
+    fn other_arrays_are_not_status_lists() {
+        assert_eq!(format_status_list(&[]), None);
+        assert_eq!(format_status_list(&[Variant::StatusCode(StatusCode::Good), Variant::UInt32(0)]), None);
+    }
+
+    #[test]
+    fn diagnostic_info_values() {
+        let info = DiagnosticInfo {
+            symbolic_id: Some(1),
+            namespace_uri: Some(0),
+            localized_text: Some(2),
+            additional_info: Some("Sensor wire broken".into()),
+            inner_status_code: Some(StatusCode::BadSensorFailure),
+            ..Default::default()
+        };
+        assert_eq!(
+            plain(&format_variant(&Variant::from(info.clone()))),
+            "DiagnosticInfo(symbolic ID #1, namespace #0, text #2, \"Sensor wire broken\", inner BadSensorFailure)"
+        );
+        assert_eq!(
+            variant_to_json(&Variant::from(info)),
+            serde_json::json!({
+                "symbolic_id": 1,
+                "namespace_uri": 0,
+                "localized_text": 2,
+                "additional_info": "Sensor wire broken",
+                "inner_status_code": "BadSensorFailure",
+            })
+        );
+        assert_eq!(plain(&format_variant(&Variant::from(DiagnosticInfo::default()))), "DiagnosticInfo(empty)");
+    }
+
+    #[test]
+    fn diagnostic_info_arrays_and_nesting() {
+        let inner = DiagnosticInfo { symbolic_id: Some(3), ..Default::default() };
+        let outer = DiagnosticInfo { locale: Some(4), inner_diagnostic_info: Some(Box::new(inner)), ..Default::default() };
+        assert_eq!(plain(&format_variant(&Variant::from(outer.clone()))), "DiagnosticInfo(locale #4, inner DiagnosticInfo(symbolic ID #3))");
+        let array = Variant::from(Array::new(VariantScalarTypeId::DiagnosticInfo, vec![Variant::from(outer), Variant::from(DiagnosticInfo::default())]).unwrap());
+        assert_eq!(
+            plain(&format_variant(&array)),
+            "[DiagnosticInfo(locale #4, inner DiagnosticInfo(symbolic ID #3)), DiagnosticInfo(empty)]"
+        );
+    }
+
+    #[test]
+    fn diagnostic_info_resolved_against_a_string_table() {
+        let table: Vec<UAString> = ["SensorFailure", "urn:plant", "Sensor defekt", "de"].map(UAString::from).to_vec();
+        let info = DiagnosticInfo {
+            symbolic_id: Some(0),
+            namespace_uri: Some(1),
+            localized_text: Some(2),
+            locale: Some(3),
+            inner_diagnostic_info: Some(Box::new(DiagnosticInfo { symbolic_id: Some(7), ..Default::default() })),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_diagnostic_info(&info, &table),
+            ["Symbolic ID: SensorFailure", "Namespace: urn:plant", "Text: Sensor defekt (de)"]
+        );
+    }
+
+    #[test]
+    fn severity_of_status_codes() {
+        let cases = [
+            (StatusCode::Good, Severity::Good),
+            (StatusCode::GoodClamped, Severity::Good),
+            (StatusCode::GoodLocalOverride, Severity::Good),
+            (StatusCode::Uncertain, Severity::Uncertain),
+            (StatusCode::UncertainLastUsableValue, Severity::Uncertain),
+            (StatusCode::UncertainSensorNotAccurate, Severity::Uncertain),
+            (StatusCode::UncertainSubstituteValue, Severity::Uncertain),
+            (StatusCode::Bad, Severity::Bad),
+            (StatusCode::BadNodeIdUnknown, Severity::Bad),
+            (StatusCode::BadCommunicationError, Severity::Bad),
+            (StatusCode::BadOutOfService, Severity::Bad),
+        ];
+        for (status, severity) in cases {
+            assert_eq!(Severity::of(&status), severity, "{}", status);
+        }
+    }
+
+    #[test]
+    fn severity_ignores_info_bits() {
+        let overflow = StatusCode::UncertainSensorNotAccurate.set_overflow(true);
+        assert_eq!(Severity::of(&overflow), Severity::Uncertain);
+        let limited = StatusCode::Good.set_limit(StatusCodeLimit::High);
+        assert_eq!(Severity::of(&limited), Severity::Good);
+    }
+
+    #[test]
+    fn uncertain_fails_only_when_strict() {
+        assert!(!Severity::Good.is_failure_in(false));
+        assert!(!Severity::Good.is_failure_in(true));
+        assert!(!Severity::Uncertain.is_failure_in(false));
+        assert!(Severity::Uncertain.is_failure_in(true));
+        assert!(Severity::Bad.is_failure_in(false));
+        assert!(Severity::Bad.is_failure_in(true));
+    }
+
+    #[test]
+    fn severity_serializes_lowercase() {
+        let json = serde_json::to_string(&[Severity::Good, Severity::Uncertain, Severity::Bad]).unwrap();
+        assert_eq!(json, r#"["good","uncertain","bad"]"#);
+    }
+
+    #[test]
+    fn plain_text_is_borrowed() {
+        assert!(matches!(sanitize_text("Temperatur °C ü 温度"), Cow::Borrowed("Temperatur °C ü 温度")));
+    }
+
+    #[test]
+    fn control_characters_are_escaped() {
+        assert_eq!(sanitize_text("line 1\nline 2"), "line 1\\nline 2");
+        assert_eq!(sanitize_text("a\tb\r\n"), "a\\tb\\r\\n");
+        assert_eq!(sanitize_text("ring\x07"), "ring\\x07");
+        assert_eq!(sanitize_text("\0\x08\x7f"), "\\x00\\x08\\x7f");
+        // C1 controls, e.g. the single-character CSI
+        assert_eq!(sanitize_text("\u{9b}2J"), "\\x9b2J");
+    }
+
+    #[test]
+    fn ansi_sequences_are_escaped() {
+        assert_eq!(sanitize_text("\x1b[31mALARM\x1b[0m"), "\\x1b[31mALARM\\x1b[0m");
+        assert_eq!(sanitize_text("\x1b[2J\x1b[H"), "\\x1b[2J\\x1b[H");
+        // OSC window title, terminated by BEL
+        assert_eq!(sanitize_text("\x1b]0;pwned\x07"), "\\x1b]0;pwned\\x07");
+    }
+
+    #[test]
+    fn bidi_overrides_are_escaped() {
+        assert_eq!(sanitize_text("abc\u{202e}fed"), "abc\\u{202e}fed");
+        assert_eq!(sanitize_text("\u{2066}x\u{2069}"), "\\u{2066}x\\u{2069}");
+        assert_eq!(sanitize_text("\u{202a}\u{202b}\u{202c}\u{202d}"), "\\u{202a}\\u{202b}\\u{202c}\\u{202d}");
+        // Marks that only hint the direction are harmless
+        assert_eq!(sanitize_text("a\u{200e}b"), "a\u{200e}b");
+    }
+
+    #[test]
+    fn raw_strings_pass_through() {
+        assert_eq!(escape_text("\x1b[31mALARM\n", true), "\x1b[31mALARM\n");
+    }
+
+    #[test]
+    fn string_values_are_sanitized() {
+        assert_eq!(format_variant(&Variant::from("\x1b[31mALARM")), "\"\\x1b[31mALARM\"");
+        let text = LocalizedText::new("en", "row 1\nrow 2");
+        assert_eq!(format_variant(&Variant::from(text)), "\"row 1\\nrow 2\"");
+        let name = QualifiedName::new(2, "a\tb");
+        assert_eq!(format_variant(&Variant::from(name)), "2:a\\tb");
+    }
+
+    #[test]
+    fn json_strings_are_not_escaped_twice() {
+        let value = variant_to_json(&Variant::from("\x1b[31mALARM\n"));
+        assert_eq!(value, serde_json::json!("\x1b[31mALARM\n"));
+        assert_eq!(value.to_string(), "\"\\u001b[31mALARM\\n\"");
+    }
+
+    fn reference(node_id: NodeId, browse_name: QualifiedName, display_name: LocalizedText) -> ReferenceDescription {
+        ReferenceDescription {
+            reference_type_id: ReferenceTypeId::Organizes.into(),
+            is_forward: true,
+            node_id: node_id.into(),
+            browse_name,
+            display_name,
+            node_class: NodeClass::Variable,
+            type_definition: ExpandedNodeId::null(),
+        }
+    }
+
+    #[test]
+    fn reference_names_prefer_the_display_name() {
+        let named = reference(NodeId::new(2, "M1"), QualifiedName::new(2, "Motor1"), LocalizedText::new("de", "Motor 1"));
+        assert_eq!(reference_name(&named), ("Motor 1".to_string(), false));
+    }
+
+    #[test]
+    fn null_display_names_fall_back_to_the_browse_name() {
+        let null = reference(NodeId::new(2, "M1"), QualifiedName::new(2, "Motor1"), LocalizedText::null());
+        assert_eq!(reference_name(&null), ("Motor1".to_string(), true));
+        // A locale without text is no name either
+        let empty = reference(NodeId::new(2, "M1"), QualifiedName::new(2, "Motor1"), LocalizedText::new("en", ""));
+        assert_eq!(reference_name(&empty), ("Motor1".to_string(), true));
+    }
+
+    #[test]
+    fn null_browse_names_fall_back_to_the_identifier() {
+        let name = |node_id: NodeId| reference_name(&reference(node_id, QualifiedName::null(), LocalizedText::null()));
+        assert_eq!(name(NodeId::new(2, 1234)), ("1234".to_string(), true));
+        assert_eq!(name(NodeId::new(3, "Line1.Motor")), ("Line1.Motor".to_string(), true));
+        let guid = Guid::new();
+        assert_eq!(name(NodeId::new(2, guid.clone())), (guid.to_string(), true));
+        assert_eq!(name(NodeId::new(2, ByteString::from(vec![1, 2, 3]))), ("ns=2;b=AQID".to_string(), true));
+    }
+
+    #[test]
+    fn write_mask_with_several_bits() {
+        // AccessLevel, BrowseName, Description, ValueRank and AccessLevelEx
+        let write_mask = 1 | 1 << 2 | 1 << 5 | 1 << 19 | 1 << 25;
+        assert_eq!(write_mask_names(write_mask), ["AccessLevel", "BrowseName", "Description", "ValueRank", "AccessLevelEx"]);
+        assert_eq!(format_write_mask(write_mask), "AccessLevel | BrowseName | Description | ValueRank | AccessLevelEx");
+    }
+
+    #[test]
+    fn write_mask_of_zero() {
+        assert!(write_mask_names(0).is_empty());
+        assert_eq!(format_write_mask(0), "None".dimmed().to_string());
+    }
+}
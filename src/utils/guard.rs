@@ -0,0 +1,109 @@
+use anyhow::{anyhow, Result};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+/// `--yes` and `--max-operations` of the current run
+static GUARD: OnceLock<Guard> = OnceLock::new();
+
+struct Guard {
+    yes: bool,
+    max_operations: Option<u64>,
+    /// Operations counted one by one where there is no estimate up front
+    spent: AtomicU64,
+}
+
+pub fn install(yes: bool, max_operations: Option<u64>) {
+    let _ = GUARD.set(Guard { yes, max_operations, spent: AtomicU64::new(0) });
+}
+
+/// Service operations an expensive phase of a command is about to make
+#[derive(Debug, Clone, Copy)]
+pub enum Operations {
+    Reads(usize),
+    Writes(usize),
+    Calls(usize),
+}
+
+impl Operations {
+    fn count(self) -> usize {
+        match self {
+            Operations::Reads(count) | Operations::Writes(count) | Operations::Calls(count) => count,
+        }
+    }
+
+    /// Operations from which on the user is asked before they are made; calls and writes change
+    /// the server, so they are asked for much earlier than reads
+    fn confirm_from(self) -> usize {
+        match self {
+            Operations::Reads(_) => 10_000,
+            Operations::Writes(_) => 100,
+            Operations::Calls(_) => 10,
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Operations::Reads(count) => format!("read {} values", group_digits(count)),
+            Operations::Writes(count) => format!("write {} values", group_digits(count)),
+            Operations::Calls(count) => format!("call {} methods", group_digits(count)),
+        }
+    }
+}
+
+/// Check the operations of the next phase before making them: more than `--max-operations` in
+/// total fail, and enough of one kind ask for confirmation on the terminal unless `--yes` is given
+pub fn confirm(planned: &[Operations]) -> Result<()> {
+    let total: usize = planned.iter().map(|operations| operations.count()).sum();
+    let description = planned.iter().map(|operations| operations.describe()).collect::<Vec<_>>().join(" / ");
+    let Some(guard) = GUARD.get() else {
+        return Ok(());
+    };
+    if let Some(max) = guard.max_operations
+        && total as u64 > max
+    {
+        return Err(anyhow!("This would {}, more than --max-operations {} allows", description, max));
+    }
+    if guard.yes || planned.iter().all(|operations| operations.count() < operations.confirm_from()) {
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!("This would {}; pass --yes to go ahead without a terminal to ask on", description));
+    }
+
+    eprint!("⚠️  This will {} — continue? [y/N] ", description);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        Ok(())
+    } else {
+        Err(anyhow!("Cancelled, the command would {}", description))
+    }
+}
+
+/// Count one more operation of a phase that cannot be estimated up front, failing once it goes
+/// beyond `--max-operations`
+pub fn spend(operations: Operations) -> Result<()> {
+    let Some(guard) = GUARD.get() else {
+        return Ok(());
+    };
+    let spent = guard.spent.fetch_add(operations.count() as u64, Ordering::Relaxed) + operations.count() as u64;
+    match guard.max_operations {
+        Some(max) if spent > max => Err(anyhow!("Stopping after {} operations, the most --max-operations allows", group_digits(max as usize))),
+        _ => Ok(()),
+    }
+}
+
+/// `14302` as `14,302`
+fn group_digits(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
@@ -0,0 +1,182 @@
+use anyhow::{anyhow, Result};
+use opcua::types::NumericRange;
+
+/// Parse an IndexRange the way the specification writes it: a single index `3`, a range `2:5`,
+/// or one of those per dimension separated by commas, `1:2,0:1` for rows 1 to 2 and columns 0
+/// to 1 of a matrix. Indexes start at 0 and a range needs its low index below its high one.
+pub fn parse_index_range(text: &str) -> Result<NumericRange> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow!("The index range is empty, expected e.g. 3, 2:5 or 1:2,0:1"));
+    }
+    if text.contains(char::is_whitespace) {
+        return Err(anyhow!("Index range '{}' contains spaces, write it without them, e.g. 1:2,0:1", text));
+    }
+
+    let dimensions = text
+        .split(',')
+        .enumerate()
+        .map(|(idx, dimension)| {
+            parse_dimension(dimension).map_err(|e| {
+                if text.contains(',') {
+                    anyhow!("Index range '{}', dimension {}: {}", text, idx + 1, e)
+                } else {
+                    anyhow!("Index range '{}': {}", text, e)
+                }
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(match <[NumericRange; 1]>::try_from(dimensions) {
+        Ok([range]) => range,
+        Err(dimensions) => NumericRange::MultipleRanges(dimensions),
+    })
+}
+
+fn parse_dimension(dimension: &str) -> Result<NumericRange> {
+    if dimension.is_empty() {
+        return Err(anyhow!("nothing between the commas, every dimension needs an index or a range"));
+    }
+    let mut bounds = dimension.split(':');
+    let (low, high) = (bounds.next().unwrap_or_default(), bounds.next());
+    if bounds.next().is_some() {
+        return Err(anyhow!("'{}' has more than one ':', a range is <low>:<high>", dimension));
+    }
+
+    let low = parse_index(low)?;
+    let Some(high) = high else {
+        return Ok(NumericRange::Index(low));
+    };
+    let high = parse_index(high)?;
+    if low == high {
+        return Err(anyhow!("'{}' covers a single element, write just {}", dimension, low));
+    }
+    if low > high {
+        return Err(anyhow!("'{}' ends before it starts, write {}:{}", dimension, high, low));
+    }
+    Ok(NumericRange::Range(low, high))
+}
+
+fn parse_index(index: &str) -> Result<u32> {
+    if index.is_empty() {
+        return Err(anyhow!("a range needs both bounds, e.g. 2:5"));
+    }
+    if index.starts_with('-') {
+        return Err(anyhow!("'{}' is negative, indexes start at 0", index));
+    }
+    if !index.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(anyhow!("'{}' is not an index, only digits are allowed", index));
+    }
+    index.parse().map_err(|_| anyhow!("{} is beyond the largest index {}", index, u32::MAX))
+}
+
+/// Number of elements the range selects in each of its dimensions
+pub fn range_lengths(range: &NumericRange) -> Vec<u32> {
+    match range {
+        NumericRange::None => Vec::new(),
+        NumericRange::Index(_) => vec![1],
+        NumericRange::Range(low, high) => vec![high - low + 1],
+        NumericRange::MultipleRanges(dimensions) => dimensions.iter().flat_map(range_lengths).collect(),
+    }
+}
+
+/// The range as shown after a node ID, e.g. ` [1:2,0:1]`, nothing without one
+pub fn range_suffix(range: &NumericRange) -> String {
+    if range.is_none() {
+        String::new()
+    } else {
+        format!(" [{}]", range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        match parse_index_range(text) {
+            Ok(range) => panic!("'{}' was accepted as {}", text, range),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn single_index_and_range() {
+        assert_eq!(parse_index_range("3").unwrap(), NumericRange::Index(3));
+        assert_eq!(parse_index_range("2:5").unwrap(), NumericRange::Range(2, 5));
+        assert_eq!(parse_index_range(" 0:4294967295 ").unwrap(), NumericRange::Range(0, u32::MAX));
+        assert_eq!(range_lengths(&parse_index_range("2:5").unwrap()), [4]);
+    }
+
+    #[test]
+    fn multi_dimensional() {
+        let range = parse_index_range("1:2,0:1").unwrap();
+        assert_eq!(range, NumericRange::MultipleRanges(vec![NumericRange::Range(1, 2), NumericRange::Range(0, 1)]));
+        assert_eq!(range_lengths(&range), [2, 2]);
+        assert_eq!(range_suffix(&range), " [1:2,0:1]");
+
+        let range = parse_index_range("4,0:2,7").unwrap();
+        assert_eq!(range_lengths(&range), [1, 3, 1]);
+        assert!(error("1:2,").contains("dimension 2: nothing between the commas"));
+        assert!(error(",1").contains("dimension 1"));
+        assert!(error("1:2, 0:1").contains("contains spaces"));
+    }
+
+    #[test]
+    fn single_element_ranges_are_rejected() {
+        assert!(error("3:3").contains("'3:3' covers a single element, write just 3"));
+        assert!(error("1:2,0:0").contains("dimension 2: '0:0' covers a single element"));
+    }
+
+    #[test]
+    fn reversed_ranges_are_rejected() {
+        assert!(error("5:2").contains("'5:2' ends before it starts, write 2:5"));
+        assert!(error("0:1,9:8").contains("dimension 2: '9:8' ends before it starts"));
+    }
+
+    #[test]
+    fn non_numeric_input() {
+        assert!(error("").contains("is empty"));
+        assert!(error("a").contains("'a' is not an index"));
+        assert!(error("1:b").contains("'b' is not an index"));
+        assert!(error("0x10").contains("'0x10' is not an index"));
+        assert!(error("+1").contains("'+1' is not an index"));
+        assert!(error("-1").contains("'-1' is negative"));
+        assert!(error("1:").contains("needs both bounds"));
+        assert!(error(":3").contains("needs both bounds"));
+        assert!(error("1:2:3").contains("more than one ':'"));
+        assert!(error("4294967296").contains("beyond the largest index"));
+    }
+
+    #[test]
+    fn generated_ranges_read_back() {
+        let dimensions = ["0", "7", "0:1", "2:5", "10:4294967295"];
+        for first in dimensions {
+            for second in dimensions {
+                for third in ["", ",3", ",1:9"] {
+                    let text = format!("{},{}{}", first, second, third);
+                    let range = parse_index_range(&text).unwrap_or_else(|e| panic!("{}: {}", text, e));
+                    assert_eq!(range.to_string(), text);
+                    assert_eq!(range_lengths(&range).len(), text.split(',').count());
+                }
+                let range = parse_index_range(first).unwrap();
+                assert_eq!(range.to_string(), first);
+            }
+        }
+    }
+
+    #[test]
+    fn generated_mistakes_are_rejected() {
+        for valid in ["3", "2:5", "1:2,0:1"] {
+            for mistake in ["x", "-", "+", ".", ",,", "::"] {
+                for position in 0..=valid.len() {
+                    let text = format!("{}{}{}", &valid[..position], mistake, &valid[position..]);
+                    assert!(parse_index_range(&text).is_err(), "'{}' was accepted", text);
+                }
+            }
+            assert!(parse_index_range(&format!("{}:", valid)).is_err());
+            assert!(parse_index_range(&format!("{},", valid)).is_err());
+            assert!(parse_index_range(&format!("{}x", valid)).is_err());
+        }
+    }
+}
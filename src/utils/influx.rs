@@ -0,0 +1,72 @@
+use opcua::types::*;
+
+use crate::utils::formatter::localized_text;
+
+/// One InfluxDB line protocol point with a single `value` field, or `None` when the value
+/// has no line protocol field type (arrays, structures, NaN and the like)
+pub fn line_protocol(measurement: &str, tags: &[(&str, &str)], value: &Variant, timestamp_ns: i64) -> Option<String> {
+    let field = field_value(value)?;
+
+    let mut line = escape(measurement, &[',', ' ']);
+    for (key, tag) in tags {
+        // Empty tag values are not allowed by the protocol
+        if tag.is_empty() {
+            continue;
+        }
+        line.push(',');
+        line.push_str(&escape(key, &[',', '=', ' ']));
+        line.push('=');
+        line.push_str(&escape(tag, &[',', '=', ' ']));
+    }
+    line.push_str(&format!(" value={} {}", field, timestamp_ns));
+    Some(line)
+}
+
+fn field_value(value: &Variant) -> Option<String> {
+    Some(match value {
+        Variant::Boolean(v) => v.to_string(),
+        Variant::SByte(v) => format!("{}i", v),
+        Variant::Byte(v) => format!("{}i", v),
+        Variant::Int16(v) => format!("{}i", v),
+        Variant::UInt16(v) => format!("{}i", v),
+        Variant::Int32(v) => format!("{}i", v),
+        Variant::UInt32(v) => format!("{}i", v),
+        Variant::Int64(v) => format!("{}i", v),
+        // Only values beyond the signed range need the unsigned type of InfluxDB 2
+        Variant::UInt64(v) if *v > i64::MAX as u64 => format!("{}u", v),
+        Variant::UInt64(v) => format!("{}i", v),
+        Variant::Float(v) if v.is_finite() => float_field(*v as f64),
+        Variant::Double(v) if v.is_finite() => float_field(*v),
+        Variant::String(v) => string_field(v.as_ref()),
+        Variant::LocalizedText(v) => string_field(localized_text(v)),
+        Variant::DateTime(v) => string_field(&v.to_string()),
+        Variant::Guid(v) => string_field(&v.to_string()),
+        _ => return None,
+    })
+}
+
+/// Floats are written with a decimal point so the field is never taken for an integer
+fn float_field(value: f64) -> String {
+    let text = value.to_string();
+    if text.contains(['.', 'e', 'E']) { text } else { format!("{}.0", text) }
+}
+
+fn string_field(value: &str) -> String {
+    format!("\"{}\"", escape(value, &['"']))
+}
+
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+        // Line breaks would end the point
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::NodeId;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::utils::formatter::{format_node_id, parse_node_id_text};
+
+/// Labels of the current run, installed once from `--labels`
+static LABELS: OnceLock<Labels> = OnceLock::new();
+
+/// Friendly names for node IDs loaded with `--labels`.
+///
+/// The file is either a JSON object or TOML-style `"<node id>" = "<label>"` lines, where `#`
+/// starts a comment and table headers such as `[labels]` are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct Labels {
+    by_node: HashMap<NodeId, String>,
+    by_label: HashMap<String, NodeId>,
+}
+
+impl Labels {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read labels {}: {}", path, e))?;
+
+        let entries: Vec<(String, String)> = if path.to_lowercase().ends_with(".json") {
+            let map: HashMap<String, String> = serde_json::from_str(&content)
+                .map_err(|e| anyhow!("{} is not a JSON object of node IDs to labels: {}", path, e))?;
+            map.into_iter().collect()
+        } else {
+            content
+                .lines()
+                .enumerate()
+                .map(|(idx, line)| (idx + 1, line.trim()))
+                .filter(|(_, line)| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+                .map(|(line_no, line)| parse_toml_line(line).map_err(|e| anyhow!("{}:{}: {}", path, line_no, e)))
+                .collect::<Result<_>>()?
+        };
+
+        let mut labels = Labels::default();
+        for (node, label) in entries {
+            let node_id = parse_node_id_text(&node)
+                .ok_or_else(|| anyhow!("{}: invalid node ID {}", path, node))?;
+            if label.is_empty() {
+                return Err(anyhow!("{}: empty label for {}", path, node));
+            }
+            if let Some(other) = labels.by_label.insert(label.to_lowercase(), node_id.clone())
+                && other != node_id
+            {
+                return Err(anyhow!("{}: label '{}' is used for more than one node", path, label));
+            }
+            labels.by_node.insert(node_id, label);
+        }
+
+        Ok(labels)
+    }
+}
+
+/// `"key" = "value"` with basic string escapes; bare keys are accepted too
+pub fn parse_toml_line(line: &str) -> Result<(String, String)> {
+    let (key, rest) = parse_toml_string(line)?;
+    let rest = rest.trim_start().strip_prefix('=').ok_or_else(|| anyhow!("expected `\"<key>\" = \"<value>\"`"))?;
+    let (value, rest) = parse_toml_string(rest.trim_start())?;
+    let rest = rest.trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(anyhow!("unexpected text after the value: {}", rest));
+    }
+    Ok((key, value))
+}
+
+fn parse_toml_string(text: &str) -> Result<(String, &str)> {
+    let Some(quoted) = text.strip_prefix('"') else {
+        // Bare key, up to the `=`
+        let end = text.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(text.len());
+        if end == 0 {
+            return Err(anyhow!("expected a quoted string"));
+        }
+        return Ok((text[..end].to_string(), &text[end..]));
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &quoted[idx + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                _ => return Err(anyhow!("unsupported escape sequence")),
+            },
+            c => value.push(c),
+        }
+    }
+    Err(anyhow!("unterminated string"))
+}
+
+/// Make the labels available to the formatting and node parsing helpers
+pub fn install(labels: Labels) {
+    let _ = LABELS.set(labels);
+}
+
+pub fn label_for(node_id: &NodeId) -> Option<&'static str> {
+    LABELS.get()?.by_node.get(node_id).map(String::as_str)
+}
+
+/// The node ID carrying this label, compared case-insensitively
+pub fn resolve_label(label: &str) -> Option<NodeId> {
+    LABELS.get()?.by_label.get(&label.to_lowercase()).cloned()
+}
+
+/// The label followed by the dimmed node ID, or just the node ID for unlabelled nodes
+pub fn format_labelled_node_id(node_id: &NodeId) -> String {
+    match label_for(node_id) {
+        Some(label) => format!("{} {}", label, format_node_id(node_id).dimmed()),
+        None => format_node_id(node_id),
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::{anyhow, Result};
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crate::types::{BenchCommand, BrowseCommand, CallCommand, Commands, MonitorCommand, ReadCommand};
+use crate::utils::time::format_duration;
+
+/// Deepest recursive browse accepted without `--i-know-what-im-doing`; real address spaces
+/// rarely nest more than a dozen levels, larger values are mostly typos that crawl for hours
+pub const MAX_DEPTH: u32 = 64;
+/// Most nodes a benchmark reads
+pub const MAX_BENCH_COUNT: usize = 100_000;
+/// Most operations a benchmark puts into one request
+pub const MAX_BATCH: usize = 10_000;
+/// Most connections or requests in flight at the same time
+pub const MAX_CONCURRENCY: usize = 64;
+/// Most reads a stale scan spreads over its window
+pub const MAX_SAMPLES: u32 = 10_000;
+/// Longest sampling interval, notification wait and timeout
+pub const MAX_INTERVAL: Duration = Duration::from_secs(3600);
+/// Longest sampling window, benchmark run and call timeout
+pub const MAX_RUN_TIME: Duration = Duration::from_secs(7 * 86400);
+
+/// Whether the children of a node `depth` levels below the start node are browsed as well.
+/// Depth 0 lists only the start node's immediate children, every further level of `max_depth`
+/// one level of their descendants; browse, export and the name searches all count this way.
+pub fn descends(depth: u32, max_depth: u32) -> bool {
+    depth < max_depth
+}
+
+/// Check the depth, count, concurrency and time arguments of `command` against the sane maxima
+/// above, unless `--i-know-what-im-doing` lifts them. Lower bounds are enforced by clap and the
+/// commands themselves.
+pub fn check(command: &Commands, i_know_what_im_doing: bool) -> Result<()> {
+    if i_know_what_im_doing {
+        return Ok(());
+    }
+    match command {
+        Commands::Browse(BrowseCommand { depth, .. })
+        | Commands::Export { depth, .. }
+        | Commands::Lint { depth, .. }
+        | Commands::Assert { depth, .. }
+        | Commands::Permissions { depth, .. }
+        | Commands::Backup { depth, .. }
+        | Commands::Copy { depth, .. } => in_range("--depth", *depth, 0..=MAX_DEPTH),
+        Commands::StaleScan { depth, window, samples, .. } => {
+            in_range("--depth", *depth, 0..=MAX_DEPTH)?;
+            duration_at_most("--window", *window, MAX_RUN_TIME)?;
+            in_range("--samples", *samples, 2..=MAX_SAMPLES)
+        }
+        Commands::Bench(BenchCommand { depth, count, batch, concurrency, duration, .. }) => {
+            in_range("--depth", *depth, 0..=MAX_DEPTH)?;
+            in_range("--count", *count, 1..=MAX_BENCH_COUNT)?;
+            in_range("--batch", *batch, 1..=MAX_BATCH)?;
+            in_range("--concurrency", *concurrency, 1..=MAX_CONCURRENCY)?;
+            duration_at_most("--duration", *duration, MAX_RUN_TIME)
+        }
+        Commands::Fleet { concurrency, .. } => in_range("--concurrency", *concurrency, 1..=MAX_CONCURRENCY),
+        Commands::Monitor(MonitorCommand { interval, grace, .. }) => {
+            duration_at_most("--interval", Duration::from_millis(*interval), MAX_INTERVAL)?;
+            duration_at_most("--grace", Duration::from_millis(*grace), MAX_INTERVAL)
+        }
+        Commands::Read(ReadCommand { wait, .. }) => duration_at_most("--wait", Duration::from_millis(*wait), MAX_INTERVAL),
+        Commands::Call(CallCommand { call_timeout: Some(timeout), .. }) => duration_at_most("--call-timeout", *timeout, MAX_RUN_TIME),
+        Commands::Report { timeout, .. } => duration_at_most("--timeout", *timeout, MAX_INTERVAL),
+        Commands::FindServers { duration, .. } => duration_at_most("--duration", *duration, MAX_INTERVAL),
+        _ => Ok(()),
+    }
+}
+
+fn in_range<T: PartialOrd + Display>(flag: &str, value: T, range: RangeInclusive<T>) -> Result<()> {
+    if value > *range.end() {
+        return Err(anyhow!(
+            "{} {} is out of range, expected {} to {}; pass --i-know-what-im-doing to go beyond",
+            flag, value, range.start(), range.end()
+        ));
+    }
+    Ok(())
+}
+
+fn duration_at_most(flag: &str, value: Duration, max: Duration) -> Result<()> {
+    if value > max {
+        return Err(anyhow!(
+            "{} {} is out of range, expected up to {}; pass --i-know-what-im-doing to go beyond",
+            flag, format_duration(value), format_duration(max)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Cli;
+    use clap::Parser;
+
+    fn command(args: &[&str]) -> Commands {
+        let argv = std::iter::once("opcua-walker").chain(args.iter().copied());
+        Cli::try_parse_from(argv).unwrap_or_else(|e| panic!("{:?}: {}", args, e)).command
+    }
+
+    fn accepted(args: &[&str]) -> bool {
+        check(&command(args), false).is_ok()
+    }
+
+    /// `flag` is accepted at `max` and refused one step above it, naming the flag and the range
+    fn upper_bound(args: &[&str], flag: &str, max: &str, above: &str) {
+        assert!(accepted(&[args, &[flag, max]].concat()), "{} {} refused", flag, max);
+        let error = check(&command(&[args, &[flag, above]].concat()), false).unwrap_err().to_string();
+        assert!(error.starts_with(&format!("{} ", flag)) && error.contains(" is out of range"), "{}", error);
+        assert!(error.contains("--i-know-what-im-doing"), "{}", error);
+    }
+
+    #[test]
+    fn depth_counts_levels_below_the_start_node() {
+        // Depth 0 lists only the start node's children, which are not descended into
+        assert!(!descends(0, 0));
+        assert!(descends(0, 1));
+        assert!(!descends(1, 1));
+        assert!(descends(2, 3));
+        assert!(!descends(3, 3));
+        assert!(!descends(MAX_DEPTH, MAX_DEPTH));
+        assert!(!descends(u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn depth_bounds() {
+        for args in [
+            &["browse"][..],
+            &["export", "--nodeset2", "model.xml"],
+            &["lint"],
+            &["assert", "spec.json"],
+            &["permissions"],
+            &["stale-scan"],
+            &["backup", "ns=2;s=Plant", "--output", "backup.json"],
+            &["copy", "ns=2;s=Plant", "--to", "opc.tcp://line2:4840"],
+            &["bench"],
+        ] {
+            upper_bound(args, "--depth", "64", "65");
+            assert!(accepted(&[args, &["--depth", "0"]].concat()));
+        }
+        let error = check(&command(&["browse", "--depth", "4294967295"]), false).unwrap_err().to_string();
+        assert!(error.contains("expected 0 to 64"), "{}", error);
+    }
+
+    #[test]
+    fn count_and_concurrency_bounds() {
+        upper_bound(&["stale-scan"], "--samples", "10000", "10001");
+        upper_bound(&["bench"], "--count", "100000", "100001");
+        upper_bound(&["bench"], "--batch", "10000", "10001");
+        upper_bound(&["bench"], "--concurrency", "64", "65");
+        upper_bound(&["fleet", "opc.tcp://plc:4840"], "--concurrency", "64", "65");
+    }
+
+    #[test]
+    fn time_bounds() {
+        upper_bound(&["stale-scan"], "--window", "7d", "7d1s");
+        upper_bound(&["bench"], "--duration", "7d", "7d1s");
+        upper_bound(&["call", "Reboot"], "--call-timeout", "7d", "7d1s");
+        upper_bound(&["report"], "--timeout", "1h", "1h1s");
+        upper_bound(&["find-servers", "--mdns"], "--duration", "1h", "1h1s");
+        upper_bound(&["monitor", "ns=2;s=Temperature"], "--interval", "3600000", "3600001");
+        upper_bound(&["monitor", "ns=2;s=Temperature", "--compare"], "--grace", "3600000", "3600001");
+        upper_bound(&["read", "ns=2;s=Temperature", "--via-subscription"], "--wait", "3600000", "3600001");
+    }
+
+    #[test]
+    fn durations_in_errors_read_like_arguments() {
+        let error = check(&command(&["stale-scan", "--window", "8d"]), false).unwrap_err().to_string();
+        assert_eq!(error, "--window 8d is out of range, expected up to 7d; pass --i-know-what-im-doing to go beyond");
+    }
+
+    #[test]
+    fn i_know_what_im_doing_lifts_the_maxima() {
+        for args in [
+            &["browse", "--depth", "4294967295"][..],
+            &["stale-scan", "--samples", "1000000", "--window", "30d"],
+            &["bench", "--count", "1000000", "--batch", "100000", "--concurrency", "1000", "--duration", "30d"],
+            &["monitor", "ns=2;s=Temperature", "--interval", "86400000"],
+        ] {
+            assert!(!accepted(args), "{:?} accepted without the flag", args);
+            assert!(check(&command(args), true).is_ok(), "{:?} refused with the flag", args);
+        }
+    }
+
+    #[test]
+    fn commands_without_limits_pass() {
+        assert!(accepted(&["info"]));
+        assert!(accepted(&["read", "ns=2;s=Temperature"]));
+        assert!(accepted(&["call", "Reboot"]));
+    }
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Local};
+use opcua::types::{DataValue, NodeId, ReadValueId, StatusCode};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::time::sleep_until;
+use tracing::debug;
+
+use crate::session::ServiceSession;
+use crate::utils::formatter::{format_value_variant, sanitize_text};
+use crate::utils::subscription::ValueSubscription;
+
+/// Nodes read per request while polling
+const POLL_BATCH: usize = 500;
+
+/// The latest value of a node in the live views, with when it last changed
+pub struct LiveValue {
+    pub value: DataValue,
+    /// When the value or its status last differed from the one before, `None` while it has
+    /// not changed since the first one arrived
+    pub last_change: Option<DateTime<Local>>,
+}
+
+impl LiveValue {
+    /// The value on one line, with the status of values that are not Good
+    pub fn text(&self) -> String {
+        let status = self.value.status.unwrap_or(StatusCode::Good);
+        let value = self.value.value.as_ref().map(|variant| {
+            let text = format_value_variant(variant);
+            sanitize_text(text.lines().next().unwrap_or_default()).into_owned()
+        });
+        match value {
+            Some(value) if status.is_good() => value,
+            Some(value) => format!("{} ({})", value, status),
+            None => status.to_string(),
+        }
+    }
+}
+
+/// Current values of the nodes a live view shows. A node counts as changed for `highlight` after
+/// a value that differs from its previous one, timestamps aside.
+pub struct LiveValues {
+    values: HashMap<NodeId, LiveValue>,
+    highlight: Duration,
+}
+
+impl LiveValues {
+    pub fn new(highlight: Duration) -> Self {
+        LiveValues { values: HashMap::new(), highlight }
+    }
+
+    pub fn update(&mut self, node_id: NodeId, value: DataValue, now: DateTime<Local>) {
+        match self.values.get_mut(&node_id) {
+            Some(live) => {
+                if live.value.value != value.value || live.value.status != value.status {
+                    live.last_change = Some(now);
+                }
+                live.value = value;
+            }
+            None => {
+                self.values.insert(node_id, LiveValue { value, last_change: None });
+            }
+        }
+    }
+
+    pub fn get(&self, node_id: &NodeId) -> Option<&LiveValue> {
+        self.values.get(node_id)
+    }
+
+    /// Whether the node changed within the highlight duration
+    pub fn is_changed(&self, node_id: &NodeId, now: DateTime<Local>) -> bool {
+        self.values
+            .get(node_id)
+            .and_then(|live| live.last_change)
+            .is_some_and(|changed| (now - changed).to_std().unwrap_or_default() < self.highlight)
+    }
+
+    /// Forget the nodes that are no longer shown, so they do not count as changed on their return
+    pub fn retain(&mut self, node_ids: &[NodeId]) {
+        self.values.retain(|node_id, _| node_ids.contains(node_id));
+    }
+}
+
+enum Source {
+    Subscription(ValueSubscription),
+    Polling { next_poll: Instant },
+}
+
+/// Values of a changing set of nodes: reported by a subscription, or read every interval when the
+/// server offers none
+pub struct LiveFeed {
+    session: ServiceSession,
+    interval: Duration,
+    source: Option<Source>,
+    /// Nodes watched right now
+    watched: Vec<NodeId>,
+    /// Node of each position of the subscription, `None` once it is no longer watched
+    positions: Vec<Option<NodeId>>,
+    /// Why the feed reads instead of subscribing, to tell the user once
+    pub fallback: Option<String>,
+}
+
+impl LiveFeed {
+    pub fn new(session: &ServiceSession, interval: Duration) -> Self {
+        LiveFeed { session: session.clone(), interval, source: None, watched: Vec::new(), positions: Vec::new(), fallback: None }
+    }
+
+    /// Watch exactly `node_ids` from now on
+    pub async fn watch(&mut self, node_ids: &[NodeId]) {
+        if node_ids == self.watched.as_slice() {
+            return;
+        }
+        let added: Vec<NodeId> = node_ids.iter().filter(|node_id| !self.watched.contains(node_id)).cloned().collect();
+        match &mut self.source {
+            None if node_ids.is_empty() => {}
+            None => match ValueSubscription::create(&self.session, node_ids, self.interval).await {
+                Ok((subscription, _)) => {
+                    self.positions = node_ids.iter().cloned().map(Some).collect();
+                    self.source = Some(Source::Subscription(subscription));
+                }
+                Err(e) => self.fall_back(format!("{:#}", e)),
+            },
+            Some(Source::Subscription(subscription)) => {
+                for index in 0..self.positions.len() {
+                    if self.positions[index].as_ref().is_some_and(|node_id| !node_ids.contains(node_id)) {
+                        subscription.remove(index).await;
+                        self.positions[index] = None;
+                    }
+                }
+                if !added.is_empty() {
+                    match subscription.add(&added).await {
+                        Ok(_) => self.positions.extend(added.iter().cloned().map(Some)),
+                        Err(e) => self.fall_back(format!("{:#}", e)),
+                    }
+                }
+            }
+            Some(Source::Polling { next_poll }) => {
+                // New nodes are read right away instead of one interval later
+                if !added.is_empty() {
+                    *next_poll = Instant::now();
+                }
+            }
+        }
+        self.watched = node_ids.to_vec();
+    }
+
+    /// Read the values from now on, deleting the subscription
+    fn fall_back(&mut self, reason: String) {
+        debug!("Reading live values every {:?}: {}", self.interval, reason);
+        if let Some(Source::Subscription(subscription)) = self.source.take() {
+            tokio::spawn(subscription.delete());
+        }
+        self.positions.clear();
+        self.source = Some(Source::Polling { next_poll: Instant::now() });
+        self.fallback = Some(reason);
+    }
+
+    /// Wait for the next values. Cancel safe: a poll cut short is repeated on the next call.
+    pub async fn next(&mut self) -> Vec<(NodeId, DataValue)> {
+        match &mut self.source {
+            None => std::future::pending().await,
+            Some(Source::Subscription(subscription)) => match subscription.next().await {
+                Some((index, value)) => match self.positions.get(index).cloned().flatten() {
+                    Some(node_id) => vec![(node_id, value)],
+                    None => Vec::new(),
+                },
+                None => {
+                    self.fall_back("the server closed the subscription".to_string());
+                    Vec::new()
+                }
+            },
+            Some(Source::Polling { next_poll }) => {
+                sleep_until((*next_poll).into()).await;
+                let mut values = Vec::new();
+                for batch in self.watched.chunks(POLL_BATCH) {
+                    let requests: Vec<ReadValueId> = batch.iter().map(ReadValueId::from).collect();
+                    match self.session.read_values(&requests).await {
+                        Ok(read) => values.extend(batch.iter().cloned().zip(read)),
+                        Err(status) => debug!("Reading live values failed: {}", status),
+                    }
+                }
+                if let Some(Source::Polling { next_poll }) = &mut self.source {
+                    *next_poll = Instant::now() + self.interval;
+                }
+                values
+            }
+        }
+    }
+}
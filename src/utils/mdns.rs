@@ -0,0 +1,404 @@
+use anyhow::{anyhow, Result};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// DNS-SD service type of OPC UA servers with the opc.tcp transport, OPC 10000-12 Annex C
+pub const OPCUA_SERVICE: &str = "_opcua-tcp._tcp.local";
+
+const MDNS_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+
+/// Queries are repeated while listening, responders drop some of them on busy networks
+const QUERY_INTERVAL: Duration = Duration::from_secs(1);
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_AAAA: u16 = 28;
+const TYPE_SRV: u16 = 33;
+
+/// A server that announced itself via mDNS
+pub struct MdnsServer {
+    /// Service instance name without the service type, e.g. `PLC-1`
+    pub instance: String,
+    pub hostname: String,
+    /// Addresses of the host, the address the response came from when it sent none
+    pub addresses: Vec<IpAddr>,
+    pub port: u16,
+    /// `caps` of the TXT record, e.g. `LDS` or `DA`
+    pub capabilities: Vec<String>,
+    /// `path` of the TXT record, appended to the URL
+    pub path: String,
+}
+
+impl MdnsServer {
+    /// opc.tcp URL of the server, by address since `.local` names rarely resolve outside mDNS
+    pub fn url(&self) -> String {
+        let host = match self.addresses.iter().find(|address| address.is_ipv4()).or(self.addresses.first()) {
+            Some(IpAddr::V6(address)) => format!("[{}]", address),
+            Some(address) => address.to_string(),
+            None => self.hostname.clone(),
+        };
+        let path = match self.path.as_str() {
+            "" | "/" => String::new(),
+            path if path.starts_with('/') => path.to_string(),
+            path => format!("/{}", path),
+        };
+        format!("opc.tcp://{}:{}{}", host, self.port, path)
+    }
+}
+
+/// Records collected from all responses, keyed by lowercase name since DNS names ignore case
+#[derive(Default)]
+struct Records {
+    /// Service instances from PTR records in the order they were first seen
+    instances: Vec<String>,
+    /// Address each instance was first announced from
+    sources: HashMap<String, IpAddr>,
+    srv: HashMap<String, (String, u16)>,
+    txt: HashMap<String, Vec<String>>,
+    addresses: HashMap<String, Vec<IpAddr>>,
+}
+
+impl Records {
+    /// The service type, and the records still missing for instances and hosts seen so far
+    fn questions(&self) -> Vec<(String, u16)> {
+        let mut questions = vec![(OPCUA_SERVICE.to_string(), TYPE_PTR)];
+        for instance in &self.instances {
+            let key = instance.to_lowercase();
+            match self.srv.get(&key) {
+                None => questions.push((instance.clone(), TYPE_SRV)),
+                Some((host, _)) if !self.addresses.contains_key(&host.to_lowercase()) => questions.push((host.clone(), TYPE_A)),
+                Some(_) => {}
+            }
+            if !self.txt.contains_key(&key) {
+                questions.push((instance.clone(), TYPE_TXT));
+            }
+        }
+        questions
+    }
+
+    fn servers(self) -> Vec<MdnsServer> {
+        let suffix = format!(".{}", OPCUA_SERVICE);
+        self.instances
+            .iter()
+            .filter_map(|instance| {
+                let key = instance.to_lowercase();
+                let Some((hostname, port)) = self.srv.get(&key) else {
+                    warn!("{} announced itself but sent no SRV record, its port is unknown", instance);
+                    return None;
+                };
+                let txt = self.txt.get(&key).map(Vec::as_slice).unwrap_or_default();
+                let entry = |name: &str| txt.iter().find_map(|entry| {
+                    let (key, value) = entry.split_once('=')?;
+                    key.eq_ignore_ascii_case(name).then_some(value)
+                });
+                Some(MdnsServer {
+                    instance: instance.strip_suffix(suffix.as_str()).unwrap_or(instance).to_string(),
+                    hostname: hostname.clone(),
+                    addresses: self.addresses
+                        .get(&hostname.to_lowercase())
+                        .cloned()
+                        .or_else(|| self.sources.get(&key).map(|source| vec![*source]))
+                        .unwrap_or_default(),
+                    port: *port,
+                    capabilities: entry("caps")
+                        .map(|caps| caps.split(',').map(str::trim).filter(|cap| !cap.is_empty()).map(String::from).collect())
+                        .unwrap_or_default(),
+                    path: entry("path").unwrap_or_default().to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Browse the local network for OPC UA servers for `duration`.
+///
+/// The queries are one-shot queries from an ephemeral port, so responders answer this socket
+/// directly and the tool does not compete with a local mDNS daemon for port 5353.
+pub async fn browse(duration: Duration) -> Result<Vec<MdnsServer>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await
+        .map_err(|e| anyhow!("Failed to open a UDP socket for mDNS: {}", e))?;
+    socket.set_multicast_ttl_v4(255)?;
+    socket.set_multicast_loop_v4(true)?;
+
+    let mut records = Records::default();
+    let deadline = Instant::now() + duration;
+    let mut next_query = Instant::now();
+    let mut buffer = vec![0u8; 9000];
+
+    while Instant::now() < deadline {
+        if Instant::now() >= next_query {
+            socket.send_to(&query(&records.questions()), (MDNS_GROUP, MDNS_PORT)).await
+                .map_err(|e| anyhow!("Sending the mDNS query failed, is there a network interface with multicast? ({})", e))?;
+            next_query += QUERY_INTERVAL;
+        }
+        match tokio::time::timeout_at(next_query.min(deadline), socket.recv_from(&mut buffer)).await {
+            Ok(Ok((len, source))) => {
+                if parse_response(&buffer[..len], source.ip(), &mut records).is_none() {
+                    debug!("Ignoring malformed mDNS response from {}", source);
+                }
+            }
+            Ok(Err(e)) => return Err(anyhow!("Receiving mDNS responses failed: {}", e)),
+            Err(_) => {}
+        }
+    }
+
+    Ok(records.servers())
+}
+
+fn query(questions: &[(String, u16)]) -> Vec<u8> {
+    let mut packet = vec![0u8; 12];
+    packet[4..6].copy_from_slice(&(questions.len() as u16).to_be_bytes());
+    for (name, record_type) in questions {
+        for label in name.split('.').filter(|label| !label.is_empty()) {
+            let label = &label.as_bytes()[..label.len().min(63)];
+            packet.push(label.len() as u8);
+            packet.extend_from_slice(label);
+        }
+        packet.push(0);
+        packet.extend_from_slice(&record_type.to_be_bytes());
+        // Class IN with the unicast-response bit
+        packet.extend_from_slice(&0x8001u16.to_be_bytes());
+    }
+    packet
+}
+
+/// Collect the records of a response, `None` when the packet is malformed
+fn parse_response(packet: &[u8], source: IpAddr, records: &mut Records) -> Option<()> {
+    let header = packet.get(..12)?;
+    // Queries of other hosts arrive on the same group
+    if header[2] & 0x80 == 0 {
+        return Some(());
+    }
+    let count = |offset: usize| u16::from_be_bytes([header[offset], header[offset + 1]]) as usize;
+    let questions = count(4);
+    let answers = count(6) + count(8) + count(10);
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = read_name(packet, pos)?.1 + 4;
+    }
+    for _ in 0..answers {
+        let (name, next) = read_name(packet, pos)?;
+        let fixed = packet.get(next..next + 10)?;
+        let record_type = u16::from_be_bytes([fixed[0], fixed[1]]);
+        let len = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+        let start = next + 10;
+        let data = packet.get(start..start + len)?;
+        let key = name.to_lowercase();
+
+        match record_type {
+            TYPE_PTR if name.eq_ignore_ascii_case(OPCUA_SERVICE) => {
+                let (instance, _) = read_name(packet, start)?;
+                if let Entry::Vacant(entry) = records.sources.entry(instance.to_lowercase()) {
+                    entry.insert(source);
+                    records.instances.push(instance);
+                }
+            }
+            TYPE_SRV if len >= 6 => {
+                let port = u16::from_be_bytes([data[4], data[5]]);
+                let (target, _) = read_name(packet, start + 6)?;
+                records.srv.insert(key, (target, port));
+            }
+            TYPE_TXT => {
+                let mut entries = Vec::new();
+                let mut rest = data;
+                while let Some((&entry_len, tail)) = rest.split_first() {
+                    let entry = tail.get(..entry_len as usize)?;
+                    if !entry.is_empty() {
+                        entries.push(String::from_utf8_lossy(entry).into_owned());
+                    }
+                    rest = &tail[entry_len as usize..];
+                }
+                records.txt.insert(key, entries);
+            }
+            TYPE_A if len == 4 => {
+                let address = IpAddr::V4(Ipv4Addr::new(data[0], data[1], data[2], data[3]));
+                push_address(records.addresses.entry(key).or_default(), address);
+            }
+            TYPE_AAAA if len == 16 => {
+                let octets: [u8; 16] = data.try_into().ok()?;
+                push_address(records.addresses.entry(key).or_default(), IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            _ => {}
+        }
+        pos = start + len;
+    }
+    Some(())
+}
+
+fn push_address(addresses: &mut Vec<IpAddr>, address: IpAddr) {
+    if !addresses.contains(&address) {
+        addresses.push(address);
+    }
+}
+
+/// Read a possibly compressed name, returning it with the position after it
+fn read_name(packet: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Pointers only point backwards in valid packets, the limit guards against loops
+    for _ in 0..128 {
+        let len = *packet.get(pos)? as usize;
+        if len & 0xC0 == 0xC0 {
+            let offset = ((len & 0x3F) << 8) | *packet.get(pos + 1)? as usize;
+            end.get_or_insert(pos + 2);
+            pos = offset;
+        } else if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(pos + 1)));
+        } else if len > 63 {
+            // 0x40 and 0x80 are reserved label types
+            return None;
+        } else {
+            let label = packet.get(pos + 1..pos + 1 + len)?;
+            labels.push(String::from_utf8_lossy(label).into_owned());
+            pos += 1 + len;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 50));
+
+    fn name(name: &str) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for label in name.split('.') {
+            encoded.push(label.len() as u8);
+            encoded.extend_from_slice(label.as_bytes());
+        }
+        encoded.push(0);
+        encoded
+    }
+
+    fn pointer(offset: usize) -> Vec<u8> {
+        vec![0xC0 | (offset >> 8) as u8, offset as u8]
+    }
+
+    fn record(name: &[u8], record_type: u16, data: &[u8]) -> Vec<u8> {
+        let mut record = name.to_vec();
+        record.extend_from_slice(&record_type.to_be_bytes());
+        record.extend_from_slice(&[0x80, 0x01, 0, 0, 0x11, 0x94]);
+        record.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        record.extend_from_slice(data);
+        record
+    }
+
+    fn response(answers: &[Vec<u8>]) -> Vec<u8> {
+        let mut packet = vec![0, 0, 0x84, 0, 0, 0, 0, answers.len() as u8, 0, 0, 0, 0];
+        for answer in answers {
+            packet.extend_from_slice(answer);
+        }
+        packet
+    }
+
+    fn parse(packet: &[u8]) -> Option<Records> {
+        let mut records = Records::default();
+        parse_response(packet, SOURCE, &mut records).map(|_| records)
+    }
+
+    #[test]
+    fn reads_a_complete_announcement() {
+        // The service type is at 12, its 23 bytes and the fixed fields put the instance name at 45
+        let ptr = record(&name(OPCUA_SERVICE), TYPE_PTR, &[&[5][..], b"PLC-1", &pointer(12)].concat());
+        let srv = record(&pointer(45), TYPE_SRV, &[&[0, 0, 0, 0, 0x12, 0x35][..], &name("plc1.local")].concat());
+        let txt = record(&pointer(45), TYPE_TXT, &[&[8][..], b"path=/ua", &[0], &[12], b"caps=LDS, DA"].concat());
+        let a = record(&name("PLC1.local"), TYPE_A, &[192, 168, 1, 10]);
+        let records = parse(&response(&[ptr, srv, txt, a])).unwrap();
+        assert_eq!(records.instances, ["PLC-1._opcua-tcp._tcp.local"]);
+
+        let servers = records.servers();
+        assert_eq!(servers.len(), 1);
+        let server = &servers[0];
+        assert_eq!(server.instance, "PLC-1");
+        assert_eq!(server.hostname, "plc1.local");
+        assert_eq!(server.capabilities, ["LDS", "DA"]);
+        assert_eq!(server.url(), "opc.tcp://192.168.1.10:4661/ua");
+    }
+
+    #[test]
+    fn servers_without_addresses_use_the_source() {
+        let ptr = record(&name(OPCUA_SERVICE), TYPE_PTR, &name("PLC-2._opcua-tcp._tcp.local"));
+        let srv = record(&name("PLC-2._opcua-tcp._tcp.local"), TYPE_SRV, &[&[0, 0, 0, 0, 0x12, 0x34][..], &name("plc2.local")].concat());
+        let servers = parse(&response(&[ptr, srv])).unwrap().servers();
+        assert_eq!(servers[0].url(), "opc.tcp://192.168.1.50:4660");
+    }
+
+    #[test]
+    fn follows_compression_pointers() {
+        let mut packet = vec![0; 12];
+        packet.extend_from_slice(&name("plc.local"));
+        packet.extend_from_slice(&[3]);
+        packet.extend_from_slice(b"www");
+        packet.extend_from_slice(&pointer(12));
+        assert_eq!(read_name(&packet, 12), Some(("plc.local".to_string(), 23)));
+        // The name ends after the first pointer, wherever it leads
+        assert_eq!(read_name(&packet, 23), Some(("www.plc.local".to_string(), 29)));
+        packet.extend_from_slice(&pointer(23));
+        assert_eq!(read_name(&packet, 29), Some(("www.plc.local".to_string(), 31)));
+    }
+
+    #[test]
+    fn pointer_loops_are_malformed() {
+        let mut packet = vec![0; 12];
+        packet.extend_from_slice(&pointer(12));
+        assert_eq!(read_name(&packet, 12), None);
+
+        let mut packet = vec![0; 12];
+        packet.extend_from_slice(&[1, b'a']);
+        packet.extend_from_slice(&pointer(16));
+        packet.extend_from_slice(&pointer(12));
+        assert_eq!(read_name(&packet, 12), None);
+    }
+
+    #[test]
+    fn oversized_labels_are_malformed() {
+        let mut packet = name("plc.local");
+        packet[0] = 64;
+        assert_eq!(read_name(&packet, 0), None);
+        packet[0] = 0x80;
+        assert_eq!(read_name(&packet, 0), None);
+    }
+
+    #[test]
+    fn truncated_packets_are_malformed() {
+        assert!(parse(&[0, 0, 0x84, 0, 0, 0]).is_none());
+
+        let a = record(&name("plc1.local"), TYPE_A, &[192, 168, 1, 10]);
+        let packet = response(&[a]);
+        assert!(parse(&packet).is_some());
+        // Cut in the data, in the fixed fields and in the name
+        for len in [packet.len() - 1, packet.len() - 6, 16] {
+            assert!(parse(&packet[..len]).is_none(), "cut at {}", len);
+        }
+        // A name pointing past the end
+        assert!(parse(&response(&[record(&pointer(500), TYPE_A, &[192, 168, 1, 10])])).is_none());
+        // A TXT entry longer than the record
+        assert!(parse(&response(&[record(&name("plc1.local"), TYPE_TXT, &[9, b'c', b'a', b'p', b's'])])).is_none());
+    }
+
+    #[test]
+    fn queries_are_ignored() {
+        let mut packet = response(&[record(&name("plc1.local"), TYPE_A, &[192, 168, 1, 10])]);
+        packet[2] = 0;
+        let records = parse(&packet).unwrap();
+        assert!(records.addresses.is_empty());
+    }
+
+    #[test]
+    fn queries_skip_empty_labels() {
+        let packet = query(&[("plc.local.".to_string(), TYPE_A)]);
+        assert_eq!(&packet[4..6], [0, 1]);
+        assert_eq!(read_name(&packet, 12), Some(("plc.local".to_string(), 23)));
+        assert_eq!(&packet[23..], [0, 1, 0x80, 0x01]);
+    }
+}
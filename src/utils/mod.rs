@@ -1,2 +1,36 @@
+pub mod archive;
+pub mod audit;
+pub mod browse_request;
+pub mod bytestring;
+pub mod cert;
+pub mod change_stats;
+pub mod client_cert;
+pub mod endpoint;
+pub mod enums;
+pub mod expand;
 pub mod formatter;
-pub mod search;
\ No newline at end of file
+pub mod guard;
+pub mod index_range;
+pub mod influx;
+pub mod labels;
+pub mod limits;
+pub mod live_values;
+pub mod mdns;
+pub mod namespace;
+pub mod nodeset;
+pub mod outcome;
+pub mod proxy;
+pub mod recording;
+pub mod reference_types;
+pub mod schema;
+pub mod search;
+pub mod security;
+pub mod shutdown;
+pub mod snapshot;
+pub mod sort;
+pub mod subscription;
+pub mod table_layout;
+pub mod terminal;
+pub mod time;
+pub mod units;
+pub mod value_map;
\ No newline at end of file
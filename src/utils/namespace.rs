@@ -0,0 +1,412 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use opcua::types::*;
+use std::fmt;
+use std::fs;
+use tracing::debug;
+
+use crate::session::ServiceSession;
+use crate::utils::formatter::{format_identifier, format_node_id, parse_node_id_text};
+use crate::utils::labels::resolve_label;
+use crate::utils::outcome::OperationResult;
+
+/// A node ID as given on the command line, either `ns=<index>;...` or `nsu=<uri>;...`
+#[derive(Debug, Clone)]
+pub struct NodeSpec {
+    pub namespace_uri: Option<String>,
+    pub node_id: NodeId,
+    /// Given by a `--labels` name rather than a node ID
+    pub by_label: bool,
+}
+
+impl fmt::Display for NodeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.namespace_uri {
+            Some(uri) => write!(f, "nsu={};{}", uri, format_identifier(&self.node_id.identifier)),
+            None => write!(f, "{}", format_node_id(&self.node_id)),
+        }
+    }
+}
+
+pub fn parse_node_spec(node_str: &str) -> Result<NodeSpec> {
+    if let Some(rest) = node_str.strip_prefix("nsu=") {
+        let (uri, identifier) = rest
+            .split_once(';')
+            .ok_or_else(|| anyhow!("Invalid node ID format: {}", node_str))?;
+        let node_id = parse_node_id_text(identifier)
+            .filter(|node_id| node_id.namespace == 0)
+            .ok_or_else(|| anyhow!("Invalid node ID format: {}", node_str))?;
+        return Ok(NodeSpec {
+            namespace_uri: Some(uri.to_string()),
+            node_id,
+            by_label: false,
+        });
+    }
+    
+    if let Some(node_id) = parse_node_id_text(node_str) {
+        return Ok(NodeSpec {
+            namespace_uri: None,
+            node_id,
+            by_label: false,
+        });
+    }
+    let node_id = resolve_label(node_str)
+        .ok_or_else(|| anyhow!("Invalid node ID format: {}", node_str))?;
+    Ok(NodeSpec {
+        namespace_uri: None,
+        node_id,
+        by_label: true,
+    })
+}
+
+/// A node ID as given on the command line: `ns=<index>;...`, a well-known folder such as
+/// `objects`, or a `--labels` name
+pub fn parse_node_id(node_str: &str) -> Result<NodeId> {
+    // Try to parse as standard node ID format (ns=X;i=Y, ns=X;s=Y, etc.)
+    if let Some(node_id) = parse_node_id_text(node_str) {
+        return Ok(node_id);
+    }
+
+    // Try common object IDs
+    match node_str.to_lowercase().as_str() {
+        "objects" | "objectsfolder" => Ok(ObjectId::ObjectsFolder.into()),
+        "server" => Ok(ObjectId::Server.into()),
+        "types" | "typesfolder" => Ok(ObjectId::TypesFolder.into()),
+        "views" | "viewsfolder" => Ok(ObjectId::ViewsFolder.into()),
+        "root" => Ok(ObjectId::RootFolder.into()),
+        _ => resolve_label(node_str).ok_or_else(|| anyhow!("Invalid node ID format: {}", node_str)),
+    }
+}
+
+/// Namespace translations loaded with `--ns-map`, for comparing servers that register the
+/// same model under different URIs or indices.
+///
+/// Each non-empty line of the file is `<from> = <to>` with either two namespace URIs or two
+/// indices; `#` starts a comment.
+#[derive(Debug, Clone, Default)]
+pub struct NamespaceMap {
+    uris: Vec<(String, String)>,
+    indices: Vec<(u16, u16)>,
+}
+
+impl NamespaceMap {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read namespace map {}: {}", path, e))?;
+        
+        let mut map = NamespaceMap::default();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            
+            let (from, to) = line
+                .split_once('=')
+                .map(|(from, to)| (from.trim(), to.trim()))
+                .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                .ok_or_else(|| anyhow!("{}:{}: expected '<from> = <to>'", path, number + 1))?;
+            
+            match (from.parse::<u16>(), to.parse::<u16>()) {
+                (Ok(from), Ok(to)) => map.indices.push((from, to)),
+                (Err(_), Err(_)) => map.uris.push((from.to_string(), to.to_string())),
+                _ => return Err(anyhow!("{}:{}: cannot map between a namespace index and a URI", path, number + 1)),
+            }
+        }
+        
+        Ok(map)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.uris.is_empty() && self.indices.is_empty()
+    }
+    
+    pub fn map_uri<'a>(&'a self, uri: &'a str) -> &'a str {
+        self.uris
+            .iter()
+            .find(|(from, _)| from == uri)
+            .map(|(_, to)| to.as_str())
+            .unwrap_or(uri)
+    }
+    
+    pub fn map_index(&self, index: u16) -> u16 {
+        self.indices
+            .iter()
+            .find(|(from, _)| *from == index)
+            .map(|(_, to)| *to)
+            .unwrap_or(index)
+    }
+    
+    /// Turn a node spec into a node ID of the server with the given namespace array.
+    /// Namespace URIs without a mapping are looked up as they are.
+    pub fn resolve(&self, spec: &NodeSpec, namespaces: &[String]) -> Result<NodeId> {
+        let mut node_id = spec.node_id.clone();
+        
+        node_id.namespace = match &spec.namespace_uri {
+            Some(uri) => {
+                let uri = self.map_uri(uri);
+                namespaces
+                    .iter()
+                    .position(|ns| ns == uri)
+                    .ok_or_else(|| anyhow!("Namespace {} is not registered on the server", uri))? as u16
+            }
+            None => self.map_index(node_id.namespace),
+        };
+        
+        Ok(node_id)
+    }
+}
+
+/// Read the server's namespace array
+pub async fn read_namespace_array(session: &ServiceSession) -> Result<Vec<String>> {
+    let read_request = ReadValueId::from(&NodeId::from(VariableId::Server_NamespaceArray));
+    let results = session.read(&[read_request], TimestampsToReturn::Neither, 0.0).await?;
+    
+    // Nano profile servers may not have the node at all
+    if let Some(status) = results.first().and_then(|dv| dv.status).filter(|status| !status.is_good()) {
+        return Err(anyhow::Error::new(OperationResult::of_status(status)).context("Server did not return its namespace array"));
+    }
+    results.first()
+        .and_then(|dv| dv.value.as_ref())
+        .and_then(parse_namespace_array)
+        .ok_or_else(|| anyhow!("Server did not return its namespace array"))
+}
+
+/// The URIs of a Server_NamespaceArray value, `None` unless it is an array.
+///
+/// Entries that are no strings become empty so the positions keep matching the namespace indices.
+pub fn parse_namespace_array(value: &Variant) -> Option<Vec<String>> {
+    match value {
+        Variant::Array(array) => Some(array.values.iter()
+            .map(|v| match v {
+                Variant::String(s) => s.as_ref().to_string(),
+                _ => String::new(),
+            })
+            .collect()),
+        _ => None,
+    }
+}
+
+/// Check whether a node exists by reading its NodeClass attribute
+pub async fn node_exists(session: &ServiceSession, node_id: &NodeId) -> Result<bool> {
+    let read_request = ReadValueId {
+        node_id: node_id.clone(),
+        attribute_id: AttributeId::NodeClass as u32,
+        ..Default::default()
+    };
+    
+    let results = session.read(&[read_request], TimestampsToReturn::Neither, 0.0).await?;
+    Ok(!results.first().and_then(|dv| dv.status).is_some_and(|s| s == StatusCode::BadNodeIdUnknown))
+}
+
+/// Find namespaces other than the requested one that contain the same string identifier.
+/// `namespaces` is the server's namespace array, usually the one [`OpcUaClient::namespaces`]
+/// cached for the session.
+///
+/// [`OpcUaClient::namespaces`]: crate::client::OpcUaClient::namespaces
+pub async fn find_in_other_namespaces(session: &ServiceSession, node_id: &NodeId, namespaces: &[String]) -> Result<Vec<(NodeId, String)>> {
+    if !matches!(node_id.identifier, Identifier::String(_)) {
+        return Ok(Vec::new());
+    }
+    
+    let candidates: Vec<(NodeId, String)> = namespaces
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != node_id.namespace as usize)
+        .map(|(index, uri)| {
+            let mut candidate = node_id.clone();
+            candidate.namespace = index as u16;
+            (candidate, uri.clone())
+        })
+        .collect();
+    
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    let read_requests: Vec<ReadValueId> = candidates
+        .iter()
+        .map(|(candidate, _)| ReadValueId {
+            node_id: candidate.clone(),
+            attribute_id: AttributeId::NodeClass as u32,
+            ..Default::default()
+        })
+        .collect();
+    
+    let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+    debug!("Checked {} alternative namespace(s) for {}", candidates.len(), format_node_id(node_id));
+    
+    Ok(candidates
+        .into_iter()
+        .zip(results)
+        .filter(|(_, dv)| dv.status.is_none_or(|s| s.is_good()))
+        .map(|(candidate, _)| candidate)
+        .collect())
+}
+
+/// Explain a BadNodeIdUnknown caused by a wrong namespace index, returning the
+/// replacement node ID when `auto_namespace` allows the substitution
+pub async fn suggest_namespace(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    namespaces: &[String],
+    auto_namespace: bool,
+) -> Result<Option<NodeId>> {
+    let alternatives = find_in_other_namespaces(session, node_id, namespaces).await?;
+    
+    match alternatives.as_slice() {
+        [] => Ok(None),
+        [(candidate, uri)] => {
+            if auto_namespace {
+                println!("↪️  Node not found in ns={}, using {} ({}) instead",
+                         node_id.namespace,
+                         format_node_id(candidate).bright_white(),
+                         uri.dimmed());
+                Ok(Some(candidate.clone()))
+            } else {
+                println!("💡 Node not found in ns={}, but an identical identifier exists in ns={} ({}); use {} to accept it automatically",
+                         node_id.namespace,
+                         candidate.namespace,
+                         uri,
+                         "--auto-namespace".bright_white());
+                Ok(None)
+            }
+        }
+        _ => {
+            let found: Vec<String> = alternatives
+                .iter()
+                .map(|(candidate, uri)| format!("ns={} ({})", candidate.namespace, uri))
+                .collect();
+            println!("💡 Node not found in ns={}, but the identifier exists in several namespaces: {}",
+                     node_id.namespace, found.join(", "));
+            Ok(None)
+        }
+    }
+}
+
+/// Return the node ID to use, substituting the namespace when the original is unknown
+pub async fn resolve_node_namespace(
+    session: &ServiceSession,
+    node_id: &NodeId,
+    namespaces: &[String],
+    auto_namespace: bool,
+) -> Result<NodeId> {
+    if !matches!(node_id.identifier, Identifier::String(_)) || node_exists(session, node_id).await? {
+        return Ok(node_id.clone());
+    }
+    
+    Ok(suggest_namespace(session, node_id, namespaces, auto_namespace).await?
+        .unwrap_or_else(|| node_id.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_node(namespace: u16, identifier: &str) -> NodeId {
+        NodeId::new(namespace, identifier.to_string())
+    }
+
+    fn parsed(text: &str) -> NodeId {
+        parse_node_spec(text).unwrap().node_id
+    }
+
+    #[test]
+    fn string_identifiers_with_semicolons_and_equals() {
+        assert_eq!(parsed("ns=3;s=Prg.Cfg;Rev=2"), string_node(3, "Prg.Cfg;Rev=2"));
+        assert_eq!(parsed("ns=2;s=a=b;c=d;"), string_node(2, "a=b;c=d;"));
+        assert_eq!(parsed("ns=2;s=s=1"), string_node(2, "s=1"));
+        assert_eq!(parsed("s=Rev=2;ns=3"), string_node(0, "Rev=2;ns=3"));
+    }
+
+    #[test]
+    fn string_identifiers_with_spaces_and_unicode() {
+        assert_eq!(parsed("ns=2;s=Line 1 Motor"), string_node(2, "Line 1 Motor"));
+        assert_eq!(parsed("ns=2;s=Temperatur Kessel °C"), string_node(2, "Temperatur Kessel °C"));
+        assert_eq!(parsed("ns=2;s=Förderband.Drehzahl"), string_node(2, "Förderband.Drehzahl"));
+        assert_eq!(parsed("ns=2;s=\" Padded \""), string_node(2, " Padded "));
+    }
+
+    #[test]
+    fn namespace_uris_with_string_identifiers() {
+        let spec = parse_node_spec("nsu=urn:plant:line1;s=Prg.Cfg;Rev=2").unwrap();
+        assert_eq!(spec.namespace_uri.as_deref(), Some("urn:plant:line1"));
+        assert_eq!(spec.node_id, string_node(0, "Prg.Cfg;Rev=2"));
+    }
+
+    #[test]
+    fn quoted_identifiers() {
+        assert_eq!(parsed("ns=3;s=\"literal;with=chars\""), string_node(3, "literal;with=chars"));
+        assert_eq!(parsed("ns=3;s=\"say \\\"hi\\\"\""), string_node(3, "say \"hi\""));
+        assert_eq!(parsed("ns=3;s=\"\\\"\""), string_node(3, "\""));
+        assert_eq!(parsed("ns=3;s=\"C:\\\\Recipes\""), string_node(3, "C:\\Recipes"));
+        assert_eq!(parsed("ns=3;s=\"\""), string_node(3, ""));
+    }
+
+    #[test]
+    fn unterminated_quotes_are_errors() {
+        for text in ["ns=3;s=\"abc", "ns=3;s=\"", "ns=3;s=\"abc\\\"", "ns=3;s=\"a\"b\"", "nsu=urn:plant;s=\"abc"] {
+            assert!(parse_node_spec(text).is_err(), "{} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn invalid_node_ids_are_errors() {
+        for text in ["ns=3;s=", "ns=x;s=abc", "ns=3", "ns=70000;i=1", "nsu=urn:plant", "Temperature"] {
+            assert!(parse_node_spec(text).is_err(), "{} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn node_ids_and_well_known_folders() {
+        assert_eq!(parse_node_id("ns=3;s=Prg.Cfg;Rev=2").unwrap(), string_node(3, "Prg.Cfg;Rev=2"));
+        assert_eq!(parse_node_id("Objects").unwrap(), NodeId::from(ObjectId::ObjectsFolder));
+        assert_eq!(parse_node_id("typesfolder").unwrap(), NodeId::from(ObjectId::TypesFolder));
+        assert_eq!(parse_node_id("root").unwrap(), NodeId::from(ObjectId::RootFolder));
+        assert!(parse_node_id("Temperature").is_err());
+    }
+
+    #[test]
+    fn formatted_identifiers_read_back() {
+        for identifier in [
+            "Prg.Cfg;Rev=2", "a=b", "Line 1 Motor", "Förderband °C", " leading", "trailing ", "",
+            "\"quoted\"", "\"half", "say \"hi\"", "Axis{1..4}", "C:\\Recipes",
+        ] {
+            let node_id = string_node(3, identifier);
+            let text = format_node_id(&node_id);
+            assert_eq!(parsed(&text), node_id, "{} does not read back", text);
+        }
+    }
+
+    #[test]
+    fn namespace_arrays_keep_their_indices() {
+        let value = Variant::from((VariantScalarTypeId::String, vec![
+            Variant::from("http://opcfoundation.org/UA/"),
+            Variant::from("urn:plant"),
+        ]));
+        assert_eq!(parse_namespace_array(&value).unwrap(), ["http://opcfoundation.org/UA/", "urn:plant"]);
+
+        // A broken entry must not shift the URIs after it to a lower index
+        let value = Variant::Array(Box::new(Array {
+            value_type: VariantScalarTypeId::String,
+            values: vec![
+                Variant::from("http://opcfoundation.org/UA/"),
+                Variant::Int32(7),
+                Variant::Empty,
+                Variant::String(UAString::null()),
+                Variant::from("urn:plant"),
+            ],
+            dimensions: None,
+        }));
+        assert_eq!(parse_namespace_array(&value).unwrap(), ["http://opcfoundation.org/UA/", "", "", "", "urn:plant"]);
+    }
+
+    #[test]
+    fn namespace_arrays_must_be_arrays() {
+        assert_eq!(parse_namespace_array(&Variant::Empty), None);
+        assert_eq!(parse_namespace_array(&Variant::from("urn:plant")), None);
+        assert_eq!(parse_namespace_array(&Variant::Int32(1)), None);
+        let empty = Variant::from((VariantScalarTypeId::String, Vec::<Variant>::new()));
+        assert_eq!(parse_namespace_array(&empty), Some(Vec::new()));
+    }
+}
@@ -0,0 +1,269 @@
+use base64::prelude::*;
+use opcua::types::*;
+use std::fmt::Write;
+
+const NODESET_NAMESPACE: &str = "http://opcfoundation.org/UA/2011/03/UANodeSet.xsd";
+const TYPES_NAMESPACE: &str = "http://opcfoundation.org/UA/2008/02/Types.xsd";
+
+/// A browsed node ready to be written as a UAObject, UAVariable or UAMethod element
+pub struct NodeSetNode {
+    pub node_id: NodeId,
+    pub node_class: NodeClass,
+    pub browse_name: QualifiedName,
+    pub display_name: LocalizedText,
+    pub description: Option<LocalizedText>,
+    pub parent_node_id: Option<NodeId>,
+    pub references: Vec<NodeSetReference>,
+    pub variable: Option<NodeSetVariable>,
+}
+
+pub struct NodeSetReference {
+    pub reference_type: NodeId,
+    pub target: NodeId,
+    pub is_forward: bool,
+}
+
+/// Variable specific attributes, only present for UAVariable elements
+pub struct NodeSetVariable {
+    pub data_type: NodeId,
+    pub value_rank: i32,
+    pub array_dimensions: Option<Vec<u32>>,
+    pub access_level: u8,
+    pub value: Option<Variant>,
+}
+
+/// Serialize nodes into a UANodeSet document.
+///
+/// `namespaces` is the server's namespace array; index 0 is the OPC UA namespace and is
+/// not written, so the remaining indices line up with the server's own numbering.
+pub fn write_nodeset(namespaces: &[String], nodes: &[NodeSetNode]) -> String {
+    let mut xml = String::new();
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<UANodeSet xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xmlns:uax=\"{}\" xmlns=\"{}\" LastModified=\"{}\">",
+        TYPES_NAMESPACE,
+        NODESET_NAMESPACE,
+        DateTime::now().as_chrono().to_rfc3339()
+    );
+
+    if namespaces.len() > 1 {
+        xml.push_str("  <NamespaceUris>\n");
+        for uri in &namespaces[1..] {
+            let _ = writeln!(xml, "    <Uri>{}</Uri>", escape(uri));
+        }
+        xml.push_str("  </NamespaceUris>\n");
+    }
+
+    for node in nodes {
+        write_node(&mut xml, node);
+    }
+
+    xml.push_str("</UANodeSet>\n");
+    xml
+}
+
+fn write_node(xml: &mut String, node: &NodeSetNode) {
+    let element = match node.node_class {
+        NodeClass::Object => "UAObject",
+        NodeClass::Variable => "UAVariable",
+        NodeClass::Method => "UAMethod",
+        _ => return,
+    };
+
+    let _ = write!(
+        xml,
+        "  <{} NodeId=\"{}\" BrowseName=\"{}\"",
+        element,
+        escape(&nodeset_node_id(&node.node_id)),
+        escape(&nodeset_browse_name(&node.browse_name))
+    );
+    if let Some(parent) = &node.parent_node_id {
+        let _ = write!(xml, " ParentNodeId=\"{}\"", escape(&nodeset_node_id(parent)));
+    }
+    if let Some(variable) = &node.variable {
+        let _ = write!(xml, " DataType=\"{}\"", escape(&nodeset_node_id(&variable.data_type)));
+        if variable.value_rank != -1 {
+            let _ = write!(xml, " ValueRank=\"{}\"", variable.value_rank);
+        }
+        if let Some(dimensions) = variable.array_dimensions.as_ref().filter(|d| !d.is_empty()) {
+            let dimensions: Vec<String> = dimensions.iter().map(|d| d.to_string()).collect();
+            let _ = write!(xml, " ArrayDimensions=\"{}\"", dimensions.join(","));
+        }
+        if variable.access_level != 1 {
+            let _ = write!(xml, " AccessLevel=\"{}\"", variable.access_level);
+        }
+    }
+    xml.push_str(">\n");
+
+    write_localized_element(xml, "DisplayName", &node.display_name);
+    if let Some(description) = node.description.as_ref().filter(|d| !d.text.is_empty()) {
+        write_localized_element(xml, "Description", description);
+    }
+
+    if !node.references.is_empty() {
+        xml.push_str("    <References>\n");
+        for reference in &node.references {
+            let direction = if reference.is_forward { "" } else { " IsForward=\"false\"" };
+            let _ = writeln!(
+                xml,
+                "      <Reference ReferenceType=\"{}\"{}>{}</Reference>",
+                escape(&nodeset_node_id(&reference.reference_type)),
+                direction,
+                escape(&nodeset_node_id(&reference.target))
+            );
+        }
+        xml.push_str("    </References>\n");
+    }
+
+    if let Some(value) = node.variable.as_ref().and_then(|v| v.value.as_ref())
+        && let Some(encoded) = encode_value(value)
+    {
+        let _ = writeln!(xml, "    <Value>{}</Value>", encoded);
+    }
+
+    let _ = writeln!(xml, "  </{}>", element);
+}
+
+fn write_localized_element(xml: &mut String, name: &str, text: &LocalizedText) {
+    if text.locale.is_empty() {
+        let _ = writeln!(xml, "    <{0}>{1}</{0}>", name, escape(text.text.as_ref()));
+    } else {
+        let _ = writeln!(
+            xml,
+            "    <{0} Locale=\"{1}\">{2}</{0}>",
+            name,
+            escape(text.locale.as_ref()),
+            escape(text.text.as_ref())
+        );
+    }
+}
+
+/// Encode simple built-in values; structures and other complex types are left out
+fn encode_value(value: &Variant) -> Option<String> {
+    match value {
+        Variant::Array(array) if array.dimensions.as_ref().is_none_or(|d| d.len() <= 1) => {
+            let type_name = scalar_type_name(array.value_type)?;
+            let items = array.values.iter()
+                .map(encode_scalar)
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("<uax:ListOf{0}>{1}</uax:ListOf{0}>", type_name, items.concat()))
+        }
+        Variant::Array(_) => None,
+        _ => encode_scalar(value),
+    }
+}
+
+fn encode_scalar(value: &Variant) -> Option<String> {
+    let (type_name, content) = match value {
+        Variant::Boolean(v) => ("Boolean", v.to_string()),
+        Variant::SByte(v) => ("SByte", v.to_string()),
+        Variant::Byte(v) => ("Byte", v.to_string()),
+        Variant::Int16(v) => ("Int16", v.to_string()),
+        Variant::UInt16(v) => ("UInt16", v.to_string()),
+        Variant::Int32(v) => ("Int32", v.to_string()),
+        Variant::UInt32(v) => ("UInt32", v.to_string()),
+        Variant::Int64(v) => ("Int64", v.to_string()),
+        Variant::UInt64(v) => ("UInt64", v.to_string()),
+        Variant::Float(v) => ("Float", xml_float(*v as f64)),
+        Variant::Double(v) => ("Double", xml_float(*v)),
+        Variant::String(v) => ("String", escape(v.as_ref())),
+        Variant::DateTime(v) => ("DateTime", v.as_chrono().to_rfc3339()),
+        Variant::Guid(v) => ("Guid", format!("<uax:String>{}</uax:String>", v)),
+        Variant::ByteString(v) => ("ByteString", v.value.as_ref()
+            .map(|bytes| BASE64_STANDARD.encode(bytes))
+            .unwrap_or_default()),
+        Variant::NodeId(v) => ("NodeId", format!("<uax:Identifier>{}</uax:Identifier>", escape(&nodeset_node_id(v)))),
+        Variant::QualifiedName(v) => ("QualifiedName", format!(
+            "<uax:NamespaceIndex>{}</uax:NamespaceIndex><uax:Name>{}</uax:Name>",
+            v.namespace_index,
+            escape(v.name.as_ref())
+        )),
+        Variant::LocalizedText(v) => ("LocalizedText", format!(
+            "<uax:Locale>{}</uax:Locale><uax:Text>{}</uax:Text>",
+            escape(v.locale.as_ref()),
+            escape(v.text.as_ref())
+        )),
+        Variant::StatusCode(v) => ("StatusCode", format!("<uax:Code>{}</uax:Code>", v.bits())),
+        _ => return None,
+    };
+    Some(format!("<uax:{0}>{1}</uax:{0}>", type_name, content))
+}
+
+fn scalar_type_name(type_id: VariantScalarTypeId) -> Option<&'static str> {
+    Some(match type_id {
+        VariantScalarTypeId::Boolean => "Boolean",
+        VariantScalarTypeId::SByte => "SByte",
+        VariantScalarTypeId::Byte => "Byte",
+        VariantScalarTypeId::Int16 => "Int16",
+        VariantScalarTypeId::UInt16 => "UInt16",
+        VariantScalarTypeId::Int32 => "Int32",
+        VariantScalarTypeId::UInt32 => "UInt32",
+        VariantScalarTypeId::Int64 => "Int64",
+        VariantScalarTypeId::UInt64 => "UInt64",
+        VariantScalarTypeId::Float => "Float",
+        VariantScalarTypeId::Double => "Double",
+        VariantScalarTypeId::String => "String",
+        VariantScalarTypeId::DateTime => "DateTime",
+        VariantScalarTypeId::Guid => "Guid",
+        VariantScalarTypeId::ByteString => "ByteString",
+        VariantScalarTypeId::NodeId => "NodeId",
+        VariantScalarTypeId::QualifiedName => "QualifiedName",
+        VariantScalarTypeId::LocalizedText => "LocalizedText",
+        VariantScalarTypeId::StatusCode => "StatusCode",
+        _ => return None,
+    })
+}
+
+/// xs:float/xs:double spell infinities and NaN differently than Rust
+fn xml_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "INF" } else { "-INF" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// NodeSet node IDs omit the namespace prefix for the OPC UA namespace
+fn nodeset_node_id(node_id: &NodeId) -> String {
+    let identifier = match &node_id.identifier {
+        Identifier::Numeric(id) => format!("i={}", id),
+        Identifier::String(id) => format!("s={}", id.as_ref()),
+        Identifier::Guid(id) => format!("g={}", id),
+        Identifier::ByteString(id) => format!("b={}", BASE64_STANDARD.encode(id.as_ref())),
+    };
+
+    if node_id.namespace == 0 {
+        identifier
+    } else {
+        format!("ns={};{}", node_id.namespace, identifier)
+    }
+}
+
+fn nodeset_browse_name(name: &QualifiedName) -> String {
+    if name.namespace_index == 0 {
+        name.name.as_ref().to_string()
+    } else {
+        format!("{}:{}", name.namespace_index, name.name.as_ref())
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab and line breaks are not allowed in XML 1.0
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
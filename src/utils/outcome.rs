@@ -0,0 +1,242 @@
+use colored::*;
+use opcua::types::{DataValue, StatusCode};
+
+use crate::client::{ConnectError, ConnectFailure};
+use crate::utils::formatter::{format_status_code, Severity};
+
+/// Who produced a result: the client itself, the server for a whole service call, or the server
+/// for a single operation of a call. Text output gives each its own prefix and JSON output its
+/// `kind`, so that a dropped connection is not mistaken for a Bad value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OperationResult {
+    Good,
+    /// Non-Good status of one operation, e.g. one node of a Read
+    OperationBad(StatusCode),
+    /// The server answered the whole service call with a ServiceFault
+    ServiceFault(StatusCode),
+    /// The request never got an answer: transport errors, timeouts, connection setup
+    ClientError(String),
+}
+
+/// Exit code of failures that are neither service faults nor client errors
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code when the server answered a service call with a Bad status
+pub const EXIT_SERVICE_FAULT: i32 = 3;
+/// Exit code when the server could not be reached or did not answer
+pub const EXIT_CLIENT_ERROR: i32 = 4;
+
+impl OperationResult {
+    /// Result of an operation, as reported in its status code
+    pub fn of_status(status: StatusCode) -> Self {
+        if status.is_good() {
+            OperationResult::Good
+        } else {
+            OperationResult::OperationBad(status)
+        }
+    }
+
+    /// Result of a read, a missing status means Good
+    pub fn of_data_value(data_value: &DataValue) -> Self {
+        data_value.status.map_or(OperationResult::Good, Self::of_status)
+    }
+
+    /// Result of a service call that failed as a whole. The stack reports transport failures
+    /// with status codes as well, those are client errors.
+    pub fn of_service_error(status: StatusCode) -> Self {
+        if is_client_side(status) {
+            OperationResult::ClientError(status.to_string())
+        } else {
+            OperationResult::ServiceFault(status)
+        }
+    }
+
+    /// Classify an error a command failed with, `None` for errors of neither kind
+    pub fn of_error(error: &anyhow::Error) -> Option<Self> {
+        if let Some(connect_error) = error.downcast_ref::<ConnectError>() {
+            // Refusals during session setup are the server's answer
+            return Some(match ConnectFailure::classify(connect_error.status()) {
+                ConnectFailure::CertificateUntrusted | ConnectFailure::IdentityRejected | ConnectFailure::TooManySessions => {
+                    OperationResult::ServiceFault(connect_error.status())
+                }
+                _ => OperationResult::ClientError(connect_error.to_string()),
+            });
+        }
+        if let Some(result) = error.downcast_ref::<OperationResult>() {
+            return Some(result.clone());
+        }
+        if let Some(status) = error.downcast_ref::<StatusCode>() {
+            return Some(Self::of_service_error(*status));
+        }
+        error.downcast_ref::<std::io::Error>().map(|e| OperationResult::ClientError(e.to_string()))
+    }
+
+    /// `kind` of the result in JSON output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OperationResult::Good => "good",
+            OperationResult::OperationBad(_) => "operation_bad",
+            OperationResult::ServiceFault(_) => "service_fault",
+            OperationResult::ClientError(_) => "client_error",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            OperationResult::Good => Severity::Good,
+            OperationResult::OperationBad(status) => Severity::of(status),
+            OperationResult::ServiceFault(_) | OperationResult::ClientError(_) => Severity::Bad,
+        }
+    }
+
+    /// Uncertain operations only fail with `--strict`
+    pub fn is_failure(&self) -> bool {
+        self.severity().is_failure()
+    }
+
+    pub fn is_good(&self) -> bool {
+        *self == OperationResult::Good
+    }
+
+    /// Colored text with a prefix per kind
+    pub fn describe(&self) -> String {
+        match self {
+            OperationResult::Good => "✅ Good".green().to_string(),
+            OperationResult::OperationBad(status) => format_status_code(status),
+            OperationResult::ServiceFault(status) => format!("🛑 Service fault ({})", status).red().to_string(),
+            OperationResult::ClientError(message) => format!("🔌 Client error ({})", message).magenta().to_string(),
+        }
+    }
+
+    /// Exit code of a command that failed with this result
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            OperationResult::Good | OperationResult::OperationBad(_) => EXIT_FAILURE,
+            OperationResult::ServiceFault(_) => EXIT_SERVICE_FAULT,
+            OperationResult::ClientError(_) => EXIT_CLIENT_ERROR,
+        }
+    }
+}
+
+/// Plain text with the same prefixes as [`OperationResult::describe`], for tab-separated output
+/// and error messages
+impl std::fmt::Display for OperationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationResult::Good => f.write_str("Good"),
+            OperationResult::OperationBad(status) => write!(f, "{}", status),
+            OperationResult::ServiceFault(status) => write!(f, "ServiceFault: {}", status),
+            OperationResult::ClientError(message) => write!(f, "ClientError: {}", message),
+        }
+    }
+}
+
+/// Commands fail with the result itself so that the exit code follows its kind
+impl std::error::Error for OperationResult {}
+
+/// Status codes the client stack produces itself when a request gets no answer
+fn is_client_side(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BadCommunicationError
+            | StatusCode::BadConnectionClosed
+            | StatusCode::BadNotConnected
+            | StatusCode::BadServerNotConnected
+            | StatusCode::BadSecureChannelClosed
+            | StatusCode::BadTcpEndpointUrlInvalid
+            | StatusCode::BadTimeout
+            | StatusCode::BadRequestTooLarge
+            | StatusCode::BadEncodingLimitsExceeded
+    )
+}
+
+/// Exit code of a failed command, by the kind of error it failed with
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    OperationResult::of_error(error).map_or(EXIT_FAILURE, |result| result.exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use opcua::types::Variant;
+
+    #[test]
+    fn operation_statuses() {
+        assert_eq!(OperationResult::of_status(StatusCode::Good), OperationResult::Good);
+        assert_eq!(OperationResult::of_status(StatusCode::BadNodeIdUnknown), OperationResult::OperationBad(StatusCode::BadNodeIdUnknown));
+        assert_eq!(OperationResult::of_status(StatusCode::UncertainLastUsableValue), OperationResult::OperationBad(StatusCode::UncertainLastUsableValue));
+    }
+
+    #[test]
+    fn data_values() {
+        let mut data_value = DataValue::new_now(Variant::from(1.5f64));
+        data_value.status = None;
+        assert_eq!(OperationResult::of_data_value(&data_value), OperationResult::Good);
+        data_value.status = Some(StatusCode::BadNotReadable);
+        assert_eq!(OperationResult::of_data_value(&data_value), OperationResult::OperationBad(StatusCode::BadNotReadable));
+    }
+
+    #[test]
+    fn service_errors() {
+        for status in [StatusCode::BadTimeout, StatusCode::BadConnectionClosed, StatusCode::BadCommunicationError, StatusCode::BadSecureChannelClosed] {
+            assert_eq!(OperationResult::of_service_error(status), OperationResult::ClientError(status.to_string()));
+        }
+        for status in [StatusCode::BadTooManyOperations, StatusCode::BadSessionIdInvalid, StatusCode::BadServiceUnsupported] {
+            assert_eq!(OperationResult::of_service_error(status), OperationResult::ServiceFault(status));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = anyhow::Error::new(StatusCode::BadServiceUnsupported);
+        assert_eq!(OperationResult::of_error(&error), Some(OperationResult::ServiceFault(StatusCode::BadServiceUnsupported)));
+
+        let error = anyhow::Error::new(StatusCode::BadTimeout).context("Read failed");
+        assert_eq!(OperationResult::of_error(&error), Some(OperationResult::ClientError(StatusCode::BadTimeout.to_string())));
+
+        let error = anyhow::Error::new(OperationResult::OperationBad(StatusCode::BadTypeMismatch));
+        assert_eq!(OperationResult::of_error(&error), Some(OperationResult::OperationBad(StatusCode::BadTypeMismatch)));
+
+        let error = anyhow::Error::new(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset by peer"));
+        assert_eq!(OperationResult::of_error(&error), Some(OperationResult::ClientError("reset by peer".to_string())));
+
+        assert_eq!(OperationResult::of_error(&anyhow!("Invalid node id")), None);
+    }
+
+    #[test]
+    fn kinds_and_exit_codes() {
+        let cases = [
+            (OperationResult::Good, "good", EXIT_FAILURE),
+            (OperationResult::OperationBad(StatusCode::BadNotWritable), "operation_bad", EXIT_FAILURE),
+            (OperationResult::ServiceFault(StatusCode::BadTooManyOperations), "service_fault", EXIT_SERVICE_FAULT),
+            (OperationResult::ClientError("timed out".to_string()), "client_error", EXIT_CLIENT_ERROR),
+        ];
+        for (result, kind, exit) in cases {
+            assert_eq!(result.kind(), kind);
+            assert_eq!(result.exit_code(), exit, "{:?}", result);
+        }
+        assert_eq!(exit_code(&anyhow::Error::new(StatusCode::BadTimeout)), EXIT_CLIENT_ERROR);
+        assert_eq!(exit_code(&anyhow::Error::new(StatusCode::BadUserAccessDenied)), EXIT_SERVICE_FAULT);
+        assert_eq!(exit_code(&anyhow!("Invalid node id")), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn severities() {
+        assert_eq!(OperationResult::Good.severity(), Severity::Good);
+        assert_eq!(OperationResult::OperationBad(StatusCode::UncertainInitialValue).severity(), Severity::Uncertain);
+        assert_eq!(OperationResult::OperationBad(StatusCode::BadNotReadable).severity(), Severity::Bad);
+        assert_eq!(OperationResult::ServiceFault(StatusCode::BadTooManyOperations).severity(), Severity::Bad);
+        assert_eq!(OperationResult::ClientError("timed out".to_string()).severity(), Severity::Bad);
+    }
+
+    #[test]
+    fn text_prefixes() {
+        assert_eq!(OperationResult::Good.to_string(), "Good");
+        assert_eq!(OperationResult::OperationBad(StatusCode::BadNotReadable).to_string(), "BadNotReadable");
+        assert_eq!(OperationResult::ServiceFault(StatusCode::BadTooManyOperations).to_string(), "ServiceFault: BadTooManyOperations");
+        assert_eq!(OperationResult::ClientError("timed out".to_string()).to_string(), "ClientError: timed out");
+
+        assert!(OperationResult::ServiceFault(StatusCode::BadTooManyOperations).describe().contains("Service fault (BadTooManyOperations)"));
+        assert!(OperationResult::ClientError("timed out".to_string()).describe().contains("Client error (timed out)"));
+    }
+}
@@ -0,0 +1,247 @@
+use anyhow::{anyhow, Result};
+use base64::prelude::*;
+use opcua::types::{BinaryDecodable, BinaryEncodable, CallResponse, ContextOwned, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{Cursor, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+
+/// Recorder or recording of the current run, installed once from `--record` or `--replay`
+static TAPE: OnceLock<Tape> = OnceLock::new();
+
+/// Encoding context of recorded requests and responses, the built-in types are all they hold
+static CONTEXT: OnceLock<ContextOwned> = OnceLock::new();
+
+fn context() -> opcua::types::Context<'static> {
+    CONTEXT.get_or_init(ContextOwned::default).context()
+}
+
+enum Tape {
+    Recording(Recorder),
+    Replay(Replay),
+}
+
+/// One service call as a line of the recording file. Requests and responses are kept in the
+/// binary encoding of OPC-UA, which every request and result type has, as base64.
+#[derive(Serialize, Deserialize)]
+struct Exchange {
+    endpoint: String,
+    service: String,
+    request: String,
+    /// The encoded response, absent when the service call failed as a whole
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    response: Option<String>,
+    /// Status of a service call that failed as a whole
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u32>,
+}
+
+/// Answers of one request in the order they were recorded
+type Answers = Vec<Result<String, StatusCode>>;
+
+/// The service calls of a `--record` run, loaded with `--replay`
+#[derive(Clone)]
+pub struct Recording {
+    path: String,
+    /// Keyed by endpoint, service and encoded request
+    answers: HashMap<(String, String, String), Answers>,
+    endpoints: BTreeSet<String>,
+}
+
+impl Recording {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read recording {}: {}", path, e))?;
+
+        let mut recording = Recording { path: path.to_string(), answers: HashMap::new(), endpoints: BTreeSet::new() };
+        for (line_no, line) in content.lines().enumerate().map(|(idx, line)| (idx + 1, line.trim())) {
+            if line.is_empty() {
+                continue;
+            }
+            let exchange: Exchange = serde_json::from_str(line)
+                .map_err(|e| anyhow!("{}:{}: not a recorded service call: {}", path, line_no, e))?;
+            let answer = match (exchange.response, exchange.status) {
+                (Some(response), _) => Ok(response),
+                (None, Some(status)) => Err(StatusCode::from(status)),
+                (None, None) => return Err(anyhow!("{}:{}: the service call has neither a response nor a status", path, line_no)),
+            };
+            recording.endpoints.insert(exchange.endpoint.clone());
+            recording.answers.entry((exchange.endpoint, exchange.service, exchange.request)).or_default().push(answer);
+        }
+
+        if recording.answers.is_empty() {
+            return Err(anyhow!("{} holds no recorded service calls", path));
+        }
+        Ok(recording)
+    }
+}
+
+struct Recorder {
+    file: Mutex<File>,
+    /// Set once writing failed, after that nothing is recorded any more
+    failed: AtomicBool,
+}
+
+struct Replay {
+    recording: Recording,
+    /// How many answers of each request were given, the last one is repeated once all were
+    given: Mutex<HashMap<(String, String, String), usize>>,
+}
+
+/// Write every service call of the run and its result to `path`, replacing an older recording
+pub fn start_recording(path: &Path) -> Result<()> {
+    let file = File::create(path).map_err(|e| anyhow!("Failed to create recording {}: {}", path.display(), e))?;
+    let _ = TAPE.set(Tape::Recording(Recorder { file: Mutex::new(file), failed: AtomicBool::new(false) }));
+    Ok(())
+}
+
+/// Answer the service calls of the run from `recording` instead of a server
+pub fn install_replay(recording: Recording) {
+    let _ = TAPE.set(Tape::Replay(Replay { recording, given: Mutex::default() }));
+}
+
+/// Path of the recording answering this run, `None` when connecting to servers
+pub fn replay_path() -> Option<&'static str> {
+    match TAPE.get()? {
+        Tape::Replay(replay) => Some(&replay.recording.path),
+        Tape::Recording(_) => None,
+    }
+}
+
+/// The recorded endpoint to replay for `endpoint`: that one when it was recorded, otherwise
+/// the only endpoint of the recording
+pub fn replayed_endpoint(endpoint: &str) -> Result<String> {
+    let Some(Tape::Replay(replay)) = TAPE.get() else {
+        return Ok(endpoint.to_string());
+    };
+    let endpoints = &replay.recording.endpoints;
+    if endpoints.contains(endpoint) {
+        return Ok(endpoint.to_string());
+    }
+    match endpoints.iter().collect::<Vec<_>>().as_slice() {
+        [only] => Ok(only.to_string()),
+        recorded => Err(anyhow!(
+            "{} is not in recording {}, it holds {}",
+            endpoint, replay.recording.path, recorded.iter().map(|e| e.as_str()).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// Encoding of the parameters of a service request, the key its answer is recorded under
+#[derive(Default)]
+pub struct RequestKey {
+    bytes: Vec<u8>,
+}
+
+impl RequestKey {
+    pub fn value<T: BinaryEncodable>(mut self, value: &T) -> Self {
+        let _ = value.encode(&mut self.bytes, &context());
+        self
+    }
+
+    pub fn array<T: BinaryEncodable>(self, values: &[T]) -> Self {
+        values.iter().fold(self.value(&(values.len() as i32)), |key, value| key.value(value))
+    }
+}
+
+/// One value in the binary encoding of OPC-UA as base64, as recordings and snapshots keep them
+pub fn encode_base64<T: BinaryEncodable>(value: &T) -> String {
+    BASE64_STANDARD.encode(RequestKey::default().value(value).bytes)
+}
+
+/// A value written by [`encode_base64`], `None` when it does not decode as `T`
+pub fn decode_base64<T: BinaryDecodable>(text: &str) -> Option<T> {
+    let bytes = BASE64_STANDARD.decode(text).ok()?;
+    T::decode(&mut Cursor::new(bytes), &context()).ok()
+}
+
+/// A service response that can be written to a recording and read back
+pub trait Recorded: Sized {
+    fn encode(&self) -> Vec<u8>;
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+impl<T: BinaryEncodable + BinaryDecodable> Recorded for Vec<T> {
+    fn encode(&self) -> Vec<u8> {
+        RequestKey::default().array(self).bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let values = Option::<Vec<T>>::decode(&mut Cursor::new(bytes), &context()).ok()?;
+        Some(values.unwrap_or_default())
+    }
+}
+
+impl Recorded for CallResponse {
+    fn encode(&self) -> Vec<u8> {
+        RequestKey::default().value(self).bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        <CallResponse as BinaryDecodable>::decode(&mut Cursor::new(bytes), &context()).ok()
+    }
+}
+
+/// The recorded answer to a request while replaying, `None` when connected to a server. Requests
+/// the recording does not hold fail with BadNotFound.
+pub fn answer<T: Recorded>(endpoint: &str, service: &str, request: &RequestKey) -> Option<Result<T, StatusCode>> {
+    let Tape::Replay(replay) = TAPE.get()? else {
+        return None;
+    };
+    let key = (endpoint.to_string(), service.to_string(), BASE64_STANDARD.encode(&request.bytes));
+    let Some(answers) = replay.recording.answers.get(&key) else {
+        warn!("{} request to {} is not in recording {}", service, endpoint, replay.recording.path);
+        return Some(Err(StatusCode::BadNotFound));
+    };
+    let mut given = replay.given.lock().unwrap_or_else(|e| e.into_inner());
+    let count = given.entry(key).or_default();
+    let answer = &answers[(*count).min(answers.len() - 1)];
+    *count += 1;
+    Some(match answer {
+        Ok(response) => BASE64_STANDARD
+            .decode(response)
+            .ok()
+            .and_then(|bytes| T::decode(&bytes))
+            .ok_or_else(|| {
+                warn!("The recorded {} response of {} cannot be decoded", service, endpoint);
+                StatusCode::BadDecodingError
+            }),
+        Err(status) => Err(*status),
+    })
+}
+
+/// Add a service call and its result to the recording, when recording
+pub fn record<T: Recorded>(endpoint: &str, service: &str, request: &RequestKey, result: &Result<T, StatusCode>) {
+    let Some(Tape::Recording(recorder)) = TAPE.get() else {
+        return;
+    };
+    if recorder.failed.load(Ordering::Relaxed) {
+        return;
+    }
+    let (response, status) = match result {
+        Ok(response) => (Some(BASE64_STANDARD.encode(response.encode())), None),
+        Err(status) => (None, Some(status.bits())),
+    };
+    let exchange = Exchange {
+        endpoint: endpoint.to_string(),
+        service: service.to_string(),
+        request: BASE64_STANDARD.encode(&request.bytes),
+        response,
+        status,
+    };
+    let Ok(mut line) = serde_json::to_string(&exchange) else {
+        return;
+    };
+    line.push('\n');
+    // One write per line, so a run ended with Ctrl+C leaves whole lines
+    let written = recorder.file.lock().unwrap_or_else(|e| e.into_inner()).write_all(line.as_bytes());
+    if let Err(e) = written
+        && !recorder.failed.swap(true, Ordering::Relaxed)
+    {
+        warn!("Writing the recording failed, later service calls are not recorded: {}", e);
+    }
+}
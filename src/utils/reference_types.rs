@@ -0,0 +1,73 @@
+use anyhow::{anyhow, Result};
+use opcua::types::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+use crate::commands::export::attribute_request;
+use crate::session::ServiceSession;
+use crate::utils::formatter::{format_node_id, localized_text, standard_reference_type_name};
+
+/// Names of the non-standard reference types looked up so far, `None` for types whose names
+/// cannot be read
+#[derive(Default)]
+pub struct ReferenceTypeCache {
+    types: Mutex<HashMap<NodeId, Option<Arc<ReferenceTypeNames>>>>,
+}
+
+/// DisplayName and InverseName of a reference type
+pub struct ReferenceTypeNames {
+    pub name: String,
+    /// `None` for symmetric reference types, which read the same in both directions
+    pub inverse_name: Option<String>,
+}
+
+/// The name of `reference_type` as read in the direction of the reference: `HasComponent` for a
+/// forward reference, `ComponentOf` for an inverse one.
+///
+/// Standard types are named without a server round trip, other types are looked up once per
+/// session. Types whose names cannot be read show as node IDs.
+pub async fn reference_type_name(session: &ServiceSession, reference_type: &NodeId, is_forward: bool) -> String {
+    if let Some(name) = standard_reference_type_name(reference_type, is_forward) {
+        return name;
+    }
+
+    let cached = session.reference_types().types.lock().unwrap().get(reference_type).cloned();
+    let names = match cached {
+        Some(names) => names,
+        None => {
+            let names = match lookup(session, reference_type).await {
+                Ok(names) => Some(Arc::new(names)),
+                Err(e) => {
+                    debug!("Reference type lookup of {} failed: {}", reference_type, e);
+                    None
+                }
+            };
+            session.reference_types().types.lock().unwrap().insert(reference_type.clone(), names.clone());
+            names
+        }
+    };
+
+    match names {
+        Some(names) if !is_forward => names.inverse_name.clone().unwrap_or_else(|| names.name.clone()),
+        Some(names) => names.name.clone(),
+        None => format_node_id(reference_type),
+    }
+}
+
+async fn lookup(session: &ServiceSession, reference_type: &NodeId) -> Result<ReferenceTypeNames> {
+    let requests = [
+        attribute_request(reference_type, AttributeId::DisplayName),
+        attribute_request(reference_type, AttributeId::InverseName),
+    ];
+    let values = session.read(&requests, TimestampsToReturn::Neither, 0.0).await?;
+    let text = |idx: usize| match values.get(idx).and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::LocalizedText(text)) if !localized_text(text).is_empty() => Some(localized_text(text).to_string()),
+        _ => None,
+    };
+
+    Ok(ReferenceTypeNames {
+        name: text(0).ok_or_else(|| anyhow!("No DisplayName"))?,
+        inverse_name: text(1),
+    })
+}
@@ -0,0 +1,401 @@
+use anyhow::{anyhow, Result};
+use opcua::types::*;
+use serde_json::{json, Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+use crate::commands::export::attribute_request;
+use crate::commands::refs::browse_references;
+use crate::session::ServiceSession;
+use crate::utils::archive::ARCHIVE_FORMAT;
+use crate::utils::enums::enum_names;
+use crate::utils::formatter::{format_data_type, format_node_id};
+
+/// Supertypes followed before a DataType is taken for opaque
+const MAX_SUPERTYPE_DEPTH: usize = 16;
+
+/// Structures nested deeper than this are accepted as they are
+const MAX_FIELD_DEPTH: usize = 4;
+
+/// JSON Schema version of the emitted schemas, draft-07 for the widest validator support
+const SCHEMA_DIALECT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// The DataTypes resolved so far, by node ID
+#[derive(Default)]
+pub struct DataTypeCache {
+    types: Mutex<HashMap<NodeId, Arc<DataTypeInfo>>>,
+}
+
+/// What a schema needs to know of a DataType
+struct DataTypeInfo {
+    name: String,
+    /// The type of namespace 0 up to Enumeration its supertype chain reaches, e.g. Double for
+    /// Duration, `None` when the chain cannot be followed
+    base: Option<DataTypeId>,
+    /// The DataTypeDefinition of structures whose server provides one
+    structure: Option<StructureDefinition>,
+}
+
+/// DataType and ValueRank of a variable, the attributes its value schema follows
+pub struct ValueType {
+    pub data_type: Option<NodeId>,
+    pub value_rank: i32,
+}
+
+/// DataType and ValueRank of each node; unreadable ones give an opaque, scalar type
+pub async fn read_value_types(session: &ServiceSession, node_ids: &[NodeId]) -> Result<Vec<ValueType>> {
+    if node_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let requests: Vec<ReadValueId> = node_ids
+        .iter()
+        .flat_map(|node_id| [AttributeId::DataType, AttributeId::ValueRank].map(|attr| attribute_request(node_id, attr)))
+        .collect();
+    let results = session.read(&requests, TimestampsToReturn::Neither, 0.0).await?;
+    Ok(results
+        .chunks(2)
+        .map(|values| ValueType {
+            data_type: match &values[0].value {
+                Some(Variant::NodeId(data_type)) => Some((**data_type).clone()),
+                _ => None,
+            },
+            value_rank: match values.get(1).and_then(|dv| dv.value.as_ref()) {
+                Some(Variant::Int32(rank)) => *rank,
+                _ => -1,
+            },
+        })
+        .collect())
+}
+
+/// Schema of the values of a variable the way [`variant_to_json`](crate::utils::formatter::variant_to_json)
+/// writes them. Null is always allowed, it stands for values with a bad status.
+pub async fn value_schema(session: &ServiceSession, value_type: &ValueType) -> JsonValue {
+    let (scalar, type_name) = match &value_type.data_type {
+        Some(data_type) => (type_schema(session, data_type, 0).await, type_info(session, data_type).await.name.clone()),
+        None => (opaque("the DataType could not be read"), "unknown DataType".to_string()),
+    };
+    let value = match value_type.value_rank {
+        -1 => scalar,
+        // Arrays of any dimension come as one flat array
+        rank if rank >= 0 => json!({ "type": "array", "items": scalar }),
+        _ => json!({ "anyOf": [scalar.clone(), { "type": "array", "items": scalar }] }),
+    };
+    json!({
+        "description": format!("{} ({})", type_name, rank_name(value_type.value_rank)),
+        "anyOf": [{ "type": "null" }, value],
+    })
+}
+
+/// Schema of one record of a `monitor --archive` file: the header, the summary, a publishing state or
+/// a change of one of `nodes`, whose positions are the ones of the header's node list
+pub fn archive_schema(nodes: &[String], values: Map<String, JsonValue>) -> JsonValue {
+    let cases: Vec<JsonValue> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node_id)| values.contains_key(*node_id))
+        .map(|(idx, node_id)| json!({
+            "if": { "properties": { "node": { "const": idx } } },
+            "then": { "properties": { "value": { "$ref": definition_ref(node_id) } } },
+        }))
+        .collect();
+
+    let mut definitions = values;
+    definitions.insert("archiveHeader".to_string(), json!({
+        "type": "object",
+        "required": ["format", "tool_version", "endpoints", "nodes", "start_time", "sequence"],
+        "properties": {
+            "format": { "const": ARCHIVE_FORMAT },
+            "tool_version": { "type": "string" },
+            "endpoints": { "type": "array" },
+            "nodes": { "type": "array", "minItems": nodes.len(), "maxItems": nodes.len() },
+            "start_time": { "type": "string" },
+            "sequence": { "type": "integer", "minimum": 1 },
+        },
+    }));
+    definitions.insert("archiveChange".to_string(), json!({
+        "type": "object",
+        "required": ["node", "value"],
+        "properties": {
+            "node": { "type": "integer", "minimum": 0, "maximum": nodes.len().saturating_sub(1) },
+            "time": { "type": "string" },
+            "value": true,
+            "status": { "type": "string" },
+        },
+        "additionalProperties": false,
+        "allOf": cases,
+    }));
+    definitions.insert("archivePublishing".to_string(), json!({
+        "type": "object",
+        "required": ["publishing"],
+        "properties": {
+            "publishing": {
+                "type": "object",
+                "required": ["endpoint", "state", "time", "silent_secs"],
+                "properties": {
+                    "endpoint": { "type": "integer", "minimum": 0 },
+                    "state": { "enum": ["silent", "recovered"] },
+                    "time": { "type": "string" },
+                    "silent_secs": { "type": "number", "minimum": 0 },
+                },
+            },
+        },
+        "additionalProperties": false,
+    }));
+    definitions.insert("archiveSummary".to_string(), json!({
+        "type": "object",
+        "required": ["summary"],
+        "properties": {
+            "summary": {
+                "type": "object",
+                "required": ["end_time", "duration_secs", "nodes"],
+                "properties": {
+                    "end_time": { "type": "string" },
+                    "duration_secs": { "type": "number", "minimum": 0 },
+                    "nodes": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["node", "notifications", "changes", "bad", "per_minute"],
+                            "properties": {
+                                "node": { "type": "integer", "minimum": 0, "maximum": nodes.len().saturating_sub(1) },
+                                "notifications": { "type": "integer", "minimum": 0 },
+                                "changes": { "type": "integer", "minimum": 0 },
+                                "bad": { "type": "integer", "minimum": 0 },
+                                "first": true,
+                                "last": true,
+                                "min": { "type": "number" },
+                                "max": { "type": "number" },
+                                "avg": { "type": "number" },
+                                "per_minute": { "type": "number", "minimum": 0 },
+                            },
+                        },
+                    },
+                },
+            },
+        },
+        "additionalProperties": false,
+    }));
+
+    json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": "opcua-walker monitor archive record",
+        "description": "One line of a monitor --archive file: the header, a data change, whose value follows the definition of its node ID, a change of an endpoint's publishing state, or the summary ending the run",
+        "definitions": definitions,
+        "oneOf": [
+            { "$ref": "#/definitions/archiveHeader" },
+            { "$ref": "#/definitions/archiveChange" },
+            { "$ref": "#/definitions/archivePublishing" },
+            { "$ref": "#/definitions/archiveSummary" },
+        ],
+    })
+}
+
+/// Schema of an object holding the values of nodes by node ID
+pub fn values_schema(values: Map<String, JsonValue>) -> JsonValue {
+    let properties: Map<String, JsonValue> = values
+        .keys()
+        .map(|node_id| (node_id.clone(), json!({ "$ref": definition_ref(node_id) })))
+        .collect();
+    json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": "opcua-walker node values",
+        "description": "Values of variables by node ID, in the JSON form of monitor archives",
+        "definitions": values,
+        "type": "object",
+        "properties": properties,
+    })
+}
+
+pub fn write_schema(path: &str, schema: &JsonValue) -> Result<()> {
+    let text = serde_json::to_string_pretty(schema)?;
+    fs::write(path, text + "\n").map_err(|e| anyhow!("Failed to write schema {}: {}", path, e))
+}
+
+/// `$ref` to the definition of a node ID: escaped as JSON pointer, then as URI fragment
+fn definition_ref(node_id: &str) -> String {
+    let mut reference = "#/definitions/".to_string();
+    for byte in node_id.replace('~', "~0").replace('/', "~1").bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&byte) {
+            reference.push(byte as char);
+        } else {
+            reference.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    reference
+}
+
+fn rank_name(value_rank: i32) -> String {
+    match value_rank {
+        -3 => "scalar or array".to_string(),
+        -2 => "scalar or array of any dimensions".to_string(),
+        -1 => "scalar".to_string(),
+        0 | 1 => "array".to_string(),
+        rank => format!("array of {} dimensions, flattened", rank),
+    }
+}
+
+/// A schema that accepts anything, saying why
+fn opaque(reason: &str) -> JsonValue {
+    json!({ "$comment": format!("Any value accepted: {}", reason) })
+}
+
+/// Schema of a scalar of `data_type`
+async fn type_schema(session: &ServiceSession, data_type: &NodeId, depth: usize) -> JsonValue {
+    let info = type_info(session, data_type).await;
+    let Some(base) = info.base else {
+        return opaque(&format!("{} derives from no known type", info.name));
+    };
+
+    if (base == DataTypeId::Enumeration || is_integer(base))
+        && let Some(names) = enum_names(session, data_type).await.filter(|names| !names.is_empty())
+    {
+        let values: Vec<JsonValue> = names.iter().map(|(value, name)| json!({ "const": value, "title": name })).collect();
+        return json!({ "title": info.name, "oneOf": values });
+    }
+
+    match base {
+        DataTypeId::Structure => match &info.structure {
+            Some(structure) if depth < MAX_FIELD_DEPTH => {
+                let mut properties = Map::new();
+                let mut required = Vec::new();
+                for field in structure.fields.as_deref().unwrap_or_default() {
+                    let mut schema = Box::pin(type_schema(session, &field.data_type, depth + 1)).await;
+                    if field.value_rank != -1 {
+                        schema = json!({ "type": "array", "items": schema });
+                    }
+                    properties.insert(field.name.to_string(), schema);
+                    if !field.is_optional && structure.structure_type != StructureType::Union {
+                        required.push(field.name.to_string());
+                    }
+                }
+                // Monitor archives carry structures in their text form; the object form
+                // describes the fields for consumers that decode them
+                json!({
+                    "title": info.name,
+                    "anyOf": [
+                        { "type": "string" },
+                        { "type": "object", "properties": properties, "required": required },
+                    ],
+                })
+            }
+            _ => json!({ "title": info.name, "type": "string", "$comment": "Structure in its text form" }),
+        },
+        DataTypeId::BaseDataType => opaque(&format!("{} can hold any type", info.name)),
+        _ => builtin_schema(base),
+    }
+}
+
+/// Schema of a value of a type of namespace 0, as written by `variant_to_json`
+fn builtin_schema(base: DataTypeId) -> JsonValue {
+    let integer = |minimum: i64, maximum: i64| json!({ "type": "integer", "minimum": minimum, "maximum": maximum });
+    match base {
+        DataTypeId::Boolean => json!({ "type": "boolean" }),
+        DataTypeId::SByte => integer(i8::MIN.into(), i8::MAX.into()),
+        DataTypeId::Byte => integer(0, u8::MAX.into()),
+        DataTypeId::Int16 => integer(i16::MIN.into(), i16::MAX.into()),
+        DataTypeId::UInt16 => integer(0, u16::MAX.into()),
+        DataTypeId::Int32 => integer(i32::MIN.into(), i32::MAX.into()),
+        DataTypeId::UInt32 => integer(0, u32::MAX.into()),
+        DataTypeId::Int64 | DataTypeId::Integer | DataTypeId::Enumeration => json!({ "type": "integer" }),
+        DataTypeId::UInt64 | DataTypeId::UInteger => json!({ "type": "integer", "minimum": 0 }),
+        // NaN and infinity become null
+        DataTypeId::Float | DataTypeId::Double | DataTypeId::Number => json!({ "type": ["number", "null"] }),
+        DataTypeId::DateTime => json!({ "type": "string", "$comment": "RFC 3339" }),
+        DataTypeId::ByteString => json!({ "type": "string", "contentEncoding": "base64" }),
+        DataTypeId::LocalizedText => json!({
+            "type": "object",
+            "required": ["locale", "text"],
+            "properties": {
+                "locale": { "type": ["string", "null"] },
+                "text": { "type": "string" },
+            },
+        }),
+        DataTypeId::DiagnosticInfo => json!({
+            "type": "object",
+            "properties": {
+                "symbolic_id": { "type": "integer" },
+                "namespace_uri": { "type": "integer" },
+                "localized_text": { "type": "integer" },
+                "locale": { "type": "integer" },
+                "additional_info": { "type": "string" },
+                "inner_status_code": { "type": "string" },
+                "inner_diagnostic_info": { "type": "object", "$comment": "DiagnosticInfo of the inner operation" },
+            },
+        }),
+        // Node IDs, status codes and the remaining types come in their text form
+        _ => json!({ "type": "string" }),
+    }
+}
+
+/// Integer types, which enumerations with names of their own may derive from as well
+fn is_integer(base: DataTypeId) -> bool {
+    matches!(
+        base,
+        DataTypeId::SByte | DataTypeId::Byte | DataTypeId::Int16 | DataTypeId::UInt16 | DataTypeId::Int32
+            | DataTypeId::UInt32 | DataTypeId::Int64 | DataTypeId::UInt64 | DataTypeId::Integer | DataTypeId::UInteger
+    )
+}
+
+/// Name, base type and structure of `data_type`, looked up once per session
+async fn type_info(session: &ServiceSession, data_type: &NodeId) -> Arc<DataTypeInfo> {
+    if let Some(base) = standard_type(data_type) {
+        return Arc::new(DataTypeInfo { name: format_data_type(data_type), base: Some(base), structure: None });
+    }
+    if let Some(cached) = session.data_types().types.lock().unwrap().get(data_type) {
+        return cached.clone();
+    }
+
+    let info = match lookup(session, data_type).await {
+        Ok(info) => info,
+        Err(e) => {
+            debug!("DataType lookup of {} failed: {}", data_type, e);
+            DataTypeInfo { name: format_data_type(data_type), base: None, structure: None }
+        }
+    };
+    let info = Arc::new(info);
+    session.data_types().types.lock().unwrap().insert(data_type.clone(), info.clone());
+    info
+}
+
+/// The built-in types of namespace 0, Boolean to DiagnosticInfo, the abstract number types
+/// and Enumeration
+fn standard_type(data_type: &NodeId) -> Option<DataTypeId> {
+    match data_type.identifier {
+        Identifier::Numeric(id @ 1..=29) if data_type.namespace == 0 => DataTypeId::try_from(id).ok(),
+        _ => None,
+    }
+}
+
+async fn lookup(session: &ServiceSession, data_type: &NodeId) -> Result<DataTypeInfo> {
+    let requests = [AttributeId::BrowseName, AttributeId::DataTypeDefinition].map(|attr| attribute_request(data_type, attr));
+    let results = session.read(&requests, TimestampsToReturn::Neither, 0.0).await?;
+    let name = match results.first().and_then(|dv| dv.value.as_ref()) {
+        Some(Variant::QualifiedName(name)) if data_type.namespace != 0 => name.name.to_string(),
+        _ => format_data_type(data_type),
+    };
+
+    let mut base = None;
+    let mut current = data_type.clone();
+    for _ in 0..MAX_SUPERTYPE_DEPTH {
+        let supertypes = browse_references(session, &current, BrowseDirection::Inverse, ReferenceTypeId::HasSubtype).await?;
+        let Some(supertype) = supertypes.into_iter().next() else {
+            break;
+        };
+        current = supertype.node_id.node_id;
+        if let Some(standard) = standard_type(&current) {
+            base = Some(standard);
+            break;
+        }
+    }
+
+    let structure = match (base, results.get(1).and_then(|dv| dv.value.as_ref())) {
+        (Some(DataTypeId::Structure), Some(Variant::ExtensionObject(definition))) => definition.inner_as::<StructureDefinition>().cloned(),
+        _ => None,
+    };
+    if base == Some(DataTypeId::Structure) && structure.is_none() {
+        debug!("{} has no StructureDefinition, its fields are not described", format_node_id(data_type));
+    }
+    Ok(DataTypeInfo { name, base, structure })
+}
@@ -1,17 +1,30 @@
 use anyhow::Result;
-use opcua::client::Session;
 use opcua::types::*;
-use std::collections::{HashSet, VecDeque};
-use std::sync::Arc;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::fmt;
 use tracing::{debug, warn};
 
-use crate::utils::formatter::format_node_id;
+use crate::session::ServiceSession;
+use crate::utils::browse_request::BrowseRequest;
+use crate::utils::formatter::{format_node_id, reference_name};
+use crate::utils::limits::descends;
+use crate::utils::outcome::OperationResult;
 
 pub struct SearchConfig {
     pub max_nodes: usize,
     pub max_depth: u32,
     pub search_methods_only: bool,
     pub search_variables_only: bool,
+    /// Return as soon as this many matches were found instead of crawling exhaustively
+    pub stop_after_matches: Option<usize>,
+    /// Explore children of nodes whose names share a prefix with the query first
+    pub prioritize_prefix: bool,
+    /// Return once a name equals the query, since nothing ranks above an exact match
+    pub stop_at_exact_match: bool,
+    /// Nodes the crawl starts from, in this order; each is crawled once even when another
+    /// root reaches it
+    pub search_roots: Vec<NodeId>,
 }
 
 impl Default for SearchConfig {
@@ -21,51 +34,196 @@ impl Default for SearchConfig {
             max_depth: 10,
             search_methods_only: false,
             search_variables_only: false,
+            stop_after_matches: None,
+            prioritize_prefix: false,
+            stop_at_exact_match: false,
+            search_roots: default_search_roots(),
         }
     }
 }
 
+/// Objects, Server and Types, then Root for folders servers add next to them
+pub fn default_search_roots() -> Vec<NodeId> {
+    vec![
+        ObjectId::ObjectsFolder.into(),
+        ObjectId::Server.into(),
+        ObjectId::TypesFolder.into(),
+        ObjectId::RootFolder.into(),
+    ]
+}
+
+/// Standard roots by name, e.g. `ObjectsFolder`, other nodes by node ID
+pub fn format_search_root(node_id: &NodeId) -> String {
+    match node_id.identifier {
+        Identifier::Numeric(id) if node_id.namespace == 0 => ObjectId::try_from(id)
+            .map(|id| format!("{:?}", id))
+            .unwrap_or_else(|_| format_node_id(node_id)),
+        _ => format_node_id(node_id),
+    }
+}
+
 pub struct SearchResult {
     pub node_id: NodeId,
     pub display_name: String,
     pub node_class: NodeClass,
     pub parent_node_id: Option<NodeId>,
+    /// How well the name matches, only set by name searches
+    pub score: Option<SearchScore>,
+    /// The search root the crawl reached the node from, `None` when it was not crawled for
+    pub root: Option<NodeId>,
+}
+
+/// Where the query occurs in a display name, better matches compare greater
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum MatchKind {
+    Substring,
+    /// At the start of a word, after a separator or at a camelCase or digit boundary
+    WordBoundary,
+    Prefix,
+    Exact,
 }
 
+impl MatchKind {
+    /// Case-insensitive match of `query_lower` in `name`, `None` when it does not occur
+    fn of(name: &str, query_lower: &str) -> Option<Self> {
+        let lower = name.to_lowercase();
+        if lower == query_lower {
+            Some(MatchKind::Exact)
+        } else if lower.starts_with(query_lower) {
+            Some(MatchKind::Prefix)
+        } else {
+            let mut positions = lower.match_indices(query_lower).map(|(idx, _)| idx).peekable();
+            positions.peek()?;
+            if positions.any(|idx| is_word_start(name, &lower, idx)) {
+                Some(MatchKind::WordBoundary)
+            } else {
+                Some(MatchKind::Substring)
+            }
+        }
+    }
+}
+
+/// Whether byte `idx` of the lowercase name starts a word
+fn is_word_start(name: &str, lower: &str, idx: usize) -> bool {
+    let Some(prev) = lower[..idx].chars().next_back() else {
+        return true;
+    };
+    if !prev.is_alphanumeric() {
+        return true;
+    }
+    // Case boundaries need the original name at the same offsets, which lowercasing keeps
+    // unless it changed the length of some character
+    if name.len() != lower.len() {
+        return false;
+    }
+    let (Some(prev), Some(current)) = (name[..idx].chars().next_back(), name[idx..].chars().next()) else {
+        return false;
+    };
+    (prev.is_lowercase() && current.is_uppercase()) || prev.is_ascii_digit() != current.is_ascii_digit()
+}
+
+/// Rank of a name search match: the kind of match, then shorter names, then shallower nodes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SearchScore {
+    pub kind: MatchKind,
+    /// Characters of the name
+    pub name_len: usize,
+    /// References between the node and the root the crawl reached it from
+    pub depth: u32,
+}
+
+impl Ord for SearchScore {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.kind.cmp(&other.kind)
+            .then(other.name_len.cmp(&self.name_len))
+            .then(other.depth.cmp(&self.depth))
+    }
+}
+
+impl PartialOrd for SearchScore {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for SearchScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            MatchKind::Exact => "exact",
+            MatchKind::Prefix => "prefix",
+            MatchKind::WordBoundary => "word",
+            MatchKind::Substring => "substring",
+        };
+        write!(f, "{}, {} chars, depth {}", kind, self.name_len, self.depth)
+    }
+}
+
+/// Pending node in the crawl; higher priority first, then insertion order
+#[derive(PartialEq, Eq)]
+struct QueueEntry {
+    priority: usize,
+    sequence: Reverse<u64>,
+    node_id: NodeId,
+    depth: u32,
+    /// Position of the search root the node was reached from
+    root: usize,
+}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.priority, self.sequence).cmp(&(other.priority, other.sequence))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Number of leading characters two lowercase names have in common
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count()
+}
+
+/// Crawl the address space for nodes whose display name contains `search_name`, ignoring case.
+/// The results are sorted best match first, see [`SearchScore`].
 pub async fn search_nodes_by_name(
-    session: &Arc<Session>,
+    session: &ServiceSession,
     search_name: &str,
     config: SearchConfig,
     verbose: bool,
 ) -> Result<Vec<SearchResult>> {
     let mut results = Vec::new();
     let mut visited = HashSet::new();
-    let mut queue = VecDeque::new();
+    let mut queue = BinaryHeap::new();
+    let mut sequence = 0u64;
     
-    // Start from multiple root nodes for comprehensive search
-    let start_nodes: Vec<NodeId> = vec![
-        ObjectId::ObjectsFolder.into(),
-        ObjectId::Server.into(),
-        ObjectId::TypesFolder.into(),
-    ];
-    
-    for start_node in start_nodes {
-        queue.push_back((start_node, 0u32));
+    // Roots reached from other roots, like Objects from Root, are only crawled as roots
+    let mut roots: Vec<NodeId> = Vec::with_capacity(config.search_roots.len());
+    for root in &config.search_roots {
+        if !roots.contains(root) {
+            roots.push(root.clone());
+        }
+    }
+    for (root, node_id) in roots.iter().enumerate() {
+        queue.push(QueueEntry { priority: 0, sequence: Reverse(sequence), node_id: node_id.clone(), depth: 0, root });
+        sequence += 1;
     }
     
     if verbose {
-        debug!("Starting search for '{}' with max_nodes={}, max_depth={}", 
-               search_name, config.max_nodes, config.max_depth);
+        debug!("Starting search for '{}' with max_nodes={}, max_depth={}, roots {}", 
+               search_name, config.max_nodes, config.max_depth,
+               roots.iter().map(format_search_root).collect::<Vec<_>>().join(", "));
     }
     
     let search_name_lower = search_name.to_lowercase();
     let mut nodes_processed = 0;
     
-    while let Some((current_node, depth)) = queue.pop_front() {
-        if nodes_processed >= config.max_nodes || depth > config.max_depth {
+    'crawl: while let Some(QueueEntry { node_id: current_node, depth, root, .. }) = queue.pop() {
+        if nodes_processed >= config.max_nodes {
             break;
         }
-        
         if visited.contains(&current_node) {
             continue;
         }
@@ -81,29 +239,55 @@ pub async fn search_nodes_by_name(
             Ok(references) => {
                 for reference in references {
                     let node_id = &reference.node_id.node_id;
-                    let display_name = reference.display_name.text.to_string();
+                    let (display_name, _) = reference_name(&reference);
                     
                     // Check if this node matches our search criteria
-                    if should_include_node(&reference, &config) &&
-                       display_name.to_lowercase().contains(&search_name_lower) {
+                    if should_include_node(&reference, &config)
+                        && let Some(kind) = MatchKind::of(&display_name, &search_name_lower)
+                    {
+                        let score = SearchScore { kind, name_len: display_name.chars().count(), depth: depth + 1 };
+                        if verbose {
+                            debug!("Found match: {} ({}) under {}, {}", display_name, format_node_id(node_id),
+                                   format_search_root(&roots[root]), score);
+                        }
                         results.push(SearchResult {
                             node_id: node_id.clone(),
                             display_name: display_name.clone(),
                             node_class: reference.node_class,
                             parent_node_id: Some(current_node.clone()),
+                            score: Some(score),
+                            root: Some(roots[root].clone()),
                         });
                         
-                        if verbose {
-                            debug!("Found match: {} ({})", display_name, format_node_id(node_id));
+                        if config.stop_after_matches.is_some_and(|limit| results.len() >= limit)
+                            || (config.stop_at_exact_match && kind == MatchKind::Exact)
+                        {
+                            break 'crawl;
                         }
                     }
                     
                     // Add child nodes to queue for further searching
-                    if depth < config.max_depth && !visited.contains(node_id) {
-                        queue.push_back((node_id.clone(), depth + 1));
+                    if descends(depth, config.max_depth) && !visited.contains(node_id) && !roots.contains(node_id) {
+                        let priority = if config.prioritize_prefix {
+                            common_prefix_len(&display_name.to_lowercase(), &search_name_lower)
+                        } else {
+                            0
+                        };
+                        queue.push(QueueEntry {
+                            priority,
+                            sequence: Reverse(sequence),
+                            node_id: node_id.clone(),
+                            depth: depth + 1,
+                            root,
+                        });
+                        sequence += 1;
                     }
                 }
             }
+            // Nano profile servers may lack a root such as the Server object, the others still count
+            Err(e) if depth == 0 => {
+                debug!("Skipping search root {}: {}", format_node_id(&current_node), e);
+            }
             Err(e) if verbose => {
                 warn!("Failed to browse node {}: {}", format_node_id(&current_node), e);
             }
@@ -116,54 +300,126 @@ pub async fn search_nodes_by_name(
                nodes_processed, results.len());
     }
     
+    // Stable, so equal matches keep the order they were found in. Nodes reached over several
+    // parents keep their best ranked entry.
+    results.sort_by_key(|result| Reverse(result.score));
+    let mut seen = HashSet::new();
+    results.retain(|result| seen.insert(result.node_id.clone()));
     Ok(results)
 }
 
+/// The best ranked method named like `method_name` below the search roots, its
+/// `parent_node_id` is the object to call it on
 pub async fn find_method_with_parent(
-    session: &Arc<Session>,
+    session: &ServiceSession,
     method_name: &str,
+    search_roots: Vec<NodeId>,
     verbose: bool,
-) -> Result<Option<(NodeId, NodeId)>> {
+) -> Result<Option<SearchResult>> {
     let config = SearchConfig {
         max_nodes: 2000,
         max_depth: 15,
         search_methods_only: true,
+        prioritize_prefix: true,
+        stop_at_exact_match: true,
+        search_roots,
         ..Default::default()
     };
     
+    // The best ranked method wins, a method named exactly like the query ends the search early
     let search_results = search_nodes_by_name(session, method_name, config, verbose).await?;
+    Ok(search_results
+        .into_iter()
+        .find(|result| result.node_class == NodeClass::Method && result.parent_node_id.is_some()))
+}
+
+/// Find the objects whose type definition is `type_id`.
+///
+/// Uses the inverse HasTypeDefinition references of the type node and falls back to crawling
+/// the Objects folder when the server does not expose those.
+pub async fn find_instances_of_type(
+    session: &ServiceSession,
+    type_id: &NodeId,
+    config: &SearchConfig,
+    verbose: bool,
+) -> Result<Vec<SearchResult>> {
+    let browse_request = BrowseRequest::new(type_id, BrowseDirection::Inverse, ReferenceTypeId::HasTypeDefinition)
+        .exact_type()
+        .classes(NodeClassMask::OBJECT)
+        .build();
     
-    for result in search_results {
-        if result.node_class == NodeClass::Method {
-            if let Some(parent_id) = result.parent_node_id {
-                return Ok(Some((result.node_id, parent_id)));
+    let browse_results = session.browse(&[browse_request], 0, None).await?;
+    let instances: Vec<SearchResult> = browse_results
+        .into_iter()
+        .next()
+        .filter(|result| result.status_code.is_good())
+        .and_then(|result| result.references)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|reference| SearchResult {
+            display_name: reference_name(&reference).0,
+            node_id: reference.node_id.node_id,
+            node_class: reference.node_class,
+            parent_node_id: None,
+            score: None,
+            root: None,
+        })
+        .collect();
+    
+    if !instances.is_empty() {
+        return Ok(instances);
+    }
+    
+    if verbose {
+        debug!("No inverse HasTypeDefinition references on {}, crawling the Objects folder", format_node_id(type_id));
+    }
+    
+    let objects_folder: NodeId = ObjectId::ObjectsFolder.into();
+    let mut results = Vec::new();
+    let mut visited = HashSet::from([objects_folder.clone()]);
+    let mut queue = VecDeque::from([(objects_folder, 0u32)]);
+    
+    while let Some((current_node, depth)) = queue.pop_front() {
+        if visited.len() > config.max_nodes {
+            break;
+        }
+        
+        for reference in browse_node(session, &current_node).await? {
+            let node_id = &reference.node_id.node_id;
+            
+            if reference.node_class == NodeClass::Object && reference.type_definition.node_id == *type_id {
+                results.push(SearchResult {
+                    node_id: node_id.clone(),
+                    display_name: reference_name(&reference).0,
+                    node_class: reference.node_class,
+                    parent_node_id: Some(current_node.clone()),
+                    score: None,
+                    root: Some(ObjectId::ObjectsFolder.into()),
+                });
+            }
+            
+            if descends(depth, config.max_depth) && visited.insert(node_id.clone()) {
+                queue.push_back((node_id.clone(), depth + 1));
             }
         }
     }
     
-    Ok(None)
+    if verbose {
+        debug!("Crawled {} nodes, found {} instance(s)", visited.len(), results.len());
+    }
+    
+    Ok(results)
 }
 
-async fn browse_node(session: &Arc<Session>, node_id: &NodeId) -> Result<Vec<ReferenceDescription>> {
-    let browse_request = BrowseDescription {
-        node_id: node_id.clone(),
-        browse_direction: BrowseDirection::Forward,
-        reference_type_id: ReferenceTypeId::HierarchicalReferences.into(),
-        include_subtypes: true,
-        node_class_mask: 0u32, // All node classes
-        result_mask: BrowseResultMask::All as u32,
-    };
+async fn browse_node(session: &ServiceSession, node_id: &NodeId) -> Result<Vec<ReferenceDescription>> {
+    let browse_request = BrowseRequest::new(node_id, BrowseDirection::Forward, ReferenceTypeId::HierarchicalReferences).build();
     
     let browse_results = session.browse(&[browse_request], 0, None).await?;
     
-    if let Some(browse_result) = browse_results.first() {
-        if browse_result.status_code.is_good() {
-            Ok(browse_result.references.clone().unwrap_or_default())
-        } else {
-            Ok(Vec::new())
-        }
-    } else {
-        Ok(Vec::new())
+    match browse_results.first() {
+        Some(browse_result) if browse_result.status_code.is_good() => Ok(browse_result.references.clone().unwrap_or_default()),
+        Some(browse_result) => Err(anyhow::Error::new(OperationResult::of_status(browse_result.status_code))),
+        None => Ok(Vec::new()),
     }
 }
 
@@ -178,4 +434,55 @@ fn should_include_node(reference: &ReferenceDescription, config: &SearchConfig)
     
     // Include all node types by default
     true
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(node_id: NodeId, browse_name: QualifiedName, display_name: LocalizedText) -> ReferenceDescription {
+        ReferenceDescription {
+            reference_type_id: ReferenceTypeId::HasComponent.into(),
+            is_forward: true,
+            node_id: node_id.into(),
+            browse_name,
+            display_name,
+            node_class: NodeClass::Variable,
+            type_definition: ExpandedNodeId::null(),
+        }
+    }
+
+    fn matches(reference: &ReferenceDescription, query: &str) -> Option<MatchKind> {
+        MatchKind::of(&reference_name(reference).0, &query.to_lowercase())
+    }
+
+    #[test]
+    fn null_display_names_match_by_browse_name() {
+        let motor = reference(NodeId::new(2, 7), QualifiedName::new(2, "MotorSpeed"), LocalizedText::null());
+        assert_eq!(matches(&motor, "MotorSpeed"), Some(MatchKind::Exact));
+        assert_eq!(matches(&motor, "speed"), Some(MatchKind::WordBoundary));
+        assert_eq!(matches(&motor, "pump"), None);
+    }
+
+    #[test]
+    fn null_browse_names_match_by_identifier() {
+        let motor = reference(NodeId::new(3, "Line1.MotorSpeed"), QualifiedName::null(), LocalizedText::null());
+        assert_eq!(matches(&motor, "motorspeed"), Some(MatchKind::WordBoundary));
+        let numeric = reference(NodeId::new(3, 4711), QualifiedName::null(), LocalizedText::new("en", ""));
+        assert_eq!(matches(&numeric, "4711"), Some(MatchKind::Exact));
+    }
+
+    #[test]
+    fn display_names_win_over_browse_names() {
+        let motor = reference(NodeId::new(2, 7), QualifiedName::new(2, "M_07"), LocalizedText::new("en", "Main motor"));
+        assert_eq!(matches(&motor, "motor"), Some(MatchKind::WordBoundary));
+        assert_eq!(matches(&motor, "m_07"), None);
+    }
+
+    #[test]
+    fn match_kinds() {
+        assert_eq!(MatchKind::of("Temperature", "temperature"), Some(MatchKind::Exact));
+        assert_eq!(MatchKind::of("TemperatureSetpoint", "temperature"), Some(MatchKind::Prefix));
+        assert_eq!(MatchKind::of("BoilerTemperature", "temperature"), Some(MatchKind::WordBoundary));
+        assert_eq!(MatchKind::of("Boilertemperature", "temperature"), Some(MatchKind::Substring));
+    }
+}
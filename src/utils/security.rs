@@ -0,0 +1,87 @@
+use colored::*;
+use opcua::crypto::SecurityPolicy;
+use opcua::types::{EndpointDescription, MessageSecurityMode, UserTokenType};
+use serde::{Deserialize, Serialize};
+
+/// The security a session was opened with, printed as a warning above the output when it is
+/// weaker than the server allows and added as `security` to JSON output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    /// Security policy of the channel, e.g. `None` or `Basic256Sha256`
+    pub policy: String,
+    /// Message security mode of the channel: `None`, `Sign` or `SignAndEncrypt`
+    pub mode: String,
+    /// User token of the session: `anonymous`, `username`, `certificate` or `issued_token`
+    pub identity: String,
+    /// What the server offers beyond the settings used, empty when nothing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weaker_than_offered: Vec<String>,
+}
+
+impl ConnectionInfo {
+    /// Describe a session on a channel with `policy` and `mode`, compared with the `offered`
+    /// endpoints of the server; without them nothing counts as weaker
+    pub fn new(policy: SecurityPolicy, mode: MessageSecurityMode, identity: UserTokenType, offered: &[EndpointDescription]) -> Self {
+        let mut weaker_than_offered = Vec::new();
+
+        let offers = |wanted: MessageSecurityMode| offered.iter().any(|endpoint| endpoint.security_mode == wanted);
+        match mode {
+            MessageSecurityMode::SignAndEncrypt => {}
+            MessageSecurityMode::Sign if offers(MessageSecurityMode::SignAndEncrypt) => {
+                weaker_than_offered.push("unencrypted channel, the server offers SignAndEncrypt".to_string());
+            }
+            MessageSecurityMode::Sign => {}
+            _ if offers(MessageSecurityMode::SignAndEncrypt) => {
+                weaker_than_offered.push("unencrypted and unsigned channel, the server offers SignAndEncrypt".to_string());
+            }
+            _ if offers(MessageSecurityMode::Sign) => {
+                weaker_than_offered.push("unsigned channel, the server offers Sign".to_string());
+            }
+            _ => {}
+        }
+
+        if identity == UserTokenType::Anonymous {
+            let mut logins: Vec<&str> = Vec::new();
+            for policy in offered.iter().flat_map(|endpoint| endpoint.user_identity_tokens.iter().flatten()) {
+                let login = identity_name(policy.token_type);
+                if policy.token_type != UserTokenType::Anonymous && !logins.contains(&login) {
+                    logins.push(login);
+                }
+            }
+            if !logins.is_empty() {
+                weaker_than_offered.push(format!("anonymous session, the server accepts {} logins", logins.join(", ")));
+            }
+        }
+
+        ConnectionInfo {
+            policy: policy.to_string(),
+            mode: mode.to_string(),
+            identity: identity_name(identity).to_string(),
+            weaker_than_offered,
+        }
+    }
+
+    /// The one-line header warning about a session weaker than the server allows
+    pub fn warning(&self) -> Option<String> {
+        if self.weaker_than_offered.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "⚠️  {} {} ({} / {}, {})",
+            "Security:".yellow().bold(),
+            self.weaker_than_offered.join("; "),
+            self.policy,
+            self.mode,
+            self.identity
+        ))
+    }
+}
+
+fn identity_name(token_type: UserTokenType) -> &'static str {
+    match token_type {
+        UserTokenType::Anonymous => "anonymous",
+        UserTokenType::UserName => "username",
+        UserTokenType::Certificate => "certificate",
+        UserTokenType::IssuedToken => "issued_token",
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+use chrono::Local;
+use opcua::types::{DataValue, NodeId, ReadValueId, ServerState, TimestampsToReturn, VariableId, Variant};
+use std::time::Duration;
+
+use crate::session::ServiceSession;
+use crate::utils::formatter::{localized_text, sanitize_text};
+use crate::utils::outcome::OperationResult;
+use crate::utils::time::format_duration;
+
+/// A shutdown the server announces in its ServerStatus
+#[derive(Clone, PartialEq)]
+pub struct ShutdownNotice {
+    /// SecondsTillShutdown, 0 once the server is in the Shutdown state
+    pub seconds: u32,
+    /// ShutdownReason in the session's locale, empty when the server gives none
+    pub reason: String,
+}
+
+impl ShutdownNotice {
+    /// `in 4m 30s (at 14:05:00)`, or `now`
+    pub fn countdown(&self) -> String {
+        if self.seconds == 0 {
+            return "now".to_string();
+        }
+        let remaining = Duration::from_secs(self.seconds.into());
+        let at = Local::now() + remaining;
+        format!("in {} (at {})", format_duration(remaining), at.format("%H:%M:%S"))
+    }
+}
+
+/// Read SecondsTillShutdown, ShutdownReason and State of the server, `None` while no shutdown is
+/// announced. Servers set SecondsTillShutdown only once they are about to shut down.
+pub async fn read_shutdown(session: &ServiceSession) -> Result<Option<ShutdownNotice>> {
+    let read_requests: Vec<ReadValueId> = [
+        VariableId::Server_ServerStatus_SecondsTillShutdown,
+        VariableId::Server_ServerStatus_ShutdownReason,
+        VariableId::Server_ServerStatus_State,
+    ]
+    .into_iter()
+    .map(|variable| ReadValueId::from(&NodeId::from(variable)))
+    .collect();
+    let results = session.read(&read_requests, TimestampsToReturn::Neither, 0.0).await?;
+    if let Some(status) = results.first().and_then(|dv| dv.status).filter(|status| !status.is_good()) {
+        return Err(anyhow::Error::new(OperationResult::of_status(status)).context("SecondsTillShutdown is not readable"));
+    }
+    Ok(shutdown_notice(&results))
+}
+
+fn shutdown_notice(results: &[DataValue]) -> Option<ShutdownNotice> {
+    let value = |idx: usize| results.get(idx).and_then(|dv| dv.value.as_ref());
+    let seconds = match value(0) {
+        Some(Variant::UInt32(seconds)) => *seconds,
+        _ => 0,
+    };
+    let shut_down = matches!(value(2), Some(Variant::Int32(state)) if *state == ServerState::Shutdown as i32);
+    if seconds == 0 && !shut_down {
+        return None;
+    }
+
+    let reason = match value(1) {
+        Some(Variant::LocalizedText(text)) => sanitize_text(localized_text(text)).into_owned(),
+        _ => String::new(),
+    };
+    Some(ShutdownNotice { seconds, reason })
+}
@@ -0,0 +1,291 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, Utc};
+use colored::*;
+use opcua::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+use crate::utils::formatter::{format_node_id, localized_text, parse_node_id_text};
+use crate::utils::recording::{decode_base64, encode_base64};
+
+/// Highest attribute ID OPC UA defines, AccessLevelEx
+pub const MAX_ATTRIBUTE_ID: u32 = 27;
+
+/// Supertypes followed at most when matching a reference type against a browse filter
+const MAX_TYPE_DEPTH: usize = 32;
+
+/// A snapshot file as `export --snapshot` writes it
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    endpoint: String,
+    /// When the snapshot was taken, RFC 3339
+    taken: String,
+    /// Node the crawl started at and how deep it went
+    root: String,
+    depth: u32,
+    nodes: Vec<SnapshotFileNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFileNode {
+    node_id: String,
+    /// DisplayName, only there to find nodes when reading the file
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    name: String,
+    /// DataValues by attribute name, in the binary encoding of OPC-UA as base64
+    attributes: BTreeMap<String, String>,
+    /// References in both directions, encoded like the attributes; absent for nodes whose
+    /// children were not browsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    references: Option<Vec<String>>,
+}
+
+/// One node to write into a snapshot
+pub struct SnapshotEntry {
+    pub node_id: NodeId,
+    pub attributes: Vec<(u32, DataValue)>,
+    pub references: Option<Vec<ReferenceDescription>>,
+}
+
+/// Write a snapshot of `entries`, crawled from `root` to `depth` on `endpoint`
+pub fn write(path: &str, endpoint: &str, root: &NodeId, depth: u32, entries: &[SnapshotEntry]) -> Result<()> {
+    let nodes = entries
+        .iter()
+        .map(|entry| {
+            let name = entry
+                .attributes
+                .iter()
+                .find_map(|(id, dv)| match (*id == AttributeId::DisplayName as u32, &dv.value) {
+                    (true, Some(Variant::LocalizedText(text))) => Some(localized_text(text).to_string()),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            SnapshotFileNode {
+                node_id: format_node_id(&entry.node_id),
+                name,
+                attributes: entry
+                    .attributes
+                    .iter()
+                    .filter_map(|(id, dv)| Some((attribute_name(*id)?, encode_base64(dv))))
+                    .collect(),
+                references: entry.references.as_ref().map(|references| references.iter().map(encode_base64).collect()),
+            }
+        })
+        .collect();
+    let file = SnapshotFile {
+        endpoint: endpoint.to_string(),
+        taken: Utc::now().to_rfc3339(),
+        root: format_node_id(root),
+        depth,
+        nodes,
+    };
+    let json = serde_json::to_string_pretty(&file)?;
+    fs::write(path, json + "\n").map_err(|e| anyhow!("Failed to write snapshot {}: {}", path, e))
+}
+
+struct SnapshotNode {
+    attributes: HashMap<u32, DataValue>,
+    references: Option<Vec<ReferenceDescription>>,
+}
+
+/// The nodes of a snapshot file, answering reads and browses in place of a server
+pub struct Snapshot {
+    pub path: String,
+    pub endpoint: String,
+    pub taken: DateTime<Utc>,
+    nodes: HashMap<NodeId, SnapshotNode>,
+    /// Supertype of each reference type, from its inverse HasSubtype reference
+    supertypes: HashMap<NodeId, NodeId>,
+}
+
+impl Snapshot {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| anyhow!("Failed to read snapshot {}: {}", path, e))?;
+        let file: SnapshotFile = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("{} is not a snapshot written by export --snapshot: {}", path, e))?;
+        let taken = DateTime::parse_from_rfc3339(&file.taken)
+            .map_err(|e| anyhow!("{}: invalid time the snapshot was taken, {}: {}", path, file.taken, e))?
+            .with_timezone(&Utc);
+
+        let mut nodes = HashMap::new();
+        let mut supertypes = HashMap::new();
+        for node in file.nodes {
+            let node_id = parse_node_id_text(&node.node_id).ok_or_else(|| anyhow!("{}: invalid node ID {}", path, node.node_id))?;
+            let invalid = |what: &str| anyhow!("{}: {} of {} cannot be decoded", path, what, node.node_id);
+            let mut attributes = HashMap::new();
+            for (name, value) in &node.attributes {
+                let id = attribute_id(name).ok_or_else(|| invalid(name))?;
+                attributes.insert(id, decode_base64::<DataValue>(value).ok_or_else(|| invalid(name))?);
+            }
+            let references = match &node.references {
+                Some(references) => Some(
+                    references
+                        .iter()
+                        .map(|reference| decode_base64::<ReferenceDescription>(reference).ok_or_else(|| invalid("a reference")))
+                        .collect::<Result<Vec<_>>>()?,
+                ),
+                None => None,
+            };
+            for reference in references.iter().flatten() {
+                if reference.reference_type_id == ReferenceTypeId::HasSubtype && !reference.is_forward {
+                    supertypes.insert(node_id.clone(), reference.node_id.node_id.clone());
+                }
+            }
+            nodes.insert(node_id, SnapshotNode { attributes, references });
+        }
+        if nodes.is_empty() {
+            return Err(anyhow!("{} holds no nodes", path));
+        }
+        Ok(Snapshot { path: path.to_string(), endpoint: file.endpoint, taken, nodes, supertypes })
+    }
+
+    /// The line of offline output that tells where its data comes from
+    pub fn banner(&self) -> String {
+        format!(
+            "💾 {} {} of {}, taken {}",
+            "Offline snapshot".yellow().bold(),
+            self.path.bright_white(),
+            self.endpoint,
+            self.taken.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+        )
+    }
+
+    /// The `snapshot` block of JSON output
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "file": self.path,
+            "endpoint": self.endpoint,
+            "taken": self.taken.to_rfc3339(),
+        })
+    }
+
+    /// Answer a Read: nodes outside the snapshot are BadNodeIdUnknown, attributes the node does
+    /// not have BadAttributeIdInvalid
+    pub fn read(&self, nodes_to_read: &[ReadValueId]) -> Vec<DataValue> {
+        nodes_to_read
+            .iter()
+            .map(|request| {
+                let Some(node) = self.nodes.get(&request.node_id) else {
+                    return bad(StatusCode::BadNodeIdUnknown);
+                };
+                let Some(value) = node.attributes.get(&request.attribute_id) else {
+                    return bad(StatusCode::BadAttributeIdInvalid);
+                };
+                if request.index_range.is_none() {
+                    return value.clone();
+                }
+                match value.value.as_ref().map(|variant| variant.range_of(&request.index_range)) {
+                    Some(Ok(range)) => DataValue { value: Some(range), ..value.clone() },
+                    Some(Err(status)) => bad(status),
+                    None => value.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Answer a Browse from the stored references. Everything is returned at once, without
+    /// continuation points; nodes the crawl reached but did not browse are BadNoData.
+    pub fn browse(&self, nodes_to_browse: &[BrowseDescription]) -> Vec<BrowseResult> {
+        nodes_to_browse
+            .iter()
+            .map(|description| {
+                let status_code = match self.nodes.get(&description.node_id) {
+                    None => StatusCode::BadNodeIdUnknown,
+                    Some(SnapshotNode { references: None, .. }) => StatusCode::BadNoData,
+                    Some(_) => StatusCode::Good,
+                };
+                let references: Vec<ReferenceDescription> = self
+                    .references(&description.node_id)
+                    .filter(|reference| match description.browse_direction {
+                        BrowseDirection::Forward => reference.is_forward,
+                        BrowseDirection::Inverse => !reference.is_forward,
+                        _ => true,
+                    })
+                    .filter(|reference| {
+                        self.is_reference_type(&reference.reference_type_id, &description.reference_type_id, description.include_subtypes)
+                    })
+                    .filter(|reference| {
+                        description.node_class_mask == 0 || description.node_class_mask & reference.node_class as u32 != 0
+                    })
+                    .cloned()
+                    .collect();
+                BrowseResult {
+                    status_code,
+                    continuation_point: ByteString::null(),
+                    references: status_code.is_good().then_some(references),
+                }
+            })
+            .collect()
+    }
+
+    /// Answer a TranslateBrowsePathsToNodeIds by following the stored references
+    pub fn translate_browse_paths(&self, browse_paths: &[BrowsePath]) -> Vec<BrowsePathResult> {
+        browse_paths
+            .iter()
+            .map(|path| {
+                let mut current = vec![path.starting_node.clone()];
+                for element in path.relative_path.elements.iter().flatten() {
+                    let mut next: Vec<NodeId> = Vec::new();
+                    for node_id in &current {
+                        for reference in self.references(node_id) {
+                            let target = &reference.node_id.node_id;
+                            if reference.is_forward != element.is_inverse
+                                && self.is_reference_type(&reference.reference_type_id, &element.reference_type_id, element.include_subtypes)
+                                && (element.target_name.is_null() || reference.browse_name == element.target_name)
+                                && !next.contains(target)
+                            {
+                                next.push(target.clone());
+                            }
+                        }
+                    }
+                    current = next;
+                }
+                if current.is_empty() {
+                    return BrowsePathResult { status_code: StatusCode::BadNoMatch, targets: None };
+                }
+                let targets = current
+                    .into_iter()
+                    .map(|node_id| BrowsePathTarget { target_id: node_id.into(), remaining_path_index: u32::MAX })
+                    .collect();
+                BrowsePathResult { status_code: StatusCode::Good, targets: Some(targets) }
+            })
+            .collect()
+    }
+
+    fn references(&self, node_id: &NodeId) -> impl Iterator<Item = &ReferenceDescription> {
+        self.nodes.get(node_id).and_then(|node| node.references.as_ref()).into_iter().flatten()
+    }
+
+    /// Whether `reference_type` is `wanted` or, with `include_subtypes`, one of its subtypes; a
+    /// null `wanted` matches every type
+    fn is_reference_type(&self, reference_type: &NodeId, wanted: &NodeId, include_subtypes: bool) -> bool {
+        if wanted.is_null() || reference_type == wanted {
+            return true;
+        }
+        if !include_subtypes {
+            return false;
+        }
+        let mut current = reference_type;
+        for _ in 0..MAX_TYPE_DEPTH {
+            match self.supertypes.get(current) {
+                Some(supertype) if supertype == wanted => return true,
+                Some(supertype) => current = supertype,
+                None => return false,
+            }
+        }
+        false
+    }
+}
+
+fn bad(status: StatusCode) -> DataValue {
+    DataValue { status: Some(status), ..DataValue::null() }
+}
+
+fn attribute_name(id: u32) -> Option<String> {
+    AttributeId::from_u32(id).ok().map(|attribute| format!("{:?}", attribute))
+}
+
+fn attribute_id(name: &str) -> Option<u32> {
+    (1..=MAX_ATTRIBUTE_ID).find(|id| attribute_name(*id).as_deref() == Some(name))
+}
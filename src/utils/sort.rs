@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+
+/// Case-insensitive natural ordering, so `Item2` sorts before `Item10`.
+///
+/// Digit runs are compared by numeric value (of any length), everything else
+/// character by character. Names that only differ in case or leading zeros fall
+/// back to a plain comparison to keep the order total and deterministic.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut left = a.chars().peekable();
+    let mut right = b.chars().peekable();
+
+    loop {
+        match (left.peek().copied(), right.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) if l.is_ascii_digit() && r.is_ascii_digit() => {
+                let left_digits = take_digits(&mut left);
+                let right_digits = take_digits(&mut right);
+                let ordering = compare_numeric(&left_digits, &right_digits);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(l), Some(r)) => {
+                let ordering = l.to_lowercase().cmp(r.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                left.next();
+                right.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        digits.push(c);
+    }
+    digits
+}
+
+fn compare_numeric(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn numeric_runs() {
+        assert_eq!(sorted(&["Item10", "Item2", "Item1"]), ["Item1", "Item2", "Item10"]);
+        assert_eq!(sorted(&["Axis1.Motor10", "Axis1.Motor9", "Axis10.Motor1", "Axis2.Motor1"]), ["Axis1.Motor9", "Axis1.Motor10", "Axis2.Motor1", "Axis10.Motor1"]);
+        assert_eq!(sorted(&["1.10", "1.9", "1.2.3", "10"]), ["1.2.3", "1.9", "1.10", "10"]);
+        assert_eq!(sorted(&["Item", "Item1", "Item 1"]), ["Item", "Item 1", "Item1"]);
+    }
+
+    #[test]
+    fn leading_zeros() {
+        assert_eq!(sorted(&["Item010", "Item9", "Item0002"]), ["Item0002", "Item9", "Item010"]);
+        // Equal values fall back to comparing the text
+        assert_eq!(sorted(&["Item2", "Item002", "Item02"]), ["Item002", "Item02", "Item2"]);
+        assert_eq!(natural_cmp("Item0", "Item000"), Ordering::Less);
+        assert_eq!(natural_cmp("Item00", "Item1"), Ordering::Less);
+    }
+
+    #[test]
+    fn mixed_case() {
+        assert_eq!(sorted(&["beta", "Alpha", "GAMMA", "alpha2", "Alpha10"]), ["Alpha", "alpha2", "Alpha10", "beta", "GAMMA"]);
+        // Names that only differ in case still get one fixed order
+        assert_eq!(sorted(&["item", "ITEM", "Item"]), ["ITEM", "Item", "item"]);
+        assert_eq!(natural_cmp("Ärger", "ärger"), Ordering::Less);
+    }
+
+    #[test]
+    fn numbers_beyond_u64() {
+        let huge = "Item99999999999999999999999";
+        let larger = "Item100000000000000000000000";
+        assert!(huge.trim_start_matches("Item").parse::<u64>().is_err());
+        assert_eq!(sorted(&[larger, huge, "Item18446744073709551615"]), ["Item18446744073709551615", huge, larger]);
+        assert_eq!(natural_cmp("Item123456789012345678901234567890", "Item123456789012345678901234567891"), Ordering::Less);
+        assert_eq!(natural_cmp("Item000099999999999999999999999", huge), Ordering::Less);
+    }
+
+    #[test]
+    fn order_is_total() {
+        let names = ["a1", "A1", "a01", "a1b", "a", "", "1", "01", "B", "b"];
+        for a in names {
+            assert_eq!(natural_cmp(a, a), Ordering::Equal);
+            for b in names {
+                assert_eq!(natural_cmp(a, b), natural_cmp(b, a).reverse(), "{} and {}", a, b);
+                if a != b {
+                    assert_ne!(natural_cmp(a, b), Ordering::Equal, "{} and {}", a, b);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use anyhow::{anyhow, Result};
+use opcua::client::{DataChangeCallback, MonitoredItem};
+use opcua::types::*;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::session::ServiceSession;
+
+/// Publishing intervals without changes after which the server answers a publish request with
+/// a keep-alive
+const KEEP_ALIVE_COUNT: u32 = 10;
+
+/// Publishing intervals without a publish request of the client after which the server closes
+/// the subscription
+const LIFETIME_COUNT: u32 = 30;
+
+/// A temporary set of subscriptions monitoring the Value attribute of a set of nodes.
+///
+/// Servers cap the monitored items per subscription, so the nodes are spread over as many
+/// subscriptions as needed. Notifications of all of them arrive as `(index, value)` pairs where
+/// `index` is the position of the node in the list passed to [`ValueSubscription::create`],
+/// followed by the nodes of later [`ValueSubscription::add`] calls.
+pub struct ValueSubscription {
+    session: ServiceSession,
+    subscription_ids: Vec<u32>,
+    interval: Duration,
+    /// Position in `subscription_ids` and server side monitored item ID of each node's item,
+    /// item 0 where creating it failed or the node was removed
+    items: Vec<(usize, u32)>,
+    /// Items per subscription, from the server's capability or learned when it refused more
+    max_items: Option<usize>,
+    receiver: mpsc::UnboundedReceiver<(usize, DataValue)>,
+    /// For the callbacks of subscriptions opened by `add`. Weak, so the receiver still ends
+    /// once the server closed all subscriptions.
+    sender: mpsc::WeakUnboundedSender<(usize, DataValue)>,
+}
+
+impl ValueSubscription {
+    /// Create the subscriptions and one monitored item per node.
+    ///
+    /// Each subscription takes as many items as the MaxMonitoredItemsPerSubscription capability
+    /// of the server allows. Servers not stating the limit are filled until they answer
+    /// `BadTooManyMonitoredItems`, and the number of items the first subscription took is used
+    /// for the others. Only failing on the first subscription is an error, the nodes left over
+    /// when a later one cannot be created get its status and the earlier ones keep running.
+    ///
+    /// Returns the per-node status of the monitored item creation alongside the subscriptions.
+    pub async fn create(
+        session: &ServiceSession,
+        node_ids: &[NodeId],
+        publishing_interval: Duration,
+    ) -> Result<(Self, Vec<StatusCode>)> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut subscription = ValueSubscription {
+            session: session.clone(),
+            subscription_ids: Vec::new(),
+            interval: publishing_interval,
+            items: Vec::new(),
+            max_items: max_items_per_subscription(session).await,
+            receiver,
+            sender: sender.downgrade(),
+        };
+
+        match subscription.place(node_ids, &sender).await {
+            Ok(statuses) => {
+                debug!("Monitoring {} node(s) in {} subscription(s)", node_ids.len(), subscription.subscription_ids.len());
+                Ok((subscription, statuses))
+            }
+            Err(failure) => {
+                subscription.delete().await;
+                Err(failure.into())
+            }
+        }
+    }
+
+    /// Monitor more nodes, numbered after the ones monitored so far. They go on the last
+    /// subscription while it has room and on new subscriptions after that.
+    ///
+    /// Returns the per-node status of the monitored item creation.
+    pub async fn add(&mut self, node_ids: &[NodeId]) -> Result<Vec<StatusCode>> {
+        let Some(sender) = self.sender.upgrade() else {
+            // The nodes still take their positions
+            self.items.resize(self.items.len() + node_ids.len(), (0, 0));
+            return Err(anyhow!("The subscriptions were closed by the server"));
+        };
+        let statuses = self.place(node_ids, &sender).await?;
+        debug!("Added {} node(s), now in {} subscription(s)", node_ids.len(), self.subscription_ids.len());
+        Ok(statuses)
+    }
+
+    /// Stop monitoring the node at `index`, its position stays taken. Returns whether the node
+    /// had a monitored item.
+    pub async fn remove(&mut self, index: usize) -> bool {
+        let (position, item) = std::mem::take(&mut self.items[index]);
+        if item == 0 {
+            return false;
+        }
+        if let Err(e) = self.session.delete_monitored_items(self.subscription_ids[position], &[item]).await {
+            debug!("Failed to delete monitored item {}: {}", item, e);
+        }
+        true
+    }
+
+    /// Create the monitored items of nodes appended after the existing ones, first on the last
+    /// subscription and then on new ones. Failing on the very first subscription is an error,
+    /// the nodes left over when a later one cannot be created get its status.
+    async fn place(
+        &mut self,
+        node_ids: &[NodeId],
+        sender: &mpsc::UnboundedSender<(usize, DataValue)>,
+    ) -> std::result::Result<Vec<StatusCode>, StatusCode> {
+        let offset = self.items.len();
+        self.items.resize(offset + node_ids.len(), (0, 0));
+        let mut statuses = vec![StatusCode::Good; node_ids.len()];
+
+        let mut pending: Vec<usize> = (offset..self.items.len()).collect();
+        let mut reuse = self.subscription_ids.len().checked_sub(1);
+        while !pending.is_empty() {
+            let fresh = reuse.is_none();
+            let position = match reuse.take() {
+                Some(position) => Ok(position),
+                None => self.open(sender).await,
+            };
+            let filled = match position {
+                Ok(position) => self.fill(position, node_ids, offset, &mut pending, &mut statuses).await.map(|taken| (position, taken)),
+                Err(e) => Err(e),
+            };
+
+            let failure = match filled {
+                Ok((position, taken)) if taken > 0 || pending.is_empty() || !fresh => {
+                    // The server stopped taking items before the nodes ran out
+                    if !pending.is_empty() {
+                        self.max_items.get_or_insert(self.held(position));
+                    }
+                    continue;
+                }
+                // A fresh subscription taking nothing would not get further on the next one
+                Ok(_) => StatusCode::BadTooManyMonitoredItems,
+                Err(e) => e,
+            };
+            if fresh && self.subscription_ids.len() <= 1 {
+                return Err(failure);
+            }
+            warn!("Failed to create subscription {} for {} monitored item(s): {}",
+                  self.subscription_ids.len(), pending.len(), failure);
+            for index in pending.drain(..) {
+                statuses[index - offset] = failure;
+            }
+        }
+        Ok(statuses)
+    }
+
+    /// Monitored items on the subscription at `position`
+    fn held(&self, position: usize) -> usize {
+        self.items.iter().filter(|(at, item)| *at == position && *item != 0).count()
+    }
+
+    /// Create the monitored items of pending nodes on the subscription at `position` until it
+    /// holds `max_items` or the server refuses more, returning how many the server took.
+    /// `node_ids` and `statuses` start with the node at `offset`.
+    async fn fill(
+        &mut self,
+        position: usize,
+        node_ids: &[NodeId],
+        offset: usize,
+        pending: &mut Vec<usize>,
+        statuses: &mut [StatusCode],
+    ) -> std::result::Result<usize, StatusCode> {
+        let subscription_id = self.subscription_ids[position];
+        let held = self.held(position);
+        let mut taken = 0;
+        let mut batch_size = pending.len();
+
+        while !pending.is_empty() {
+            let room = self.max_items.map_or(usize::MAX, |max| max.saturating_sub(held + taken));
+            let count = batch_size.min(room).min(pending.len());
+            if count == 0 {
+                break;
+            }
+
+            let items = pending[..count]
+                .iter()
+                .map(|index| item_request(*index, &node_ids[*index - offset], self.interval))
+                .collect();
+            let results = match self.session.create_monitored_items(subscription_id, TimestampsToReturn::Both, items).await {
+                Ok(results) => results,
+                // Some servers refuse the whole request instead of the items that do not fit
+                Err(status) if status == StatusCode::BadTooManyMonitoredItems && count > 1 => {
+                    batch_size = count / 2;
+                    continue;
+                }
+                Err(status) if status == StatusCode::BadTooManyMonitoredItems => break,
+                Err(status) => return Err(status),
+            };
+
+            let batch: Vec<usize> = pending.drain(..count).collect();
+            let mut overflow = Vec::new();
+            for (index, result) in batch.into_iter().zip(results) {
+                if result.status_code == StatusCode::BadTooManyMonitoredItems {
+                    overflow.push(index);
+                    continue;
+                }
+                if result.status_code.is_good() {
+                    taken += 1;
+                }
+                self.items[index] = (position, result.monitored_item_id);
+                statuses[index - offset] = result.status_code;
+            }
+            if !overflow.is_empty() {
+                pending.splice(0..0, overflow);
+                break;
+            }
+        }
+        Ok(taken)
+    }
+
+    /// Create one more subscription whose notifications go to `sender`, returning its position
+    async fn open(&mut self, sender: &mpsc::UnboundedSender<(usize, DataValue)>) -> std::result::Result<usize, StatusCode> {
+        let sender = sender.clone();
+        let subscription_id = self.session
+            .create_subscription(
+                self.interval,
+                LIFETIME_COUNT,
+                KEEP_ALIVE_COUNT,
+                DataChangeCallback::new(move |data_value: DataValue, item: &MonitoredItem| {
+                    // The receiver is gone once the subscription is being torn down
+                    let _ = sender.send((item.client_handle() as usize - 1, data_value));
+                }),
+            )
+            .await?;
+        debug!("Created subscription {}", subscription_id);
+        self.subscription_ids.push(subscription_id);
+        Ok(self.subscription_ids.len() - 1)
+    }
+
+    /// Number of subscriptions the nodes are spread over
+    pub fn subscription_count(&self) -> usize {
+        self.subscription_ids.len()
+    }
+
+    /// How long the server may leave the publish requests unanswered before the subscriptions
+    /// count as silent: `intervals` publishing intervals as the server revised them, and at least
+    /// one past the keep-alive a subscription without changes is sent
+    pub fn silence_limit(&self, intervals: u32) -> Duration {
+        self.subscription_ids
+            .iter()
+            .map(|subscription_id| {
+                let (interval, keep_alive_count) = self.session
+                    .subscription_timing(*subscription_id)
+                    .unwrap_or((self.interval, KEEP_ALIVE_COUNT));
+                interval * intervals.max(keep_alive_count + 1)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Monitor another node in place of the node at `index`, keeping its position
+    pub async fn replace(&mut self, index: usize, node_id: &NodeId) -> Result<StatusCode> {
+        let (position, old_item) = self.items[index];
+        let subscription_id = self.subscription_ids[position];
+        if old_item != 0
+            && let Err(e) = self.session.delete_monitored_items(subscription_id, &[old_item]).await
+        {
+            debug!("Failed to delete monitored item {}: {}", old_item, e);
+        }
+        self.items[index].1 = 0;
+
+        let result = self.session
+            .create_monitored_items(subscription_id, TimestampsToReturn::Both, vec![item_request(index, node_id, self.interval)])
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        self.items[index].1 = result.monitored_item_id;
+        Ok(result.status_code)
+    }
+
+    /// Wait for the next notification, or `None` once the subscription is closed
+    pub async fn next(&mut self) -> Option<(usize, DataValue)> {
+        self.receiver.recv().await
+    }
+
+    /// Poll for the next notification, for waiting on several subscriptions at once
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<(usize, DataValue)>> {
+        self.receiver.poll_recv(cx)
+    }
+
+    /// Delete the subscriptions on the server, logging rather than failing on errors
+    pub async fn delete(self) {
+        for subscription_id in &self.subscription_ids {
+            if let Err(e) = self.session.delete_subscription(*subscription_id).await {
+                warn!("Failed to delete subscription {}: {}", subscription_id, e);
+            }
+        }
+    }
+}
+
+/// The MaxMonitoredItemsPerSubscription capability, `None` when the server states no limit
+async fn max_items_per_subscription(session: &ServiceSession) -> Option<usize> {
+    let node_id: NodeId = VariableId::Server_ServerCapabilities_MaxMonitoredItemsPerSubscription.into();
+    match session.read(&[ReadValueId::from(&node_id)], TimestampsToReturn::Neither, 0.0).await {
+        Ok(values) => match values.into_iter().next().and_then(|dv| dv.value) {
+            Some(Variant::UInt32(max)) if max > 0 => Some(max as usize),
+            _ => None,
+        },
+        Err(e) => {
+            debug!("Reading MaxMonitoredItemsPerSubscription failed: {}", e);
+            None
+        }
+    }
+}
+
+fn item_request(index: usize, node_id: &NodeId, interval: Duration) -> MonitoredItemCreateRequest {
+    MonitoredItemCreateRequest::new(
+        node_id.into(),
+        MonitoringMode::Reporting,
+        MonitoringParameters {
+            // Handle 0 would be replaced by a client generated one
+            client_handle: index as u32 + 1,
+            sampling_interval: interval.as_millis() as f64,
+            queue_size: 1,
+            discard_oldest: true,
+            ..Default::default()
+        },
+    )
+}
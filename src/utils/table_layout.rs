@@ -0,0 +1,271 @@
+use colored::*;
+use std::borrow::Cow;
+use tabled::builder::Builder;
+use tabled::{Table, Tabled};
+
+use crate::utils::terminal::{char_width, terminal_width};
+
+/// Terminals narrower than this get one block per row instead of a table
+const MIN_TABLE_WIDTH: usize = 60;
+
+/// The wrapped column must keep at least this many columns, or the rows become blocks
+const MIN_WRAP_WIDTH: usize = 16;
+
+/// Colour reset closing a cut line, `colored` ends every coloured text with it
+const RESET: &str = "\x1b[0m";
+
+/// How rows are laid out for the width available
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// A table as wide as its content
+    Table,
+    /// A table with the flexible column wrapped to this many columns
+    Wrapped(usize),
+    /// A block of `key: value` lines per row
+    Records,
+}
+
+/// The layout of a table whose columns are `widths` wide in `available` columns, `None` for
+/// output without a width limit. Only the `flexible` column gives way; when the others leave it
+/// too little room, the rows become blocks.
+pub fn choose_layout(widths: &[usize], flexible: usize, available: Option<usize>) -> Layout {
+    let Some(available) = available else {
+        return Layout::Table;
+    };
+    // `| a | b |`: a border and two padding spaces per column, one more border per line
+    let frame = widths.len() * 3 + 1;
+    if widths.iter().sum::<usize>() + frame <= available {
+        return Layout::Table;
+    }
+    if available < MIN_TABLE_WIDTH {
+        return Layout::Records;
+    }
+    let fixed: usize = widths.iter().enumerate().filter(|(column, _)| *column != flexible).map(|(_, width)| width).sum();
+    match available.checked_sub(fixed + frame) {
+        Some(width) if width >= MIN_WRAP_WIDTH => Layout::Wrapped(width),
+        _ => Layout::Records,
+    }
+}
+
+/// Print `rows` fitted to the terminal: the `flexible` column, usually the values, is wrapped
+/// when the table would be wider, and with `records` or when the other columns leave it too
+/// little room every row is printed as a block of `key: value` lines. Files and pipes get the
+/// table as wide as it is.
+pub fn print_table<T: Tabled>(rows: &[T], flexible: usize, records: bool) {
+    print_fitted(rows, flexible, records, false);
+}
+
+/// [`print_table`] for rows whose first column names them, such as attributes: as records every
+/// row is one `name: other columns` line
+pub fn print_keyed_table<T: Tabled>(rows: &[T], flexible: usize, records: bool) {
+    print_fitted(rows, flexible, records, true);
+}
+
+fn print_fitted<T: Tabled>(rows: &[T], flexible: usize, records: bool, keyed: bool) {
+    let headers = T::headers();
+    let cells: Vec<Vec<Cow<'_, str>>> = rows.iter().map(Tabled::fields).collect();
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|column| {
+            std::iter::once(&headers[column])
+                .chain(cells.iter().map(|row| &row[column]))
+                .map(|text| cell_width(text))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let layout = if records { Layout::Records } else { choose_layout(&widths, flexible, terminal_width()) };
+    match layout {
+        Layout::Table => println!("{}", Table::new(rows)),
+        Layout::Wrapped(width) => {
+            let mut builder = Builder::default();
+            builder.push_record(headers.iter().map(|header| header.to_string()));
+            for row in &cells {
+                builder.push_record(row.iter().enumerate().map(|(column, text)| {
+                    if column == flexible { wrap_cell(text, width) } else { text.to_string() }
+                }));
+            }
+            println!("{}", builder.build());
+        }
+        Layout::Records if keyed => print_keyed_records(&cells),
+        Layout::Records => print_records(&headers, &cells),
+    }
+}
+
+fn print_keyed_records(cells: &[Vec<Cow<'_, str>>]) {
+    let key_width = cells.iter().filter_map(|row| row.first()).map(|key| cell_width(key) + 1).max().unwrap_or(0);
+    for row in cells {
+        let Some((key, columns)) = row.split_first() else {
+            continue;
+        };
+        let text = columns.iter().map(|column| column.as_ref()).filter(|column| !column.is_empty()).collect::<Vec<_>>().join("  ");
+        let mut lines = text.lines();
+        let key = format!("{:<width$}", format!("{}:", key), width = key_width);
+        println!("{}  {}", key.bright_white(), lines.next().unwrap_or_default());
+        for line in lines {
+            println!("{:width$}  {}", "", line, width = key_width);
+        }
+    }
+}
+
+fn print_records(headers: &[Cow<'static, str>], cells: &[Vec<Cow<'_, str>>]) {
+    let key_width = headers.iter().map(|header| header.chars().count() + 1).max().unwrap_or(0);
+    for (index, row) in cells.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        for (header, text) in headers.iter().zip(row) {
+            let mut lines = text.lines();
+            let key = format!("{:<width$}", format!("{}:", header), width = key_width);
+            println!("{}  {}", key.bright_white(), lines.next().unwrap_or_default());
+            for line in lines {
+                println!("{:width$}  {}", "", line, width = key_width);
+            }
+        }
+    }
+}
+
+/// Width of a cell the way the table measures it: colour sequences are not left out, every
+/// character of them counts
+fn cell_width(text: &str) -> usize {
+    text.lines().map(|line| line.chars().map(char_width).sum()).max().unwrap_or(0)
+}
+
+/// `text` with every line broken into lines of at most `width` as the table measures them,
+/// after a space where there is one. Colour sequences are never split; a colour open at a break
+/// is reset at the end of the line and taken up again on the next one, so it does not bleed into
+/// the neighbouring cells.
+fn wrap_cell(text: &str, width: usize) -> String {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let mut current = String::new();
+        let mut current_width = 0;
+        let mut visible = false;
+        // Colour sequences since the last reset
+        let mut active = String::new();
+        // Where the line may be broken after the last space, with the colours open there
+        let mut last_space: Option<(usize, String)> = None;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                let mut sequence = String::from(c);
+                for c in chars.by_ref() {
+                    sequence.push(c);
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+                current_width += cell_width(&sequence);
+                current.push_str(&sequence);
+                if sequence == RESET {
+                    active.clear();
+                } else {
+                    active.push_str(&sequence);
+                }
+                continue;
+            }
+            let c_width = char_width(c);
+            let reserve = if active.is_empty() { 0 } else { cell_width(RESET) };
+            if visible && current_width + c_width + reserve > width {
+                let (mut head, tail, open) = match last_space.take() {
+                    Some((at, open)) => (current[..at].to_string(), current[at..].to_string(), open),
+                    None => (current.clone(), String::new(), active.clone()),
+                };
+                if !open.is_empty() {
+                    head.push_str(RESET);
+                }
+                lines.push(head);
+                current = open + &tail;
+                current_width = cell_width(&current);
+            }
+            current.push(c);
+            current_width += c_width;
+            visible = true;
+            if c == ' ' {
+                last_space = Some((current.len(), active.clone()));
+            }
+        }
+        lines.push(current);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Node ID, Attribute and Value columns of `read --all-attributes`, 122 columns with the frame
+    const WIDTHS: [usize; 3] = [20, 12, 80];
+    const VALUE: usize = 2;
+
+    #[test]
+    fn files_and_pipes_get_the_full_table() {
+        assert_eq!(choose_layout(&WIDTHS, VALUE, None), Layout::Table);
+        assert_eq!(choose_layout(&[20, 12, 5000], VALUE, None), Layout::Table);
+    }
+
+    #[test]
+    fn table_that_fits_is_kept() {
+        assert_eq!(choose_layout(&WIDTHS, VALUE, Some(122)), Layout::Table);
+        assert_eq!(choose_layout(&WIDTHS, VALUE, Some(200)), Layout::Table);
+        // Narrow terminals only matter for tables that do not fit
+        assert_eq!(choose_layout(&[8, 12], 1, Some(40)), Layout::Table);
+    }
+
+    #[test]
+    fn only_the_flexible_column_is_wrapped() {
+        assert_eq!(choose_layout(&WIDTHS, VALUE, Some(121)), Layout::Wrapped(79));
+        assert_eq!(choose_layout(&WIDTHS, VALUE, Some(120)), Layout::Wrapped(78));
+        assert_eq!(choose_layout(&WIDTHS, VALUE, Some(80)), Layout::Wrapped(38));
+        assert_eq!(choose_layout(&[80, 12, 20], 0, Some(80)), Layout::Wrapped(38));
+    }
+
+    #[test]
+    fn narrow_terminals_get_records() {
+        assert_eq!(choose_layout(&WIDTHS, VALUE, Some(MIN_TABLE_WIDTH)), Layout::Wrapped(MIN_TABLE_WIDTH - 42));
+        assert_eq!(choose_layout(&WIDTHS, VALUE, Some(MIN_TABLE_WIDTH - 1)), Layout::Records);
+        assert_eq!(choose_layout(&WIDTHS, VALUE, Some(20)), Layout::Records);
+    }
+
+    #[test]
+    fn too_little_room_for_the_flexible_column_gives_records() {
+        // The other columns and the frame take 44 columns, leaving exactly the minimum
+        assert_eq!(choose_layout(&[30, 4, 80], VALUE, Some(60)), Layout::Wrapped(MIN_WRAP_WIDTH));
+        assert_eq!(choose_layout(&[31, 4, 80], VALUE, Some(60)), Layout::Records);
+        // The other columns alone are wider than the terminal
+        assert_eq!(choose_layout(&[100, 12, 80], VALUE, Some(100)), Layout::Records);
+    }
+
+    #[test]
+    fn wrapping_breaks_after_spaces() {
+        assert_eq!(wrap_cell("the quick brown fox", 10), "the quick \nbrown fox");
+        assert_eq!(wrap_cell("short", 10), "short");
+    }
+
+    #[test]
+    fn wrapping_breaks_long_words() {
+        assert_eq!(wrap_cell("abcdefghijkl", 5), "abcde\nfghij\nkl");
+        assert_eq!(wrap_cell("温度温度温度", 4), "温度\n温度\n温度");
+    }
+
+    #[test]
+    fn wrapping_keeps_existing_lines() {
+        assert_eq!(wrap_cell("first line\nsecond", 6), "first \nline\nsecond");
+    }
+
+    #[test]
+    fn colours_do_not_bleed_across_lines() {
+        let red = "\x1b[31m";
+        let text = format!("{}abcdefghij{}", red, RESET);
+        let wrapped = wrap_cell(&text, 12);
+        let lines: Vec<&str> = wrapped.lines().collect();
+        assert!(lines.len() > 1, "{:?}", lines);
+        for line in &lines {
+            assert!(cell_width(line) <= 12, "{:?}", line);
+            assert!(line.starts_with(red), "{:?}", line);
+            assert!(line.ends_with(RESET), "{:?}", line);
+        }
+        let visible: String = lines.iter().map(|line| line.replace(red, "").replace(RESET, "")).collect();
+        assert_eq!(visible, "abcdefghij");
+    }
+}
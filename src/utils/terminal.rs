@@ -0,0 +1,304 @@
+use anyhow::{anyhow, Result};
+use std::io::{self, IsTerminal, Read, Write};
+use std::sync::{Mutex, Once};
+
+/// Terminal settings from before raw mode, taken back by whoever restores first
+static ORIGINAL: Mutex<Option<platform::Settings>> = Mutex::new(None);
+static PANIC_HOOK: Once = Once::new();
+
+/// Switch to the alternate screen and hide the cursor, and back
+const ENTER_SCREEN: &str = "\x1b[?1049h\x1b[?25l";
+const LEAVE_SCREEN: &str = "\x1b[?25h\x1b[?1049l";
+
+/// Raw mode on the alternate screen for full-screen views. Dropping it, or a panic while it is
+/// active, restores the terminal the way it was.
+pub struct RawTerminal;
+
+impl RawTerminal {
+    pub fn enter() -> Result<Self> {
+        check_interactive()?;
+
+        let original = platform::enable_raw_mode()?;
+        *ORIGINAL.lock().unwrap_or_else(|e| e.into_inner()) = Some(original);
+
+        // Panic messages are unreadable on the alternate screen and leave the shell in raw mode
+        PANIC_HOOK.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                restore();
+                previous(info);
+            }));
+        });
+
+        let mut stdout = io::stdout();
+        stdout.write_all(ENTER_SCREEN.as_bytes())?;
+        stdout.flush()?;
+        Ok(RawTerminal)
+    }
+
+    /// Columns and rows of the terminal, 80x24 when it does not say
+    pub fn size(&self) -> (usize, usize) {
+        platform::window_size().unwrap_or((80, 24))
+    }
+}
+
+/// Columns of the terminal stdout goes to, `None` when it goes to a file or pipe, which have no
+/// width limit
+pub fn terminal_width() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    platform::window_size().map(|(columns, _)| columns)
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+fn restore() {
+    if let Some(original) = ORIGINAL.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(LEAVE_SCREEN.as_bytes());
+        let _ = stdout.flush();
+        platform::restore(&original);
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use anyhow::{anyhow, Result};
+    use std::io;
+
+    pub type Settings = libc::termios;
+
+    /// Switch stdin to raw mode, returning the settings to restore
+    pub fn enable_raw_mode() -> Result<Settings> {
+        let mut original = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(anyhow!("Cannot read the terminal settings: {}", io::Error::last_os_error()));
+        }
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &raw) } != 0 {
+            return Err(anyhow!("Cannot switch the terminal to raw mode: {}", io::Error::last_os_error()));
+        }
+        Ok(original)
+    }
+
+    pub fn restore(original: &Settings) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, original) };
+    }
+
+    /// Columns and rows of the terminal stdout goes to, `None` when it does not say
+    pub fn window_size() -> Option<(usize, usize)> {
+        let mut size = unsafe { std::mem::zeroed::<libc::winsize>() };
+        if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0 || size.ws_col == 0 || size.ws_row == 0 {
+            return None;
+        }
+        Some((size.ws_col as usize, size.ws_row as usize))
+    }
+}
+
+/// Without termios there is no raw mode for full-screen views, and tables are not fitted to
+/// the window
+#[cfg(not(unix))]
+mod platform {
+    use anyhow::{anyhow, Result};
+
+    pub type Settings = ();
+
+    pub fn enable_raw_mode() -> Result<Settings> {
+        Err(anyhow!("Full-screen views are only supported in Unix terminals"))
+    }
+
+    pub fn restore(_original: &Settings) {}
+
+    pub fn window_size() -> Option<(usize, usize)> {
+        None
+    }
+}
+
+/// Full-screen views need a terminal on both ends that understands ANSI escape sequences
+pub fn check_interactive() -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(anyhow!("This view needs an interactive terminal, stdin and stdout must not be redirected"));
+    }
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return Err(anyhow!("This view needs a terminal that understands ANSI escape sequences, TERM is 'dumb'"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Escape,
+    /// Ctrl+C, raw mode delivers it as a key instead of a signal
+    Interrupt,
+    Char(char),
+}
+
+/// Read keys from stdin on a thread of their own; a blocked read must not hold up the runtime
+/// when the view ends
+pub fn spawn_key_reader() -> tokio::sync::mpsc::UnboundedReceiver<Key> {
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; 64];
+        let mut stdin = io::stdin();
+        while let Ok(len) = stdin.read(&mut buffer) {
+            if len == 0 || parse_keys(&buffer[..len]).into_iter().any(|key| sender.send(key).is_err()) {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// The keys in one read from the terminal, escape sequences arrive in one piece
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut keys = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if chars.peek().is_some_and(|c| *c == '[' || *c == 'O') => {
+                chars.next();
+                let mut sequence = String::new();
+                for c in chars.by_ref() {
+                    sequence.push(c);
+                    if c.is_ascii_alphabetic() || c == '~' {
+                        break;
+                    }
+                }
+                match sequence.as_str() {
+                    "A" => Key::Up,
+                    "B" => Key::Down,
+                    "C" => Key::Right,
+                    "D" => Key::Left,
+                    "H" | "1~" | "7~" => Key::Home,
+                    "F" | "4~" | "8~" => Key::End,
+                    "5~" => Key::PageUp,
+                    "6~" => Key::PageDown,
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Escape,
+            '\r' | '\n' => Key::Enter,
+            '\x03' => Key::Interrupt,
+            c if c.is_control() => continue,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// Columns a character takes: emoji and CJK take two, combining marks and variation selectors
+/// none. Close enough for the symbols this tool prints, without a Unicode width table.
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F | 0x231A | 0x231B | 0x23E9..=0x23F3 | 0x2705 | 0x274C | 0x2753..=0x2755 | 0x2B50
+        | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFF00..=0xFF60 | 0x1F000..=0x1FAFF => 2,
+        _ => 1,
+    }
+}
+
+/// `text` cut to `width` columns and padded to it, colour sequences are kept and the colour
+/// reset after a cut
+pub fn fit_width(text: &str, width: usize) -> String {
+    let mut fitted = String::new();
+    let mut visible = 0;
+    let mut in_escape = false;
+    let mut cut = false;
+    for c in text.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            _ if in_escape => in_escape = !c.is_ascii_alphabetic(),
+            _ if visible + char_width(c) > width => {
+                cut = true;
+                break;
+            }
+            _ => visible += char_width(c),
+        }
+        fitted.push(c);
+    }
+    if cut && text.contains('\x1b') {
+        fitted.push_str("\x1b[0m");
+    }
+    fitted.push_str(&" ".repeat(width - visible));
+    fitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_control_keys() {
+        assert_eq!(parse_keys(b"q/\r\n\x03"), [Key::Char('q'), Key::Char('/'), Key::Enter, Key::Enter, Key::Interrupt]);
+        assert_eq!(parse_keys("ä€".as_bytes()), [Key::Char('ä'), Key::Char('€')]);
+        // Other control characters are dropped
+        assert_eq!(parse_keys(b"\x01a\x7f"), [Key::Char('a')]);
+        assert_eq!(parse_keys(b""), []);
+    }
+
+    #[test]
+    fn parses_escape_sequences() {
+        assert_eq!(parse_keys(b"\x1b[A\x1b[B\x1b[C\x1b[D"), [Key::Up, Key::Down, Key::Right, Key::Left]);
+        // Application cursor mode sends O instead of [
+        assert_eq!(parse_keys(b"\x1bOA\x1bOH\x1bOF"), [Key::Up, Key::Home, Key::End]);
+        assert_eq!(parse_keys(b"\x1b[1~\x1b[7~\x1b[H"), [Key::Home, Key::Home, Key::Home]);
+        assert_eq!(parse_keys(b"\x1b[4~\x1b[8~\x1b[F"), [Key::End, Key::End, Key::End]);
+        assert_eq!(parse_keys(b"\x1b[5~\x1b[6~"), [Key::PageUp, Key::PageDown]);
+        assert_eq!(parse_keys(b"\x1b[Ak"), [Key::Up, Key::Char('k')]);
+    }
+
+    #[test]
+    fn lone_and_unknown_escapes() {
+        assert_eq!(parse_keys(b"\x1b"), [Key::Escape]);
+        assert_eq!(parse_keys(b"\x1bx"), [Key::Escape, Key::Char('x')]);
+        // Unknown sequences are skipped as a whole, F5 and Shift+Up here
+        assert_eq!(parse_keys(b"\x1b[15~\x1b[1;2Aj"), [Key::Char('j')]);
+        // A sequence cut off at the end of the read is dropped
+        assert_eq!(parse_keys(b"j\x1b[5"), [Key::Char('j')]);
+    }
+
+    #[test]
+    fn fits_plain_text() {
+        assert_eq!(fit_width("abc", 5), "abc  ");
+        assert_eq!(fit_width("abcdef", 4), "abcd");
+        assert_eq!(fit_width("abcd", 4), "abcd");
+        assert_eq!(fit_width("", 3), "   ");
+        assert_eq!(fit_width("abc", 0), "");
+    }
+
+    #[test]
+    fn fits_wide_and_zero_width_characters() {
+        assert_eq!(fit_width("✅ ok", 6), "✅ ok ");
+        // A wide character that does not fit is left out and its column padded
+        assert_eq!(fit_width("a✅", 2), "a ");
+        assert_eq!(fit_width("e\u{301}x", 2), "e\u{301}x");
+        assert_eq!(char_width('漢'), 2);
+        assert_eq!(char_width('\u{FE0F}'), 0);
+        assert_eq!(char_width('a'), 1);
+    }
+
+    #[test]
+    fn colour_sequences_take_no_columns() {
+        assert_eq!(fit_width("\x1b[31mred\x1b[0m", 4), "\x1b[31mred\x1b[0m ");
+        // The colour is reset after a cut
+        assert_eq!(fit_width("\x1b[31mredder\x1b[0m", 3), "\x1b[31mred\x1b[0m");
+        assert_eq!(fit_width("abcdef", 3), "abc");
+    }
+}
@@ -0,0 +1,251 @@
+use chrono::{DateTime, Days, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::time::Duration;
+
+/// Units of durations with their length in seconds
+const DURATION_UNITS: [(&str, f64); 6] = [
+    ("ms", 0.001),
+    ("s", 1.0),
+    ("m", 60.0),
+    ("h", 3600.0),
+    ("d", 86400.0),
+    ("w", 604800.0),
+];
+
+const TIME_FORMS: &str = "now, today, yesterday or tomorrow with an optional HH:MM[:SS], a time of day, \
+    YYYY-MM-DD [HH:MM[:SS]], an offset like -24h or now+30m, or an RFC 3339 timestamp";
+
+/// Parse a duration such as `90m`, `1h30m`, `1.5h`, `500ms` or `2d 12h`; a plain number counts
+/// as seconds so the flags keep accepting what they took before
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    parse_duration_parts(text.trim()).map_err(|e| format!("invalid duration \"{}\": {}", text.trim(), e))
+}
+
+fn parse_duration_parts(text: &str) -> Result<Duration, String> {
+    if text.is_empty() {
+        return Err("it is empty".to_string());
+    }
+    if let Ok(seconds) = text.parse::<f64>() {
+        return seconds_to_duration(seconds);
+    }
+
+    let mut seconds = 0.0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let position = text.len() - rest.len() + 1;
+        let number_len = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        if number_len == 0 {
+            return Err(format!("expected a number at position {}, found \"{}\"", position, rest));
+        }
+        let number_text = &rest[..number_len];
+        let number: f64 = number_text
+            .parse()
+            .map_err(|_| format!("\"{}\" at position {} is not a number", number_text, position))?;
+        rest = &rest[number_len..];
+
+        let unit_len = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        let unit = &rest[..unit_len];
+        let Some((_, factor)) = DURATION_UNITS.iter().find(|(name, _)| name.eq_ignore_ascii_case(unit)) else {
+            let found = if unit.is_empty() { "no unit".to_string() } else { format!("unknown unit \"{}\"", unit) };
+            return Err(format!(
+                "{} after {} at position {}, expected ms, s, m, h, d or w",
+                found, number_text, position + number_len
+            ));
+        };
+        seconds += number * factor;
+        rest = rest[unit_len..].trim_start();
+    }
+    seconds_to_duration(seconds)
+}
+
+/// A duration the way [`parse_duration`] takes it, e.g. `1h 30m`, `45s` or `2.5s`
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds < 60 && duration.subsec_millis() > 0 {
+        return if seconds == 0 { format!("{}ms", duration.subsec_millis()) } else { format!("{:.1}s", duration.as_secs_f64()) };
+    }
+    let parts: Vec<String> = [(seconds / 86400, "d"), (seconds % 86400 / 3600, "h"), (seconds % 3600 / 60, "m"), (seconds % 60, "s")]
+        .into_iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect();
+    if parts.is_empty() { "0s".to_string() } else { parts.join(" ") }
+}
+
+fn seconds_to_duration(seconds: f64) -> Result<Duration, String> {
+    if seconds < 0.0 {
+        return Err("it must not be negative".to_string());
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| "it is too long".to_string())
+}
+
+/// Parse a point in time, relative to now or absolute, into UTC. Dates and times of day without
+/// an offset are local time.
+pub fn parse_time(text: &str) -> Result<DateTime<Utc>, String> {
+    parse_time_at(text, Utc::now())
+}
+
+/// [`parse_time`] with `now` given, relative inputs are offsets from it
+pub fn parse_time_at(text: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    parse_time_in(text, now, &Local)
+}
+
+/// [`parse_time_at`] with dates and times of day in `zone` instead of local time
+fn parse_time_in<Tz: TimeZone>(text: &str, now: DateTime<Utc>, zone: &Tz) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(format!("empty time, expected {}", TIME_FORMS));
+    }
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    let lower = text.to_lowercase();
+    let (sign, offset) = match lower.strip_prefix("now") {
+        Some("") => return Ok(now),
+        Some(rest) => match rest.trim_start().split_at_checked(1) {
+            Some(("+", offset)) => (1, offset),
+            Some(("-", offset)) => (-1, offset),
+            _ => return Err(format!("\"{}\" after \"now\" in \"{}\" is no offset, expected e.g. now-2h", rest.trim(), text)),
+        },
+        None => match lower.split_at_checked(1) {
+            Some(("+", offset)) => (1, offset),
+            Some(("-", offset)) => (-1, offset),
+            _ => return parse_local(text, &lower, now, zone),
+        },
+    };
+    let offset = parse_duration(offset)?;
+    let offset = chrono::Duration::from_std(offset).map_err(|_| format!("offset \"{}\" is too large", text))?;
+    let time = if sign > 0 { now.checked_add_signed(offset) } else { now.checked_sub_signed(offset) };
+    time.ok_or_else(|| format!("\"{}\" is out of range", text))
+}
+
+/// Keywords, dates and times of day, all in the wall-clock time of `zone`
+fn parse_local<Tz: TimeZone>(text: &str, lower: &str, now: DateTime<Utc>, zone: &Tz) -> Result<DateTime<Utc>, String> {
+    let today = now.with_timezone(zone).date_naive();
+    // `2024-03-31T02:30` without an offset is local time like `2024-03-31 02:30`
+    let split = lower
+        .split_once(' ')
+        .or_else(|| lower.split_once('t').filter(|(day, _)| NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok()));
+    let (day, time) = match split.map(|(day, time)| (day, time.trim())) {
+        Some((day, time)) => (day, Some(time)),
+        None if lower.contains(':') => ("today", Some(lower)),
+        None => (lower, None),
+    };
+
+    let date = match day {
+        "today" => today,
+        "yesterday" => today.checked_sub_days(Days::new(1)).ok_or("yesterday is out of range")?,
+        "tomorrow" => today.checked_add_days(Days::new(1)).ok_or("tomorrow is out of range")?,
+        _ => NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|_| {
+            let token = text.get(..day.len()).unwrap_or(day);
+            format!("unknown day \"{}\" in \"{}\", expected {}", token, text, TIME_FORMS)
+        })?,
+    };
+    let time = match time {
+        Some(time) => NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+            .map_err(|_| {
+                let token = text.get(text.len() - time.len()..).unwrap_or(time);
+                format!("\"{}\" in \"{}\" is no time of day, expected HH:MM or HH:MM:SS", token, text)
+            })?,
+        None => NaiveTime::MIN,
+    };
+    local_to_utc(zone, date.and_time(time), text)
+}
+
+/// Local wall-clock time in UTC. Of the two instants of a time repeated when clocks go back,
+/// the earlier one is taken; times skipped when clocks go forward do not exist.
+fn local_to_utc<Tz: TimeZone>(zone: &Tz, local: NaiveDateTime, text: &str) -> Result<DateTime<Utc>, String> {
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(time) => Ok(time.with_timezone(&Utc)),
+        LocalResult::Ambiguous(earliest, _) => Ok(earliest.with_timezone(&Utc)),
+        LocalResult::None => Err(format!(
+            "\"{}\" ({}) does not exist in the local time zone, clocks skip it for daylight saving time",
+            text, local.format("%Y-%m-%d %H:%M:%S")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America::New_York, Europe::Berlin};
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn durations_with_units() {
+        assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2d 12h"), Ok(Duration::from_secs(216000)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("10"), Ok(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn durations_out_of_range_are_errors() {
+        assert!(parse_duration("-5").unwrap_err().contains("negative"));
+        assert!(parse_duration("1e30").unwrap_err().contains("too long"));
+        assert!(parse_duration("99999999999999999999w").unwrap_err().contains("too long"));
+        assert!(parse_duration("inf").is_err());
+        assert!(parse_duration("NaN").is_err());
+    }
+
+    #[test]
+    fn durations_point_at_the_failing_token() {
+        assert_eq!(
+            parse_duration("1h30x").unwrap_err(),
+            "invalid duration \"1h30x\": unknown unit \"x\" after 30 at position 5, expected ms, s, m, h, d or w"
+        );
+        assert!(parse_duration("h").unwrap_err().contains("expected a number at position 1"));
+    }
+
+    #[test]
+    fn negative_offsets() {
+        let now = utc("2024-06-15T12:00:00Z");
+        assert_eq!(parse_time_in("-24h", now, &Berlin), Ok(utc("2024-06-14T12:00:00Z")));
+        assert_eq!(parse_time_in("now-90m", now, &Berlin), Ok(utc("2024-06-15T10:30:00Z")));
+        assert_eq!(parse_time_in("now+1d", now, &Berlin), Ok(utc("2024-06-16T12:00:00Z")));
+        assert_eq!(parse_time_in("2024-03-10T01:30:00-05:00", now, &Berlin), Ok(utc("2024-03-10T06:30:00Z")));
+        assert_eq!(parse_time_in("2024-11-03T01:30:00-04:00", now, &Berlin), Ok(utc("2024-11-03T05:30:00Z")));
+    }
+
+    #[test]
+    fn local_times_in_a_zone_behind_utc() {
+        let now = utc("2024-06-15T02:00:00Z");
+        // Still the 14th in New York
+        assert_eq!(parse_time_in("today 06:00", now, &New_York), Ok(utc("2024-06-14T10:00:00Z")));
+        assert_eq!(parse_time_in("yesterday", now, &New_York), Ok(utc("2024-06-13T04:00:00Z")));
+    }
+
+    #[test]
+    fn local_times_next_to_clocks_going_forward() {
+        // Berlin goes from 02:00 CET to 03:00 CEST on 2024-03-31
+        let now = utc("2024-03-31T12:00:00Z");
+        assert_eq!(parse_time_in("yesterday 06:00", now, &Berlin), Ok(utc("2024-03-30T05:00:00Z")));
+        assert_eq!(parse_time_in("today 06:00", now, &Berlin), Ok(utc("2024-03-31T04:00:00Z")));
+        assert_eq!(parse_time_in("2024-03-31 01:59:59", now, &Berlin), Ok(utc("2024-03-31T00:59:59Z")));
+        assert_eq!(parse_time_in("2024-03-31 03:00", now, &Berlin), Ok(utc("2024-03-31T01:00:00Z")));
+        assert!(parse_time_in("2024-03-31 02:30", now, &Berlin).unwrap_err().contains("does not exist"));
+        assert!(parse_time_in("2024-03-10 02:30", now, &New_York).unwrap_err().contains("does not exist"));
+    }
+
+    #[test]
+    fn local_times_next_to_clocks_going_back() {
+        // Of the repeated hour the earlier instant is taken
+        let now = utc("2024-10-27T12:00:00Z");
+        assert_eq!(parse_time_in("2024-10-27 02:30", now, &Berlin), Ok(utc("2024-10-27T00:30:00Z")));
+        assert_eq!(parse_time_in("2024-10-27T03:00", now, &Berlin), Ok(utc("2024-10-27T02:00:00Z")));
+        assert_eq!(parse_time_in("2024-11-03 01:30", now, &New_York), Ok(utc("2024-11-03T05:30:00Z")));
+    }
+
+    #[test]
+    fn times_point_at_the_failing_token() {
+        let now = utc("2024-06-15T12:00:00Z");
+        assert!(parse_time_in("someday 06:00", now, &Berlin).unwrap_err().starts_with("unknown day \"someday\""));
+        assert!(parse_time_in("today 25:00", now, &Berlin).unwrap_err().starts_with("\"25:00\" in \"today 25:00\""));
+        assert!(parse_time_in("now*2h", now, &Berlin).unwrap_err().starts_with("\"*2h\" after \"now\""));
+    }
+}
@@ -0,0 +1,210 @@
+use opcua::types::Variant;
+
+/// Quantities whose units can be converted into each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitFamily {
+    Temperature,
+    Pressure,
+    VolumeFlow,
+    Length,
+}
+
+/// A unit with its linear mapping onto the base unit of its family: `base = value * factor + offset`
+#[derive(Debug, Clone, Copy)]
+pub struct Unit {
+    /// UNECE common code, e.g. `CEL`
+    pub code: &'static str,
+    pub symbol: &'static str,
+    pub family: UnitFamily,
+    factor: f64,
+    offset: f64,
+}
+
+const fn unit(code: &'static str, symbol: &'static str, family: UnitFamily, factor: f64, offset: f64) -> Unit {
+    Unit { code, symbol, family, factor, offset }
+}
+
+/// Base units: K, Pa, m³/s and m
+const UNITS: &[Unit] = &[
+    unit("KEL", "K", UnitFamily::Temperature, 1.0, 0.0),
+    unit("CEL", "°C", UnitFamily::Temperature, 1.0, 273.15),
+    unit("FAH", "°F", UnitFamily::Temperature, 5.0 / 9.0, 273.15 - 32.0 * 5.0 / 9.0),
+    unit("PAL", "Pa", UnitFamily::Pressure, 1.0, 0.0),
+    unit("A97", "hPa", UnitFamily::Pressure, 100.0, 0.0),
+    unit("KPA", "kPa", UnitFamily::Pressure, 1_000.0, 0.0),
+    unit("MPA", "MPa", UnitFamily::Pressure, 1_000_000.0, 0.0),
+    unit("MBR", "mbar", UnitFamily::Pressure, 100.0, 0.0),
+    unit("BAR", "bar", UnitFamily::Pressure, 100_000.0, 0.0),
+    unit("PS", "psi", UnitFamily::Pressure, 6_894.757_293_168, 0.0),
+    unit("MQS", "m³/s", UnitFamily::VolumeFlow, 1.0, 0.0),
+    unit("MQH", "m³/h", UnitFamily::VolumeFlow, 1.0 / 3_600.0, 0.0),
+    unit("L2", "l/min", UnitFamily::VolumeFlow, 1.0 / 60_000.0, 0.0),
+    unit("E32", "l/h", UnitFamily::VolumeFlow, 1.0 / 3_600_000.0, 0.0),
+    unit("G2", "gal/min", UnitFamily::VolumeFlow, 0.003_785_411_784 / 60.0, 0.0),
+    unit("MTR", "m", UnitFamily::Length, 1.0, 0.0),
+    unit("KMT", "km", UnitFamily::Length, 1_000.0, 0.0),
+    unit("CMT", "cm", UnitFamily::Length, 0.01, 0.0),
+    unit("MMT", "mm", UnitFamily::Length, 0.001, 0.0),
+    unit("INH", "in", UnitFamily::Length, 0.0254, 0.0),
+    unit("FOT", "ft", UnitFamily::Length, 0.3048, 0.0),
+];
+
+/// Decimal places kept after conversion
+const CONVERTED_DECIMALS: i32 = 3;
+
+/// Look up a unit by UNECE code or symbol, e.g. `FAH`, `°F` or `degF`
+pub fn find_unit(name: &str) -> Option<&'static Unit> {
+    let name = name.trim();
+    let normalized = match name.to_ascii_lowercase().as_str() {
+        "degc" | "c" => "°C",
+        "degf" | "f" => "°F",
+        "m3/h" => "m³/h",
+        "m3/s" => "m³/s",
+        "gpm" => "gal/min",
+        _ => name,
+    };
+
+    UNITS.iter().find(|u| {
+        u.code.eq_ignore_ascii_case(normalized) || u.symbol == normalized || u.symbol.eq_ignore_ascii_case(normalized)
+    })
+}
+
+/// Symbols of all units in the conversion table
+pub fn supported_symbols() -> Vec<&'static str> {
+    UNITS.iter().map(|u| u.symbol).collect()
+}
+
+/// Look up a unit by the UnitId of an EUInformation structure
+pub fn find_unit_by_id(unit_id: i32) -> Option<&'static Unit> {
+    UNITS.iter().find(|u| unece_unit_id(u.code) == unit_id)
+}
+
+/// UnitId as defined by OPC UA Part 8: the common code's ASCII bytes packed big-endian
+pub fn unece_unit_id(code: &str) -> i32 {
+    code.bytes().fold(0, |id, byte| (id << 8) | byte as i32)
+}
+
+/// Convert between units of the same family, `None` when the families differ
+pub fn convert(value: f64, from: &Unit, to: &Unit) -> Option<f64> {
+    if from.family != to.family {
+        return None;
+    }
+
+    let base = value * from.factor + from.offset;
+    Some(round((base - to.offset) / to.factor, CONVERTED_DECIMALS))
+}
+
+/// Round half away from zero, avoiding `-0` for tiny negative results
+fn round(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    let rounded = (value * scale).round() / scale;
+    if rounded == 0.0 { 0.0 } else { rounded }
+}
+
+/// Numeric scalar values as f64, `None` for anything that cannot be converted
+pub fn numeric_value(value: &Variant) -> Option<f64> {
+    Some(match value {
+        Variant::SByte(v) => *v as f64,
+        Variant::Byte(v) => *v as f64,
+        Variant::Int16(v) => *v as f64,
+        Variant::UInt16(v) => *v as f64,
+        Variant::Int32(v) => *v as f64,
+        Variant::UInt32(v) => *v as f64,
+        Variant::Int64(v) => *v as f64,
+        Variant::UInt64(v) => *v as f64,
+        Variant::Float(v) => *v as f64,
+        Variant::Double(v) => *v,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn converted(value: f64, from: &str, to: &str) -> Option<f64> {
+        convert(value, find_unit(from).unwrap(), find_unit(to).unwrap())
+    }
+
+    #[test]
+    fn table_is_consistent() {
+        for (idx, unit) in UNITS.iter().enumerate() {
+            assert!(UNITS[..idx].iter().all(|other| other.code != unit.code && other.symbol != unit.symbol), "{} twice", unit.code);
+            assert_eq!(find_unit(unit.code).unwrap().code, unit.code);
+            assert_eq!(find_unit(unit.symbol).unwrap().code, unit.code);
+            assert_eq!(find_unit_by_id(unece_unit_id(unit.code)).unwrap().code, unit.code);
+            assert_eq!(convert(12.345, unit, unit), Some(12.345));
+        }
+        assert_eq!(supported_symbols().len(), UNITS.len());
+    }
+
+    #[test]
+    fn unit_ids_pack_the_common_code() {
+        assert_eq!(unece_unit_id("CEL"), 4408652);
+        assert_eq!(unece_unit_id("FAH"), 4604232);
+        assert_eq!(find_unit_by_id(4342098).unwrap().symbol, "bar");
+        assert!(find_unit_by_id(0).is_none());
+    }
+
+    #[test]
+    fn units_by_alias() {
+        for name in ["degF", "F", "fah", "°F", " FAH "] {
+            assert_eq!(find_unit(name).unwrap().code, "FAH", "{}", name);
+        }
+        assert_eq!(find_unit("m3/h").unwrap().code, "MQH");
+        assert_eq!(find_unit("gpm").unwrap().code, "G2");
+        assert_eq!(find_unit("PSI").unwrap().code, "PS");
+        assert!(find_unit("furlong").is_none());
+        assert!(find_unit("").is_none());
+    }
+
+    #[test]
+    fn temperatures() {
+        assert_eq!(converted(100.0, "°C", "°F"), Some(212.0));
+        assert_eq!(converted(-40.0, "°C", "°F"), Some(-40.0));
+        assert_eq!(converted(0.0, "°C", "K"), Some(273.15));
+        assert_eq!(converted(98.6, "°F", "°C"), Some(37.0));
+        assert_eq!(converted(0.0, "K", "°F"), Some(-459.67));
+    }
+
+    #[test]
+    fn pressures_flows_and_lengths() {
+        assert_eq!(converted(1.0, "bar", "psi"), Some(14.504));
+        assert_eq!(converted(1.0, "bar", "kPa"), Some(100.0));
+        assert_eq!(converted(1013.25, "hPa", "bar"), Some(1.013));
+        assert_eq!(converted(2.5, "MPa", "mbar"), Some(25000.0));
+        assert_eq!(converted(1.0, "m³/h", "l/min"), Some(16.667));
+        assert_eq!(converted(10.0, "gal/min", "l/min"), Some(37.854));
+        assert_eq!(converted(1.0, "in", "mm"), Some(25.4));
+        assert_eq!(converted(1.0, "ft", "m"), Some(0.305));
+        assert_eq!(converted(1.5, "km", "cm"), Some(150000.0));
+    }
+
+    #[test]
+    fn incompatible_units() {
+        assert_eq!(converted(1.0, "bar", "°C"), None);
+        assert_eq!(converted(1.0, "m", "m³/h"), None);
+    }
+
+    #[test]
+    fn rounding() {
+        // Half away from zero, at three decimals
+        assert_eq!(round(0.0625, 3), 0.063);
+        assert_eq!(round(-0.0625, 3), -0.063);
+        assert_eq!(round(1.0004, 3), 1.0);
+        assert_eq!(round(1.0006, 3), 1.001);
+        assert!(round(-0.0001, 3).is_sign_positive());
+        assert!(converted(-0.0001, "m", "m").unwrap().is_sign_positive());
+        assert_eq!(converted(1.0, "l/h", "m³/s"), Some(0.0));
+    }
+
+    #[test]
+    fn numeric_values() {
+        assert_eq!(numeric_value(&Variant::from(-3i8)), Some(-3.0));
+        assert_eq!(numeric_value(&Variant::from(u64::MAX)), Some(u64::MAX as f64));
+        assert_eq!(numeric_value(&Variant::from(1.5f32)), Some(1.5));
+        assert_eq!(numeric_value(&Variant::from("12")), None);
+        assert_eq!(numeric_value(&Variant::from(true)), None);
+        assert_eq!(numeric_value(&Variant::Empty), None);
+    }
+}
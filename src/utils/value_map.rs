@@ -0,0 +1,128 @@
+use anyhow::{anyhow, Result};
+use opcua::types::{NodeId, Variant};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::utils::formatter::{format_data_type, localized_text, parse_node_id_text};
+use crate::utils::labels::parse_toml_line;
+
+/// Value map of the current run, installed once from `--value-map`
+static VALUE_MAP: OnceLock<ValueMap> = OnceLock::new();
+
+/// Prefix of sections whose rules apply to every node of a DataType
+const TYPE_PREFIX: &str = "type:";
+
+/// Display words for raw values loaded with `--value-map`, e.g. `ON`/`OFF` for a Boolean.
+///
+/// The file is either a JSON object of sections or TOML-style `[section]` headers followed by
+/// `<raw value> = "<display>"` lines, where `#` starts a comment. A section is a node ID, or
+/// `type:` and a DataType by name or node ID; rules of a node take precedence over the ones of
+/// its DataType.
+#[derive(Debug, Clone, Default)]
+pub struct ValueMap {
+    by_node: HashMap<NodeId, HashMap<String, String>>,
+    /// Keyed by the DataType as `format_data_type` names it
+    by_type: HashMap<String, HashMap<String, String>>,
+}
+
+impl ValueMap {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read value map {}: {}", path, e))?;
+
+        let sections: Vec<(String, Vec<(String, String)>)> = if path.to_lowercase().ends_with(".json") {
+            let map: HashMap<String, HashMap<String, String>> = serde_json::from_str(&content)
+                .map_err(|e| anyhow!("{} is not a JSON object of sections mapping raw values to display strings: {}", path, e))?;
+            map.into_iter().map(|(section, rules)| (section, rules.into_iter().collect())).collect()
+        } else {
+            let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+            for (line_no, line) in content.lines().enumerate().map(|(idx, line)| (idx + 1, line.trim())) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                if let Some(header) = line.strip_prefix('[') {
+                    let header = header.split_once(']').map(|(header, _)| header.trim())
+                        .ok_or_else(|| anyhow!("{}:{}: unterminated section header", path, line_no))?;
+                    let header = header.strip_prefix('"').and_then(|header| header.strip_suffix('"')).unwrap_or(header);
+                    sections.push((header.to_string(), Vec::new()));
+                    continue;
+                }
+                let rule = parse_toml_line(line).map_err(|e| anyhow!("{}:{}: {}", path, line_no, e))?;
+                match sections.last_mut() {
+                    Some((_, rules)) => rules.push(rule),
+                    None => return Err(anyhow!("{}:{}: rule outside of a [<node id>] or [type:<DataType>] section", path, line_no)),
+                }
+            }
+            sections
+        };
+
+        let mut value_map = ValueMap::default();
+        for (section, rules) in sections {
+            if let Some((raw, _)) = rules.iter().find(|(_, display)| display.is_empty()) {
+                return Err(anyhow!("{}: empty display string for {} in [{}]", path, raw, section));
+            }
+            let rules = rules.into_iter();
+            match section.strip_prefix(TYPE_PREFIX) {
+                Some(data_type) => {
+                    let data_type = data_type.trim();
+                    if data_type.is_empty() {
+                        return Err(anyhow!("{}: [{}] names no DataType", path, section));
+                    }
+                    // `type:i=1` and `type:Boolean` are the same rules
+                    let name = parse_node_id_text(data_type).map_or_else(|| data_type.to_string(), |id| format_data_type(&id));
+                    value_map.by_type.entry(name).or_default().extend(rules);
+                }
+                None => {
+                    let node_id = parse_node_id_text(&section)
+                        .ok_or_else(|| anyhow!("{}: [{}] is neither a node ID nor {}<DataType>", path, section, TYPE_PREFIX))?;
+                    value_map.by_node.entry(node_id).or_default().extend(rules);
+                }
+            }
+        }
+
+        Ok(value_map)
+    }
+}
+
+/// Make the value map available to the value formatting of read, browse and monitor
+pub fn install(value_map: ValueMap) {
+    let _ = VALUE_MAP.set(value_map);
+}
+
+/// Whether any rule applies by DataType, so the DataType of values must be known
+pub fn maps_types() -> bool {
+    VALUE_MAP.get().is_some_and(|value_map| !value_map.by_type.is_empty())
+}
+
+/// The display string for `value` of a node, its DataType as `format_data_type` names it or
+/// empty when unknown. Values without a rule are `None` and shown as they are.
+pub fn mapped_value(node_id: &NodeId, data_type: &str, value: &Variant) -> Option<&'static str> {
+    let value_map = VALUE_MAP.get()?;
+    let raw = raw_key(value)?;
+    value_map.by_node.get(node_id)
+        .and_then(|rules| rules.get(&raw))
+        .or_else(|| value_map.by_type.get(data_type)?.get(&raw))
+        .map(String::as_str)
+}
+
+/// A scalar value as it is written in rules: `true`/`false`, numbers in decimal and texts without
+/// quotes. Enumerations are matched by their number.
+fn raw_key(value: &Variant) -> Option<String> {
+    Some(match value {
+        Variant::Boolean(v) => v.to_string(),
+        Variant::SByte(v) => v.to_string(),
+        Variant::Byte(v) => v.to_string(),
+        Variant::Int16(v) => v.to_string(),
+        Variant::UInt16(v) => v.to_string(),
+        Variant::Int32(v) => v.to_string(),
+        Variant::UInt32(v) => v.to_string(),
+        Variant::Int64(v) => v.to_string(),
+        Variant::UInt64(v) => v.to_string(),
+        Variant::Float(v) => v.to_string(),
+        Variant::Double(v) => v.to_string(),
+        Variant::String(v) => v.as_ref().to_string(),
+        Variant::LocalizedText(v) => localized_text(v).to_string(),
+        _ => return None,
+    })
+}
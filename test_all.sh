@@ -136,6 +136,15 @@ else
     echo "❌ Certificate validation failed"
 fi
 
+echo ""
+echo "8️⃣  Testing the examples against the command line..."
+echo "-----------------------------------------------------"
+if $WALKER examples --check; then
+    echo "✅ All examples parse"
+else
+    echo "❌ Some examples no longer parse"
+fi
+
 echo ""
 echo "🎉 All tests completed!"
 echo "========================"
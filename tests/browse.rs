@@ -0,0 +1,63 @@
+mod common;
+
+use opcua::types::*;
+use opcua_walker::commands::browse::{browse_tree, TreeNode};
+use opcua_walker::commands::ParsedCommand;
+
+use common::{scalars, TestServer};
+
+fn names(tree: &[TreeNode]) -> Vec<&str> {
+    tree.iter().map(|node| node.reference.browse_name.name.as_ref()).collect()
+}
+
+fn child<'a>(tree: &'a [TreeNode], name: &str) -> &'a TreeNode {
+    tree.iter()
+        .find(|node| node.reference.browse_name.name.as_ref() == name)
+        .unwrap_or_else(|| panic!("no child {} among {:?}", name, names(tree)))
+}
+
+async fn browse(server: &TestServer, args: &[&str]) -> Vec<TreeNode> {
+    let client = server.connect().await;
+    let ParsedCommand::Browse(args) = server.command(args) else { unreachable!() };
+    browse_tree(client.session().unwrap(), &args, false).await.unwrap()
+}
+
+#[tokio::test]
+async fn browses_the_folders_below_a_node() {
+    let server = TestServer::start().await;
+    let plant = server.node("Plant").to_string();
+    let tree = browse(&server, &["browse", "--node", &plant, "--depth", "1", "--sort", "name"]).await;
+
+    assert_eq!(names(&tree), ["Arrays", "Machine", "Scalars", "Structures"]);
+    let scalars_folder = child(&tree, "Scalars");
+    assert_eq!(scalars_folder.reference.node_class, NodeClass::Object);
+    assert_eq!(scalars_folder.reference.type_definition.node_id, NodeId::from(ObjectTypeId::FolderType));
+    assert_eq!(scalars_folder.children.len(), scalars().len());
+    assert_eq!(child(&child(&tree, "Arrays").children, "Int32Array").reference.node_class, NodeClass::Variable);
+}
+
+#[tokio::test]
+async fn depth_limits_the_tree() {
+    let server = TestServer::start().await;
+    let plant = server.node("Plant").to_string();
+    let tree = browse(&server, &["browse", "--node", &plant, "--depth", "0"]).await;
+
+    assert_eq!(tree.len(), 4);
+    assert!(tree.iter().all(|node| node.children.is_empty()));
+}
+
+#[tokio::test]
+async fn classes_and_filter_prune_the_tree() {
+    let server = TestServer::start().await;
+    let plant = server.node("Plant").to_string();
+
+    let methods = browse(&server, &["browse", "--node", &plant, "--classes", "method"]).await;
+    assert_eq!(names(&methods), ["Machine"]);
+    assert_eq!(names(&methods[0].children), ["Scale"]);
+
+    let filtered = browse(&server, &["browse", "--node", &plant, "--filter", "int16"]).await;
+    assert_eq!(names(&filtered), ["Scalars"]);
+    let mut found = names(&filtered[0].children);
+    found.sort();
+    assert_eq!(found, ["Int16", "UInt16"]);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use opcua::types::*;
+use opcua_walker::commands::call::{call_method, resolve_target, CallArgs};
+use opcua_walker::commands::ParsedCommand;
+
+use common::TestServer;
+
+fn call_args(server: &TestServer, args: &[&str]) -> CallArgs {
+    let ParsedCommand::Call(args) = server.command(args) else { unreachable!() };
+    args
+}
+
+/// Call the method the arguments target, returning the method and object it was called on
+async fn call(server: &TestServer, args: &[&str]) -> (NodeId, NodeId, CallMethodResult) {
+    let client = server.connect().await;
+    let args = call_args(server, args);
+    let (method_id, object_id) = resolve_target(&client, &args).await.unwrap();
+    let response = call_method(client.session().unwrap(), &method_id, &object_id, &args).await.unwrap();
+    let result = response.results.unwrap().into_iter().next().expect("one result");
+    (method_id, object_id, result)
+}
+
+#[tokio::test]
+async fn calls_a_method_with_typed_arguments() {
+    let server = TestServer::start().await;
+    let (method, object) = (server.node("Scale").to_string(), server.node("Machine").to_string());
+    let (_, _, result) = call(&server, &["call", &method, &object, "--args", "2.5,4"]).await;
+
+    assert_eq!(result.status_code, StatusCode::Good);
+    assert_eq!(result.output_arguments, Some(vec![Variant::Double(10.0)]));
+}
+
+#[tokio::test]
+async fn json_arguments_keep_their_types() {
+    let server = TestServer::start().await;
+    let (method, object) = (server.node("Scale").to_string(), server.node("Machine").to_string());
+    let (_, _, result) = call(&server, &["call", &method, &object, "--args", "[0.5, 3]"]).await;
+
+    assert_eq!(result.output_arguments, Some(vec![Variant::Double(1.5)]));
+}
+
+#[tokio::test]
+async fn finds_the_object_of_a_method() {
+    let server = TestServer::start().await;
+    let (method_id, object_id, result) = call(&server, &["call", &server.node("Scale").to_string(), "--args", "1.0,2"]).await;
+
+    assert_eq!((method_id, object_id), (server.node("Scale"), server.node("Machine")));
+    assert_eq!(result.output_arguments, Some(vec![Variant::Double(2.0)]));
+}
+
+#[tokio::test]
+async fn finds_a_method_by_name() {
+    let server = TestServer::start().await;
+    let plant = server.node("Plant").to_string();
+    let (method_id, object_id, _) = call(&server, &["call", "Scale", "--search-root", &plant, "--args", "1.0,1"]).await;
+
+    assert_eq!((method_id, object_id), (server.node("Scale"), server.node("Machine")));
+}
+
+#[tokio::test]
+async fn wrong_argument_types_fail_the_call() {
+    let server = TestServer::start().await;
+    let (method, object) = (server.node("Scale").to_string(), server.node("Machine").to_string());
+    let (_, _, result) = call(&server, &["call", &method, &object, "--args", "text,4"]).await;
+
+    assert_eq!(result.status_code, StatusCode::BadTypeMismatch);
+    assert!(result.output_arguments.unwrap_or_default().is_empty());
+}
+
+#[tokio::test]
+async fn unknown_methods_are_not_found() {
+    let server = TestServer::start().await;
+    let client = server.connect().await;
+    let plant = server.node("Plant").to_string();
+    let args = call_args(&server, &["call", "Missing", "--search-root", &plant]);
+
+    let error = resolve_target(&client, &args).await.unwrap_err();
+    assert_eq!(error.to_string(), "Method 'Missing' not found");
+}
@@ -0,0 +1,225 @@
+//! An in-process OPC-UA server with a known address space, and a client connected to it
+//!
+//! The address space below the Objects folder:
+//!
+//! ```text
+//! Plant
+//! ├── Scalars      one variable per built-in scalar type, named after the type
+//! ├── Arrays       Int32Array [1, 2, 3]
+//! ├── Structures   Range, an ExtensionObject {low: 0, high: 100}
+//! └── Machine      object with Scale(Value: Double, Factor: Int32) -> Result: Double
+//! Parameters
+//! ├── Setpoint     Double 21.5, writable
+//! └── Locked       Int32 7, writable but not by the session's user
+//! ```
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::Parser;
+use opcua::nodes::{AccessLevel, MethodBuilder, ObjectBuilder, VariableBuilder};
+use opcua::server::diagnostics::NamespaceMetadata;
+use opcua::server::node_manager::memory::{simple_node_manager, SimpleNodeManager};
+use opcua::server::{ServerBuilder, ServerHandle};
+use opcua::types::*;
+use tokio::net::TcpListener;
+
+use opcua_walker::client::OpcUaClient;
+use opcua_walker::commands::{self, ParsedCommand};
+use opcua_walker::types::Cli;
+
+pub const NAMESPACE_URI: &str = "urn:opcua-walker:test";
+
+/// Built-in scalar types with the value their variable holds
+pub fn scalars() -> Vec<(&'static str, DataTypeId, Variant)> {
+    vec![
+        ("Boolean", DataTypeId::Boolean, Variant::Boolean(true)),
+        ("SByte", DataTypeId::SByte, Variant::SByte(-8)),
+        ("Byte", DataTypeId::Byte, Variant::Byte(200)),
+        ("Int16", DataTypeId::Int16, Variant::Int16(-16)),
+        ("UInt16", DataTypeId::UInt16, Variant::UInt16(16)),
+        ("Int32", DataTypeId::Int32, Variant::Int32(-32)),
+        ("UInt32", DataTypeId::UInt32, Variant::UInt32(32)),
+        ("Int64", DataTypeId::Int64, Variant::Int64(-64)),
+        ("UInt64", DataTypeId::UInt64, Variant::UInt64(64)),
+        ("Float", DataTypeId::Float, Variant::Float(1.5)),
+        ("Double", DataTypeId::Double, Variant::Double(2.5)),
+        ("String", DataTypeId::String, Variant::from("text")),
+        ("DateTime", DataTypeId::DateTime, Variant::from(DateTime::ymd_hms(2024, 5, 17, 8, 30, 0))),
+        ("Guid", DataTypeId::Guid, Variant::from(Guid::from_bytes([0x12; 16]))),
+        ("ByteString", DataTypeId::ByteString, Variant::from(ByteString::from(vec![0xde, 0xad, 0xbe, 0xef]))),
+        ("XmlElement", DataTypeId::XmlElement, Variant::XmlElement(XmlElement::from("<a/>"))),
+        ("NodeId", DataTypeId::NodeId, Variant::from(NodeId::new(1, "Target"))),
+        ("ExpandedNodeId", DataTypeId::ExpandedNodeId, Variant::from(ExpandedNodeId::from(NodeId::new(1, 7)))),
+        ("StatusCode", DataTypeId::StatusCode, Variant::StatusCode(StatusCode::UncertainLastUsableValue)),
+        ("QualifiedName", DataTypeId::QualifiedName, Variant::from(QualifiedName::new(1, "Name"))),
+        ("LocalizedText", DataTypeId::LocalizedText, Variant::from(LocalizedText::new("en", "Hello"))),
+    ]
+}
+
+/// A running test server, stopped when dropped
+pub struct TestServer {
+    handle: ServerHandle,
+    pub endpoint: String,
+    /// Index of [`NAMESPACE_URI`] on the server
+    pub ns: u16,
+    pki_dir: PathBuf,
+}
+
+impl TestServer {
+    pub async fn start() -> TestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind a free port");
+        let port = listener.local_addr().unwrap().port();
+        let pki_dir = std::env::temp_dir().join(format!("opcua-walker-test-{}-{}", std::process::id(), port));
+
+        let (server, handle) = ServerBuilder::new_anonymous("opcua-walker test server")
+            .application_uri("urn:opcua-walker:test-server")
+            .host("127.0.0.1")
+            .port(port)
+            // The None endpoint needs no certificate, and generating one takes seconds in
+            // debug builds
+            .pki_dir(&pki_dir)
+            .create_sample_keypair(false)
+            .diagnostics_enabled(true)
+            .with_node_manager(simple_node_manager(
+                NamespaceMetadata { namespace_uri: NAMESPACE_URI.to_string(), ..Default::default() },
+                "test",
+            ))
+            .build()
+            .expect("build the test server");
+        let ns = handle.get_namespace_index(NAMESPACE_URI).expect("test namespace is registered");
+        let node_manager = handle.node_managers().get_of_type::<SimpleNodeManager>().expect("simple node manager");
+        populate(&node_manager, ns);
+
+        tokio::spawn(server.run_with(listener));
+        TestServer { handle, endpoint: format!("opc.tcp://127.0.0.1:{}/", port), ns, pki_dir }
+    }
+
+    pub fn node(&self, name: &str) -> NodeId {
+        NodeId::new(self.ns, name.to_string())
+    }
+
+    /// The CLI as parsed from `opcua-walker -e <endpoint> <args>`
+    pub fn cli(&self, args: &[&str]) -> Cli {
+        let argv = ["opcua-walker", "-e", &self.endpoint].into_iter().chain(args.iter().copied());
+        Cli::try_parse_from(argv).expect("valid command line")
+    }
+
+    /// The validated arguments of the command in `args`
+    pub fn command(&self, args: &[&str]) -> ParsedCommand {
+        commands::validate(&self.cli(args).command).expect("valid command arguments")
+    }
+
+    /// Subscriptions open on the server, and created since it started
+    pub fn subscriptions(&self) -> (u32, u32) {
+        let diagnostics = &self.handle.info().diagnostics;
+        let count = |id| match diagnostics.get(id).and_then(|value| value.value) {
+            Some(Variant::UInt32(count)) => count,
+            other => panic!("no subscription count: {:?}", other),
+        };
+        (
+            count(VariableId::Server_ServerDiagnostics_ServerDiagnosticsSummary_CurrentSubscriptionCount),
+            count(VariableId::Server_ServerDiagnostics_ServerDiagnosticsSummary_CumulatedSubscriptionCount),
+        )
+    }
+
+    pub async fn connect(&self) -> OpcUaClient {
+        let mut client = OpcUaClient::for_endpoint(&self.cli(&["info"]), &self.endpoint).expect("client for the test server");
+        client.connect().await.expect("connect to the test server");
+        client
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.handle.cancel();
+        let _ = std::fs::remove_dir_all(&self.pki_dir);
+    }
+}
+
+fn populate(node_manager: &Arc<SimpleNodeManager>, ns: u16) {
+    let node = |name: &str| NodeId::new(ns, name.to_string());
+    let address_space = node_manager.address_space();
+    let mut address_space = address_space.write();
+
+    let folder = |address_space: &mut _, name: &str, parent: NodeId| {
+        ObjectBuilder::new(&node(name), QualifiedName::new(ns, name), name)
+            .is_folder()
+            .organized_by(parent)
+            .insert(address_space);
+    };
+    folder(&mut *address_space, "Plant", ObjectId::ObjectsFolder.into());
+    for name in ["Scalars", "Arrays", "Structures"] {
+        folder(&mut *address_space, name, node("Plant"));
+    }
+
+    for (name, data_type, value) in scalars() {
+        VariableBuilder::new(&node(name), QualifiedName::new(ns, name), name)
+            .data_type(data_type)
+            .value(value)
+            .organized_by(node("Scalars"))
+            .insert(&mut *address_space);
+    }
+
+    VariableBuilder::new(&node("Int32Array"), QualifiedName::new(ns, "Int32Array"), "Int32Array")
+        .data_type(DataTypeId::Int32)
+        .value_rank(1)
+        .array_dimensions(&[3])
+        .value((VariantScalarTypeId::Int32, vec![Variant::Int32(1), Variant::Int32(2), Variant::Int32(3)]))
+        .organized_by(node("Arrays"))
+        .insert(&mut *address_space);
+
+    VariableBuilder::new(&node("Range"), QualifiedName::new(ns, "Range"), "Range")
+        .data_type(DataTypeId::Range)
+        .value(ExtensionObject::from_message(Range { low: 0.0, high: 100.0 }))
+        .organized_by(node("Structures"))
+        .insert(&mut *address_space);
+
+    folder(&mut *address_space, "Parameters", ObjectId::ObjectsFolder.into());
+    VariableBuilder::new(&node("Setpoint"), QualifiedName::new(ns, "Setpoint"), "Setpoint")
+        .data_type(DataTypeId::Double)
+        .value(21.5)
+        .writable()
+        .organized_by(node("Parameters"))
+        .insert(&mut *address_space);
+    VariableBuilder::new(&node("Locked"), QualifiedName::new(ns, "Locked"), "Locked")
+        .data_type(DataTypeId::Int32)
+        .value(7)
+        .access_level(AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE)
+        .user_access_level(AccessLevel::CURRENT_READ)
+        .organized_by(node("Parameters"))
+        .insert(&mut *address_space);
+
+    ObjectBuilder::new(&node("Machine"), QualifiedName::new(ns, "Machine"), "Machine")
+        .has_type_definition(ObjectTypeId::BaseObjectType)
+        .organized_by(node("Plant"))
+        .insert(&mut *address_space);
+    MethodBuilder::new(&node("Scale"), QualifiedName::new(ns, "Scale"), "Scale")
+        .component_of(node("Machine"))
+        .input_args(&mut *address_space, &node("Scale.InputArguments"), &[
+            argument("Value", DataTypeId::Double),
+            argument("Factor", DataTypeId::Int32),
+        ])
+        .output_args(&mut *address_space, &node("Scale.OutputArguments"), &[argument("Result", DataTypeId::Double)])
+        .executable(true)
+        .user_executable(true)
+        .insert(&mut *address_space);
+
+    node_manager.inner().add_method_callback(node("Scale"), |inputs| match inputs {
+        [Variant::Double(value), Variant::Int32(factor)] => Ok(vec![Variant::Double(value * *factor as f64)]),
+        [_, _] => Err(StatusCode::BadTypeMismatch),
+        _ => Err(StatusCode::BadArgumentsMissing),
+    });
+}
+
+fn argument(name: &str, data_type: DataTypeId) -> Argument {
+    Argument {
+        name: name.into(),
+        data_type: data_type.into(),
+        value_rank: -1,
+        array_dimensions: None,
+        description: LocalizedText::null(),
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use opcua::types::*;
+use opcua_walker::commands::copy::{copy_parameters, CopiedParameter, Outcome};
+use opcua_walker::commands::ParsedCommand;
+
+use common::TestServer;
+
+async fn copy(source: &TestServer, destination: &TestServer, args: &[&str]) -> Vec<CopiedParameter> {
+    let source_client = source.connect().await;
+    let destination_client = destination.connect().await;
+    let root = source.node("Parameters").to_string();
+    let args: Vec<&str> = ["copy", &root, "--to", &destination.endpoint].iter().chain(args).copied().collect();
+    let ParsedCommand::Copy(args) = source.command(&args) else { unreachable!() };
+    copy_parameters(&source_client, &destination_client, &args).await.unwrap()
+}
+
+fn outcome<'a>(parameters: &'a [CopiedParameter], name: &str) -> &'a Outcome {
+    parameters
+        .iter()
+        .find(|parameter| parameter.path.last().is_some_and(|last| last.ends_with(name)))
+        .map(|parameter| &parameter.outcome)
+        .unwrap_or_else(|| panic!("no parameter {}", name))
+}
+
+#[tokio::test]
+async fn copies_only_what_the_user_may_write() {
+    let source = TestServer::start().await;
+    let destination = TestServer::start().await;
+    let client = source.connect().await;
+    let setpoint = WriteValue {
+        node_id: source.node("Setpoint"),
+        attribute_id: AttributeId::Value as u32,
+        index_range: NumericRange::None,
+        value: DataValue { value: Some(Variant::Double(42.0)), ..Default::default() },
+    };
+    assert_eq!(client.session().unwrap().write(&[setpoint]).await.unwrap(), [StatusCode::Good]);
+
+    let parameters = copy(&source, &destination, &[]).await;
+    assert_eq!(parameters.len(), 2);
+    assert_eq!(outcome(&parameters, "Setpoint"), &Outcome::Copied);
+    // AccessLevel allows writes, UserAccessLevel does not
+    assert_eq!(outcome(&parameters, "Locked"), &Outcome::Skipped("Not writable".to_string()));
+
+    let client = destination.connect().await;
+    let setpoint = ReadValueId::from(destination.node("Setpoint"));
+    let copied = client.session().unwrap().read(&[setpoint], TimestampsToReturn::Neither, 0.0).await.unwrap();
+    assert_eq!(copied[0].value, Some(Variant::Double(42.0)));
+}
+
+#[tokio::test]
+async fn dry_runs_check_the_same_targets() {
+    let source = TestServer::start().await;
+    let destination = TestServer::start().await;
+    let parameters = copy(&source, &destination, &["--dry-run"]).await;
+
+    assert_eq!(outcome(&parameters, "Setpoint"), &Outcome::WouldCopy);
+    assert_eq!(outcome(&parameters, "Locked"), &Outcome::Skipped("Not writable".to_string()));
+}
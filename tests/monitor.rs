@@ -0,0 +1,37 @@
+mod common;
+
+use opcua_walker::commands::monitor;
+use opcua_walker::commands::ParsedCommand;
+
+use common::TestServer;
+
+async fn monitor(server: &TestServer, args: &[&str]) -> anyhow::Result<()> {
+    let mut client = server.connect().await;
+    let ParsedCommand::Monitor(args) = server.command(args) else { unreachable!() };
+    monitor::execute(&mut client, &args).await
+}
+
+#[tokio::test]
+async fn failing_outputs_delete_the_subscription() {
+    let server = TestServer::start().await;
+    let node = server.node("Double").to_string();
+    let missing = std::env::temp_dir().join(format!("opcua-walker-missing-{}", std::process::id()));
+    let line_protocol = missing.join("changes.lp").display().to_string();
+
+    let error = monitor(&server, &["monitor", "--influx-lp", &line_protocol, &node]).await.unwrap_err();
+    assert!(error.to_string().contains("changes.lp"), "{}", error);
+    assert_eq!(server.subscriptions(), (0, 1));
+}
+
+#[tokio::test]
+async fn failing_schemas_delete_the_subscription() {
+    let server = TestServer::start().await;
+    let node = server.node("Double").to_string();
+    let archive = std::env::temp_dir().join(format!("opcua-walker-archive-{}", std::process::id()));
+    let schema = archive.join("missing").join("schema.json").display().to_string();
+
+    let result = monitor(&server, &["monitor", "--archive", &archive.display().to_string(), "--emit-schema", &schema, &node]).await;
+    let _ = std::fs::remove_dir_all(&archive);
+    assert!(result.is_err());
+    assert_eq!(server.subscriptions(), (0, 1));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use opcua_walker::utils::namespace::parse_node_spec;
+
+use common::{TestServer, NAMESPACE_URI};
+
+#[tokio::test]
+async fn namespace_array_is_read_once() {
+    let server = TestServer::start().await;
+    let client = server.connect().await;
+
+    let namespaces = client.namespaces().await.unwrap();
+    assert_eq!(namespaces[0], "http://opcfoundation.org/UA/");
+    assert_eq!(namespaces[server.ns as usize], NAMESPACE_URI);
+    // Later calls hand out the cached array instead of reading it again
+    assert!(std::ptr::eq(namespaces, client.namespaces().await.unwrap()));
+}
+
+#[tokio::test]
+async fn looks_up_uris_and_indices() {
+    let server = TestServer::start().await;
+    let client = server.connect().await;
+
+    assert_eq!(client.ns_index_for_uri(NAMESPACE_URI).await.unwrap(), Some(server.ns));
+    assert_eq!(client.ns_index_for_uri("urn:unknown").await.unwrap(), None);
+    assert_eq!(client.uri_for_index(server.ns).await.unwrap(), Some(NAMESPACE_URI));
+    let count = client.namespaces().await.unwrap().len() as u16;
+    assert_eq!(client.uri_for_index(count).await.unwrap(), None);
+    assert_eq!(client.uri_for_index(u16::MAX).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn resolves_namespace_uris() {
+    let server = TestServer::start().await;
+    let client = server.connect().await;
+
+    let spec = parse_node_spec(&format!("nsu={};s=Plant", NAMESPACE_URI)).unwrap();
+    assert_eq!(client.resolve_node(&spec).await.unwrap(), server.node("Plant"));
+
+    let spec = parse_node_spec("nsu=urn:unknown;s=Plant").unwrap();
+    let error = client.resolve_node(&spec).await.unwrap_err();
+    assert_eq!(error.to_string(), "Namespace urn:unknown is not registered on the server");
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use opcua::types::*;
+use opcua_walker::commands::read::{read_nodes, NodeData};
+use opcua_walker::commands::ParsedCommand;
+
+use common::{scalars, TestServer};
+
+async fn read(server: &TestServer, args: &[&str]) -> Vec<NodeData> {
+    let client = server.connect().await;
+    let ParsedCommand::Read(args) = server.command(args) else { unreachable!() };
+    read_nodes(&client, &args).await.unwrap()
+}
+
+fn value(data: &NodeData) -> &Variant {
+    data.attribute(AttributeId::Value)
+        .and_then(|dv| dv.value.as_ref())
+        .unwrap_or_else(|| panic!("no value read for {}", data.node_id()))
+}
+
+#[tokio::test]
+async fn reads_every_scalar_type() {
+    let server = TestServer::start().await;
+    let node_ids: Vec<String> = scalars().iter().map(|(name, ..)| server.node(name).to_string()).collect();
+    let args: Vec<&str> = ["read", "--include-value"].into_iter().chain(node_ids.iter().map(String::as_str)).collect();
+    let results = read(&server, &args).await;
+
+    assert_eq!(results.len(), scalars().len());
+    for ((name, _, expected), data) in scalars().into_iter().zip(&results) {
+        assert_eq!(data.node_id(), &server.node(name));
+        assert_eq!(value(data), &expected, "value of {}", name);
+        assert_eq!(data.value_result().map(|result| result.is_failure()), Some(false), "status of {}", name);
+        assert_eq!(data.node_class(), Some(NodeClass::Variable as u32));
+    }
+}
+
+#[tokio::test]
+async fn reads_arrays_whole_and_by_index_range() {
+    let server = TestServer::start().await;
+    let array = server.node("Int32Array").to_string();
+
+    let whole = read(&server, &["read", "--include-value", &array]).await;
+    let Variant::Array(values) = value(&whole[0]) else { panic!("not an array: {:?}", value(&whole[0])) };
+    assert_eq!(values.value_type, VariantScalarTypeId::Int32);
+    assert_eq!(values.values, [Variant::Int32(1), Variant::Int32(2), Variant::Int32(3)]);
+
+    let range = read(&server, &["read", "--include-value", "--index-range", "1:2", &array]).await;
+    let Variant::Array(values) = value(&range[0]) else { panic!("not an array: {:?}", value(&range[0])) };
+    assert_eq!(values.values, [Variant::Int32(2), Variant::Int32(3)]);
+}
+
+#[tokio::test]
+async fn reads_structures() {
+    let server = TestServer::start().await;
+    let results = read(&server, &["read", "--include-value", &server.node("Range").to_string()]).await;
+
+    let Variant::ExtensionObject(object) = value(&results[0]) else { panic!("not a structure: {:?}", value(&results[0])) };
+    let range = object.inner_as::<Range>().expect("a Range");
+    assert_eq!((range.low, range.high), (0.0, 100.0));
+}
+
+#[tokio::test]
+async fn reads_attributes_of_objects() {
+    let server = TestServer::start().await;
+    let results = read(&server, &["read", "--all-attributes", &server.node("Machine").to_string()]).await;
+
+    let data = &results[0];
+    assert_eq!(data.node_class(), Some(NodeClass::Object as u32));
+    assert!(data.attribute(AttributeId::Value).is_none());
+    let browse_name = data.attribute(AttributeId::BrowseName).and_then(|dv| dv.value.as_ref());
+    assert_eq!(browse_name, Some(&Variant::from(QualifiedName::new(server.ns, "Machine"))));
+}
+
+#[tokio::test]
+async fn unknown_nodes_report_their_status() {
+    let server = TestServer::start().await;
+    let results = read(&server, &["read", "--include-value", &server.node("Missing").to_string()]).await;
+
+    let data = &results[0];
+    assert!(data.fault().is_none());
+    let status = data.attribute(AttributeId::DisplayName).and_then(|dv| dv.status);
+    assert_eq!(status, Some(StatusCode::BadNodeIdUnknown));
+}
+
+#[tokio::test]
+async fn search_reads_every_match() {
+    let server = TestServer::start().await;
+    let plant = server.node("Plant").to_string();
+    let results = read(&server, &["read", "--search", "--search-root", &plant, "Int16"]).await;
+
+    let mut found: Vec<&NodeId> = results.iter().map(NodeData::node_id).collect();
+    found.sort_by_key(|node_id| node_id.to_string());
+    assert_eq!(found, [&server.node("Int16"), &server.node("UInt16")]);
+    // Variables found by a search are read with their value
+    let int16 = results.iter().find(|data| data.node_id() == &server.node("Int16")).unwrap();
+    assert_eq!(value(int16), &Variant::Int16(-16));
+}
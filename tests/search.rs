@@ -0,0 +1,64 @@
+mod common;
+
+use opcua::types::*;
+use opcua_walker::utils::search::{find_method_with_parent, search_nodes_by_name, SearchConfig, SearchResult};
+
+use common::TestServer;
+
+async fn search(server: &TestServer, term: &str, config: SearchConfig) -> Vec<SearchResult> {
+    let client = server.connect().await;
+    let config = SearchConfig { search_roots: vec![server.node("Plant")], ..config };
+    search_nodes_by_name(client.session().unwrap(), term, config, false).await.unwrap()
+}
+
+fn names(results: &[SearchResult]) -> Vec<&str> {
+    results.iter().map(|result| result.display_name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn ranks_exact_matches_first() {
+    let server = TestServer::start().await;
+    let results = search(&server, "int16", SearchConfig::default()).await;
+
+    assert_eq!(names(&results), ["Int16", "UInt16"]);
+    assert_eq!(results[0].node_id, server.node("Int16"));
+    assert_eq!(results[0].node_class, NodeClass::Variable);
+    assert_eq!(results[0].parent_node_id, Some(server.node("Scalars")));
+    assert_eq!(results[0].root, Some(server.node("Plant")));
+    assert!(results[0].score > results[1].score);
+}
+
+#[tokio::test]
+async fn restricts_matches_to_variables() {
+    let server = TestServer::start().await;
+
+    let all = search(&server, "array", SearchConfig::default()).await;
+    let mut found = names(&all);
+    found.sort();
+    assert_eq!(found, ["Arrays", "Int32Array"]);
+
+    let variables = search(&server, "array", SearchConfig { search_variables_only: true, ..Default::default() }).await;
+    assert_eq!(names(&variables), ["Int32Array"]);
+}
+
+#[tokio::test]
+async fn finds_no_match_outside_the_roots() {
+    let server = TestServer::start().await;
+    let results = search(&server, "ServerStatus", SearchConfig::default()).await;
+
+    assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn finds_methods_with_their_object() {
+    let server = TestServer::start().await;
+    let client = server.connect().await;
+    let found = find_method_with_parent(client.session().unwrap(), "Scale", vec![server.node("Plant")], false)
+        .await
+        .unwrap()
+        .expect("Scale is found");
+
+    assert_eq!(found.node_id, server.node("Scale"));
+    assert_eq!(found.node_class, NodeClass::Method);
+    assert_eq!(found.parent_node_id, Some(server.node("Machine")));
+}